@@ -0,0 +1,68 @@
+// Exploitability reached by each CFR variant after the same number of iterations, and the time
+// it took. Run with `cargo bench --bench convergence`.
+use solver::abstraction::{parse_action_set, BlindSummary, GameStateSummary};
+use solver::cfr::variant::CfrVariantKind;
+use solver::cfr::{CfrConfig, CfrSolver};
+use solver::game_tree::GameTree;
+use std::time::{Duration, Instant};
+
+const CHECKPOINTS: [usize; 5] = [50, 100, 200, 400, 800];
+const VARIANTS: [CfrVariantKind; 5] = [
+    CfrVariantKind::Vanilla,
+    CfrVariantKind::CfrPlus,
+    CfrVariantKind::PredictiveCfrPlus,
+    CfrVariantKind::Discounted,
+    CfrVariantKind::Sampled,
+];
+
+fn river_tree(sizes: &[&str]) -> GameTree {
+    let summary = GameStateSummary {
+        pot: 10.0,
+        street: "river".into(),
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let sizes: Vec<String> = sizes.iter().map(|size| size.to_string()).collect();
+    let specs = parse_action_set(&sizes, &summary, 100.0);
+    GameTree::from_action_specs(&specs, &summary, 100.0)
+}
+
+fn main() {
+    let spots = [
+        ("river, 2 sizes", river_tree(&["pot:0.5", "all-in"])),
+        (
+            "river, 4 sizes",
+            river_tree(&["pot:0.33", "pot:0.75", "pot:1.5", "all-in"]),
+        ),
+    ];
+    for (name, tree) in &spots {
+        for buckets in [16, 64] {
+            println!("{} ({} nodes, {} buckets)", name, tree.nodes.len(), buckets);
+            print!("{:>10}", "variant");
+            for checkpoint in CHECKPOINTS {
+                print!("{:>12}", checkpoint);
+            }
+            println!("{:>12}", "time");
+            for kind in VARIANTS {
+                let mut config = CfrConfig::new(CHECKPOINTS[CHECKPOINTS.len() - 1]);
+                config.variant = kind;
+                config.buckets = buckets;
+                config.seed = 1;
+                let mut solver = CfrSolver::new(tree, &config);
+                let mut elapsed = Duration::ZERO;
+                print!("{:>10}", kind.as_str());
+                for checkpoint in CHECKPOINTS {
+                    let started = Instant::now();
+                    solver.run(checkpoint - solver.iterations());
+                    elapsed += started.elapsed();
+                    print!("{:>12.2e}", solver.exploitability());
+                }
+                println!("{:>10}ms", elapsed.as_millis());
+            }
+            println!();
+        }
+    }
+}
@@ -1,5 +1,23 @@
 fn main() {
     println!("cargo:rerun-if-changed=../../proto/solver.proto");
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+
+    // Responses carry the commit they were built from: the image's GIT_SHA build arg, else the
+    // checkout's HEAD, else "dev".
+    let commit = std::env::var("GIT_SHA")
+        .ok()
+        .filter(|sha| !sha.is_empty())
+        .or_else(|| {
+            std::process::Command::new("git")
+                .args(["rev-parse", "--short=12", "HEAD"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+                .map(|sha| sha.trim().to_string())
+        })
+        .unwrap_or_else(|| "dev".to_string());
+    println!("cargo:rustc-env=SOLVER_GIT_SHA={commit}");
 
     // Use a vendored protoc binary so CI machines do not need system-level protoc installed.
     let protoc_path = protoc_bin_vendored::protoc_bin_path()
@@ -9,8 +27,13 @@ fn main() {
     // binary, but it respects the PROTOC env var. Point it at the vendored binary.
     std::env::set_var("PROTOC", protoc_path);
 
+    // Serde derives let the offline CLI read requests from and write responses to JSON files.
     tonic_build::configure()
         .build_server(true)
+        .message_attribute(
+            ".",
+            "#[derive(serde::Serialize, serde::Deserialize)] #[serde(default)]",
+        )
         .compile(&["../../proto/solver.proto"], &["../../proto"])
         .unwrap();
 }
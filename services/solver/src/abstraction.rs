@@ -1,12 +1,110 @@
+use crate::abstraction::overbet::{expand_overbet_tokens, OVERBET_AUTO};
+use crate::cards::Card;
+use crate::game_tree::ActionKind;
+use crate::solver_proto::{BettingRoundActions, GameState, StreetActionSets};
 use serde::Deserialize;
+use std::fmt;
+
+pub mod all_in;
+pub mod blockers;
+pub mod matrix;
+pub mod overbet;
+pub mod quality;
+pub mod realization;
+pub mod showdown;
+pub mod templates;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ActionSpec {
     pub label: String,
+    // What hero puts in: 0 to fold or check, the facing bet to call, the street total otherwise.
     pub amount: f64,
+    pub kind: ActionKind,
+}
+
+impl ActionSpec {
+    pub fn is_passive(&self) -> bool {
+        self.kind.is_passive()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+}
+
+impl Street {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "preflop" => Some(Street::Preflop),
+            "flop" => Some(Street::Flop),
+            "turn" => Some(Street::Turn),
+            "river" => Some(Street::River),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Street::Preflop => "preflop",
+            Street::Flop => "flop",
+            Street::Turn => "turn",
+            Street::River => "river",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GameVariant {
+    #[default]
+    Holdem,
+    // Six-plus: sixes to aces, flushes beat full houses, and only antes are posted. The ante is
+    // the betting unit, standing in for the big blind.
+    ShortDeck,
+}
+
+impl GameVariant {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "holdem" | "nlhe" => Some(GameVariant::Holdem),
+            "short-deck" | "shortdeck" | "six-plus" | "6+" => Some(GameVariant::ShortDeck),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameVariant::Holdem => "holdem",
+            GameVariant::ShortDeck => "short-deck",
+        }
+    }
+
+    // Lowest rank dealt, 0 being the deuce.
+    pub fn lowest_rank(&self) -> u8 {
+        match self {
+            GameVariant::Holdem => 0,
+            GameVariant::ShortDeck => 4,
+        }
+    }
+
+    pub fn deck(&self) -> Vec<Card> {
+        (self.lowest_rank()..13)
+            .flat_map(|rank| (0..4).map(move |suit| Card { rank, suit }))
+            .collect()
+    }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BettingRound {
+    Bet,
+    Raise,
+    Reraise,
+}
+
+#[derive(Clone, Debug, Deserialize, Default)]
 pub struct GameStateSummary {
     #[serde(default)]
     pub pot: f64,
@@ -14,30 +112,471 @@ pub struct GameStateSummary {
     pub street: String,
     #[serde(default)]
     pub blinds: BlindSummary,
+    #[serde(default, alias = "numPlayers")]
+    pub num_players: usize,
+    // Set when `pot` already contains the antes and straddle, so they are not counted twice.
+    #[serde(default, alias = "forcedBetsInPot")]
+    pub forced_bets_in_pot: bool,
+    // "holdem" (the default) or "short-deck".
+    #[serde(default, alias = "gameVariant")]
+    pub variant: String,
+    // Present for fixed-limit games; absent means no-limit.
+    #[serde(default)]
+    pub limit: Option<LimitSummary>,
+    // Whether hero acts last after the flop; absent means hero is the button preflop and out of
+    // position on later streets, matching who acts first at the root.
+    #[serde(default, alias = "heroInPosition")]
+    pub hero_in_position: Option<bool>,
+    // Everyone dealt in. With `hero_seat` set the effective stack comes from here rather than
+    // from the request.
+    #[serde(default)]
+    pub players: Vec<PlayerSummary>,
+    #[serde(default, alias = "heroSeat")]
+    pub hero_seat: Option<usize>,
+    // The opponent hero is solving against; absent means the deepest one still in the hand.
+    #[serde(default, alias = "villainSeat")]
+    pub villain_seat: Option<usize>,
+    // The hand so far, oldest first. This street's actions give the bet hero faces, the size of
+    // the last raise and who made it; earlier streets are only context.
+    #[serde(default)]
+    pub history: Vec<ActionRecord>,
+    // What a call costs hero on this street, in chips; absent derives it from `history`.
+    #[serde(default, alias = "facingBet")]
+    pub facing_bet: Option<f64>,
+    // The seat whose bet hero faces; absent derives it from `history`.
+    #[serde(default, alias = "aggressorSeat")]
+    pub aggressor_seat: Option<usize>,
+}
+
+// This street's betting replayed from the history, in chips. Only amounts count, so a record's
+// action name is informational.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StreetBetting {
+    // The largest street total of any opponent.
+    pub largest: f64,
+    // Hero's own street total.
+    pub hero: f64,
+    // How far the latest bet or raise went past the total before it.
+    pub last_raise: f64,
+    // Bets plus raises, the opening bet included.
+    pub aggressions: u32,
+    pub aggressor: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize, Default, PartialEq)]
+pub struct ActionRecord {
+    #[serde(default)]
+    pub seat: usize,
+    #[serde(default)]
+    pub street: String,
+    #[serde(default)]
+    pub action: String,
+    #[serde(default)]
+    pub amount: f64,
+}
+
+// In chips like the blinds.
+#[derive(Clone, Debug, Deserialize, Default, PartialEq)]
+pub struct PlayerSummary {
+    #[serde(default)]
+    pub seat: usize,
+    // E.g. "BTN" or "BB"; informational only.
+    #[serde(default)]
+    pub position: String,
+    // Chips behind, not counting `committed`.
+    #[serde(default)]
+    pub stack: f64,
+    // Chips already put in on this street.
+    #[serde(default)]
+    pub committed: f64,
+    #[serde(default)]
+    pub folded: bool,
+}
+
+// Fixed-limit sizes, in chips like the blinds.
+#[derive(Clone, Debug, Deserialize, Default, PartialEq)]
+pub struct LimitSummary {
+    // Preflop and flop bet; defaults to the big blind.
+    #[serde(default, alias = "smallBet")]
+    pub small_bet: f64,
+    // Turn and river bet; defaults to twice the small bet.
+    #[serde(default, alias = "bigBet")]
+    pub big_bet: f64,
+    // Bets plus raises allowed per street; 0 means the usual cap of 4.
+    #[serde(default)]
+    pub cap: u32,
+}
+
+pub const DEFAULT_LIMIT_CAP: u32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BettingStructure {
+    NoLimit,
+    // Every bet and raise is exactly `bet_bb`, and a street allows at most `cap` of them.
+    FixedLimit { bet_bb: f64, cap: u32 },
 }
 
 #[derive(Clone, Debug, Deserialize, Default)]
 pub struct BlindSummary {
+    #[serde(default)]
+    pub small: f64,
     #[serde(default)]
     pub big: f64,
+    #[serde(default)]
+    pub ante: f64,
+    #[serde(default, alias = "bigBlindAnte")]
+    pub big_blind_ante: f64,
+    #[serde(default)]
+    pub straddle: f64,
+}
+
+impl BlindSummary {
+    // Antes are dead money: posted by every seat (or once by the big blind) and never part of a
+    // player's bet, so they only grow the pot.
+    pub fn dead_money(&self, players: usize) -> f64 {
+        self.ante.max(0.0) * players as f64 + self.big_blind_ante.max(0.0)
+    }
 }
 
 impl GameStateSummary {
+    pub fn from_proto(state: &GameState) -> Self {
+        let blinds = state.blinds.clone().unwrap_or_default();
+        Self {
+            pot: state.pot,
+            street: state.street.clone(),
+            blinds: BlindSummary {
+                small: blinds.small,
+                big: blinds.big,
+                ante: blinds.ante,
+                big_blind_ante: blinds.big_blind_ante,
+                straddle: blinds.straddle,
+            },
+            num_players: state.num_players as usize,
+            forced_bets_in_pot: state.forced_bets_in_pot,
+            variant: state.variant.clone(),
+            limit: state.limit.as_ref().map(|limit| LimitSummary {
+                small_bet: limit.small_bet,
+                big_bet: limit.big_bet,
+                cap: limit.cap,
+            }),
+            hero_in_position: state.hero_in_position,
+            players: state
+                .players
+                .iter()
+                .map(|player| PlayerSummary {
+                    seat: player.seat as usize,
+                    position: player.position.clone(),
+                    stack: player.stack,
+                    committed: player.committed,
+                    folded: player.folded,
+                })
+                .collect(),
+            hero_seat: state.hero_seat.map(|seat| seat as usize),
+            villain_seat: state.villain_seat.map(|seat| seat as usize),
+            history: state
+                .history
+                .iter()
+                .map(|action| ActionRecord {
+                    seat: action.seat as usize,
+                    street: action.street.clone(),
+                    action: action.action.clone(),
+                    amount: action.amount,
+                })
+                .collect(),
+            facing_bet: state.facing_bet,
+            aggressor_seat: state.aggressor_seat.map(|seat| seat as usize),
+        }
+    }
+
+    pub fn street_kind(&self) -> Option<Street> {
+        Street::parse(&self.street)
+    }
+
+    pub fn hero_has_position(&self) -> bool {
+        self.hero_in_position
+            .unwrap_or(self.street_kind() == Some(Street::Preflop))
+    }
+
+    pub fn seated_players(&self) -> usize {
+        self.num_players.max(self.players.len()).max(2)
+    }
+
+    pub fn player(&self, seat: usize) -> Option<&PlayerSummary> {
+        self.players.iter().find(|player| player.seat == seat)
+    }
+
+    // What `seat` can still put in that `opponent` is able to match, in BB.
+    pub fn effective_stack_bb(&self, seat: usize, opponent: usize) -> Option<f64> {
+        let (player, opponent) = (self.player(seat)?, self.player(opponent)?);
+        let covered = (player.stack + player.committed).min(opponent.stack + opponent.committed);
+        Some((covered - player.committed).max(0.0) / self.chips_per_bb())
+    }
+
+    // Hero's effective stack against `villain_seat`, else the player whose bet hero faces, else
+    // the deepest opponent still in the hand: beyond that, nobody can call. None without a
+    // seated hero.
+    pub fn hero_effective_stack_bb(&self) -> Option<f64> {
+        let hero = self.hero_seat?;
+        if let Some(villain) = self.villain_seat {
+            return self.effective_stack_bb(hero, villain);
+        }
+        let facing = self.aggressor();
+        if let Some(stack) = facing.and_then(|seat| self.effective_stack_bb(hero, seat)) {
+            return Some(stack);
+        }
+        self.players
+            .iter()
+            .filter(|player| player.seat != hero && !player.folded)
+            .filter_map(|player| self.effective_stack_bb(hero, player.seat))
+            .reduce(f64::max)
+    }
+
+    pub fn street_history(&self) -> impl Iterator<Item = &ActionRecord> {
+        self.history
+            .iter()
+            .filter(|record| record.street.eq_ignore_ascii_case(&self.street))
+    }
+
+    pub fn street_betting(&self) -> StreetBetting {
+        let mut betting = StreetBetting::default();
+        let mut totals: Vec<(usize, f64)> = Vec::new();
+        for record in self.street_history() {
+            let total = match totals.iter_mut().find(|(seat, _)| *seat == record.seat) {
+                Some((_, total)) => {
+                    *total += record.amount.max(0.0);
+                    *total
+                }
+                None => {
+                    totals.push((record.seat, record.amount.max(0.0)));
+                    record.amount.max(0.0)
+                }
+            };
+            let top = betting.largest.max(betting.hero);
+            if total > top {
+                betting.last_raise = total - top;
+                betting.aggressions += 1;
+                betting.aggressor = Some(record.seat);
+            }
+            if Some(record.seat) == self.hero_seat {
+                betting.hero = total;
+            } else {
+                betting.largest = betting.largest.max(total);
+            }
+        }
+        betting
+    }
+
+    // The seat whose bet hero faces: `aggressor_seat`, or whoever made the latest bet or raise on
+    // this street unless that was hero.
+    pub fn aggressor(&self) -> Option<usize> {
+        self.aggressor_seat.or_else(|| {
+            self.street_betting()
+                .aggressor
+                .filter(|seat| Some(*seat) != self.hero_seat)
+        })
+    }
+
+    // What a call costs hero on this street, in BB: `facing_bet`, or the largest amount an
+    // opponent has put in beyond hero's own chips. Preflop that is at least the big blind (or
+    // straddle) even before anyone acts.
+    pub fn facing_bet_bb(&self) -> f64 {
+        let chips = match self.facing_bet {
+            Some(chips) => chips.max(0.0),
+            None => {
+                let betting = self.street_betting();
+                (betting.largest - betting.hero).max(0.0)
+            }
+        };
+        let facing = chips / self.chips_per_bb();
+        if self.street_kind() == Some(Street::Preflop) {
+            facing.max(self.min_bet_bb())
+        } else {
+            facing
+        }
+    }
+
+    // Unknown variants fall back to hold'em; validation reports them.
+    pub fn game_variant(&self) -> GameVariant {
+        GameVariant::parse(&self.variant).unwrap_or_default()
+    }
+
+    pub fn forced_bets(&self) -> f64 {
+        match self.game_variant() {
+            GameVariant::Holdem => {
+                self.blinds.dead_money(self.seated_players()) + self.blinds.straddle.max(0.0)
+            }
+            // Every seat antes and the button posts a second ante; blinds are ignored.
+            GameVariant::ShortDeck => {
+                self.blinds.ante.max(0.0) * (self.seated_players() + 1) as f64
+            }
+        }
+    }
+
+    pub fn betting_structure(&self) -> BettingStructure {
+        let Some(limit) = &self.limit else {
+            return BettingStructure::NoLimit;
+        };
+        let unit = self.chips_per_bb();
+        let small = if limit.small_bet > 0.0 {
+            limit.small_bet / unit
+        } else {
+            1.0
+        };
+        let big = if limit.big_bet > 0.0 {
+            limit.big_bet / unit
+        } else {
+            2.0 * small
+        };
+        let bet_bb = match self.street_kind() {
+            Some(Street::Turn | Street::River) => big,
+            _ => small,
+        };
+        let cap = if limit.cap > 0 {
+            limit.cap
+        } else {
+            DEFAULT_LIMIT_CAP
+        };
+        BettingStructure::FixedLimit { bet_bb, cap }
+    }
+
+    // Chips per betting unit: the big blind, or the ante in short deck.
+    pub fn chips_per_bb(&self) -> f64 {
+        match self.game_variant() {
+            GameVariant::Holdem => self.blinds.big.max(1.0),
+            GameVariant::ShortDeck if self.blinds.ante > 0.0 => self.blinds.ante,
+            GameVariant::ShortDeck => 1.0,
+        }
+    }
+
     pub fn pot_in_bb(&self) -> f64 {
-        let big_blind = self.blinds.big.max(1.0);
+        let big_blind = self.chips_per_bb();
+        let pot = if self.forced_bets_in_pot {
+            self.pot
+        } else {
+            self.pot + self.forced_bets()
+        };
         let derived = if big_blind > 0.0 {
-            self.pot / big_blind
+            pot / big_blind
         } else {
-            self.pot
+            pot
         };
         derived.max(1.0)
     }
+
+    // A straddle becomes the amount to open over preflop; otherwise the floor is one big blind.
+    // In short deck the floor is the button's double ante.
+    pub fn min_bet_bb(&self) -> f64 {
+        if self.game_variant() == GameVariant::ShortDeck {
+            return 2.0;
+        }
+        let big_blind = self.blinds.big.max(1.0);
+        if self.street.eq_ignore_ascii_case("preflop") && self.blinds.straddle > 0.0 {
+            (self.blinds.straddle / big_blind).max(1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+// Picks the tokens configured for this street and betting round. An empty round falls back to
+// the round below it (re-raise -> raise -> bet), and an unconfigured street to the flat set.
+pub fn street_action_tokens<'a>(
+    sets: Option<&'a StreetActionSets>,
+    street: Option<Street>,
+    round: BettingRound,
+    flat: &'a [String],
+) -> &'a [String] {
+    let rounds = match (sets, street) {
+        (Some(sets), Some(street)) => match street {
+            Street::Preflop => sets.preflop.as_ref(),
+            Street::Flop => sets.flop.as_ref(),
+            Street::Turn => sets.turn.as_ref(),
+            Street::River => sets.river.as_ref(),
+        },
+        _ => None,
+    };
+    rounds
+        .and_then(|rounds| round_tokens(rounds, round))
+        .unwrap_or(flat)
+}
+
+fn round_tokens(rounds: &BettingRoundActions, round: BettingRound) -> Option<&[String]> {
+    let candidates: &[&Vec<String>] = match round {
+        BettingRound::Bet => &[&rounds.bet],
+        BettingRound::Raise => &[&rounds.raise, &rounds.bet],
+        BettingRound::Reraise => &[&rounds.reraise, &rounds.raise, &rounds.bet],
+    };
+    candidates
+        .iter()
+        .find(|tokens| !tokens.is_empty())
+        .map(|tokens| tokens.as_slice())
+}
+
+// How token sizes are fitted to the spot: the smallest size in BB, whether a size beyond the
+// stack is capped to it or dropped, and the share of the stack above which a bet is an all-in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SizingPolicy {
+    pub min_bet_bb: f64,
+    pub reject_oversize: bool,
+    pub all_in_threshold: f64,
+    // Most sizes above the pot (all-ins aside) kept in the tree, the earliest first; 0 keeps all.
+    pub max_overbets: usize,
+}
+
+impl Default for SizingPolicy {
+    fn default() -> Self {
+        Self {
+            min_bet_bb: 0.5,
+            reject_oversize: false,
+            all_in_threshold: 1.0,
+            max_overbets: 0,
+        }
+    }
+}
+
+impl SizingPolicy {
+    // None when the size is over the stack and oversize bets are rejected.
+    fn cap(&self, amount: f64, stack_cap: f64) -> Option<f64> {
+        if self.reject_oversize && amount > stack_cap + 1e-9 {
+            return None;
+        }
+        Some(amount.min(stack_cap))
+    }
+
+    fn fit(&self, amount: f64, stack_cap: f64) -> Option<f64> {
+        self.cap(amount.max(self.min_bet_bb), stack_cap)
+    }
+
+    // A threshold of 1 keeps capped sizes under their own labels.
+    fn shove(&self, spec: ActionSpec, stack_cap: f64) -> ActionSpec {
+        if !spec.is_passive()
+            && self.all_in_threshold < 1.0
+            && spec.amount + 1e-9 >= self.all_in_threshold * stack_cap
+        {
+            ActionSpec {
+                label: "all-in".to_string(),
+                amount: stack_cap,
+                kind: ActionKind::AllIn,
+            }
+        } else {
+            spec
+        }
+    }
 }
 
 pub fn parse_action_set(
     raw: &[String],
     summary: &GameStateSummary,
     effective_stack_bb: f64,
+) -> Vec<ActionSpec> {
+    parse_action_set_with_policy(raw, summary, effective_stack_bb, &SizingPolicy::default())
+}
+
+pub fn parse_action_set_with_policy(
+    raw: &[String],
+    summary: &GameStateSummary,
+    effective_stack_bb: f64,
+    policy: &SizingPolicy,
 ) -> Vec<ActionSpec> {
     if raw.is_empty() {
         return Vec::new();
@@ -45,25 +584,275 @@ pub fn parse_action_set(
 
     let pot_bb = summary.pot_in_bb();
     let stack_cap = effective_stack_bb.max(1.0);
+    let facing_bb = summary.facing_bet_bb();
 
-    raw.iter()
-        .filter_map(|value| parse_action_token(value, pot_bb, stack_cap))
+    // Without the board, overbet:auto reads the spot from the betting alone.
+    let tokens = expand_overbet_tokens(raw, summary, &[], effective_stack_bb);
+    let mut overbets = 0;
+    tokens
+        .iter()
+        .filter_map(|value| parse_action_token(value, pot_bb, stack_cap, facing_bb, policy))
+        .map(|spec| policy.shove(spec, stack_cap))
+        .filter(|spec| {
+            if spec.is_passive() || spec.label == "all-in" || spec.amount <= pot_bb + 1e-9 {
+                return true;
+            }
+            overbets += 1;
+            policy.max_overbets == 0 || overbets <= policy.max_overbets
+        })
         .collect()
 }
 
-fn parse_action_token(token: &str, pot_bb: f64, stack_cap: f64) -> Option<ActionSpec> {
+// Collapses near-duplicate sizes so they do not each get their own branch. The earliest size in
+// a cluster is kept, except that an all-in always survives over bets that land close to it.
+// Folds, checks and calls are not sizes and are kept as they are.
+pub fn merge_similar_sizes(specs: Vec<ActionSpec>, tolerance: f64) -> Vec<ActionSpec> {
+    if tolerance <= 0.0 || specs.len() < 2 {
+        return specs;
+    }
+
+    let mut merged: Vec<ActionSpec> = Vec::with_capacity(specs.len());
+    for spec in specs {
+        if spec.is_passive() {
+            merged.push(spec);
+            continue;
+        }
+        let close = merged.iter().position(|kept| {
+            let largest = kept.amount.abs().max(spec.amount.abs());
+            !kept.is_passive()
+                && largest > 0.0
+                && (kept.amount - spec.amount).abs() / largest <= tolerance
+        });
+        match close {
+            Some(index) => {
+                if spec.label == "all-in" && merged[index].label != "all-in" {
+                    merged[index] = spec;
+                }
+            }
+            None => merged.push(spec),
+        }
+    }
+    merged
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenError {
+    Unknown(String),
+    InvalidNumber(String),
+    OutOfRange {
+        token: String,
+        expected: &'static str,
+    },
+    // An x:N token with no bet on this street to multiply.
+    NoFacingBet(String),
+    // A size beyond the stack under a policy that rejects rather than caps.
+    Oversize(String),
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenError::Unknown(token) => write!(f, "unknown action token '{}'", token),
+            TokenError::Oversize(token) => write!(
+                f,
+                "action token '{}' bets more than the effective stack",
+                token
+            ),
+            TokenError::NoFacingBet(token) => write!(
+                f,
+                "action token '{}' multiplies the facing bet, but there is no bet on this street",
+                token
+            ),
+            TokenError::InvalidNumber(token) => {
+                write!(
+                    f,
+                    "action token '{}' does not contain a valid number",
+                    token
+                )
+            }
+            TokenError::OutOfRange { token, expected } => {
+                write!(
+                    f,
+                    "action token '{}' is out of range: expected {}",
+                    token, expected
+                )
+            }
+        }
+    }
+}
+
+impl TokenError {
+    pub fn reason(&self) -> &'static str {
+        match self {
+            TokenError::NoFacingBet(_) => "NO_FACING_BET",
+            TokenError::Oversize(_) => "OVERSIZE_BET",
+            _ => "INVALID_ACTION_TOKEN",
+        }
+    }
+}
+
+// Like parse_action_set, but fails listing every token it would drop or default, by index.
+pub fn parse_action_set_strict(
+    raw: &[String],
+    summary: &GameStateSummary,
+    effective_stack_bb: f64,
+    policy: &SizingPolicy,
+) -> Result<Vec<ActionSpec>, Vec<(usize, TokenError)>> {
+    let (pot_bb, stack_cap) = (summary.pot_in_bb(), effective_stack_bb.max(1.0));
+    let errors: Vec<(usize, TokenError)> = raw
+        .iter()
+        .enumerate()
+        .filter_map(|(index, token)| {
+            check_action_token_in_spot(token, summary)
+                .and_then(|()| {
+                    // Spot-valid tokens only fail to size when the policy rejects them.
+                    parse_action_token(token, pot_bb, stack_cap, summary.facing_bet_bb(), policy)
+                        .map(|_| ())
+                        .ok_or_else(|| TokenError::Oversize(token.to_string()))
+                })
+                .err()
+                .map(|error| (index, error))
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(parse_action_set_with_policy(
+            raw,
+            summary,
+            effective_stack_bb,
+            policy,
+        ))
+    } else {
+        Err(errors)
+    }
+}
+
+// check_action_token, plus what the spot decides: x:N needs a bet to multiply. Whether a fold,
+// check or call applies is left to legalization, which knows the bet hero faces.
+pub fn check_action_token_in_spot(
+    token: &str,
+    summary: &GameStateSummary,
+) -> Result<(), TokenError> {
+    check_action_token(token)?;
+    if token.starts_with("x:") && summary.facing_bet_bb() <= 0.0 {
+        return Err(TokenError::NoFacingBet(token.to_string()));
+    }
+    Ok(())
+}
+
+// "fold", "check" or "call", in any case.
+fn passive_kind(token: &str) -> Option<ActionKind> {
+    ActionKind::parse(&token.to_ascii_lowercase()).filter(ActionKind::is_passive)
+}
+
+// Reports what parse_action_token would silently drop or default.
+pub fn check_action_token(token: &str) -> Result<(), TokenError> {
+    if token.eq_ignore_ascii_case("all-in")
+        || token == OVERBET_AUTO
+        || passive_kind(token).is_some()
+    {
+        return Ok(());
+    }
+
+    let number = |rest: &str| {
+        rest.parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| TokenError::InvalidNumber(token.to_string()))
+    };
+    let out_of_range = |expected| TokenError::OutOfRange {
+        token: token.to_string(),
+        expected,
+    };
+
+    if let Some(rest) = token.strip_prefix("pot:") {
+        return match number(rest)? {
+            value if value > 0.0 => Ok(()),
+            _ => Err(out_of_range("a pot fraction above 0")),
+        };
+    }
+    if let Some(rest) = token.strip_prefix("geo:") {
+        return match rest.parse::<u32>() {
+            Ok(streets) if streets >= 1 => Ok(()),
+            Ok(_) => Err(out_of_range("at least 1 street")),
+            Err(_) => Err(TokenError::InvalidNumber(token.to_string())),
+        };
+    }
+    if let Some(rest) = token.strip_prefix("stack:") {
+        return match number(rest)? {
+            value if value > 0.0 && value <= 1.0 => Ok(()),
+            _ => Err(out_of_range("a stack fraction in (0, 1]")),
+        };
+    }
+    if let Some(rest) = token.strip_prefix("x:") {
+        return match number(rest)? {
+            value if value > 1.0 => Ok(()),
+            _ => Err(out_of_range("a multiple of the facing bet above 1")),
+        };
+    }
+    if let Some(rest) = token.strip_prefix("abs:") {
+        return match number(rest)? {
+            value if value > 0.0 => Ok(()),
+            _ => Err(out_of_range("a positive BB amount")),
+        };
+    }
+
+    match token.parse::<f64>() {
+        Ok(value) if value > 0.0 && value.is_finite() => Ok(()),
+        _ => Err(TokenError::Unknown(token.to_string())),
+    }
+}
+
+fn parse_action_token(
+    token: &str,
+    pot_bb: f64,
+    stack_cap: f64,
+    facing_bb: f64,
+    policy: &SizingPolicy,
+) -> Option<ActionSpec> {
+    // Fold and check put nothing in, a call the bet faced. Legalization drops them where they
+    // don't apply.
+    if let Some(kind) = passive_kind(token) {
+        return Some(ActionSpec {
+            label: kind.as_str().to_string(),
+            amount: if kind == ActionKind::Call {
+                facing_bb.min(stack_cap)
+            } else {
+                0.0
+            },
+            kind,
+        });
+    }
+
     if token.eq_ignore_ascii_case("all-in") {
         return Some(ActionSpec {
             label: "all-in".to_string(),
             amount: stack_cap,
+            kind: ActionKind::AllIn,
         });
     }
 
+    let sized = if facing_bb > 0.0 {
+        ActionKind::Raise
+    } else {
+        ActionKind::Bet
+    };
+
     if let Some(rest) = token.strip_prefix("pot:") {
         let fraction = rest.parse::<f64>().unwrap_or(0.0).max(0.01);
         return Some(ActionSpec {
             label: format!("pot-{:.2}", fraction),
-            amount: (fraction * pot_bb).clamp(0.5, stack_cap),
+            amount: policy.fit(fraction * pot_bb, stack_cap)?,
+            kind: sized,
+        });
+    }
+
+    if let Some(rest) = token.strip_prefix("geo:") {
+        let streets = rest.parse::<u32>().unwrap_or(1).max(1);
+        let fraction = geometric_fraction(pot_bb, stack_cap, streets);
+        return Some(ActionSpec {
+            label: format!("geo-{}", streets),
+            amount: policy.fit(fraction * pot_bb, stack_cap)?,
+            kind: sized,
         });
     }
 
@@ -71,7 +860,21 @@ fn parse_action_token(token: &str, pot_bb: f64, stack_cap: f64) -> Option<Action
         let fraction = rest.parse::<f64>().unwrap_or(0.0).clamp(0.0, 1.0);
         return Some(ActionSpec {
             label: format!("stack-{:.2}", fraction),
-            amount: (fraction * stack_cap).max(0.5),
+            amount: policy.fit(fraction * stack_cap, stack_cap)?,
+            kind: sized,
+        });
+    }
+
+    // Raise to N times the facing bet; there is nothing to multiply when no bet is faced.
+    if let Some(rest) = token.strip_prefix("x:") {
+        let multiple = rest.parse::<f64>().unwrap_or(0.0);
+        if facing_bb <= 0.0 || multiple <= 1.0 {
+            return None;
+        }
+        return Some(ActionSpec {
+            label: format!("x-{:.2}", multiple),
+            amount: policy.cap(multiple * facing_bb, stack_cap)?,
+            kind: sized,
         });
     }
 
@@ -79,7 +882,8 @@ fn parse_action_token(token: &str, pot_bb: f64, stack_cap: f64) -> Option<Action
         let value = rest.parse::<f64>().unwrap_or(0.0).max(0.0);
         return Some(ActionSpec {
             label: format!("abs-{:.2}", value),
-            amount: value.min(stack_cap),
+            amount: policy.cap(value, stack_cap)?,
+            kind: sized,
         });
     }
 
@@ -88,7 +892,8 @@ fn parse_action_token(token: &str, pot_bb: f64, stack_cap: f64) -> Option<Action
         if value > 0.0 {
             return Some(ActionSpec {
                 label: format!("abs-{:.2}", value),
-                amount: value.min(stack_cap),
+                amount: policy.cap(value, stack_cap)?,
+                kind: sized,
             });
         }
     }
@@ -96,6 +901,16 @@ fn parse_action_token(token: &str, pot_bb: f64, stack_cap: f64) -> Option<Action
     None
 }
 
+// Pot fraction that, bet and called on each of `streets` streets, leaves both players all-in:
+// pot * (1 + 2f)^n = pot + 2 * stack.
+pub fn geometric_fraction(pot_bb: f64, stack_bb: f64, streets: u32) -> f64 {
+    if pot_bb <= 0.0 || stack_bb <= 0.0 {
+        return 0.0;
+    }
+    let growth = (pot_bb + 2.0 * stack_bb) / pot_bb;
+    (growth.powf(1.0 / streets.max(1) as f64) - 1.0) / 2.0
+}
+
 pub fn bucket_hole_cards(card_codes: &[String]) -> String {
     if card_codes.len() < 2 {
         return "unknown".to_string();
@@ -0,0 +1,267 @@
+use crate::abstraction::quality::{enumerate_completions, live_cards, live_combos, sample};
+use crate::abstraction::showdown::ShowdownMatrix;
+use crate::abstraction::{GameVariant, Street};
+use crate::cards::ranking::{evaluate, HandRank};
+use crate::cards::{board_size, Card};
+use crate::rng::SolverRng;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+
+// Exact matrices already dealt, by board, buckets, variant and runouts. The same boards come up
+// solve after solve, and dealing every runout again is most of a flop solve's setup.
+type ExactKey = (Vec<Card>, usize, GameVariant, usize);
+static EXACT: OnceLock<Mutex<HashMap<ExactKey, Arc<ShowdownMatrix>>>> = OnceLock::new();
+// Past this many boards the memo starts over.
+const MAX_EXACT_BOARDS: usize = 256;
+
+// How many runouts `board` has left: every way to deal the cards still to come from those live.
+pub fn runout_count(board: &[Card], variant: GameVariant) -> usize {
+    let missing = board_size(Street::River).saturating_sub(board.len());
+    let live = live_cards(board, variant).len();
+    (0..missing).fold(1, |count, dealt| count * (live - dealt) / (dealt + 1))
+}
+
+// `all_in_showdowns` for a solve: exact matrices are dealt once per process and reused, and
+// dealing stops at `deadline`, settling on the runouts dealt by then, drawn in random order.
+// Without a deadline it is `all_in_showdowns` itself.
+pub fn budgeted_all_in_showdowns(
+    board: &[Card],
+    buckets: usize,
+    variant: GameVariant,
+    runouts: usize,
+    deadline: Option<Instant>,
+    rng: &mut SolverRng,
+) -> Option<Arc<ShowdownMatrix>> {
+    let mut sorted = board.to_vec();
+    sorted.sort();
+    let key = (sorted, buckets, variant, runouts);
+    let memo = EXACT.get_or_init(Mutex::default);
+    let cached = memo
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get(&key)
+        .cloned();
+    if cached.is_some() {
+        return cached;
+    }
+    let (matrix, exact) = deal(board, buckets, variant, runouts, deadline, rng)?;
+    let matrix = Arc::new(matrix);
+    if exact {
+        let mut memo = memo.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if memo.len() >= MAX_EXACT_BOARDS {
+            memo.clear();
+        }
+        memo.insert(key, matrix.clone());
+    }
+    Some(matrix)
+}
+
+// Showdowns for pots that are all-in before the river. Nothing is left to bet, so each pairing of
+// buckets is settled at its equity over the remaining runouts, hand against hand with the
+// evaluator, instead of the higher bucket always winning or equity being discounted for how much
+// of it a hand would realize. Every runout is dealt when there are at most `runouts` of them
+// (every turn and river of a flop by default, `runout_count` of them), otherwise that many are
+// sampled. Buckets are
+// quantiles of equity against the whole range over the same runouts, as usual. Pairings are
+// equally likely, like the plain showdowns the rest of the tree uses. None on the river, where
+// the plain showdown is already exact.
+pub fn all_in_showdowns(
+    board: &[Card],
+    buckets: usize,
+    variant: GameVariant,
+    runouts: usize,
+    rng: &mut SolverRng,
+) -> Option<ShowdownMatrix> {
+    deal(board, buckets, variant, runouts, None, rng).map(|(matrix, _)| matrix)
+}
+
+// The matrix, and whether it is exact: every runout dealt, none left out for time.
+fn deal(
+    board: &[Card],
+    buckets: usize,
+    variant: GameVariant,
+    runouts: usize,
+    deadline: Option<Instant>,
+    rng: &mut SolverRng,
+) -> Option<(ShowdownMatrix, bool)> {
+    let river = board_size(Street::River);
+    if board.len() >= river || buckets == 0 || runouts == 0 {
+        return None;
+    }
+    let live = live_cards(board, variant);
+    let combos = live_combos(&live);
+    let missing = river - board.len();
+    let enumerated = enumerate_completions(&live, missing, runouts);
+    let mut exact = enumerated.is_some();
+    let mut completions: Vec<(usize, Vec<Card>)> = enumerated
+        .unwrap_or_else(|| (0..runouts).map(|_| sample(&live, missing, rng)).collect())
+        .into_iter()
+        .enumerate()
+        .collect();
+    // Any prefix of a shuffled enumeration is a fair sample of it, so stopping early only widens
+    // the error. Sums run in dealing order either way, so a full deal doesn't depend on the seed.
+    if deadline.is_some() {
+        for slot in 0..completions.len() {
+            let pick = slot + rng.below(completions.len() - slot);
+            completions.swap(slot, pick);
+        }
+    }
+    let mut dealt: Vec<(usize, Vec<(HandRank, usize)>)> = Vec::with_capacity(completions.len());
+    for (order, completion) in &completions {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            exact = false;
+            break;
+        }
+        dealt.push((*order, rank_runout(board, completion, &combos, variant)));
+    }
+    dealt.sort_by_key(|(order, _)| *order);
+    let ranked: Vec<Vec<(HandRank, usize)>> = dealt.into_iter().map(|(_, runout)| runout).collect();
+
+    let mut equity = vec![0.0; combos.len()];
+    let mut dealt = vec![0u32; combos.len()];
+    let everyone = vec![0; combos.len()];
+    for runout in &ranked {
+        sweep(
+            runout,
+            &combos,
+            &everyone,
+            1,
+            |combo, _, [beaten, tied, opponents]| {
+                if opponents > 0 {
+                    equity[combo] += (beaten as f64 + tied as f64 / 2.0) / opponents as f64;
+                    dealt[combo] += 1;
+                }
+            },
+        );
+    }
+    let mut order: Vec<usize> = (0..combos.len())
+        .filter(|&combo| dealt[combo] > 0)
+        .collect();
+    if order.len() < buckets {
+        return None;
+    }
+    let mean = |combo: usize| equity[combo] / dealt[combo] as f64;
+    order.sort_by(|a, b| mean(*a).total_cmp(&mean(*b)));
+    let mut bucket_of = vec![0; combos.len()];
+    for (position, combo) in order.iter().enumerate() {
+        bucket_of[*combo] = position * buckets / order.len();
+    }
+
+    let cells = buckets * buckets;
+    let mut pairs = vec![0.0; cells];
+    let mut wins = vec![0.0; cells];
+    let mut ties = vec![0.0; cells];
+    for runout in &ranked {
+        sweep(
+            runout,
+            &combos,
+            &bucket_of,
+            buckets,
+            |combo, opposing, counts| {
+                let cell = bucket_of[combo] * buckets + opposing;
+                let [beaten, tied, opponents] = counts.map(f64::from);
+                wins[cell] += beaten;
+                ties[cell] += tied;
+                pairs[cell] += opponents;
+            },
+        );
+    }
+    let share = |counts: &[f64], empty: f64| -> Vec<f64> {
+        counts
+            .iter()
+            .zip(&pairs)
+            .map(|(count, pairs)| if *pairs > 0.0 { count / pairs } else { empty })
+            .collect()
+    };
+    let matrix = ShowdownMatrix {
+        buckets,
+        weight: vec![1.0; cells],
+        win: share(&wins, 0.5),
+        tie: share(&ties, 0.0),
+    };
+    Some((matrix, exact))
+}
+
+// The combos `completion` leaves live on the full board, weakest first.
+fn rank_runout(
+    board: &[Card],
+    completion: &[Card],
+    combos: &[[Card; 2]],
+    variant: GameVariant,
+) -> Vec<(HandRank, usize)> {
+    let full: Vec<Card> = board.iter().chain(completion).copied().collect();
+    let mut ranked: Vec<(HandRank, usize)> = combos
+        .iter()
+        .enumerate()
+        .filter(|(_, combo)| !combo.iter().any(|card| completion.contains(card)))
+        .filter_map(|(index, combo)| {
+            let cards: Vec<Card> = combo.iter().chain(&full).copied().collect();
+            evaluate(&cards, variant).map(|rank| (rank, index))
+        })
+        .collect();
+    ranked.sort();
+    ranked
+}
+
+// Walks one runout's combos in rank order and reports, for each combo and each column of
+// opponents (`column` per combo), how many it beats, ties with, and can meet at all, counting
+// only combos that share no card with it. As in `showdown_equities`, per-card counts include
+// the combo itself once per card, so it is added back once to leave it out.
+fn sweep(
+    runout: &[(HandRank, usize)],
+    combos: &[[Card; 2]],
+    column: &[usize],
+    columns: usize,
+    mut record: impl FnMut(usize, usize, [u32; 3]),
+) {
+    let mut total = vec![0u32; columns];
+    let mut per_card = vec![0u32; 52 * columns];
+    for (_, index) in runout {
+        total[column[*index]] += 1;
+        for card in &combos[*index] {
+            per_card[card.index() * columns + column[*index]] += 1;
+        }
+    }
+    let mut below = vec![0u32; columns];
+    let mut below_per_card = vec![0u32; 52 * columns];
+    let mut level = vec![0u32; columns];
+    let mut level_per_card = vec![0u32; 52 * columns];
+    let mut start = 0;
+    while start < runout.len() {
+        let end = start
+            + runout[start..]
+                .iter()
+                .take_while(|(rank, _)| *rank == runout[start].0)
+                .count();
+        let group = &runout[start..end];
+        for (_, index) in group {
+            level[column[*index]] += 1;
+            for card in &combos[*index] {
+                level_per_card[card.index() * columns + column[*index]] += 1;
+            }
+        }
+        for (_, index) in group {
+            let [first, second] = combos[*index].map(|card| card.index() * columns);
+            for opposing in 0..columns {
+                let itself = (column[*index] == opposing) as u32;
+                let (first, second) = (first + opposing, second + opposing);
+                let opponents = total[opposing] + itself - per_card[first] - per_card[second];
+                let beaten = below[opposing] - below_per_card[first] - below_per_card[second];
+                let tied =
+                    level[opposing] + itself - level_per_card[first] - level_per_card[second];
+                record(*index, opposing, [beaten, tied, opponents]);
+            }
+        }
+        for (_, index) in group {
+            let own = column[*index];
+            below[own] += 1;
+            level[own] -= 1;
+            for card in &combos[*index] {
+                below_per_card[card.index() * columns + own] += 1;
+                level_per_card[card.index() * columns + own] -= 1;
+            }
+        }
+        start = end;
+    }
+}
@@ -0,0 +1,121 @@
+use crate::abstraction::quality::{live_cards, live_combos, showdown_equities};
+use crate::abstraction::showdown::ShowdownMatrix;
+use crate::abstraction::GameVariant;
+use crate::cards::ranking::evaluate;
+use crate::cards::Card;
+use std::cmp::Ordering;
+
+// Bucket-against-bucket showdowns on a known river with card removal: the hands in each bucket
+// are the real combos, and pairings that share a card never happen. Holding the nut-flush
+// blocker makes the nut bucket less likely across the table, which plain equity buckets (every
+// pairing equally likely, the higher bucket always winning) can't see. Buckets are equity
+// quantiles of the combos, as with plain buckets. Compares every pair of combos, about a million
+// on a hold'em river, hence opt-in.
+pub fn card_removal(
+    board: &[Card],
+    buckets: usize,
+    variant: GameVariant,
+) -> Option<ShowdownMatrix> {
+    if board.len() != 5 || buckets == 0 {
+        return None;
+    }
+    bucketed_showdowns(board, &combos_by_equity(board, variant), buckets, variant)
+}
+
+// The river solved without card abstraction: every live combo is its own bucket, weakest
+// first, and showdowns are settled by exact hand ranks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExactRiver {
+    pub showdowns: ShowdownMatrix,
+    // The combo of each bucket.
+    pub combos: Vec<[Card; 2]>,
+}
+
+pub fn exact_river(board: &[Card], variant: GameVariant) -> Option<ExactRiver> {
+    if board.len() != 5 {
+        return None;
+    }
+    let combos = combos_by_equity(board, variant);
+    let showdowns = bucketed_showdowns(board, &combos, combos.len(), variant)?;
+    Some(ExactRiver { showdowns, combos })
+}
+
+// The live combos, weakest showdown equity first.
+fn combos_by_equity(board: &[Card], variant: GameVariant) -> Vec<[Card; 2]> {
+    let combos = live_combos(&live_cards(board, variant));
+    let mut by_equity = showdown_equities(board, &combos, variant);
+    by_equity.sort_by(|a, b| a.1.total_cmp(&b.1));
+    by_equity.iter().map(|(index, _)| combos[*index]).collect()
+}
+
+// Equity quantiles of `combos`, which are sorted weakest first.
+fn bucketed_showdowns(
+    board: &[Card],
+    combos: &[[Card; 2]],
+    buckets: usize,
+    variant: GameVariant,
+) -> Option<ShowdownMatrix> {
+    let count = combos.len();
+    if buckets == 0 || count < buckets {
+        return None;
+    }
+    let bucket_of: Vec<usize> = (0..count)
+        .map(|position| position * buckets / count)
+        .collect();
+    let ranks: Vec<_> = combos
+        .iter()
+        .map(|combo| {
+            let cards: Vec<Card> = combo.iter().chain(board).copied().collect();
+            evaluate(&cards, variant)
+        })
+        .collect();
+
+    let cells = buckets * buckets;
+    let mut pairs = vec![0.0; cells];
+    let mut wins = vec![0.0; cells];
+    let mut ties = vec![0.0; cells];
+    for (first, combo) in combos.iter().enumerate() {
+        for (second, other) in combos.iter().enumerate().skip(first + 1) {
+            if combo.iter().any(|card| other.contains(card)) {
+                continue;
+            }
+            let forward = bucket_of[first] * buckets + bucket_of[second];
+            let backward = bucket_of[second] * buckets + bucket_of[first];
+            pairs[forward] += 1.0;
+            pairs[backward] += 1.0;
+            match ranks[first].cmp(&ranks[second]) {
+                Ordering::Greater => wins[forward] += 1.0,
+                Ordering::Less => wins[backward] += 1.0,
+                Ordering::Equal => {
+                    ties[forward] += 1.0;
+                    ties[backward] += 1.0;
+                }
+            }
+        }
+    }
+
+    let mut sizes = vec![0.0; buckets];
+    for bucket in &bucket_of {
+        sizes[*bucket] += 1.0;
+    }
+    let mut weight: Vec<f64> = (0..cells)
+        .map(|cell| pairs[cell] / (sizes[cell / buckets] * sizes[cell % buckets]))
+        .collect();
+    let mean = weight.iter().sum::<f64>() / cells as f64;
+    if mean > 0.0 {
+        weight.iter_mut().for_each(|cell| *cell /= mean);
+    }
+    let share = |counts: &[f64]| -> Vec<f64> {
+        counts
+            .iter()
+            .zip(&pairs)
+            .map(|(count, pairs)| if *pairs > 0.0 { count / pairs } else { 0.0 })
+            .collect()
+    };
+    Some(ShowdownMatrix {
+        buckets,
+        win: share(&wins),
+        tie: share(&ties),
+        weight,
+    })
+}
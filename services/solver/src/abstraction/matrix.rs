@@ -0,0 +1,98 @@
+use crate::abstraction::quality::hand_equities;
+use crate::abstraction::GameVariant;
+use crate::cards::Card;
+use crate::rng::SolverRng;
+use serde::Serialize;
+
+const RANK_CODES: &[u8] = b"AKQJT98765432";
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MatrixCell {
+    // "AA", "AKs" or "AKo".
+    pub hand: String,
+    // Combos of the class the board leaves live; 0 leaves the frequencies at 0 too.
+    pub combos: usize,
+    // In the order of the matrix's actions, averaged over the class's combos.
+    pub frequencies: Vec<f64>,
+}
+
+// The familiar 13x13 hand grid: aces first, pairs on the diagonal, suited hands above it and
+// offsuit ones below.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StrategyMatrix {
+    pub actions: Vec<String>,
+    pub rows: Vec<Vec<MatrixCell>>,
+}
+
+pub fn hand_class(row: usize, column: usize) -> String {
+    let (high, low) = (RANK_CODES[row.min(column)], RANK_CODES[row.max(column)]);
+    let suffix = match row.cmp(&column) {
+        std::cmp::Ordering::Equal => "",
+        std::cmp::Ordering::Less => "s",
+        std::cmp::Ordering::Greater => "o",
+    };
+    format!("{}{}{}", high as char, low as char, suffix)
+}
+
+// Spreads a per-bucket strategy (`strategy[bucket][action]`) back over the hands. Every live
+// combo is put in the bucket its equity falls in, the way the solve cut its quantiles, and each
+// cell averages its combos.
+pub fn strategy_matrix(
+    board: &[Card],
+    variant: GameVariant,
+    actions: Vec<String>,
+    strategy: &[Vec<f64>],
+    runouts: usize,
+    rng: &mut SolverRng,
+) -> StrategyMatrix {
+    let buckets = strategy.len();
+    let mut equities = hand_equities(board, variant, runouts.max(1), rng);
+    equities.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let count = equities.len();
+
+    let mut sums = vec![vec![vec![0.0; actions.len()]; 13]; 13];
+    let mut combos = vec![vec![0; 13]; 13];
+    for (position, (combo, _)) in equities.iter().enumerate() {
+        let Some(row) = strategy.get(position * buckets / count.max(1)) else {
+            continue;
+        };
+        let (row_index, column) = cell_of(combo);
+        combos[row_index][column] += 1;
+        for (sum, frequency) in sums[row_index][column].iter_mut().zip(row) {
+            *sum += frequency;
+        }
+    }
+    let rows = (0..13)
+        .map(|row| {
+            (0..13)
+                .map(|column| {
+                    let combos = combos[row][column];
+                    MatrixCell {
+                        hand: hand_class(row, column),
+                        combos,
+                        frequencies: sums[row][column]
+                            .iter()
+                            .map(|sum| if combos > 0 { sum / combos as f64 } else { 0.0 })
+                            .collect(),
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    StrategyMatrix { actions, rows }
+}
+
+pub fn matrix_json(matrix: &StrategyMatrix) -> String {
+    serde_json::to_string(matrix).unwrap_or_default()
+}
+
+// Row and column of a combo: the higher rank's row for suited hands, its column for offsuit.
+pub fn cell_of(combo: &[Card; 2]) -> (usize, usize) {
+    let [first, second] = combo.map(|card| 12 - card.rank as usize);
+    let (high, low) = (first.min(second), first.max(second));
+    if combo[0].suit == combo[1].suit {
+        (high, low)
+    } else {
+        (low, high)
+    }
+}
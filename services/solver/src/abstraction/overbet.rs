@@ -0,0 +1,91 @@
+use crate::abstraction::GameStateSummary;
+use crate::cards::Card;
+
+pub const OVERBET_AUTO: &str = "overbet:auto";
+
+// Overbet sizes as pot fractions, from the most to the least polar.
+const OVERBET_FRACTIONS: &[f64] = &[3.0, 2.5, 2.0, 1.5, 1.25];
+
+// How strongly hero's range holds the nuts here, 0 to 1 with 0.5 even. A rough read from the
+// board and the betting: dry, high, paired boards favour whoever took the initiative, while
+// connected or suited boards hand the caller straights and flushes.
+pub fn nut_advantage(summary: &GameStateSummary, board: &[Card]) -> f64 {
+    let mut advantage: f64 = 0.5;
+    if board.len() >= 3 {
+        let mut ranks: Vec<u8> = board.iter().map(|card| card.rank).collect();
+        ranks.sort_unstable();
+        ranks.dedup();
+        if ranks.len() < board.len() {
+            advantage += 0.1;
+        }
+        // Queen or better.
+        if ranks.last().is_some_and(|rank| *rank >= 10) {
+            advantage += 0.1;
+        }
+        let flush_draw =
+            (0..4).any(|suit| board.iter().filter(|card| card.suit == suit).count() >= 3);
+        if flush_draw {
+            advantage -= 0.15;
+        }
+        if ranks.windows(3).any(|window| window[2] - window[0] <= 4) {
+            advantage -= 0.15;
+        }
+    }
+    // The last bet or raise before this street.
+    let aggressor = summary
+        .history
+        .iter()
+        .rev()
+        .filter(|record| !record.street.eq_ignore_ascii_case(&summary.street))
+        .find(|record| matches!(record.action.as_str(), "bet" | "raise"))
+        .map(|record| record.seat);
+    match (aggressor, summary.hero_seat) {
+        (Some(aggressor), Some(hero)) if aggressor == hero => advantage += 0.15,
+        (Some(_), Some(_)) => advantage -= 0.15,
+        _ => {}
+    }
+    advantage.clamp(0.0, 1.0)
+}
+
+// Overbets worth trying, most preferred first. Only sizes that leave chips behind count (the
+// all-in covers the rest): the two biggest with a clear nut advantage, the smallest with an
+// even one, and none without.
+pub fn auto_overbet_fractions(spr: f64, nut_advantage: f64) -> Vec<f64> {
+    let fitting: Vec<f64> = OVERBET_FRACTIONS
+        .iter()
+        .copied()
+        .filter(|fraction| *fraction < spr)
+        .collect();
+    match nut_advantage {
+        advantage if advantage >= 0.65 => fitting.into_iter().take(2).collect(),
+        advantage if advantage >= 0.4 => fitting.last().copied().into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Replaces each `overbet:auto` with the pot-fraction tokens it stands for in this spot.
+pub fn expand_overbet_tokens(
+    tokens: &[String],
+    summary: &GameStateSummary,
+    board: &[Card],
+    effective_stack_bb: f64,
+) -> Vec<String> {
+    if !tokens.iter().any(|token| token == OVERBET_AUTO) {
+        return tokens.to_vec();
+    }
+    let spr = effective_stack_bb / summary.pot_in_bb();
+    let auto: Vec<String> = auto_overbet_fractions(spr, nut_advantage(summary, board))
+        .into_iter()
+        .map(|fraction| format!("pot:{}", fraction))
+        .collect();
+    tokens
+        .iter()
+        .flat_map(|token| {
+            if token == OVERBET_AUTO {
+                auto.clone()
+            } else {
+                vec![token.clone()]
+            }
+        })
+        .collect()
+}
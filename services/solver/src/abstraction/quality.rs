@@ -0,0 +1,347 @@
+use crate::abstraction::{GameVariant, Street};
+use crate::cards::ranking::{evaluate, HandRank};
+use crate::cards::{board_size, Card};
+use crate::rng::SolverRng;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualityOptions {
+    // Runouts averaged into a hand's equity before the river; every runout when there are no more.
+    pub runouts: usize,
+    // Boards sampled for each street after the spot's.
+    pub boards: usize,
+    // Hands sharing a bucket further apart in equity than this collide.
+    pub collision_tolerance: f64,
+}
+
+impl Default for QualityOptions {
+    fn default() -> Self {
+        Self {
+            runouts: 48,
+            boards: 4,
+            collision_tolerance: 0.05,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BucketQuality {
+    pub combos: usize,
+    pub mean_equity: f64,
+    pub equity_variance: f64,
+    pub min_equity: f64,
+    pub max_equity: f64,
+}
+
+// How well equity buckets describe one street, averaged over the boards sampled for it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreetQuality {
+    pub street: Street,
+    pub buckets: usize,
+    pub boards: usize,
+    pub combos: usize,
+    pub equity_variance: f64,
+    // Left inside the buckets, weighted by their size; what the abstraction can't tell apart.
+    pub within_bucket_variance: f64,
+    pub collision_rate: f64,
+    pub bucket_details: Vec<BucketQuality>,
+}
+
+// The spot's street and every later one. The solver's buckets are equity quantiles, so each board
+// is scored by ranking every hand by its equity against a random hand and cutting the ranking
+// into `buckets` equal parts.
+pub fn abstraction_quality(
+    board: &[Card],
+    street: Street,
+    buckets: usize,
+    variant: GameVariant,
+    options: &QualityOptions,
+    rng: &mut SolverRng,
+) -> Vec<StreetQuality> {
+    let buckets = buckets.max(1);
+    [Street::Preflop, Street::Flop, Street::Turn, Street::River]
+        .into_iter()
+        .filter(|later| *later >= street)
+        .map(|later| {
+            let boards: Vec<Vec<Card>> = if later == street {
+                vec![board.to_vec()]
+            } else {
+                (0..options.boards.max(1))
+                    .map(|_| deal(board, board_size(later), variant, rng))
+                    .collect()
+            };
+            let scored: Vec<BoardQuality> = boards
+                .iter()
+                .map(|board| board_quality(board, buckets, variant, options, rng))
+                .collect();
+            combine(later, buckets, &scored)
+        })
+        .collect()
+}
+
+struct BoardQuality {
+    combos: usize,
+    equity_variance: f64,
+    within_bucket_variance: f64,
+    same_bucket_pairs: u64,
+    collisions: u64,
+    buckets: Vec<BucketQuality>,
+}
+
+fn combine(street: Street, buckets: usize, scored: &[BoardQuality]) -> StreetQuality {
+    let boards = scored.len().max(1) as f64;
+    let mean = |value: fn(&BoardQuality) -> f64| scored.iter().map(value).sum::<f64>() / boards;
+    let pairs: u64 = scored.iter().map(|board| board.same_bucket_pairs).sum();
+    let collisions: u64 = scored.iter().map(|board| board.collisions).sum();
+    let bucket_details = (0..buckets)
+        .map(|bucket| {
+            let rows: Vec<&BucketQuality> = scored
+                .iter()
+                .filter_map(|board| board.buckets.get(bucket))
+                .collect();
+            let count = rows.len().max(1) as f64;
+            BucketQuality {
+                combos: rows.first().map(|row| row.combos).unwrap_or(0),
+                mean_equity: rows.iter().map(|row| row.mean_equity).sum::<f64>() / count,
+                equity_variance: rows.iter().map(|row| row.equity_variance).sum::<f64>() / count,
+                min_equity: rows.iter().map(|row| row.min_equity).fold(1.0, f64::min),
+                max_equity: rows.iter().map(|row| row.max_equity).fold(0.0, f64::max),
+            }
+        })
+        .collect();
+    StreetQuality {
+        street,
+        buckets,
+        boards: scored.len(),
+        combos: scored.first().map(|board| board.combos).unwrap_or(0),
+        equity_variance: mean(|board| board.equity_variance),
+        within_bucket_variance: mean(|board| board.within_bucket_variance),
+        collision_rate: if pairs > 0 {
+            collisions as f64 / pairs as f64
+        } else {
+            0.0
+        },
+        bucket_details,
+    }
+}
+
+fn board_quality(
+    board: &[Card],
+    buckets: usize,
+    variant: GameVariant,
+    options: &QualityOptions,
+    rng: &mut SolverRng,
+) -> BoardQuality {
+    let mut equities: Vec<f64> = hand_equities(board, variant, options.runouts.max(1), rng)
+        .into_iter()
+        .map(|(_, equity)| equity)
+        .collect();
+    equities.sort_by(f64::total_cmp);
+    let combos = equities.len();
+    let mean = equities.iter().sum::<f64>() / combos.max(1) as f64;
+    let equity_variance = variance(&equities, mean);
+
+    let mut quality = BoardQuality {
+        combos,
+        equity_variance,
+        within_bucket_variance: 0.0,
+        same_bucket_pairs: 0,
+        collisions: 0,
+        buckets: Vec::with_capacity(buckets),
+    };
+    for bucket in 0..buckets {
+        let members = &equities[bucket * combos / buckets..(bucket + 1) * combos / buckets];
+        let count = members.len();
+        let bucket_mean = members.iter().sum::<f64>() / count.max(1) as f64;
+        let bucket_variance = variance(members, bucket_mean);
+        quality.within_bucket_variance += bucket_variance * count as f64 / combos.max(1) as f64;
+        quality.same_bucket_pairs += (count * count.saturating_sub(1) / 2) as u64;
+        // Sorted, so every hand more than the tolerance above `low` collides with it.
+        let mut high = 0;
+        for (low, equity) in members.iter().enumerate() {
+            high = high.max(low);
+            while high < count && members[high] - equity <= options.collision_tolerance {
+                high += 1;
+            }
+            quality.collisions += (count - high) as u64;
+        }
+        quality.buckets.push(BucketQuality {
+            combos: count,
+            mean_equity: bucket_mean,
+            equity_variance: bucket_variance,
+            min_equity: members.first().copied().unwrap_or(0.0),
+            max_equity: members.last().copied().unwrap_or(0.0),
+        });
+    }
+    quality
+}
+
+// Every hand's equity against a uniformly random other hand, averaged over runouts to the river.
+// Hands are left out of runouts that use their cards.
+pub fn hand_equities(
+    board: &[Card],
+    variant: GameVariant,
+    runouts: usize,
+    rng: &mut SolverRng,
+) -> Vec<([Card; 2], f64)> {
+    let live = live_cards(board, variant);
+    let combos = live_combos(&live);
+    let missing = board_size(Street::River).saturating_sub(board.len());
+    let completions = enumerate_completions(&live, missing, runouts)
+        .unwrap_or_else(|| (0..runouts).map(|_| sample(&live, missing, rng)).collect());
+
+    let mut sums = vec![0.0; combos.len()];
+    let mut counts = vec![0u32; combos.len()];
+    for completion in completions {
+        let full: Vec<Card> = board.iter().chain(&completion).copied().collect();
+        for (index, equity) in showdown_equities(&full, &combos, variant) {
+            sums[index] += equity;
+            counts[index] += 1;
+        }
+    }
+    combos
+        .into_iter()
+        .zip(sums.iter().zip(&counts))
+        .filter(|(_, (_, count))| **count > 0)
+        .map(|(combo, (sum, count))| (combo, sum / *count as f64))
+        .collect()
+}
+
+// Equity of each combo not blocked by `board` against every other unblocked combo, by sweeping
+// the combos in rank order and counting how many below and level with each one share no card
+// with it.
+pub fn showdown_equities(
+    board: &[Card],
+    combos: &[[Card; 2]],
+    variant: GameVariant,
+) -> Vec<(usize, f64)> {
+    let mut ranked: Vec<(HandRank, usize)> = combos
+        .iter()
+        .enumerate()
+        .filter(|(_, combo)| !combo.iter().any(|card| board.contains(card)))
+        .filter_map(|(index, combo)| {
+            let cards: Vec<Card> = combo.iter().chain(board).copied().collect();
+            evaluate(&cards, variant).map(|rank| (rank, index))
+        })
+        .collect();
+    ranked.sort();
+
+    let mut per_card = [0u32; 52];
+    for (_, index) in &ranked {
+        for card in &combos[*index] {
+            per_card[card.index()] += 1;
+        }
+    }
+    let total = ranked.len() as u32;
+    let mut below = 0u32;
+    let mut below_per_card = [0u32; 52];
+    let mut equities = Vec::with_capacity(ranked.len());
+    let mut start = 0;
+    while start < ranked.len() {
+        let end = start
+            + ranked[start..]
+                .iter()
+                .take_while(|(rank, _)| *rank == ranked[start].0)
+                .count();
+        let group = &ranked[start..end];
+        let mut level_per_card = [0u32; 52];
+        for (_, index) in group {
+            for card in &combos[*index] {
+                level_per_card[card.index()] += 1;
+            }
+        }
+        for (_, index) in group {
+            let [first, second] = combos[*index].map(|card| card.index());
+            // Counts by card include the combo itself once per card; add it back once to exclude
+            // it a single time.
+            let opponents = total + 1 - per_card[first] - per_card[second];
+            let beaten = below - below_per_card[first] - below_per_card[second];
+            let tied = group.len() as u32 + 1 - level_per_card[first] - level_per_card[second];
+            if opponents > 0 {
+                let equity = (beaten as f64 + tied as f64 / 2.0) / opponents as f64;
+                equities.push((*index, equity));
+            }
+        }
+        for (_, index) in group {
+            for card in &combos[*index] {
+                below_per_card[card.index()] += 1;
+            }
+        }
+        below += group.len() as u32;
+        start = end;
+    }
+    equities
+}
+
+// Every way to add `missing` cards from `live`, if there are at most `limit` of them.
+pub fn enumerate_completions(
+    live: &[Card],
+    missing: usize,
+    limit: usize,
+) -> Option<Vec<Vec<Card>>> {
+    let mut completions = vec![Vec::new()];
+    for _ in 0..missing {
+        completions = completions
+            .iter()
+            .flat_map(|partial: &Vec<Card>| {
+                let next = partial
+                    .last()
+                    .and_then(|last| live.iter().position(|card| card == last))
+                    .map(|position| position + 1)
+                    .unwrap_or(0);
+                live[next..].iter().map(move |card| {
+                    let mut extended = partial.clone();
+                    extended.push(*card);
+                    extended
+                })
+            })
+            .collect();
+        if completions.len() > limit {
+            return None;
+        }
+    }
+    Some(completions)
+}
+
+pub fn sample(live: &[Card], count: usize, rng: &mut SolverRng) -> Vec<Card> {
+    let mut deck = live.to_vec();
+    for slot in 0..count.min(deck.len()) {
+        let pick = slot + rng.below(deck.len() - slot);
+        deck.swap(slot, pick);
+    }
+    deck.truncate(count);
+    deck
+}
+
+// The variant's deck less the board.
+pub fn live_cards(board: &[Card], variant: GameVariant) -> Vec<Card> {
+    variant
+        .deck()
+        .into_iter()
+        .filter(|card| !board.contains(card))
+        .collect()
+}
+
+// Every two-card hand from `live`.
+pub fn live_combos(live: &[Card]) -> Vec<[Card; 2]> {
+    (0..live.len())
+        .flat_map(|first| (first + 1..live.len()).map(move |second| [live[first], live[second]]))
+        .collect()
+}
+
+fn deal(board: &[Card], size: usize, variant: GameVariant, rng: &mut SolverRng) -> Vec<Card> {
+    let live = live_cards(board, variant);
+    let mut dealt = board.to_vec();
+    dealt.extend(sample(&live, size.saturating_sub(board.len()), rng));
+    dealt
+}
+
+fn variance(values: &[f64], mean: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / values.len() as f64
+}
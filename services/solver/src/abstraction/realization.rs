@@ -0,0 +1,110 @@
+use crate::abstraction::quality::hand_equities;
+use crate::abstraction::showdown::ShowdownMatrix;
+use crate::abstraction::{GameVariant, Street};
+use crate::cards::{board_size, Card};
+use crate::rng::SolverRng;
+
+// How much of its raw equity a hand keeps when a tree that stops before the river settles it at
+// showdown. Pairs play on well, offsuit hands miss and get pushed off, and position helps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Realization {
+    pub pair: f64,
+    pub suited: f64,
+    pub offsuit: f64,
+    pub in_position: f64,
+    pub out_of_position: f64,
+    // Runouts averaged into each hand's raw equity.
+    pub runouts: usize,
+}
+
+impl Default for Realization {
+    fn default() -> Self {
+        Self {
+            pair: 1.0,
+            suited: 0.95,
+            offsuit: 0.85,
+            in_position: 1.05,
+            out_of_position: 0.9,
+            runouts: 32,
+        }
+    }
+}
+
+impl Realization {
+    pub fn class_factor(&self, combo: &[Card; 2]) -> f64 {
+        if combo[0].rank == combo[1].rank {
+            self.pair
+        } else if combo[0].suit == combo[1].suit {
+            self.suited
+        } else {
+            self.offsuit
+        }
+    }
+}
+
+// Showdowns for a board short of the river. Buckets are equity quantiles as usual; each one
+// carries its mean equity and the mean realization factor of its hands. A pairing's raw equity
+// comes from the two bucket equities (log5: how often the first beats the second if each beat a
+// random hand at its own rate), and each side's share is then scaled by its factor and seat. None
+// on the river, where equity is already final.
+pub fn realized_showdowns(
+    board: &[Card],
+    buckets: usize,
+    variant: GameVariant,
+    realization: &Realization,
+    hero_in_position: bool,
+    rng: &mut SolverRng,
+) -> Option<ShowdownMatrix> {
+    if board.len() >= board_size(Street::River) || buckets == 0 {
+        return None;
+    }
+    let mut equities = hand_equities(board, variant, realization.runouts.max(1), rng);
+    equities.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let count = equities.len();
+    if count < buckets {
+        return None;
+    }
+    let mut equity = vec![0.0; buckets];
+    let mut factor = vec![0.0; buckets];
+    for bucket in 0..buckets {
+        let members = &equities[bucket * count / buckets..(bucket + 1) * count / buckets];
+        let size = members.len() as f64;
+        equity[bucket] = members.iter().map(|(_, equity)| equity).sum::<f64>() / size;
+        factor[bucket] = members
+            .iter()
+            .map(|(combo, _)| realization.class_factor(combo))
+            .sum::<f64>()
+            / size;
+    }
+    let (hero_seat, villain_seat) = if hero_in_position {
+        (realization.in_position, realization.out_of_position)
+    } else {
+        (realization.out_of_position, realization.in_position)
+    };
+
+    let mut win = vec![0.0; buckets * buckets];
+    for hero in 0..buckets {
+        for villain in 0..buckets {
+            let (own, other) = (equity[hero], equity[villain]);
+            let odds = own * (1.0 - other) + other * (1.0 - own);
+            let raw = if odds > 0.0 {
+                own * (1.0 - other) / odds
+            } else {
+                0.5
+            };
+            let kept = factor[hero] * hero_seat * raw;
+            let conceded = factor[villain] * villain_seat * (1.0 - raw);
+            win[hero * buckets + villain] = if kept + conceded > 0.0 {
+                kept / (kept + conceded)
+            } else {
+                0.5
+            };
+        }
+    }
+    Some(ShowdownMatrix {
+        buckets,
+        weight: vec![1.0; buckets * buckets],
+        tie: vec![0.0; buckets * buckets],
+        win,
+    })
+}
@@ -0,0 +1,31 @@
+use crate::game_tree::HERO;
+
+// How each pairing of buckets plays out at showdown, when that is more than "the higher bucket
+// wins". Rows are hero's bucket and columns villain's; villain's side is read off the same cells.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShowdownMatrix {
+    pub buckets: usize,
+    // How likely the pairing is relative to independent deals, 1 on average.
+    pub weight: Vec<f64>,
+    // Hero's share of the pairing's showdowns won and split.
+    pub win: Vec<f64>,
+    pub tie: Vec<f64>,
+}
+
+impl ShowdownMatrix {
+    pub fn cell(&self, hero: usize, villain: usize) -> usize {
+        hero * self.buckets + villain
+    }
+
+    // (weight, share won, share split) for `seat` holding `own` against `opposing`.
+    pub fn outcome(&self, seat: usize, own: usize, opposing: usize) -> (f64, f64, f64) {
+        if seat == HERO {
+            let cell = self.cell(own, opposing);
+            (self.weight[cell], self.win[cell], self.tie[cell])
+        } else {
+            let cell = self.cell(opposing, own);
+            let lost = 1.0 - self.win[cell] - self.tie[cell];
+            (self.weight[cell], lost, self.tie[cell])
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::solver_proto::{BettingRoundActions, StreetActionSets, SubgameRequest};
+
+pub const TREE_TEMPLATES: &[&str] = &["simple-33-75-allin", "triple-geometric", "river-polarized"];
+
+// Named per-street sizes for common trees. Every template opens preflop at 1.5x the pot with an
+// all-in behind, and raises all-in once a street has been re-raised.
+pub fn tree_template(name: &str) -> Option<StreetActionSets> {
+    let preflop = rounds(&["pot:1.5", "all-in"], &["pot:1", "all-in"]);
+    let sets = match name.to_ascii_lowercase().as_str() {
+        // A third and three quarters of the pot on every street.
+        "simple-33-75-allin" => {
+            let street = rounds(&["pot:0.33", "pot:0.75", "all-in"], &["pot:0.75", "all-in"]);
+            StreetActionSets {
+                preflop: Some(preflop),
+                flop: Some(street.clone()),
+                turn: Some(street.clone()),
+                river: Some(street),
+            }
+        }
+        // One size per street that gets the stacks in by the river when bet every time.
+        "triple-geometric" => StreetActionSets {
+            preflop: Some(preflop),
+            flop: Some(rounds(&["geo:3", "all-in"], &["geo:2", "all-in"])),
+            turn: Some(rounds(&["geo:2", "all-in"], &["geo:1", "all-in"])),
+            river: Some(rounds(&["geo:1", "all-in"], &["all-in"])),
+        },
+        // Standard sizes until a river of small bets and overbets.
+        "river-polarized" => {
+            let street = rounds(&["pot:0.5", "pot:1", "all-in"], &["pot:1", "all-in"]);
+            StreetActionSets {
+                preflop: Some(preflop),
+                flop: Some(street.clone()),
+                turn: Some(street),
+                river: Some(rounds(&["pot:0.25", "pot:1.5", "all-in"], &["all-in"])),
+            }
+        }
+        _ => return None,
+    };
+    Some(sets)
+}
+
+// The request's own per-street sizes, else its template's.
+pub fn request_action_sets(request: &SubgameRequest) -> Option<StreetActionSets> {
+    request
+        .street_action_sets
+        .clone()
+        .or_else(|| tree_template(&request.tree_template))
+}
+
+fn rounds(bet: &[&str], raise: &[&str]) -> BettingRoundActions {
+    let tokens = |tokens: &[&str]| tokens.iter().map(|token| token.to_string()).collect();
+    BettingRoundActions {
+        bet: tokens(bet),
+        raise: tokens(raise),
+        reraise: vec!["all-in".to_string()],
+    }
+}
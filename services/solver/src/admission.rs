@@ -0,0 +1,213 @@
+use crate::error::SolverError;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashSet};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdmissionLimits {
+    pub max_concurrent: usize,
+    pub max_queued: usize,
+}
+
+// Queued solves are admitted highest priority first, then in arrival order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SolvePriority {
+    // Offline analysis that can wait.
+    Background,
+    #[default]
+    Normal,
+    // In-hand decisions with a player waiting on them.
+    Realtime,
+}
+
+impl SolvePriority {
+    // "" is None so each RPC can pick its own default.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "background" | "offline" => Some(SolvePriority::Background),
+            "normal" => Some(SolvePriority::Normal),
+            "realtime" | "real-time" => Some(SolvePriority::Realtime),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SolvePriority::Background => "background",
+            SolvePriority::Normal => "normal",
+            SolvePriority::Realtime => "realtime",
+        }
+    }
+}
+
+// Orders the queue: highest priority first, then lowest sequence number.
+type Ticket = (Reverse<SolvePriority>, u64);
+
+#[derive(Debug, Default)]
+struct AdmissionState {
+    running: usize,
+    waiting: BTreeSet<Ticket>,
+    // Queued tickets pushed out of a full queue by a higher-priority arrival.
+    evicted: HashSet<Ticket>,
+    next_ticket: u64,
+    closed: bool,
+}
+
+// Caps how many solves share the CPU at once. Requests past the cap wait in a bounded queue;
+// once the queue is full they are turned away rather than all missing their budgets together.
+// A full queue still takes a solve of higher priority than its lowest entry, which is turned
+// away in its place.
+pub struct AdmissionControl {
+    limits: AdmissionLimits,
+    state: Mutex<AdmissionState>,
+    released: Condvar,
+}
+
+pub struct AdmissionPermit {
+    control: Arc<AdmissionControl>,
+    waited: Duration,
+}
+
+impl AdmissionControl {
+    pub fn new(limits: AdmissionLimits) -> Self {
+        Self {
+            limits: AdmissionLimits {
+                max_concurrent: limits.max_concurrent.max(1),
+                max_queued: limits.max_queued,
+            },
+            state: Mutex::new(AdmissionState::default()),
+            released: Condvar::new(),
+        }
+    }
+
+    pub fn limits(&self) -> AdmissionLimits {
+        self.limits
+    }
+
+    pub fn running(&self) -> usize {
+        self.lock().running
+    }
+
+    pub fn queued(&self) -> usize {
+        self.lock().waiting.len()
+    }
+
+    // Blocks for at most `max_wait` while queued.
+    pub fn acquire(self: &Arc<Self>, max_wait: Duration) -> Result<AdmissionPermit, SolverError> {
+        self.acquire_with_priority(max_wait, SolvePriority::Normal)
+    }
+
+    pub fn acquire_with_priority(
+        self: &Arc<Self>,
+        max_wait: Duration,
+        priority: SolvePriority,
+    ) -> Result<AdmissionPermit, SolverError> {
+        let started = Instant::now();
+        let mut state = self.lock();
+        if state.closed {
+            return Err(SolverError::ShuttingDown);
+        }
+        let ahead = state
+            .waiting
+            .first()
+            .is_some_and(|(Reverse(waiting), _)| *waiting >= priority);
+        if state.running < self.limits.max_concurrent && !ahead {
+            state.running += 1;
+            return Ok(self.permit(started));
+        }
+        if state.waiting.len() >= self.limits.max_queued {
+            let lowest = state.waiting.last().copied();
+            match lowest {
+                Some(lowest) if lowest.0 .0 < priority => {
+                    state.waiting.remove(&lowest);
+                    state.evicted.insert(lowest);
+                    self.released.notify_all();
+                }
+                _ => {
+                    return Err(SolverError::QueueFull {
+                        limit: self.limits.max_queued,
+                    })
+                }
+            }
+        }
+
+        let ticket = (Reverse(priority), state.next_ticket);
+        state.next_ticket += 1;
+        state.waiting.insert(ticket);
+        let deadline = started + max_wait;
+        loop {
+            if state.evicted.remove(&ticket) {
+                return Err(SolverError::QueueFull {
+                    limit: self.limits.max_queued,
+                });
+            }
+            if state.closed {
+                state.waiting.remove(&ticket);
+                return Err(SolverError::ShuttingDown);
+            }
+            if state.running < self.limits.max_concurrent && state.waiting.first() == Some(&ticket)
+            {
+                break;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                state.waiting.remove(&ticket);
+                // The next solve in line may be able to start now that this one is gone.
+                self.released.notify_all();
+                return Err(SolverError::QueueTimeout {
+                    waited_ms: started.elapsed().as_millis() as u64,
+                });
+            }
+            state = match self.released.wait_timeout(state, deadline - now) {
+                Ok((state, _)) => state,
+                Err(poisoned) => poisoned.into_inner().0,
+            };
+        }
+        state.waiting.remove(&ticket);
+        state.running += 1;
+        // Wakes the next in line in case more than one slot is free.
+        self.released.notify_all();
+        Ok(self.permit(started))
+    }
+
+    // Turns away new and queued solves; running ones keep their permits until they finish.
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.released.notify_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    fn permit(self: &Arc<Self>, started: Instant) -> AdmissionPermit {
+        AdmissionPermit {
+            control: self.clone(),
+            waited: started.elapsed(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, AdmissionState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl AdmissionPermit {
+    // Time spent queued; callers take it out of the request budget.
+    pub fn waited(&self) -> Duration {
+        self.waited
+    }
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        let mut state = self.control.lock();
+        state.running = state.running.saturating_sub(1);
+        drop(state);
+        // Queued solves and a draining shutdown both wait on this.
+        self.control.released.notify_all();
+    }
+}
@@ -0,0 +1,65 @@
+// Highest core count a CPU set covers, matching the C library's cpu_set_t.
+pub const MAX_CORES: usize = 1024;
+
+// Restricts the calling thread, and the threads it spawns from then on, to `cores`. An empty
+// list leaves the thread as it is. False when the set can't be applied: a core out of range or
+// outside the process's allowance, or a platform without thread affinity.
+pub fn pin_current_thread(cores: &[usize]) -> bool {
+    if cores.is_empty() {
+        return true;
+    }
+    if cores.iter().any(|core| *core >= MAX_CORES) {
+        return false;
+    }
+    sys::set_affinity(cores)
+}
+
+// Cores the calling thread may run on; empty where the platform can't tell.
+pub fn allowed_cores() -> Vec<usize> {
+    sys::affinity()
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use super::MAX_CORES;
+
+    type CpuSet = [u64; MAX_CORES / 64];
+
+    extern "C" {
+        fn sched_setaffinity(pid: i32, size: usize, set: *const u64) -> i32;
+        fn sched_getaffinity(pid: i32, size: usize, set: *mut u64) -> i32;
+    }
+
+    pub fn set_affinity(cores: &[usize]) -> bool {
+        let mut set: CpuSet = [0; MAX_CORES / 64];
+        for core in cores {
+            set[core / 64] |= 1 << (core % 64);
+        }
+        // SAFETY: the set is a live buffer of exactly the size passed; pid 0 is this thread.
+        unsafe { sched_setaffinity(0, std::mem::size_of::<CpuSet>(), set.as_ptr()) == 0 }
+    }
+
+    pub fn affinity() -> Vec<usize> {
+        let mut set: CpuSet = [0; MAX_CORES / 64];
+        // SAFETY: as above, and the kernel writes no more than the size passed.
+        let status =
+            unsafe { sched_getaffinity(0, std::mem::size_of::<CpuSet>(), set.as_mut_ptr()) };
+        if status != 0 {
+            return Vec::new();
+        }
+        (0..MAX_CORES)
+            .filter(|core| set[core / 64] & (1 << (core % 64)) != 0)
+            .collect()
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    pub fn set_affinity(_cores: &[usize]) -> bool {
+        false
+    }
+
+    pub fn affinity() -> Vec<usize> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,267 @@
+use crate::config::ArchiveConfig;
+use crate::metrics::SolverMetrics;
+use crate::persist::SolveSnapshot;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+#[cfg(feature = "archive")]
+pub mod object;
+
+const UPLOAD_ATTEMPTS: u32 = 3;
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Io(std::io::Error),
+    // The backend isn't known, or this build doesn't include it.
+    Unsupported(String),
+    Backend(String),
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::Io(error) => write!(f, "archive I/O failed: {}", error),
+            ArchiveError::Unsupported(backend) => {
+                write!(f, "archive backend '{}' is not available", backend)
+            }
+            ArchiveError::Backend(message) => write!(f, "archive backend failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<std::io::Error> for ArchiveError {
+    fn from(error: std::io::Error) -> Self {
+        ArchiveError::Io(error)
+    }
+}
+
+// Somewhere to keep solved trees, addressed by key. Calls block until the object is stored or
+// read; the archive makes them from its own thread, never from a solve.
+pub trait StrategyStore: Send + Sync {
+    fn describe(&self) -> String;
+    fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), ArchiveError>;
+    fn get(&self, key: &str) -> Result<Vec<u8>, ArchiveError>;
+}
+
+// Keys as relative paths under a local directory.
+pub struct FileStore {
+    root: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl StrategyStore for FileStore {
+    fn describe(&self) -> String {
+        format!("file://{}", self.root.display())
+    }
+
+    fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), ArchiveError> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Written aside and renamed, so a reader never sees half an object.
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, bytes)?;
+        std::fs::rename(&partial, &path)?;
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>, ArchiveError> {
+        Ok(std::fs::read(self.root.join(key))?)
+    }
+}
+
+// The store `config` selects; None when archiving is off.
+pub fn open_store(config: &ArchiveConfig) -> Result<Option<Box<dyn StrategyStore>>, ArchiveError> {
+    match config.backend.as_str() {
+        "" => Ok(None),
+        "file" => Ok(Some(Box::new(FileStore::new(&config.location)))),
+        #[cfg(feature = "archive")]
+        "s3" => Ok(Some(Box::new(object::ObjectStoreBackend::s3(
+            &config.location,
+        )?))),
+        #[cfg(feature = "archive")]
+        "gcs" => Ok(Some(Box::new(object::ObjectStoreBackend::gcs(
+            &config.location,
+        )?))),
+        other => Err(ArchiveError::Unsupported(other.to_string())),
+    }
+}
+
+struct Upload {
+    key: String,
+    snapshot: Box<SolveSnapshot>,
+}
+
+// Uploads solved trees in the background: a solve hands its snapshot over and returns at once,
+// and a worker thread encodes and stores it, so archiving never spends a solve's time budget.
+// Snapshots that arrive while the queue is full are dropped rather than waited for.
+pub struct StrategyArchive {
+    store: Arc<dyn StrategyStore>,
+    sender: SyncSender<Upload>,
+    prefix: String,
+    min_nodes: usize,
+    pending: Arc<Pending>,
+}
+
+#[derive(Default)]
+struct Pending {
+    count: Mutex<usize>,
+    drained: Condvar,
+}
+
+impl Pending {
+    fn lock(&self) -> MutexGuard<'_, usize> {
+        self.count
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn done(&self) {
+        let mut count = self.lock();
+        *count -= 1;
+        if *count == 0 {
+            self.drained.notify_all();
+        }
+    }
+}
+
+impl StrategyArchive {
+    pub fn start(
+        store: Box<dyn StrategyStore>,
+        config: &ArchiveConfig,
+        metrics: Arc<SolverMetrics>,
+    ) -> Self {
+        let store: Arc<dyn StrategyStore> = Arc::from(store);
+        let (sender, receiver) = mpsc::sync_channel(config.queue.max(1));
+        let pending = Arc::new(Pending::default());
+        let worker_store = store.clone();
+        let worker_pending = pending.clone();
+        thread::Builder::new()
+            .name("strategy-archive".to_string())
+            .spawn(move || upload_all(receiver, &*worker_store, &worker_pending, &metrics))
+            .expect("archive thread spawns");
+        Self {
+            store,
+            sender,
+            prefix: config.prefix.clone(),
+            min_nodes: config.min_nodes,
+            pending,
+        }
+    }
+
+    pub fn store(&self) -> &dyn StrategyStore {
+        &*self.store
+    }
+
+    // Whether a solve of a tree this size should keep its snapshot for the archive.
+    pub fn wants(&self, nodes: usize) -> bool {
+        nodes >= self.min_nodes
+    }
+
+    // Queues `snapshot` and returns the key it will be stored under, or None if it was dropped.
+    pub fn submit(&self, snapshot: Box<SolveSnapshot>) -> Option<String> {
+        let key = archive_key(
+            &self.prefix,
+            &snapshot.meta.state_fingerprint,
+            snapshot.meta.seed,
+        );
+        *self.pending.lock() += 1;
+        match self.sender.try_send(Upload {
+            key: key.clone(),
+            snapshot,
+        }) {
+            Ok(()) => Some(key),
+            Err(_) => {
+                self.pending.done();
+                warn!(key = %key, "archive queue full; solve not archived");
+                None
+            }
+        }
+    }
+
+    // Waits up to `timeout` for queued uploads to finish; false if some are still going.
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut count = self.pending.lock();
+        while *count > 0 {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return false;
+            }
+            count = self
+                .pending
+                .drained
+                .wait_timeout(count, left)
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .0;
+        }
+        true
+    }
+}
+
+fn upload_all(
+    receiver: Receiver<Upload>,
+    store: &dyn StrategyStore,
+    pending: &Pending,
+    metrics: &SolverMetrics,
+) {
+    for upload in receiver {
+        let bytes = upload.snapshot.encode();
+        let mut attempt = 1;
+        let stored = loop {
+            match store.put(&upload.key, bytes.clone()) {
+                Ok(()) => break true,
+                Err(error) if attempt < UPLOAD_ATTEMPTS => {
+                    warn!(key = %upload.key, attempt, "archive upload failed, retrying: {}", error);
+                    thread::sleep(RETRY_BACKOFF * attempt);
+                    attempt += 1;
+                }
+                Err(error) => {
+                    warn!(key = %upload.key, "archive upload failed: {}", error);
+                    break false;
+                }
+            }
+        };
+        metrics.record_archive(stored);
+        pending.done();
+    }
+}
+
+// `<prefix><fingerprint>/<unix ms>-<seed>.slvs`, with anything but letters, digits, `-` and `_`
+// in the fingerprint replaced so it stays one path segment.
+pub fn archive_key(prefix: &str, state_fingerprint: &str, seed: u64) -> String {
+    let spot: String = state_fingerprint
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let spot = if spot.is_empty() {
+        "unnamed".to_string()
+    } else {
+        spot
+    };
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or(0);
+    format!("{}{}/{}-{:016x}.slvs", prefix, spot, now_ms, seed)
+}
@@ -0,0 +1,69 @@
+use super::{ArchiveError, StrategyStore};
+use object_store::aws::AmazonS3Builder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use tokio::runtime::Runtime;
+
+// S3 or GCS through `object_store`, with credentials and region taken from each cloud's standard
+// environment variables. The client is async, so the store carries a runtime of its own to
+// block on from the archive thread.
+pub struct ObjectStoreBackend {
+    store: Box<dyn ObjectStore>,
+    runtime: Runtime,
+    url: String,
+}
+
+impl ObjectStoreBackend {
+    pub fn s3(bucket: &str) -> Result<Self, ArchiveError> {
+        let store = AmazonS3Builder::from_env()
+            .with_bucket_name(bucket)
+            .build()
+            .map_err(backend_error)?;
+        Self::new(Box::new(store), format!("s3://{}", bucket))
+    }
+
+    pub fn gcs(bucket: &str) -> Result<Self, ArchiveError> {
+        let store = GoogleCloudStorageBuilder::from_env()
+            .with_bucket_name(bucket)
+            .build()
+            .map_err(backend_error)?;
+        Self::new(Box::new(store), format!("gs://{}", bucket))
+    }
+
+    fn new(store: Box<dyn ObjectStore>, url: String) -> Result<Self, ArchiveError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            store,
+            runtime,
+            url,
+        })
+    }
+}
+
+impl StrategyStore for ObjectStoreBackend {
+    fn describe(&self) -> String {
+        self.url.clone()
+    }
+
+    fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), ArchiveError> {
+        self.runtime
+            .block_on(self.store.put(&Path::from(key), bytes.into()))
+            .map_err(backend_error)?;
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>, ArchiveError> {
+        let bytes = self
+            .runtime
+            .block_on(async { self.store.get(&Path::from(key)).await?.bytes().await })
+            .map_err(backend_error)?;
+        Ok(bytes.to_vec())
+    }
+}
+
+fn backend_error(error: object_store::Error) -> ArchiveError {
+    ArchiveError::Backend(error.to_string())
+}
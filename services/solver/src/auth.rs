@@ -0,0 +1,163 @@
+use crate::config::ApiKeyConfig;
+use std::sync::Mutex;
+use std::time::Instant;
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Status};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AuthError {
+    MissingCredentials,
+    UnknownKey,
+    RateLimited { key: String },
+}
+
+impl AuthError {
+    pub fn to_status(&self) -> Status {
+        match self {
+            AuthError::MissingCredentials => {
+                Status::unauthenticated("missing API key; send `authorization: Bearer <key>`")
+            }
+            AuthError::UnknownKey => Status::unauthenticated("API key is not recognised"),
+            AuthError::RateLimited { key } => {
+                Status::resource_exhausted(format!("rate limit exceeded for key '{}'", key))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate_per_sec: f64,
+    burst: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate_per_sec: f64, burst: f64, now: Instant) -> Self {
+        let burst = burst.max(1.0);
+        Self {
+            rate_per_sec: rate_per_sec.max(0.0),
+            burst,
+            tokens: burst,
+            refilled_at: now,
+        }
+    }
+
+    pub fn try_take(&mut self, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate_per_sec).min(self.burst);
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+struct KeyEntry {
+    name: String,
+    secret: String,
+    // None means the key is not rate limited.
+    bucket: Option<Mutex<TokenBucket>>,
+}
+
+// With no keys configured every caller is let through, which keeps local and test setups
+// working unchanged.
+pub struct ApiKeyAuth {
+    keys: Vec<KeyEntry>,
+}
+
+impl ApiKeyAuth {
+    pub fn new(keys: &[ApiKeyConfig]) -> Self {
+        let now = Instant::now();
+        let keys = keys
+            .iter()
+            .filter(|key| !key.key.is_empty())
+            .map(|key| KeyEntry {
+                name: key.name.clone(),
+                secret: key.key.clone(),
+                bucket: (key.rate_per_sec > 0.0).then(|| {
+                    let burst = if key.burst > 0 {
+                        key.burst as f64
+                    } else {
+                        key.rate_per_sec
+                    };
+                    Mutex::new(TokenBucket::new(key.rate_per_sec, burst, now))
+                }),
+            })
+            .collect();
+        Self { keys }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    // Returns the name of the key that authorised the call.
+    pub fn authorize(&self, token: Option<&str>, now: Instant) -> Result<Option<&str>, AuthError> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+        let token = token
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .ok_or(AuthError::MissingCredentials)?;
+        // Compare against every key so the response time does not reveal which one matched.
+        let mut matched = None;
+        for entry in &self.keys {
+            if constant_time_eq(entry.secret.as_bytes(), token.as_bytes()) {
+                matched = Some(entry);
+            }
+        }
+        let entry = matched.ok_or(AuthError::UnknownKey)?;
+        if let Some(bucket) = &entry.bucket {
+            let mut bucket = bucket
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if !bucket.try_take(now) {
+                return Err(AuthError::RateLimited {
+                    key: entry.name.clone(),
+                });
+            }
+        }
+        Ok(Some(entry.name.as_str()))
+    }
+
+    pub fn intercept(&self, request: Request<()>) -> Result<Request<()>, Status> {
+        let token = bearer_token(request.metadata());
+        self.authorize(token.as_deref(), Instant::now())
+            .map_err(|error| error.to_status())?;
+        Ok(request)
+    }
+}
+
+// Accepts `authorization: Bearer <key>` or a bare `x-api-key` header.
+pub fn bearer_token(metadata: &MetadataMap) -> Option<String> {
+    if let Some(value) = metadata
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+    {
+        let value = value.trim();
+        let token = value
+            .strip_prefix("Bearer ")
+            .or_else(|| value.strip_prefix("bearer "))
+            .unwrap_or(value);
+        return Some(token.to_string());
+    }
+    metadata
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
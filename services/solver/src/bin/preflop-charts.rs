@@ -0,0 +1,113 @@
+use solver::cfr::CfrConfig;
+use solver::config::SolverConfig;
+use solver::game_tree::preflop::Position;
+use solver::preflop::{solve_charts, ChartConfig};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: preflop-charts --output <dir> [--positions UTG,HJ,CO,BTN,SB] [--stacks 20,40,100] [--iterations <n>] [--buckets <n>] [--runouts <n>] [--seed <n>] [--config <path>]";
+
+struct Args {
+    output: PathBuf,
+    config: Option<PathBuf>,
+    charts: ChartConfig,
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(mut raw: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut output = None;
+    let mut config = None;
+    let mut charts = ChartConfig::default();
+    while let Some(arg) = raw.next() {
+        let mut value = || raw.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "-o" | "--output" => output = Some(PathBuf::from(value()?)),
+            "--config" => config = Some(PathBuf::from(value()?)),
+            "--positions" => {
+                charts.positions = value()?
+                    .split(',')
+                    .map(|name| Position::parse(name).ok_or(format!("unknown position '{}'", name)))
+                    .collect::<Result<_, _>>()?
+            }
+            "--stacks" => {
+                charts.stacks_bb = value()?
+                    .split(',')
+                    .map(|stack| match stack.trim().parse::<f64>() {
+                        Ok(stack) if stack >= 1.0 && stack.is_finite() => Ok(stack),
+                        _ => Err(format!("invalid stack depth '{}'", stack)),
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            "--iterations" => charts.iterations = number(&arg, &value()?)?,
+            "--buckets" => charts.buckets = number(&arg, &value()?)?,
+            "--runouts" => charts.runouts = number(&arg, &value()?)?,
+            "--seed" => {
+                let seed = value()?;
+                charts.seed = seed
+                    .parse()
+                    .map_err(|_| format!("invalid seed '{}'", seed))?
+            }
+            "-h" | "--help" => return Err(String::new()),
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    Ok(Args {
+        output: output.ok_or("missing output directory")?,
+        config,
+        charts,
+    })
+}
+
+fn number(flag: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse::<usize>()
+        .ok()
+        .filter(|number| *number > 0)
+        .ok_or(format!("{} needs a positive number, got '{}'", flag, value))
+}
+
+// Solves one tree at a time and writes each position and stack's charts as soon as they're
+// done, so a long run leaves what it finished.
+fn run(args: &Args) -> Result<(), String> {
+    let config = SolverConfig::load(args.config.as_deref()).map_err(|error| error.to_string())?;
+    let mut cfr = CfrConfig::new(args.charts.iterations);
+    cfr.variant = config.variant().unwrap_or_default();
+    cfr.backend = config.backend().unwrap_or_default();
+    cfr.pruning.enabled = config.cfr.pruning;
+    cfr.sampling_baseline = config.cfr.sampling_baseline;
+
+    std::fs::create_dir_all(&args.output)
+        .map_err(|error| format!("cannot create {}: {}", args.output.display(), error))?;
+    for &position in &args.charts.positions {
+        for &stack_bb in &args.charts.stacks_bb {
+            let file = solve_charts(position, stack_bb, &args.charts, &cfr);
+            let path = args.output.join(file.file_name());
+            let json = serde_json::to_string_pretty(&file).map_err(|error| error.to_string())?;
+            std::fs::write(&path, json + "\n")
+                .map_err(|error| format!("cannot write {}: {}", path.display(), error))?;
+            eprintln!(
+                "{}: {} iterations, exploitability {:.4}",
+                path.display(),
+                file.iterations,
+                file.exploitability
+            );
+        }
+    }
+    Ok(())
+}
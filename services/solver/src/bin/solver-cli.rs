@@ -0,0 +1,109 @@
+use solver::config::SolverConfig;
+use solver::metrics::SolverMetrics;
+use solver::solver::SolverEngine;
+use solver::solver_proto::SubgameRequest;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+const USAGE: &str = "usage: solver-cli <request.json|-> [--output <path>] [--config <path>] [--validate] [--pretty]";
+
+struct Args {
+    input: String,
+    output: Option<PathBuf>,
+    config: Option<PathBuf>,
+    validate_only: bool,
+    pretty: bool,
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(mut raw: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut input = None;
+    let mut output = None;
+    let mut config = None;
+    let mut validate_only = false;
+    let mut pretty = false;
+    while let Some(arg) = raw.next() {
+        match arg.as_str() {
+            "-o" | "--output" => output = raw.next().map(PathBuf::from),
+            "--config" => config = raw.next().map(PathBuf::from),
+            "--validate" => validate_only = true,
+            "--pretty" => pretty = true,
+            "-h" | "--help" => return Err(String::new()),
+            _ if input.is_none() => input = Some(arg),
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    Ok(Args {
+        input: input.ok_or("missing request file")?,
+        output,
+        config,
+        validate_only,
+        pretty,
+    })
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let config = SolverConfig::load(args.config.as_deref()).map_err(|error| error.to_string())?;
+    let engine = SolverEngine::from_config(config, Arc::new(SolverMetrics::new()));
+    let request = read_request(&args.input)?;
+
+    let json = if args.validate_only {
+        let report = solver::validation::to_report(&engine.validate(&request));
+        to_json(&report, args.pretty)?
+    } else {
+        let response = engine
+            .solve(&request)
+            .map_err(|error| format!("{}: {}", error.reason(), error))?;
+        to_json(&response, args.pretty)?
+    };
+
+    match &args.output {
+        Some(path) => std::fs::write(path, json + "\n")
+            .map_err(|error| format!("cannot write {}: {}", path.display(), error)),
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            writeln!(stdout, "{}", json).map_err(|error| error.to_string())
+        }
+    }
+}
+
+fn read_request(input: &str) -> Result<SubgameRequest, String> {
+    let text = if input == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|error| format!("cannot read stdin: {}", error))?;
+        text
+    } else {
+        std::fs::read_to_string(input)
+            .map_err(|error| format!("cannot read {}: {}", input, error))?
+    };
+    serde_json::from_str(&text).map_err(|error| format!("invalid request JSON: {}", error))
+}
+
+fn to_json<T: serde::Serialize>(value: &T, pretty: bool) -> Result<String, String> {
+    let json = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    json.map_err(|error| error.to_string())
+}
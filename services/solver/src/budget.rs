@@ -1,16 +1,30 @@
 use std::time::{Duration, Instant};
 
+// A request's time budget split three ways: work aims to finish by the soft deadline, must finish
+// by the hard one, and the slack after that is kept to build and serialize the response.
 pub struct BudgetClock {
     start: Instant,
     budget: Duration,
+    soft: Duration,
+    hard: Duration,
 }
 
 impl BudgetClock {
+    // Both deadlines at the end of the budget, with no slack.
     pub fn new(budget_ms: i32) -> Self {
-        let budget_ms = budget_ms.max(0) as u64;
+        Self::with_deadlines(budget_ms, 1.0, 0)
+    }
+
+    // The hard deadline keeps `slack_ms` back from the budget, and the soft one comes after
+    // `soft_share` of the time up to it.
+    pub fn with_deadlines(budget_ms: i32, soft_share: f64, slack_ms: u64) -> Self {
+        let budget = Duration::from_millis(budget_ms.max(0) as u64);
+        let hard = budget.saturating_sub(Duration::from_millis(slack_ms));
         Self {
             start: Instant::now(),
-            budget: Duration::from_millis(budget_ms),
+            budget,
+            soft: hard.mul_f64(soft_share.clamp(0.0, 1.0)),
+            hard,
         }
     }
 
@@ -22,6 +36,21 @@ impl BudgetClock {
         self.elapsed().as_millis() as u64
     }
 
+    // The hard deadline; None when there is no time budget.
+    pub fn deadline(&self) -> Option<Instant> {
+        (!self.budget.is_zero()).then_some(self.start + self.hard)
+    }
+
+    pub fn soft_deadline(&self) -> Option<Instant> {
+        (!self.budget.is_zero()).then_some(self.start + self.soft)
+    }
+
+    // Past the soft deadline, where timed solves stop; false without a time budget.
+    pub fn out_of_time(&self) -> bool {
+        self.soft_deadline()
+            .is_some_and(|soft| Instant::now() >= soft)
+    }
+
     pub fn remaining_millis(&self) -> u64 {
         if self.budget.is_zero() {
             return 0;
@@ -38,3 +67,37 @@ impl BudgetClock {
         self.budget.is_zero() || self.elapsed() >= self.budget
     }
 }
+
+// Value of a `grpc-timeout` header: an integer of at most 8 digits and a unit, e.g. "250m".
+pub fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    let unit = value.chars().last()?;
+    let amount = &value[..value.len() - unit.len_utf8()];
+    if amount.is_empty() || amount.len() > 8 || !amount.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let amount: u64 = amount.parse().ok()?;
+    match unit {
+        'H' => Some(Duration::from_secs(amount * 3600)),
+        'M' => Some(Duration::from_secs(amount * 60)),
+        'S' => Some(Duration::from_secs(amount)),
+        'm' => Some(Duration::from_millis(amount)),
+        'u' => Some(Duration::from_micros(amount)),
+        'n' => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+// The budget a request can use when its caller gives up after `timeout`, keeping `slack` to
+// serialize and send the response. An unset budget (0) takes all of it; a negative one is left
+// for validation to reject.
+pub fn clamp_to_deadline(budget_ms: i32, timeout: Duration, slack: Duration) -> i32 {
+    let available = timeout
+        .saturating_sub(slack)
+        .as_millis()
+        .min(i32::MAX as u128) as i32;
+    if budget_ms == 0 {
+        available
+    } else {
+        budget_ms.min(available)
+    }
+}
@@ -0,0 +1,232 @@
+use crate::persist::PersistError;
+use crate::solver_proto::{SubgameRequest, SubgameResponse};
+use prost::Message;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+// Saved caches: magic "SLVC" | version u16 | each entry's request and response, length-delimited
+// protobuf, least recently used first.
+const SAVED_MAGIC: &[u8; 4] = b"SLVC";
+const SAVED_VERSION: u16 = 1;
+
+struct Entry {
+    response: SubgameResponse,
+    bytes: u64,
+    last_used: u64,
+    spot: Vec<u8>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<Vec<u8>, Entry>,
+    // The latest solve of each spot whatever its abstraction, for blueprint lookups.
+    blueprints: HashMap<Vec<u8>, Vec<u8>>,
+    // Keys by last use, oldest first.
+    recency: BTreeMap<u64, Vec<u8>>,
+    bytes: u64,
+    clock: u64,
+}
+
+// Solved spots, least recently used evicted first once either limit is passed. Lookups ignore
+// how much effort a request asks for (budgets, priority, target exploitability), so a spot solved
+// ahead of time answers the in-hand request for it whatever its budget.
+pub struct SolveCache {
+    max_entries: usize,
+    max_bytes: u64,
+    state: Mutex<CacheState>,
+}
+
+impl SolveCache {
+    pub fn new(max_entries: usize, max_bytes: u64) -> Self {
+        Self {
+            max_entries,
+            max_bytes,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_entries > 0 && self.max_bytes > 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, request: &SubgameRequest) -> bool {
+        self.lock().entries.contains_key(&cache_key(request))
+    }
+
+    pub fn get(&self, request: &SubgameRequest) -> Option<SubgameResponse> {
+        let key = cache_key(request);
+        self.lock().touch(key)
+    }
+
+    // The latest solve of the same spot, board and betting, under any action abstraction or
+    // solver settings: a coarse answer for when the request can't be solved as asked.
+    pub fn blueprint(&self, request: &SubgameRequest) -> Option<SubgameResponse> {
+        let mut state = self.lock();
+        let key = state.blueprints.get(&spot_key(request))?.clone();
+        state.touch(key)
+    }
+
+    // Keeps whichever solve of the spot ran more iterations.
+    pub fn insert(&self, request: &SubgameRequest, response: &SubgameResponse) {
+        let bytes = response.encoded_len() as u64;
+        if !self.is_enabled() || bytes > self.max_bytes {
+            return;
+        }
+        let key = cache_key(request);
+        let mut state = self.lock();
+        if let Some(existing) = state.entries.get(&key) {
+            if existing.response.iterations > response.iterations {
+                return;
+            }
+        }
+        if let Some(replaced) = state.entries.remove(&key) {
+            state.recency.remove(&replaced.last_used);
+            state.bytes -= replaced.bytes;
+        }
+        while state.entries.len() >= self.max_entries || state.bytes + bytes > self.max_bytes {
+            let Some((_, oldest)) = state.recency.pop_first() else {
+                break;
+            };
+            if let Some(evicted) = state.entries.remove(&oldest) {
+                state.bytes -= evicted.bytes;
+                if state.blueprints.get(&evicted.spot) == Some(&oldest) {
+                    state.blueprints.remove(&evicted.spot);
+                }
+            }
+        }
+        state.clock += 1;
+        let now = state.clock;
+        let spot = spot_key(request);
+        state.recency.insert(now, key.clone());
+        state.blueprints.insert(spot.clone(), key.clone());
+        state.bytes += bytes;
+        state.entries.insert(
+            key,
+            Entry {
+                response: response.clone(),
+                bytes,
+                last_used: now,
+                spot,
+            },
+        );
+    }
+
+    // Writes every entry to `path`, through a temporary file so a crash mid-write leaves the last
+    // save in place. Returns how many entries were written.
+    pub fn save(&self, path: &Path) -> Result<usize, PersistError> {
+        let mut bytes = SAVED_MAGIC.to_vec();
+        bytes.extend_from_slice(&SAVED_VERSION.to_le_bytes());
+        let state = self.lock();
+        for key in state.recency.values() {
+            let Some(entry) = state.entries.get(key) else {
+                continue;
+            };
+            // Keys are encoded requests, so each writes out as it is.
+            prost::encoding::encode_varint(key.len() as u64, &mut bytes);
+            bytes.extend_from_slice(key);
+            entry
+                .response
+                .encode_length_delimited(&mut bytes)
+                .map_err(|_| PersistError::Corrupt("cache entry"))?;
+        }
+        let saved = state.entries.len();
+        drop(state);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, bytes)?;
+        std::fs::rename(&partial, path)?;
+        Ok(saved)
+    }
+
+    // Inserts the entries a `save` wrote, oldest first so the most recently used stay when the
+    // limits are smaller now. A missing file loads nothing. Returns how many entries were read.
+    pub fn load(&self, path: &Path) -> Result<usize, PersistError> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(error) => return Err(error.into()),
+        };
+        let header = SAVED_MAGIC.len() + 2;
+        if bytes.len() < header {
+            return Err(PersistError::Truncated);
+        }
+        if &bytes[..SAVED_MAGIC.len()] != SAVED_MAGIC {
+            return Err(PersistError::BadMagic);
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != SAVED_VERSION {
+            return Err(PersistError::UnsupportedVersion(version));
+        }
+        let mut rest = &bytes[header..];
+        let mut loaded = 0;
+        while !rest.is_empty() {
+            let request = SubgameRequest::decode_length_delimited(&mut rest)
+                .map_err(|_| PersistError::Corrupt("cached request"))?;
+            let response = SubgameResponse::decode_length_delimited(&mut rest)
+                .map_err(|_| PersistError::Corrupt("cached response"))?;
+            self.insert(&request, &response);
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl CacheState {
+    fn touch(&mut self, key: Vec<u8>) -> Option<SubgameResponse> {
+        self.clock += 1;
+        let now = self.clock;
+        let entry = self.entries.get_mut(&key)?;
+        let previous = std::mem::replace(&mut entry.last_used, now);
+        let response = entry.response.clone();
+        self.recency.remove(&previous);
+        self.recency.insert(now, key);
+        Some(response)
+    }
+}
+
+// The request minus the fields that only say how hard to work on it.
+fn cache_key(request: &SubgameRequest) -> Vec<u8> {
+    effort_free(request).encode_to_vec()
+}
+
+// Also without the action abstraction, solver settings and what the response should include.
+fn spot_key(request: &SubgameRequest) -> Vec<u8> {
+    let mut spot = effort_free(request);
+    spot.include_per_hand = false;
+    spot.action_set.clear();
+    spot.street_action_sets = None;
+    spot.tree_template.clear();
+    spot.config = None;
+    spot.encode_to_vec()
+}
+
+// The request minus budgets, priority and target exploitability: what it asks to be solved,
+// without how hard.
+pub fn effort_free(request: &SubgameRequest) -> SubgameRequest {
+    let mut spot = request.clone();
+    spot.budget_ms = 0;
+    spot.budget_iterations = 0;
+    spot.priority.clear();
+    spot.target_exploitability = 0.0;
+    if let Some(config) = spot.config.as_mut() {
+        config.skip_cache = false;
+    }
+    spot
+}
@@ -0,0 +1,153 @@
+use crate::abstraction::{GameVariant, Street};
+use std::fmt;
+
+pub mod flops;
+pub mod ranking;
+pub mod runouts;
+
+const RANKS: &[u8] = b"23456789TJQKA";
+const SUITS: &[u8] = b"cdhs";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Card {
+    // 0 = deuce ... 12 = ace.
+    pub rank: u8,
+    // 0 = clubs, 1 = diamonds, 2 = hearts, 3 = spades.
+    pub suit: u8,
+}
+
+impl Card {
+    pub fn parse(code: &str) -> Result<Self, CardError> {
+        let bytes = code.as_bytes();
+        if bytes.len() != 2 {
+            return Err(CardError::Malformed(code.to_string()));
+        }
+        let rank = RANKS
+            .iter()
+            .position(|r| *r == bytes[0].to_ascii_uppercase())
+            .ok_or_else(|| CardError::Malformed(code.to_string()))?;
+        let suit = SUITS
+            .iter()
+            .position(|s| *s == bytes[1].to_ascii_lowercase())
+            .ok_or_else(|| CardError::Malformed(code.to_string()))?;
+        Ok(Self {
+            rank: rank as u8,
+            suit: suit as u8,
+        })
+    }
+
+    pub fn index(&self) -> usize {
+        self.rank as usize * 4 + self.suit as usize
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            RANKS[self.rank as usize] as char, SUITS[self.suit as usize] as char
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CardError {
+    Malformed(String),
+    Duplicate(String),
+    NotInDeck {
+        card: String,
+        variant: GameVariant,
+    },
+    HoleCardCount(usize),
+    StreetMismatch {
+        street: Street,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CardError::Malformed(code) => write!(f, "malformed card code '{}'", code),
+            CardError::Duplicate(card) => write!(f, "card {} appears more than once", card),
+            CardError::NotInDeck { card, variant } => {
+                write!(f, "card {} is not in the {} deck", card, variant.as_str())
+            }
+            CardError::HoleCardCount(found) => {
+                write!(f, "expected 2 hole cards, found {}", found)
+            }
+            CardError::StreetMismatch {
+                street,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} requires {} board cards, found {}",
+                street.as_str(),
+                expected,
+                found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CardError {}
+
+pub fn board_size(street: Street) -> usize {
+    match street {
+        Street::Preflop => 0,
+        Street::Flop => 3,
+        Street::Turn => 4,
+        Street::River => 5,
+    }
+}
+
+pub fn parse_cards(codes: &[String]) -> Result<Vec<Card>, CardError> {
+    codes.iter().map(|code| Card::parse(code.trim())).collect()
+}
+
+// Parses the board and checks it against the hole cards and the street. The street check is
+// skipped when the street is unknown or no board was sent, which older callers never do.
+pub fn validate_board(
+    board_codes: &[String],
+    hole_codes: &[String],
+    street: Option<Street>,
+) -> Result<Vec<Card>, CardError> {
+    let board = parse_cards(board_codes)?;
+    let hole = parse_cards(hole_codes)?;
+    if !hole.is_empty() && hole.len() != 2 {
+        return Err(CardError::HoleCardCount(hole.len()));
+    }
+
+    let mut seen = [false; 52];
+    for card in hole.iter().chain(board.iter()) {
+        if std::mem::replace(&mut seen[card.index()], true) {
+            return Err(CardError::Duplicate(card.to_string()));
+        }
+    }
+
+    if let Some(street) = street.filter(|_| !board.is_empty()) {
+        let expected = board_size(street);
+        if board.len() != expected {
+            return Err(CardError::StreetMismatch {
+                street,
+                expected,
+                found: board.len(),
+            });
+        }
+    }
+
+    Ok(board)
+}
+
+pub fn check_deck(cards: &[Card], variant: GameVariant) -> Result<(), CardError> {
+    match cards.iter().find(|card| card.rank < variant.lowest_rank()) {
+        Some(card) => Err(CardError::NotInDeck {
+            card: card.to_string(),
+            variant,
+        }),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,108 @@
+use crate::abstraction::GameVariant;
+use crate::cards::Card;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightedBoard {
+    pub cards: Vec<Card>,
+    // Share of all flops this board stands for; weights of a full set sum to 1.
+    pub weight: f64,
+}
+
+// The strategically distinct flops of the variant's deck (1,755 in hold'em): one per class of
+// flops equal up to renaming suits, weighted by how many raw flops fall in the class.
+pub fn canonical_flops(variant: GameVariant) -> Vec<WeightedBoard> {
+    let deck = variant.deck();
+    let mut classes: BTreeMap<Vec<Card>, usize> = BTreeMap::new();
+    for first in 0..deck.len() {
+        for second in first + 1..deck.len() {
+            for third in second + 1..deck.len() {
+                let flop = [deck[first], deck[second], deck[third]];
+                *classes.entry(canonical(&flop)).or_default() += 1;
+            }
+        }
+    }
+    let total: usize = classes.values().sum();
+    // Highest cards first, so neighbouring entries share their top card.
+    let mut flops: Vec<WeightedBoard> = classes
+        .into_iter()
+        .map(|(cards, count)| WeightedBoard {
+            cards,
+            weight: count as f64 / total as f64,
+        })
+        .collect();
+    flops.sort_by(|a, b| b.cards.cmp(&a.cards));
+    flops
+}
+
+// `count` flops standing in for all of them: the canonical list is cut into `count` runs of
+// similar flops and each run is represented by its most frequent flop, carrying the run's weight.
+// Representatives are re-suited to avoid `dead` cards where possible and dropped otherwise.
+pub fn representative_flops(
+    count: usize,
+    dead: &[Card],
+    variant: GameVariant,
+) -> Vec<WeightedBoard> {
+    let flops = canonical_flops(variant);
+    let count = count.clamp(1, flops.len());
+    let mut chosen: Vec<WeightedBoard> = (0..count)
+        .filter_map(|run| {
+            let start = run * flops.len() / count;
+            let end = (run + 1) * flops.len() / count;
+            let members = &flops[start..end];
+            let weight = members.iter().map(|flop| flop.weight).sum();
+            let mut candidates: Vec<&WeightedBoard> = members.iter().collect();
+            candidates.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+            candidates.iter().find_map(|flop| {
+                avoiding(&flop.cards, dead).map(|cards| WeightedBoard { cards, weight })
+            })
+        })
+        .collect();
+    let total: f64 = chosen.iter().map(|flop| flop.weight).sum();
+    if total > 0.0 {
+        for flop in &mut chosen {
+            flop.weight /= total;
+        }
+    }
+    chosen
+}
+
+fn suit_permutations() -> Vec<[u8; 4]> {
+    let mut permutations = Vec::with_capacity(24);
+    for a in 0..4u8 {
+        for b in (0..4u8).filter(|b| *b != a) {
+            for c in (0..4u8).filter(|c| *c != a && *c != b) {
+                let d = 6 - a - b - c;
+                permutations.push([a, b, c, d]);
+            }
+        }
+    }
+    permutations
+}
+
+fn relabel(cards: &[Card], permutation: &[u8; 4]) -> Vec<Card> {
+    let mut relabelled: Vec<Card> = cards
+        .iter()
+        .map(|card| Card {
+            rank: card.rank,
+            suit: permutation[card.suit as usize],
+        })
+        .collect();
+    relabelled.sort_by(|a, b| b.cmp(a));
+    relabelled
+}
+
+fn canonical(cards: &[Card]) -> Vec<Card> {
+    suit_permutations()
+        .iter()
+        .map(|permutation| relabel(cards, permutation))
+        .min()
+        .unwrap_or_default()
+}
+
+fn avoiding(cards: &[Card], dead: &[Card]) -> Option<Vec<Card>> {
+    suit_permutations()
+        .iter()
+        .map(|permutation| relabel(cards, permutation))
+        .find(|relabelled| relabelled.iter().all(|card| !dead.contains(card)))
+}
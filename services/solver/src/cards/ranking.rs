@@ -0,0 +1,124 @@
+use crate::abstraction::GameVariant;
+use crate::cards::Card;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandCategory {
+    HighCard,
+    Pair,
+    TwoPair,
+    Trips,
+    Straight,
+    Flush,
+    FullHouse,
+    Quads,
+    StraightFlush,
+}
+
+impl HandCategory {
+    // Position in the variant's ordering; short deck swaps flushes and full houses, since with
+    // fewer cards per suit flushes are the rarer hand.
+    pub fn strength(&self, variant: GameVariant) -> u8 {
+        match (self, variant) {
+            (HandCategory::Flush, GameVariant::ShortDeck) => HandCategory::FullHouse as u8,
+            (HandCategory::FullHouse, GameVariant::ShortDeck) => HandCategory::Flush as u8,
+            (category, _) => *category as u8,
+        }
+    }
+}
+
+// Compares like the hands it describes: higher is better under the variant it was built for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandRank {
+    pub strength: u8,
+    // Ranks in tie-break order, e.g. the trips rank then the pair rank for a full house.
+    pub ranks: [u8; 5],
+    pub category: HandCategory,
+}
+
+// Best five-card hand out of five to seven cards; None for fewer than five.
+pub fn evaluate(cards: &[Card], variant: GameVariant) -> Option<HandRank> {
+    if cards.len() < 5 {
+        return None;
+    }
+    let mut best: Option<HandRank> = None;
+    let n = cards.len();
+    for a in 0..n {
+        for b in a + 1..n {
+            for c in b + 1..n {
+                for d in c + 1..n {
+                    for e in d + 1..n {
+                        let rank = evaluate_five(
+                            [cards[a], cards[b], cards[c], cards[d], cards[e]],
+                            variant,
+                        );
+                        best = best.max(Some(rank));
+                    }
+                }
+            }
+        }
+    }
+    best
+}
+
+fn evaluate_five(cards: [Card; 5], variant: GameVariant) -> HandRank {
+    let flush = cards.iter().all(|card| card.suit == cards[0].suit);
+
+    let mut counts = [0u8; 13];
+    for card in &cards {
+        counts[card.rank as usize] += 1;
+    }
+    // Ranks by group size, then by rank: quads before kickers, trips before the pair.
+    let mut groups: Vec<(u8, u8)> = (0..13u8)
+        .rev()
+        .filter(|rank| counts[*rank as usize] > 0)
+        .map(|rank| (counts[rank as usize], rank))
+        .collect();
+    groups.sort_by(|a, b| b.cmp(a));
+    let mut ranks = [0u8; 5];
+    for (slot, (_, rank)) in ranks.iter_mut().zip(&groups) {
+        *slot = *rank;
+    }
+
+    let straight = straight_high(&counts, variant);
+    let category = match (
+        straight,
+        flush,
+        groups[0].0,
+        groups.get(1).map(|group| group.0),
+    ) {
+        (Some(_), true, _, _) => HandCategory::StraightFlush,
+        (_, _, 4, _) => HandCategory::Quads,
+        (_, _, 3, Some(2)) => HandCategory::FullHouse,
+        (_, true, _, _) => HandCategory::Flush,
+        (Some(_), _, _, _) => HandCategory::Straight,
+        (_, _, 3, _) => HandCategory::Trips,
+        (_, _, 2, Some(2)) => HandCategory::TwoPair,
+        (_, _, 2, _) => HandCategory::Pair,
+        _ => HandCategory::HighCard,
+    };
+    if let Some(high) = straight {
+        ranks = [high, 0, 0, 0, 0];
+    }
+    HandRank {
+        strength: category.strength(variant),
+        ranks,
+        category,
+    }
+}
+
+// High card of a straight, if the five ranks make one. The ace also plays low, below the
+// variant's lowest rank: A-2-3-4-5 in hold'em, A-6-7-8-9 in short deck.
+fn straight_high(counts: &[u8; 13], variant: GameVariant) -> Option<u8> {
+    let present: Vec<u8> = (0..13u8)
+        .filter(|rank| counts[*rank as usize] == 1)
+        .collect();
+    if present.len() != 5 {
+        return None;
+    }
+    if present[4] - present[0] == 4 {
+        return Some(present[4]);
+    }
+    let low = variant.lowest_rank();
+    let wheel = [low, low + 1, low + 2, low + 3, 12];
+    (present == wheel).then_some(low + 3)
+}
@@ -0,0 +1,49 @@
+use crate::abstraction::GameVariant;
+use crate::cards::Card;
+
+// How the next card changes the board (a river on a turn, or a turn on a flop), most
+// significant first: a card that both pairs the board and brings a third suited card counts as
+// flush-completing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RunoutClass {
+    FlushCompleting,
+    Pairing,
+    Brick,
+}
+
+impl RunoutClass {
+    pub const ALL: [RunoutClass; 3] = [
+        RunoutClass::FlushCompleting,
+        RunoutClass::Pairing,
+        RunoutClass::Brick,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunoutClass::FlushCompleting => "flush",
+            RunoutClass::Pairing => "pairing",
+            RunoutClass::Brick => "brick",
+        }
+    }
+}
+
+// Flush-completing means the card puts at least three cards of its suit on the board.
+pub fn classify_river(turn: &[Card], river: Card) -> RunoutClass {
+    let suited = turn.iter().filter(|card| card.suit == river.suit).count() + 1;
+    if suited >= 3 {
+        RunoutClass::FlushCompleting
+    } else if turn.iter().any(|card| card.rank == river.rank) {
+        RunoutClass::Pairing
+    } else {
+        RunoutClass::Brick
+    }
+}
+
+// Every card of the variant's deck that can still come next, in deck order.
+pub fn river_cards(turn: &[Card], dead: &[Card], variant: GameVariant) -> Vec<Card> {
+    variant
+        .deck()
+        .into_iter()
+        .filter(|card| !turn.contains(card) && !dead.contains(card))
+        .collect()
+}
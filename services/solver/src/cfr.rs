@@ -1,53 +1,1416 @@
-use crate::game_tree::GameTree;
+use crate::abstraction::showdown::ShowdownMatrix;
+use crate::cfr::backend::{
+    CfrBackend, CfrBackendKind, DeviceTraversal, LayoutNode, Pairing, TableView, TraversalLayout,
+};
+use crate::cfr::compressed::CompressedStrategies;
+use crate::cfr::constraints::RootConstraints;
+use crate::cfr::deep::{DeepCfrConfig, DeepCfrSolver};
+use crate::cfr::diagnostics::{root_bet_diagnostics, root_responses, BetDiagnostics, ResponseStat};
+use crate::cfr::locks::NodeLocks;
+use crate::cfr::opponent::OpponentModel;
+use crate::cfr::refine::{RefinementConfig, RefinementStats};
+use crate::cfr::variant::{CfrVariant, CfrVariantKind, Discount};
+use crate::game_tree::chips::ChipTerminal;
+use crate::game_tree::eval::{PlayerContribution, TerminalNode};
+use crate::game_tree::{
+    ActionKind, GameTree, GameTreeAction, NodeKind, TerminalKind, TreeNode, HERO, VILLAIN,
+};
+use crate::persist::SolveSnapshot;
+use crate::progress::SolveProgress;
+use crate::rng::SolverRng;
+use std::mem::size_of;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub mod aivat;
+pub mod backend;
+pub mod benchmark;
+pub mod compressed;
+pub mod constraints;
+pub mod deep;
+pub mod deepening;
+pub mod diagnostics;
+pub mod diff;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod locks;
+pub mod opponent;
+pub mod purify;
+pub mod refine;
+pub mod simd;
+pub mod variant;
+
+pub const DEFAULT_BUCKETS: usize = 16;
+// How far a sampling baseline moves toward each new observation of its action's value.
+const BASELINE_DECAY: f64 = 0.5;
 
 #[derive(Clone, Debug)]
 pub struct ActionStat {
     pub label: String,
     pub amount: f64,
+    pub kind: ActionKind,
     pub frequency: f64,
+    // In BB, relative to the decision point: folding is worth 0.
     pub ev: f64,
+    // Share of the pot the hands taking this action win at showdown against villain's range.
+    pub equity: f64,
     pub regret: f64,
 }
 
-pub fn run_cfr(tree: &GameTree, iterations: usize) -> Vec<ActionStat> {
-    if tree.is_empty() {
-        return Vec::new();
+// Root strategy of one strength bucket of the acting player.
+#[derive(Clone, Debug, Default)]
+pub struct HandStat {
+    pub bucket: usize,
+    // In root action order; `ev` is what this bucket makes taking the action.
+    pub actions: Vec<ActionStat>,
+    // EV of the bucket's mixed strategy, in BB.
+    pub ev: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct PruningConfig {
+    pub enabled: bool,
+    // Actions whose cumulative regret is below minus this value in every reaching bucket are
+    // skipped until the next re-check iteration.
+    pub threshold: f64,
+    pub recheck_interval: usize,
+}
+
+impl Default for PruningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 1.0,
+            recheck_interval: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CfrOutcome {
+    pub actions: Vec<ActionStat>,
+    // Exploitability of the average strategy as a fraction of the pot.
+    pub exploitability: f64,
+    pub converged: bool,
+    pub iterations: usize,
+    // Quantized average strategy at every decision node.
+    pub strategies: CompressedStrategies,
+    pub hands: Vec<HandStat>,
+    pub diagnostics: Vec<BetDiagnostics>,
+    // Villain's reply to each root action.
+    pub responses: Vec<Vec<ResponseStat>>,
+    // See `CfrSolver::constraint_cost`; 0 when the solve had no constraints.
+    pub constraint_cost: f64,
+    // The whole solved tree, regrets included; only when `keep_snapshot` is set.
+    pub snapshot: Option<Box<SolveSnapshot>>,
+    pub memory: SolveMemory,
+    // Only for solves that refined their abstraction as they went.
+    pub refinement: Option<RefinementStats>,
+}
+
+// What a solve held while it ran, counted from its allocations: the tree arena, and the CFR
+// tables (regrets, strategy sums, the current strategy, baselines and showdown weights).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SolveMemory {
+    pub tree_bytes: u64,
+    pub table_bytes: u64,
+    pub nodes: usize,
+    pub info_sets: usize,
+}
+
+impl SolveMemory {
+    // Both live for the whole solve.
+    pub fn peak_bytes(&self) -> u64 {
+        self.tree_bytes + self.table_bytes
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Evaluation {
+    AverageStrategy,
+    BestResponse,
+}
+
+#[derive(Clone, Debug)]
+pub struct WarmStartPrior {
+    // One probability per root action.
+    pub root_strategy: Vec<f64>,
+    pub weight: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct CfrConfig {
+    pub iterations: usize,
+    // Stop here even with iterations left; None runs all of them. No iteration is started that
+    // would run past `deadline` if it took as long as the slowest one so far, and the solve
+    // stops at `soft_deadline` once it is passed.
+    pub deadline: Option<Instant>,
+    pub soft_deadline: Option<Instant>,
+    pub variant: CfrVariantKind,
+    pub backend: CfrBackendKind,
+    pub buckets: usize,
+    pub pruning: PruningConfig,
+    pub warm_start: Option<WarmStartPrior>,
+    // Solve exploitatively against this model of villain instead of for an equilibrium.
+    pub opponent: Option<OpponentModel>,
+    // Strategies pinned by the request; everything else best-responds to them.
+    pub locks: NodeLocks,
+    // Bounds on the root strategy; locks still win for the buckets they pin.
+    pub constraints: RootConstraints,
+    // Stop as soon as exploitability (fraction of pot) drops to this value; 0 disables.
+    pub target_exploitability: f64,
+    pub check_interval: usize,
+    // Seeds every random draw the solver makes, so equal configs give identical strategies.
+    pub seed: u64,
+    // Counts iterations as they run, for callers watching the solve from another thread.
+    pub progress: Option<Arc<SolveProgress>>,
+    // Return a snapshot of the solved tree with the outcome, for archiving.
+    pub keep_snapshot: bool,
+    // Sampling variants correct each sampled value with a learned per-action baseline
+    // (VR-MCCFR); ignored by variants that walk every action.
+    pub sampling_baseline: bool,
+    // Showdowns and folds weighted and scored per bucket pairing, for card removal or equity
+    // realization; None treats buckets as independent, the higher one always winning.
+    pub showdowns: Option<Arc<ShowdownMatrix>>,
+    // Replaces `showdowns` where both players are all-in, settling those pots at equity over the
+    // runouts.
+    pub all_in_showdowns: Option<Arc<ShowdownMatrix>>,
+    // Share of the range in each bucket, weakest first; empty means equally likely buckets.
+    pub bucket_weights: Vec<f64>,
+    // Start from coarse buckets and split the ones whose regrets disagree most while the budget
+    // lasts; the outcome is still reported over `buckets`.
+    pub refinement: Option<RefinementConfig>,
+}
+
+impl CfrConfig {
+    pub fn new(iterations: usize) -> Self {
+        Self {
+            iterations,
+            deadline: None,
+            soft_deadline: None,
+            variant: CfrVariantKind::default(),
+            backend: CfrBackendKind::default(),
+            buckets: DEFAULT_BUCKETS,
+            pruning: PruningConfig::default(),
+            warm_start: None,
+            opponent: None,
+            locks: NodeLocks::default(),
+            constraints: RootConstraints::default(),
+            target_exploitability: 0.0,
+            check_interval: 10,
+            seed: 0,
+            progress: None,
+            keep_snapshot: false,
+            sampling_baseline: true,
+            showdowns: None,
+            all_in_showdowns: None,
+            bucket_weights: Vec::new(),
+            refinement: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum TerminalPayoff {
+    Fold([f64; 2]),
+    Showdown {
+        win: [f64; 2],
+        tie: [f64; 2],
+        lose: [f64; 2],
+    },
+}
+
+// Action-major (slot = action * buckets + bucket) so the per-action kernels run over contiguous
+// buckets.
+#[derive(Clone, Debug, Default)]
+struct InfosetTable {
+    actions: usize,
+    regrets: Vec<f64>,
+    strategy_sum: Vec<f64>,
+    // Predictive variants only: the last regret deltas, and the regrets plus them that the next
+    // strategy is matched against. Empty otherwise.
+    last_deltas: Vec<f64>,
+    predicted: Vec<f64>,
+}
+
+// Range-vs-range CFR over an abstract hand ranking: each player holds one of `buckets` strength
+// buckets, equally likely unless weighted, and the higher bucket wins at showdown.
+pub struct CfrSolver<'a> {
+    tree: &'a GameTree,
+    variant: Box<dyn CfrVariant>,
+    backend: Box<dyn CfrBackend>,
+    // Set when the backend runs whole iterations itself.
+    device: Option<Box<dyn DeviceTraversal>>,
+    buckets: usize,
+    pruning: PruningConfig,
+    opponent: Option<OpponentModel>,
+    locks: NodeLocks,
+    constraints: RootConstraints,
+    payoffs: Vec<Option<TerminalPayoff>>,
+    tables: Vec<InfosetTable>,
+    // Regret-matched strategy per node for the pass in progress, action-major.
+    current: Vec<Vec<f64>>,
+    // VR-MCCFR baselines: per node and traverser, a running estimate of each opponent action's
+    // counterfactual value per unit of reach, action-major. Empty unless the variant samples.
+    baselines: Vec<[Vec<f64>; 2]>,
+    showdowns: Option<Arc<ShowdownMatrix>>,
+    // Per node: the all-in showdown matrix when the node is an all-in showdown.
+    all_in: Vec<Option<Arc<ShowdownMatrix>>>,
+    // Chance of each bucket; both players share it.
+    prior: Vec<f64>,
+    iterations: usize,
+    pruned_branches: u64,
+    rng: SolverRng,
+}
+
+impl<'a> CfrSolver<'a> {
+    pub fn new(tree: &'a GameTree, config: &CfrConfig) -> Self {
+        let mut solver = Self::with_variant(tree, config, config.variant.build());
+        // A device applies the named variants' update rules itself, and plays the plain
+        // regret-matched strategy at every action.
+        let plain = !solver.variant.samples_opponent()
+            && solver.opponent.is_none()
+            && solver.locks.is_empty()
+            && solver.constraints.is_empty();
+        if plain && config.variant != CfrVariantKind::Deep {
+            solver.device = solver.backend.traversal(&solver.layout());
+        }
+        solver
+    }
+
+    // For variants that are not selectable by name; `config.variant` is ignored.
+    pub fn with_variant(
+        tree: &'a GameTree,
+        config: &CfrConfig,
+        variant: Box<dyn CfrVariant>,
+    ) -> Self {
+        let buckets = config.buckets.max(1);
+        let predicted_slots = |actions: usize| {
+            if variant.predicts_regrets() {
+                buckets * actions
+            } else {
+                0
+            }
+        };
+        let payoffs = tree
+            .nodes
+            .iter()
+            .map(|node| match node.kind {
+                NodeKind::Terminal(kind) => Some(terminal_payoff(tree, node.committed, kind)),
+                NodeKind::Decision { .. } => None,
+            })
+            .collect();
+        let tables = tree
+            .nodes
+            .iter()
+            .map(|node| match &node.kind {
+                NodeKind::Decision { children, .. } => InfosetTable {
+                    actions: children.len(),
+                    regrets: vec![0.0; buckets * children.len()],
+                    strategy_sum: vec![0.0; buckets * children.len()],
+                    last_deltas: vec![0.0; predicted_slots(children.len())],
+                    predicted: vec![0.0; predicted_slots(children.len())],
+                },
+                NodeKind::Terminal(_) => InfosetTable::default(),
+            })
+            .collect();
+        let all_in_showdowns = config
+            .all_in_showdowns
+            .clone()
+            .filter(|showdowns| showdowns.buckets == buckets);
+        let all_in = (0..tree.nodes.len())
+            .map(|node| {
+                all_in_showdowns
+                    .clone()
+                    .filter(|_| tree.is_all_in_showdown(node))
+            })
+            .collect();
+        let baselines = if variant.samples_opponent() && config.sampling_baseline {
+            tree.nodes
+                .iter()
+                .map(|node| match &node.kind {
+                    NodeKind::Decision { children, .. } => {
+                        let slots = buckets * children.len();
+                        [vec![0.0; slots], vec![0.0; slots]]
+                    }
+                    NodeKind::Terminal(_) => Default::default(),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            tree,
+            variant,
+            backend: config.backend.select(),
+            device: None,
+            buckets,
+            pruning: config.pruning.clone(),
+            opponent: config.opponent.clone(),
+            locks: config.locks.clone(),
+            constraints: config.constraints.clone(),
+            payoffs,
+            tables,
+            current: Vec::new(),
+            baselines,
+            showdowns: config
+                .showdowns
+                .clone()
+                .filter(|showdowns| showdowns.buckets == buckets),
+            all_in,
+            prior: bucket_prior(&config.bucket_weights, buckets),
+            iterations: 0,
+            pruned_branches: 0,
+            rng: SolverRng::seeded(config.seed),
+        }
+    }
+
+    pub fn tree(&self) -> &GameTree {
+        self.tree
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    pub fn buckets(&self) -> usize {
+        self.buckets
+    }
+
+    pub fn variant(&self) -> &dyn CfrVariant {
+        self.variant.as_ref()
+    }
+
+    pub fn backend(&self) -> &dyn CfrBackend {
+        self.backend.as_ref()
+    }
+
+    // Whether iterations run whole on the backend's device rather than traversing on the CPU.
+    pub fn runs_on_device(&self) -> bool {
+        self.device.is_some()
+    }
+
+    pub fn locks(&self) -> &NodeLocks {
+        &self.locks
+    }
+
+    // EV in BB the root player gives up to the constraints: what each bucket would make
+    // switching to its best root action, the rest of the strategy held fixed, over what it makes
+    // playing the constrained mix. 0 without constraints once the solve has converged.
+    pub fn constraint_cost(&self) -> f64 {
+        self.root_hand_stats()
+            .iter()
+            .zip(&self.prior)
+            .map(|(hand, weight)| {
+                let best = hand
+                    .actions
+                    .iter()
+                    .map(|action| action.ev)
+                    .fold(f64::NEG_INFINITY, f64::max);
+                weight * (best - hand.ev).max(0.0)
+            })
+            .sum()
+    }
+
+    pub fn pruned_branches(&self) -> u64 {
+        self.pruned_branches
+    }
+
+    pub fn memory(&self) -> SolveMemory {
+        let floats = |values: &Vec<f64>| (values.capacity() * size_of::<f64>()) as u64;
+        let nodes = &self.tree.nodes;
+        let tree_bytes = (nodes.capacity() * size_of::<TreeNode>()) as u64
+            + nodes
+                .iter()
+                .map(|node| {
+                    let children = match &node.kind {
+                        NodeKind::Decision { children, .. } => children.capacity(),
+                        NodeKind::Terminal(_) => 0,
+                    };
+                    (node.label.capacity() + children * size_of::<usize>()) as u64
+                })
+                .sum::<u64>();
+        let mut table_bytes = (self.tables.capacity() * size_of::<InfosetTable>()
+            + self.payoffs.capacity() * size_of::<Option<TerminalPayoff>>()
+            + self.current.capacity() * size_of::<Vec<f64>>()
+            + self.baselines.capacity() * size_of::<[Vec<f64>; 2]>())
+            as u64;
+        for table in &self.tables {
+            table_bytes += floats(&table.regrets)
+                + floats(&table.strategy_sum)
+                + floats(&table.last_deltas)
+                + floats(&table.predicted);
+        }
+        table_bytes += self.current.iter().map(floats).sum::<u64>();
+        table_bytes += self.baselines.iter().flatten().map(floats).sum::<u64>();
+        let all_in = self.all_in.iter().flatten().next();
+        for showdowns in self.showdowns.iter().chain(all_in) {
+            table_bytes +=
+                floats(&showdowns.weight) + floats(&showdowns.win) + floats(&showdowns.tie);
+        }
+        SolveMemory {
+            tree_bytes,
+            table_bytes,
+            nodes: nodes.len(),
+            info_sets: self.tables.iter().filter(|table| table.actions > 0).count() * self.buckets,
+        }
+    }
+
+    // Sampling code must draw from here rather than an unseeded source.
+    pub fn rng_mut(&mut self) -> &mut SolverRng {
+        &mut self.rng
+    }
+
+    // Seeds `node` so regret matching starts out playing `prior` in every bucket, as if it had
+    // already been the average strategy for `weight` iterations.
+    pub fn warm_start(&mut self, node: usize, prior: &[f64], weight: f64) {
+        let buckets = self.buckets as f64;
+        let scale = weight.max(0.0) * self.tree.pot_bb.max(1.0) / buckets;
+        let table = &mut self.tables[node];
+        if prior.len() != table.actions || weight <= 0.0 {
+            return;
+        }
+        let prior = normalize(prior);
+        for (action, probability) in prior.iter().enumerate() {
+            let slots = action * self.buckets..(action + 1) * self.buckets;
+            table.regrets[slots.clone()].fill(probability * scale);
+            table.strategy_sum[slots].fill(probability * weight / buckets);
+        }
+    }
+
+    // Carries a solve of the same tree over to a finer abstraction: each bucket starts from the
+    // regrets of the coarser bucket `parents[bucket]` it was split from, and from that bucket's
+    // strategy sums scaled down to its share of the hands. The iteration count carries over so
+    // discounting picks up where it left off.
+    pub fn inherit(&mut self, coarser: &CfrSolver, parents: &[usize]) {
+        let buckets = self.buckets;
+        let coarse = coarser.buckets;
+        if parents.len() != buckets || parents.iter().any(|parent| *parent >= coarse) {
+            return;
+        }
+        let shares: Vec<f64> = parents
+            .iter()
+            .enumerate()
+            .map(|(bucket, &parent)| {
+                let parent_weight = coarser.prior[parent];
+                if parent_weight > 0.0 {
+                    self.prior[bucket] / parent_weight
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let spread = |fine: &mut [f64], coarse_values: &[f64], scale: bool| {
+            if fine.len() != buckets * (coarse_values.len() / coarse.max(1)) {
+                return;
+            }
+            for (slot, value) in fine.iter_mut().enumerate() {
+                let (action, bucket) = (slot / buckets, slot % buckets);
+                let inherited = coarse_values[action * coarse + parents[bucket]];
+                *value = if scale {
+                    inherited * shares[bucket]
+                } else {
+                    inherited
+                };
+            }
+        };
+        for (table, source) in self.tables.iter_mut().zip(&coarser.tables) {
+            if table.actions != source.actions {
+                continue;
+            }
+            spread(&mut table.regrets, &source.regrets, false);
+            spread(&mut table.strategy_sum, &source.strategy_sum, true);
+            spread(&mut table.last_deltas, &source.last_deltas, false);
+            spread(&mut table.predicted, &source.predicted, false);
+        }
+        for (baselines, source) in self.baselines.iter_mut().zip(&coarser.baselines) {
+            for (baseline, source) in baselines.iter_mut().zip(source) {
+                spread(baseline, source, false);
+            }
+        }
+        self.iterations = coarser.iterations;
+    }
+
+    // Carries a solve over to a deeper tree with the same buckets: every node the shallower tree
+    // shares, by the actions leading to it, starts from its regrets and strategy sums there, and
+    // the actions it lacked start from nothing.
+    pub fn extend(&mut self, shallower: &CfrSolver) {
+        if shallower.buckets != self.buckets {
+            return;
+        }
+        let buckets = self.buckets;
+        let matches = self.tree.matching_nodes(shallower.tree);
+        for (node, matched) in matches.into_iter().enumerate() {
+            let Some(theirs) = matched else {
+                continue;
+            };
+            let (
+                NodeKind::Decision { children, .. },
+                NodeKind::Decision {
+                    children: their, ..
+                },
+            ) = (
+                &self.tree.nodes[node].kind,
+                &shallower.tree.nodes[theirs].kind,
+            )
+            else {
+                continue;
+            };
+            let source = &shallower.tables[theirs];
+            let table = &mut self.tables[node];
+            for (action, &child) in children.iter().enumerate() {
+                let label = &self.tree.nodes[child].label;
+                let Some(from) = their
+                    .iter()
+                    .position(|&c| &shallower.tree.nodes[c].label == label)
+                else {
+                    continue;
+                };
+                let (to, from) = (action * buckets..(action + 1) * buckets, from * buckets);
+                let from = from..from + buckets;
+                table.regrets[to.clone()].copy_from_slice(&source.regrets[from.clone()]);
+                table.strategy_sum[to.clone()].copy_from_slice(&source.strategy_sum[from.clone()]);
+                if !table.predicted.is_empty() && !source.predicted.is_empty() {
+                    table.last_deltas[to.clone()]
+                        .copy_from_slice(&source.last_deltas[from.clone()]);
+                    table.predicted[to].copy_from_slice(&source.predicted[from]);
+                }
+            }
+        }
+        self.iterations = shallower.iterations;
+    }
+
+    pub fn run(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            self.iterate();
+        }
+    }
+
+    pub fn iterate(&mut self) {
+        self.iterations += 1;
+        let discount = self.variant.discount(self.iterations);
+        if !discount.is_none() {
+            self.apply_discount(discount);
+        }
+        if let Some(device) = &mut self.device {
+            let weight = self.variant.strategy_weight(self.iterations);
+            let mut tables: Vec<TableView> = self
+                .tables
+                .iter_mut()
+                .map(|table| TableView {
+                    regrets: &mut table.regrets,
+                    strategy_sum: &mut table.strategy_sum,
+                    last_deltas: &mut table.last_deltas,
+                })
+                .collect();
+            if device.iterate(&mut tables, weight) {
+                return;
+            }
+            tracing::warn!("device traversal failed; traversing on the CPU");
+            self.device = None;
+        }
+        let prior = self.prior.clone();
+        let predicts = self.variant.predicts_regrets();
+        for traverser in [HERO, VILLAIN] {
+            if predicts {
+                for table in &mut self.tables {
+                    table.predicted.copy_from_slice(&table.regrets);
+                    simd::add(&mut table.predicted, &table.last_deltas);
+                }
+            }
+            let tables: Vec<(&[f64], usize)> = self
+                .tables
+                .iter()
+                .map(|table| {
+                    let regrets = if predicts {
+                        &table.predicted
+                    } else {
+                        &table.regrets
+                    };
+                    (regrets.as_slice(), table.actions)
+                })
+                .collect();
+            self.current = self.backend.regret_match_all(&tables, self.buckets);
+            self.traverse(0, traverser, &prior, &prior);
+        }
+    }
+
+    // Average strategy at `node`, one row of action probabilities per bucket.
+    pub fn average_strategy(&self, node: usize) -> Vec<Vec<f64>> {
+        let table = &self.tables[node];
+        let locked = self.locks.node(node);
+        if node == 0 && !self.constraints.is_empty() {
+            return self.constrained_root(table);
+        }
+        (0..self.buckets)
+            .map(|bucket| {
+                if let Some(locked) = locked.filter(|locked| locked.is_locked(bucket)) {
+                    return (0..table.actions)
+                        .map(|action| locked.strategy[action * self.buckets + bucket])
+                        .collect();
+                }
+                let row: Vec<f64> = (0..table.actions)
+                    .map(|action| table.strategy_sum[action * self.buckets + bucket])
+                    .collect();
+                normalize(&row)
+            })
+            .collect()
+    }
+
+    // The accumulated root average projected once more, so a warm start or rounding can't leave
+    // it outside the bounds; locked buckets then keep their lock.
+    fn constrained_root(&self, table: &InfosetTable) -> Vec<Vec<f64>> {
+        let buckets = self.buckets;
+        let rows: Vec<Vec<f64>> = (0..buckets)
+            .map(|bucket| {
+                let row: Vec<f64> = (0..table.actions)
+                    .map(|action| table.strategy_sum[action * buckets + bucket])
+                    .collect();
+                normalize(&row)
+            })
+            .collect();
+        let mut strategy: Vec<f64> = (0..table.actions)
+            .flat_map(|action| rows.iter().map(move |row| row[action]))
+            .collect();
+        self.constraints.project(&mut strategy, buckets);
+        self.locks.apply(0, &mut strategy, buckets);
+        (0..buckets)
+            .map(|bucket| {
+                (0..table.actions)
+                    .map(|action| strategy[action * buckets + bucket])
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Replaces the accumulated average strategy at `node` with `rows`, one per bucket.
+    pub fn set_average_strategy(&mut self, node: usize, rows: &[Vec<f64>]) {
+        let buckets = self.buckets;
+        let table = &mut self.tables[node];
+        for (bucket, row) in rows.iter().enumerate().take(buckets) {
+            for (action, probability) in row.iter().enumerate().take(table.actions) {
+                table.strategy_sum[action * buckets + bucket] = *probability;
+            }
+        }
+    }
+
+    // Cumulative regrets at `node`, bucket-major; empty for terminal nodes.
+    pub fn regrets(&self, node: usize) -> Vec<f64> {
+        let table = &self.tables[node];
+        (0..self.buckets)
+            .flat_map(|bucket| {
+                (0..table.actions).map(move |action| table.regrets[action * self.buckets + bucket])
+            })
+            .collect()
+    }
+
+    pub fn root_stats(&self) -> Vec<ActionStat> {
+        range_stats(&self.tree.actions, &self.root_hand_stats())
+    }
+
+    // Per-bucket breakdown of the root strategy, with EVs in BB relative to the root.
+    pub fn root_hand_stats(&self) -> Vec<HandStat> {
+        let NodeKind::Decision { player, children } = &self.tree.nodes[0].kind else {
+            return Vec::new();
+        };
+        let table = &self.tables[0];
+        let strategy = self.average_strategy(0);
+        let iterations = self.iterations.max(1) as f64;
+        let prior = self.prior.clone();
+        let action_values: Vec<Vec<f64>> = children
+            .iter()
+            .map(|&child| self.evaluate(child, *player, &prior, Evaluation::AverageStrategy))
+            .collect();
+        let mut below = 0.0;
+        (0..self.buckets)
+            .map(|bucket| {
+                // Middle of the bucket's slice of the range.
+                let equity = below + prior[bucket] / 2.0;
+                below += prior[bucket];
+                let actions: Vec<ActionStat> = self
+                    .tree
+                    .actions
+                    .iter()
+                    .enumerate()
+                    .map(|(index, action)| ActionStat {
+                        label: action.label.clone(),
+                        amount: action.amount,
+                        kind: action.kind,
+                        frequency: strategy[bucket][index],
+                        ev: action_values[index][bucket],
+                        equity,
+                        regret: table.regrets[index * self.buckets + bucket].max(0.0) / iterations,
+                    })
+                    .collect();
+                let ev = actions
+                    .iter()
+                    .map(|action| action.frequency * action.ev)
+                    .sum();
+                HandStat {
+                    bucket,
+                    actions,
+                    ev,
+                }
+            })
+            .collect()
+    }
+
+    // Sum over both players of how much a best response gains against the average strategy, in
+    // BB. Zero at an exact equilibrium.
+    pub fn nash_conv(&self) -> f64 {
+        [HERO, VILLAIN]
+            .iter()
+            .map(|&player| {
+                self.value(player, Evaluation::BestResponse)
+                    - self.value(player, Evaluation::AverageStrategy)
+            })
+            .sum::<f64>()
+            .max(0.0)
+    }
+
+    // Average gain of a best response as a fraction of the pot.
+    pub fn exploitability(&self) -> f64 {
+        self.nash_conv() / 2.0 / self.tree.pot_bb.max(1.0)
+    }
+
+    pub fn expected_value(&self, player: usize) -> f64 {
+        self.value(player, Evaluation::AverageStrategy)
     }
 
-    let iterations = iterations.max(1) as f64;
-    let count = tree.actions.len() as f64;
-    let base_frequency = 1.0 / count;
+    // Per-bucket EV, in BB, of the player acting at each decision node under the average strategy,
+    // given the node is reached. None for terminal nodes.
+    pub fn node_values(&self) -> Vec<Option<Vec<f64>>> {
+        let prior = self.prior.clone();
+        let mut values = vec![None; self.tree.nodes.len()];
+        self.collect_node_values(0, [prior.clone(), prior], &mut values);
+        values
+    }
 
-    let mut raw_freqs = Vec::with_capacity(tree.actions.len());
-    let mut total = 0.0;
-    for (index, _) in tree.actions.iter().enumerate() {
-        let modulation = 1.0 - (index as f64 * 0.05);
-        let freq = (base_frequency * modulation).max(0.0);
-        total += freq;
-        raw_freqs.push(freq);
+    // Per-bucket EV, in BB, of `player` once `node` is reached, against an opponent whose buckets
+    // got there in proportion to `opp_reach`; both play the average strategy from there on.
+    pub fn values_at(&self, node: usize, player: usize, opp_reach: &[f64]) -> Vec<f64> {
+        let total: f64 = opp_reach.iter().sum();
+        if total <= 0.0 {
+            return vec![0.0; self.buckets];
+        }
+        self.evaluate(node, player, opp_reach, Evaluation::AverageStrategy)
+            .iter()
+            .map(|value| value / total)
+            .collect()
     }
 
-    if total <= f64::EPSILON {
-        total = count;
-        raw_freqs.iter_mut().for_each(|freq| *freq = 1.0);
+    fn collect_node_values(
+        &self,
+        node: usize,
+        reach: [Vec<f64>; 2],
+        values: &mut Vec<Option<Vec<f64>>>,
+    ) {
+        let NodeKind::Decision { player, children } = &self.tree.nodes[node].kind else {
+            return;
+        };
+        let opponent = 1 - player;
+        let total: f64 = reach[opponent].iter().sum();
+        let counterfactual =
+            self.evaluate(node, *player, &reach[opponent], Evaluation::AverageStrategy);
+        values[node] = Some(
+            counterfactual
+                .iter()
+                .map(|value| if total > 0.0 { value / total } else { 0.0 })
+                .collect(),
+        );
+        let strategy = self.average_strategy(node);
+        for (action, &child) in children.iter().enumerate() {
+            let mut child_reach = reach.clone();
+            for (bucket, row) in strategy.iter().enumerate() {
+                child_reach[*player][bucket] *= row[action];
+            }
+            self.collect_node_values(child, child_reach, values);
+        }
     }
 
-    tree.actions
+    fn value(&self, player: usize, evaluation: Evaluation) -> f64 {
+        let prior = self.prior.clone();
+        self.evaluate(0, player, &prior, evaluation)
+            .iter()
+            .zip(&prior)
+            .map(|(value, weight)| value * weight)
+            .sum()
+    }
+
+    fn evaluate(
+        &self,
+        node: usize,
+        player: usize,
+        opp_reach: &[f64],
+        evaluation: Evaluation,
+    ) -> Vec<f64> {
+        let (actor, children) = match &self.tree.nodes[node].kind {
+            NodeKind::Terminal(_) => return self.terminal_values(node, player, opp_reach),
+            NodeKind::Decision { player, children } => (*player, children),
+        };
+        let strategy = self.average_strategy(node);
+
+        if actor != player {
+            let mut values = vec![0.0; self.buckets];
+            for (action, &child) in children.iter().enumerate() {
+                let reach: Vec<f64> = (0..self.buckets)
+                    .map(|bucket| opp_reach[bucket] * strategy[bucket][action])
+                    .collect();
+                let child_values = self.evaluate(child, player, &reach, evaluation);
+                for (value, child_value) in values.iter_mut().zip(child_values) {
+                    *value += child_value;
+                }
+            }
+            return values;
+        }
+
+        let mut values = match evaluation {
+            Evaluation::AverageStrategy => vec![0.0; self.buckets],
+            Evaluation::BestResponse => vec![f64::NEG_INFINITY; self.buckets],
+        };
+        for (action, &child) in children.iter().enumerate() {
+            let child_values = self.evaluate(child, player, opp_reach, evaluation);
+            for bucket in 0..self.buckets {
+                match evaluation {
+                    Evaluation::AverageStrategy => {
+                        values[bucket] += strategy[bucket][action] * child_values[bucket]
+                    }
+                    Evaluation::BestResponse => {
+                        values[bucket] = values[bucket].max(child_values[bucket])
+                    }
+                }
+            }
+        }
+        values
+    }
+
+    // Returns the traverser's counterfactual value for each of their buckets.
+    fn traverse(
+        &mut self,
+        node: usize,
+        traverser: usize,
+        own_reach: &[f64],
+        opp_reach: &[f64],
+    ) -> Vec<f64> {
+        let (player, children) = match &self.tree.nodes[node].kind {
+            NodeKind::Terminal(_) => return self.terminal_values(node, traverser, opp_reach),
+            NodeKind::Decision { player, children } => (*player, children.clone()),
+        };
+        let actions = children.len();
+        let mut strategy = self.current[node].clone();
+        if player != traverser {
+            if let Some(model) = &self.opponent {
+                model.mix(node, &mut strategy);
+            }
+        }
+        if node == 0 {
+            self.constraints.project(&mut strategy, self.buckets);
+        }
+        self.locks.apply(node, &mut strategy, self.buckets);
+
+        if player != traverser && self.variant.samples_opponent() {
+            // Follow one action, scaling the reach by 1/q so the expected value is unchanged.
+            let weights: Vec<f64> = strategy
+                .chunks(self.buckets)
+                .map(|row| row.iter().zip(opp_reach).map(|(p, r)| p * r).sum())
+                .collect();
+            let total: f64 = weights.iter().sum();
+            if total <= 0.0 {
+                return vec![0.0; self.buckets];
+            }
+            let action = self.rng.sample_index(&weights);
+            if !self.baselines.is_empty() {
+                return self.sample_with_baseline(
+                    node, traverser, own_reach, opp_reach, &strategy, &weights, action,
+                );
+            }
+            let mut reach = vec![0.0; self.buckets];
+            simd::scaled_product(
+                &mut reach,
+                opp_reach,
+                &strategy[action * self.buckets..][..self.buckets],
+                total / weights[action],
+            );
+            return self.traverse(children[action], traverser, own_reach, &reach);
+        }
+
+        let mut reach = vec![0.0; self.buckets];
+        if player != traverser {
+            let mut values = vec![0.0; self.buckets];
+            for (row, &child) in strategy.chunks(self.buckets).zip(&children) {
+                simd::scaled_product(&mut reach, opp_reach, row, 1.0);
+                let child_values = self.traverse(child, traverser, own_reach, &reach);
+                simd::add(&mut values, &child_values);
+            }
+            return values;
+        }
+
+        let recheck = self.pruning.recheck_interval.max(1);
+        let may_prune = self.pruning.enabled && self.iterations % recheck != 0;
+        let mut values = vec![0.0; self.buckets];
+        let mut action_values: Vec<Option<Vec<f64>>> = Vec::with_capacity(actions);
+        for (action, &child) in children.iter().enumerate() {
+            if may_prune && self.is_prunable(node, action, own_reach, &strategy) {
+                self.pruned_branches += 1;
+                action_values.push(None);
+                continue;
+            }
+            let row = &strategy[action * self.buckets..][..self.buckets];
+            simd::scaled_product(&mut reach, own_reach, row, 1.0);
+            let child_values = self.traverse(child, traverser, &reach, opp_reach);
+            simd::add_scaled_product(&mut values, row, &child_values, 1.0);
+            action_values.push(Some(child_values));
+        }
+
+        let weight = self.variant.strategy_weight(self.iterations);
+        let buckets = self.buckets;
+        let table = &mut self.tables[node];
+        // Locked buckets neither learn nor accumulate; their average is the lock itself.
+        let locked: Vec<bool> = (0..buckets)
+            .map(|bucket| {
+                self.locks
+                    .node(node)
+                    .is_some_and(|locked| locked.is_locked(bucket))
+            })
+            .collect();
+        let mut learn_reach = own_reach.to_vec();
+        for (reach, locked) in learn_reach.iter_mut().zip(&locked) {
+            if *locked {
+                *reach = 0.0;
+            }
+        }
+        let mut deltas = vec![0.0; buckets];
+        let predicts = !table.last_deltas.is_empty();
+        for (action, child_values) in action_values.iter().enumerate() {
+            let slots = action * buckets..(action + 1) * buckets;
+            let Some(child_values) = child_values else {
+                // A pruned action learned nothing, so nothing is predicted for it either.
+                if predicts {
+                    table.last_deltas[slots].fill(0.0);
+                }
+                continue;
+            };
+            simd::difference(&mut deltas, child_values, &values);
+            for (delta, locked) in deltas.iter_mut().zip(&locked) {
+                if *locked {
+                    *delta = 0.0;
+                }
+            }
+            self.variant
+                .update_regrets(&mut table.regrets[slots.clone()], &deltas);
+            if predicts {
+                table.last_deltas[slots.clone()].copy_from_slice(&deltas);
+            }
+            simd::add_scaled_product(
+                &mut table.strategy_sum[slots.clone()],
+                &learn_reach,
+                &strategy[slots],
+                weight,
+            );
+        }
+        values
+    }
+
+    // The control-variate estimate of VR-MCCFR: every reachable action contributes its baseline,
+    // and the sampled one adds its error against the baseline divided by its sampling
+    // probability. Unbiased whatever the baselines hold, and low-variance once they track the
+    // real values.
+    #[allow(clippy::too_many_arguments)]
+    fn sample_with_baseline(
+        &mut self,
+        node: usize,
+        traverser: usize,
+        own_reach: &[f64],
+        opp_reach: &[f64],
+        strategy: &[f64],
+        weights: &[f64],
+        action: usize,
+    ) -> Vec<f64> {
+        let buckets = self.buckets;
+        let NodeKind::Decision { children, .. } = &self.tree.nodes[node].kind else {
+            return vec![0.0; buckets];
+        };
+        let child = children[action];
+        let total: f64 = weights.iter().sum();
+        let probability = weights[action] / total;
+        let mut reach = vec![0.0; buckets];
+        simd::scaled_product(
+            &mut reach,
+            opp_reach,
+            &strategy[action * buckets..][..buckets],
+            1.0,
+        );
+        let sampled = self.traverse(child, traverser, own_reach, &reach);
+
+        // Baselines are kept per unit of the reach flowing into the action, so they stay put
+        // while the opponent's strategy (and with it each action's share) moves between passes.
+        let baseline = &mut self.baselines[node][traverser];
+        let mut estimate = vec![0.0; buckets];
+        for (row, weight) in baseline.chunks(buckets).zip(weights) {
+            for (estimate, expected) in estimate.iter_mut().zip(row) {
+                *estimate += weight * expected;
+            }
+        }
+        let weight = weights[action];
+        let slots = &mut baseline[action * buckets..][..buckets];
+        for ((estimate, value), expected) in estimate.iter_mut().zip(&sampled).zip(slots.iter_mut())
+        {
+            *estimate += (value - weight * *expected) / probability;
+            *expected += BASELINE_DECAY * (value / weight - *expected);
+        }
+        estimate
+    }
+
+    fn layout(&self) -> TraversalLayout {
+        let nodes = self
+            .tree
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| {
+                let pairing = if self.all_in[index].is_some() {
+                    Pairing::AllIn
+                } else if self.showdowns.is_some() {
+                    Pairing::Showdowns
+                } else {
+                    Pairing::Order
+                };
+                match (&node.kind, self.payoffs[index]) {
+                    (NodeKind::Decision { player, children }, _) => LayoutNode::Decision {
+                        player: *player,
+                        children: children.clone(),
+                    },
+                    (_, Some(TerminalPayoff::Showdown { win, tie, lose })) => {
+                        LayoutNode::Showdown {
+                            win,
+                            tie,
+                            lose,
+                            pairing,
+                        }
+                    }
+                    (_, Some(TerminalPayoff::Fold(net))) => LayoutNode::Fold { net, pairing },
+                    (_, None) => LayoutNode::Fold {
+                        net: [0.0; 2],
+                        pairing,
+                    },
+                }
+            })
+            .collect();
+        TraversalLayout {
+            buckets: self.buckets,
+            prior: self.prior.clone(),
+            nodes,
+            showdowns: self.showdowns.clone(),
+            all_in_showdowns: self.all_in.iter().flatten().next().cloned(),
+            floors_regrets: self.variant.floors_regrets(),
+            predicts_regrets: self.variant.predicts_regrets(),
+        }
+    }
+
+    fn apply_discount(&mut self, discount: Discount) {
+        for table in &mut self.tables {
+            simd::scale_signed(
+                &mut table.regrets,
+                discount.positive_regret,
+                discount.negative_regret,
+            );
+            simd::scale(&mut table.strategy_sum, discount.strategy);
+        }
+    }
+
+    fn is_prunable(&self, node: usize, action: usize, own_reach: &[f64], strategy: &[f64]) -> bool {
+        let table = &self.tables[node];
+        (0..self.buckets)
+            .filter(|&bucket| own_reach[bucket] > 0.0)
+            .all(|bucket| {
+                let slot = action * self.buckets + bucket;
+                strategy[slot] <= 0.0 && table.regrets[slot] < -self.pruning.threshold
+            })
+    }
+
+    fn terminal_values(&self, node: usize, traverser: usize, opp_reach: &[f64]) -> Vec<f64> {
+        if let Some(showdowns) = self.all_in[node].as_ref().or(self.showdowns.as_ref()) {
+            return self.terminal_values_by_pairing(showdowns, node, traverser, opp_reach);
+        }
+        match self.payoffs[node] {
+            Some(TerminalPayoff::Fold(net)) => {
+                let total: f64 = opp_reach.iter().sum();
+                vec![net[traverser] * total; self.buckets]
+            }
+            Some(TerminalPayoff::Showdown { win, tie, lose }) => {
+                let total: f64 = opp_reach.iter().sum();
+                let mut below = 0.0;
+                (0..self.buckets)
+                    .map(|bucket| {
+                        let above = total - below - opp_reach[bucket];
+                        let value = win[traverser] * below
+                            + tie[traverser] * opp_reach[bucket]
+                            + lose[traverser] * above;
+                        below += opp_reach[bucket];
+                        value
+                    })
+                    .collect()
+            }
+            None => vec![0.0; self.buckets],
+        }
+    }
+
+    fn terminal_values_by_pairing(
+        &self,
+        showdowns: &ShowdownMatrix,
+        node: usize,
+        traverser: usize,
+        opp_reach: &[f64],
+    ) -> Vec<f64> {
+        let Some(payoff) = self.payoffs[node] else {
+            return vec![0.0; self.buckets];
+        };
+        (0..self.buckets)
+            .map(|bucket| {
+                opp_reach
+                    .iter()
+                    .enumerate()
+                    .map(|(opposing, reach)| {
+                        let (weight, won, split) = showdowns.outcome(traverser, bucket, opposing);
+                        let value = match payoff {
+                            TerminalPayoff::Fold(net) => net[traverser],
+                            TerminalPayoff::Showdown { win, tie, lose } => {
+                                win[traverser] * won
+                                    + tie[traverser] * split
+                                    + lose[traverser] * (1.0 - won - split)
+                            }
+                        };
+                        reach * weight * value
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+// Root actions over the whole range. EVs are in BB relative to the root and averaged over the
+// hands that take the action; equity likewise.
+pub fn range_stats(actions: &[GameTreeAction], hands: &[HandStat]) -> Vec<ActionStat> {
+    let buckets = hands.len().max(1) as f64;
+    actions
         .iter()
         .enumerate()
         .map(|(index, action)| {
-            let modulation = 1.0 - (index as f64 * 0.05);
-            let normalized_frequency = (raw_freqs[index] / total).clamp(0.0, 1.0);
-            let ev = (tree.effective_stack_bb.max(1.0) / 100.0) * modulation.max(0.1);
-            let regret = ((iterations - 1.0) / iterations) * (0.1 - index as f64 * 0.01).max(0.0);
+            let stats: Vec<&ActionStat> = hands.iter().map(|hand| &hand.actions[index]).collect();
+            let frequency = stats.iter().map(|stat| stat.frequency).sum::<f64>() / buckets;
+            let regret = stats.iter().map(|stat| stat.regret).sum::<f64>() / buckets;
+            // An action nobody takes is valued as if the whole range took it.
+            let weights: Vec<f64> = if frequency > 0.0 {
+                stats.iter().map(|stat| stat.frequency).collect()
+            } else {
+                vec![1.0; stats.len()]
+            };
+            let total: f64 = weights.iter().sum::<f64>().max(f64::EPSILON);
+            let weighted = |value: fn(&ActionStat) -> f64| {
+                stats
+                    .iter()
+                    .zip(&weights)
+                    .map(|(stat, weight)| value(stat) * weight)
+                    .sum::<f64>()
+                    / total
+            };
 
             ActionStat {
                 label: action.label.clone(),
                 amount: action.amount,
-                frequency: normalized_frequency,
-                ev,
+                kind: action.kind,
+                frequency: frequency.clamp(0.0, 1.0),
+                ev: weighted(|stat| stat.ev),
+                equity: weighted(|stat| stat.equity),
                 regret,
             }
         })
         .collect()
 }
+
+pub fn run_cfr(tree: &GameTree, iterations: usize) -> Vec<ActionStat> {
+    run_cfr_with_config(tree, &CfrConfig::new(iterations)).actions
+}
+
+pub fn run_cfr_with_config(tree: &GameTree, config: &CfrConfig) -> CfrOutcome {
+    if let Some(progress) = &config.progress {
+        progress.begin(config.iterations.max(1));
+    }
+    let solved = if tree.is_empty() {
+        CfrOutcome::default()
+    } else {
+        solve_tree(tree, config)
+    };
+    if let Some(progress) = &config.progress {
+        progress.finish();
+    }
+    solved
+}
+
+fn solve_tree(tree: &GameTree, config: &CfrConfig) -> CfrOutcome {
+    if config.variant == CfrVariantKind::Deep {
+        let mut deep =
+            DeepCfrSolver::new(tree, config, DeepCfrConfig::for_budget(config.iterations));
+        deep.run();
+        return outcome(&deep.to_tabular(config), config, false);
+    }
+
+    if let Some(refinement) = config.refinement.filter(|_| refine::applies(config)) {
+        let (solver, converged, stats) = refine::solve_refined(tree, config, &refinement);
+        let mut solved = outcome(&solver, config, converged);
+        solved.refinement = Some(stats);
+        return solved;
+    }
+
+    let mut solver = CfrSolver::new(tree, config);
+    if let Some(prior) = &config.warm_start {
+        solver.warm_start(0, &prior.root_strategy, prior.weight);
+    }
+    let stopped = run_iterations(&mut solver, config, config.iterations.max(1));
+    outcome(&solver, config, stopped == Stopped::Converged)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stopped {
+    // Ran every iteration it was given.
+    Budget,
+    Converged,
+    // Cancelled, or out of time.
+    Interrupted,
+}
+
+fn run_iterations(solver: &mut CfrSolver, config: &CfrConfig, iterations: usize) -> Stopped {
+    let target = config.target_exploitability;
+    let check_interval = config.check_interval.max(1);
+    let mut slowest = Duration::ZERO;
+    for _ in 0..iterations {
+        let started = Instant::now();
+        solver.iterate();
+        slowest = slowest.max(started.elapsed());
+        let watched = config.progress.as_ref();
+        if let Some(progress) = watched {
+            progress.advance();
+            if progress.is_cancelled() {
+                return Stopped::Interrupted;
+            }
+        }
+        let measure =
+            target > 0.0 || watched.is_some_and(|progress| progress.tracks_exploitability());
+        if measure && solver.iterations() % check_interval == 0 {
+            let exploitability = solver.exploitability();
+            if let Some(progress) = watched {
+                progress.record_exploitability(exploitability);
+            }
+            if target > 0.0 && exploitability <= target {
+                return Stopped::Converged;
+            }
+        }
+        let now = Instant::now();
+        if config.soft_deadline.is_some_and(|soft| now >= soft)
+            || config.deadline.is_some_and(|hard| now + slowest >= hard)
+        {
+            return Stopped::Interrupted;
+        }
+    }
+    Stopped::Budget
+}
+
+fn outcome(solver: &CfrSolver, config: &CfrConfig, converged: bool) -> CfrOutcome {
+    let target = config.target_exploitability;
+    let exploitability = solver.exploitability();
+    let strategies = CompressedStrategies::from_solver(solver);
+    let snapshot = config.keep_snapshot.then(|| {
+        Box::new(SolveSnapshot::from_solved(
+            solver,
+            "",
+            config.seed,
+            exploitability,
+            strategies.clone(),
+        ))
+    });
+    CfrOutcome {
+        actions: solver.root_stats(),
+        exploitability,
+        converged: converged || (target > 0.0 && exploitability <= target),
+        iterations: solver.iterations(),
+        strategies,
+        hands: solver.root_hand_stats(),
+        diagnostics: root_bet_diagnostics(solver),
+        responses: root_responses(solver),
+        constraint_cost: if config.constraints.is_empty() {
+            0.0
+        } else {
+            solver.constraint_cost()
+        },
+        snapshot,
+        memory: solver.memory(),
+        refinement: None,
+    }
+}
+
+fn bucket_prior(weights: &[f64], buckets: usize) -> Vec<f64> {
+    let total: f64 = weights.iter().sum();
+    if weights.len() != buckets || total <= 0.0 || weights.iter().any(|weight| *weight < 0.0) {
+        return vec![1.0 / buckets as f64; buckets];
+    }
+    weights.iter().map(|weight| weight / total).collect()
+}
+
+fn terminal_payoff(tree: &GameTree, committed: [f64; 2], kind: TerminalKind) -> TerminalPayoff {
+    let folder = match kind {
+        TerminalKind::Fold { folder } => Some(folder),
+        TerminalKind::Showdown => None,
+    };
+    if let Some(chips) = &tree.chips {
+        let terminal = ChipTerminal::new(committed.map(|bb| chips.to_units(bb)), folder, chips.pot)
+            .with_rake(&tree.rake, tree.preflop, chips.units_per_bb);
+        let net = |winner: Option<usize>| terminal.net(winner).map(|units| chips.to_bb(units));
+        return match folder {
+            Some(_) => TerminalPayoff::Fold(net(None)),
+            None => {
+                let hero_wins = net(Some(HERO));
+                let villain_wins = net(Some(VILLAIN));
+                TerminalPayoff::Showdown {
+                    win: [hero_wins[HERO], villain_wins[VILLAIN]],
+                    tie: net(None),
+                    lose: [villain_wins[HERO], hero_wins[VILLAIN]],
+                }
+            }
+        };
+    }
+    let contributions = (0..2)
+        .map(|seat| PlayerContribution {
+            committed: committed[seat],
+            folded: folder == Some(seat),
+        })
+        .collect();
+    let terminal =
+        TerminalNode::new(contributions, tree.pot_bb).with_rake(&tree.rake, tree.preflop);
+    let net = |strengths: [f64; 2]| {
+        let payouts = terminal.payouts(&strengths);
+        [payouts[HERO], payouts[VILLAIN]]
+    };
+
+    match folder {
+        Some(_) => TerminalPayoff::Fold(net([0.0, 0.0])),
+        None => {
+            let hero_wins = net([1.0, 0.0]);
+            let villain_wins = net([0.0, 1.0]);
+            TerminalPayoff::Showdown {
+                win: [hero_wins[HERO], villain_wins[VILLAIN]],
+                tie: net([0.0, 0.0]),
+                lose: [villain_wins[HERO], hero_wins[VILLAIN]],
+            }
+        }
+    }
+}
+
+fn normalize(weights: &[f64]) -> Vec<f64> {
+    let total: f64 = weights.iter().sum();
+    if total > f64::EPSILON {
+        weights.iter().map(|weight| weight / total).collect()
+    } else {
+        vec![1.0 / weights.len().max(1) as f64; weights.len()]
+    }
+}
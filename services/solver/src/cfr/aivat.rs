@@ -0,0 +1,191 @@
+use crate::cfr::CfrSolver;
+use crate::game_tree::NodeKind;
+use std::fmt;
+
+// A hand the bot played in the solved spot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayedHand {
+    // Action labels from the root to the terminal node the hand ended at.
+    pub line: Vec<String>,
+    // Seat the bot played, HERO or VILLAIN.
+    pub player: usize,
+    pub bucket: usize,
+    // Known when the opponent's cards are shown after every hand, as in competition logs; their
+    // deal is then corrected too. Give it for every hand or none: knowing it only for the hands
+    // that reached showdown would bias the estimate.
+    pub opponent_bucket: Option<usize>,
+    // Net BB the bot won, scored like the tree's terminal payoffs.
+    pub outcome: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum HandError {
+    UnknownAction { label: String },
+    // The line stops at a decision node.
+    Unfinished,
+    BucketOutOfRange { bucket: usize, buckets: usize },
+    BadSeat { player: usize },
+}
+
+impl fmt::Display for HandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandError::UnknownAction { label } => {
+                write!(f, "action '{}' is not in the solved tree", label)
+            }
+            HandError::Unfinished => write!(f, "the line does not reach the end of the hand"),
+            HandError::BucketOutOfRange { bucket, buckets } => {
+                write!(
+                    f,
+                    "bucket {} is out of range for {} buckets",
+                    bucket, buckets
+                )
+            }
+            HandError::BadSeat { player } => write!(f, "player {} is not 0 or 1", player),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HandEstimate {
+    pub outcome: f64,
+    pub estimate: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AivatReport {
+    pub hands: Vec<HandEstimate>,
+    pub mean_outcome: f64,
+    pub outcome_std_error: f64,
+    pub mean_estimate: f64,
+    pub estimate_std_error: f64,
+}
+
+// AIVAT: the outcome of each hand less the luck the solved values can see, so a small sample says
+// as much as a much larger one. The luck is the deal (the hand's value against the average over
+// every deal) and the bot's own mixing at each decision (the value of the action taken against
+// the strategy's mix). Each correction has zero mean while the bot plays the solved strategy, so
+// the estimate stays unbiased; the opponent's choices are left alone since their strategy is
+// unknown, and only narrow the range the values are taken against.
+pub fn evaluate_hands(
+    solver: &CfrSolver,
+    hands: &[PlayedHand],
+) -> Result<AivatReport, (usize, HandError)> {
+    let estimates = hands
+        .iter()
+        .enumerate()
+        .map(|(index, hand)| evaluate_hand(solver, hand).map_err(|error| (index, error)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (mean_outcome, outcome_std_error) =
+        mean_and_error(estimates.iter().map(|hand| hand.outcome));
+    let (mean_estimate, estimate_std_error) =
+        mean_and_error(estimates.iter().map(|hand| hand.estimate));
+    Ok(AivatReport {
+        hands: estimates,
+        mean_outcome,
+        outcome_std_error,
+        mean_estimate,
+        estimate_std_error,
+    })
+}
+
+pub fn evaluate_hand(solver: &CfrSolver, hand: &PlayedHand) -> Result<HandEstimate, HandError> {
+    let buckets = solver.buckets();
+    if hand.player > 1 {
+        return Err(HandError::BadSeat {
+            player: hand.player,
+        });
+    }
+    for bucket in std::iter::once(hand.bucket).chain(hand.opponent_bucket) {
+        if bucket >= buckets {
+            return Err(HandError::BucketOutOfRange { bucket, buckets });
+        }
+    }
+    let tree = solver.tree();
+    let player = hand.player;
+    let bucket = hand.bucket;
+    let prior = vec![1.0 / buckets as f64; buckets];
+    let mut opp_reach = match hand.opponent_bucket {
+        Some(opponent) => {
+            let mut known = vec![0.0; buckets];
+            known[opponent] = 1.0;
+            known
+        }
+        None => prior.clone(),
+    };
+
+    // The deal: this hand's value against the average over every deal.
+    let average = solver.values_at(0, player, &prior).iter().sum::<f64>() / buckets as f64;
+    let mut correction = solver.values_at(0, player, &opp_reach)[bucket] - average;
+
+    let mut node = 0;
+    for label in &hand.line {
+        let NodeKind::Decision {
+            player: actor,
+            children,
+        } = &tree.nodes[node].kind
+        else {
+            return Err(HandError::UnknownAction {
+                label: label.clone(),
+            });
+        };
+        let Some(action) = children
+            .iter()
+            .position(|child| tree.nodes[*child].label == *label)
+        else {
+            return Err(HandError::UnknownAction {
+                label: label.clone(),
+            });
+        };
+        let strategy = solver.average_strategy(node);
+        if *actor == player {
+            let values: Vec<f64> = children
+                .iter()
+                .map(|&child| solver.values_at(child, player, &opp_reach)[bucket])
+                .collect();
+            let expected: f64 = values
+                .iter()
+                .zip(&strategy[bucket])
+                .map(|(value, probability)| value * probability)
+                .sum();
+            correction += values[action] - expected;
+        } else {
+            let narrowed: Vec<f64> = opp_reach
+                .iter()
+                .zip(&strategy)
+                .map(|(reach, row)| reach * row[action])
+                .collect();
+            // An action the solve never takes says nothing about the range; keep the old one.
+            if narrowed.iter().sum::<f64>() > 0.0 {
+                opp_reach = narrowed;
+            }
+        }
+        node = children[action];
+    }
+    if !tree.nodes[node].is_terminal() {
+        return Err(HandError::Unfinished);
+    }
+    Ok(HandEstimate {
+        outcome: hand.outcome,
+        estimate: hand.outcome - correction,
+    })
+}
+
+// Sample mean and its standard error; the error is 0 below two samples.
+fn mean_and_error(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let values: Vec<f64> = values.collect();
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    if values.len() < 2 {
+        return (mean, 0.0);
+    }
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / (count - 1.0);
+    (mean, (variance / count).sqrt())
+}
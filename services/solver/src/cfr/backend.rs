@@ -0,0 +1,152 @@
+use crate::abstraction::showdown::ShowdownMatrix;
+use crate::cfr::simd;
+use std::sync::Arc;
+
+// Where a solve's arithmetic runs. Every backend regret-matches: at the start of each traverser
+// pass the solver asks it for every infoset's current strategy in one batch; a pass visits each
+// node at most once and only updates nodes after leaving them, so this gives the same strategies
+// as matching on visit. A backend can also take over whole iterations, traversal and updates
+// included, for the solves `traversal` accepts.
+pub trait CfrBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // `tables[i]` is (action-major regrets, action count); returns one action-major strategy per
+    // table, empty for tables with no actions.
+    fn regret_match_all(&self, tables: &[(&[f64], usize)], buckets: usize) -> Vec<Vec<f64>>;
+
+    // Runs the iterations of the solve laid out in `layout` on the backend's device; None leaves
+    // the traversal to the solver, which then only asks for regret matching.
+    fn traversal(&self, layout: &TraversalLayout) -> Option<Box<dyn DeviceTraversal>> {
+        let _ = layout;
+        None
+    }
+}
+
+// A solve in the shape a device walks it: every node with its actor and children or its payoffs,
+// the bucket prior, the showdown matrices and the variant's update rules. Only solves that play
+// every action with the plain regret-matched strategy are laid out (no sampling, opponent model,
+// locks or constraints); regret pruning doesn't apply, the device walking every action.
+#[derive(Clone, Debug)]
+pub struct TraversalLayout {
+    pub buckets: usize,
+    pub prior: Vec<f64>,
+    pub nodes: Vec<LayoutNode>,
+    pub showdowns: Option<Arc<ShowdownMatrix>>,
+    pub all_in_showdowns: Option<Arc<ShowdownMatrix>>,
+    pub floors_regrets: bool,
+    pub predicts_regrets: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LayoutNode {
+    Decision {
+        player: usize,
+        children: Vec<usize>,
+    },
+    // What each seat nets at a fold, by seat.
+    Fold {
+        net: [f64; 2],
+        pairing: Pairing,
+    },
+    // What each seat nets winning, tying and losing the showdown, by seat.
+    Showdown {
+        win: [f64; 2],
+        tie: [f64; 2],
+        lose: [f64; 2],
+        pairing: Pairing,
+    },
+}
+
+// How a terminal scores bucket against bucket.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pairing {
+    // Buckets are independent and the higher one wins.
+    Order,
+    // Weighted and scored by `TraversalLayout::showdowns`.
+    Showdowns,
+    // Weighted and scored by `TraversalLayout::all_in_showdowns`.
+    AllIn,
+}
+
+// Whole CFR iterations on a device: for each traverser in turn, regret matching, reach pushed
+// down the tree, terminal values, then values, regrets and strategy sums back up.
+pub trait DeviceTraversal: Send + Sync {
+    // Runs one iteration over `tables`, one per node in node order (empty for terminals), whose
+    // strategies count `weight` in the average. False when the device failed, in which case
+    // the tables are left as they were.
+    fn iterate(&mut self, tables: &mut [TableView<'_>], weight: f64) -> bool;
+}
+
+// One node's action-major tables, as the solver keeps them.
+pub struct TableView<'a> {
+    pub regrets: &'a mut [f64],
+    pub strategy_sum: &'a mut [f64],
+    // Empty unless the variant predicts regrets.
+    pub last_deltas: &'a mut [f64],
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CpuBackend;
+
+impl CfrBackend for CpuBackend {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    fn regret_match_all(&self, tables: &[(&[f64], usize)], buckets: usize) -> Vec<Vec<f64>> {
+        tables
+            .iter()
+            .map(|(regrets, actions)| {
+                let mut strategy = vec![0.0; regrets.len()];
+                simd::regret_match(regrets, *actions, buckets, &mut strategy);
+                strategy
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CfrBackendKind {
+    #[default]
+    Cpu,
+    // Whole iterations on the GPU, every bucket of a tree level at once, for solves that walk
+    // every action with the plain regret-matched strategy; the rest traverse on the CPU with
+    // regret matching on the GPU. Requires the `gpu` feature and an adapter; falls back to the
+    // CPU otherwise.
+    Gpu,
+    // GPU when one is present, else CPU.
+    Auto,
+}
+
+impl CfrBackendKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "cpu" => Some(CfrBackendKind::Cpu),
+            "gpu" => Some(CfrBackendKind::Gpu),
+            "auto" => Some(CfrBackendKind::Auto),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CfrBackendKind::Cpu => "cpu",
+            CfrBackendKind::Gpu => "gpu",
+            CfrBackendKind::Auto => "auto",
+        }
+    }
+
+    pub fn select(&self) -> Box<dyn CfrBackend> {
+        if *self == CfrBackendKind::Cpu {
+            return Box::new(CpuBackend);
+        }
+        #[cfg(feature = "gpu")]
+        if let Some(gpu) = crate::cfr::gpu::GpuBackend::shared() {
+            return Box::new(gpu);
+        }
+        if *self == CfrBackendKind::Gpu {
+            tracing::warn!("GPU backend requested but unavailable; using the CPU");
+        }
+        Box::new(CpuBackend)
+    }
+}
@@ -0,0 +1,95 @@
+use crate::abstraction::{parse_action_set, BlindSummary, GameStateSummary};
+use crate::cfr::{CfrConfig, CfrSolver};
+use crate::game_tree::GameTree;
+use std::time::{Duration, Instant};
+
+// The fixed spots every benchmark solves, smallest first, so runs on different machines compare.
+const SUITE: &[(&str, &str, &[&str])] = &[
+    ("river, 2 sizes", "river", &["pot:0.5", "all-in"]),
+    (
+        "river, 4 sizes",
+        "river",
+        &["pot:0.33", "pot:0.75", "pot:1.5", "all-in"],
+    ),
+    ("turn, 3 sizes", "turn", &["pot:0.5", "pot:1", "all-in"]),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkCase {
+    pub name: String,
+    pub nodes: usize,
+    pub buckets: usize,
+    pub iterations: usize,
+    pub elapsed: Duration,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Throughput {
+    pub iterations_per_sec: f64,
+    // One traversal per player each iteration.
+    pub traversals_per_sec: f64,
+    // Tree nodes a traversal covers, pruned branches included.
+    pub nodes_per_sec: f64,
+}
+
+impl BenchmarkCase {
+    pub fn throughput(&self) -> Throughput {
+        suite_throughput(std::slice::from_ref(self))
+    }
+}
+
+// Over the suite as a whole: every case's work divided by the time they took together.
+pub fn suite_throughput(cases: &[BenchmarkCase]) -> Throughput {
+    let seconds = cases
+        .iter()
+        .map(|case| case.elapsed)
+        .sum::<Duration>()
+        .as_secs_f64()
+        .max(f64::EPSILON);
+    let iterations: usize = cases.iter().map(|case| case.iterations).sum();
+    let nodes: usize = cases.iter().map(|case| case.iterations * case.nodes).sum();
+    Throughput {
+        iterations_per_sec: iterations as f64 / seconds,
+        traversals_per_sec: 2.0 * iterations as f64 / seconds,
+        nodes_per_sec: 2.0 * nodes as f64 / seconds,
+    }
+}
+
+pub fn benchmark_trees() -> Vec<(&'static str, GameTree)> {
+    SUITE
+        .iter()
+        .map(|(name, street, sizes)| {
+            let summary = GameStateSummary {
+                pot: 10.0,
+                street: street.to_string(),
+                blinds: BlindSummary {
+                    big: 1.0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let sizes: Vec<String> = sizes.iter().map(|size| size.to_string()).collect();
+            let specs = parse_action_set(&sizes, &summary, 100.0);
+            (*name, GameTree::from_action_specs(&specs, &summary, 100.0))
+        })
+        .collect()
+}
+
+// Times `config.iterations` iterations of each suite spot with everything else in `config`.
+pub fn run_benchmark(config: &CfrConfig) -> Vec<BenchmarkCase> {
+    benchmark_trees()
+        .into_iter()
+        .map(|(name, tree)| {
+            let mut solver = CfrSolver::new(&tree, config);
+            let started = Instant::now();
+            solver.run(config.iterations);
+            BenchmarkCase {
+                name: name.to_string(),
+                nodes: tree.nodes.len(),
+                buckets: solver.buckets(),
+                iterations: solver.iterations(),
+                elapsed: started.elapsed(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,169 @@
+use crate::cfr::CfrSolver;
+use crate::game_tree::NodeKind;
+use std::mem::size_of;
+
+// Probabilities are stored as u16 in units of 1/QUANT_SCALE; each bucket's weights sum to exactly
+// QUANT_SCALE, so rounding error per action is below 1/QUANT_SCALE.
+pub const QUANT_SCALE: u32 = u16::MAX as u32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuantizedEntry {
+    pub action: u16,
+    pub weight: u16,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum QuantizedLayout {
+    // One weight per bucket and action.
+    Dense(Vec<u16>),
+    // Only actions a bucket actually plays; entries[offsets[b]..offsets[b + 1]] belong to bucket b.
+    Sparse {
+        offsets: Vec<u32>,
+        entries: Vec<QuantizedEntry>,
+    },
+}
+
+// One decision node's average strategy, in whichever layout is smaller.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuantizedStrategy {
+    pub actions: usize,
+    pub buckets: usize,
+    pub layout: QuantizedLayout,
+}
+
+impl QuantizedStrategy {
+    pub fn from_rows(rows: &[Vec<f64>]) -> Self {
+        let actions = rows.first().map(Vec::len).unwrap_or(0);
+        let dense: Vec<u16> = rows.iter().flat_map(|row| quantize_row(row)).collect();
+        let played = dense.iter().filter(|weight| **weight > 0).count();
+        let sparse_bytes =
+            (rows.len() + 1) * size_of::<u32>() + played * size_of::<QuantizedEntry>();
+        let layout = if sparse_bytes < dense.len() * size_of::<u16>() {
+            let mut offsets = Vec::with_capacity(rows.len() + 1);
+            let mut entries = Vec::with_capacity(played);
+            offsets.push(0);
+            for row in dense.chunks(actions.max(1)) {
+                for (action, weight) in row.iter().enumerate() {
+                    if *weight > 0 {
+                        entries.push(QuantizedEntry {
+                            action: action as u16,
+                            weight: *weight,
+                        });
+                    }
+                }
+                offsets.push(entries.len() as u32);
+            }
+            QuantizedLayout::Sparse { offsets, entries }
+        } else {
+            QuantizedLayout::Dense(dense)
+        };
+        Self {
+            actions,
+            buckets: rows.len(),
+            layout,
+        }
+    }
+
+    pub fn weight(&self, bucket: usize, action: usize) -> u16 {
+        if bucket >= self.buckets || action >= self.actions {
+            return 0;
+        }
+        match &self.layout {
+            QuantizedLayout::Dense(weights) => weights[bucket * self.actions + action],
+            QuantizedLayout::Sparse { offsets, entries } => entries
+                [offsets[bucket] as usize..offsets[bucket + 1] as usize]
+                .iter()
+                .find(|entry| entry.action as usize == action)
+                .map(|entry| entry.weight)
+                .unwrap_or(0),
+        }
+    }
+
+    pub fn probability(&self, bucket: usize, action: usize) -> f64 {
+        self.weight(bucket, action) as f64 / QUANT_SCALE as f64
+    }
+
+    pub fn row(&self, bucket: usize) -> Vec<f64> {
+        (0..self.actions)
+            .map(|action| self.probability(bucket, action))
+            .collect()
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        match &self.layout {
+            QuantizedLayout::Dense(weights) => weights.len() * size_of::<u16>(),
+            QuantizedLayout::Sparse { offsets, entries } => {
+                offsets.len() * size_of::<u32>() + entries.len() * size_of::<QuantizedEntry>()
+            }
+        }
+    }
+}
+
+// Average strategies for every decision node of a solved tree; terminal nodes hold nothing.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompressedStrategies {
+    pub nodes: Vec<Option<QuantizedStrategy>>,
+}
+
+impl CompressedStrategies {
+    pub fn from_solver(solver: &CfrSolver) -> Self {
+        let nodes = solver
+            .tree()
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| match node.kind {
+                NodeKind::Decision { .. } => Some(QuantizedStrategy::from_rows(
+                    &solver.average_strategy(index),
+                )),
+                NodeKind::Terminal(_) => None,
+            })
+            .collect();
+        Self { nodes }
+    }
+
+    pub fn node(&self, node: usize) -> Option<&QuantizedStrategy> {
+        self.nodes.get(node).and_then(Option::as_ref)
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        self.nodes.len() * size_of::<Option<QuantizedStrategy>>()
+            + self
+                .nodes
+                .iter()
+                .flatten()
+                .map(QuantizedStrategy::memory_bytes)
+                .sum::<usize>()
+    }
+}
+
+// Largest-remainder rounding: floor every share, then hand the leftover units to the actions
+// that lost the most, so the row still sums to QUANT_SCALE.
+pub fn quantize_row(row: &[f64]) -> Vec<u16> {
+    let total: f64 = row.iter().map(|value| value.max(0.0)).sum();
+    if row.is_empty() {
+        return Vec::new();
+    }
+    let shares: Vec<f64> = if total > 0.0 {
+        row.iter()
+            .map(|value| value.max(0.0) / total * QUANT_SCALE as f64)
+            .collect()
+    } else {
+        vec![QUANT_SCALE as f64 / row.len() as f64; row.len()]
+    };
+    let mut weights: Vec<u32> = shares.iter().map(|share| share.floor() as u32).collect();
+    let assigned: u32 = weights.iter().sum();
+    let mut order: Vec<usize> = (0..row.len()).collect();
+    order.sort_by(|a, b| {
+        let remainder_a = shares[*a] - shares[*a].floor();
+        let remainder_b = shares[*b] - shares[*b].floor();
+        remainder_b.total_cmp(&remainder_a)
+    });
+    for index in order
+        .into_iter()
+        .take(QUANT_SCALE.saturating_sub(assigned) as usize)
+    {
+        weights[index] += 1;
+    }
+    weights.into_iter().map(|weight| weight as u16).collect()
+}
@@ -0,0 +1,121 @@
+// Linear bounds on the root strategy, such as "bet at least 25% of the range" or "bucket 3 never
+// folds". The root player's strategy is projected onto them every iteration, so the average,
+// which mixes feasible strategies with the same weight for every bucket, satisfies them too.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrategyConstraint {
+    // Root actions whose combined frequency is bounded.
+    pub actions: Vec<usize>,
+    // Buckets the frequency is averaged over; empty is the whole range.
+    pub buckets: Vec<usize>,
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RootConstraints {
+    pub constraints: Vec<StrategyConstraint>,
+}
+
+// Bounds are met one after another; a few rounds settle constraints that pull against each
+// other.
+const PROJECTION_ROUNDS: usize = 8;
+
+impl RootConstraints {
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+
+    // Moves an action-major `strategy` into the bounds. A bucket short of a minimum mixes toward
+    // the constrained actions (keeping their proportions) and one over a maximum toward the rest,
+    // every bucket by the same share.
+    pub fn project(&self, strategy: &mut [f64], buckets: usize) {
+        if self.is_empty() || buckets == 0 {
+            return;
+        }
+        let actions = strategy.len() / buckets;
+        for _ in 0..PROJECTION_ROUNDS {
+            for constraint in &self.constraints {
+                constraint.enforce(strategy, actions, buckets);
+            }
+        }
+    }
+
+    pub fn satisfied(&self, strategy: &[f64], buckets: usize, tolerance: f64) -> bool {
+        let actions = strategy.len() / buckets.max(1);
+        self.constraints.iter().all(|constraint| {
+            let share = constraint.share(strategy, actions, buckets);
+            share >= constraint.min - tolerance && share <= constraint.max + tolerance
+        })
+    }
+}
+
+impl StrategyConstraint {
+    fn members(&self, buckets: usize) -> Vec<usize> {
+        if self.buckets.is_empty() {
+            (0..buckets).collect()
+        } else {
+            self.buckets
+                .iter()
+                .copied()
+                .filter(|bucket| *bucket < buckets)
+                .collect()
+        }
+    }
+
+    fn inside(&self, action: usize) -> bool {
+        self.actions.contains(&action)
+    }
+
+    // Mean over the constraint's buckets of the constrained actions' combined frequency.
+    fn share(&self, strategy: &[f64], actions: usize, buckets: usize) -> f64 {
+        let members = self.members(buckets);
+        if members.is_empty() {
+            return 0.0;
+        }
+        members
+            .iter()
+            .map(|bucket| {
+                (0..actions)
+                    .filter(|action| self.inside(*action))
+                    .map(|action| strategy[action * buckets + bucket])
+                    .sum::<f64>()
+            })
+            .sum::<f64>()
+            / members.len() as f64
+    }
+
+    fn enforce(&self, strategy: &mut [f64], actions: usize, buckets: usize) {
+        let share = self.share(strategy, actions, buckets);
+        let (toward_inside, mix) = if share < self.min {
+            (true, (self.min - share) / (1.0 - share).max(f64::EPSILON))
+        } else if share > self.max {
+            (false, (share - self.max) / share.max(f64::EPSILON))
+        } else {
+            return;
+        };
+        let targets: Vec<usize> = (0..actions)
+            .filter(|action| self.inside(*action) == toward_inside)
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+        let mix = mix.clamp(0.0, 1.0);
+        for bucket in self.members(buckets) {
+            let mass: f64 = targets
+                .iter()
+                .map(|action| strategy[action * buckets + bucket])
+                .sum();
+            for action in 0..actions {
+                let slot = action * buckets + bucket;
+                let goal = if !targets.contains(&action) {
+                    0.0
+                } else if mass > 0.0 {
+                    strategy[slot] / mass
+                } else {
+                    1.0 / targets.len() as f64
+                };
+                strategy[slot] = (1.0 - mix) * strategy[slot] + mix * goal;
+            }
+        }
+    }
+}
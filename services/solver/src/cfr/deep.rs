@@ -0,0 +1,435 @@
+use crate::cfr::{normalize, terminal_payoff, CfrConfig, CfrSolver, TerminalPayoff};
+use crate::game_tree::{GameTree, NodeKind, HERO, VILLAIN};
+use crate::rng::SolverRng;
+
+// Deep CFR (Brown et al.): instead of regret tables, each player has an advantage network that
+// predicts the regret of an (infoset, action) pair from a handful of features, and a strategy
+// network learns the average strategy from samples. Training data comes from external-sampling
+// traversals, so memory grows with the number of traversals rather than the size of the tree.
+// Experimental: selected with the "deep" cfr_variant.
+pub const FEATURES: usize = 7;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeepCfrConfig {
+    pub iterations: usize,
+    // Sampled traversals per player per iteration.
+    pub traversals: usize,
+    pub hidden: usize,
+    pub learning_rate: f64,
+    // Minibatches each advantage network trains on after every iteration.
+    pub train_steps: usize,
+    pub batch_size: usize,
+    // Reservoir capacity of each sample memory.
+    pub memory: usize,
+}
+
+impl Default for DeepCfrConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 50,
+            traversals: 32,
+            hidden: 16,
+            learning_rate: 0.02,
+            train_steps: 100,
+            batch_size: 32,
+            memory: 20_000,
+        }
+    }
+}
+
+impl DeepCfrConfig {
+    // The budget-derived iteration count is spent as traversals: one deep iteration per
+    // `traversals` tabular iterations, with at least one.
+    pub fn for_budget(iterations: usize) -> Self {
+        let defaults = Self::default();
+        Self {
+            iterations: (iterations / defaults.traversals).max(1),
+            ..defaults
+        }
+    }
+}
+
+// Slope of the hidden activation below zero; keeps units from dying on large early regrets.
+const LEAK: f64 = 0.01;
+
+// Two-layer perceptron (leaky-ReLU hidden layer, linear output) trained by minibatch gradient
+// descent on weighted squared error.
+#[derive(Clone, Debug)]
+pub struct Mlp {
+    inputs: usize,
+    hidden: usize,
+    w1: Vec<f64>,
+    b1: Vec<f64>,
+    w2: Vec<f64>,
+    b2: f64,
+}
+
+impl Mlp {
+    pub fn new(inputs: usize, hidden: usize, rng: &mut SolverRng) -> Self {
+        let scale = (1.0 / inputs.max(1) as f64).sqrt();
+        let mut init = |count: usize| -> Vec<f64> {
+            (0..count)
+                .map(|_| (rng.next_f64() * 2.0 - 1.0) * scale)
+                .collect()
+        };
+        Self {
+            inputs,
+            hidden,
+            w1: init(inputs * hidden),
+            b1: vec![0.0; hidden],
+            w2: init(hidden),
+            b2: 0.0,
+        }
+    }
+
+    pub fn predict(&self, x: &[f64]) -> f64 {
+        self.pre_activations(x)
+            .iter()
+            .zip(&self.w2)
+            .map(|(z, w)| activate(*z) * w)
+            .sum::<f64>()
+            + self.b2
+    }
+
+    // `batch` holds (features, target, weight) triples.
+    pub fn train_batch(&mut self, batch: &[(&[f64], f64, f64)], learning_rate: f64) {
+        if batch.is_empty() {
+            return;
+        }
+        let mut grad_w1 = vec![0.0; self.w1.len()];
+        let mut grad_b1 = vec![0.0; self.hidden];
+        let mut grad_w2 = vec![0.0; self.hidden];
+        let mut grad_b2 = 0.0;
+        for (x, target, weight) in batch {
+            let pre = self.pre_activations(x);
+            let output = pre
+                .iter()
+                .zip(&self.w2)
+                .map(|(z, w)| activate(*z) * w)
+                .sum::<f64>()
+                + self.b2;
+            // Clipped so one outlying regret cannot blow the weights up.
+            let error = ((output - target) * weight).clamp(-10.0, 10.0);
+            grad_b2 += error;
+            let units = pre
+                .iter()
+                .zip(&self.w2)
+                .zip(grad_w2.iter_mut().zip(grad_b1.iter_mut()))
+                .zip(grad_w1.chunks_mut(self.inputs));
+            for (((z, w2), (g_w2, g_b1)), g_row) in units {
+                *g_w2 += error * activate(*z);
+                let slope = if *z > 0.0 { 1.0 } else { LEAK };
+                let g_hidden = error * w2 * slope;
+                *g_b1 += g_hidden;
+                for (g, v) in g_row.iter_mut().zip(x.iter()) {
+                    *g += g_hidden * v;
+                }
+            }
+        }
+        let step = learning_rate / batch.len() as f64;
+        for (w, g) in self.w1.iter_mut().zip(&grad_w1) {
+            *w -= step * g;
+        }
+        for (b, g) in self.b1.iter_mut().zip(&grad_b1) {
+            *b -= step * g;
+        }
+        for (w, g) in self.w2.iter_mut().zip(&grad_w2) {
+            *w -= step * g;
+        }
+        self.b2 -= step * grad_b2;
+    }
+
+    fn pre_activations(&self, x: &[f64]) -> Vec<f64> {
+        self.w1
+            .chunks(self.inputs)
+            .zip(&self.b1)
+            .map(|(row, bias)| row.iter().zip(x).map(|(w, v)| w * v).sum::<f64>() + bias)
+            .collect()
+    }
+}
+
+fn activate(z: f64) -> f64 {
+    if z > 0.0 {
+        z
+    } else {
+        z * LEAK
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Sample {
+    features: [f64; FEATURES],
+    target: f64,
+    // Linear CFR weighting: samples from iteration t count t times.
+    weight: f64,
+}
+
+#[derive(Clone, Debug)]
+struct Reservoir {
+    capacity: usize,
+    seen: usize,
+    samples: Vec<Sample>,
+}
+
+impl Reservoir {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            seen: 0,
+            samples: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, sample: Sample, rng: &mut SolverRng) {
+        self.seen += 1;
+        if self.samples.len() < self.capacity {
+            self.samples.push(sample);
+        } else {
+            let slot = rng.below(self.seen);
+            if slot < self.capacity {
+                self.samples[slot] = sample;
+            }
+        }
+    }
+}
+
+pub struct DeepCfrSolver<'a> {
+    tree: &'a GameTree,
+    config: DeepCfrConfig,
+    buckets: usize,
+    payoffs: Vec<Option<TerminalPayoff>>,
+    advantage: [Mlp; 2],
+    policy: Mlp,
+    advantage_memory: [Reservoir; 2],
+    strategy_memory: Reservoir,
+    iterations: usize,
+    rng: SolverRng,
+}
+
+impl<'a> DeepCfrSolver<'a> {
+    pub fn new(tree: &'a GameTree, cfr: &CfrConfig, config: DeepCfrConfig) -> Self {
+        let mut rng = SolverRng::seeded(cfr.seed);
+        let payoffs = tree
+            .nodes
+            .iter()
+            .map(|node| match node.kind {
+                NodeKind::Terminal(kind) => Some(terminal_payoff(tree, node.committed, kind)),
+                NodeKind::Decision { .. } => None,
+            })
+            .collect();
+        let advantage = [
+            Mlp::new(FEATURES, config.hidden, &mut rng),
+            Mlp::new(FEATURES, config.hidden, &mut rng),
+        ];
+        let policy = Mlp::new(FEATURES, config.hidden, &mut rng);
+        Self {
+            tree,
+            buckets: cfr.buckets.max(1),
+            payoffs,
+            advantage,
+            policy,
+            advantage_memory: [Reservoir::new(config.memory), Reservoir::new(config.memory)],
+            strategy_memory: Reservoir::new(config.memory),
+            iterations: 0,
+            config,
+            rng,
+        }
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    pub fn run(&mut self) {
+        for _ in 0..self.config.iterations {
+            self.iterate();
+        }
+        self.train_policy();
+    }
+
+    pub fn iterate(&mut self) {
+        self.iterations += 1;
+        for traverser in [HERO, VILLAIN] {
+            for _ in 0..self.config.traversals {
+                let hands = [self.rng.below(self.buckets), self.rng.below(self.buckets)];
+                self.traverse(0, traverser, hands);
+            }
+            self.train_advantage(traverser);
+        }
+    }
+
+    // Average strategy at `node` from the policy network, one row per bucket.
+    pub fn average_strategy(&self, node: usize) -> Vec<Vec<f64>> {
+        let actions = match &self.tree.nodes[node].kind {
+            NodeKind::Decision { children, .. } => children.len(),
+            NodeKind::Terminal(_) => 0,
+        };
+        (0..self.buckets)
+            .map(|bucket| {
+                let predicted: Vec<f64> = (0..actions)
+                    .map(|action| {
+                        self.policy
+                            .predict(&self.features(node, bucket, action))
+                            .max(0.0)
+                    })
+                    .collect();
+                normalize(&predicted)
+            })
+            .collect()
+    }
+
+    // A tabular solver over the same tree holding the learned average strategy, so the usual
+    // exploitability, EV and export code can evaluate it.
+    pub fn to_tabular(&self, config: &CfrConfig) -> CfrSolver<'a> {
+        let mut solver = CfrSolver::new(self.tree, config);
+        for (node, tree_node) in self.tree.nodes.iter().enumerate() {
+            if !tree_node.is_terminal() {
+                solver.set_average_strategy(node, &self.average_strategy(node));
+            }
+        }
+        solver.iterations = self.iterations;
+        solver
+    }
+
+    fn traverse(&mut self, node: usize, traverser: usize, hands: [usize; 2]) -> f64 {
+        let (actor, children) = match &self.tree.nodes[node].kind {
+            NodeKind::Terminal(_) => return self.terminal_value(node, traverser, hands),
+            NodeKind::Decision { player, children } => (*player, children.clone()),
+        };
+        let bucket = hands[actor];
+        let strategy = self.current_strategy(node, actor, bucket);
+        let weight = self.iterations as f64;
+
+        if actor != traverser {
+            for (action, probability) in strategy.iter().enumerate() {
+                let sample = Sample {
+                    features: self.features(node, bucket, action),
+                    target: *probability,
+                    weight,
+                };
+                self.strategy_memory.push(sample, &mut self.rng);
+            }
+            let action = self.rng.sample_index(&strategy);
+            return self.traverse(children[action], traverser, hands);
+        }
+
+        let values: Vec<f64> = children
+            .iter()
+            .map(|&child| self.traverse(child, traverser, hands))
+            .collect();
+        let ev: f64 = values.iter().zip(&strategy).map(|(v, p)| v * p).sum();
+        for (action, value) in values.iter().enumerate() {
+            let sample = Sample {
+                features: self.features(node, bucket, action),
+                target: value - ev,
+                weight,
+            };
+            self.advantage_memory[traverser].push(sample, &mut self.rng);
+        }
+        ev
+    }
+
+    // Regret matching over the advantage network's predictions.
+    fn current_strategy(&self, node: usize, actor: usize, bucket: usize) -> Vec<f64> {
+        let actions = match &self.tree.nodes[node].kind {
+            NodeKind::Decision { children, .. } => children.len(),
+            NodeKind::Terminal(_) => 0,
+        };
+        let advantages: Vec<f64> = (0..actions)
+            .map(|action| {
+                self.advantage[actor]
+                    .predict(&self.features(node, bucket, action))
+                    .max(0.0)
+            })
+            .collect();
+        normalize(&advantages)
+    }
+
+    fn terminal_value(&self, node: usize, traverser: usize, hands: [usize; 2]) -> f64 {
+        let opponent = 1 - traverser;
+        match self.payoffs[node] {
+            Some(TerminalPayoff::Fold(net)) => net[traverser],
+            Some(TerminalPayoff::Showdown { win, tie, lose }) => {
+                match hands[traverser].cmp(&hands[opponent]) {
+                    std::cmp::Ordering::Greater => win[traverser],
+                    std::cmp::Ordering::Equal => tie[traverser],
+                    std::cmp::Ordering::Less => lose[traverser],
+                }
+            }
+            None => 0.0,
+        }
+    }
+
+    // Hand strength, who acts, what each player has put in and what the action adds, all
+    // relative to the pot so one network serves every node.
+    fn features(&self, node: usize, bucket: usize, action: usize) -> [f64; FEATURES] {
+        let tree_node = &self.tree.nodes[node];
+        let pot = self.tree.pot_bb.max(1.0);
+        let (actor, child) = match &tree_node.kind {
+            NodeKind::Decision { player, children } => {
+                (*player, &self.tree.nodes[children[action]])
+            }
+            NodeKind::Terminal(_) => (HERO, tree_node),
+        };
+        [
+            (bucket as f64 + 0.5) / self.buckets as f64,
+            actor as f64,
+            tree_node.committed[HERO] / pot,
+            tree_node.committed[VILLAIN] / pot,
+            (child.committed[actor] - tree_node.committed[actor]) / pot,
+            (child.label == "fold") as u8 as f64,
+            (child.label == "call" || child.label == "check") as u8 as f64,
+        ]
+    }
+
+    fn train_advantage(&mut self, player: usize) {
+        // Targets are scaled to the pot so the learning rate does not depend on stake sizes.
+        let pot = self.tree.pot_bb.max(1.0);
+        let scale = self.iterations.max(1) as f64;
+        for _ in 0..self.config.train_steps {
+            let batch = sample_batch(
+                &self.advantage_memory[player],
+                self.config.batch_size,
+                &mut self.rng,
+            );
+            let batch: Vec<(&[f64], f64, f64)> = batch
+                .iter()
+                .map(|sample| {
+                    (
+                        sample.features.as_slice(),
+                        sample.target / pot,
+                        sample.weight / scale,
+                    )
+                })
+                .collect();
+            self.advantage[player].train_batch(&batch, self.config.learning_rate);
+        }
+    }
+
+    fn train_policy(&mut self) {
+        let scale = self.iterations.max(1) as f64;
+        for _ in 0..self.config.train_steps * self.config.iterations.max(1) {
+            let batch = sample_batch(&self.strategy_memory, self.config.batch_size, &mut self.rng);
+            let batch: Vec<(&[f64], f64, f64)> = batch
+                .iter()
+                .map(|sample| {
+                    (
+                        sample.features.as_slice(),
+                        sample.target,
+                        sample.weight / scale,
+                    )
+                })
+                .collect();
+            self.policy.train_batch(&batch, self.config.learning_rate);
+        }
+    }
+}
+
+fn sample_batch<'m>(memory: &'m Reservoir, size: usize, rng: &mut SolverRng) -> Vec<&'m Sample> {
+    if memory.samples.is_empty() {
+        return Vec::new();
+    }
+    (0..size.max(1))
+        .map(|_| &memory.samples[rng.below(memory.samples.len())])
+        .collect()
+}
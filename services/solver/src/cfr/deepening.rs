@@ -0,0 +1,42 @@
+use crate::cfr::variant::CfrVariantKind;
+use crate::cfr::{outcome, run_cfr_with_config, run_iterations, CfrConfig, CfrOutcome};
+use crate::cfr::{CfrSolver, Stopped};
+use crate::game_tree::GameTree;
+
+// Solves `trees`, shallowest first, each pass starting from the one before it and getting an
+// even share of the iterations left. Deepening stops once the deadline passes or the solve is
+// cancelled, so a shallower tree's answer comes back rather than none. Returns the index of the
+// tree the outcome is for.
+pub fn run_cfr_deepening(trees: &[GameTree], config: &CfrConfig) -> (usize, CfrOutcome) {
+    let Some(deepest) = trees.last() else {
+        return (0, CfrOutcome::default());
+    };
+    if trees.len() == 1 || config.variant == CfrVariantKind::Deep {
+        return (trees.len() - 1, run_cfr_with_config(deepest, config));
+    }
+    if let Some(progress) = &config.progress {
+        progress.begin(config.iterations.max(1));
+    }
+    let total = config.iterations.max(1);
+    let mut solver = CfrSolver::new(&trees[0], config);
+    if let Some(prior) = &config.warm_start {
+        solver.warm_start(0, &prior.root_strategy, prior.weight);
+    }
+    let mut depth = 0;
+    let converged = loop {
+        let remaining = total.saturating_sub(solver.iterations());
+        let stopped = run_iterations(&mut solver, config, remaining / (trees.len() - depth));
+        if stopped == Stopped::Interrupted || depth + 1 == trees.len() {
+            break stopped == Stopped::Converged;
+        }
+        depth += 1;
+        let mut deeper = CfrSolver::new(&trees[depth], config);
+        deeper.extend(&solver);
+        solver = deeper;
+    };
+    let solved = outcome(&solver, config, converged);
+    if let Some(progress) = &config.progress {
+        progress.finish();
+    }
+    (depth, solved)
+}
@@ -0,0 +1,169 @@
+use crate::cfr::CfrSolver;
+use crate::game_tree::{NodeKind, HERO, VILLAIN};
+
+// Betting hands at or above this equity against the continuing range are value, below
+// BLUFF_EQUITY bluffs, and the rest merged: thin value that also folds out better hands.
+pub const VALUE_EQUITY: f64 = 0.6;
+pub const BLUFF_EQUITY: f64 = 0.4;
+
+// Textbook quantities for one root bet, next to what the solved strategies actually do.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BetDiagnostics {
+    pub label: String,
+    // Villain's price: the call as a share of the pot after calling.
+    pub pot_odds: f64,
+    // Minimum defense frequency: below this villain folds too much and any two cards can bet.
+    pub required_defense: f64,
+    // How often villain continues against the bet in the solved strategy.
+    pub defense_frequency: f64,
+    // Bluffs per value hand in hero's betting range; infinite when only bluffs bet. A hand is
+    // value when it wins more than half the time against villain's continuing range.
+    pub bluff_to_value: f64,
+    // The ratio that leaves villain indifferent between calling and folding.
+    pub indifferent_bluff_to_value: f64,
+    // How the betting range splits into value, merged and bluff hands; 0 each when nothing bets.
+    pub value_share: f64,
+    pub merged_share: f64,
+    pub bluff_share: f64,
+}
+
+pub fn root_bet_diagnostics(solver: &CfrSolver) -> Vec<BetDiagnostics> {
+    let tree = solver.tree();
+    let NodeKind::Decision { player, children } = &tree.nodes[0].kind else {
+        return Vec::new();
+    };
+    if *player != HERO {
+        return Vec::new();
+    }
+    let buckets = solver.buckets();
+    let betting = solver.average_strategy(0);
+    let pot = tree.pot_bb.max(f64::EPSILON);
+    children
+        .iter()
+        .enumerate()
+        .filter_map(|(action, &child)| {
+            let node = &tree.nodes[child];
+            let NodeKind::Decision { player, children } = &node.kind else {
+                return None;
+            };
+            let fold = children
+                .iter()
+                .position(|next| tree.nodes[*next].label == "fold")?;
+            if *player != VILLAIN {
+                return None;
+            }
+            let call = node.committed[HERO] - node.committed[VILLAIN];
+            if call <= 0.0 {
+                return None;
+            }
+            let pot_odds = call / (pot + node.committed[HERO] + call);
+
+            let continuing: Vec<f64> = solver
+                .average_strategy(child)
+                .iter()
+                .map(|row| 1.0 - row[fold])
+                .collect();
+            let defended: f64 = continuing.iter().sum();
+            let mut value = 0.0;
+            let mut bluffs = 0.0;
+            let mut shares = [0.0; 3];
+            for (bucket, row) in betting.iter().enumerate() {
+                let equity = showdown_equity(bucket, &continuing, defended);
+                if equity > 0.5 {
+                    value += row[action];
+                } else {
+                    bluffs += row[action];
+                }
+                let category = if equity >= VALUE_EQUITY {
+                    0
+                } else if equity >= BLUFF_EQUITY {
+                    1
+                } else {
+                    2
+                };
+                shares[category] += row[action];
+            }
+            let betting_range = value + bluffs;
+            if betting_range > 0.0 {
+                shares.iter_mut().for_each(|share| *share /= betting_range);
+            }
+            let bluff_to_value = if value > 0.0 {
+                bluffs / value
+            } else if bluffs > 0.0 {
+                f64::INFINITY
+            } else {
+                0.0
+            };
+
+            Some(BetDiagnostics {
+                label: node.label.clone(),
+                pot_odds,
+                required_defense: pot / (pot + node.committed[HERO]),
+                defense_frequency: defended / buckets as f64,
+                bluff_to_value,
+                indifferent_bluff_to_value: pot_odds / (1.0 - pot_odds),
+                value_share: shares[0],
+                merged_share: shares[1],
+                bluff_share: shares[2],
+            })
+        })
+        .collect()
+}
+
+// Against a range that never continues, equity is measured against the whole range.
+fn showdown_equity(bucket: usize, continuing: &[f64], defended: f64) -> f64 {
+    let uniform = vec![1.0; continuing.len()];
+    let (weights, total) = if defended > 0.0 {
+        (continuing, defended)
+    } else {
+        (uniform.as_slice(), continuing.len() as f64)
+    };
+    weights
+        .iter()
+        .enumerate()
+        .map(|(other, weight)| match bucket.cmp(&other) {
+            std::cmp::Ordering::Greater => *weight,
+            std::cmp::Ordering::Equal => weight / 2.0,
+            std::cmp::Ordering::Less => 0.0,
+        })
+        .sum::<f64>()
+        / total
+}
+
+// How often villain takes one action at the node after a root action, over the whole range.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResponseStat {
+    pub label: String,
+    pub frequency: f64,
+}
+
+// Villain's solved reply to each root action, in root order; empty where the action ends the
+// hand or hero acts again.
+pub fn root_responses(solver: &CfrSolver) -> Vec<Vec<ResponseStat>> {
+    let tree = solver.tree();
+    let NodeKind::Decision { children, .. } = &tree.nodes[0].kind else {
+        return Vec::new();
+    };
+    let buckets = solver.buckets().max(1) as f64;
+    children
+        .iter()
+        .map(|&child| {
+            let NodeKind::Decision {
+                player: VILLAIN,
+                children: replies,
+            } = &tree.nodes[child].kind
+            else {
+                return Vec::new();
+            };
+            let strategy = solver.average_strategy(child);
+            replies
+                .iter()
+                .enumerate()
+                .map(|(reply, &next)| ResponseStat {
+                    label: tree.nodes[next].label.clone(),
+                    frequency: strategy.iter().map(|row| row[reply]).sum::<f64>() / buckets,
+                })
+                .collect()
+        })
+        .collect()
+}
@@ -0,0 +1,228 @@
+use crate::cfr::compressed::CompressedStrategies;
+use crate::game_tree::{GameTree, NodeKind};
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionDelta {
+    pub label: String,
+    pub baseline: f64,
+    pub candidate: f64,
+}
+
+impl ActionDelta {
+    pub fn delta(&self) -> f64 {
+        self.candidate - self.baseline
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeDiff {
+    // Labels of the actions from the root, joined by "/"; empty for the root.
+    pub path: String,
+    pub player: usize,
+    // Every action either solve offers here; one missing from a tree counts as never taken.
+    pub actions: Vec<ActionDelta>,
+    pub total_variation: f64,
+    // Per-bucket distance averaged over the range reaching the node; only when both solves used
+    // the same buckets.
+    pub bucket_total_variation: Option<f64>,
+    // How likely the baseline strategy is to reach the node.
+    pub reach: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StrategyDiff {
+    pub nodes: Vec<NodeDiff>,
+    // Decision nodes only one of the trees has, e.g. a size the other abstraction dropped.
+    pub unmatched: Vec<String>,
+}
+
+impl StrategyDiff {
+    pub fn max_total_variation(&self) -> f64 {
+        self.nodes
+            .iter()
+            .map(|node| node.total_variation)
+            .fold(0.0, f64::max)
+    }
+
+    // Weighted by reach, so nodes the baseline hardly ever gets to barely count.
+    pub fn weighted_total_variation(&self) -> f64 {
+        let reach: f64 = self.nodes.iter().map(|node| node.reach).sum();
+        if reach <= 0.0 {
+            return 0.0;
+        }
+        self.nodes
+            .iter()
+            .map(|node| node.reach * node.total_variation)
+            .sum::<f64>()
+            / reach
+    }
+}
+
+// One decision node as a solve plays it: the strategy of every bucket and how much of the
+// acting player's range gets there in each.
+struct NodeView {
+    path: String,
+    player: usize,
+    labels: Vec<String>,
+    rows: Vec<Vec<f64>>,
+    range: Vec<f64>,
+    reach: f64,
+}
+
+impl NodeView {
+    // The acting range's overall frequency of `label`.
+    fn frequency(&self, label: &str) -> f64 {
+        let Some(action) = self.labels.iter().position(|known| known == label) else {
+            return 0.0;
+        };
+        let weights = range_weights(&self.range);
+        self.rows
+            .iter()
+            .zip(&weights)
+            .map(|(row, weight)| row[action] * weight)
+            .sum()
+    }
+
+    fn bucket_frequency(&self, bucket: usize, label: &str) -> f64 {
+        self.labels
+            .iter()
+            .position(|known| known == label)
+            .map(|action| self.rows[bucket][action])
+            .unwrap_or(0.0)
+    }
+}
+
+// Compares two solves of the same spot node by node. Nodes are matched by their action path, so
+// trees built from different abstractions still line up wherever they share a line.
+pub fn diff_strategies(
+    baseline_tree: &GameTree,
+    baseline: &CompressedStrategies,
+    candidate_tree: &GameTree,
+    candidate: &CompressedStrategies,
+) -> StrategyDiff {
+    let baseline_nodes = node_views(baseline_tree, baseline);
+    let mut candidate_nodes: HashMap<String, NodeView> = node_views(candidate_tree, candidate)
+        .into_iter()
+        .map(|view| (view.path.clone(), view))
+        .collect();
+    let mut diff = StrategyDiff::default();
+    for base in baseline_nodes {
+        match candidate_nodes.remove(&base.path) {
+            Some(other) => diff.nodes.push(node_diff(&base, &other)),
+            None => diff.unmatched.push(base.path),
+        }
+    }
+    let mut extra: Vec<String> = candidate_nodes.into_keys().collect();
+    extra.sort();
+    diff.unmatched.extend(extra);
+    diff
+}
+
+fn node_diff(base: &NodeView, other: &NodeView) -> NodeDiff {
+    let mut labels = base.labels.clone();
+    labels.extend(
+        other
+            .labels
+            .iter()
+            .filter(|label| !base.labels.contains(label))
+            .cloned(),
+    );
+    let actions: Vec<ActionDelta> = labels
+        .iter()
+        .map(|label| ActionDelta {
+            label: label.clone(),
+            baseline: base.frequency(label),
+            candidate: other.frequency(label),
+        })
+        .collect();
+    let total_variation = actions
+        .iter()
+        .map(|action| action.delta().abs())
+        .sum::<f64>()
+        / 2.0;
+    let bucket_total_variation = (base.rows.len() == other.rows.len()).then(|| {
+        range_weights(&base.range)
+            .iter()
+            .enumerate()
+            .map(|(bucket, weight)| {
+                let distance = labels
+                    .iter()
+                    .map(|label| {
+                        (base.bucket_frequency(bucket, label)
+                            - other.bucket_frequency(bucket, label))
+                        .abs()
+                    })
+                    .sum::<f64>()
+                    / 2.0;
+                weight * distance
+            })
+            .sum()
+    });
+    NodeDiff {
+        path: base.path.clone(),
+        player: base.player,
+        actions,
+        total_variation,
+        bucket_total_variation,
+        reach: base.reach,
+    }
+}
+
+// Every decision node in depth-first order, carrying each player's per-bucket reach down the
+// tree.
+fn node_views(tree: &GameTree, strategies: &CompressedStrategies) -> Vec<NodeView> {
+    let Some(buckets) = strategies.node(0).map(|root| root.buckets) else {
+        return Vec::new();
+    };
+    let mut views = Vec::new();
+    let mut stack = vec![(0, String::new(), [vec![1.0; buckets], vec![1.0; buckets]])];
+    while let Some((index, path, reach)) = stack.pop() {
+        let NodeKind::Decision { player, children } = &tree.nodes[index].kind else {
+            continue;
+        };
+        let Some(strategy) = strategies.node(index) else {
+            continue;
+        };
+        let rows: Vec<Vec<f64>> = (0..strategy.buckets)
+            .map(|bucket| strategy.row(bucket))
+            .collect();
+        let labels = children
+            .iter()
+            .map(|child| tree.nodes[*child].label.clone())
+            .collect();
+        for (action, child) in children.iter().enumerate().rev() {
+            let mut next = reach.clone();
+            for (bucket, share) in next[*player].iter_mut().enumerate() {
+                *share *= rows.get(bucket).map(|row| row[action]).unwrap_or(0.0);
+            }
+            let label = &tree.nodes[*child].label;
+            let child_path = if path.is_empty() {
+                label.clone()
+            } else {
+                format!("{}/{}", path, label)
+            };
+            stack.push((*child, child_path, next));
+        }
+        let mean = |shares: &Vec<f64>| shares.iter().sum::<f64>() / shares.len().max(1) as f64;
+        views.push(NodeView {
+            path,
+            player: *player,
+            labels,
+            reach: mean(&reach[0]) * mean(&reach[1]),
+            range: reach[*player].clone(),
+            rows,
+        });
+    }
+    views
+}
+
+// Normalized, or uniform when none of the range gets here.
+fn range_weights(range: &[f64]) -> Vec<f64> {
+    let total: f64 = range.iter().sum();
+    if total > 0.0 {
+        range.iter().map(|share| share / total).collect()
+    } else {
+        vec![1.0 / range.len().max(1) as f64; range.len()]
+    }
+}
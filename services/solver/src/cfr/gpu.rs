@@ -0,0 +1,947 @@
+use crate::abstraction::showdown::ShowdownMatrix;
+use crate::cfr::backend::{
+    CfrBackend, CpuBackend, DeviceTraversal, LayoutNode, Pairing, TableView, TraversalLayout,
+};
+use crate::game_tree::{HERO, VILLAIN};
+use std::num::NonZeroU64;
+use std::sync::{mpsc, Arc, OnceLock};
+use wgpu::util::DeviceExt;
+
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_GROUPS_PER_DIM: u32 = 65_535;
+
+// Regret matching alone, for solves the device can't traverse: the solver hands over every
+// infoset's regrets once per traverser pass and traverses the tree on the CPU with the
+// strategies that come back. One invocation per (table, bucket). Regrets are f32 on the device,
+// so strategies can differ from the CPU backend in the last few bits.
+const SHADER: &str = r#"
+struct Params {
+    buckets: u32,
+    tables: u32,
+    row_stride: u32,
+    pad: u32,
+};
+
+@group(0) @binding(0) var<storage, read> regrets: array<f32>;
+@group(0) @binding(1) var<storage, read> offsets: array<u32>;
+@group(0) @binding(2) var<storage, read> actions: array<u32>;
+@group(0) @binding(3) var<storage, read_write> strategy: array<f32>;
+@group(0) @binding(4) var<uniform> params: Params;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.y * params.row_stride + id.x;
+    if (index >= params.tables * params.buckets) {
+        return;
+    }
+    let table = index / params.buckets;
+    let bucket = index % params.buckets;
+    let base = offsets[table];
+    let count = actions[table];
+    var total = 0.0;
+    for (var action = 0u; action < count; action++) {
+        total += max(regrets[base + action * params.buckets + bucket], 0.0);
+    }
+    for (var action = 0u; action < count; action++) {
+        let slot = base + action * params.buckets + bucket;
+        if (total > 0.0) {
+            strategy[slot] = max(regrets[slot], 0.0) / total;
+        } else {
+            strategy[slot] = 1.0 / f32(count);
+        }
+    }
+}
+"#;
+
+// Whole iterations, one dispatch per step of a traverser pass, each running one invocation per
+// (node, bucket) of the nodes it covers:
+// - predict: predicted = regrets + last deltas, per slot (predictive variants);
+// - regret_match: the current strategy of every decision node;
+// - start: both reaches at the root set to the prior;
+// - forward, one tree level at a time from the root: each decision node's reaches pushed to its
+//   children, the actor's scaled by the strategy;
+// - terminal: every terminal's value for the traverser against the opposing reach;
+// - backward, one level at a time from the deepest: each decision node's value from its
+//   children's, and at the traverser's nodes the regret and strategy sum updates.
+// Nodes on one level never share a child, so no two invocations write the same value.
+//
+// `tree` holds a 4-word record per node (actor, or FOLD or SHOWDOWN; first child; child count;
+// table offset, or a terminal's pairing), then the child lists, then the per-dispatch node lists.
+// `numbers` holds the prior, 6 payoffs per node (fold: the nets; showdown: win, tie and lose, by
+// seat) and the showdown matrices' weight, win and tie. `tables` holds regrets, strategy sums,
+// last deltas, predicted regrets and the current strategy, `slots` each; `work` holds the
+// traverser's reach, the opposing reach and the traverser's values, one row of buckets per node.
+const TRAVERSAL_SHADER: &str = r#"
+struct Params {
+    buckets: u32,
+    traverser: u32,
+    first: u32,
+    count: u32,
+    row_stride: u32,
+    slots: u32,
+    nodes: u32,
+    children_at: u32,
+    payoffs_at: u32,
+    showdowns_at: u32,
+    all_in_at: u32,
+    floors: u32,
+    predicts: u32,
+    weight: f32,
+    pad0: u32,
+    pad1: u32,
+};
+
+const FOLD: u32 = 2u;
+const SHOWDOWN: u32 = 3u;
+const BY_ORDER: u32 = 0u;
+const ALL_IN: u32 = 2u;
+
+@group(0) @binding(0) var<storage, read> tree: array<u32>;
+@group(0) @binding(1) var<storage, read> numbers: array<f32>;
+@group(0) @binding(2) var<storage, read_write> tables: array<f32>;
+@group(0) @binding(3) var<storage, read_write> work: array<f32>;
+@group(0) @binding(4) var<uniform> params: Params;
+
+fn invocation(id: vec3<u32>) -> u32 {
+    return id.y * params.row_stride + id.x;
+}
+
+fn own_reach(node: u32, bucket: u32) -> u32 {
+    return node * params.buckets + bucket;
+}
+
+fn opp_reach(node: u32, bucket: u32) -> u32 {
+    return (params.nodes + node) * params.buckets + bucket;
+}
+
+fn value_at(node: u32, bucket: u32) -> u32 {
+    return (2u * params.nodes + node) * params.buckets + bucket;
+}
+
+@compute @workgroup_size(64)
+fn predict(@builtin(global_invocation_id) id: vec3<u32>) {
+    let slot = invocation(id);
+    if (slot >= params.count) {
+        return;
+    }
+    tables[3u * params.slots + slot] = tables[slot] + tables[2u * params.slots + slot];
+}
+
+@compute @workgroup_size(64)
+fn regret_match(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = invocation(id);
+    if (index >= params.count * params.buckets) {
+        return;
+    }
+    let node = tree[params.first + index / params.buckets];
+    let bucket = index % params.buckets;
+    let count = tree[node * 4u + 2u];
+    let base = tree[node * 4u + 3u] + bucket;
+    let source = select(0u, 3u * params.slots, params.predicts != 0u);
+    var total = 0.0;
+    for (var action = 0u; action < count; action++) {
+        total += max(tables[source + base + action * params.buckets], 0.0);
+    }
+    for (var action = 0u; action < count; action++) {
+        let slot = base + action * params.buckets;
+        var probability = 1.0 / f32(count);
+        if (total > 0.0) {
+            probability = max(tables[source + slot], 0.0) / total;
+        }
+        tables[4u * params.slots + slot] = probability;
+    }
+}
+
+@compute @workgroup_size(64)
+fn start(@builtin(global_invocation_id) id: vec3<u32>) {
+    let bucket = invocation(id);
+    if (bucket >= params.buckets) {
+        return;
+    }
+    work[own_reach(0u, bucket)] = numbers[bucket];
+    work[opp_reach(0u, bucket)] = numbers[bucket];
+}
+
+@compute @workgroup_size(64)
+fn forward(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = invocation(id);
+    if (index >= params.count * params.buckets) {
+        return;
+    }
+    let node = tree[params.first + index / params.buckets];
+    let bucket = index % params.buckets;
+    let actor = tree[node * 4u];
+    let first_child = params.children_at + tree[node * 4u + 1u];
+    let count = tree[node * 4u + 2u];
+    let base = 4u * params.slots + tree[node * 4u + 3u] + bucket;
+    let own = work[own_reach(node, bucket)];
+    let opp = work[opp_reach(node, bucket)];
+    for (var action = 0u; action < count; action++) {
+        let child = tree[first_child + action];
+        let probability = tables[base + action * params.buckets];
+        if (actor == params.traverser) {
+            work[own_reach(child, bucket)] = own * probability;
+            work[opp_reach(child, bucket)] = opp;
+        } else {
+            work[own_reach(child, bucket)] = own;
+            work[opp_reach(child, bucket)] = opp * probability;
+        }
+    }
+}
+
+@compute @workgroup_size(64)
+fn terminal(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = invocation(id);
+    if (index >= params.count * params.buckets) {
+        return;
+    }
+    let node = tree[params.first + index / params.buckets];
+    let bucket = index % params.buckets;
+    let seat = params.traverser;
+    let fold = tree[node * 4u] == FOLD;
+    let pairing = tree[node * 4u + 3u];
+    let payoff = params.payoffs_at + node * 6u;
+    let win = numbers[payoff + seat];
+    let tie = numbers[payoff + 2u + seat];
+    let lose = numbers[payoff + 4u + seat];
+    var value = 0.0;
+    if (pairing == BY_ORDER) {
+        var below = 0.0;
+        var total = 0.0;
+        for (var opposing = 0u; opposing < params.buckets; opposing++) {
+            let reach = work[opp_reach(node, opposing)];
+            total += reach;
+            if (opposing < bucket) {
+                below += reach;
+            }
+        }
+        if (fold) {
+            value = win * total;
+        } else {
+            let level = work[opp_reach(node, bucket)];
+            value = win * below + tie * level + lose * (total - below - level);
+        }
+    } else {
+        let matrix = select(params.showdowns_at, params.all_in_at, pairing == ALL_IN);
+        let cells = params.buckets * params.buckets;
+        for (var opposing = 0u; opposing < params.buckets; opposing++) {
+            var cell = opposing * params.buckets + bucket;
+            if (seat == 0u) {
+                cell = bucket * params.buckets + opposing;
+            }
+            let split = numbers[matrix + 2u * cells + cell];
+            var won = numbers[matrix + cells + cell];
+            if (seat != 0u) {
+                won = 1.0 - won - split;
+            }
+            var net = win;
+            if (!fold) {
+                net = win * won + tie * split + lose * (1.0 - won - split);
+            }
+            value += work[opp_reach(node, opposing)] * numbers[matrix + cell] * net;
+        }
+    }
+    work[value_at(node, bucket)] = value;
+}
+
+@compute @workgroup_size(64)
+fn backward(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = invocation(id);
+    if (index >= params.count * params.buckets) {
+        return;
+    }
+    let node = tree[params.first + index / params.buckets];
+    let bucket = index % params.buckets;
+    let first_child = params.children_at + tree[node * 4u + 1u];
+    let count = tree[node * 4u + 2u];
+    let base = tree[node * 4u + 3u] + bucket;
+    let current = 4u * params.slots + base;
+    var value = 0.0;
+    if (tree[node * 4u] != params.traverser) {
+        for (var action = 0u; action < count; action++) {
+            value += work[value_at(tree[first_child + action], bucket)];
+        }
+        work[value_at(node, bucket)] = value;
+        return;
+    }
+    for (var action = 0u; action < count; action++) {
+        let child_value = work[value_at(tree[first_child + action], bucket)];
+        value += tables[current + action * params.buckets] * child_value;
+    }
+    let reach = work[own_reach(node, bucket)];
+    for (var action = 0u; action < count; action++) {
+        let slot = base + action * params.buckets;
+        let delta = work[value_at(tree[first_child + action], bucket)] - value;
+        var regret = tables[slot] + delta;
+        if (params.floors != 0u) {
+            regret = max(regret, 0.0);
+        }
+        tables[slot] = regret;
+        if (params.predicts != 0u) {
+            tables[2u * params.slots + slot] = delta;
+        }
+        tables[params.slots + slot] += params.weight * reach * tables[current + action * params.buckets];
+    }
+    work[value_at(node, bucket)] = value;
+}
+"#;
+
+// The traversal's entry points, in `Stage` order.
+const STAGES: [&str; 6] = [
+    "predict",
+    "regret_match",
+    "start",
+    "forward",
+    "terminal",
+    "backward",
+];
+
+#[derive(Clone, Copy)]
+enum Stage {
+    Predict,
+    RegretMatch,
+    Start,
+    Forward,
+    Terminal,
+    Backward,
+}
+
+const NODE_WORDS: usize = 4;
+const PAYOFF_WORDS: usize = 6;
+const FOLD: u32 = 2;
+const SHOWDOWN: u32 = 3;
+// Size of `Params` in the traversal shader.
+const PARAMS_BYTES: u64 = 64;
+
+struct GpuContext {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    traversal_layout: wgpu::BindGroupLayout,
+    // One per `Stage`.
+    traversal: Vec<wgpu::ComputePipeline>,
+    adapter_name: String,
+}
+
+#[derive(Clone)]
+pub struct GpuBackend {
+    context: Arc<GpuContext>,
+}
+
+static SHARED: OnceLock<Option<GpuBackend>> = OnceLock::new();
+
+impl GpuBackend {
+    // Probes for an adapter once per process; every solve after that reuses the device.
+    pub fn shared() -> Option<GpuBackend> {
+        SHARED
+            .get_or_init(|| {
+                let backend = pollster::block_on(Self::detect());
+                match &backend {
+                    Some(gpu) => tracing::info!(adapter = %gpu.adapter_name(), "GPU backend ready"),
+                    None => tracing::info!("no GPU adapter found"),
+                }
+                backend
+            })
+            .clone()
+    }
+
+    pub fn adapter_name(&self) -> &str {
+        &self.context.adapter_name
+    }
+
+    async fn detect() -> Option<GpuBackend> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                force_fallback_adapter: false,
+                compatible_surface: None,
+            })
+            .await?;
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("solver-cfr"),
+                    required_features: wgpu::Features::empty(),
+                    required_limits: wgpu::Limits::default(),
+                },
+                None,
+            )
+            .await
+            .ok()?;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("regret-match"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("regret-match"),
+            layout: None,
+            module: &module,
+            entry_point: "main",
+        });
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let traversal_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("cfr-traversal"),
+            entries: &[
+                storage(0, true),
+                storage(1, true),
+                storage(2, false),
+                storage(3, false),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: NonZeroU64::new(PARAMS_BYTES),
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("cfr-traversal"),
+            bind_group_layouts: &[&traversal_layout],
+            push_constant_ranges: &[],
+        });
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("cfr-traversal"),
+            source: wgpu::ShaderSource::Wgsl(TRAVERSAL_SHADER.into()),
+        });
+        let traversal = STAGES
+            .iter()
+            .map(|entry_point| {
+                device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: Some(entry_point),
+                    layout: Some(&pipeline_layout),
+                    module: &module,
+                    entry_point,
+                })
+            })
+            .collect();
+        Some(GpuBackend {
+            context: Arc::new(GpuContext {
+                device,
+                queue,
+                pipeline,
+                traversal_layout,
+                traversal,
+                adapter_name: adapter.get_info().name,
+            }),
+        })
+    }
+
+    fn dispatch(&self, tables: &[(&[f64], usize)], buckets: usize) -> Option<Vec<f32>> {
+        let context = &self.context;
+        let mut regrets = Vec::new();
+        let mut offsets = Vec::with_capacity(tables.len());
+        let mut actions = Vec::with_capacity(tables.len());
+        for (table, count) in tables {
+            offsets.push(regrets.len() as u32);
+            actions.push(*count as u32);
+            regrets.extend(table.iter().map(|regret| *regret as f32));
+        }
+        if regrets.is_empty() {
+            return Some(Vec::new());
+        }
+
+        let invocations = (tables.len() * buckets) as u32;
+        let groups = invocations.div_ceil(WORKGROUP_SIZE);
+        let groups_x = groups.min(MAX_GROUPS_PER_DIM);
+        let groups_y = groups.div_ceil(groups_x);
+        let params = [
+            buckets as u32,
+            tables.len() as u32,
+            groups_x * WORKGROUP_SIZE,
+            0,
+        ];
+
+        let storage = |label, bytes: Vec<u8>| {
+            context
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(label),
+                    contents: &bytes,
+                    usage: wgpu::BufferUsages::STORAGE,
+                })
+        };
+        let regrets_buffer = storage("regrets", f32_bytes(&regrets));
+        let offsets_buffer = storage("offsets", u32_bytes(&offsets));
+        let actions_buffer = storage("actions", u32_bytes(&actions));
+        let params_buffer = context
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("params"),
+                contents: &u32_bytes(&params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let size = (regrets.len() * std::mem::size_of::<f32>()) as u64;
+        let output = context.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("strategy"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = context.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("strategy-readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = context
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("regret-match"),
+                layout: &context.pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: regrets_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: offsets_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: actions_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: output.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: params_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+        let mut encoder = context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("regret-match"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&context.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(groups_x, groups_y, 1);
+        }
+        encoder.copy_buffer_to_buffer(&output, 0, &staging, 0, size);
+        context.queue.submit(Some(encoder.finish()));
+
+        read_back(context, &staging)
+    }
+}
+
+impl CfrBackend for GpuBackend {
+    fn name(&self) -> &'static str {
+        "gpu"
+    }
+
+    fn regret_match_all(&self, tables: &[(&[f64], usize)], buckets: usize) -> Vec<Vec<f64>> {
+        let Some(flat) = self.dispatch(tables, buckets) else {
+            tracing::warn!("GPU regret matching failed; recomputing on the CPU");
+            return CpuBackend.regret_match_all(tables, buckets);
+        };
+        let mut offset = 0;
+        tables
+            .iter()
+            .map(|(regrets, _)| {
+                let strategy = flat[offset..offset + regrets.len()]
+                    .iter()
+                    .map(|value| *value as f64)
+                    .collect();
+                offset += regrets.len();
+                strategy
+            })
+            .collect()
+    }
+
+    fn traversal(&self, layout: &TraversalLayout) -> Option<Box<dyn DeviceTraversal>> {
+        let traversal = GpuTraversal::new(self.context.clone(), layout)?;
+        Some(Box::new(traversal))
+    }
+}
+
+struct Dispatch {
+    stage: Stage,
+    groups: (u32, u32),
+}
+
+// One solve's tree and tables on the device. The tables go up at the start of each iteration
+// and come back at its end, so the solver's own copy stays current between iterations.
+struct GpuTraversal {
+    context: Arc<GpuContext>,
+    bind_group: wgpu::BindGroup,
+    params: wgpu::Buffer,
+    // Per dispatch, the `Params` words but the weight, which changes every iteration.
+    records: Vec<[u32; 16]>,
+    params_stride: u64,
+    dispatches: Vec<Dispatch>,
+    tables: wgpu::Buffer,
+    staging: wgpu::Buffer,
+    // Table slots in all, and where each node's table starts.
+    slots: usize,
+    offsets: Vec<usize>,
+}
+
+impl GpuTraversal {
+    // None for trees with no decisions or too big for the device's buffers.
+    fn new(context: Arc<GpuContext>, layout: &TraversalLayout) -> Option<Self> {
+        let buckets = layout.buckets;
+        let nodes = layout.nodes.len();
+        let mut tree = vec![0u32; nodes * NODE_WORDS];
+        let mut children_list = Vec::new();
+        let mut payoffs = vec![0f32; nodes * PAYOFF_WORDS];
+        let mut offsets = vec![0; nodes];
+        let mut slots = 0;
+        let pairing = |pairing: &Pairing| match pairing {
+            Pairing::Order => 0,
+            Pairing::Showdowns => 1,
+            Pairing::AllIn => 2,
+        };
+        for (index, node) in layout.nodes.iter().enumerate() {
+            let record = &mut tree[index * NODE_WORDS..][..NODE_WORDS];
+            let payoff = &mut payoffs[index * PAYOFF_WORDS..][..PAYOFF_WORDS];
+            match node {
+                LayoutNode::Decision { player, children } => {
+                    record.copy_from_slice(&[
+                        *player as u32,
+                        children_list.len() as u32,
+                        children.len() as u32,
+                        slots as u32,
+                    ]);
+                    children_list.extend(children.iter().map(|&child| child as u32));
+                    offsets[index] = slots;
+                    slots += children.len() * buckets;
+                }
+                LayoutNode::Fold { net, pairing: by } => {
+                    record.copy_from_slice(&[FOLD, 0, 0, pairing(by)]);
+                    payoff[..2].copy_from_slice(&net.map(|net| net as f32));
+                }
+                LayoutNode::Showdown {
+                    win,
+                    tie,
+                    lose,
+                    pairing: by,
+                } => {
+                    record.copy_from_slice(&[SHOWDOWN, 0, 0, pairing(by)]);
+                    for (index, values) in [win, tie, lose].into_iter().enumerate() {
+                        payoff[index * 2..][..2].copy_from_slice(&values.map(|v| v as f32));
+                    }
+                }
+            }
+        }
+        if slots == 0 || layout.prior.len() != buckets {
+            return None;
+        }
+
+        // Decision nodes a level at a time from the root, and every terminal.
+        let mut levels: Vec<Vec<u32>> = Vec::new();
+        let mut terminals = Vec::new();
+        let mut frontier = vec![0];
+        while !frontier.is_empty() {
+            let mut level = Vec::new();
+            let mut next = Vec::new();
+            for node in frontier {
+                match &layout.nodes[node] {
+                    LayoutNode::Decision { children, .. } => {
+                        level.push(node as u32);
+                        next.extend(children.iter().copied());
+                    }
+                    _ => terminals.push(node as u32),
+                }
+            }
+            if !level.is_empty() {
+                levels.push(level);
+            }
+            frontier = next;
+        }
+        let children_at = tree.len();
+        tree.extend(children_list);
+        let mut list = |nodes: &[u32]| {
+            let first = tree.len() as u32;
+            tree.extend_from_slice(nodes);
+            (first, nodes.len() as u32)
+        };
+        let level_lists: Vec<(u32, u32)> = levels.iter().map(|level| list(level)).collect();
+        let decisions = list(&levels.concat());
+        let terminal_list = list(&terminals);
+
+        let mut numbers: Vec<f32> = layout.prior.iter().map(|&p| p as f32).collect();
+        let payoffs_at = numbers.len();
+        numbers.extend(payoffs);
+        let mut matrix_at = |matrix: &Option<Arc<ShowdownMatrix>>| {
+            let at = numbers.len();
+            if let Some(matrix) = matrix.as_ref().filter(|m| m.buckets == buckets) {
+                for values in [&matrix.weight, &matrix.win, &matrix.tie] {
+                    numbers.extend(values.iter().map(|&value| value as f32));
+                }
+            }
+            at
+        };
+        let showdowns_at = matrix_at(&layout.showdowns);
+        let all_in_at = matrix_at(&layout.all_in_showdowns);
+
+        let device = &context.device;
+        let limits = device.limits();
+        let float_bytes = std::mem::size_of::<f32>() as u64;
+        let tables_bytes = 5 * slots as u64 * float_bytes;
+        let work_bytes = 3 * (nodes * buckets) as u64 * float_bytes;
+        let largest = tables_bytes
+            .max(work_bytes)
+            .max(tree.len() as u64 * float_bytes)
+            .max(numbers.len() as u64 * float_bytes);
+        if largest > limits.max_storage_buffer_binding_size as u64
+            || largest > limits.max_buffer_size
+        {
+            return None;
+        }
+
+        let template = |first: u32, count: u32| {
+            [
+                buckets as u32,
+                0,
+                first,
+                count,
+                0,
+                slots as u32,
+                nodes as u32,
+                children_at as u32,
+                payoffs_at as u32,
+                showdowns_at as u32,
+                all_in_at as u32,
+                layout.floors_regrets as u32,
+                layout.predicts_regrets as u32,
+                0,
+                0,
+                0,
+            ]
+        };
+        let mut records = Vec::new();
+        let mut dispatches = Vec::new();
+        for traverser in [HERO, VILLAIN] {
+            let mut push = |stage: Stage, (first, count): (u32, u32), invocations: usize| {
+                let groups = (invocations as u32).div_ceil(WORKGROUP_SIZE);
+                let groups_x = groups.clamp(1, MAX_GROUPS_PER_DIM);
+                let mut record = template(first, count);
+                record[1] = traverser as u32;
+                record[4] = groups_x * WORKGROUP_SIZE;
+                records.push(record);
+                dispatches.push(Dispatch {
+                    stage,
+                    groups: (groups_x, groups.div_ceil(groups_x)),
+                });
+            };
+            if layout.predicts_regrets {
+                push(Stage::Predict, (0, slots as u32), slots);
+            }
+            let per_bucket =
+                |(first, count): (u32, u32)| ((first, count), count as usize * buckets);
+            let (list, invocations) = per_bucket(decisions);
+            push(Stage::RegretMatch, list, invocations);
+            push(Stage::Start, (0, 1), buckets);
+            for &level in &level_lists {
+                let (list, invocations) = per_bucket(level);
+                push(Stage::Forward, list, invocations);
+            }
+            if terminal_list.1 > 0 {
+                let (list, invocations) = per_bucket(terminal_list);
+                push(Stage::Terminal, list, invocations);
+            }
+            for &level in level_lists.iter().rev() {
+                let (list, invocations) = per_bucket(level);
+                push(Stage::Backward, list, invocations);
+            }
+        }
+
+        let params_stride = PARAMS_BYTES.max(limits.min_uniform_buffer_offset_alignment as u64);
+        let storage = |label, bytes: Vec<u8>, usage: wgpu::BufferUsages| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: &bytes,
+                usage: wgpu::BufferUsages::STORAGE | usage,
+            })
+        };
+        let tree_buffer = storage("cfr-tree", u32_bytes(&tree), wgpu::BufferUsages::empty());
+        let numbers_buffer = storage(
+            "cfr-numbers",
+            f32_bytes(&numbers),
+            wgpu::BufferUsages::empty(),
+        );
+        let tables = storage(
+            "cfr-tables",
+            vec![0; tables_bytes as usize],
+            wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+        );
+        let work = storage(
+            "cfr-work",
+            vec![0; work_bytes as usize],
+            wgpu::BufferUsages::empty(),
+        );
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("cfr-params"),
+            size: records.len() as u64 * params_stride,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("cfr-tables-readback"),
+            size: 3 * slots as u64 * float_bytes,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("cfr-traversal"),
+            layout: &context.traversal_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: tree_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: numbers_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: tables.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: work.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &params,
+                        offset: 0,
+                        size: NonZeroU64::new(PARAMS_BYTES),
+                    }),
+                },
+            ],
+        });
+        Some(GpuTraversal {
+            context,
+            bind_group,
+            params,
+            records,
+            params_stride,
+            dispatches,
+            tables,
+            staging,
+            slots,
+            offsets,
+        })
+    }
+
+    fn run(&self, tables: &mut [TableView<'_>], weight: f64) -> Option<()> {
+        let context = &self.context;
+        let slots = self.slots;
+        let mut host = vec![0f32; 3 * slots];
+        for (view, &offset) in tables.iter().zip(&self.offsets) {
+            let columns = [&*view.regrets, &*view.strategy_sum, &*view.last_deltas];
+            for (column, values) in columns.into_iter().enumerate() {
+                let to = &mut host[column * slots + offset..][..values.len()];
+                for (to, value) in to.iter_mut().zip(values) {
+                    *to = *value as f32;
+                }
+            }
+        }
+        context
+            .queue
+            .write_buffer(&self.tables, 0, &f32_bytes(&host));
+        let mut params = vec![0; self.records.len() * self.params_stride as usize];
+        for (index, record) in self.records.iter().enumerate() {
+            let mut record = *record;
+            record[13] = (weight as f32).to_bits();
+            params[index * self.params_stride as usize..][..PARAMS_BYTES as usize]
+                .copy_from_slice(&u32_bytes(&record));
+        }
+        context.queue.write_buffer(&self.params, 0, &params);
+
+        let mut encoder = context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("cfr-iteration"),
+                timestamp_writes: None,
+            });
+            for (index, dispatch) in self.dispatches.iter().enumerate() {
+                let offset = (index as u64 * self.params_stride) as u32;
+                pass.set_pipeline(&context.traversal[dispatch.stage as usize]);
+                pass.set_bind_group(0, &self.bind_group, &[offset]);
+                pass.dispatch_workgroups(dispatch.groups.0, dispatch.groups.1, 1);
+            }
+        }
+        encoder.copy_buffer_to_buffer(&self.tables, 0, &self.staging, 0, self.staging.size());
+        context.queue.submit(Some(encoder.finish()));
+
+        let host = read_back(context, &self.staging)?;
+        for (view, &offset) in tables.iter_mut().zip(&self.offsets) {
+            let columns = [
+                &mut *view.regrets,
+                &mut *view.strategy_sum,
+                &mut *view.last_deltas,
+            ];
+            for (column, values) in columns.into_iter().enumerate() {
+                let from = &host[column * slots + offset..][..values.len()];
+                for (value, from) in values.iter_mut().zip(from) {
+                    *value = *from as f64;
+                }
+            }
+        }
+        Some(())
+    }
+}
+
+impl DeviceTraversal for GpuTraversal {
+    fn iterate(&mut self, tables: &mut [TableView<'_>], weight: f64) -> bool {
+        tables.len() == self.offsets.len() && self.run(tables, weight).is_some()
+    }
+}
+
+fn read_back(context: &GpuContext, staging: &wgpu::Buffer) -> Option<Vec<f32>> {
+    let slice = staging.slice(..);
+    let (sender, receiver) = mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    context.device.poll(wgpu::Maintain::Wait);
+    receiver.recv().ok()?.ok()?;
+    let values = slice
+        .get_mapped_range()
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect();
+    staging.unmap();
+    Some(values)
+}
+
+fn f32_bytes(values: &[f32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+fn u32_bytes(values: &[u32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
@@ -0,0 +1,87 @@
+// Strategies pinned at chosen nodes and buckets. The locked player always plays the pinned
+// strategy there and never learns, so the rest of the tree converges to a best response to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockedNode {
+    // Action-major, like the solver's tables; only rows of locked buckets are meaningful.
+    pub strategy: Vec<f64>,
+    pub locked: Vec<bool>,
+}
+
+impl LockedNode {
+    pub fn is_locked(&self, bucket: usize) -> bool {
+        self.locked.get(bucket).copied().unwrap_or(false)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeLocks {
+    pub nodes: Vec<Option<LockedNode>>,
+}
+
+impl NodeLocks {
+    pub fn is_empty(&self) -> bool {
+        self.nodes.iter().all(Option::is_none)
+    }
+
+    pub fn node(&self, node: usize) -> Option<&LockedNode> {
+        self.nodes.get(node)?.as_ref()
+    }
+
+    // Pins `frequencies` (one per action, normalized here) for `buckets` at `node`, or for every
+    // bucket when `buckets` is empty. A later lock on the same bucket replaces an earlier one.
+    // Returns false when the frequencies are unusable.
+    pub fn lock(
+        &mut self,
+        node: usize,
+        total_buckets: usize,
+        frequencies: &[f64],
+        buckets: &[usize],
+    ) -> bool {
+        let total: f64 = frequencies.iter().map(|frequency| frequency.max(0.0)).sum();
+        if frequencies.is_empty() || total <= 0.0 {
+            return false;
+        }
+        if self.nodes.len() <= node {
+            self.nodes.resize(node + 1, None);
+        }
+        let actions = frequencies.len();
+        let entry = self.nodes[node].get_or_insert_with(|| LockedNode {
+            strategy: vec![0.0; actions * total_buckets],
+            locked: vec![false; total_buckets],
+        });
+        if entry.strategy.len() != actions * total_buckets {
+            return false;
+        }
+        let targets: Vec<usize> = if buckets.is_empty() {
+            (0..total_buckets).collect()
+        } else {
+            buckets
+                .iter()
+                .copied()
+                .filter(|bucket| *bucket < total_buckets)
+                .collect()
+        };
+        for bucket in targets {
+            entry.locked[bucket] = true;
+            for (action, frequency) in frequencies.iter().enumerate() {
+                entry.strategy[action * total_buckets + bucket] = frequency.max(0.0) / total;
+            }
+        }
+        true
+    }
+
+    // Overwrites the locked buckets' rows of an action-major `strategy` at `node`.
+    pub fn apply(&self, node: usize, strategy: &mut [f64], buckets: usize) {
+        let Some(locked) = self.node(node) else {
+            return;
+        };
+        if locked.strategy.len() != strategy.len() {
+            return;
+        }
+        for (index, slot) in strategy.iter_mut().enumerate() {
+            if locked.is_locked(index % buckets) {
+                *slot = locked.strategy[index];
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::game_tree::{GameTree, NodeKind, VILLAIN};
+
+// A fixed model of how villain plays, mixed into villain's strategy whenever hero is the one
+// learning. Weight 1 makes hero's solution a best response to the model; values in between give
+// a restricted Nash response, which exploits the model while staying hard to counter-exploit.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OpponentModel {
+    pub weight: f64,
+    // Action-major strategy per node; None leaves the node to the solver.
+    pub strategies: Vec<Option<Vec<f64>>>,
+}
+
+impl OpponentModel {
+    // `fold_frequency(label)` is how often villain folds facing the bet labelled `label`, or None
+    // to leave that spot alone. Folds come from the weakest buckets first, as a real player's
+    // would.
+    pub fn from_fold_frequencies(
+        tree: &GameTree,
+        buckets: usize,
+        weight: f64,
+        fold_frequency: impl Fn(&str) -> Option<f64>,
+    ) -> Self {
+        let buckets = buckets.max(1);
+        let strategies = tree
+            .nodes
+            .iter()
+            .map(|node| {
+                let NodeKind::Decision { player, children } = &node.kind else {
+                    return None;
+                };
+                if *player != VILLAIN {
+                    return None;
+                }
+                let fold = children
+                    .iter()
+                    .position(|child| tree.nodes[*child].label == "fold")?;
+                let frequency = fold_frequency(&node.label)?.clamp(0.0, 1.0);
+                let others = (children.len() - 1).max(1) as f64;
+                let mut strategy = vec![0.0; children.len() * buckets];
+                for bucket in 0..buckets {
+                    let folds = (frequency * buckets as f64 - bucket as f64).clamp(0.0, 1.0);
+                    for action in 0..children.len() {
+                        strategy[action * buckets + bucket] = if action == fold {
+                            folds
+                        } else {
+                            (1.0 - folds) / others
+                        };
+                    }
+                }
+                Some(strategy)
+            })
+            .collect();
+        Self {
+            weight: weight.clamp(0.0, 1.0),
+            strategies,
+        }
+    }
+
+    pub fn node(&self, node: usize) -> Option<&[f64]> {
+        self.strategies.get(node)?.as_deref()
+    }
+
+    // Blends the model into `strategy` (action-major) at `node`.
+    pub fn mix(&self, node: usize, strategy: &mut [f64]) {
+        let Some(fixed) = self.node(node) else {
+            return;
+        };
+        if fixed.len() != strategy.len() {
+            return;
+        }
+        for (slot, modelled) in strategy.iter_mut().zip(fixed) {
+            *slot = self.weight * modelled + (1.0 - self.weight) * *slot;
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::cfr::HandStat;
+
+pub const DEFAULT_PURIFY_THRESHOLD: f64 = 0.05;
+
+// For consumers that can't act on fine-grained mixes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Purification {
+    #[default]
+    None,
+    // Drop actions a hand takes less often than this and spread their weight over the rest.
+    Threshold(f64),
+    // Each hand takes only its most frequent action; ties go to the earlier action.
+    Argmax,
+}
+
+impl Purification {
+    // "" is no purification; a threshold of 0 means the default 5%.
+    pub fn parse(mode: &str, threshold: f64) -> Option<Self> {
+        match mode.to_ascii_lowercase().as_str() {
+            "" | "none" => Some(Purification::None),
+            "threshold" => Some(Purification::Threshold(if threshold > 0.0 {
+                threshold
+            } else {
+                DEFAULT_PURIFY_THRESHOLD
+            })),
+            "argmax" => Some(Purification::Argmax),
+            _ => None,
+        }
+    }
+}
+
+pub fn purify_row(row: &mut [f64], purification: Purification) {
+    match purification {
+        Purification::None => {}
+        Purification::Threshold(threshold) => {
+            let kept: f64 = row.iter().filter(|weight| **weight >= threshold).sum();
+            // Nothing clears the bar: fall back to the most frequent action.
+            if kept <= 0.0 {
+                return purify_row(row, Purification::Argmax);
+            }
+            for weight in row.iter_mut() {
+                *weight = if *weight >= threshold {
+                    *weight / kept
+                } else {
+                    0.0
+                };
+            }
+        }
+        Purification::Argmax => {
+            let best =
+                row.iter().enumerate().fold(
+                    0,
+                    |best, (action, weight)| {
+                        if *weight > row[best] {
+                            action
+                        } else {
+                            best
+                        }
+                    },
+                );
+            for (action, weight) in row.iter_mut().enumerate() {
+                *weight = if action == best { 1.0 } else { 0.0 };
+            }
+        }
+    }
+}
+
+// Purifies each bucket's strategy and recomputes its EV from the new mix.
+pub fn purify_hands(hands: &mut [HandStat], purification: Purification) {
+    if purification == Purification::None {
+        return;
+    }
+    for hand in hands {
+        let mut row: Vec<f64> = hand.actions.iter().map(|action| action.frequency).collect();
+        purify_row(&mut row, purification);
+        for (action, frequency) in hand.actions.iter_mut().zip(row) {
+            action.frequency = frequency;
+        }
+        hand.ev = hand
+            .actions
+            .iter()
+            .map(|action| action.frequency * action.ev)
+            .sum();
+    }
+}
+
+// Reported frequencies made readable: actions under `min_frequency` dropped, then every
+// frequency rounded to a multiple of `step`. 0 turns either off.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrequencyRounding {
+    pub step: f64,
+    pub min_frequency: f64,
+}
+
+// Keeps the frequencies summing to exactly 1. Rounding hands the units left over after rounding
+// down to the largest remainders, so the total never drifts; a step that doesn't divide 1 is
+// taken as the nearest one that does (0.03 rounds to thirty-thirds).
+pub fn round_frequencies(frequencies: &mut [f64], rounding: &FrequencyRounding) {
+    let total: f64 = frequencies.iter().sum();
+    if total <= 0.0 {
+        return;
+    }
+    let kept: f64 = frequencies
+        .iter()
+        .filter(|frequency| **frequency >= rounding.min_frequency)
+        .sum();
+    for frequency in frequencies.iter_mut() {
+        *frequency = if kept <= 0.0 {
+            *frequency / total
+        } else if *frequency >= rounding.min_frequency {
+            *frequency / kept
+        } else {
+            0.0
+        };
+    }
+    if rounding.step > 0.0 {
+        let units = (1.0 / rounding.step).round().max(1.0) as u64;
+        let scaled: Vec<f64> = frequencies
+            .iter()
+            .map(|frequency| frequency * units as f64)
+            .collect();
+        let mut counts: Vec<u64> = scaled.iter().map(|value| value.floor() as u64).collect();
+        let mut order: Vec<usize> = (0..scaled.len()).collect();
+        order.sort_by(|a, b| {
+            let remainder = |index: usize| scaled[index] - scaled[index].floor();
+            remainder(*b).total_cmp(&remainder(*a))
+        });
+        let left = units.saturating_sub(counts.iter().sum());
+        for index in order.into_iter().take(left as usize) {
+            counts[index] += 1;
+        }
+        for (frequency, count) in frequencies.iter_mut().zip(counts) {
+            *frequency = count as f64 / units as f64;
+        }
+    }
+    // Floating-point division can leave the sum an ulp off; the last action takes up the slack.
+    if let Some(last) = frequencies.iter().rposition(|frequency| *frequency > 0.0) {
+        let before: f64 = frequencies[..last].iter().sum();
+        frequencies[last] = 1.0 - before;
+    }
+}
@@ -0,0 +1,165 @@
+use crate::cfr::{run_iterations, CfrConfig, CfrSolver, Stopped};
+use crate::game_tree::{GameTree, NodeKind};
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RefinementConfig {
+    // Buckets the first pass solves with.
+    pub initial_buckets: usize,
+    // Most times the abstraction is split; each refinement gets an even share of the iterations.
+    pub rounds: usize,
+}
+
+impl Default for RefinementConfig {
+    fn default() -> Self {
+        Self {
+            initial_buckets: 4,
+            rounds: 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RefinementStats {
+    // Refinements that split at least one bucket.
+    pub rounds: usize,
+    pub initial_buckets: usize,
+    // Buckets the last pass solved with, at most the configured count.
+    pub final_buckets: usize,
+    pub splits: usize,
+}
+
+// Refined buckets stand for runs of the configured buckets, so anything that pins or weighs the
+// configured buckets one by one rules refinement out.
+pub fn applies(config: &CfrConfig) -> bool {
+    config.buckets > 1
+        && config.showdowns.is_none()
+        && config.bucket_weights.is_empty()
+        && config.opponent.is_none()
+        && config.locks.is_empty()
+        && config.constraints.is_empty()
+}
+
+// `buckets` split into `groups` runs of near-equal length, weakest first.
+pub fn coarse_groups(buckets: usize, groups: usize) -> Vec<Range<usize>> {
+    let groups = groups.clamp(1, buckets.max(1));
+    (0..groups)
+        .map(|group| group * buckets / groups..(group + 1) * buckets / groups)
+        .collect()
+}
+
+// How much each bucket's hands disagree about what to do: the spread of its average regrets
+// across actions, summed over decision nodes. A bucket whose hands want different things keeps
+// large regrets on several actions; one that has settled has them near zero.
+pub fn regret_variance(solver: &CfrSolver) -> Vec<f64> {
+    let buckets = solver.buckets();
+    let iterations = solver.iterations().max(1) as f64;
+    let mut variance = vec![0.0; buckets];
+    for (node, tree_node) in solver.tree().nodes.iter().enumerate() {
+        let NodeKind::Decision { children, .. } = &tree_node.kind else {
+            continue;
+        };
+        let actions = children.len();
+        if actions < 2 {
+            continue;
+        }
+        for (bucket, row) in solver.regrets(node).chunks(actions).enumerate() {
+            let mean = row.iter().sum::<f64>() / actions as f64 / iterations;
+            variance[bucket] += row
+                .iter()
+                .map(|regret| (regret / iterations - mean).powi(2))
+                .sum::<f64>()
+                / actions as f64;
+        }
+    }
+    variance
+}
+
+// Halves every group scoring at least the average of the groups that can still be split, and
+// returns the new groups with the index of the group each came from.
+pub fn split_groups(groups: &[Range<usize>], scores: &[f64]) -> (Vec<Range<usize>>, Vec<usize>) {
+    let splittable: Vec<f64> = groups
+        .iter()
+        .zip(scores)
+        .filter(|(group, _)| group.len() > 1)
+        .map(|(_, score)| *score)
+        .collect();
+    let threshold = splittable.iter().sum::<f64>() / splittable.len().max(1) as f64;
+    let mut refined = Vec::with_capacity(groups.len() * 2);
+    let mut parents = Vec::with_capacity(groups.len() * 2);
+    for (index, (group, score)) in groups.iter().zip(scores).enumerate() {
+        if group.len() > 1 && *score > 0.0 && *score >= threshold {
+            let middle = group.start + group.len() / 2;
+            refined.extend([group.start..middle, middle..group.end]);
+            parents.extend([index, index]);
+        } else {
+            refined.push(group.clone());
+            parents.push(index);
+        }
+    }
+    (refined, parents)
+}
+
+// Solves with coarse buckets first and refines the ones that matter while iterations remain,
+// then carries the result over to `config.buckets` so everything downstream reads the usual
+// resolution.
+pub fn solve_refined<'a>(
+    tree: &'a GameTree,
+    config: &CfrConfig,
+    refinement: &RefinementConfig,
+) -> (CfrSolver<'a>, bool, RefinementStats) {
+    let buckets = config.buckets.max(1);
+    let mut groups = coarse_groups(buckets, refinement.initial_buckets);
+    let mut stats = RefinementStats {
+        initial_buckets: groups.len(),
+        ..Default::default()
+    };
+    let mut solver = CfrSolver::new(tree, &grouped_config(config, &groups));
+    if let Some(prior) = &config.warm_start {
+        solver.warm_start(0, &prior.root_strategy, prior.weight);
+    }
+
+    let total = config.iterations.max(1);
+    let passes = refinement.rounds + 1;
+    let mut stopped = Stopped::Budget;
+    for pass in 0..passes {
+        let remaining = total.saturating_sub(solver.iterations());
+        stopped = run_iterations(&mut solver, config, remaining / (passes - pass));
+        if stopped != Stopped::Budget || pass + 1 == passes {
+            break;
+        }
+        // A wide group's disagreement covers more hands.
+        let scores: Vec<f64> = regret_variance(&solver)
+            .iter()
+            .zip(&groups)
+            .map(|(variance, group)| variance * group.len() as f64)
+            .collect();
+        let (refined, parents) = split_groups(&groups, &scores);
+        if refined.len() == groups.len() {
+            continue;
+        }
+        let mut finer = CfrSolver::new(tree, &grouped_config(config, &refined));
+        finer.inherit(&solver, &parents);
+        stats.rounds += 1;
+        stats.splits += refined.len() - groups.len();
+        solver = finer;
+        groups = refined;
+    }
+    stats.final_buckets = groups.len();
+
+    let parents: Vec<usize> = groups
+        .iter()
+        .enumerate()
+        .flat_map(|(index, group)| group.clone().map(move |_| index))
+        .collect();
+    let mut full = CfrSolver::new(tree, config);
+    full.inherit(&solver, &parents);
+    (full, stopped == Stopped::Converged, stats)
+}
+
+fn grouped_config(config: &CfrConfig, groups: &[Range<usize>]) -> CfrConfig {
+    let mut grouped = config.clone();
+    grouped.buckets = groups.len();
+    grouped.bucket_weights = groups.iter().map(|group| group.len() as f64).collect();
+    grouped
+}
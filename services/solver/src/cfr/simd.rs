@@ -0,0 +1,109 @@
+// Inner-loop kernels over the action-major infoset tables (slot = action * buckets + bucket), so
+// every per-action pass walks one contiguous run of buckets. The accumulators work in fixed
+// LANES-wide chunks, which stable rustc lowers to packed SIMD, with a scalar tail; the simple
+// element-wise maps are left to the autovectorizer.
+pub const LANES: usize = 4;
+
+// dst[i] += src[i]
+pub fn add(dst: &mut [f64], src: &[f64]) {
+    let mut dst_chunks = dst.chunks_exact_mut(LANES);
+    let mut src_chunks = src.chunks_exact(LANES);
+    for (d, s) in (&mut dst_chunks).zip(&mut src_chunks) {
+        for lane in 0..LANES {
+            d[lane] += s[lane];
+        }
+    }
+    for (d, s) in dst_chunks
+        .into_remainder()
+        .iter_mut()
+        .zip(src_chunks.remainder())
+    {
+        *d += s;
+    }
+}
+
+// dst[i] = max(dst[i] + src[i], 0)
+pub fn add_floored(dst: &mut [f64], src: &[f64]) {
+    let mut dst_chunks = dst.chunks_exact_mut(LANES);
+    let mut src_chunks = src.chunks_exact(LANES);
+    for (d, s) in (&mut dst_chunks).zip(&mut src_chunks) {
+        for lane in 0..LANES {
+            d[lane] = (d[lane] + s[lane]).max(0.0);
+        }
+    }
+    for (d, s) in dst_chunks
+        .into_remainder()
+        .iter_mut()
+        .zip(src_chunks.remainder())
+    {
+        *d = (*d + s).max(0.0);
+    }
+}
+
+// dst[i] += scale * a[i] * b[i]
+pub fn add_scaled_product(dst: &mut [f64], a: &[f64], b: &[f64], scale: f64) {
+    let mut dst_chunks = dst.chunks_exact_mut(LANES);
+    let mut a_chunks = a.chunks_exact(LANES);
+    let mut b_chunks = b.chunks_exact(LANES);
+    for ((d, x), y) in (&mut dst_chunks).zip(&mut a_chunks).zip(&mut b_chunks) {
+        for lane in 0..LANES {
+            d[lane] += scale * x[lane] * y[lane];
+        }
+    }
+    for ((d, x), y) in dst_chunks
+        .into_remainder()
+        .iter_mut()
+        .zip(a_chunks.remainder())
+        .zip(b_chunks.remainder())
+    {
+        *d += scale * x * y;
+    }
+}
+
+// out[i] = scale * a[i] * b[i]
+pub fn scaled_product(out: &mut [f64], a: &[f64], b: &[f64], scale: f64) {
+    for ((o, x), y) in out.iter_mut().zip(a).zip(b) {
+        *o = scale * x * y;
+    }
+}
+
+// out[i] = a[i] - b[i]
+pub fn difference(out: &mut [f64], a: &[f64], b: &[f64]) {
+    for ((o, x), y) in out.iter_mut().zip(a).zip(b) {
+        *o = x - y;
+    }
+}
+
+// Positive values scaled by `positive`, the rest by `negative`.
+pub fn scale_signed(values: &mut [f64], positive: f64, negative: f64) {
+    for value in values {
+        *value *= if *value > 0.0 { positive } else { negative };
+    }
+}
+
+pub fn scale(values: &mut [f64], factor: f64) {
+    for value in values {
+        *value *= factor;
+    }
+}
+
+// Regret matching for one infoset: each bucket plays its actions in proportion to their positive
+// regret, or uniformly when none is positive. `regrets` and `out` are action-major.
+pub fn regret_match(regrets: &[f64], actions: usize, buckets: usize, out: &mut [f64]) {
+    let mut totals = vec![0.0; buckets];
+    for action in 0..actions {
+        let row = &regrets[action * buckets..][..buckets];
+        let positive = &mut out[action * buckets..][..buckets];
+        for ((total, slot), regret) in totals.iter_mut().zip(positive.iter_mut()).zip(row) {
+            *slot = regret.max(0.0);
+            *total += *slot;
+        }
+    }
+    let uniform = 1.0 / actions.max(1) as f64;
+    for action in 0..actions {
+        let row = &mut out[action * buckets..][..buckets];
+        for (slot, total) in row.iter_mut().zip(&totals) {
+            *slot = if *total > 0.0 { *slot / total } else { uniform };
+        }
+    }
+}
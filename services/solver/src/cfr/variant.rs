@@ -0,0 +1,226 @@
+use crate::cfr::simd;
+
+// What distinguishes one CFR flavour from another. The traversal in CfrSolver is shared; it asks
+// the variant how to fold a regret delta in, how much weight this iteration's strategy gets in
+// the average, what to discount before the iteration starts, and whether to sample the
+// opponent's actions instead of walking all of them.
+pub trait CfrVariant: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // Folds one action's per-bucket deltas into its cumulative regrets.
+    fn update_regrets(&self, regrets: &mut [f64], deltas: &[f64]) {
+        if self.floors_regrets() {
+            simd::add_floored(regrets, deltas);
+        } else {
+            simd::add(regrets, deltas);
+        }
+    }
+
+    // CFR+ style: regrets never drop below zero.
+    fn floors_regrets(&self) -> bool {
+        false
+    }
+
+    // Weight of iteration `iteration` (1-based) in the average strategy.
+    fn strategy_weight(&self, iteration: usize) -> f64 {
+        let _ = iteration;
+        1.0
+    }
+
+    // Applied to the accumulated tables just before iteration `iteration` runs.
+    fn discount(&self, iteration: usize) -> Discount {
+        let _ = iteration;
+        Discount::NONE
+    }
+
+    fn samples_opponent(&self) -> bool {
+        false
+    }
+
+    // Predictive variants regret-match the cumulative regrets plus a guess at the next
+    // iteration's, taken to be the last iteration's.
+    fn predicts_regrets(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Discount {
+    pub positive_regret: f64,
+    pub negative_regret: f64,
+    pub strategy: f64,
+}
+
+impl Discount {
+    pub const NONE: Discount = Discount {
+        positive_regret: 1.0,
+        negative_regret: 1.0,
+        strategy: 1.0,
+    };
+
+    pub fn is_none(&self) -> bool {
+        *self == Discount::NONE
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VanillaCfr;
+
+impl CfrVariant for VanillaCfr {
+    fn name(&self) -> &'static str {
+        "vanilla"
+    }
+}
+
+// Regrets are floored at zero after every update and later iterations count linearly more in
+// the average.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CfrPlus;
+
+impl CfrVariant for CfrPlus {
+    fn name(&self) -> &'static str {
+        "cfr+"
+    }
+
+    fn floors_regrets(&self) -> bool {
+        true
+    }
+
+    fn strategy_weight(&self, iteration: usize) -> f64 {
+        iteration as f64
+    }
+}
+
+// Predictive CFR+ (Farina, Kroer & Sandholm): regrets are floored as in CFR+, but the strategy
+// played is regret-matched against the cumulative regrets plus the last iteration's, and the
+// average weights iteration t by t^2.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PredictiveCfrPlus;
+
+impl CfrVariant for PredictiveCfrPlus {
+    fn name(&self) -> &'static str {
+        "pcfr+"
+    }
+
+    fn floors_regrets(&self) -> bool {
+        true
+    }
+
+    fn strategy_weight(&self, iteration: usize) -> f64 {
+        (iteration * iteration) as f64
+    }
+
+    fn predicts_regrets(&self) -> bool {
+        true
+    }
+}
+
+// Discounted CFR (Brown & Sandholm): before iteration t, positive regrets are scaled by
+// (t-1)^a / ((t-1)^a + 1), negative ones by (t-1)^b / ((t-1)^b + 1), and the strategy sum by
+// ((t-1) / t)^g.
+#[derive(Clone, Copy, Debug)]
+pub struct DiscountedCfr {
+    pub alpha: f64,
+    pub beta: f64,
+    pub gamma: f64,
+}
+
+impl Default for DiscountedCfr {
+    fn default() -> Self {
+        Self {
+            alpha: 1.5,
+            beta: 0.0,
+            gamma: 2.0,
+        }
+    }
+}
+
+impl CfrVariant for DiscountedCfr {
+    fn name(&self) -> &'static str {
+        "dcfr"
+    }
+
+    fn discount(&self, iteration: usize) -> Discount {
+        if iteration <= 1 {
+            return Discount::NONE;
+        }
+        let previous = (iteration - 1) as f64;
+        let scale = |exponent: f64| {
+            let weight = previous.powf(exponent);
+            weight / (weight + 1.0)
+        };
+        Discount {
+            positive_regret: scale(self.alpha),
+            negative_regret: scale(self.beta),
+            strategy: (previous / iteration as f64).powf(self.gamma),
+        }
+    }
+}
+
+// External-sampling flavour: the opponent plays a single sampled action per visit, drawn from
+// the solver's seeded RNG, with the reach scaled so values stay unbiased.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SampledCfr;
+
+impl CfrVariant for SampledCfr {
+    fn name(&self) -> &'static str {
+        "sampled"
+    }
+
+    fn samples_opponent(&self) -> bool {
+        true
+    }
+}
+
+// The variants selectable by name from config and requests.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CfrVariantKind {
+    #[default]
+    Vanilla,
+    CfrPlus,
+    PredictiveCfrPlus,
+    Discounted,
+    Sampled,
+    // Experimental neural approximation; see cfr::deep.
+    Deep,
+}
+
+impl CfrVariantKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "vanilla" | "cfr" => Some(CfrVariantKind::Vanilla),
+            "cfr+" | "cfr-plus" | "cfrplus" => Some(CfrVariantKind::CfrPlus),
+            "pcfr+" | "pcfr" | "predictive-cfr+" | "pcfrplus" => {
+                Some(CfrVariantKind::PredictiveCfrPlus)
+            }
+            "dcfr" | "discounted" => Some(CfrVariantKind::Discounted),
+            "sampled" | "mccfr" | "external-sampling" => Some(CfrVariantKind::Sampled),
+            "deep" | "deep-cfr" => Some(CfrVariantKind::Deep),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CfrVariantKind::Vanilla => "vanilla",
+            CfrVariantKind::CfrPlus => "cfr+",
+            CfrVariantKind::PredictiveCfrPlus => "pcfr+",
+            CfrVariantKind::Discounted => "dcfr",
+            CfrVariantKind::Sampled => "sampled",
+            CfrVariantKind::Deep => "deep",
+        }
+    }
+
+    pub fn build(&self) -> Box<dyn CfrVariant> {
+        match self {
+            CfrVariantKind::Vanilla => Box::new(VanillaCfr),
+            CfrVariantKind::CfrPlus => Box::new(CfrPlus),
+            CfrVariantKind::PredictiveCfrPlus => Box::new(PredictiveCfrPlus),
+            CfrVariantKind::Discounted => Box::new(DiscountedCfr::default()),
+            CfrVariantKind::Sampled => Box::new(SampledCfr),
+            // Deep CFR trains networks in its own loop and only hands the tabular solver a
+            // finished average strategy to evaluate, so no update rule is needed here.
+            CfrVariantKind::Deep => Box::new(VanillaCfr),
+        }
+    }
+}
@@ -0,0 +1,854 @@
+use crate::abstraction::all_in::runout_count;
+use crate::abstraction::realization::Realization;
+use crate::abstraction::{GameVariant, SizingPolicy, Street};
+use crate::admission::AdmissionLimits;
+use crate::affinity::MAX_CORES;
+use crate::cards::board_size;
+use crate::cfr::backend::CfrBackendKind;
+use crate::cfr::variant::CfrVariantKind;
+use crate::cfr::DEFAULT_BUCKETS;
+use crate::game_tree::limits::TreeLimits;
+use crate::preflop::pushfold::{PushFoldOptions, MAX_PUSH_FOLD_STACK_BB};
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SolverConfig {
+    pub server: ServerConfig,
+    pub threads: ThreadConfig,
+    pub cfr: CfrDefaults,
+    pub cache: CacheConfig,
+    pub abstraction: AbstractionConfig,
+    pub realization: RealizationConfig,
+    pub push_fold: PushFoldConfig,
+    pub admission: AdmissionConfig,
+    pub tls: TlsConfig,
+    pub auth: AuthConfig,
+    pub limits: LimitsConfig,
+    pub jobs: JobsConfig,
+    pub history: HistoryConfig,
+    pub archive: ArchiveConfig,
+    pub profiling: ProfilingConfig,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub listen_addr: String,
+    pub metrics_port: u16,
+    // How long shutdown waits for in-flight solves before cancelling them.
+    pub shutdown_grace_ms: u64,
+    // Kept back from a caller's gRPC deadline to serialize and send the response.
+    pub deadline_slack_ms: u64,
+    // Serve the HTTP/JSON gateway for browsers on the gRPC port, behind the same TLS.
+    pub gateway: bool,
+    // Sent as Access-Control-Allow-Origin by the gateway; empty sends no CORS headers, so only
+    // same-origin pages can read its responses.
+    pub gateway_allow_origin: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ThreadConfig {
+    // 0 keeps the runtime default of one worker per core.
+    pub workers: usize,
+    // Threads sharing the boards of one multi-board solve; 0 means one per core.
+    pub solver: usize,
+    // Cores every service thread is pinned to, including the solves; empty leaves placement to
+    // the OS. With pinning, "one per core" above counts only these cores.
+    pub cores: Vec<usize>,
+}
+
+impl ThreadConfig {
+    // Cores the service runs on: the pinned ones, else every core it's allowed.
+    pub fn core_count(&self) -> usize {
+        if self.cores.is_empty() {
+            std::thread::available_parallelism().map_or(1, usize::from)
+        } else {
+            self.cores.len()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CfrDefaults {
+    pub variant: String,
+    // Where CFR iterations run; see CfrBackendKind.
+    pub backend: String,
+    pub pruning: bool,
+    pub prune_threshold: f64,
+    pub prune_recheck_interval: usize,
+    pub check_interval: usize,
+    pub sampling_baseline: bool,
+    // Responses without a target exploitability count as converged at or below this; 0 never
+    // does.
+    pub converged_exploitability: f64,
+}
+
+// What the solve cache is saved as inside `[cache] path`.
+pub const CACHE_FILE: &str = "solves.bin";
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CacheConfig {
+    // Directory the solve cache is saved to at shutdown and loaded from at startup; empty keeps
+    // it in memory only.
+    pub path: String,
+    pub max_entries: usize,
+    pub max_bytes: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AbstractionConfig {
+    pub buckets: usize,
+    pub merge_tolerance: f64,
+    // Build river buckets from the board's real combos, with card removal. Costs a pass over
+    // every pair of hands per solve.
+    pub card_removal: bool,
+    // Smallest size a pot, geometric or stack token can produce.
+    pub min_bet_bb: f64,
+    // Drop sizes beyond the effective stack instead of capping them to it.
+    pub reject_oversize: bool,
+    // Sizes at or above this share of the stack become all-ins; 1 leaves them alone.
+    pub all_in_threshold: f64,
+    // Most sizes above the pot in a tree; 0 keeps them all.
+    pub max_overbets: usize,
+    // Most raises per no-limit street, counting those made before the root; 0 lets villain only
+    // fold or call hero's bet.
+    pub max_raises: u32,
+    // All-in pots before the river are settled at equity over every runout when there are at
+    // most this many (every turn and river of a flop by default), else over this many sampled;
+    // 0 settles them like any other showdown. Timed solves deal for at most half the time before
+    // their soft deadline and settle on the runouts dealt by then.
+    pub all_in_runouts: usize,
+    // Solve with `refine_initial_buckets` first and split the buckets whose regrets disagree
+    // most, up to `refine_rounds` times, before reporting over `buckets`.
+    pub refine: bool,
+    pub refine_initial_buckets: usize,
+    pub refine_rounds: usize,
+}
+
+// Equity realization for trees that stop before the river; the factors scale each side's share
+// of a showdown by hand class and seat.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RealizationConfig {
+    pub enabled: bool,
+    pub pair: f64,
+    pub suited: f64,
+    pub offsuit: f64,
+    pub in_position: f64,
+    pub out_of_position: f64,
+    pub runouts: usize,
+}
+
+// Unopened and facing-a-jam preflop spots at or below `max_stack_bb` answered from a push/fold
+// equilibrium over the 169 hand classes instead of a tree.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PushFoldConfig {
+    pub enabled: bool,
+    pub max_stack_bb: f64,
+    pub iterations: usize,
+    pub runouts: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AdmissionConfig {
+    // 0 means one solve per available core.
+    pub max_concurrent: usize,
+    pub max_queued: usize,
+}
+
+// TLS is on once both a certificate and key are configured; a client CA additionally requires
+// callers to present a certificate signed by it (mTLS).
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+    pub client_ca_path: String,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LimitsConfig {
+    pub max_nodes: usize,
+    pub max_memory_mb: u64,
+    // Step oversized trees down the degradation ladder (fewer buckets, fewer sizes, a shallower
+    // tree, then a cached blueprint) instead of rejecting them.
+    pub coarsen: bool,
+    // Information sets CFR visits per millisecond on this host. With a rate set, a tree whose
+    // time budget can't cover `min_iterations` iterations steps down the ladder as well; 0 only
+    // looks at size.
+    pub info_sets_per_ms: f64,
+    pub min_iterations: usize,
+    // Solve with the fewest raises first, then one more at a time up to the fixed-limit cap or
+    // the no-limit `max_raises`, then with the next street's betting added on the flop and turn,
+    // each pass starting from the last, until the time budget runs out.
+    pub deepening: bool,
+    // A timed solve aims to stop after this share of its budget and never runs past the budget
+    // less `response_slack_ms`, which is kept to build and serialize the response.
+    pub soft_deadline_share: f64,
+    pub response_slack_ms: u64,
+}
+
+// Background solves started with SubmitSolve.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct JobsConfig {
+    // How long a finished job's result stays fetchable.
+    pub ttl_ms: u64,
+    pub max_jobs: usize,
+}
+
+// Every solve recorded to an SQLite database for auditing; off while `path` is empty. Needs the
+// `history` feature.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub path: String,
+    // Records older than this are deleted; 0 keeps them forever.
+    pub retention_days: u32,
+    // Also keep each solve's full response, not just its summary.
+    pub store_strategy: bool,
+}
+
+// Full solved trees uploaded after the response; off while `backend` is empty. "file" writes under
+// the directory in `location`; "s3" and "gcs" (which need the `archive` feature) write to the
+// bucket named there.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    pub backend: String,
+    pub location: String,
+    pub prefix: String,
+    // Smaller trees aren't archived.
+    pub min_nodes: usize,
+    // Uploads waiting beyond this many are dropped.
+    pub queue: usize,
+}
+
+// pprof CPU and heap profiles served on the metrics port; both need the `profiling` feature.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ProfilingConfig {
+    pub enabled: bool,
+    // Longest CPU profile a request may ask for.
+    pub max_seconds: u64,
+    // CPU samples per second.
+    pub frequency: i32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AuthConfig {
+    pub keys: Vec<ApiKeyConfig>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ApiKeyConfig {
+    pub name: String,
+    pub key: String,
+    // 0 leaves the key unlimited; burst defaults to one second's worth of requests.
+    pub rate_per_sec: f64,
+    pub burst: u32,
+}
+
+pub struct TlsMaterial {
+    pub cert_pem: Vec<u8>,
+    pub key_pem: Vec<u8>,
+    pub client_ca_pem: Option<Vec<u8>>,
+}
+
+impl TlsConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.cert_path.is_empty() && !self.key_path.is_empty()
+    }
+
+    pub fn requires_client_cert(&self) -> bool {
+        self.is_enabled() && !self.client_ca_path.is_empty()
+    }
+
+    pub fn load(&self) -> Result<Option<TlsMaterial>, ConfigError> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+        let client_ca_pem = if self.client_ca_path.is_empty() {
+            None
+        } else {
+            Some(read_file(&self.client_ca_path)?)
+        };
+        Ok(Some(TlsMaterial {
+            cert_pem: read_file(&self.cert_path)?,
+            key_pem: read_file(&self.key_path)?,
+            client_ca_pem,
+        }))
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: "0.0.0.0:50051".to_string(),
+            metrics_port: 9464,
+            shutdown_grace_ms: 30_000,
+            deadline_slack_ms: 25,
+            gateway: false,
+            gateway_allow_origin: String::new(),
+        }
+    }
+}
+
+impl Default for CfrDefaults {
+    fn default() -> Self {
+        Self {
+            variant: CfrVariantKind::default().as_str().to_string(),
+            backend: CfrBackendKind::default().as_str().to_string(),
+            pruning: true,
+            prune_threshold: 1.0,
+            prune_recheck_interval: 10,
+            check_interval: 10,
+            sampling_baseline: true,
+            converged_exploitability: 0.01,
+        }
+    }
+}
+
+impl CacheConfig {
+    pub fn file(&self) -> Option<PathBuf> {
+        (!self.path.is_empty()).then(|| Path::new(&self.path).join(CACHE_FILE))
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            path: "/cache".to_string(),
+            max_entries: 10_000,
+            max_bytes: 512 * 1024 * 1024,
+        }
+    }
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 0,
+            max_queued: 64,
+        }
+    }
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        let limits = TreeLimits::default();
+        Self {
+            max_nodes: limits.max_nodes,
+            max_memory_mb: limits.max_bytes / (1024 * 1024),
+            coarsen: limits.coarsen,
+            info_sets_per_ms: 0.0,
+            min_iterations: 100,
+            deepening: false,
+            soft_deadline_share: 0.8,
+            response_slack_ms: 5,
+        }
+    }
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            ttl_ms: 10 * 60 * 1000,
+            max_jobs: 1000,
+        }
+    }
+}
+
+impl HistoryConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.path.is_empty()
+    }
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            retention_days: 30,
+            store_strategy: false,
+        }
+    }
+}
+
+impl ArchiveConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.backend.is_empty()
+    }
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            backend: String::new(),
+            location: String::new(),
+            prefix: "solves/".to_string(),
+            min_nodes: 0,
+            queue: 64,
+        }
+    }
+}
+
+impl Default for ProfilingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_seconds: 60,
+            frequency: 99,
+        }
+    }
+}
+
+impl Default for AbstractionConfig {
+    fn default() -> Self {
+        Self {
+            buckets: DEFAULT_BUCKETS,
+            merge_tolerance: 0.0,
+            card_removal: false,
+            min_bet_bb: 0.5,
+            reject_oversize: false,
+            all_in_threshold: 1.0,
+            max_overbets: 0,
+            max_raises: 0,
+            // Every turn and river of a hold'em flop.
+            all_in_runouts: runout_count(
+                &GameVariant::Holdem.deck()[..board_size(Street::Flop)],
+                GameVariant::Holdem,
+            ),
+            refine: false,
+            refine_initial_buckets: 4,
+            refine_rounds: 3,
+        }
+    }
+}
+
+impl RealizationConfig {
+    pub fn realization(&self) -> Realization {
+        Realization {
+            pair: self.pair,
+            suited: self.suited,
+            offsuit: self.offsuit,
+            in_position: self.in_position,
+            out_of_position: self.out_of_position,
+            runouts: self.runouts,
+        }
+    }
+}
+
+impl Default for RealizationConfig {
+    fn default() -> Self {
+        let defaults = Realization::default();
+        Self {
+            enabled: false,
+            pair: defaults.pair,
+            suited: defaults.suited,
+            offsuit: defaults.offsuit,
+            in_position: defaults.in_position,
+            out_of_position: defaults.out_of_position,
+            runouts: defaults.runouts,
+        }
+    }
+}
+
+impl PushFoldConfig {
+    // Thresholds are swept a big blind at a time up to the largest stack the fast path takes.
+    pub fn options(&self) -> PushFoldOptions {
+        PushFoldOptions {
+            iterations: self.iterations,
+            runouts: self.runouts,
+            seed: 0,
+            threshold_stacks_bb: (1..=self.max_stack_bb.floor() as u32)
+                .map(f64::from)
+                .collect(),
+        }
+    }
+}
+
+impl Default for PushFoldConfig {
+    fn default() -> Self {
+        let defaults = PushFoldOptions::default();
+        Self {
+            enabled: false,
+            max_stack_bb: MAX_PUSH_FOLD_STACK_BB,
+            iterations: defaults.iterations,
+            runouts: defaults.runouts,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io { path: String, message: String },
+    Parse(String),
+    InvalidValue { key: String, value: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, message } => write!(f, "cannot read {}: {}", path, message),
+            ConfigError::Parse(message) => write!(f, "invalid config file: {}", message),
+            ConfigError::InvalidValue { key, value } => {
+                write!(f, "invalid value '{}' for {}", value, key)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl SolverConfig {
+    // Defaults, then the TOML file, then environment overrides.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut config = match path {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+        config.apply_env(|key| std::env::var(key).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|error| ConfigError::Io {
+            path: path.display().to_string(),
+            message: error.to_string(),
+        })?;
+        Self::from_toml(&text)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, ConfigError> {
+        toml::from_str(text).map_err(|error| ConfigError::Parse(error.to_string()))
+    }
+
+    // SOLVER_ADDR wins over SOLVER_PORT, matching the variables the service has always read.
+    pub fn apply_env<F>(&mut self, lookup: F) -> Result<(), ConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(addr) = lookup("SOLVER_ADDR") {
+            self.server.listen_addr = addr;
+        } else if let Some(port) = lookup("SOLVER_PORT") {
+            let port: u16 = parse_value("SOLVER_PORT", &port)?;
+            self.server.listen_addr = format!("0.0.0.0:{}", port);
+        }
+        if let Some(port) = lookup("SOLVER_METRICS_PORT") {
+            self.server.metrics_port = parse_value("SOLVER_METRICS_PORT", &port)?;
+        }
+        if let Some(slack) = lookup("SOLVER_DEADLINE_SLACK_MS") {
+            self.server.deadline_slack_ms = parse_value("SOLVER_DEADLINE_SLACK_MS", &slack)?;
+        }
+        if let Some(workers) = lookup("SOLVER_WORKER_THREADS") {
+            self.threads.workers = parse_value("SOLVER_WORKER_THREADS", &workers)?;
+        }
+        if let Some(threads) = lookup("SOLVER_THREADS") {
+            self.threads.solver = parse_value("SOLVER_THREADS", &threads)?;
+        }
+        if let Some(cores) = lookup("SOLVER_CORES") {
+            self.threads.cores = parse_cores(&cores)?;
+        }
+        if let Some(variant) = lookup("SOLVER_CFR_VARIANT") {
+            self.cfr.variant = variant;
+        }
+        if let Some(backend) = lookup("SOLVER_CFR_BACKEND") {
+            self.cfr.backend = backend;
+        }
+        if let Some(path) = lookup("SOLVER_CACHE_PATH") {
+            self.cache.path = path;
+        }
+        if let Some(entries) = lookup("SOLVER_CACHE_ENTRIES") {
+            self.cache.max_entries = parse_value("SOLVER_CACHE_ENTRIES", &entries)?;
+        }
+        if let Some(buckets) = lookup("SOLVER_BUCKETS") {
+            self.abstraction.buckets = parse_value("SOLVER_BUCKETS", &buckets)?;
+        }
+        if let Some(limit) = lookup("SOLVER_MAX_CONCURRENT") {
+            self.admission.max_concurrent = parse_value("SOLVER_MAX_CONCURRENT", &limit)?;
+        }
+        if let Some(path) = lookup("SOLVER_TLS_CERT") {
+            self.tls.cert_path = path;
+        }
+        if let Some(path) = lookup("SOLVER_TLS_KEY") {
+            self.tls.key_path = path;
+        }
+        if let Some(path) = lookup("SOLVER_TLS_CLIENT_CA") {
+            self.tls.client_ca_path = path;
+        }
+        if let Some(nodes) = lookup("SOLVER_MAX_NODES") {
+            self.limits.max_nodes = parse_value("SOLVER_MAX_NODES", &nodes)?;
+        }
+        if let Some(megabytes) = lookup("SOLVER_MAX_MEMORY_MB") {
+            self.limits.max_memory_mb = parse_value("SOLVER_MAX_MEMORY_MB", &megabytes)?;
+        }
+        if let Some(keys) = lookup("SOLVER_API_KEYS") {
+            self.auth.keys = parse_api_keys(&keys)?;
+        }
+        if let Some(limit) = lookup("SOLVER_MAX_QUEUED") {
+            self.admission.max_queued = parse_value("SOLVER_MAX_QUEUED", &limit)?;
+        }
+        if let Some(ttl) = lookup("SOLVER_JOB_TTL_MS") {
+            self.jobs.ttl_ms = parse_value("SOLVER_JOB_TTL_MS", &ttl)?;
+        }
+        if let Some(path) = lookup("SOLVER_HISTORY_PATH") {
+            self.history.path = path;
+        }
+        if let Some(backend) = lookup("SOLVER_ARCHIVE_BACKEND") {
+            self.archive.backend = backend;
+        }
+        if let Some(location) = lookup("SOLVER_ARCHIVE_LOCATION") {
+            self.archive.location = location;
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self
+            .server
+            .listen_addr
+            .parse::<std::net::SocketAddr>()
+            .is_err()
+        {
+            return Err(invalid("server.listen_addr", &self.server.listen_addr));
+        }
+        if let Some(core) = self.threads.cores.iter().find(|core| **core >= MAX_CORES) {
+            return Err(invalid("threads.cores", &core.to_string()));
+        }
+        if self.variant().is_none() {
+            return Err(invalid("cfr.variant", &self.cfr.variant));
+        }
+        if self.backend().is_none() {
+            return Err(invalid("cfr.backend", &self.cfr.backend));
+        }
+        if self.abstraction.buckets < 2 {
+            return Err(invalid(
+                "abstraction.buckets",
+                &self.abstraction.buckets.to_string(),
+            ));
+        }
+        if self.abstraction.refine_initial_buckets == 0 {
+            return Err(invalid(
+                "abstraction.refine_initial_buckets",
+                &self.abstraction.refine_initial_buckets.to_string(),
+            ));
+        }
+        if !(0.0..1.0).contains(&self.abstraction.merge_tolerance) {
+            return Err(invalid(
+                "abstraction.merge_tolerance",
+                &self.abstraction.merge_tolerance.to_string(),
+            ));
+        }
+        if !(self.abstraction.min_bet_bb >= 0.0 && self.abstraction.min_bet_bb.is_finite()) {
+            return Err(invalid(
+                "abstraction.min_bet_bb",
+                &self.abstraction.min_bet_bb.to_string(),
+            ));
+        }
+        if !(self.abstraction.all_in_threshold > 0.0 && self.abstraction.all_in_threshold <= 1.0) {
+            return Err(invalid(
+                "abstraction.all_in_threshold",
+                &self.abstraction.all_in_threshold.to_string(),
+            ));
+        }
+        let realization = &self.realization;
+        for (key, factor) in [
+            ("realization.pair", realization.pair),
+            ("realization.suited", realization.suited),
+            ("realization.offsuit", realization.offsuit),
+            ("realization.in_position", realization.in_position),
+            ("realization.out_of_position", realization.out_of_position),
+        ] {
+            if !(factor > 0.0 && factor.is_finite()) {
+                return Err(invalid(key, &factor.to_string()));
+            }
+        }
+        if !(self.push_fold.max_stack_bb >= 1.0
+            && self.push_fold.max_stack_bb <= MAX_PUSH_FOLD_STACK_BB)
+        {
+            return Err(invalid(
+                "push_fold.max_stack_bb",
+                &self.push_fold.max_stack_bb.to_string(),
+            ));
+        }
+        if self.tls.cert_path.is_empty() != self.tls.key_path.is_empty() {
+            return Err(invalid(
+                "tls",
+                "cert_path and key_path must be set together",
+            ));
+        }
+        if !self.tls.client_ca_path.is_empty() && !self.tls.is_enabled() {
+            return Err(invalid("tls.client_ca_path", &self.tls.client_ca_path));
+        }
+        if self.cfr.prune_threshold < 0.0 {
+            return Err(invalid(
+                "cfr.prune_threshold",
+                &self.cfr.prune_threshold.to_string(),
+            ));
+        }
+        if !(self.cfr.converged_exploitability >= 0.0
+            && self.cfr.converged_exploitability.is_finite())
+        {
+            return Err(invalid(
+                "cfr.converged_exploitability",
+                &self.cfr.converged_exploitability.to_string(),
+            ));
+        }
+        if !(self.limits.soft_deadline_share > 0.0 && self.limits.soft_deadline_share <= 1.0) {
+            return Err(invalid(
+                "limits.soft_deadline_share",
+                &self.limits.soft_deadline_share.to_string(),
+            ));
+        }
+        if !(self.limits.info_sets_per_ms >= 0.0 && self.limits.info_sets_per_ms.is_finite()) {
+            return Err(invalid(
+                "limits.info_sets_per_ms",
+                &self.limits.info_sets_per_ms.to_string(),
+            ));
+        }
+        if self.jobs.max_jobs == 0 {
+            return Err(invalid("jobs.max_jobs", "0"));
+        }
+        if !matches!(self.archive.backend.as_str(), "" | "file" | "s3" | "gcs") {
+            return Err(invalid("archive.backend", &self.archive.backend));
+        }
+        if self.archive.is_enabled() && self.archive.location.is_empty() {
+            return Err(invalid("archive.location", ""));
+        }
+        if !(1..=1000).contains(&self.profiling.frequency) {
+            return Err(invalid(
+                "profiling.frequency",
+                &self.profiling.frequency.to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn variant(&self) -> Option<CfrVariantKind> {
+        CfrVariantKind::parse(&self.cfr.variant)
+    }
+
+    pub fn backend(&self) -> Option<CfrBackendKind> {
+        CfrBackendKind::parse(&self.cfr.backend)
+    }
+
+    pub fn tree_limits(&self) -> TreeLimits {
+        TreeLimits {
+            max_nodes: self.limits.max_nodes,
+            max_bytes: self.limits.max_memory_mb.saturating_mul(1024 * 1024),
+            max_info_sets: 0,
+            coarsen: self.limits.coarsen,
+        }
+    }
+
+    pub fn sizing_policy(&self) -> SizingPolicy {
+        SizingPolicy {
+            min_bet_bb: self.abstraction.min_bet_bb,
+            reject_oversize: self.abstraction.reject_oversize,
+            all_in_threshold: self.abstraction.all_in_threshold,
+            max_overbets: self.abstraction.max_overbets,
+        }
+    }
+
+    pub fn admission_limits(&self) -> AdmissionLimits {
+        let max_concurrent = if self.admission.max_concurrent > 0 {
+            self.admission.max_concurrent
+        } else {
+            self.threads.core_count()
+        };
+        AdmissionLimits {
+            max_concurrent,
+            max_queued: self.admission.max_queued,
+        }
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, ConfigError> {
+    value.trim().parse().map_err(|_| invalid(key, value))
+}
+
+// A taskset-style core list: single cores and inclusive ranges separated by commas, e.g. `2,4-7`.
+fn parse_cores(raw: &str) -> Result<Vec<usize>, ConfigError> {
+    let mut cores = Vec::new();
+    for entry in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+    {
+        match entry.split_once('-') {
+            Some((first, last)) => {
+                let first: usize = parse_value("SOLVER_CORES", first)?;
+                let last: usize = parse_value("SOLVER_CORES", last)?;
+                // Checked before expanding, so "0-99999999999" can't allocate a huge list.
+                if first > last || last >= MAX_CORES {
+                    return Err(invalid("SOLVER_CORES", raw));
+                }
+                cores.extend(first..=last);
+            }
+            None => {
+                let core: usize = parse_value("SOLVER_CORES", entry)?;
+                if core >= MAX_CORES {
+                    return Err(invalid("SOLVER_CORES", raw));
+                }
+                cores.push(core);
+            }
+        }
+    }
+    cores.sort_unstable();
+    cores.dedup();
+    Ok(cores)
+}
+
+// `name:key[:rate_per_sec]` entries separated by commas.
+fn parse_api_keys(raw: &str) -> Result<Vec<ApiKeyConfig>, ConfigError> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let mut parts = entry.splitn(3, ':');
+            let name = parts.next().unwrap_or_default();
+            let key = parts.next().unwrap_or_default();
+            if name.is_empty() || key.is_empty() {
+                return Err(invalid("SOLVER_API_KEYS", name));
+            }
+            let rate_per_sec = match parts.next() {
+                Some(rate) => parse_value("SOLVER_API_KEYS", rate)?,
+                None => 0.0,
+            };
+            Ok(ApiKeyConfig {
+                name: name.to_string(),
+                key: key.to_string(),
+                rate_per_sec,
+                burst: 0,
+            })
+        })
+        .collect()
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, ConfigError> {
+    std::fs::read(path).map_err(|error| ConfigError::Io {
+        path: path.to_string(),
+        message: error.to_string(),
+    })
+}
+
+fn invalid(key: &str, value: &str) -> ConfigError {
+    ConfigError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+    }
+}
@@ -0,0 +1,150 @@
+use crate::budget::BudgetClock;
+use crate::cache::effort_free;
+use crate::error::SolverError;
+use crate::solver_proto::{SubgameRequest, SubgameResponse};
+use prost::Message;
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Instant;
+
+type SolveResult = Result<SubgameResponse, SolverError>;
+
+#[derive(Default)]
+struct Shared {
+    effort: Effort,
+    state: Mutex<SharedState>,
+    finished: Condvar,
+}
+
+// How hard a request asks for its spot to be solved.
+#[derive(Clone, Copy, Default)]
+struct Effort {
+    iterations: u64,
+    target_exploitability: f64,
+    // When the time budget runs out; None for a solve bounded by iterations only.
+    deadline: Option<Instant>,
+}
+
+impl Effort {
+    fn of(request: &SubgameRequest) -> Self {
+        Self {
+            iterations: request.budget_iterations,
+            target_exploitability: request.target_exploitability,
+            deadline: BudgetClock::new(request.budget_ms).deadline(),
+        }
+    }
+
+    // Whether a solve run this hard answers `wanted` as well as its own solve would: at least as
+    // many iterations, a target at least as tight, and done no later than `wanted` needs it.
+    fn covers(&self, wanted: &Effort) -> bool {
+        let iterations = self.iterations == wanted.iterations
+            || (wanted.iterations > 0 && self.iterations > wanted.iterations);
+        let target = self.target_exploitability == wanted.target_exploitability
+            || (wanted.target_exploitability > 0.0
+                && self.target_exploitability > 0.0
+                && self.target_exploitability < wanted.target_exploitability);
+        let deadline = match (self.deadline, wanted.deadline) {
+            (None, None) => true,
+            (Some(ours), Some(theirs)) => ours <= theirs,
+            _ => false,
+        };
+        iterations && target && deadline
+    }
+}
+
+#[derive(Default)]
+struct SharedState {
+    done: bool,
+    // None once done means the solve panicked, and each waiter solves for itself.
+    result: Option<SolveResult>,
+}
+
+// Coalesces identical requests that arrive while one of them is solving: the first runs the
+// solve and the rest wait for its result. Like the cache, requests are identical when they ask
+// for the same spot whatever their budgets, which each caller's deadline has already trimmed.
+// A request only waits on a solve at least as thorough as its own that is due to finish within
+// its time budget, and otherwise solves alongside it, so a seeded request with an iteration
+// budget always gets the same answer.
+#[derive(Default)]
+pub struct SolveDedup {
+    in_flight: Mutex<HashMap<Vec<u8>, Arc<Shared>>>,
+}
+
+impl SolveDedup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.lock().len()
+    }
+
+    // Runs `solve`, or waits for an identical request already running it. The flag is true
+    // when the result was shared from another request.
+    pub fn run<F>(&self, request: &SubgameRequest, solve: F) -> (SolveResult, bool)
+    where
+        F: FnOnce() -> SolveResult,
+    {
+        let key = effort_free(request).encode_to_vec();
+        let effort = Effort::of(request);
+        let mut in_flight = self.lock();
+        if let Some(shared) = in_flight.get(&key).cloned() {
+            drop(in_flight);
+            if !shared.effort.covers(&effort) {
+                return (solve(), false);
+            }
+            let mut state = lock(&shared.state);
+            while !state.done {
+                state = shared
+                    .finished
+                    .wait(state)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+            if let Some(result) = state.result.clone() {
+                return (result, true);
+            }
+            drop(state);
+            return (solve(), false);
+        }
+        let shared = Arc::new(Shared {
+            effort,
+            ..Shared::default()
+        });
+        in_flight.insert(key.clone(), shared.clone());
+        drop(in_flight);
+
+        // Releases the waiters even if the solve panics.
+        let leader = Leader {
+            dedup: self,
+            key,
+            shared,
+        };
+        let result = solve();
+        lock(&leader.shared.state).result = Some(result.clone());
+        (result, false)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Vec<u8>, Arc<Shared>>> {
+        lock(&self.in_flight)
+    }
+}
+
+struct Leader<'a> {
+    dedup: &'a SolveDedup,
+    key: Vec<u8>,
+    shared: Arc<Shared>,
+}
+
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        self.dedup.lock().remove(&self.key);
+        lock(&self.shared.state).done = true;
+        self.shared.finished.notify_all();
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,189 @@
+use crate::cards::CardError;
+use crate::solver_proto::ErrorDetail;
+use crate::validation::RequestIssue;
+use prost::Message;
+use std::fmt;
+use tonic::{Code, Status};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SolverError {
+    InvalidGameState(String),
+    InvalidCards(CardError),
+    EmptyActionSet,
+    NoLegalActions,
+    BudgetExhausted { budget_ms: i32 },
+    TreeTooLarge { nodes: usize, limit: usize },
+    MemoryBudgetExceeded { bytes: u64, limit: u64 },
+    // Even the coarsest tree needs more work per iteration than the time budget covers.
+    BudgetTooSmall { info_sets: usize, limit: usize },
+    // Blocking issues found by the validation pass.
+    InvalidRequest(Vec<RequestIssue>),
+    QueueFull { limit: usize },
+    QueueTimeout { waited_ms: u64 },
+    ShuttingDown,
+    JobNotFound { job_id: String },
+    // The job hasn't finished; poll its status until it has.
+    JobPending { job_id: String },
+    TooManyJobs { limit: usize },
+}
+
+impl SolverError {
+    // Stable, machine-readable reason; clients should match on this rather than the message.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            SolverError::InvalidGameState(_) => "INVALID_GAME_STATE",
+            SolverError::InvalidCards(_) => "INVALID_CARDS",
+            SolverError::EmptyActionSet => "EMPTY_ACTION_SET",
+            SolverError::NoLegalActions => "NO_LEGAL_ACTIONS",
+            SolverError::BudgetExhausted { .. } => "BUDGET_EXHAUSTED",
+            SolverError::TreeTooLarge { .. } => "TREE_TOO_LARGE",
+            SolverError::MemoryBudgetExceeded { .. } => "MEMORY_BUDGET_EXCEEDED",
+            SolverError::BudgetTooSmall { .. } => "BUDGET_TOO_SMALL",
+            SolverError::QueueFull { .. } => "QUEUE_FULL",
+            SolverError::QueueTimeout { .. } => "QUEUE_TIMEOUT",
+            SolverError::ShuttingDown => "SHUTTING_DOWN",
+            SolverError::JobNotFound { .. } => "JOB_NOT_FOUND",
+            SolverError::JobPending { .. } => "JOB_PENDING",
+            SolverError::TooManyJobs { .. } => "TOO_MANY_JOBS",
+            SolverError::InvalidRequest(issues) => issues
+                .first()
+                .map(|issue| issue.reason)
+                .unwrap_or("INVALID_REQUEST"),
+        }
+    }
+
+    pub fn field(&self) -> &str {
+        match self {
+            SolverError::InvalidGameState(_) => "game_state_json",
+            SolverError::InvalidCards(_) => "board",
+            SolverError::EmptyActionSet | SolverError::NoLegalActions => "action_set",
+            SolverError::BudgetExhausted { .. }
+            | SolverError::BudgetTooSmall { .. }
+            | SolverError::QueueTimeout { .. } => "budget_ms",
+            SolverError::TreeTooLarge { .. } | SolverError::MemoryBudgetExceeded { .. } => {
+                "action_set"
+            }
+            SolverError::JobNotFound { .. } | SolverError::JobPending { .. } => "job_id",
+            SolverError::QueueFull { .. }
+            | SolverError::ShuttingDown
+            | SolverError::TooManyJobs { .. } => "",
+            SolverError::InvalidRequest(issues) => issues
+                .first()
+                .map(|issue| issue.field.as_str())
+                .unwrap_or(""),
+        }
+    }
+
+    pub fn code(&self) -> Code {
+        match self {
+            SolverError::InvalidGameState(_)
+            | SolverError::InvalidCards(_)
+            | SolverError::EmptyActionSet
+            | SolverError::NoLegalActions
+            | SolverError::InvalidRequest(_) => Code::InvalidArgument,
+            SolverError::BudgetExhausted { .. } | SolverError::BudgetTooSmall { .. } => {
+                Code::DeadlineExceeded
+            }
+            SolverError::TreeTooLarge { .. }
+            | SolverError::MemoryBudgetExceeded { .. }
+            | SolverError::QueueFull { .. }
+            | SolverError::QueueTimeout { .. }
+            | SolverError::TooManyJobs { .. } => Code::ResourceExhausted,
+            SolverError::ShuttingDown => Code::Unavailable,
+            SolverError::JobNotFound { .. } => Code::NotFound,
+            SolverError::JobPending { .. } => Code::FailedPrecondition,
+        }
+    }
+
+    pub fn detail(&self) -> ErrorDetail {
+        ErrorDetail {
+            reason: self.reason().to_string(),
+            field: self.field().to_string(),
+            message: self.to_string(),
+            issues: match self {
+                SolverError::InvalidRequest(issues) => {
+                    issues.iter().map(RequestIssue::to_proto).collect()
+                }
+                _ => Vec::new(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverError::InvalidGameState(error) => {
+                write!(f, "game_state_json is not valid: {}", error)
+            }
+            SolverError::InvalidCards(error) => write!(f, "{}", error),
+            SolverError::EmptyActionSet => write!(f, "no usable action tokens were supplied"),
+            SolverError::NoLegalActions => {
+                write!(f, "none of the requested actions are legal in this spot")
+            }
+            SolverError::BudgetExhausted { budget_ms } => {
+                write!(f, "budget of {}ms leaves no time to solve", budget_ms)
+            }
+            SolverError::TreeTooLarge { nodes, limit } => {
+                write!(f, "tree needs {} nodes, limit is {}", nodes, limit)
+            }
+            SolverError::MemoryBudgetExceeded { bytes, limit } => {
+                write!(f, "tree needs {} bytes, memory budget is {}", bytes, limit)
+            }
+            SolverError::BudgetTooSmall { info_sets, limit } => {
+                write!(
+                    f,
+                    "tree visits {} information sets per iteration, the time budget covers {}",
+                    info_sets, limit
+                )
+            }
+            SolverError::QueueFull { limit } => {
+                write!(f, "solver is busy and its queue of {} is full", limit)
+            }
+            SolverError::QueueTimeout { waited_ms } => {
+                write!(
+                    f,
+                    "waited {}ms for a solver slot without getting one",
+                    waited_ms
+                )
+            }
+            SolverError::ShuttingDown => write!(f, "solver is shutting down"),
+            SolverError::JobNotFound { job_id } => {
+                write!(f, "no solve job {}; it may have expired", job_id)
+            }
+            SolverError::JobPending { job_id } => {
+                write!(f, "solve job {} has not finished", job_id)
+            }
+            SolverError::TooManyJobs { limit } => {
+                write!(f, "{} solve jobs are already stored", limit)
+            }
+            SolverError::InvalidRequest(issues) => {
+                let messages: Vec<String> = issues
+                    .iter()
+                    .map(|issue| format!("{}: {}", issue.field, issue.message))
+                    .collect();
+                write!(f, "invalid request: {}", messages.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
+
+impl From<CardError> for SolverError {
+    fn from(error: CardError) -> Self {
+        SolverError::InvalidCards(error)
+    }
+}
+
+// The encoded ErrorDetail travels in the status details so callers can decode it instead of
+// parsing the message.
+impl From<SolverError> for Status {
+    fn from(error: SolverError) -> Self {
+        Status::with_details(
+            error.code(),
+            error.to_string(),
+            error.detail().encode_to_vec().into(),
+        )
+    }
+}
@@ -1,39 +1,331 @@
-use crate::abstraction::ActionSpec;
+use crate::abstraction::{ActionSpec, BettingStructure, GameStateSummary};
+use crate::game_tree::chips::TreeChips;
+use crate::game_tree::eval::RakeModel;
+use crate::game_tree::fixed_limit::build_limit_nodes;
+use crate::game_tree::legality::{legalize_actions, BettingContext, LegalizationReport};
+use crate::game_tree::reraise::Reraises;
+
+pub mod chips;
+pub mod eval;
+pub mod export;
+pub mod fixed_limit;
+pub mod legality;
+pub mod limits;
+pub mod next_street;
+pub mod preflop;
+pub mod reraise;
+pub mod size_search;
 
 #[derive(Clone, Debug)]
 pub struct GameTreeAction {
+    // For display; `kind` says what the action does.
     pub label: String,
     pub amount: f64,
+    pub kind: ActionKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActionKind {
+    Fold,
+    Check,
+    Call,
+    Bet,
+    Raise,
+    AllIn,
+}
+
+impl ActionKind {
+    // A bet or raise labelled `label`, made facing `facing_bet_bb`.
+    pub fn aggressive(label: &str, facing_bet_bb: f64) -> Self {
+        if label == "all-in" {
+            ActionKind::AllIn
+        } else if facing_bet_bb > 0.0 {
+            ActionKind::Raise
+        } else {
+            ActionKind::Bet
+        }
+    }
+
+    // Fold, check or call: no size of its own.
+    pub fn is_passive(&self) -> bool {
+        matches!(
+            self,
+            ActionKind::Fold | ActionKind::Check | ActionKind::Call
+        )
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "fold" => Some(ActionKind::Fold),
+            "check" => Some(ActionKind::Check),
+            "call" => Some(ActionKind::Call),
+            "bet" => Some(ActionKind::Bet),
+            "raise" => Some(ActionKind::Raise),
+            "all-in" => Some(ActionKind::AllIn),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionKind::Fold => "fold",
+            ActionKind::Check => "check",
+            ActionKind::Call => "call",
+            ActionKind::Bet => "bet",
+            ActionKind::Raise => "raise",
+            ActionKind::AllIn => "all-in",
+        }
+    }
+}
+
+pub const HERO: usize = 0;
+pub const VILLAIN: usize = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerminalKind {
+    Fold { folder: usize },
+    Showdown,
+}
+
+#[derive(Clone, Debug)]
+pub enum NodeKind {
+    // Children are node indices, in the same order as the actions offered at this node.
+    Decision { player: usize, children: Vec<usize> },
+    Terminal(TerminalKind),
+}
+
+#[derive(Clone, Debug)]
+pub struct TreeNode {
+    pub kind: NodeKind,
+    // Action that led here; empty for the root.
+    pub label: String,
+    // Chips each player has added since the root decision, in BB.
+    pub committed: [f64; 2],
+}
+
+impl TreeNode {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self.kind, NodeKind::Terminal(_))
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct GameTree {
     pub actions: Vec<GameTreeAction>,
+    pub nodes: Vec<TreeNode>,
     pub effective_stack_bb: f64,
+    pub pot_bb: f64,
+    pub preflop: bool,
+    pub rake: RakeModel,
+    pub legalization: LegalizationReport,
+    // Set in integer-chip mode; the BB amounts above are then exact conversions of it.
+    pub chips: Option<TreeChips>,
 }
 
 impl GameTree {
-    pub fn from_action_specs(specs: &[ActionSpec], effective_stack_bb: f64) -> Self {
-        let mut actions = Vec::with_capacity(specs.len());
-        for spec in specs {
-            let amount = if spec.amount <= 0.0 {
-                effective_stack_bb.max(1.0)
-            } else {
-                spec.amount.min(effective_stack_bb.max(1.0))
-            };
-            actions.push(GameTreeAction {
-                label: spec.label.clone(),
-                amount,
-            });
-        }
+    pub fn from_action_specs(
+        specs: &[ActionSpec],
+        summary: &GameStateSummary,
+        effective_stack_bb: f64,
+    ) -> Self {
+        Self::build(
+            specs,
+            summary,
+            effective_stack_bb,
+            &BettingContext::opening(summary),
+        )
+    }
+
+    // Fixed-limit games take only the folds, checks and calls from `specs`, ahead of the bet or
+    // raise: the structure alone decides the sizes.
+    pub fn build(
+        specs: &[ActionSpec],
+        summary: &GameStateSummary,
+        effective_stack_bb: f64,
+        context: &BettingContext,
+    ) -> Self {
+        let stack_cap = effective_stack_bb.max(1.0);
+        let (actions, nodes, legalization) = match summary.betting_structure() {
+            BettingStructure::NoLimit => {
+                let (actions, legalization) = legalize_actions(specs, context, stack_cap);
+                let nodes = build_nodes(&actions, context, stack_cap);
+                (actions, nodes, legalization)
+            }
+            BettingStructure::FixedLimit { bet_bb, cap } => {
+                let passive: Vec<ActionSpec> = specs
+                    .iter()
+                    .filter(|spec| spec.is_passive())
+                    .cloned()
+                    .collect();
+                let (mut actions, legalization) = legalize_actions(&passive, context, stack_cap);
+                let (sized, mut nodes) = build_limit_nodes(context, bet_bb, cap, stack_cap);
+                let passive_children: Vec<usize> = actions
+                    .iter()
+                    .map(|action| push_passive_node(&mut nodes, action))
+                    .collect();
+                if let NodeKind::Decision { children, .. } = &mut nodes[0].kind {
+                    children.splice(0..0, passive_children);
+                }
+                actions.extend(sized);
+                (actions, nodes, legalization)
+            }
+        };
 
         Self {
             actions,
+            nodes,
             effective_stack_bb,
+            pot_bb: summary.pot_in_bb(),
+            preflop: summary.street.eq_ignore_ascii_case("preflop"),
+            rake: RakeModel::default(),
+            legalization,
+            chips: None,
         }
     }
 
+    pub fn with_rake(mut self, rake: RakeModel) -> Self {
+        self.rake = rake;
+        self
+    }
+
+    // Snaps every amount to whole chip units and keeps the counts, so terminals are settled in
+    // integers; the BB amounts are rewritten from the counts.
+    pub fn with_integer_chips(mut self, units_per_bb: f64) -> Self {
+        if !units_per_bb.is_finite() || units_per_bb <= 0.0 {
+            return self;
+        }
+        let mut chips = TreeChips {
+            units_per_bb,
+            pot: 0,
+            stack: 0,
+            committed: Vec::with_capacity(self.nodes.len()),
+        };
+        chips.pot = chips.to_units(self.pot_bb);
+        chips.stack = chips.to_units(self.effective_stack_bb);
+        for node in &mut self.nodes {
+            let committed = node.committed.map(|bb| chips.to_units(bb));
+            node.committed = committed.map(|units| chips.to_bb(units));
+            chips.committed.push(committed);
+        }
+        for action in &mut self.actions {
+            action.amount = chips.to_bb(chips.to_units(action.amount));
+        }
+        self.pot_bb = chips.to_bb(chips.pot);
+        self.effective_stack_bb = chips.to_bb(chips.stack);
+        self.chips = Some(chips);
+        self
+    }
+
     pub fn is_empty(&self) -> bool {
         self.actions.is_empty()
     }
+
+    pub fn root(&self) -> &TreeNode {
+        &self.nodes[0]
+    }
+
+    // A showdown with hero's whole stack in, so villain's too: nothing is left to bet on later
+    // streets, and the hand is settled on the runouts alone.
+    pub fn is_all_in_showdown(&self, node: usize) -> bool {
+        let node = &self.nodes[node];
+        matches!(node.kind, NodeKind::Terminal(TerminalKind::Showdown))
+            && node.committed[HERO] + 1e-6 >= self.effective_stack_bb.max(1.0)
+    }
+
+    // For each node here, the node of `other` reached by the same actions, if it has one.
+    pub fn matching_nodes(&self, other: &GameTree) -> Vec<Option<usize>> {
+        let mut matches = vec![None; self.nodes.len()];
+        if self.is_empty() || other.is_empty() {
+            return matches;
+        }
+        let mut pending = vec![(0, 0)];
+        while let Some((node, theirs)) = pending.pop() {
+            matches[node] = Some(theirs);
+            let (
+                NodeKind::Decision { children, .. },
+                NodeKind::Decision {
+                    children: their, ..
+                },
+            ) = (&self.nodes[node].kind, &other.nodes[theirs].kind)
+            else {
+                continue;
+            };
+            for &child in children {
+                let label = &self.nodes[child].label;
+                if let Some(&found) = their.iter().find(|&&c| &other.nodes[c].label == label) {
+                    pending.push((child, found));
+                }
+            }
+        }
+        matches
+    }
+}
+
+// Hero picks one of the root actions. After a bet or raise villain folds, calls, or re-raises
+// while the street allows, and a call goes to showdown; a hero fold ends the hand, and a check or
+// call goes straight to showdown.
+fn build_nodes(
+    actions: &[GameTreeAction],
+    context: &BettingContext,
+    stack_cap: f64,
+) -> Vec<TreeNode> {
+    let mut nodes = vec![TreeNode {
+        kind: NodeKind::Decision {
+            player: HERO,
+            children: Vec::with_capacity(actions.len()),
+        },
+        label: String::new(),
+        committed: [0.0, 0.0],
+    }];
+
+    let facing = context.facing_bet_bb.max(0.0);
+    let reraises = Reraises {
+        max_raises: context.max_raises,
+        stack_cap,
+        base: [0.0, facing],
+    };
+    // Hero's action is a raise itself when there is a bet to answer.
+    let raises = if facing > 0.0 {
+        context.raises_this_street + 1
+    } else {
+        0
+    };
+    let mut root_children = Vec::with_capacity(actions.len());
+    for action in actions {
+        if action.kind.is_passive() {
+            root_children.push(push_passive_node(&mut nodes, action));
+            continue;
+        }
+        root_children.push(reraises.respond(
+            &mut nodes,
+            VILLAIN,
+            [action.amount, 0.0],
+            raises,
+            &action.label,
+        ));
+    }
+
+    if let NodeKind::Decision { children, .. } = &mut nodes[0].kind {
+        *children = root_children;
+    }
+    nodes
+}
+
+// Villain's bet, if any, was made before the root, so only hero's call is committed here.
+fn push_passive_node(nodes: &mut Vec<TreeNode>, action: &GameTreeAction) -> usize {
+    let kind = if action.kind == ActionKind::Fold {
+        NodeKind::Terminal(TerminalKind::Fold { folder: HERO })
+    } else {
+        NodeKind::Terminal(TerminalKind::Showdown)
+    };
+    push_node(nodes, kind, &action.label, [action.amount, 0.0])
+}
+
+fn push_node(nodes: &mut Vec<TreeNode>, kind: NodeKind, label: &str, committed: [f64; 2]) -> usize {
+    nodes.push(TreeNode {
+        kind,
+        label: label.to_string(),
+        committed,
+    });
+    nodes.len() - 1
 }
@@ -0,0 +1,138 @@
+use crate::game_tree::eval::RakeModel;
+
+// Turns BB amounts into whole chips a table accepts, for actuators that cannot bet fractions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChipRounding {
+    pub chips_per_bb: f64,
+    // Smallest chip; every amount but an all-in is a multiple of it. 0 means 1.
+    pub increment: u64,
+    // Hero's chips behind; an all-in is exactly this, whatever the increment.
+    pub stack: u64,
+    // Smallest legal bet or raise, in chips; rounding never goes below it.
+    pub min_amount: u64,
+}
+
+impl ChipRounding {
+    pub fn new(chips_per_bb: f64, increment: u64, stack_bb: f64, min_amount_bb: f64) -> Self {
+        Self {
+            chips_per_bb,
+            increment,
+            stack: (stack_bb.max(0.0) * chips_per_bb).round() as u64,
+            min_amount: (min_amount_bb.max(0.0) * chips_per_bb).ceil() as u64,
+        }
+    }
+
+    // Nearest multiple of the increment, lifted to the legal minimum and capped at the stack.
+    pub fn to_chips(&self, amount_bb: f64) -> u64 {
+        if amount_bb <= 0.0 {
+            return 0;
+        }
+        let increment = self.increment.max(1);
+        let chips = amount_bb * self.chips_per_bb;
+        if chips + 1e-9 >= self.stack as f64 {
+            return self.stack;
+        }
+        let rounded = (chips / increment as f64).round() as u64 * increment;
+        let min_amount = self.min_amount.div_ceil(increment) * increment;
+        rounded.max(min_amount).min(self.stack)
+    }
+}
+
+// Largest amount, in chip units, a tree may hold: f64 counts units exactly up to here, and
+// settling a pot adds a handful of them far inside the i64 range.
+pub const MAX_CHIP_UNITS: f64 = 9_007_199_254_740_992.0;
+
+// A tree's amounts as whole chip units (multiples of the chip increment), so pots add up
+// exactly however many bets and raises lead to a node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeChips {
+    pub units_per_bb: f64,
+    pub pot: i64,
+    pub stack: i64,
+    // Per node, like `TreeNode::committed`.
+    pub committed: Vec<[i64; 2]>,
+}
+
+impl TreeChips {
+    // Any positive amount is at least one unit, so no bet rounds away. Validation keeps
+    // amounts within MAX_CHIP_UNITS; anything past it is capped there.
+    pub fn to_units(&self, bb: f64) -> i64 {
+        let units = (bb * self.units_per_bb).round();
+        if units.is_nan() || bb <= 0.0 {
+            0
+        } else {
+            units.clamp(1.0, MAX_CHIP_UNITS) as i64
+        }
+    }
+
+    pub fn to_bb(&self, units: i64) -> f64 {
+        units as f64 / self.units_per_bb
+    }
+}
+
+// A heads-up terminal settled in chip units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChipTerminal {
+    pub committed: [i64; 2],
+    pub folder: Option<usize>,
+    // Chips in the pot before the root decision.
+    pub dead: i64,
+    pub rake: i64,
+}
+
+impl ChipTerminal {
+    pub fn new(committed: [i64; 2], folder: Option<usize>, dead: i64) -> Self {
+        Self {
+            committed: committed.map(|chips| chips.max(0)),
+            folder,
+            dead: dead.max(0),
+            rake: 0,
+        }
+    }
+
+    // The dealer takes whole units, so the rake rounds down.
+    pub fn with_rake(mut self, model: &RakeModel, preflop: bool, units_per_bb: f64) -> Self {
+        let rake_bb = model.rake(self.contested() as f64 / units_per_bb, preflop);
+        self.rake = ((rake_bb * units_per_bb + 1e-9).floor() as i64).min(self.contested());
+        self
+    }
+
+    // Dead money plus the matched bets; nothing is contested once someone folds.
+    pub fn contested(&self) -> i64 {
+        if self.folder.is_some() {
+            return 0;
+        }
+        self.dead
+            .saturating_add(self.committed[0].min(self.committed[1]).saturating_mul(2))
+    }
+
+    // Net units each seat wins with `winner` taking the pot, or splitting it when None. An
+    // uncalled excess goes back to its owner, and the odd unit of a split goes to seat 0, the
+    // first to act. Sums saturate rather than overflow on amounts validation would refuse.
+    pub fn net(&self, winner: Option<usize>) -> [i64; 2] {
+        let mut received = [0i64; 2];
+        if let Some(folder) = self.folder {
+            received[1 - folder] = self
+                .dead
+                .saturating_add(self.committed[0])
+                .saturating_add(self.committed[1]);
+        } else {
+            let matched = self.committed[0].min(self.committed[1]);
+            let pot = self.contested().saturating_sub(self.rake);
+            match winner {
+                Some(seat) => received[seat] = received[seat].saturating_add(pot),
+                None => {
+                    received[0] = received[0].saturating_add(pot - pot / 2);
+                    received[1] = received[1].saturating_add(pot / 2);
+                }
+            }
+            for (received, committed) in received.iter_mut().zip(self.committed) {
+                *received = received.saturating_add(committed - matched);
+            }
+        }
+        [
+            received[0].saturating_sub(self.committed[0]),
+            received[1].saturating_sub(self.committed[1]),
+        ]
+    }
+}
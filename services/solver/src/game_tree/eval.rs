@@ -0,0 +1,193 @@
+use crate::solver_proto::RakeConfig;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerContribution {
+    pub committed: f64,
+    pub folded: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SidePot {
+    pub amount: f64,
+    pub eligible: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RakeModel {
+    pub percentage: f64,
+    pub cap: f64,
+    pub no_flop_no_drop: bool,
+}
+
+impl RakeModel {
+    pub fn from_config(config: &RakeConfig) -> Self {
+        Self {
+            percentage: config.percentage.clamp(0.0, 100.0),
+            cap: config.cap_bb.max(0.0),
+            no_flop_no_drop: config.no_flop_no_drop,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.percentage > 0.0
+    }
+
+    pub fn rake(&self, pot: f64, preflop: bool) -> f64 {
+        if !self.is_enabled() || pot <= 0.0 || (preflop && self.no_flop_no_drop) {
+            return 0.0;
+        }
+        let rake = pot * self.percentage / 100.0;
+        if self.cap > 0.0 {
+            rake.min(self.cap)
+        } else {
+            rake
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TerminalNode {
+    pub contributions: Vec<PlayerContribution>,
+    // Chips already in the pot from earlier streets; every live player is eligible for them.
+    pub dead_money: f64,
+    pub rake: f64,
+}
+
+impl TerminalNode {
+    pub fn new(contributions: Vec<PlayerContribution>, dead_money: f64) -> Self {
+        Self {
+            contributions,
+            dead_money: dead_money.max(0.0),
+            rake: 0.0,
+        }
+    }
+
+    // Uncalled bets come back to their owner and are never raked, so only pots with more than
+    // one eligible player count towards the drop.
+    pub fn with_rake(mut self, model: &RakeModel, preflop: bool) -> Self {
+        self.rake = model.rake(self.contested_pot(), preflop);
+        self
+    }
+
+    pub fn contested_pot(&self) -> f64 {
+        self.side_pots()
+            .iter()
+            .filter(|pot| pot.eligible.len() > 1)
+            .map(|pot| pot.amount)
+            .sum()
+    }
+
+    pub fn total_pot(&self) -> f64 {
+        self.dead_money
+            + self
+                .contributions
+                .iter()
+                .map(|c| c.committed.max(0.0))
+                .sum::<f64>()
+    }
+
+    pub fn side_pots(&self) -> Vec<SidePot> {
+        let mut pots = build_side_pots(&self.contributions);
+        if let Some(main) = pots.first_mut() {
+            main.amount += self.dead_money;
+        }
+        pots
+    }
+
+    // Net chips won or lost by each player when the hand ends at this node. Higher strength
+    // wins; equal strengths split. Folded players' strengths are ignored. Rake comes out of the
+    // contested pots in proportion to their size.
+    pub fn payouts(&self, strengths: &[f64]) -> Vec<f64> {
+        let contested = self.contested_pot();
+        let rake_share = if contested > 0.0 {
+            (self.rake / contested).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let mut received = vec![0.0; self.contributions.len()];
+        for mut pot in self.side_pots() {
+            if pot.eligible.len() > 1 {
+                pot.amount *= 1.0 - rake_share;
+            }
+            let best = pot
+                .eligible
+                .iter()
+                .map(|&seat| strengths.get(seat).copied().unwrap_or(f64::NEG_INFINITY))
+                .fold(f64::NEG_INFINITY, f64::max);
+            let winners: Vec<usize> = pot
+                .eligible
+                .iter()
+                .copied()
+                .filter(|&seat| {
+                    let strength = strengths.get(seat).copied().unwrap_or(f64::NEG_INFINITY);
+                    (strength - best).abs() <= f64::EPSILON
+                })
+                .collect();
+            if winners.is_empty() {
+                continue;
+            }
+            let share = pot.amount / winners.len() as f64;
+            for seat in winners {
+                received[seat] += share;
+            }
+        }
+
+        received
+            .iter()
+            .zip(&self.contributions)
+            .map(|(won, contribution)| won - contribution.committed.max(0.0))
+            .collect()
+    }
+}
+
+// Splits the committed chips into a main pot and side pots, one per distinct all-in level of
+// the players still in the hand. Chips folded players put in beyond the last level go into the
+// final pot, so the returned list always has at least a main pot.
+pub fn build_side_pots(contributions: &[PlayerContribution]) -> Vec<SidePot> {
+    let mut levels: Vec<f64> = contributions
+        .iter()
+        .filter(|c| !c.folded && c.committed > 0.0)
+        .map(|c| c.committed)
+        .collect();
+    levels.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    levels.dedup_by(|a, b| (*a - *b).abs() <= f64::EPSILON);
+
+    let mut pots: Vec<SidePot> = Vec::with_capacity(levels.len());
+    let mut previous = 0.0;
+    for &level in &levels {
+        let amount: f64 = contributions
+            .iter()
+            .map(|c| {
+                let committed = c.committed.max(0.0);
+                committed.min(level) - committed.min(previous)
+            })
+            .sum();
+        let eligible: Vec<usize> = contributions
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| !c.folded && c.committed + f64::EPSILON >= level)
+            .map(|(seat, _)| seat)
+            .collect();
+        pots.push(SidePot { amount, eligible });
+        previous = level;
+    }
+
+    let overflow: f64 = contributions
+        .iter()
+        .map(|c| (c.committed.max(0.0) - previous).max(0.0))
+        .sum();
+    match pots.last_mut() {
+        Some(last) => last.amount += overflow,
+        None => pots.push(SidePot {
+            amount: overflow,
+            eligible: contributions
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| !c.folded)
+                .map(|(seat, _)| seat)
+                .collect(),
+        }),
+    }
+
+    pots
+}
@@ -0,0 +1,144 @@
+use crate::cfr::compressed::CompressedStrategies;
+use crate::game_tree::{GameTree, NodeKind, TerminalKind, HERO};
+use serde::Serialize;
+use std::fmt::Write as _;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeFormat {
+    Dot,
+    Json,
+}
+
+impl TreeFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "dot" | "graphviz" => Some(TreeFormat::Dot),
+            "" | "json" => Some(TreeFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TreeFormat::Dot => "dot",
+            TreeFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ExportedNode {
+    pub node: usize,
+    // Action that led here; empty for the root.
+    pub label: String,
+    // "hero" or "villain" to act, or "fold" or "showdown".
+    pub kind: &'static str,
+    pub pot_bb: f64,
+    pub stacks_bb: [f64; 2],
+    pub committed_bb: [f64; 2],
+    // Each action's frequency averaged over the buckets; empty at terminals or without a strategy.
+    pub frequencies: Vec<f64>,
+    // strategy[action][bucket]
+    pub strategy: Vec<Vec<f64>>,
+    pub children: Vec<ExportedNode>,
+}
+
+// The tree as nested nodes from the root, with the solved strategy where one is given.
+pub fn exported_tree(tree: &GameTree, strategies: Option<&CompressedStrategies>) -> ExportedNode {
+    exported_node(tree, strategies, 0)
+}
+
+pub fn export_tree(
+    tree: &GameTree,
+    strategies: Option<&CompressedStrategies>,
+    format: TreeFormat,
+) -> String {
+    match format {
+        TreeFormat::Dot => tree_dot(tree, strategies),
+        TreeFormat::Json => {
+            serde_json::to_string_pretty(&exported_tree(tree, strategies)).unwrap_or_default()
+        }
+    }
+}
+
+// Graphviz: a box per decision, an ellipse per terminal, and each edge labelled with its action
+// and, when solved, how often it is taken.
+pub fn tree_dot(tree: &GameTree, strategies: Option<&CompressedStrategies>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "digraph tree {{");
+    let _ = writeln!(out, "  node [shape=box, fontname=\"monospace\"];");
+    let mut stack = vec![exported_tree(tree, strategies)];
+    while let Some(node) = stack.pop() {
+        let shape = if node.children.is_empty() {
+            ", shape=ellipse"
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            out,
+            "  n{} [label=\"{}\\npot {:.2}\\nstacks {:.2} / {:.2}\"{}];",
+            node.node, node.kind, node.pot_bb, node.stacks_bb[0], node.stacks_bb[1], shape
+        );
+        for (action, child) in node.children.iter().enumerate() {
+            let label = match node.frequencies.get(action) {
+                Some(frequency) => format!("{} {:.1}%", child.label, frequency * 100.0),
+                None => child.label.clone(),
+            };
+            let _ = writeln!(
+                out,
+                "  n{} -> n{} [label=\"{}\"];",
+                node.node,
+                child.node,
+                label.replace('"', "\\\"")
+            );
+        }
+        stack.extend(node.children.into_iter().rev());
+    }
+    let _ = writeln!(out, "}}");
+    out
+}
+
+fn exported_node(
+    tree: &GameTree,
+    strategies: Option<&CompressedStrategies>,
+    index: usize,
+) -> ExportedNode {
+    let node = &tree.nodes[index];
+    let committed = node.committed;
+    let mut exported = ExportedNode {
+        node: index,
+        label: node.label.clone(),
+        kind: "showdown",
+        pot_bb: tree.pot_bb + committed[0] + committed[1],
+        stacks_bb: committed.map(|chips| (tree.effective_stack_bb - chips).max(0.0)),
+        committed_bb: committed,
+        frequencies: Vec::new(),
+        strategy: Vec::new(),
+        children: Vec::new(),
+    };
+    match &node.kind {
+        NodeKind::Terminal(TerminalKind::Fold { .. }) => exported.kind = "fold",
+        NodeKind::Terminal(TerminalKind::Showdown) => {}
+        NodeKind::Decision { player, children } => {
+            exported.kind = if *player == HERO { "hero" } else { "villain" };
+            if let Some(strategy) = strategies.and_then(|strategies| strategies.node(index)) {
+                let rows: Vec<Vec<f64>> = (0..strategy.buckets)
+                    .map(|bucket| strategy.row(bucket))
+                    .collect();
+                exported.strategy = (0..strategy.actions)
+                    .map(|action| rows.iter().map(|row| row[action]).collect())
+                    .collect();
+                exported.frequencies = exported
+                    .strategy
+                    .iter()
+                    .map(|row| row.iter().sum::<f64>() / row.len().max(1) as f64)
+                    .collect();
+            }
+            exported.children = children
+                .iter()
+                .map(|child| exported_node(tree, strategies, *child))
+                .collect();
+        }
+    }
+    exported
+}
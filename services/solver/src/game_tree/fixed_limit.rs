@@ -0,0 +1,118 @@
+use crate::game_tree::legality::BettingContext;
+use crate::game_tree::{
+    push_node, ActionKind, GameTreeAction, NodeKind, TerminalKind, TreeNode, HERO,
+};
+
+const SIZE_EPSILON: f64 = 1e-6;
+
+// Hero bets (or raises) exactly `bet_bb`, then each player in turn folds, calls, or raises by
+// another `bet_bb` until `cap` bets and raises have been made on the street or the stack runs
+// out. Amounts are street totals, as in the no-limit tree.
+pub fn build_limit_nodes(
+    context: &BettingContext,
+    bet_bb: f64,
+    cap: u32,
+    stack_cap: f64,
+) -> (Vec<GameTreeAction>, Vec<TreeNode>) {
+    let mut nodes = vec![TreeNode {
+        kind: NodeKind::Decision {
+            player: HERO,
+            children: Vec::new(),
+        },
+        label: String::new(),
+        committed: [0.0, 0.0],
+    }];
+    let facing = context.facing_bet_bb.max(0.0);
+    let made = if facing > 0.0 {
+        1 + context.raises_this_street
+    } else {
+        0
+    };
+    if context.opponent_all_in || made >= cap || facing + SIZE_EPSILON >= stack_cap {
+        return (Vec::new(), nodes);
+    }
+
+    let limit = Limit {
+        bet_bb,
+        cap,
+        stack_cap,
+        base: [0.0, facing],
+    };
+    let amount = (facing + bet_bb).min(stack_cap);
+    let kind = if amount + SIZE_EPSILON >= stack_cap {
+        ActionKind::AllIn
+    } else if facing > 0.0 {
+        ActionKind::Raise
+    } else {
+        ActionKind::Bet
+    };
+    let action = GameTreeAction {
+        label: if facing > 0.0 { "raise" } else { "bet" }.to_string(),
+        amount,
+        kind,
+    };
+    let response = limit.respond(
+        &mut nodes,
+        1 - HERO,
+        [action.amount, 0.0],
+        made + 1,
+        &action.label,
+    );
+    if let NodeKind::Decision { children, .. } = &mut nodes[0].kind {
+        children.push(response);
+    }
+    (vec![action], nodes)
+}
+
+// The betting after a fixed-limit bet: each reply folds, calls, or raises by another `bet_bb`
+// while the street has bets and raises left under `cap`.
+pub struct Limit {
+    pub bet_bb: f64,
+    pub cap: u32,
+    pub stack_cap: f64,
+    // Each player's street total before the root decision.
+    pub base: [f64; 2],
+}
+
+impl Limit {
+    // `actor` faces the other player's latest bet; returns the index of their decision node.
+    pub fn respond(
+        &self,
+        nodes: &mut Vec<TreeNode>,
+        actor: usize,
+        committed: [f64; 2],
+        made: u32,
+        label: &str,
+    ) -> usize {
+        let other = 1 - actor;
+        let facing = self.base[other] + committed[other];
+        let mut children = vec![push_node(
+            nodes,
+            NodeKind::Terminal(TerminalKind::Fold { folder: actor }),
+            "fold",
+            committed,
+        )];
+        let mut called = committed;
+        called[actor] = facing - self.base[actor];
+        children.push(push_node(
+            nodes,
+            NodeKind::Terminal(TerminalKind::Showdown),
+            "call",
+            called,
+        ));
+        if made < self.cap && facing + SIZE_EPSILON < self.stack_cap {
+            let mut raised = committed;
+            raised[actor] = (facing + self.bet_bb).min(self.stack_cap) - self.base[actor];
+            children.push(self.respond(nodes, other, raised, made + 1, "raise"));
+        }
+        push_node(
+            nodes,
+            NodeKind::Decision {
+                player: actor,
+                children,
+            },
+            label,
+            committed,
+        )
+    }
+}
@@ -0,0 +1,248 @@
+use crate::abstraction::{ActionSpec, BettingRound, GameStateSummary};
+use crate::game_tree::{ActionKind, GameTreeAction};
+
+const SIZE_EPSILON: f64 = 1e-6;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BettingContext {
+    pub facing_bet_bb: f64,
+    // Size of the last bet or raise increment; the next raise must be at least this much more.
+    pub last_raise_bb: f64,
+    pub min_bet_bb: f64,
+    pub opponent_all_in: bool,
+    pub raises_this_street: u32,
+    // Most raises the street allows, those before the root included; 0 stops at villain's fold
+    // or call. Fixed-limit streets use their own cap.
+    pub max_raises: u32,
+}
+
+impl BettingContext {
+    pub fn opening(summary: &GameStateSummary) -> Self {
+        Self {
+            facing_bet_bb: 0.0,
+            last_raise_bb: 0.0,
+            min_bet_bb: summary.min_bet_bb(),
+            opponent_all_in: false,
+            raises_this_street: 0,
+            max_raises: 0,
+        }
+    }
+
+    // Hero's spot at the root: facing `facing_bet` or this street's latest bet or raise, and
+    // opening the action otherwise. The blinds alone don't count as a bet here.
+    pub fn from_summary(summary: &GameStateSummary) -> Self {
+        let opening = Self::opening(summary);
+        let betting = summary.street_betting();
+        if summary.facing_bet.is_none() && betting.aggressions == 0 {
+            return opening;
+        }
+        let facing_bet_bb = summary.facing_bet_bb();
+        if facing_bet_bb <= 0.0 {
+            return opening;
+        }
+        let last_raise_bb = if betting.last_raise > 0.0 {
+            betting.last_raise / summary.chips_per_bb()
+        } else {
+            facing_bet_bb
+        };
+        Self {
+            facing_bet_bb,
+            last_raise_bb,
+            opponent_all_in: summary
+                .aggressor()
+                .and_then(|seat| summary.player(seat))
+                .is_some_and(|player| player.stack <= 0.0),
+            raises_this_street: betting.aggressions.saturating_sub(1),
+            ..opening
+        }
+    }
+
+    pub fn round(&self) -> BettingRound {
+        if self.facing_bet_bb <= 0.0 {
+            BettingRound::Bet
+        } else if self.raises_this_street == 0 {
+            BettingRound::Raise
+        } else {
+            BettingRound::Reraise
+        }
+    }
+
+    pub fn min_legal_amount(&self) -> f64 {
+        if self.facing_bet_bb <= 0.0 {
+            self.min_bet_bb.max(0.0)
+        } else {
+            self.facing_bet_bb + self.last_raise_bb.max(self.min_bet_bb)
+        }
+    }
+
+    pub fn can_raise(&self, stack_cap: f64) -> bool {
+        !self.opponent_all_in && self.facing_bet_bb + SIZE_EPSILON < stack_cap
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdjustmentReason {
+    RaisedToMinimum,
+    CappedToAllIn,
+    DroppedFacingAllIn,
+    MergedDuplicate,
+    DroppedForMemory,
+    // A fold or call with no bet to answer, or a check facing one.
+    Unavailable,
+}
+
+impl AdjustmentReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdjustmentReason::RaisedToMinimum => "min-raise",
+            AdjustmentReason::CappedToAllIn => "all-in",
+            AdjustmentReason::DroppedFacingAllIn => "facing-all-in",
+            AdjustmentReason::MergedDuplicate => "duplicate",
+            AdjustmentReason::DroppedForMemory => "memory-budget",
+            AdjustmentReason::Unavailable => "unavailable",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SizeAdjustment {
+    pub label: String,
+    pub requested: f64,
+    // None when the action was removed from the tree.
+    pub legal: Option<f64>,
+    pub reason: AdjustmentReason,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LegalizationReport {
+    pub adjustments: Vec<SizeAdjustment>,
+}
+
+impl LegalizationReport {
+    pub fn is_clean(&self) -> bool {
+        self.adjustments.is_empty()
+    }
+
+    fn record(
+        &mut self,
+        label: &str,
+        requested: f64,
+        legal: Option<f64>,
+        reason: AdjustmentReason,
+    ) {
+        self.adjustments.push(SizeAdjustment {
+            label: label.to_string(),
+            requested,
+            legal,
+            reason,
+        });
+    }
+}
+
+// Turns requested sizes into ones a real table would accept: raises are lifted to the minimum
+// raise, anything at or beyond the stack becomes a single all-in, and no aggression is offered
+// when the opponent is already all-in. Folds and calls are kept facing a bet and checks when
+// there is none; a call puts in the bet faced, up to the stack.
+pub fn legalize_actions(
+    specs: &[ActionSpec],
+    context: &BettingContext,
+    stack_cap: f64,
+) -> (Vec<GameTreeAction>, LegalizationReport) {
+    let mut report = LegalizationReport::default();
+    let mut actions: Vec<GameTreeAction> = Vec::with_capacity(specs.len());
+
+    let facing = context.facing_bet_bb > 0.0;
+    let can_raise = context.can_raise(stack_cap);
+    let min_legal = context.min_legal_amount().min(stack_cap);
+    for spec in specs {
+        if spec.is_passive() {
+            let amount = if spec.kind == ActionKind::Call {
+                context.facing_bet_bb.min(stack_cap)
+            } else {
+                0.0
+            };
+            let reason = if (spec.kind == ActionKind::Check) == facing {
+                Some(AdjustmentReason::Unavailable)
+            } else if actions.iter().any(|existing| existing.kind == spec.kind) {
+                Some(AdjustmentReason::MergedDuplicate)
+            } else {
+                None
+            };
+            match reason {
+                Some(reason) => report.record(&spec.label, spec.amount, None, reason),
+                None => actions.push(GameTreeAction {
+                    label: spec.label.clone(),
+                    amount,
+                    kind: spec.kind,
+                }),
+            }
+            continue;
+        }
+        if !can_raise {
+            report.record(
+                &spec.label,
+                spec.amount,
+                None,
+                AdjustmentReason::DroppedFacingAllIn,
+            );
+            continue;
+        }
+
+        let requested = if spec.amount <= 0.0 {
+            stack_cap
+        } else {
+            spec.amount
+        };
+
+        let (label, amount) = if requested + SIZE_EPSILON >= stack_cap {
+            if spec.label != "all-in" {
+                report.record(
+                    &spec.label,
+                    spec.amount,
+                    Some(stack_cap),
+                    AdjustmentReason::CappedToAllIn,
+                );
+            }
+            ("all-in".to_string(), stack_cap)
+        } else if requested + SIZE_EPSILON < min_legal {
+            if min_legal + SIZE_EPSILON >= stack_cap {
+                report.record(
+                    &spec.label,
+                    spec.amount,
+                    Some(stack_cap),
+                    AdjustmentReason::CappedToAllIn,
+                );
+                ("all-in".to_string(), stack_cap)
+            } else {
+                report.record(
+                    &spec.label,
+                    spec.amount,
+                    Some(min_legal),
+                    AdjustmentReason::RaisedToMinimum,
+                );
+                (spec.label.clone(), min_legal)
+            }
+        } else {
+            (spec.label.clone(), requested)
+        };
+
+        if actions.iter().any(|existing| {
+            !existing.kind.is_passive() && (existing.amount - amount).abs() <= SIZE_EPSILON
+        }) {
+            report.record(
+                &spec.label,
+                spec.amount,
+                None,
+                AdjustmentReason::MergedDuplicate,
+            );
+            continue;
+        }
+        actions.push(GameTreeAction {
+            kind: ActionKind::aggressive(&label, context.facing_bet_bb),
+            label,
+            amount,
+        });
+    }
+
+    (actions, report)
+}
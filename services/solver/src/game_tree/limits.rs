@@ -0,0 +1,308 @@
+use crate::abstraction::{ActionSpec, BettingStructure, GameStateSummary};
+use crate::error::SolverError;
+use crate::game_tree::eval::RakeModel;
+use crate::game_tree::legality::{AdjustmentReason, BettingContext, SizeAdjustment};
+use crate::game_tree::next_street::with_next_street;
+use crate::game_tree::{GameTree, NodeKind, TreeNode};
+use std::mem::size_of;
+
+// Coarsening halves the bucket count down to this floor before it starts dropping sizes.
+pub const MIN_COARSE_BUCKETS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TreeLimits {
+    pub max_nodes: usize,
+    pub max_bytes: u64,
+    // Information sets one iteration may visit for the time budget to cover enough iterations;
+    // 0 leaves the budget out of it.
+    pub max_info_sets: usize,
+    // When false an oversized tree is rejected instead of coarsened.
+    pub coarsen: bool,
+}
+
+impl Default for TreeLimits {
+    fn default() -> Self {
+        Self {
+            max_nodes: 1_000_000,
+            max_bytes: 512 * 1024 * 1024,
+            max_info_sets: 0,
+            coarsen: true,
+        }
+    }
+}
+
+// The rungs a tree steps down, in order, when it doesn't fit the limits. Blueprint is the last:
+// no tree fits at all and a cached solve of the spot answers instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DegradationLevel {
+    #[default]
+    Full,
+    FewerBuckets,
+    FewerSizes,
+    ShallowerTree,
+    Blueprint,
+}
+
+impl DegradationLevel {
+    pub fn parse(name: &str) -> Option<Self> {
+        [
+            DegradationLevel::Full,
+            DegradationLevel::FewerBuckets,
+            DegradationLevel::FewerSizes,
+            DegradationLevel::ShallowerTree,
+            DegradationLevel::Blueprint,
+        ]
+        .into_iter()
+        .find(|level| level.as_str() == name)
+    }
+
+    // Empty for a full solve.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DegradationLevel::Full => "",
+            DegradationLevel::FewerBuckets => "fewer_buckets",
+            DegradationLevel::FewerSizes => "fewer_sizes",
+            DegradationLevel::ShallowerTree => "shallower_tree",
+            DegradationLevel::Blueprint => "blueprint",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TreeFootprint {
+    pub nodes: usize,
+    pub info_sets: usize,
+    pub bytes: u64,
+}
+
+impl TreeFootprint {
+    // Tree arena plus the CFR tables: a regret and a strategy sum per bucket and action at every
+    // decision node.
+    pub fn of(tree: &GameTree, buckets: usize) -> Self {
+        let buckets = buckets.max(1);
+        let mut info_sets = 0;
+        let mut bytes = (tree.nodes.len() * size_of::<TreeNode>()) as u64;
+        for node in &tree.nodes {
+            bytes += node.label.len() as u64;
+            // Per-node payoff slot and infoset table header.
+            bytes += (size_of::<Option<[f64; 6]>>()
+                + size_of::<usize>()
+                + size_of::<Vec<f64>>() * 2) as u64;
+            if let NodeKind::Decision { children, .. } = &node.kind {
+                info_sets += buckets;
+                bytes += (children.len() * size_of::<usize>()) as u64;
+                bytes += (2 * buckets * children.len() * size_of::<f64>()) as u64;
+            }
+        }
+        Self {
+            nodes: tree.nodes.len(),
+            info_sets,
+            bytes,
+        }
+    }
+}
+
+impl TreeLimits {
+    pub fn admits(&self, footprint: &TreeFootprint) -> bool {
+        footprint.nodes <= self.max_nodes
+            && footprint.bytes <= self.max_bytes
+            && (self.max_info_sets == 0 || footprint.info_sets <= self.max_info_sets)
+    }
+
+    // Caps the work per iteration so `budget_ms` covers `min_iterations` at `info_sets_per_ms`.
+    // A rate of 0 or no time budget leaves the limits as they are.
+    pub fn with_time_budget(
+        mut self,
+        budget_ms: i32,
+        min_iterations: usize,
+        info_sets_per_ms: f64,
+    ) -> Self {
+        if budget_ms > 0 && info_sets_per_ms > 0.0 {
+            let per_iteration = budget_ms as f64 * info_sets_per_ms / min_iterations.max(1) as f64;
+            self.max_info_sets = (per_iteration as usize).max(1);
+        }
+        self
+    }
+
+    pub fn rejection(&self, footprint: &TreeFootprint) -> SolverError {
+        if footprint.nodes > self.max_nodes {
+            SolverError::TreeTooLarge {
+                nodes: footprint.nodes,
+                limit: self.max_nodes,
+            }
+        } else if footprint.bytes > self.max_bytes {
+            SolverError::MemoryBudgetExceeded {
+                bytes: footprint.bytes,
+                limit: self.max_bytes,
+            }
+        } else {
+            SolverError::BudgetTooSmall {
+                info_sets: footprint.info_sets,
+                limit: self.max_info_sets,
+            }
+        }
+    }
+}
+
+// The size to give up first: the one sitting closest to a neighbouring size, since its strategy
+// is the best covered by what remains. Ties go to the size with neighbours on both sides, so the
+// range of sizes is kept. All-in, folds, checks and calls are never dropped.
+pub fn drop_candidate(specs: &[ActionSpec]) -> Option<usize> {
+    let mut order: Vec<usize> = (0..specs.len())
+        .filter(|index| !specs[*index].is_passive())
+        .collect();
+    order.sort_by(|a, b| specs[*a].amount.total_cmp(&specs[*b].amount));
+    order
+        .iter()
+        .enumerate()
+        .filter(|(_, index)| specs[**index].label != "all-in")
+        .map(|(position, index)| {
+            let amount = specs[*index].amount;
+            let below = position
+                .checked_sub(1)
+                .map(|previous| amount - specs[order[previous]].amount)
+                .unwrap_or(f64::INFINITY);
+            let above = order
+                .get(position + 1)
+                .map(|next| specs[*next].amount - amount)
+                .unwrap_or(f64::INFINITY);
+            (*index, below.min(above), below.max(above))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1).then(a.2.total_cmp(&b.2)))
+        .map(|(index, _, _)| index)
+}
+
+pub struct FittedTree {
+    pub tree: GameTree,
+    pub buckets: usize,
+    pub footprint: TreeFootprint,
+    // The lowest rung the tree had to step down to.
+    pub level: DegradationLevel,
+    // What the tree was built from, with sizes dropped and a fixed-limit cap or no-limit
+    // `max_raises` lowered if they had to be.
+    pub summary: GameStateSummary,
+    pub specs: Vec<ActionSpec>,
+    pub context: BettingContext,
+}
+
+// Builds the tree, coarsening until it fits: buckets are halved down to MIN_COARSE_BUCKETS, then
+// sizes are dropped one at a time, then the street allows one raise fewer at a time.
+// Dropped sizes are reported alongside the legalization notes.
+pub fn fit_tree(
+    specs: &[ActionSpec],
+    summary: &GameStateSummary,
+    effective_stack_bb: f64,
+    context: &BettingContext,
+    rake: &RakeModel,
+    buckets: usize,
+    limits: &TreeLimits,
+) -> Result<FittedTree, SolverError> {
+    let mut specs = specs.to_vec();
+    let mut summary = summary.clone();
+    let mut context = context.clone();
+    let mut buckets = buckets.max(1);
+    let mut dropped = Vec::new();
+    let mut level = DegradationLevel::Full;
+    loop {
+        let mut tree =
+            GameTree::build(&specs, &summary, effective_stack_bb, &context).with_rake(rake.clone());
+        if tree.is_empty() {
+            return Err(SolverError::NoLegalActions);
+        }
+        let footprint = TreeFootprint::of(&tree, buckets);
+        if limits.admits(&footprint) {
+            tree.legalization.adjustments.extend(dropped);
+            return Ok(FittedTree {
+                tree,
+                buckets,
+                footprint,
+                level,
+                summary,
+                specs,
+                context,
+            });
+        }
+        if !limits.coarsen {
+            return Err(limits.rejection(&footprint));
+        }
+        if buckets > MIN_COARSE_BUCKETS {
+            buckets = (buckets / 2).max(MIN_COARSE_BUCKETS);
+            level = level.max(DegradationLevel::FewerBuckets);
+            continue;
+        }
+        if let Some(index) = drop_candidate(&specs).filter(|_| specs.len() > 1) {
+            let spec = specs.remove(index);
+            dropped.push(SizeAdjustment {
+                label: spec.label,
+                requested: spec.amount,
+                legal: None,
+                reason: AdjustmentReason::DroppedForMemory,
+            });
+            level = level.max(DegradationLevel::FewerSizes);
+            continue;
+        }
+        match (summary.betting_structure(), summary.limit.as_mut()) {
+            (BettingStructure::FixedLimit { cap, .. }, Some(limit)) if cap > 1 => {
+                limit.cap = cap - 1;
+                level = DegradationLevel::ShallowerTree;
+            }
+            (BettingStructure::NoLimit, _) if context.max_raises > 0 => {
+                context.max_raises -= 1;
+                level = DegradationLevel::ShallowerTree;
+            }
+            _ => return Err(limits.rejection(&footprint)),
+        }
+    }
+}
+
+// The passes iterative deepening solves before the fitted tree, shallowest first, each allowing
+// one raise more: a fixed-limit street from one raise up to one below the fitted cap, a no-limit
+// one from no re-raises up to one below the fitted `max_raises`. The street after comes last, in
+// `next_street_tree`.
+pub fn shallower_trees(
+    fitted: &FittedTree,
+    effective_stack_bb: f64,
+    rake: &RakeModel,
+) -> Vec<GameTree> {
+    let structure = fitted.summary.betting_structure();
+    let depths = match structure {
+        BettingStructure::FixedLimit { cap, .. } => 1..cap,
+        BettingStructure::NoLimit => 0..fitted.context.max_raises,
+    };
+    let mut trees: Vec<GameTree> = Vec::new();
+    for depth in depths {
+        let mut summary = fitted.summary.clone();
+        let mut context = fitted.context.clone();
+        match (structure, summary.limit.as_mut()) {
+            (BettingStructure::FixedLimit { .. }, Some(limit)) => limit.cap = depth,
+            _ => context.max_raises = depth,
+        }
+        let tree = GameTree::build(&fitted.specs, &summary, effective_stack_bb, &context)
+            .with_rake(rake.clone());
+        // Raises the street or the stack had already used up add nothing.
+        let deeper = trees
+            .last()
+            .map_or(0, |previous: &GameTree| previous.nodes.len());
+        if !tree.is_empty()
+            && tree.nodes.len() > deeper
+            && tree.nodes.len() < fitted.tree.nodes.len()
+        {
+            trees.push(tree);
+        }
+    }
+    trees
+}
+
+// The pass iterative deepening solves after the fitted tree: the same tree with the next street's
+// betting after its showdowns, if the street has a next one and the tree still fits `limits`.
+pub fn next_street_tree(fitted: &FittedTree, limits: &TreeLimits) -> Option<GameTree> {
+    let tree = with_next_street(
+        &fitted.tree,
+        &fitted.summary,
+        &fitted.specs,
+        &fitted.context,
+    )?;
+    limits
+        .admits(&TreeFootprint::of(&tree, fitted.buckets))
+        .then_some(tree)
+}
@@ -0,0 +1,155 @@
+use crate::abstraction::{ActionSpec, BettingStructure, GameStateSummary, Street};
+use crate::game_tree::fixed_limit::Limit;
+use crate::game_tree::legality::BettingContext;
+use crate::game_tree::reraise::Reraises;
+use crate::game_tree::{push_node, GameTree, NodeKind, TerminalKind, TreeNode, HERO, VILLAIN};
+
+const SIZE_EPSILON: f64 = 1e-6;
+
+// `tree` with the next street's betting played wherever it went to showdown with chips behind:
+// the player out of position checks or bets, the other checks back or bets after a check, and a
+// bet is answered as on the root's street (fold, call, or re-raise while `max_raises` allows; a
+// fixed-limit street's bet and cap). No-limit bets are the request's pot fractions of the new
+// pot, and all-in. The card the street deals isn't bucketed on its own: hands keep their
+// buckets, whose showdowns already average over every runout. None on the river, preflop
+// (whose rake turns on whether a flop is dealt) and when every showdown is all-in.
+pub fn with_next_street(
+    tree: &GameTree,
+    summary: &GameStateSummary,
+    specs: &[ActionSpec],
+    context: &BettingContext,
+) -> Option<GameTree> {
+    let next = match summary.street_kind()? {
+        Street::Flop => Street::Turn,
+        Street::Turn => Street::River,
+        Street::Preflop | Street::River => return None,
+    };
+    let showdowns: Vec<usize> = (0..tree.nodes.len())
+        .filter(|&node| {
+            matches!(
+                tree.nodes[node].kind,
+                NodeKind::Terminal(TerminalKind::Showdown)
+            ) && !tree.is_all_in_showdown(node)
+        })
+        .collect();
+    if showdowns.is_empty() {
+        return None;
+    }
+    let mut later = summary.clone();
+    later.street = next.as_str().to_string();
+    let street = NextStreet {
+        structure: later.betting_structure(),
+        fractions: specs
+            .iter()
+            .filter_map(|spec| {
+                let fraction = spec.label.strip_prefix("pot-")?.parse::<f64>().ok()?;
+                Some((fraction, spec.label.clone()))
+            })
+            .collect(),
+        max_raises: context.max_raises,
+        min_bet_bb: later.min_bet_bb(),
+        stack_cap: tree.effective_stack_bb.max(1.0),
+        pot_bb: tree.pot_bb,
+        opener: if summary.hero_has_position() {
+            VILLAIN
+        } else {
+            HERO
+        },
+    };
+    let mut extended = tree.clone();
+    for node in showdowns {
+        let committed = extended.nodes[node].committed;
+        let children = street.open(&mut extended.nodes, committed);
+        extended.nodes[node].kind = NodeKind::Decision {
+            player: street.opener,
+            children,
+        };
+    }
+    Some(extended)
+}
+
+struct NextStreet {
+    structure: BettingStructure,
+    // Pot fractions to bet, with their labels.
+    fractions: Vec<(f64, String)>,
+    max_raises: u32,
+    min_bet_bb: f64,
+    stack_cap: f64,
+    pot_bb: f64,
+    // Who acts first on the street.
+    opener: usize,
+}
+
+impl NextStreet {
+    // The opener's actions once the street is dealt to a pot the players put `committed` into
+    // since the root: a check, then each bet.
+    fn open(&self, nodes: &mut Vec<TreeNode>, committed: [f64; 2]) -> Vec<usize> {
+        let last = 1 - self.opener;
+        let mut replies = vec![push_node(
+            nodes,
+            NodeKind::Terminal(TerminalKind::Showdown),
+            "check",
+            committed,
+        )];
+        replies.extend(self.bets(nodes, last, committed));
+        let mut children = vec![push_node(
+            nodes,
+            NodeKind::Decision {
+                player: last,
+                children: replies,
+            },
+            "check",
+            committed,
+        )];
+        children.extend(self.bets(nodes, self.opener, committed));
+        children
+    }
+
+    // `actor`'s bets into an unbet street, each leading to the other player's reply.
+    fn bets(&self, nodes: &mut Vec<TreeNode>, actor: usize, committed: [f64; 2]) -> Vec<usize> {
+        // Street totals count from what each player had in when the street was dealt; hero had
+        // nothing in before the root, so hero's chips since then are the street so far.
+        let base = committed.map(|chips| -chips);
+        let behind = self.stack_cap - committed[HERO];
+        let bet = |size: f64| {
+            let mut bet = committed;
+            bet[actor] += size;
+            bet
+        };
+        match self.structure {
+            BettingStructure::FixedLimit { bet_bb, cap } => {
+                let limit = Limit {
+                    bet_bb,
+                    cap,
+                    stack_cap: behind,
+                    base,
+                };
+                vec![limit.respond(nodes, 1 - actor, bet(bet_bb.min(behind)), 1, "bet")]
+            }
+            BettingStructure::NoLimit => {
+                let reraises = Reraises {
+                    max_raises: self.max_raises,
+                    stack_cap: behind,
+                    base,
+                };
+                let pot = self.pot_bb + committed[HERO] + committed[VILLAIN];
+                let mut sizes: Vec<(f64, &str)> = Vec::new();
+                for (fraction, label) in &self.fractions {
+                    let size = (fraction * pot).max(self.min_bet_bb);
+                    if size + SIZE_EPSILON < behind
+                        && !sizes
+                            .iter()
+                            .any(|(kept, _)| (kept - size).abs() < SIZE_EPSILON)
+                    {
+                        sizes.push((size, label));
+                    }
+                }
+                sizes.push((behind, "all-in"));
+                sizes
+                    .into_iter()
+                    .map(|(size, label)| reraises.respond(nodes, 1 - actor, bet(size), 0, label))
+                    .collect()
+            }
+        }
+    }
+}
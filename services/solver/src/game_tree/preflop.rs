@@ -0,0 +1,210 @@
+use crate::game_tree::eval::RakeModel;
+use crate::game_tree::legality::LegalizationReport;
+use crate::game_tree::{
+    push_node, ActionKind, GameTree, GameTreeAction, NodeKind, TerminalKind, TreeNode, HERO,
+    VILLAIN,
+};
+
+const SMALL_BLIND_BB: f64 = 0.5;
+
+// Seats that can raise first in at a six-handed table; the big blind only defends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Position {
+    Utg,
+    Hijack,
+    Cutoff,
+    Button,
+    SmallBlind,
+}
+
+impl Position {
+    pub const ALL: [Position; 5] = [
+        Position::Utg,
+        Position::Hijack,
+        Position::Cutoff,
+        Position::Button,
+        Position::SmallBlind,
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|position| position.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Position::Utg => "UTG",
+            Position::Hijack => "HJ",
+            Position::Cutoff => "CO",
+            Position::Button => "BTN",
+            Position::SmallBlind => "SB",
+        }
+    }
+
+    // What the seat has in the pot before anyone acts.
+    fn posted(&self) -> f64 {
+        match self {
+            Position::SmallBlind => SMALL_BLIND_BB,
+            _ => 0.0,
+        }
+    }
+}
+
+// Raise sizes as street totals in BB; an all-in is offered at every raise whatever they are.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreflopSizing {
+    pub open_bb: f64,
+    pub sb_open_bb: f64,
+    // Multiples of the raise being re-raised.
+    pub three_bet: f64,
+    pub four_bet: f64,
+}
+
+impl Default for PreflopSizing {
+    fn default() -> Self {
+        Self {
+            open_bb: 2.5,
+            sb_open_bb: 3.0,
+            three_bet: 3.0,
+            four_bet: 2.25,
+        }
+    }
+}
+
+// Hero raises first in from `position` against the big blind, everyone between folding: hero
+// opens or folds, the big blind 3-bets, hero 4-bets, and the big blind can only jam after that.
+// Every raise can be an all-in instead, and sizes at or past the stack are left to it. A call
+// ends the tree at a showdown of the buckets, so the flop is played out at equity.
+pub fn preflop_tree(position: Position, stack_bb: f64, sizing: &PreflopSizing) -> GameTree {
+    let builder = Builder {
+        sizing,
+        stack: stack_bb.max(1.0),
+        open: if position == Position::SmallBlind {
+            sizing.sb_open_bb
+        } else {
+            sizing.open_bb
+        },
+        posted: [position.posted(), 1.0],
+    };
+    let mut nodes = vec![TreeNode {
+        kind: NodeKind::Decision {
+            player: HERO,
+            children: Vec::new(),
+        },
+        label: String::new(),
+        committed: [0.0, 0.0],
+    }];
+    let mut actions = vec![GameTreeAction {
+        label: "fold".to_string(),
+        amount: 0.0,
+        kind: ActionKind::Fold,
+    }];
+    let mut children = vec![push_node(
+        &mut nodes,
+        NodeKind::Terminal(TerminalKind::Fold { folder: HERO }),
+        "fold",
+        [0.0, 0.0],
+    )];
+    for (label, to) in builder.raises(builder.posted[VILLAIN], 0) {
+        let mut raised = builder.posted;
+        raised[HERO] = to;
+        children.push(builder.respond(&mut nodes, VILLAIN, raised, 1, &label));
+        // The blinds are a bet to raise.
+        actions.push(GameTreeAction {
+            kind: ActionKind::aggressive(&label, builder.posted[VILLAIN]),
+            label,
+            amount: to,
+        });
+    }
+    if let NodeKind::Decision { children: root, .. } = &mut nodes[0].kind {
+        *root = children;
+    }
+
+    GameTree {
+        actions,
+        nodes,
+        effective_stack_bb: builder.stack,
+        pot_bb: SMALL_BLIND_BB + 1.0,
+        preflop: true,
+        rake: RakeModel::default(),
+        legalization: LegalizationReport::default(),
+        chips: None,
+    }
+}
+
+struct Builder<'a> {
+    sizing: &'a PreflopSizing,
+    stack: f64,
+    open: f64,
+    // Blinds each seat had in before the root; spent before the tree starts, so not counted in
+    // `committed`.
+    posted: [f64; 2],
+}
+
+impl Builder<'_> {
+    fn committed(&self, totals: [f64; 2]) -> [f64; 2] {
+        [totals[0] - self.posted[0], totals[1] - self.posted[1]]
+    }
+
+    // Raises open to a player facing `facing` after `raises` raises: the next size up, while it
+    // stays below the stack, then all-in.
+    fn raises(&self, facing: f64, raises: usize) -> Vec<(String, f64)> {
+        if facing >= self.stack {
+            return Vec::new();
+        }
+        let next = match raises {
+            0 => Some(("open", self.open)),
+            1 => Some(("3-bet", facing * self.sizing.three_bet)),
+            2 => Some(("4-bet", facing * self.sizing.four_bet)),
+            _ => None,
+        };
+        let mut options: Vec<(String, f64)> = next
+            .filter(|(_, to)| *to > facing && *to < self.stack)
+            .map(|(label, to)| (label.to_string(), to))
+            .into_iter()
+            .collect();
+        options.push(("all-in".to_string(), self.stack));
+        options
+    }
+
+    // `actor` faces the other player's raise to `totals[other]`; returns their decision node.
+    fn respond(
+        &self,
+        nodes: &mut Vec<TreeNode>,
+        actor: usize,
+        totals: [f64; 2],
+        raises: usize,
+        label: &str,
+    ) -> usize {
+        let other = 1 - actor;
+        let mut children = vec![push_node(
+            nodes,
+            NodeKind::Terminal(TerminalKind::Fold { folder: actor }),
+            "fold",
+            self.committed(totals),
+        )];
+        let mut called = totals;
+        called[actor] = totals[other];
+        children.push(push_node(
+            nodes,
+            NodeKind::Terminal(TerminalKind::Showdown),
+            "call",
+            self.committed(called),
+        ));
+        for (raise, to) in self.raises(totals[other], raises) {
+            let mut raised = totals;
+            raised[actor] = to;
+            children.push(self.respond(nodes, other, raised, raises + 1, &raise));
+        }
+        push_node(
+            nodes,
+            NodeKind::Decision {
+                player: actor,
+                children,
+            },
+            label,
+            self.committed(totals),
+        )
+    }
+}
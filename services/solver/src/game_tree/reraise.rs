@@ -0,0 +1,76 @@
+use crate::game_tree::{push_node, NodeKind, TerminalKind, TreeNode};
+
+const SIZE_EPSILON: f64 = 1e-6;
+// A no-limit re-raise goes to this many times the street total it faces.
+pub const RERAISE_MULTIPLE: f64 = 3.0;
+
+// The betting after a no-limit bet or raise: each reply folds, calls, or re-raises while the street
+// has raises left under `max_raises`. A re-raise goes to RERAISE_MULTIPLE times the bet faced or
+// all-in, and sizes at or past the stack are left to the all-in. Amounts are street totals above
+// hero's chips at the root, as in the fixed-limit tree.
+pub struct Reraises {
+    pub max_raises: u32,
+    pub stack_cap: f64,
+    // Each player's street total before the root decision.
+    pub base: [f64; 2],
+}
+
+impl Reraises {
+    // `actor` faces the other player's latest bet after `raises` raises on the street; returns
+    // the index of their decision node.
+    pub fn respond(
+        &self,
+        nodes: &mut Vec<TreeNode>,
+        actor: usize,
+        committed: [f64; 2],
+        raises: u32,
+        label: &str,
+    ) -> usize {
+        let other = 1 - actor;
+        let facing = self.base[other] + committed[other];
+        let mut children = vec![push_node(
+            nodes,
+            NodeKind::Terminal(TerminalKind::Fold { folder: actor }),
+            "fold",
+            committed,
+        )];
+        let mut called = committed;
+        called[actor] = (facing - self.base[actor]).max(0.0);
+        children.push(push_node(
+            nodes,
+            NodeKind::Terminal(TerminalKind::Showdown),
+            "call",
+            called,
+        ));
+        if raises < self.max_raises && facing + SIZE_EPSILON < self.stack_cap {
+            let sized = RERAISE_MULTIPLE * facing;
+            if sized + SIZE_EPSILON < self.stack_cap {
+                children.push(self.raise_to(nodes, actor, committed, raises, sized, "raise"));
+            }
+            children.push(self.raise_to(nodes, actor, committed, raises, self.stack_cap, "all-in"));
+        }
+        push_node(
+            nodes,
+            NodeKind::Decision {
+                player: actor,
+                children,
+            },
+            label,
+            committed,
+        )
+    }
+
+    fn raise_to(
+        &self,
+        nodes: &mut Vec<TreeNode>,
+        actor: usize,
+        committed: [f64; 2],
+        raises: u32,
+        total: f64,
+        label: &str,
+    ) -> usize {
+        let mut raised = committed;
+        raised[actor] = total - self.base[actor];
+        self.respond(nodes, 1 - actor, raised, raises + 1, label)
+    }
+}
@@ -0,0 +1,82 @@
+// 1/phi: each step keeps this share of the bracket.
+const INVERSE_PHI: f64 = 0.618_033_988_749_894_9;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SizeSearch {
+    // Pot fractions bracketing the search.
+    pub min: f64,
+    pub max: f64,
+    // Stop once the bracket is this narrow.
+    pub tolerance: f64,
+    // Stop after this many evaluations even if the bracket is still wide.
+    pub max_evaluations: usize,
+}
+
+impl Default for SizeSearch {
+    fn default() -> Self {
+        Self {
+            min: 0.1,
+            max: 2.0,
+            tolerance: 0.02,
+            max_evaluations: 12,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SizeEvaluation {
+    pub pot_fraction: f64,
+    pub ev: f64,
+}
+
+// Golden-section search for the pot fraction `evaluate` values most. EV is assumed unimodal in
+// the size; when it is not, the search still returns the best size it tried. Evaluations are
+// returned in the order they were made.
+pub fn search_bet_size<E>(
+    search: &SizeSearch,
+    mut evaluate: impl FnMut(f64) -> Result<f64, E>,
+) -> Result<Vec<SizeEvaluation>, E> {
+    let (mut low, mut high) = (search.min.min(search.max), search.max.max(search.min));
+    let budget = search.max_evaluations.max(1);
+    let mut evaluations = Vec::with_capacity(budget);
+    let mut probe = |fraction: f64, evaluations: &mut Vec<SizeEvaluation>| {
+        let ev = evaluate(fraction)?;
+        evaluations.push(SizeEvaluation {
+            pot_fraction: fraction,
+            ev,
+        });
+        Ok(ev)
+    };
+
+    if budget == 1 || high - low <= search.tolerance {
+        probe((low + high) / 2.0, &mut evaluations)?;
+        return Ok(evaluations);
+    }
+    let mut left = high - INVERSE_PHI * (high - low);
+    let mut right = low + INVERSE_PHI * (high - low);
+    let mut left_ev = probe(left, &mut evaluations)?;
+    let mut right_ev = probe(right, &mut evaluations)?;
+    while evaluations.len() < budget && high - low > search.tolerance {
+        if left_ev >= right_ev {
+            high = right;
+            right = left;
+            right_ev = left_ev;
+            left = high - INVERSE_PHI * (high - low);
+            left_ev = probe(left, &mut evaluations)?;
+        } else {
+            low = left;
+            left = right;
+            left_ev = right_ev;
+            right = low + INVERSE_PHI * (high - low);
+            right_ev = probe(right, &mut evaluations)?;
+        }
+    }
+    Ok(evaluations)
+}
+
+pub fn best_size(evaluations: &[SizeEvaluation]) -> Option<SizeEvaluation> {
+    evaluations
+        .iter()
+        .copied()
+        .max_by(|a, b| a.ev.total_cmp(&b.ev))
+}
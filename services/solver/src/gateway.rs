@@ -0,0 +1,220 @@
+use axum::http::header::{HeaderMap, HeaderValue};
+use axum::http::{Request, Response};
+use hyper::body::{Buf, Bytes, HttpBody};
+use serde::Serialize;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tonic::body::BoxBody;
+use tonic::codegen::Service;
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::server::NamedService;
+use tonic::{Code, Status};
+
+// Larger bodies are refused before they are read.
+pub const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+// How long a caller gets to send the whole body once its headers are in.
+pub const BODY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Every RPC, each reachable as a POST of its request message as JSON to `path()`. SolveStream
+// answers with one JSON update per line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Route {
+    Solve,
+    Validate,
+    Estimate,
+    SubmitSolve,
+    SolveStatus,
+    SolveResult,
+    SolveStream,
+    Prewarm,
+    History,
+    DiffStrategies,
+    EvaluateHands,
+    AbstractionInfo,
+    ExportTree,
+    ActionRemoval,
+    BetSizeSearch,
+    StackSweep,
+    Benchmark,
+}
+
+impl Route {
+    pub const ALL: [Route; 17] = [
+        Route::Solve,
+        Route::Validate,
+        Route::Estimate,
+        Route::SubmitSolve,
+        Route::SolveStatus,
+        Route::SolveResult,
+        Route::SolveStream,
+        Route::Prewarm,
+        Route::History,
+        Route::DiffStrategies,
+        Route::EvaluateHands,
+        Route::AbstractionInfo,
+        Route::ExportTree,
+        Route::ActionRemoval,
+        Route::BetSizeSearch,
+        Route::StackSweep,
+        Route::Benchmark,
+    ];
+
+    pub fn parse(path: &str) -> Option<Self> {
+        let path = path.split('?').next().unwrap_or_default();
+        Self::ALL.into_iter().find(|route| route.path() == path)
+    }
+
+    pub fn path(&self) -> &'static str {
+        match self {
+            Route::Solve => "/v1/solve",
+            Route::Validate => "/v1/validate",
+            Route::Estimate => "/v1/estimate",
+            Route::SubmitSolve => "/v1/jobs/submit",
+            Route::SolveStatus => "/v1/jobs/status",
+            Route::SolveResult => "/v1/jobs/result",
+            Route::SolveStream => "/v1/solve-stream",
+            Route::Prewarm => "/v1/prewarm",
+            Route::History => "/v1/history",
+            Route::DiffStrategies => "/v1/diff",
+            Route::EvaluateHands => "/v1/evaluate-hands",
+            Route::AbstractionInfo => "/v1/abstraction-info",
+            Route::ExportTree => "/v1/export-tree",
+            Route::ActionRemoval => "/v1/action-removal",
+            Route::BetSizeSearch => "/v1/bet-size",
+            Route::StackSweep => "/v1/stack-sweep",
+            Route::Benchmark => "/v1/benchmark",
+        }
+    }
+}
+
+// The gateway's routes as a service the gRPC server mounts beside the Solver service: tonic sends
+// every `/v1/...` path here by the name, and the rest to gRPC.
+#[derive(Clone)]
+pub struct GatewayService {
+    router: axum::Router,
+}
+
+impl GatewayService {
+    pub fn new(router: axum::Router) -> Self {
+        Self { router }
+    }
+}
+
+impl NamedService for GatewayService {
+    const NAME: &'static str = "v1";
+}
+
+impl Service<Request<hyper::Body>> for GatewayService {
+    type Response = Response<BoxBody>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<hyper::Body>) -> Self::Future {
+        let response = self.router.call(request);
+        Box::pin(async move {
+            let response = response.await?;
+            Ok(response.map(|body| {
+                body.map_err(|error| Status::internal(error.to_string()))
+                    .boxed_unsync()
+            }))
+        })
+    }
+}
+
+// The headers a gRPC call would carry, so API keys and deadlines work the same way.
+pub fn call_metadata(headers: &HeaderMap) -> MetadataMap {
+    let mut metadata = MetadataMap::new();
+    for name in ["authorization", "x-api-key", "grpc-timeout"] {
+        if let Some(value) = headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| MetadataValue::try_from(value).ok())
+        {
+            metadata.insert(name, value);
+        }
+    }
+    metadata
+}
+
+// Reads a request body of at most `limit` bytes, giving up after `timeout` so a caller trickling
+// bytes can't hold a task forever.
+pub async fn read_body<B>(mut body: B, limit: usize, timeout: Duration) -> Result<Bytes, Status>
+where
+    B: HttpBody + Unpin,
+    B::Error: std::fmt::Display,
+{
+    let read = async {
+        let mut bytes = Vec::new();
+        while let Some(chunk) = body.data().await {
+            let chunk = chunk.map_err(|error| Status::invalid_argument(error.to_string()))?;
+            if bytes.len() + chunk.remaining() > limit {
+                return Err(Status::resource_exhausted("request body too large"));
+            }
+            bytes.extend_from_slice(chunk.chunk());
+        }
+        Ok(Bytes::from(bytes))
+    };
+    tokio::time::timeout(timeout, read)
+        .await
+        .map_err(|_| Status::deadline_exceeded("request body not received in time"))?
+}
+
+pub fn http_status(code: Code) -> u16 {
+    match code {
+        Code::Ok => 200,
+        Code::InvalidArgument | Code::OutOfRange => 400,
+        Code::FailedPrecondition => 412,
+        Code::Unauthenticated => 401,
+        Code::PermissionDenied => 403,
+        Code::NotFound => 404,
+        Code::AlreadyExists | Code::Aborted => 409,
+        Code::ResourceExhausted => 429,
+        Code::Cancelled => 499,
+        Code::Unimplemented => 501,
+        Code::Unavailable => 503,
+        Code::DeadlineExceeded => 504,
+        _ => 500,
+    }
+}
+
+#[derive(Serialize)]
+pub struct ErrorBody<'a> {
+    pub code: String,
+    pub message: &'a str,
+}
+
+pub fn error_body(status: &Status) -> String {
+    serde_json::to_string(&ErrorBody {
+        code: format!("{:?}", status.code()),
+        message: status.message(),
+    })
+    .unwrap_or_default()
+}
+
+// The CORS headers a browser on `allow_origin` needs to read a response; none when it is empty.
+pub fn cors_headers(allow_origin: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let Some(origin) = Some(allow_origin)
+        .filter(|origin| !origin.is_empty())
+        .and_then(|origin| HeaderValue::from_str(origin).ok())
+    else {
+        return headers;
+    };
+    headers.insert("access-control-allow-origin", origin);
+    headers.insert(
+        "access-control-allow-methods",
+        HeaderValue::from_static("POST, OPTIONS"),
+    );
+    headers.insert(
+        "access-control-allow-headers",
+        HeaderValue::from_static("authorization, content-type, grpc-timeout, x-api-key"),
+    );
+    headers
+}
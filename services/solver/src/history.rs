@@ -0,0 +1,273 @@
+use crate::config::HistoryConfig;
+use crate::error::SolverError;
+use crate::solver_proto::{HistoryEntry, HistoryQuery, SubgameRequest, SubgameResponse};
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tonic::Status;
+
+#[cfg(feature = "history")]
+mod sqlite;
+
+#[cfg(feature = "history")]
+use sqlite::Store;
+
+pub const DEFAULT_QUERY_LIMIT: u32 = 100;
+pub const MAX_QUERY_LIMIT: u32 = 1000;
+// Old records are deleted at most this often, from whichever solve records next.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug)]
+pub enum HistoryError {
+    Unsupported,
+    Storage(String),
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryError::Unsupported => {
+                write!(
+                    f,
+                    "solve history needs the solver built with the `history` feature"
+                )
+            }
+            HistoryError::Storage(message) => {
+                write!(f, "solve history storage failed: {}", message)
+            }
+            HistoryError::Corrupt(what) => write!(f, "solve history is corrupt: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+impl From<HistoryError> for Status {
+    fn from(error: HistoryError) -> Self {
+        match error {
+            HistoryError::Unsupported => Status::failed_precondition(error.to_string()),
+            _ => Status::internal(error.to_string()),
+        }
+    }
+}
+
+// One solve as the history keeps it: the request as received, what came of it, and the full
+// response when strategies are stored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryRecord {
+    pub id: u64,
+    pub recorded_at_ms: i64,
+    pub request: SubgameRequest,
+    pub status: String,
+    pub error: String,
+    pub source: String,
+    pub exploitability: f64,
+    pub iterations: u64,
+    pub compute_time_ms: i32,
+    pub seed: u64,
+    pub response: Option<SubgameResponse>,
+}
+
+impl HistoryRecord {
+    pub fn new(
+        request: &SubgameRequest,
+        result: &Result<SubgameResponse, SolverError>,
+        recorded_at_ms: i64,
+        keep_response: bool,
+    ) -> Self {
+        let mut record = Self {
+            id: 0,
+            recorded_at_ms,
+            request: request.clone(),
+            status: "OK".to_string(),
+            error: String::new(),
+            source: String::new(),
+            exploitability: 0.0,
+            iterations: 0,
+            compute_time_ms: 0,
+            seed: 0,
+            response: None,
+        };
+        match result {
+            Ok(response) => {
+                record.source = response.source.clone();
+                record.exploitability = response.exploitability;
+                record.iterations = response.iterations;
+                record.compute_time_ms = response.compute_time_ms;
+                record.seed = response.seed;
+                record.response = keep_response.then(|| response.clone());
+            }
+            Err(error) => {
+                record.status = error.reason().to_string();
+                record.error = error.to_string();
+            }
+        }
+        record
+    }
+
+    pub fn to_proto(&self, include_response: bool) -> HistoryEntry {
+        HistoryEntry {
+            id: self.id,
+            recorded_at_ms: self.recorded_at_ms,
+            state_fingerprint: self.request.state_fingerprint.clone(),
+            request: Some(self.request.clone()),
+            status: self.status.clone(),
+            error: self.error.clone(),
+            source: self.source.clone(),
+            exploitability: self.exploitability,
+            iterations: self.iterations,
+            compute_time_ms: self.compute_time_ms,
+            seed: self.seed,
+            response: self.response.clone().filter(|_| include_response),
+        }
+    }
+}
+
+// What a query selects, with the proto's "0 means unset" fields resolved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryFilter {
+    pub state_fingerprint: Option<String>,
+    pub since_ms: i64,
+    pub until_ms: i64,
+    pub status: Option<String>,
+    pub limit: u32,
+}
+
+impl HistoryFilter {
+    pub fn from_query(query: &HistoryQuery) -> Self {
+        let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_string());
+        Self {
+            state_fingerprint: non_empty(&query.state_fingerprint),
+            since_ms: query.since_ms.max(0),
+            until_ms: if query.until_ms > 0 {
+                query.until_ms
+            } else {
+                i64::MAX
+            },
+            status: non_empty(&query.status),
+            limit: match query.limit {
+                0 => DEFAULT_QUERY_LIMIT,
+                limit => limit.min(MAX_QUERY_LIMIT),
+            },
+        }
+    }
+
+    pub fn matches(&self, record: &HistoryRecord) -> bool {
+        self.state_fingerprint
+            .as_ref()
+            .is_none_or(|fingerprint| *fingerprint == record.request.state_fingerprint)
+            && self
+                .status
+                .as_ref()
+                .is_none_or(|status| *status == record.status)
+            && record.recorded_at_ms >= self.since_ms
+            && record.recorded_at_ms < self.until_ms
+    }
+}
+
+// Every solve the engine runs, kept in an embedded SQLite database for auditing after the
+// session. Records older than the retention period are deleted as new ones come in.
+pub struct SolveHistory {
+    config: HistoryConfig,
+    store: Store,
+    last_pruned: Mutex<Instant>,
+}
+
+impl SolveHistory {
+    #[cfg(feature = "history")]
+    pub fn open(config: &HistoryConfig) -> Result<Self, HistoryError> {
+        let history = Self {
+            config: config.clone(),
+            store: Store::open(std::path::Path::new(&config.path))?,
+            last_pruned: Mutex::new(Instant::now()),
+        };
+        history.prune()?;
+        Ok(history)
+    }
+
+    #[cfg(not(feature = "history"))]
+    pub fn open(_config: &HistoryConfig) -> Result<Self, HistoryError> {
+        Err(HistoryError::Unsupported)
+    }
+
+    pub fn stores_strategy(&self) -> bool {
+        self.config.store_strategy
+    }
+
+    pub fn record(
+        &self,
+        request: &SubgameRequest,
+        result: &Result<SubgameResponse, SolverError>,
+    ) -> Result<u64, HistoryError> {
+        let record = HistoryRecord::new(request, result, now_ms(), self.config.store_strategy);
+        let id = self.store.insert(&record)?;
+        let due = {
+            let mut last_pruned = self.last_pruned();
+            let due = last_pruned.elapsed() >= PRUNE_INTERVAL;
+            if due {
+                *last_pruned = Instant::now();
+            }
+            due
+        };
+        if due {
+            self.prune()?;
+        }
+        Ok(id)
+    }
+
+    pub fn query(&self, query: &HistoryQuery) -> Result<Vec<HistoryRecord>, HistoryError> {
+        self.store
+            .select(&HistoryFilter::from_query(query), query.include_strategy)
+    }
+
+    // Deletes records past the retention period and returns how many went.
+    pub fn prune(&self) -> Result<usize, HistoryError> {
+        match retention_cutoff(self.config.retention_days, now_ms()) {
+            Some(cutoff) => self.store.delete_before(cutoff),
+            None => Ok(0),
+        }
+    }
+
+    fn last_pruned(&self) -> MutexGuard<'_, Instant> {
+        self.last_pruned
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// Records before the returned time are past retention; None keeps them all.
+pub fn retention_cutoff(retention_days: u32, now_ms: i64) -> Option<i64> {
+    (retention_days > 0).then(|| now_ms - i64::from(retention_days) * DAY_MS)
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+// Without the `history` feature no store can be opened, so `SolveHistory` never exists.
+#[cfg(not(feature = "history"))]
+enum Store {}
+
+#[cfg(not(feature = "history"))]
+impl Store {
+    fn insert(&self, _record: &HistoryRecord) -> Result<u64, HistoryError> {
+        match *self {}
+    }
+
+    fn select(
+        &self,
+        _filter: &HistoryFilter,
+        _with_response: bool,
+    ) -> Result<Vec<HistoryRecord>, HistoryError> {
+        match *self {}
+    }
+
+    fn delete_before(&self, _cutoff_ms: i64) -> Result<usize, HistoryError> {
+        match *self {}
+    }
+}
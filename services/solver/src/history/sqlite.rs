@@ -0,0 +1,169 @@
+use super::{HistoryError, HistoryFilter, HistoryRecord};
+use crate::solver_proto::{SubgameRequest, SubgameResponse};
+use prost::Message;
+use rusqlite::{params, Connection};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+// The request and response are stored protobuf-encoded; the summary columns are what queries
+// filter on.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS solves (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recorded_at_ms INTEGER NOT NULL,
+    state_fingerprint TEXT NOT NULL,
+    request BLOB NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT NOT NULL,
+    source TEXT NOT NULL,
+    exploitability REAL NOT NULL,
+    iterations INTEGER NOT NULL,
+    compute_time_ms INTEGER NOT NULL,
+    seed INTEGER NOT NULL,
+    response BLOB
+);
+CREATE INDEX IF NOT EXISTS solves_by_spot ON solves (state_fingerprint, recorded_at_ms);
+CREATE INDEX IF NOT EXISTS solves_by_time ON solves (recorded_at_ms);
+";
+
+impl From<rusqlite::Error> for HistoryError {
+    fn from(error: rusqlite::Error) -> Self {
+        HistoryError::Storage(error.to_string())
+    }
+}
+
+pub struct Store {
+    connection: Mutex<Connection>,
+}
+
+struct StoredRow {
+    id: i64,
+    recorded_at_ms: i64,
+    request: Vec<u8>,
+    status: String,
+    error: String,
+    source: String,
+    exploitability: f64,
+    iterations: i64,
+    compute_time_ms: i32,
+    seed: i64,
+    response: Option<Vec<u8>>,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self, HistoryError> {
+        let connection = Connection::open(path)?;
+        connection.busy_timeout(Duration::from_secs(5))?;
+        // The pragma answers with the mode it switched to, so it has to be run as a query.
+        connection.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    pub fn insert(&self, record: &HistoryRecord) -> Result<u64, HistoryError> {
+        let connection = self.lock();
+        connection.execute(
+            "INSERT INTO solves (recorded_at_ms, state_fingerprint, request, status, error, source,
+                exploitability, iterations, compute_time_ms, seed, response)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                record.recorded_at_ms,
+                record.request.state_fingerprint,
+                record.request.encode_to_vec(),
+                record.status,
+                record.error,
+                record.source,
+                record.exploitability,
+                record.iterations as i64,
+                record.compute_time_ms,
+                record.seed as i64,
+                record.response.as_ref().map(Message::encode_to_vec),
+            ],
+        )?;
+        Ok(connection.last_insert_rowid() as u64)
+    }
+
+    pub fn select(
+        &self,
+        filter: &HistoryFilter,
+        with_response: bool,
+    ) -> Result<Vec<HistoryRecord>, HistoryError> {
+        let connection = self.lock();
+        let mut statement = connection.prepare(
+            "SELECT id, recorded_at_ms, request, status, error, source, exploitability, iterations,
+                compute_time_ms, seed, CASE WHEN ?6 THEN response END
+             FROM solves
+             WHERE (?1 IS NULL OR state_fingerprint = ?1)
+                AND (?2 IS NULL OR status = ?2)
+                AND recorded_at_ms >= ?3 AND recorded_at_ms < ?4
+             ORDER BY id DESC
+             LIMIT ?5",
+        )?;
+        let rows = statement.query_map(
+            params![
+                filter.state_fingerprint,
+                filter.status,
+                filter.since_ms,
+                filter.until_ms,
+                filter.limit,
+                with_response,
+            ],
+            |row| {
+                Ok(StoredRow {
+                    id: row.get(0)?,
+                    recorded_at_ms: row.get(1)?,
+                    request: row.get(2)?,
+                    status: row.get(3)?,
+                    error: row.get(4)?,
+                    source: row.get(5)?,
+                    exploitability: row.get(6)?,
+                    iterations: row.get(7)?,
+                    compute_time_ms: row.get(8)?,
+                    seed: row.get(9)?,
+                    response: row.get(10)?,
+                })
+            },
+        )?;
+        rows.map(|row| decode(row?)).collect()
+    }
+
+    pub fn delete_before(&self, cutoff_ms: i64) -> Result<usize, HistoryError> {
+        let deleted = self.lock().execute(
+            "DELETE FROM solves WHERE recorded_at_ms < ?1",
+            params![cutoff_ms],
+        )?;
+        Ok(deleted)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        self.connection
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn decode(row: StoredRow) -> Result<HistoryRecord, HistoryError> {
+    let request = SubgameRequest::decode(row.request.as_slice())
+        .map_err(|_| HistoryError::Corrupt("undecodable request"))?;
+    let response = row
+        .response
+        .map(|bytes| SubgameResponse::decode(bytes.as_slice()))
+        .transpose()
+        .map_err(|_| HistoryError::Corrupt("undecodable response"))?;
+    Ok(HistoryRecord {
+        id: row.id as u64,
+        recorded_at_ms: row.recorded_at_ms,
+        request,
+        status: row.status,
+        error: row.error,
+        source: row.source,
+        exploitability: row.exploitability,
+        iterations: row.iterations as u64,
+        compute_time_ms: row.compute_time_ms,
+        seed: row.seed as u64,
+        response,
+    })
+}
@@ -0,0 +1,172 @@
+use crate::error::SolverError;
+use crate::progress::SolveProgress;
+use crate::solver_proto::{SolveJob, SubgameResponse};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JobState {
+    Queued,
+    Running,
+    Done(Box<SubgameResponse>),
+    Failed(SolverError),
+}
+
+impl JobState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobState::Queued => "queued",
+            JobState::Running => "running",
+            JobState::Done(_) => "done",
+            JobState::Failed(_) => "failed",
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobState::Done(_) | JobState::Failed(_))
+    }
+}
+
+struct Job {
+    state: JobState,
+    progress: Arc<SolveProgress>,
+    finished_at: Option<Instant>,
+}
+
+// Solves submitted to run in the background. A finished job keeps its result for `ttl`, then
+// is dropped the next time the store is used; unfinished jobs never expire.
+pub struct JobStore {
+    ttl: Duration,
+    max_jobs: usize,
+    next_id: AtomicU64,
+    // Keys job IDs, so they can't be guessed from one another.
+    hasher: RandomState,
+    jobs: Mutex<HashMap<String, Job>>,
+}
+
+impl JobStore {
+    pub fn new(ttl: Duration, max_jobs: usize) -> Self {
+        Self {
+            ttl,
+            max_jobs: max_jobs.max(1),
+            next_id: AtomicU64::new(1),
+            hasher: RandomState::new(),
+            jobs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Registers a queued job; the caller runs the solve and reports back through `start` and
+    // `finish`, feeding iterations to the returned progress.
+    pub fn submit(&self) -> Result<(String, Arc<SolveProgress>), SolverError> {
+        let mut jobs = self.lock();
+        if jobs.len() >= self.max_jobs {
+            return Err(SolverError::TooManyJobs {
+                limit: self.max_jobs,
+            });
+        }
+        let sequence = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut hasher = self.hasher.build_hasher();
+        hasher.write_u64(sequence);
+        let job_id = format!("{:x}-{:016x}", sequence, hasher.finish());
+        let progress = Arc::new(SolveProgress::with_exploitability());
+        jobs.insert(
+            job_id.clone(),
+            Job {
+                state: JobState::Queued,
+                progress: progress.clone(),
+                finished_at: None,
+            },
+        );
+        Ok((job_id, progress))
+    }
+
+    pub fn start(&self, job_id: &str) {
+        if let Some(job) = self.lock().get_mut(job_id) {
+            job.state = JobState::Running;
+        }
+    }
+
+    pub fn finish(&self, job_id: &str, result: Result<SubgameResponse, SolverError>) {
+        if let Some(job) = self.lock().get_mut(job_id) {
+            job.state = match result {
+                Ok(response) => JobState::Done(Box::new(response)),
+                Err(error) => JobState::Failed(error),
+            };
+            job.finished_at = Some(Instant::now());
+        }
+    }
+
+    pub fn status(&self, job_id: &str) -> Result<SolveJob, SolverError> {
+        let jobs = self.lock();
+        let job = jobs.get(job_id).ok_or_else(|| not_found(job_id))?;
+        let progress = if job.state.is_finished() {
+            1.0
+        } else {
+            job.progress.fraction()
+        };
+        let expires_in_ms = job
+            .finished_at
+            .map(|finished_at| self.ttl.saturating_sub(finished_at.elapsed()).as_millis() as i64)
+            .unwrap_or(0);
+        Ok(SolveJob {
+            job_id: job_id.to_string(),
+            state: job.state.as_str().to_string(),
+            progress,
+            error: match &job.state {
+                JobState::Failed(error) => Some(error.detail()),
+                _ => None,
+            },
+            expires_in_ms,
+            detail: Some(job.progress.to_proto()),
+        })
+    }
+
+    // The response of a finished job, or the error it failed with. Fetching doesn't remove the
+    // job, so a caller that lost the response can fetch again until it expires.
+    pub fn result(&self, job_id: &str) -> Result<SubgameResponse, SolverError> {
+        let jobs = self.lock();
+        let job = jobs.get(job_id).ok_or_else(|| not_found(job_id))?;
+        match &job.state {
+            JobState::Done(response) => Ok((**response).clone()),
+            JobState::Failed(error) => Err(error.clone()),
+            JobState::Queued | JobState::Running => Err(SolverError::JobPending {
+                job_id: job_id.to_string(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Job>> {
+        let mut jobs = match self.jobs.lock() {
+            Ok(jobs) => jobs,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let ttl = self.ttl;
+        jobs.retain(|_, job| {
+            job.finished_at
+                .is_none_or(|finished_at| finished_at.elapsed() < ttl)
+        });
+        jobs
+    }
+}
+
+fn not_found(job_id: &str) -> SolverError {
+    SolverError::JobNotFound {
+        job_id: job_id.to_string(),
+    }
+}
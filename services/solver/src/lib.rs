@@ -3,7 +3,28 @@ pub mod solver_proto {
 }
 
 pub mod abstraction;
+pub mod admission;
+pub mod affinity;
+pub mod archive;
+pub mod auth;
 pub mod budget;
+pub mod cache;
+pub mod cards;
 pub mod cfr;
+pub mod config;
+pub mod dedup;
+pub mod error;
 pub mod game_tree;
+pub mod gateway;
+pub mod history;
+pub mod jobs;
+pub mod metrics;
+pub mod persist;
+pub mod pool;
+pub mod preflop;
+pub mod profiling;
+pub mod progress;
+pub mod rng;
 pub mod solver;
+pub mod tournament;
+pub mod validation;
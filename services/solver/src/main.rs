@@ -1,12 +1,188 @@
+use axum::body::{Body, StreamBody};
+use axum::http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use axum::http::{Method, Request as HttpRequest, StatusCode};
+use axum::response::{IntoResponse, Response as HttpResponse};
+use axum::routing::any;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use solver::admission::{AdmissionControl, SolvePriority};
+use solver::affinity;
+use solver::archive::{open_store, StrategyArchive};
+use solver::auth::ApiKeyAuth;
+use solver::budget::{clamp_to_deadline, parse_grpc_timeout};
+use solver::config::{ConfigError, ProfilingConfig, SolverConfig};
+use solver::dedup::SolveDedup;
+use solver::error::SolverError;
+use solver::gateway::{
+    call_metadata, cors_headers, error_body, http_status, read_body, GatewayService, Route,
+    BODY_TIMEOUT, MAX_BODY_BYTES,
+};
+use solver::history::SolveHistory;
+use solver::jobs::JobStore;
+use solver::metrics::SolverMetrics;
+use solver::profiling::{collect_profile, profile_request, ProfileError};
+use solver::progress::SolveProgress;
 use solver::solver::SolverEngine;
 use solver::solver_proto::solver_server::{Solver, SolverServer};
-use solver::solver_proto::{SubgameRequest, SubgameResponse};
+use solver::solver_proto::{
+    AbstractionInfo, AbstractionInfoRequest, ActionRemovalReport, ActionRemovalRequest,
+    BenchmarkReport, BenchmarkRequest, BetSizeSearchReport, BetSizeSearchRequest,
+    HandEvaluationReport, HandEvaluationRequest, HistoryPage, HistoryQuery, PrewarmReply,
+    PrewarmRequest, SolveJob, SolveJobRef, SolveUpdate, StackSweepReport, StackSweepRequest,
+    StrategyDiffReport, StrategyDiffRequest, SubgameRequest, SubgameResponse, TreeEstimate,
+    TreeExport, TreeExportRequest, ValidationReport,
+};
+use solver::validation::to_report;
+use std::convert::Infallible;
 use std::env;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::metadata::MetadataMap;
+use tonic::transport::server::Routes;
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
 use tonic::{Request, Response, Status};
+use tracing::{info, info_span, warn, Span};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
 
-#[derive(Default)]
+// How long a solve may queue when it has no time budget to spend on waiting: one with only an
+// iteration budget, or a background job.
+const UNTIMED_QUEUE_WAIT: Duration = Duration::from_secs(60);
+// How long exiting waits for blocking tasks still running after the drain.
+const EXIT_WAIT: Duration = Duration::from_secs(1);
+// How long the server keeps running after the drain cancels solves, for them to answer.
+const CANCEL_FLUSH: Duration = Duration::from_secs(1);
+// How often SolveStream reports progress.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Clone)]
 struct SolverService {
-    engine: SolverEngine,
+    engine: Arc<SolverEngine>,
+    admission: Arc<AdmissionControl>,
+    jobs: Arc<JobStore>,
+    dedup: Arc<SolveDedup>,
+    deadline_slack: Duration,
+}
+
+impl SolverService {
+    // A caller that set a gRPC deadline stops waiting when it passes, so the budget must end
+    // earlier still.
+    fn apply_deadline(
+        &self,
+        metadata: &MetadataMap,
+        request: &mut SubgameRequest,
+    ) -> Result<(), Status> {
+        let Some(timeout) = metadata
+            .get("grpc-timeout")
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_grpc_timeout)
+        else {
+            return Ok(());
+        };
+        let budget_ms = clamp_to_deadline(request.budget_ms, timeout, self.deadline_slack);
+        if budget_ms <= 0 && request.budget_ms >= 0 {
+            return Err(SolverError::BudgetExhausted { budget_ms }.into());
+        }
+        request.budget_ms = budget_ms;
+        Ok(())
+    }
+
+    fn reject_invalid(&self, request: &SubgameRequest) -> Result<(), SolverError> {
+        let blocking: Vec<_> = self
+            .engine
+            .validate(request)
+            .into_iter()
+            .filter(|issue| issue.blocking)
+            .collect();
+        if blocking.is_empty() {
+            Ok(())
+        } else {
+            Err(SolverError::InvalidRequest(blocking))
+        }
+    }
+
+    // Solves are CPU-bound, so they run on the blocking pool.
+    fn spawn_solve(
+        &self,
+        span: Span,
+        request: SubgameRequest,
+        progress: Arc<SolveProgress>,
+    ) -> JoinHandle<Result<SubgameResponse, SolverError>> {
+        let engine = self.engine.clone();
+        let admission = self.admission.clone();
+        tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            solve_admitted(&engine, &admission, request, &progress)
+        })
+    }
+
+    // Like `spawn_solve`, but joins an identical solve already in flight instead of starting
+    // another.
+    fn spawn_shared_solve(
+        &self,
+        span: Span,
+        request: SubgameRequest,
+    ) -> JoinHandle<Result<SubgameResponse, SolverError>> {
+        let engine = self.engine.clone();
+        let admission = self.admission.clone();
+        let dedup = self.dedup.clone();
+        tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            let (mut result, joined) = dedup.run(&request, || {
+                solve_admitted(&engine, &admission, request.clone(), &Arc::default())
+            });
+            if joined {
+                engine.metrics().record_deduplicated();
+                // The leader's stamp names its own budgets; a blueprint answer keeps the stamp of
+                // the solve that produced it.
+                match &mut result {
+                    Ok(response) if response.source != "blueprint" => {
+                        response.request_hash = engine.request_hash(&request);
+                    }
+                    _ => {}
+                }
+            }
+            result
+        })
+    }
+}
+
+// Queues for a solver slot, spending the wait out of the request's time budget, then solves.
+fn solve_admitted(
+    engine: &SolverEngine,
+    admission: &Arc<AdmissionControl>,
+    mut request: SubgameRequest,
+    progress: &Arc<SolveProgress>,
+) -> Result<SubgameResponse, SolverError> {
+    let timed = request.budget_ms > 0 || request.budget_iterations == 0;
+    let max_wait = if timed {
+        Duration::from_millis(request.budget_ms.max(0) as u64)
+    } else {
+        UNTIMED_QUEUE_WAIT
+    };
+    let priority = SolvePriority::parse(&request.priority).unwrap_or_default();
+    let permit = match admission.acquire_with_priority(max_wait, priority) {
+        Ok(permit) => permit,
+        Err(error) => {
+            engine.record_history(&request, &Err(error.clone()));
+            return Err(error);
+        }
+    };
+    if timed {
+        request.budget_ms -= permit.waited().as_millis() as i32;
+    }
+    engine.solve_with_progress(&request, progress)
 }
 
 #[tonic::async_trait]
@@ -15,26 +191,917 @@ impl Solver for SolverService {
         &self,
         request: Request<SubgameRequest>,
     ) -> Result<Response<SubgameResponse>, Status> {
-        let response = self.engine.solve(&request.into_inner());
+        let span = info_span!("grpc.solve");
+        link_remote_parent(&span, request.metadata());
+        let (metadata, _, mut request) = request.into_parts();
+        self.apply_deadline(&metadata, &mut request)?;
+        let response = self
+            .spawn_shared_solve(span, request)
+            .await
+            .map_err(|error| Status::internal(format!("solve task failed: {}", error)))??;
+        Ok(Response::new(response))
+    }
+
+    type SolveStreamStream = ReceiverStream<Result<SolveUpdate, Status>>;
+
+    async fn solve_stream(
+        &self,
+        request: Request<SubgameRequest>,
+    ) -> Result<Response<Self::SolveStreamStream>, Status> {
+        let span = info_span!("grpc.solve_stream");
+        link_remote_parent(&span, request.metadata());
+        let (metadata, _, mut request) = request.into_parts();
+        self.apply_deadline(&metadata, &mut request)?;
+        let progress = Arc::new(SolveProgress::with_exploitability());
+        let mut solve = self.spawn_solve(span, request, progress.clone());
+        let (updates, receiver) = mpsc::channel(4);
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(PROGRESS_INTERVAL);
+            loop {
+                tokio::select! {
+                    finished = &mut solve => {
+                        let last = match finished {
+                            Ok(Ok(response)) => Ok(SolveUpdate {
+                                progress: Some(progress.to_proto()),
+                                result: Some(response),
+                            }),
+                            Ok(Err(error)) => Err(error.into()),
+                            Err(error) => {
+                                Err(Status::internal(format!("solve task failed: {}", error)))
+                            }
+                        };
+                        let _ = updates.send(last).await;
+                        return;
+                    }
+                    _ = ticks.tick() => {
+                        let update = SolveUpdate {
+                            progress: Some(progress.to_proto()),
+                            result: None,
+                        };
+                        if updates.send(Ok(update)).await.is_err() {
+                            // The caller closed the stream, so nobody wants the result.
+                            progress.cancel();
+                        }
+                    }
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn validate_request(
+        &self,
+        request: Request<SubgameRequest>,
+    ) -> Result<Response<ValidationReport>, Status> {
+        let issues = self.engine.validate(&request.into_inner());
+        Ok(Response::new(to_report(&issues)))
+    }
+
+    async fn estimate_tree(
+        &self,
+        request: Request<SubgameRequest>,
+    ) -> Result<Response<TreeEstimate>, Status> {
+        let estimate = self.engine.estimate(&request.into_inner())?;
+        Ok(Response::new(estimate))
+    }
+
+    async fn submit_solve(
+        &self,
+        request: Request<SubgameRequest>,
+    ) -> Result<Response<SolveJob>, Status> {
+        let span = info_span!("grpc.submit_solve");
+        link_remote_parent(&span, request.metadata());
+        let request = request.into_inner();
+        // Requests that can never solve fail here rather than as a job.
+        self.reject_invalid(&request)?;
+        let (job_id, progress) = self.jobs.submit()?;
+        let status = self.jobs.status(&job_id)?;
+        let engine = self.engine.clone();
+        let admission = self.admission.clone();
+        let jobs = self.jobs.clone();
+        // Background jobs queue without spending their budget, since nobody is waiting on them.
+        tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            let priority =
+                SolvePriority::parse(&request.priority).unwrap_or(SolvePriority::Background);
+            let result = admission
+                .acquire_with_priority(UNTIMED_QUEUE_WAIT, priority)
+                .and_then(|_permit| {
+                    jobs.start(&job_id);
+                    engine.solve_with_progress(&request, &progress)
+                });
+            jobs.finish(&job_id, result);
+        });
+        Ok(Response::new(status))
+    }
+
+    async fn prewarm(
+        &self,
+        request: Request<PrewarmRequest>,
+    ) -> Result<Response<PrewarmReply>, Status> {
+        let span = info_span!("grpc.prewarm");
+        link_remote_parent(&span, request.metadata());
+        let mut reply = PrewarmReply::default();
+        let mut spots = Vec::new();
+        for (index, spot) in request.into_inner().spots.into_iter().enumerate() {
+            if self
+                .engine
+                .validate(&spot)
+                .iter()
+                .any(|issue| issue.blocking)
+            {
+                reply.invalid.push(index as u32);
+            } else if self.engine.is_cached(&spot) {
+                reply.cached += 1;
+            } else {
+                spots.push(spot);
+            }
+        }
+        reply.queued = spots.len() as u32;
+        let engine = self.engine.clone();
+        let admission = self.admission.clone();
+        // One spot at a time, so prewarming never holds more than one solver slot.
+        tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            for spot in spots {
+                let result = admission
+                    .acquire_with_priority(UNTIMED_QUEUE_WAIT, SolvePriority::Background)
+                    .and_then(|_permit| engine.solve(&spot));
+                match result {
+                    Ok(_) => {}
+                    Err(SolverError::ShuttingDown) => return,
+                    Err(error) => warn!(
+                        state_fingerprint = %spot.state_fingerprint,
+                        "prewarm solve failed: {}",
+                        error
+                    ),
+                }
+            }
+        });
+        Ok(Response::new(reply))
+    }
+
+    async fn get_solve_status(
+        &self,
+        request: Request<SolveJobRef>,
+    ) -> Result<Response<SolveJob>, Status> {
+        let status = self.jobs.status(&request.into_inner().job_id)?;
+        Ok(Response::new(status))
+    }
+
+    async fn fetch_solve_result(
+        &self,
+        request: Request<SolveJobRef>,
+    ) -> Result<Response<SubgameResponse>, Status> {
+        let response = self.jobs.result(&request.into_inner().job_id)?;
         Ok(Response::new(response))
     }
+
+    async fn diff_strategies(
+        &self,
+        request: Request<StrategyDiffRequest>,
+    ) -> Result<Response<StrategyDiffReport>, Status> {
+        let span = info_span!("grpc.diff_strategies");
+        link_remote_parent(&span, request.metadata());
+        let request = request.into_inner();
+        let (Some(baseline), Some(candidate)) = (request.baseline, request.candidate) else {
+            return Err(Status::invalid_argument(
+                "both baseline and candidate are required",
+            ));
+        };
+        self.reject_invalid(&baseline)?;
+        self.reject_invalid(&candidate)?;
+        let engine = self.engine.clone();
+        let admission = self.admission.clone();
+        // Analysis, not play: it queues behind in-hand solves and holds one slot for both solves.
+        let report = tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            let _permit =
+                admission.acquire_with_priority(UNTIMED_QUEUE_WAIT, SolvePriority::Background)?;
+            engine.diff(&baseline, &candidate)
+        })
+        .await
+        .map_err(|error| Status::internal(format!("solve task failed: {}", error)))??;
+        Ok(Response::new(report))
+    }
+
+    async fn evaluate_hands(
+        &self,
+        request: Request<HandEvaluationRequest>,
+    ) -> Result<Response<HandEvaluationReport>, Status> {
+        let span = info_span!("grpc.evaluate_hands");
+        link_remote_parent(&span, request.metadata());
+        let request = request.into_inner();
+        let Some(spot) = request.spot.as_ref() else {
+            return Err(Status::invalid_argument("spot is required"));
+        };
+        self.reject_invalid(spot)?;
+        let engine = self.engine.clone();
+        let admission = self.admission.clone();
+        let report = tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            let _permit =
+                admission.acquire_with_priority(UNTIMED_QUEUE_WAIT, SolvePriority::Background)?;
+            engine.evaluate_hands(&request)
+        })
+        .await
+        .map_err(|error| Status::internal(format!("solve task failed: {}", error)))??;
+        Ok(Response::new(report))
+    }
+
+    async fn get_abstraction_info(
+        &self,
+        request: Request<AbstractionInfoRequest>,
+    ) -> Result<Response<AbstractionInfo>, Status> {
+        let span = info_span!("grpc.get_abstraction_info");
+        link_remote_parent(&span, request.metadata());
+        let request = request.into_inner();
+        let engine = self.engine.clone();
+        let admission = self.admission.clone();
+        // Equity enumeration is CPU-bound like a solve, so it takes a background slot too.
+        let info = tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            let _permit =
+                admission.acquire_with_priority(UNTIMED_QUEUE_WAIT, SolvePriority::Background)?;
+            engine.abstraction_info(&request)
+        })
+        .await
+        .map_err(|error| Status::internal(format!("abstraction task failed: {}", error)))??;
+        Ok(Response::new(info))
+    }
+
+    async fn export_tree(
+        &self,
+        request: Request<TreeExportRequest>,
+    ) -> Result<Response<TreeExport>, Status> {
+        let span = info_span!("grpc.export_tree");
+        link_remote_parent(&span, request.metadata());
+        let request = request.into_inner();
+        let Some(spot) = request.spot.as_ref() else {
+            return Err(Status::invalid_argument("spot is required"));
+        };
+        if request.solve {
+            self.reject_invalid(spot)?;
+        }
+        let engine = self.engine.clone();
+        let admission = self.admission.clone();
+        let export = tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            let _permit =
+                admission.acquire_with_priority(UNTIMED_QUEUE_WAIT, SolvePriority::Background)?;
+            engine.export_tree(&request)
+        })
+        .await
+        .map_err(|error| Status::internal(format!("export task failed: {}", error)))??;
+        Ok(Response::new(export))
+    }
+
+    async fn analyze_action_removal(
+        &self,
+        request: Request<ActionRemovalRequest>,
+    ) -> Result<Response<ActionRemovalReport>, Status> {
+        let span = info_span!("grpc.analyze_action_removal");
+        link_remote_parent(&span, request.metadata());
+        let request = request.into_inner();
+        let Some(spot) = request.spot.as_ref() else {
+            return Err(Status::invalid_argument("spot is required"));
+        };
+        self.reject_invalid(spot)?;
+        let engine = self.engine.clone();
+        let admission = self.admission.clone();
+        let report = tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            let _permit =
+                admission.acquire_with_priority(UNTIMED_QUEUE_WAIT, SolvePriority::Background)?;
+            engine.action_removal(&request)
+        })
+        .await
+        .map_err(|error| Status::internal(format!("solve task failed: {}", error)))??;
+        Ok(Response::new(report))
+    }
+
+    async fn optimize_bet_size(
+        &self,
+        request: Request<BetSizeSearchRequest>,
+    ) -> Result<Response<BetSizeSearchReport>, Status> {
+        let span = info_span!("grpc.optimize_bet_size");
+        link_remote_parent(&span, request.metadata());
+        let request = request.into_inner();
+        let Some(spot) = request.spot.as_ref() else {
+            return Err(Status::invalid_argument("spot is required"));
+        };
+        self.reject_invalid(spot)?;
+        let engine = self.engine.clone();
+        let admission = self.admission.clone();
+        let report = tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            let _permit =
+                admission.acquire_with_priority(UNTIMED_QUEUE_WAIT, SolvePriority::Background)?;
+            engine.optimize_bet_size(&request)
+        })
+        .await
+        .map_err(|error| Status::internal(format!("solve task failed: {}", error)))??;
+        Ok(Response::new(report))
+    }
+
+    async fn sweep_stacks(
+        &self,
+        request: Request<StackSweepRequest>,
+    ) -> Result<Response<StackSweepReport>, Status> {
+        let span = info_span!("grpc.sweep_stacks");
+        link_remote_parent(&span, request.metadata());
+        let request = request.into_inner();
+        let Some(spot) = request.spot.as_ref() else {
+            return Err(Status::invalid_argument("spot is required"));
+        };
+        if request.stacks_bb.is_empty() {
+            return Err(Status::invalid_argument("stacks_bb is required"));
+        }
+        for stack in &request.stacks_bb {
+            self.reject_invalid(&SubgameRequest {
+                effective_stack_bb: *stack,
+                ..spot.clone()
+            })?;
+        }
+        let engine = self.engine.clone();
+        let admission = self.admission.clone();
+        let report = tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            let _permit =
+                admission.acquire_with_priority(UNTIMED_QUEUE_WAIT, SolvePriority::Background)?;
+            engine.sweep_stacks(&request)
+        })
+        .await
+        .map_err(|error| Status::internal(format!("solve task failed: {}", error)))??;
+        Ok(Response::new(report))
+    }
+
+    async fn benchmark(
+        &self,
+        request: Request<BenchmarkRequest>,
+    ) -> Result<Response<BenchmarkReport>, Status> {
+        let span = info_span!("grpc.benchmark");
+        link_remote_parent(&span, request.metadata());
+        let request = request.into_inner();
+        let engine = self.engine.clone();
+        let admission = self.admission.clone();
+        // Takes a solve slot like any other CPU-bound call; the numbers include whatever other
+        // solves run alongside.
+        let report = tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            let _permit =
+                admission.acquire_with_priority(UNTIMED_QUEUE_WAIT, SolvePriority::Background)?;
+            engine.benchmark(&request)
+        })
+        .await
+        .map_err(|error| Status::internal(format!("solve task failed: {}", error)))??;
+        Ok(Response::new(report))
+    }
+
+    async fn query_history(
+        &self,
+        request: Request<HistoryQuery>,
+    ) -> Result<Response<HistoryPage>, Status> {
+        let Some(history) = self.engine.history().cloned() else {
+            return Err(Status::failed_precondition("solve history is not enabled"));
+        };
+        let query = request.into_inner();
+        let include_strategy = query.include_strategy;
+        // SQLite reads block.
+        let records = tokio::task::spawn_blocking(move || history.query(&query))
+            .await
+            .map_err(|error| Status::internal(format!("history query failed: {}", error)))??;
+        Ok(Response::new(HistoryPage {
+            entries: records
+                .iter()
+                .map(|record| record.to_proto(include_strategy))
+                .collect(),
+        }))
+    }
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let addr = {
-        if let Ok(addr) = env::var("SOLVER_ADDR") {
-            addr.parse()?
-        } else {
-            let port = env::var("SOLVER_PORT").unwrap_or_else(|_| "50051".to_string());
-            format!("0.0.0.0:{}", port).parse()?
+// jemalloc keeps the allocation samples the heap profile is read from.
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+// Heap sampling on from startup, one sample per 512 KiB allocated on average.
+#[cfg(feature = "profiling")]
+#[allow(non_upper_case_globals)]
+#[export_name = "malloc_conf"]
+pub static malloc_conf: &[u8] = b"prof:true,prof_active:true,lg_prof_sample:19\0";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let config = SolverConfig::load(config_path().as_deref())?;
+    // New threads inherit the pinning, so doing it before the runtime starts covers the
+    // runtime's workers, its blocking pool where solves run, and their board threads.
+    if !affinity::pin_current_thread(&config.threads.cores) {
+        return Err(Box::new(ConfigError::InvalidValue {
+            key: "threads.cores".to_string(),
+            value: format!("{:?}", config.threads.cores),
+        }));
+    }
+    let mut runtime = tokio::runtime::Builder::new_multi_thread();
+    runtime.enable_all();
+    if config.threads.workers > 0 {
+        runtime.worker_threads(config.threads.workers);
+    }
+    let runtime = runtime.build()?;
+    let result = runtime.block_on(serve(config));
+    // Dropping the runtime would wait for every blocking task, and work the drain didn't cancel
+    // (an export, a benchmark) could hold the exit past the grace period.
+    runtime.shutdown_timeout(EXIT_WAIT);
+    result
+}
+
+// `--config <path>` on the command line, else SOLVER_CONFIG; without either only defaults and
+// environment overrides apply.
+fn config_path() -> Option<PathBuf> {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
         }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    env::var("SOLVER_CONFIG").ok().map(PathBuf::from)
+}
+
+async fn serve(config: SolverConfig) -> Result<(), Box<dyn std::error::Error>> {
+    init_tracing()?;
+    let addr: SocketAddr = config.server.listen_addr.parse()?;
+    let metrics_addr = SocketAddr::new(addr.ip(), config.server.metrics_port);
+    let metrics = Arc::new(SolverMetrics::new());
+    let admission = Arc::new(AdmissionControl::new(config.admission_limits()));
+    let tls = config.tls.clone();
+    let grace = Duration::from_millis(config.server.shutdown_grace_ms);
+    let deadline_slack = Duration::from_millis(config.server.deadline_slack_ms);
+    let auth = Arc::new(ApiKeyAuth::new(&config.auth.keys));
+    if auth.is_enabled() {
+        info!("API key authentication enabled");
+    }
+    let jobs = Arc::new(JobStore::new(
+        Duration::from_millis(config.jobs.ttl_ms),
+        config.jobs.max_jobs,
+    ));
+    let mut engine = SolverEngine::from_config(config.clone(), metrics.clone());
+    if config.history.is_enabled() {
+        engine = engine.with_history(SolveHistory::open(&config.history)?);
+        info!(path = %config.history.path, "recording solve history");
+    }
+    if let Some(store) = open_store(&config.archive)? {
+        info!(store = %store.describe(), "archiving solved trees");
+        engine = engine.with_archive(StrategyArchive::start(
+            store,
+            &config.archive,
+            metrics.clone(),
+        ));
+    }
+    let cache_file = config.cache.file();
+    if let Some(file) = &cache_file {
+        match engine.cache().load(file) {
+            Ok(loaded) => info!(loaded, path = %file.display(), "loaded saved solves"),
+            Err(error) => warn!(%error, path = %file.display(), "saved solves not loaded"),
+        }
+    }
+    let archive = engine.archive().cloned();
+    let engine = Arc::new(engine);
+    let service = SolverService {
+        engine: engine.clone(),
+        admission: admission.clone(),
+        jobs,
+        dedup: Arc::new(SolveDedup::new()),
+        deadline_slack,
     };
-    let service = SolverService::default();
-    println!("Solver listening on {}", addr);
-    tonic::transport::Server::builder()
-        .add_service(SolverServer::new(service))
-        .serve(addr)
-        .await?;
+    let listener = TcpListener::bind(metrics_addr).await?;
+    info!("Metrics listening on {}", metrics_addr);
+    if config.profiling.enabled {
+        info!("pprof profiles served on the metrics port");
+    }
+    tokio::spawn(serve_metrics(
+        listener,
+        metrics.clone(),
+        config.profiling.clone(),
+    ));
+    let gateway = config.server.gateway.then(|| {
+        gateway_router(
+            service.clone(),
+            auth.clone(),
+            &config.server.gateway_allow_origin,
+        )
+    });
+    let mut server = tonic::transport::Server::builder();
+    if let Some(material) = tls.load()? {
+        let mtls = material.client_ca_pem.is_some();
+        let mut tls_config = ServerTlsConfig::new()
+            .identity(Identity::from_pem(material.cert_pem, material.key_pem));
+        if let Some(client_ca) = material.client_ca_pem {
+            tls_config = tls_config.client_ca_root(Certificate::from_pem(client_ca));
+        }
+        server = server.tls_config(tls_config)?;
+        info!(mtls, "TLS enabled");
+    }
+    info!("Solver listening on {}", addr);
+    let draining = Arc::new(tokio::sync::Notify::new());
+    let shutdown = {
+        let admission = admission.clone();
+        let draining = draining.clone();
+        async move {
+            shutdown_signal().await;
+            info!("shutdown requested; draining in-flight solves");
+            admission.close();
+            draining.notify_one();
+        }
+    };
+    let mut routes = Routes::new(SolverServer::with_interceptor(service, move |request| {
+        auth.intercept(request)
+    }));
+    if let Some(gateway) = gateway {
+        // Browsers may speak HTTP/1.1 to it; gRPC callers still use HTTP/2.
+        server = server.accept_http1(true);
+        routes = routes.add_service(GatewayService::new(gateway));
+        info!("HTTP/JSON gateway served on {}", addr);
+    }
+    let server = server
+        .add_routes(routes)
+        .serve_with_shutdown(addr, shutdown);
+    // The server stops accepting connections on the signal and returns once open calls finish;
+    // the grace period caps that wait in case a caller holds a connection open. Solves still
+    // running then are cancelled, and the server gets a little longer to send the answers they
+    // stop with before it is dropped along with any call still open.
+    tokio::pin!(server);
+    tokio::select! {
+        result = &mut server => result?,
+        _ = async {
+            draining.notified().await;
+            tokio::time::sleep(grace).await;
+        } => {
+            let cancelled = engine.cancel_running();
+            warn!(
+                running = admission.running(),
+                cancelled,
+                "drain grace period elapsed; cancelled running solves"
+            );
+            match tokio::time::timeout(CANCEL_FLUSH, &mut server).await {
+                Ok(result) => result?,
+                Err(_) => warn!("calls still open after cancelling; dropping them"),
+            }
+        }
+    }
+    if let Some(archive) = archive {
+        let flushed = tokio::task::spawn_blocking(move || archive.flush(grace)).await?;
+        if !flushed {
+            warn!("archive uploads still pending at exit");
+        }
+    }
+    if let Some(file) = cache_file {
+        let cache = engine.clone();
+        match tokio::task::spawn_blocking(move || cache.cache().save(&file)).await? {
+            Ok(saved) => info!(saved, "saved the solve cache"),
+            Err(error) => warn!(%error, "solve cache not saved"),
+        }
+    }
+    // Nothing scrapes the metrics port once the process is gone, so the final counts go to the
+    // log.
+    info!(
+        solves_ok = metrics.solves_ok.load(Ordering::Relaxed),
+        solves_failed = metrics.solves_failed.load(Ordering::Relaxed),
+        budget_exhausted = metrics.budget_exhausted.load(Ordering::Relaxed),
+        deduplicated = metrics.deduplicated.load(Ordering::Relaxed),
+        iterations = metrics.iterations.load(Ordering::Relaxed),
+        cache_hits = metrics.cache_hits.load(Ordering::Relaxed),
+        cache_misses = metrics.cache_misses.load(Ordering::Relaxed),
+        archived = metrics.archived.load(Ordering::Relaxed),
+        "solver stopped"
+    );
+    #[cfg(feature = "otlp")]
+    opentelemetry::global::shutdown_tracer_provider();
+    Ok(())
+}
+
+async fn shutdown_signal() {
+    let interrupt = tokio::signal::ctrl_c();
+    #[cfg(unix)]
+    {
+        let terminate = async {
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(mut signal) => {
+                    signal.recv().await;
+                }
+                Err(_) => std::future::pending::<()>().await,
+            }
+        };
+        tokio::select! {
+            _ = interrupt => {}
+            _ = terminate => {}
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = interrupt.await;
+    }
+}
+
+fn init_tracing() -> Result<(), Box<dyn std::error::Error>> {
+    let filter = EnvFilter::try_from_env("LOG_LEVEL").unwrap_or_else(|_| EnvFilter::new("info"));
+    let registry = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer());
+
+    #[cfg(feature = "otlp")]
+    if let Ok(endpoint) = env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+        use opentelemetry::KeyValue;
+        use opentelemetry_otlp::WithExportConfig;
+        use opentelemetry_sdk::{propagation::TraceContextPropagator, trace, Resource};
+
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let tracer = opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(
+                opentelemetry_otlp::new_exporter()
+                    .tonic()
+                    .with_endpoint(endpoint),
+            )
+            .with_trace_config(
+                trace::config()
+                    .with_resource(Resource::new(vec![KeyValue::new("service.name", "solver")])),
+            )
+            .install_batch(opentelemetry_sdk::runtime::Tokio)?;
+        registry
+            .with(tracing_opentelemetry::layer().with_tracer(tracer))
+            .try_init()?;
+        return Ok(());
+    }
+
+    registry.try_init()?;
     Ok(())
 }
+
+// Continues the caller's trace when a W3C traceparent header came in with the request.
+#[cfg(feature = "otlp")]
+fn link_remote_parent(span: &tracing::Span, metadata: &tonic::metadata::MetadataMap) {
+    use opentelemetry::propagation::Extractor;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    struct MetadataExtractor<'a>(&'a tonic::metadata::MetadataMap);
+
+    impl Extractor for MetadataExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|value| value.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0
+                .keys()
+                .filter_map(|key| match key {
+                    tonic::metadata::KeyRef::Ascii(key) => Some(key.as_str()),
+                    tonic::metadata::KeyRef::Binary(_) => None,
+                })
+                .collect()
+        }
+    }
+
+    let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&MetadataExtractor(metadata))
+    });
+    span.set_parent(parent);
+}
+
+#[cfg(not(feature = "otlp"))]
+fn link_remote_parent(_span: &tracing::Span, _metadata: &tonic::metadata::MetadataMap) {}
+
+async fn serve_metrics(
+    listener: TcpListener,
+    metrics: Arc<SolverMetrics>,
+    profiling: ProfilingConfig,
+) {
+    let profiling = Arc::new(profiling);
+    loop {
+        let Ok((mut socket, _)) = listener.accept().await else {
+            continue;
+        };
+        let (metrics, profiling) = (metrics.clone(), profiling.clone());
+        tokio::spawn(async move {
+            let mut buffer = [0u8; 1024];
+            let Ok(read) = socket.read(&mut buffer).await else {
+                return;
+            };
+            let request = String::from_utf8_lossy(&buffer[..read]);
+            let path = request
+                .strip_prefix("GET ")
+                .and_then(|rest| rest.split_whitespace().next())
+                .unwrap_or("");
+            let (status, content_type, body) = if path.starts_with("/metrics") {
+                let body = metrics.render().into_bytes();
+                ("200 OK", "text/plain; version=0.0.4", body)
+            } else if let Some(kind) = profile_request(path, &profiling) {
+                // A CPU profile sleeps while it samples.
+                let profile =
+                    tokio::task::spawn_blocking(move || collect_profile(kind, &profiling)).await;
+                match profile {
+                    Ok(Ok(profile)) => ("200 OK", "application/octet-stream", profile),
+                    Ok(Err(error)) => {
+                        let status = match error {
+                            ProfileError::Unsupported => "501 Not Implemented",
+                            ProfileError::Busy => "409 Conflict",
+                            ProfileError::Failed(_) => "500 Internal Server Error",
+                        };
+                        (status, "text/plain", error.to_string().into_bytes())
+                    }
+                    Err(error) => (
+                        "500 Internal Server Error",
+                        "text/plain",
+                        error.to_string().into_bytes(),
+                    ),
+                }
+            } else {
+                ("404 Not Found", "text/plain", Vec::new())
+            };
+            let head = format!(
+                "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                content_type,
+                body.len()
+            );
+            let _ = socket.write_all(head.as_bytes()).await;
+            let _ = socket.write_all(&body).await;
+        });
+    }
+}
+
+// The HTTP/JSON gateway, served beside the gRPC service on its port and so behind the same TLS
+// and client certificate checks. Each route is answered by the gRPC handler itself, so API
+// keys, validation, admission, deduplication and the cache all apply.
+fn gateway_router(
+    service: SolverService,
+    auth: Arc<ApiKeyAuth>,
+    allow_origin: &str,
+) -> axum::Router {
+    let gateway = Arc::new(Gateway {
+        service,
+        auth,
+        cors: cors_headers(allow_origin),
+    });
+    let mut router = axum::Router::new();
+    for route in Route::ALL {
+        let gateway = gateway.clone();
+        router = router.route(
+            route.path(),
+            any(
+                move |request: HttpRequest<Body>| async move { gateway.call(route, request).await },
+            ),
+        );
+    }
+    let unknown = gateway.clone();
+    router.route(
+        "/v1/*rest",
+        any(move || async move {
+            unknown.respond(404, &error_body(&Status::not_found("no such endpoint")))
+        }),
+    )
+}
+
+struct Gateway {
+    service: SolverService,
+    auth: Arc<ApiKeyAuth>,
+    cors: HeaderMap,
+}
+
+impl Gateway {
+    async fn call(&self, route: Route, request: HttpRequest<Body>) -> HttpResponse {
+        let (parts, body) = request.into_parts();
+        // CORS preflight.
+        if parts.method == Method::OPTIONS {
+            return (StatusCode::NO_CONTENT, self.cors.clone()).into_response();
+        }
+        if parts.method != Method::POST {
+            return self.respond(
+                405,
+                &error_body(&Status::invalid_argument("endpoints take POST")),
+            );
+        }
+        let metadata = call_metadata(&parts.headers);
+        let mut probe = Request::new(());
+        *probe.metadata_mut() = metadata.clone();
+        if let Err(status) = self.auth.intercept(probe) {
+            return self.error(&status);
+        }
+        let body = match read_body(body, MAX_BODY_BYTES, BODY_TIMEOUT).await {
+            Ok(body) => body,
+            Err(status) => return self.error(&status),
+        };
+        let service = &self.service;
+        let result = match route {
+            Route::Solve => gateway_rpc(&body, metadata, |call| service.solve(call)).await,
+            Route::Validate => {
+                gateway_rpc(&body, metadata, |call| service.validate_request(call)).await
+            }
+            Route::Estimate => {
+                gateway_rpc(&body, metadata, |call| service.estimate_tree(call)).await
+            }
+            Route::SubmitSolve => {
+                gateway_rpc(&body, metadata, |call| service.submit_solve(call)).await
+            }
+            Route::SolveStatus => {
+                gateway_rpc(&body, metadata, |call| service.get_solve_status(call)).await
+            }
+            Route::SolveResult => {
+                gateway_rpc(&body, metadata, |call| service.fetch_solve_result(call)).await
+            }
+            Route::SolveStream => return self.stream(&body, metadata).await,
+            Route::Prewarm => gateway_rpc(&body, metadata, |call| service.prewarm(call)).await,
+            Route::History => {
+                gateway_rpc(&body, metadata, |call| service.query_history(call)).await
+            }
+            Route::DiffStrategies => {
+                gateway_rpc(&body, metadata, |call| service.diff_strategies(call)).await
+            }
+            Route::EvaluateHands => {
+                gateway_rpc(&body, metadata, |call| service.evaluate_hands(call)).await
+            }
+            Route::AbstractionInfo => {
+                gateway_rpc(&body, metadata, |call| service.get_abstraction_info(call)).await
+            }
+            Route::ExportTree => {
+                gateway_rpc(&body, metadata, |call| service.export_tree(call)).await
+            }
+            Route::ActionRemoval => {
+                gateway_rpc(&body, metadata, |call| service.analyze_action_removal(call)).await
+            }
+            Route::BetSizeSearch => {
+                gateway_rpc(&body, metadata, |call| service.optimize_bet_size(call)).await
+            }
+            Route::StackSweep => {
+                gateway_rpc(&body, metadata, |call| service.sweep_stacks(call)).await
+            }
+            Route::Benchmark => gateway_rpc(&body, metadata, |call| service.benchmark(call)).await,
+        };
+        match result {
+            Ok(json) => self.respond(200, &json),
+            Err(status) => self.error(&status),
+        }
+    }
+
+    // SolveStream as newline-delimited JSON: the progress updates, then the response or the
+    // error. Closing the connection stops the solve, as closing the gRPC stream does.
+    async fn stream(&self, body: &[u8], metadata: MetadataMap) -> HttpResponse {
+        let message: SubgameRequest = match parse_json(body) {
+            Ok(message) => message,
+            Err(status) => return self.error(&status),
+        };
+        let mut call = Request::new(message);
+        *call.metadata_mut() = metadata;
+        let updates = match self.service.solve_stream(call).await {
+            Ok(updates) => updates.into_inner(),
+            Err(status) => return self.error(&status),
+        };
+        let lines = updates.map(|update| {
+            let line = match update {
+                Ok(update) => serde_json::to_string(&update).unwrap_or_default(),
+                Err(status) => error_body(&status),
+            };
+            Ok::<_, Infallible>(line + "\n")
+        });
+        let mut headers = self.cors.clone();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-ndjson"),
+        );
+        (StatusCode::OK, headers, StreamBody::new(lines)).into_response()
+    }
+
+    fn error(&self, status: &Status) -> HttpResponse {
+        self.respond(http_status(status.code()), &error_body(status))
+    }
+
+    fn respond(&self, status: u16, json: &str) -> HttpResponse {
+        let mut headers = self.cors.clone();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, headers, json.to_string()).into_response()
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(body: &[u8]) -> Result<T, Status> {
+    serde_json::from_slice(body)
+        .map_err(|error| Status::invalid_argument(format!("invalid JSON body: {}", error)))
+}
+
+async fn gateway_rpc<T, R, F, Fut>(
+    body: &[u8],
+    metadata: MetadataMap,
+    rpc: F,
+) -> Result<String, Status>
+where
+    T: DeserializeOwned,
+    R: Serialize,
+    F: FnOnce(Request<T>) -> Fut,
+    Fut: Future<Output = Result<Response<R>, Status>>,
+{
+    let mut call = Request::new(parse_json(body)?);
+    *call.metadata_mut() = metadata;
+    let response = rpc(call).await?;
+    serde_json::to_string(response.get_ref()).map_err(|error| Status::internal(error.to_string()))
+}
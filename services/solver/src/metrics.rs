@@ -0,0 +1,229 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub const LATENCY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+pub struct Histogram {
+    bounds: Vec<f64>,
+    counts: Vec<AtomicU64>,
+    sum_micros: AtomicU64,
+    total: AtomicU64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &[f64]) -> Self {
+        Self {
+            bounds: bounds.to_vec(),
+            counts: bounds.iter().map(|_| AtomicU64::new(0)).collect(),
+            sum_micros: AtomicU64::new(0),
+            total: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, seconds: f64) {
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            if seconds <= *bound {
+                count.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.sum_micros
+            .fetch_add((seconds.max(0.0) * 1e6) as u64, Ordering::Relaxed);
+        self.total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum_micros.load(Ordering::Relaxed) as f64 / 1e6
+    }
+
+    fn render(&self, out: &mut String, name: &str) {
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            let _ = writeln!(
+                out,
+                "{}_bucket{{le=\"{}\"}} {}",
+                name,
+                bound,
+                count.load(Ordering::Relaxed)
+            );
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, self.count());
+        let _ = writeln!(out, "{}_sum {}", name, self.sum());
+        let _ = writeln!(out, "{}_count {}", name, self.count());
+    }
+}
+
+// Counters are plain atomics so the solve path never blocks on a scrape.
+pub struct SolverMetrics {
+    pub solves_ok: AtomicU64,
+    pub solves_failed: AtomicU64,
+    pub budget_exhausted: AtomicU64,
+    // Solves answered with the result of an identical request already in flight.
+    pub deduplicated: AtomicU64,
+    // Solved trees uploaded to the strategy archive, and uploads that gave up.
+    pub archived: AtomicU64,
+    pub archive_failures: AtomicU64,
+    pub iterations: AtomicU64,
+    pub cache_hits: AtomicU64,
+    pub cache_misses: AtomicU64,
+    pub latency: Histogram,
+    last_iterations_per_sec: Mutex<f64>,
+}
+
+impl SolverMetrics {
+    pub fn new() -> Self {
+        Self {
+            solves_ok: AtomicU64::new(0),
+            solves_failed: AtomicU64::new(0),
+            budget_exhausted: AtomicU64::new(0),
+            deduplicated: AtomicU64::new(0),
+            archived: AtomicU64::new(0),
+            archive_failures: AtomicU64::new(0),
+            iterations: AtomicU64::new(0),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
+            latency: Histogram::new(&LATENCY_BUCKETS),
+            last_iterations_per_sec: Mutex::new(0.0),
+        }
+    }
+
+    pub fn record_solve(&self, ok: bool, elapsed: Duration) {
+        if ok {
+            self.solves_ok.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.solves_failed.fetch_add(1, Ordering::Relaxed);
+        }
+        self.latency.observe(elapsed.as_secs_f64());
+    }
+
+    pub fn record_iterations(&self, iterations: usize, elapsed: Duration) {
+        self.iterations
+            .fetch_add(iterations as u64, Ordering::Relaxed);
+        let seconds = elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            if let Ok(mut rate) = self.last_iterations_per_sec.lock() {
+                *rate = iterations as f64 / seconds;
+            }
+        }
+    }
+
+    pub fn record_budget_exhausted(&self) {
+        self.budget_exhausted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_deduplicated(&self) {
+        self.deduplicated.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_archive(&self, stored: bool) {
+        if stored {
+            self.archived.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.archive_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_cache_lookup(&self, hit: bool) {
+        if hit {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.cache_misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn iterations_per_sec(&self) -> f64 {
+        self.last_iterations_per_sec
+            .lock()
+            .map(|rate| *rate)
+            .unwrap_or(0.0)
+    }
+
+    pub fn cache_hit_ratio(&self) -> f64 {
+        let hits = self.cache_hits.load(Ordering::Relaxed) as f64;
+        let misses = self.cache_misses.load(Ordering::Relaxed) as f64;
+        if hits + misses == 0.0 {
+            0.0
+        } else {
+            hits / (hits + misses)
+        }
+    }
+
+    // Prometheus text exposition format, version 0.0.4.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# TYPE solver_solves_total counter");
+        let _ = writeln!(
+            out,
+            "solver_solves_total{{status=\"ok\"}} {}",
+            self.solves_ok.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "solver_solves_total{{status=\"error\"}} {}",
+            self.solves_failed.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(out, "# TYPE solver_solve_latency_seconds histogram");
+        self.latency
+            .render(&mut out, "solver_solve_latency_seconds");
+        let _ = writeln!(out, "# TYPE solver_iterations_total counter");
+        let _ = writeln!(
+            out,
+            "solver_iterations_total {}",
+            self.iterations.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(out, "# TYPE solver_iterations_per_second gauge");
+        let _ = writeln!(
+            out,
+            "solver_iterations_per_second {}",
+            self.iterations_per_sec()
+        );
+        let _ = writeln!(out, "# TYPE solver_cache_hits_total counter");
+        let _ = writeln!(
+            out,
+            "solver_cache_hits_total {}",
+            self.cache_hits.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(out, "# TYPE solver_cache_misses_total counter");
+        let _ = writeln!(
+            out,
+            "solver_cache_misses_total {}",
+            self.cache_misses.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(out, "# TYPE solver_cache_hit_ratio gauge");
+        let _ = writeln!(out, "solver_cache_hit_ratio {}", self.cache_hit_ratio());
+        let _ = writeln!(out, "# TYPE solver_budget_exhausted_total counter");
+        let _ = writeln!(
+            out,
+            "solver_budget_exhausted_total {}",
+            self.budget_exhausted.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(out, "# TYPE solver_deduplicated_solves_total counter");
+        let _ = writeln!(
+            out,
+            "solver_deduplicated_solves_total {}",
+            self.deduplicated.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(out, "# TYPE solver_archived_solves_total counter");
+        let _ = writeln!(
+            out,
+            "solver_archived_solves_total{{status=\"ok\"}} {}",
+            self.archived.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "solver_archived_solves_total{{status=\"error\"}} {}",
+            self.archive_failures.load(Ordering::Relaxed)
+        );
+        out
+    }
+}
+
+impl Default for SolverMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
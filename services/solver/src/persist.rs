@@ -0,0 +1,487 @@
+use crate::cfr::compressed::{
+    CompressedStrategies, QuantizedEntry, QuantizedLayout, QuantizedStrategy,
+};
+use crate::cfr::CfrSolver;
+use crate::game_tree::eval::RakeModel;
+use crate::game_tree::legality::LegalizationReport;
+use crate::game_tree::{ActionKind, GameTree, GameTreeAction, NodeKind, TerminalKind, TreeNode};
+use std::fmt;
+use std::path::Path;
+
+pub mod pio;
+
+// File layout (all integers little-endian):
+//   magic "SLVS" | version u16 | body length u64 | body | FNV-1a 64 checksum of body
+// The body holds the metadata, the tree, the quantized strategies and the raw regrets, in that
+// order. Bump FORMAT_VERSION whenever the body layout changes.
+pub const MAGIC: &[u8; 4] = b"SLVS";
+pub const FORMAT_VERSION: u16 = 2;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotMeta {
+    pub state_fingerprint: String,
+    pub seed: u64,
+    pub iterations: u64,
+    pub buckets: u32,
+    pub exploitability: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct SolveSnapshot {
+    pub meta: SnapshotMeta,
+    pub tree: GameTree,
+    pub strategies: CompressedStrategies,
+    // Cumulative regrets per node, bucket-major; empty for terminal nodes.
+    pub regrets: Vec<Vec<f64>>,
+}
+
+#[derive(Debug)]
+pub enum PersistError {
+    Io(std::io::Error),
+    BadMagic,
+    UnsupportedVersion(u16),
+    Truncated,
+    ChecksumMismatch,
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistError::Io(error) => write!(f, "snapshot I/O failed: {}", error),
+            PersistError::BadMagic => write!(f, "not a solver snapshot"),
+            PersistError::UnsupportedVersion(version) => {
+                write!(f, "snapshot format version {} is not supported", version)
+            }
+            PersistError::Truncated => write!(f, "snapshot is truncated"),
+            PersistError::ChecksumMismatch => write!(f, "snapshot checksum does not match"),
+            PersistError::Corrupt(what) => write!(f, "snapshot is corrupt: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for PersistError {}
+
+impl From<std::io::Error> for PersistError {
+    fn from(error: std::io::Error) -> Self {
+        PersistError::Io(error)
+    }
+}
+
+impl SolveSnapshot {
+    pub fn from_solver(solver: &CfrSolver, state_fingerprint: &str, seed: u64) -> Self {
+        Self::from_solved(
+            solver,
+            state_fingerprint,
+            seed,
+            solver.exploitability(),
+            CompressedStrategies::from_solver(solver),
+        )
+    }
+
+    // Like `from_solver`, reusing the exploitability and strategies the solve already computed.
+    pub fn from_solved(
+        solver: &CfrSolver,
+        state_fingerprint: &str,
+        seed: u64,
+        exploitability: f64,
+        strategies: CompressedStrategies,
+    ) -> Self {
+        let meta = SnapshotMeta {
+            state_fingerprint: state_fingerprint.to_string(),
+            seed,
+            iterations: solver.iterations() as u64,
+            buckets: solver.buckets() as u32,
+            exploitability,
+        };
+        let tree = solver.tree().clone();
+        let regrets = (0..tree.nodes.len())
+            .map(|node| solver.regrets(node))
+            .collect();
+        Self {
+            meta,
+            strategies,
+            tree,
+            regrets,
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), PersistError> {
+        std::fs::write(path, self.encode())?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, PersistError> {
+        Self::decode(&std::fs::read(path)?)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Writer::default();
+        body.string(&self.meta.state_fingerprint);
+        body.u64(self.meta.seed);
+        body.u64(self.meta.iterations);
+        body.u32(self.meta.buckets);
+        body.f64(self.meta.exploitability);
+        write_tree(&mut body, &self.tree);
+        write_strategies(&mut body, &self.strategies);
+        body.u32(self.regrets.len() as u32);
+        for regrets in &self.regrets {
+            body.f64s(regrets);
+        }
+
+        let mut out = Writer::default();
+        out.bytes.extend_from_slice(MAGIC);
+        out.u16(FORMAT_VERSION);
+        out.u64(body.bytes.len() as u64);
+        out.bytes.extend_from_slice(&body.bytes);
+        out.u64(checksum(&body.bytes));
+        out.bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, PersistError> {
+        let mut header = Reader::new(bytes);
+        if header.take(MAGIC.len())? != MAGIC {
+            return Err(PersistError::BadMagic);
+        }
+        let version = header.u16()?;
+        if version != FORMAT_VERSION {
+            return Err(PersistError::UnsupportedVersion(version));
+        }
+        let length = header.u64()? as usize;
+        let body = header.take(length)?;
+        if header.u64()? != checksum(body) {
+            return Err(PersistError::ChecksumMismatch);
+        }
+
+        let mut reader = Reader::new(body);
+        let meta = SnapshotMeta {
+            state_fingerprint: reader.string()?,
+            seed: reader.u64()?,
+            iterations: reader.u64()?,
+            buckets: reader.u32()?,
+            exploitability: reader.f64()?,
+        };
+        let tree = read_tree(&mut reader)?;
+        let strategies = read_strategies(&mut reader)?;
+        let count = reader.u32()? as usize;
+        let mut regrets = Vec::with_capacity(count.min(tree.nodes.len()));
+        for _ in 0..count {
+            regrets.push(reader.f64s()?);
+        }
+        if strategies.nodes.len() != tree.nodes.len() || regrets.len() != tree.nodes.len() {
+            return Err(PersistError::Corrupt("node counts disagree"));
+        }
+        Ok(Self {
+            meta,
+            tree,
+            strategies,
+            regrets,
+        })
+    }
+}
+
+fn write_tree(writer: &mut Writer, tree: &GameTree) {
+    writer.f64(tree.effective_stack_bb);
+    writer.f64(tree.pot_bb);
+    writer.bool(tree.preflop);
+    writer.f64(tree.rake.percentage);
+    writer.f64(tree.rake.cap);
+    writer.bool(tree.rake.no_flop_no_drop);
+    writer.u32(tree.actions.len() as u32);
+    for action in &tree.actions {
+        writer.string(&action.label);
+        writer.f64(action.amount);
+        writer.string(action.kind.as_str());
+    }
+    writer.u32(tree.nodes.len() as u32);
+    for node in &tree.nodes {
+        writer.string(&node.label);
+        writer.f64(node.committed[0]);
+        writer.f64(node.committed[1]);
+        match &node.kind {
+            NodeKind::Decision { player, children } => {
+                writer.u8(0);
+                writer.u8(*player as u8);
+                writer.u32(children.len() as u32);
+                for child in children {
+                    writer.u32(*child as u32);
+                }
+            }
+            NodeKind::Terminal(TerminalKind::Fold { folder }) => {
+                writer.u8(1);
+                writer.u8(*folder as u8);
+            }
+            NodeKind::Terminal(TerminalKind::Showdown) => writer.u8(2),
+        }
+    }
+}
+
+fn read_tree(reader: &mut Reader) -> Result<GameTree, PersistError> {
+    let effective_stack_bb = reader.f64()?;
+    let pot_bb = reader.f64()?;
+    let preflop = reader.bool()?;
+    let rake = RakeModel {
+        percentage: reader.f64()?,
+        cap: reader.f64()?,
+        no_flop_no_drop: reader.bool()?,
+    };
+    let action_count = reader.u32()? as usize;
+    let mut actions = Vec::with_capacity(action_count.min(reader.remaining()));
+    for _ in 0..action_count {
+        actions.push(GameTreeAction {
+            label: reader.string()?,
+            amount: reader.f64()?,
+            kind: ActionKind::parse(&reader.string()?)
+                .ok_or(PersistError::Corrupt("unknown action kind"))?,
+        });
+    }
+    let node_count = reader.u32()? as usize;
+    let mut nodes = Vec::with_capacity(node_count.min(reader.remaining()));
+    for _ in 0..node_count {
+        let label = reader.string()?;
+        let committed = [reader.f64()?, reader.f64()?];
+        let kind = match reader.u8()? {
+            0 => {
+                let player = reader.u8()? as usize;
+                let count = reader.u32()? as usize;
+                let mut children = Vec::with_capacity(count.min(reader.remaining()));
+                for _ in 0..count {
+                    let child = reader.u32()? as usize;
+                    if child >= node_count {
+                        return Err(PersistError::Corrupt("child index out of range"));
+                    }
+                    children.push(child);
+                }
+                NodeKind::Decision { player, children }
+            }
+            1 => NodeKind::Terminal(TerminalKind::Fold {
+                folder: reader.u8()? as usize,
+            }),
+            2 => NodeKind::Terminal(TerminalKind::Showdown),
+            _ => return Err(PersistError::Corrupt("unknown node kind")),
+        };
+        nodes.push(TreeNode {
+            kind,
+            label,
+            committed,
+        });
+    }
+    Ok(GameTree {
+        actions,
+        nodes,
+        effective_stack_bb,
+        pot_bb,
+        preflop,
+        rake,
+        legalization: LegalizationReport::default(),
+        // Snapshots keep the snapped amounts but settle terminals in BB again.
+        chips: None,
+    })
+}
+
+fn write_strategies(writer: &mut Writer, strategies: &CompressedStrategies) {
+    writer.u32(strategies.nodes.len() as u32);
+    for node in &strategies.nodes {
+        let Some(strategy) = node else {
+            writer.u8(0);
+            continue;
+        };
+        writer.u8(1);
+        writer.u32(strategy.actions as u32);
+        writer.u32(strategy.buckets as u32);
+        match &strategy.layout {
+            QuantizedLayout::Dense(weights) => {
+                writer.u8(1);
+                writer.u32(weights.len() as u32);
+                for weight in weights {
+                    writer.u16(*weight);
+                }
+            }
+            QuantizedLayout::Sparse { offsets, entries } => {
+                writer.u8(2);
+                writer.u32(offsets.len() as u32);
+                for offset in offsets {
+                    writer.u32(*offset);
+                }
+                writer.u32(entries.len() as u32);
+                for entry in entries {
+                    writer.u16(entry.action);
+                    writer.u16(entry.weight);
+                }
+            }
+        }
+    }
+}
+
+fn read_strategies(reader: &mut Reader) -> Result<CompressedStrategies, PersistError> {
+    let count = reader.u32()? as usize;
+    let mut nodes = Vec::with_capacity(count.min(reader.remaining()));
+    for _ in 0..count {
+        if !reader.bool()? {
+            nodes.push(None);
+            continue;
+        }
+        let actions = reader.u32()? as usize;
+        let buckets = reader.u32()? as usize;
+        let layout = match reader.u8()? {
+            1 => {
+                let length = reader.u32()? as usize;
+                if length != actions * buckets {
+                    return Err(PersistError::Corrupt("dense strategy has the wrong size"));
+                }
+                let mut weights = Vec::with_capacity(length.min(reader.remaining()));
+                for _ in 0..length {
+                    weights.push(reader.u16()?);
+                }
+                QuantizedLayout::Dense(weights)
+            }
+            2 => {
+                let offset_count = reader.u32()? as usize;
+                let mut offsets = Vec::with_capacity(offset_count.min(reader.remaining()));
+                for _ in 0..offset_count {
+                    offsets.push(reader.u32()?);
+                }
+                let entry_count = reader.u32()? as usize;
+                let mut entries = Vec::with_capacity(entry_count.min(reader.remaining()));
+                for _ in 0..entry_count {
+                    entries.push(QuantizedEntry {
+                        action: reader.u16()?,
+                        weight: reader.u16()?,
+                    });
+                }
+                let consistent = offsets.len() == buckets + 1
+                    && offsets.windows(2).all(|pair| pair[0] <= pair[1])
+                    && offsets.last().map(|last| *last as usize) == Some(entries.len());
+                if !consistent {
+                    return Err(PersistError::Corrupt("sparse strategy offsets"));
+                }
+                QuantizedLayout::Sparse { offsets, entries }
+            }
+            _ => return Err(PersistError::Corrupt("unknown strategy layout")),
+        };
+        nodes.push(Some(QuantizedStrategy {
+            actions,
+            buckets,
+            layout,
+        }));
+    }
+    Ok(CompressedStrategies { nodes })
+}
+
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
+
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f64s(&mut self, values: &[f64]) {
+        self.u32(values.len() as u32);
+        for value in values {
+            self.f64(*value);
+        }
+    }
+
+    fn string(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], PersistError> {
+        if length > self.remaining() {
+            return Err(PersistError::Truncated);
+        }
+        let slice = &self.bytes[self.position..self.position + length];
+        self.position += length;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], PersistError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, PersistError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool, PersistError> {
+        Ok(self.u8()? != 0)
+    }
+
+    fn u16(&mut self) -> Result<u16, PersistError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, PersistError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, PersistError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn f64(&mut self) -> Result<f64, PersistError> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    fn f64s(&mut self) -> Result<Vec<f64>, PersistError> {
+        let count = self.u32()? as usize;
+        let mut values = Vec::with_capacity(count.min(self.remaining() / 8));
+        for _ in 0..count {
+            values.push(self.f64()?);
+        }
+        Ok(values)
+    }
+
+    fn string(&mut self) -> Result<String, PersistError> {
+        let length = self.u32()? as usize;
+        String::from_utf8(self.take(length)?.to_vec())
+            .map_err(|_| PersistError::Corrupt("string is not UTF-8"))
+    }
+}
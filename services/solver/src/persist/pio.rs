@@ -0,0 +1,142 @@
+use crate::cfr::CfrSolver;
+use crate::game_tree::{GameTree, NodeKind, HERO};
+use std::fmt::Write as _;
+use std::path::Path;
+
+// Text export laid out like PioSolver's `show_strategy`/`calc_ev` script output: one block per
+// decision node keyed by its Pio node path ("r:0:b500:c"), one line per action with a frequency
+// per hand, then a line of EVs. Our hands are strength buckets rather than 1326 combos, so the
+// header records the bucket count for viewers that need to map them back onto a range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PioExportOptions {
+    // Pio trees use integer chip amounts; bets are written as BB * chips_per_bb, rounded.
+    pub chips_per_bb: f64,
+    pub precision: usize,
+}
+
+impl Default for PioExportOptions {
+    fn default() -> Self {
+        Self {
+            chips_per_bb: 100.0,
+            precision: 4,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PioNode {
+    pub node: usize,
+    pub path: String,
+    pub player: &'static str,
+    // Pio action codes: "f", "c", or "b<chips>".
+    pub actions: Vec<String>,
+    // strategy[action][bucket]
+    pub strategy: Vec<Vec<f64>>,
+    pub ev: Vec<f64>,
+}
+
+pub fn pio_nodes(solver: &CfrSolver, options: &PioExportOptions) -> Vec<PioNode> {
+    let tree = solver.tree();
+    let paths = node_paths(tree, options);
+    let values = solver.node_values();
+    let mut nodes = Vec::new();
+    for (index, node) in tree.nodes.iter().enumerate() {
+        let NodeKind::Decision { player, children } = &node.kind else {
+            continue;
+        };
+        let strategy = solver.average_strategy(index);
+        nodes.push(PioNode {
+            node: index,
+            path: paths[index].clone(),
+            player: if *player == HERO { "OOP" } else { "IP" },
+            actions: children
+                .iter()
+                .map(|child| action_code(tree, *child, *player, options))
+                .collect(),
+            strategy: (0..children.len())
+                .map(|action| strategy.iter().map(|row| row[action]).collect())
+                .collect(),
+            ev: values[index].clone().unwrap_or_default(),
+        });
+    }
+    nodes
+}
+
+pub fn export_pio(solver: &CfrSolver, options: &PioExportOptions) -> String {
+    let tree = solver.tree();
+    let precision = options.precision;
+    let mut out = String::new();
+    let _ = writeln!(out, "#PIO_STRATEGY_EXPORT 1");
+    let _ = writeln!(out, "#BUCKETS {}", solver.buckets());
+    let _ = writeln!(out, "#POT {}", chips(tree.pot_bb, options));
+    let _ = writeln!(
+        out,
+        "#EFFECTIVE_STACK {}",
+        chips(tree.effective_stack_bb, options)
+    );
+    let _ = writeln!(out, "#ITERATIONS {}", solver.iterations());
+    for node in pio_nodes(solver, options) {
+        let _ = writeln!(out, "NODE {}", node.path);
+        let _ = writeln!(out, "PLAYER {}", node.player);
+        let _ = writeln!(out, "ACTIONS {}", node.actions.join(" "));
+        for (action, frequencies) in node.actions.iter().zip(&node.strategy) {
+            let _ = writeln!(
+                out,
+                "STRATEGY {} {}",
+                action,
+                join_values(frequencies, precision)
+            );
+        }
+        let _ = writeln!(out, "EV {}", join_values(&node.ev, precision));
+        let _ = writeln!(out, "END");
+    }
+    out
+}
+
+pub fn write_pio(
+    solver: &CfrSolver,
+    options: &PioExportOptions,
+    path: &Path,
+) -> std::io::Result<()> {
+    std::fs::write(path, export_pio(solver, options))
+}
+
+fn node_paths(tree: &GameTree, options: &PioExportOptions) -> Vec<String> {
+    let mut paths = vec![String::new(); tree.nodes.len()];
+    paths[0] = "r:0".to_string();
+    let mut stack = vec![0];
+    while let Some(node) = stack.pop() {
+        if let NodeKind::Decision { player, children } = &tree.nodes[node].kind {
+            for child in children {
+                paths[*child] = format!(
+                    "{}:{}",
+                    paths[node],
+                    action_code(tree, *child, *player, options)
+                );
+                stack.push(*child);
+            }
+        }
+    }
+    paths
+}
+
+fn action_code(tree: &GameTree, child: usize, actor: usize, options: &PioExportOptions) -> String {
+    let node = &tree.nodes[child];
+    match node.label.as_str() {
+        "fold" => "f".to_string(),
+        "call" | "check" => "c".to_string(),
+        _ => format!("b{}", chips(node.committed[actor], options)),
+    }
+}
+
+fn chips(bb: f64, options: &PioExportOptions) -> i64 {
+    (bb * options.chips_per_bb).round() as i64
+}
+
+fn join_values(values: &[f64], precision: usize) -> String {
+    values
+        .iter()
+        .map(|value| format!("{:.*}", precision, value))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,78 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+
+// Runs every task on up to `workers` threads and returns the results in task order. Each task
+// comes with an estimate of its cost: tasks are dealt biggest first to the least loaded queue,
+// and a worker whose queue runs dry steals from the back of the fullest one.
+pub fn run_stealing<T, R, F>(tasks: Vec<(T, f64)>, workers: usize, run: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let workers = workers.clamp(1, tasks.len().max(1));
+    if workers == 1 {
+        return tasks.into_iter().map(|(task, _)| run(task)).collect();
+    }
+
+    let count = tasks.len();
+    let mut tasks: Vec<(usize, T, f64)> = tasks
+        .into_iter()
+        .enumerate()
+        .map(|(index, (task, cost))| (index, task, cost))
+        .collect();
+    tasks.sort_by(|a, b| b.2.total_cmp(&a.2));
+    let mut queues: Vec<VecDeque<(usize, T)>> = (0..workers).map(|_| VecDeque::new()).collect();
+    let mut loads = vec![0.0f64; workers];
+    for (index, task, cost) in tasks {
+        let lightest = (0..workers)
+            .min_by(|a, b| loads[*a].total_cmp(&loads[*b]))
+            .unwrap_or(0);
+        loads[lightest] += cost.max(0.0);
+        queues[lightest].push_back((index, task));
+    }
+    let queues: Vec<Mutex<VecDeque<(usize, T)>>> = queues.into_iter().map(Mutex::new).collect();
+    let results: Mutex<Vec<Option<R>>> = Mutex::new((0..count).map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for worker in 0..workers {
+            let (queues, results, run) = (&queues, &results, &run);
+            scope.spawn(move || {
+                while let Some((index, task)) = next_task(queues, worker) {
+                    let result = run(task);
+                    lock(results)[index] = Some(result);
+                }
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .into_iter()
+        .map(|result| result.expect("every task ran"))
+        .collect()
+}
+
+// The worker's own next task, or one stolen from the fullest other queue. No tasks are added
+// once the workers start, so empty queues everywhere mean the work is done.
+fn next_task<T>(queues: &[Mutex<VecDeque<(usize, T)>>], worker: usize) -> Option<(usize, T)> {
+    if let Some(task) = lock(&queues[worker]).pop_front() {
+        return Some(task);
+    }
+    let fullest = (0..queues.len())
+        .filter(|other| *other != worker)
+        .max_by_key(|other| lock(&queues[*other]).len())?;
+    let stolen = lock(&queues[fullest]).pop_back();
+    // Another worker may have emptied it meanwhile; look again before giving up.
+    match stolen {
+        Some(task) => Some(task),
+        None if queues.iter().any(|queue| !lock(queue).is_empty()) => next_task(queues, worker),
+        None => None,
+    }
+}
+
+fn lock<V>(mutex: &Mutex<V>) -> MutexGuard<'_, V> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,144 @@
+use crate::abstraction::matrix::{strategy_matrix, MatrixCell};
+use crate::abstraction::GameVariant;
+use crate::cfr::{CfrConfig, CfrSolver};
+use crate::game_tree::preflop::{preflop_tree, Position, PreflopSizing};
+use crate::game_tree::{GameTree, NodeKind, HERO};
+use crate::rng::SolverRng;
+use serde::Serialize;
+
+pub mod pushfold;
+
+pub const DEFAULT_STACKS_BB: [f64; 3] = [20.0, 40.0, 100.0];
+
+// The line each chart is read at, as the actions taken from the root.
+const CHART_LINES: [(&str, &[&str]); 4] = [
+    ("rfi", &[]),
+    ("vs_open", &["open"]),
+    ("vs_3bet", &["open", "3-bet"]),
+    ("vs_4bet", &["open", "3-bet", "4-bet"]),
+];
+
+// What an offline chart run covers: one preflop tree per position and stack depth.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartConfig {
+    pub positions: Vec<Position>,
+    pub stacks_bb: Vec<f64>,
+    pub sizing: PreflopSizing,
+    pub iterations: usize,
+    pub buckets: usize,
+    // Runouts each hand's preflop equity is averaged over when buckets go back onto the grid.
+    pub runouts: usize,
+    pub seed: u64,
+}
+
+impl Default for ChartConfig {
+    fn default() -> Self {
+        Self {
+            positions: Position::ALL.to_vec(),
+            stacks_bb: DEFAULT_STACKS_BB.to_vec(),
+            sizing: PreflopSizing::default(),
+            iterations: 2_000,
+            buckets: 32,
+            runouts: 64,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PreflopChart {
+    // "rfi", "vs_open", "vs_3bet" or "vs_4bet".
+    pub name: String,
+    // The opener's position, or "BB".
+    pub player: String,
+    pub actions: Vec<String>,
+    // 13x13, aces first, suited hands above the diagonal.
+    pub rows: Vec<Vec<MatrixCell>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ChartFile {
+    pub position: String,
+    pub stack_bb: f64,
+    pub iterations: usize,
+    // Of the average strategy over the whole tree, as a fraction of the pot.
+    pub exploitability: f64,
+    // In line order; a line the stack doesn't leave room for (no 4-bet short of all-in) has no
+    // chart.
+    pub charts: Vec<PreflopChart>,
+}
+
+impl ChartFile {
+    // "BTN_100bb.json".
+    pub fn file_name(&self) -> String {
+        format!("{}_{}bb.json", self.position, self.stack_bb)
+    }
+}
+
+// Solves the preflop tree for one seat and stack with `cfr`'s variant and backend, and reads a
+// chart off every line it has.
+pub fn solve_charts(
+    position: Position,
+    stack_bb: f64,
+    config: &ChartConfig,
+    cfr: &CfrConfig,
+) -> ChartFile {
+    let tree = preflop_tree(position, stack_bb, &config.sizing);
+    let mut cfr = cfr.clone();
+    cfr.iterations = config.iterations;
+    cfr.buckets = config.buckets.max(1);
+    cfr.seed = config.seed;
+    let mut solver = CfrSolver::new(&tree, &cfr);
+    solver.run(config.iterations);
+
+    let mut rng = SolverRng::seeded(config.seed);
+    let charts = CHART_LINES
+        .iter()
+        .filter_map(|(name, line)| {
+            let node = follow(&tree, line)?;
+            let NodeKind::Decision { player, children } = &tree.nodes[node].kind else {
+                return None;
+            };
+            let actions = children
+                .iter()
+                .map(|&child| tree.nodes[child].label.clone())
+                .collect();
+            let matrix = strategy_matrix(
+                &[],
+                GameVariant::Holdem,
+                actions,
+                &solver.average_strategy(node),
+                config.runouts,
+                &mut rng,
+            );
+            Some(PreflopChart {
+                name: name.to_string(),
+                player: if *player == HERO {
+                    position.as_str().to_string()
+                } else {
+                    "BB".to_string()
+                },
+                actions: matrix.actions,
+                rows: matrix.rows,
+            })
+        })
+        .collect();
+    ChartFile {
+        position: position.as_str().to_string(),
+        stack_bb,
+        iterations: solver.iterations(),
+        exploitability: solver.exploitability(),
+        charts,
+    }
+}
+
+fn follow(tree: &GameTree, line: &[&str]) -> Option<usize> {
+    line.iter()
+        .try_fold(0, |node, label| match &tree.nodes[node].kind {
+            NodeKind::Decision { children, .. } => children
+                .iter()
+                .copied()
+                .find(|&child| tree.nodes[child].label == *label),
+            NodeKind::Terminal(_) => None,
+        })
+}
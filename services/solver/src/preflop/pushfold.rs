@@ -0,0 +1,463 @@
+use crate::abstraction::matrix::{cell_of, hand_class};
+use crate::abstraction::quality::{live_cards, live_combos, sample};
+use crate::abstraction::GameVariant;
+use crate::cards::ranking::evaluate;
+use crate::cards::Card;
+use crate::rng::SolverRng;
+use serde::Serialize;
+use std::sync::{Arc, Mutex, OnceLock};
+
+// The 13x13 grid flattened row by row: aces first, suited hands above the diagonal.
+pub const CLASSES: usize = 169;
+
+// Stacks at and below this play push/fold well enough to skip the tree.
+pub const MAX_PUSH_FOLD_STACK_BB: f64 = 15.0;
+
+// Solved equities and threshold sweeps kept for the process; a sweep is a few dozen solves.
+const MAX_CACHED: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PushFoldOptions {
+    // Fictitious-play rounds per solve.
+    pub iterations: usize,
+    // Boards every class-versus-class equity is averaged over.
+    pub runouts: usize,
+    pub seed: u64,
+    // Stacks swept for each hand's jam and call thresholds, in BB.
+    pub threshold_stacks_bb: Vec<f64>,
+}
+
+impl Default for PushFoldOptions {
+    fn default() -> Self {
+        Self {
+            iterations: 1_000,
+            runouts: 200,
+            seed: 0,
+            threshold_stacks_bb: (1..=15).map(f64::from).collect(),
+        }
+    }
+}
+
+// Heads-up and all-in or fold: the small blind jams the stack or folds, and the big blind calls
+// or folds. Amounts in BB.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PushFoldGame {
+    pub stack_bb: f64,
+    pub small_blind_bb: f64,
+    // Antes and anything else in the pot that neither blind put in.
+    pub dead_bb: f64,
+}
+
+impl PushFoldGame {
+    pub fn new(stack_bb: f64) -> Self {
+        Self {
+            stack_bb,
+            small_blind_bb: 0.5,
+            dead_bb: 0.0,
+        }
+    }
+
+    pub fn at_stack(&self, stack_bb: f64) -> Self {
+        Self { stack_bb, ..*self }
+    }
+}
+
+// What the small blind and the big blind, in that order, end up with at each way the hand can
+// end, in one unit: BB for chip EV, or tournament equity. Only differences between terminals
+// matter, so any baseline will do.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PushFoldPayoffs {
+    pub small_blind_folds: [f64; 2],
+    pub big_blind_folds: [f64; 2],
+    // Showdowns of the all-in. Ties are split out of the equities as half a win each.
+    pub small_blind_wins: [f64; 2],
+    pub big_blind_wins: [f64; 2],
+}
+
+impl PushFoldPayoffs {
+    // Chips won or lost over the hand, in BB.
+    pub fn chips(game: &PushFoldGame) -> Self {
+        let (stack, dead) = (game.stack_bb, game.dead_bb);
+        Self {
+            small_blind_folds: [-game.small_blind_bb, game.small_blind_bb + dead],
+            big_blind_folds: [1.0 + dead, -1.0],
+            small_blind_wins: [stack + dead, -stack],
+            big_blind_wins: [-stack, stack + dead],
+        }
+    }
+
+    // What the small blind's steal is worth over folding: the pot, in chips.
+    pub fn pot(&self) -> f64 {
+        self.big_blind_folds[0] - self.small_blind_folds[0]
+    }
+}
+
+// Preflop all-in equity of every hand class against every other, with how many combo pairs each
+// matchup has once shared cards are taken out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassEquities {
+    // `CLASSES` x `CLASSES`, row class against column class.
+    pub equity: Vec<f64>,
+    pub pairs: Vec<f64>,
+    // Combo pairs each class is dealt into, the row sums of `pairs`.
+    pub deals: Vec<f64>,
+}
+
+impl ClassEquities {
+    // Every combo is run out against every combo it shares no card with on each sampled board,
+    // so blockers weigh in the way they do at the table.
+    pub fn compute(runouts: usize, seed: u64) -> Self {
+        let mut rng = SolverRng::seeded(seed);
+        let live = live_cards(&[], GameVariant::Holdem);
+        let combos = live_combos(&live);
+        let classes: Vec<usize> = combos.iter().map(class_of).collect();
+
+        let mut pairs = vec![0.0; CLASSES * CLASSES];
+        for (first, combo) in combos.iter().enumerate() {
+            for (second, other) in combos.iter().enumerate() {
+                if first != second && disjoint(combo, other) {
+                    pairs[classes[first] * CLASSES + classes[second]] += 1.0;
+                }
+            }
+        }
+
+        let mut wins = vec![0.0; CLASSES * CLASSES];
+        let mut counts = vec![0.0; CLASSES * CLASSES];
+        for _ in 0..runouts.max(1) {
+            let board = sample(&live, 5, &mut rng);
+            let ranks: Vec<_> = combos
+                .iter()
+                .map(|combo| {
+                    if combo.iter().any(|card| board.contains(card)) {
+                        return None;
+                    }
+                    let cards: Vec<Card> = combo.iter().chain(&board).copied().collect();
+                    evaluate(&cards, GameVariant::Holdem)
+                })
+                .collect();
+            for first in 0..combos.len() {
+                let Some(rank) = ranks[first] else {
+                    continue;
+                };
+                for second in first + 1..combos.len() {
+                    let Some(other) = ranks[second] else {
+                        continue;
+                    };
+                    if !disjoint(&combos[first], &combos[second]) {
+                        continue;
+                    }
+                    let share = match rank.cmp(&other) {
+                        std::cmp::Ordering::Greater => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Less => 0.0,
+                    };
+                    let (row, column) = (classes[first], classes[second]);
+                    wins[row * CLASSES + column] += share;
+                    wins[column * CLASSES + row] += 1.0 - share;
+                    counts[row * CLASSES + column] += 1.0;
+                    counts[column * CLASSES + row] += 1.0;
+                }
+            }
+        }
+        // A matchup every sampled board blocked is called a coin flip.
+        let equity = wins
+            .iter()
+            .zip(&counts)
+            .map(|(wins, count)| if *count > 0.0 { wins / count } else { 0.5 })
+            .collect();
+        let deals = pairs.chunks(CLASSES).map(|row| row.iter().sum()).collect();
+        Self {
+            equity,
+            pairs,
+            deals,
+        }
+    }
+
+    // Computed once per process for each runout count and seed.
+    pub fn shared(runouts: usize, seed: u64) -> Arc<Self> {
+        static SHARED: OnceLock<Cached<(usize, u64), ClassEquities>> = OnceLock::new();
+        let shared = SHARED.get_or_init(Mutex::default);
+        let key = (runouts, seed);
+        if let Some((_, equities)) = lock(shared).iter().find(|(cached, _)| *cached == key) {
+            return equities.clone();
+        }
+        let equities = Arc::new(Self::compute(runouts, seed));
+        remember(&mut lock(shared), key, equities.clone());
+        equities
+    }
+
+    pub fn equity(&self, class: usize, against: usize) -> f64 {
+        self.equity[class * CLASSES + against]
+    }
+
+    pub fn pairs(&self, class: usize, against: usize) -> f64 {
+        self.pairs[class * CLASSES + against]
+    }
+}
+
+// The average strategies of a fictitious-play solve, per hand class.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PushFoldSolution {
+    pub payoffs: PushFoldPayoffs,
+    pub iterations: usize,
+    // The small blind's jam frequency and the big blind's call frequency facing the jam.
+    pub jam: Vec<f64>,
+    pub call: Vec<f64>,
+    // What jamming and calling win over folding, in the payoffs' unit, against the other side's
+    // strategy.
+    pub jam_ev: Vec<f64>,
+    pub call_ev: Vec<f64>,
+    // What best responses would gain over the strategies, averaged over the two players.
+    pub exploitability: f64,
+}
+
+// Fictitious play: each round both players best-respond to the other's average so far, and the
+// averages converge to the equilibrium of the two-player constant-sum chip game. Tournament
+// payoffs aren't constant-sum (the rest of the field gains from every bust), where the averages
+// settle all the same in practice and `exploitability` says how well.
+pub fn solve_push_fold(
+    payoffs: &PushFoldPayoffs,
+    equities: &ClassEquities,
+    iterations: usize,
+) -> PushFoldSolution {
+    let mut jam = vec![0.5; CLASSES];
+    let mut call = vec![0.5; CLASSES];
+    for round in 0..iterations {
+        let jam_ev = jam_values(payoffs, equities, &call);
+        let call_ev = call_values(payoffs, equities, &jam);
+        let step = 1.0 / (round + 2) as f64;
+        for class in 0..CLASSES {
+            let jams = if jam_ev[class] > 0.0 { 1.0 } else { 0.0 };
+            let calls = if call_ev[class] > 0.0 { 1.0 } else { 0.0 };
+            jam[class] += (jams - jam[class]) * step;
+            call[class] += (calls - call[class]) * step;
+        }
+    }
+
+    let jam_ev = jam_values(payoffs, equities, &call);
+    let call_ev = call_values(payoffs, equities, &jam);
+    // The big blind only decides in the deals the small blind jams.
+    let jammed: Vec<f64> = (0..CLASSES)
+        .map(|class| {
+            (0..CLASSES)
+                .map(|jammer| equities.pairs(class, jammer) * jam[jammer])
+                .sum()
+        })
+        .collect();
+    let total: f64 = equities.deals.iter().sum();
+    let gain = |strategy: &[f64], values: &[f64], deals: &[f64]| {
+        (0..CLASSES)
+            .map(|class| deals[class] * (values[class].max(0.0) - strategy[class] * values[class]))
+            .sum::<f64>()
+            / total.max(1.0)
+    };
+    let exploitability =
+        (gain(&jam, &jam_ev, &equities.deals) + gain(&call, &call_ev, &jammed)) / 2.0;
+    PushFoldSolution {
+        payoffs: *payoffs,
+        iterations,
+        jam,
+        call,
+        jam_ev,
+        call_ev,
+        exploitability,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PushFoldHand {
+    // "AA", "AKs" or "AKo".
+    pub hand: String,
+    // 6 for a pair, 4 suited, 12 offsuit.
+    pub combos: usize,
+    pub jam: f64,
+    pub call: f64,
+    // Over folding, in the payoffs' unit, against the other side's equilibrium strategy.
+    pub jam_ev: f64,
+    pub call_ev: f64,
+    // The deepest swept stack the hand still jams or calls at more often than not; 0 for none.
+    pub jam_threshold_bb: f64,
+    pub call_threshold_bb: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PushFoldChart {
+    pub stack_bb: f64,
+    pub iterations: usize,
+    // What the small blind's steal is worth, in the payoffs' unit: the pot, for chips.
+    pub pot: f64,
+    pub exploitability: f64,
+    // In grid order, aces first.
+    pub hands: Vec<PushFoldHand>,
+}
+
+// Solves `game` for chips and sweeps the configured stacks for thresholds; both come from the
+// process-wide equities, and a sweep is solved once per blinds and dead money.
+pub fn push_fold_chart(game: &PushFoldGame, options: &PushFoldOptions) -> PushFoldChart {
+    let equities = ClassEquities::shared(options.runouts, options.seed);
+    let solution = solve_push_fold(&PushFoldPayoffs::chips(game), &equities, options.iterations);
+    let thresholds = chip_thresholds(game, options, &equities);
+    chart(game, &solution, &thresholds)
+}
+
+// Like `push_fold_chart` under payoffs `game` is turned into at each stack, such as tournament
+// equity. Nothing is cached but the equities, so the sweep is solved every time.
+pub fn push_fold_chart_with(
+    game: &PushFoldGame,
+    options: &PushFoldOptions,
+    payoffs: impl Fn(&PushFoldGame) -> PushFoldPayoffs,
+) -> PushFoldChart {
+    let equities = ClassEquities::shared(options.runouts, options.seed);
+    let solution = solve_push_fold(&payoffs(game), &equities, options.iterations);
+    let thresholds = thresholds(game, options, &equities, payoffs);
+    chart(game, &solution, &thresholds)
+}
+
+fn chart(
+    game: &PushFoldGame,
+    solution: &PushFoldSolution,
+    thresholds: &[[f64; 2]],
+) -> PushFoldChart {
+    PushFoldChart {
+        stack_bb: game.stack_bb,
+        iterations: solution.iterations,
+        pot: solution.payoffs.pot(),
+        exploitability: solution.exploitability,
+        hands: (0..CLASSES)
+            .map(|class| PushFoldHand {
+                hand: hand_class(class / 13, class % 13),
+                combos: match (class / 13).cmp(&(class % 13)) {
+                    std::cmp::Ordering::Equal => 6,
+                    std::cmp::Ordering::Less => 4,
+                    std::cmp::Ordering::Greater => 12,
+                },
+                jam: solution.jam[class],
+                call: solution.call[class],
+                jam_ev: solution.jam_ev[class],
+                call_ev: solution.call_ev[class],
+                jam_threshold_bb: thresholds[class][0],
+                call_threshold_bb: thresholds[class][1],
+            })
+            .collect(),
+    }
+}
+
+pub fn class_of(combo: &[Card; 2]) -> usize {
+    let (row, column) = cell_of(combo);
+    row * 13 + column
+}
+
+type Cached<K, V> = Mutex<Vec<(K, Arc<V>)>>;
+
+type SweepKey = (usize, usize, u64, Vec<u64>, u64, u64);
+
+fn chip_thresholds(
+    game: &PushFoldGame,
+    options: &PushFoldOptions,
+    equities: &ClassEquities,
+) -> Arc<Vec<[f64; 2]>> {
+    static SWEEPS: OnceLock<Cached<SweepKey, Vec<[f64; 2]>>> = OnceLock::new();
+    let sweeps = SWEEPS.get_or_init(Mutex::default);
+    let key = (
+        options.iterations,
+        options.runouts,
+        options.seed,
+        options
+            .threshold_stacks_bb
+            .iter()
+            .map(|stack| stack.to_bits())
+            .collect(),
+        game.small_blind_bb.to_bits(),
+        game.dead_bb.to_bits(),
+    );
+    if let Some((_, found)) = lock(sweeps).iter().find(|(cached, _)| *cached == key) {
+        return found.clone();
+    }
+    let found = Arc::new(thresholds(game, options, equities, PushFoldPayoffs::chips));
+    remember(&mut lock(sweeps), key, found.clone());
+    found
+}
+
+// The deepest swept stack each class jams and calls at.
+fn thresholds(
+    game: &PushFoldGame,
+    options: &PushFoldOptions,
+    equities: &ClassEquities,
+    payoffs: impl Fn(&PushFoldGame) -> PushFoldPayoffs,
+) -> Vec<[f64; 2]> {
+    let mut found = vec![[0.0; 2]; CLASSES];
+    for &stack_bb in &options.threshold_stacks_bb {
+        let payoffs = payoffs(&game.at_stack(stack_bb));
+        let solution = solve_push_fold(&payoffs, equities, options.iterations);
+        for (class, found) in found.iter_mut().enumerate() {
+            if solution.jam[class] > 0.5 {
+                found[0] = f64::max(found[0], stack_bb);
+            }
+            if solution.call[class] > 0.5 {
+                found[1] = f64::max(found[1], stack_bb);
+            }
+        }
+    }
+    found
+}
+
+// What jamming each class wins over folding it, against the big blind calling with `call`.
+fn jam_values(payoffs: &PushFoldPayoffs, equities: &ClassEquities, call: &[f64]) -> Vec<f64> {
+    (0..CLASSES)
+        .map(|class| {
+            let deals = equities.deals[class];
+            if deals <= 0.0 {
+                return 0.0;
+            }
+            let jam = (0..CLASSES)
+                .map(|against| {
+                    let equity = equities.equity(class, against);
+                    let called = equity * payoffs.small_blind_wins[0]
+                        + (1.0 - equity) * payoffs.big_blind_wins[0];
+                    let stolen = payoffs.big_blind_folds[0];
+                    equities.pairs(class, against) * (stolen + call[against] * (called - stolen))
+                })
+                .sum::<f64>()
+                / deals;
+            jam - payoffs.small_blind_folds[0]
+        })
+        .collect()
+}
+
+// What calling a jam with each class wins over folding it, against the small blind jamming with
+// `jam`. With nothing jamming there is nothing to call.
+fn call_values(payoffs: &PushFoldPayoffs, equities: &ClassEquities, jam: &[f64]) -> Vec<f64> {
+    (0..CLASSES)
+        .map(|class| {
+            let (value, weight) = (0..CLASSES).fold((0.0, 0.0), |(value, weight), jammer| {
+                let weight_here = equities.pairs(class, jammer) * jam[jammer];
+                let equity = equities.equity(class, jammer);
+                let called = equity * payoffs.big_blind_wins[1]
+                    + (1.0 - equity) * payoffs.small_blind_wins[1];
+                (value + weight_here * called, weight + weight_here)
+            });
+            if weight > 0.0 {
+                value / weight - payoffs.big_blind_folds[1]
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+fn disjoint(combo: &[Card; 2], other: &[Card; 2]) -> bool {
+    !combo.iter().any(|card| other.contains(card))
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn remember<K, V>(cached: &mut Vec<(K, V)>, key: K, value: V) {
+    if cached.len() >= MAX_CACHED {
+        cached.remove(0);
+    }
+    cached.push((key, value));
+}
@@ -0,0 +1,132 @@
+use crate::config::ProfilingConfig;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+pub const CPU_PROFILE_PATH: &str = "/debug/pprof/profile";
+pub const HEAP_PROFILE_PATH: &str = "/debug/pprof/heap";
+const DEFAULT_CPU_SECONDS: u64 = 30;
+
+// The sampler is process-wide, so only one CPU profile runs at a time.
+static CPU_PROFILE_RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProfileKind {
+    Cpu { duration: Duration },
+    Heap,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ProfileError {
+    Unsupported,
+    Busy,
+    Failed(String),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::Unsupported => {
+                write!(
+                    f,
+                    "profiling needs the solver built with the `profiling` feature"
+                )
+            }
+            ProfileError::Busy => write!(f, "a CPU profile is already running"),
+            ProfileError::Failed(message) => write!(f, "profiling failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+// The profile a metrics-port request path asks for, the way `go tool pprof` asks: a CPU profile
+// of `?seconds=N` (30 by default, capped by the config) or a heap snapshot. None for any other
+// path, and for every path while profiling is off.
+pub fn profile_request(path: &str, config: &ProfilingConfig) -> Option<ProfileKind> {
+    if !config.enabled {
+        return None;
+    }
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    match path {
+        CPU_PROFILE_PATH => {
+            let seconds = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("seconds="))
+                .and_then(|seconds| seconds.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_CPU_SECONDS)
+                .clamp(1, config.max_seconds.max(1));
+            Some(ProfileKind::Cpu {
+                duration: Duration::from_secs(seconds),
+            })
+        }
+        HEAP_PROFILE_PATH => Some(ProfileKind::Heap),
+        _ => None,
+    }
+}
+
+// A pprof protobuf. Blocks for the whole of a CPU profile, so call it off the async runtime.
+pub fn collect_profile(
+    kind: ProfileKind,
+    config: &ProfilingConfig,
+) -> Result<Vec<u8>, ProfileError> {
+    match kind {
+        ProfileKind::Cpu { duration } => {
+            if CPU_PROFILE_RUNNING.swap(true, Ordering::AcqRel) {
+                return Err(ProfileError::Busy);
+            }
+            let profile = cpu_profile(duration, config.frequency);
+            CPU_PROFILE_RUNNING.store(false, Ordering::Release);
+            profile
+        }
+        ProfileKind::Heap => heap_profile(),
+    }
+}
+
+#[cfg(feature = "profiling")]
+fn cpu_profile(duration: Duration, frequency: i32) -> Result<Vec<u8>, ProfileError> {
+    use pprof::protos::Message;
+
+    let failed = |error: pprof::Error| ProfileError::Failed(error.to_string());
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(frequency.max(1))
+        // Unwinding inside these while a signal lands can deadlock.
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()
+        .map_err(failed)?;
+    std::thread::sleep(duration);
+    let profile = guard
+        .report()
+        .build()
+        .and_then(|report| report.pprof())
+        .map_err(failed)?;
+    Ok(profile.encode_to_vec())
+}
+
+#[cfg(not(feature = "profiling"))]
+fn cpu_profile(_duration: Duration, _frequency: i32) -> Result<Vec<u8>, ProfileError> {
+    Err(ProfileError::Unsupported)
+}
+
+// Needs jemalloc as the global allocator with sampling on, which the binary sets up in
+// profiling builds.
+#[cfg(feature = "profiling")]
+fn heap_profile() -> Result<Vec<u8>, ProfileError> {
+    let control = jemalloc_pprof::PROF_CTL
+        .as_ref()
+        .ok_or(ProfileError::Unsupported)?;
+    let mut control = control.blocking_lock();
+    if !control.activated() {
+        return Err(ProfileError::Failed(
+            "jemalloc heap sampling is off".to_string(),
+        ));
+    }
+    control
+        .dump_pprof()
+        .map_err(|error| ProfileError::Failed(error.to_string()))
+}
+
+#[cfg(not(feature = "profiling"))]
+fn heap_profile() -> Result<Vec<u8>, ProfileError> {
+    Err(ProfileError::Unsupported)
+}
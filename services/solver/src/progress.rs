@@ -0,0 +1,152 @@
+use crate::solver_proto::ProgressUpdate;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// How far a solve has got, updated by the solver as it runs and readable from other threads.
+// A request can take several CFR solves (one per board); each counts as an equal part.
+#[derive(Debug, Default)]
+pub struct SolveProgress {
+    parts: AtomicU64,
+    finished: AtomicU64,
+    planned: AtomicU64,
+    done: AtomicU64,
+    total: AtomicU64,
+    // Exploitability costs a best-response pass, so it is only measured for watched solves.
+    tracks_exploitability: bool,
+    // f64 bits; 0 until the first measurement.
+    exploitability: AtomicU64,
+    measured: AtomicBool,
+    cancelled: AtomicBool,
+    timing: Mutex<Timing>,
+}
+
+#[derive(Debug, Default)]
+struct Timing {
+    started: Option<Instant>,
+    deadline: Option<Instant>,
+}
+
+impl SolveProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // For solves someone is watching: the solver also measures exploitability as it goes.
+    pub fn with_exploitability() -> Self {
+        Self {
+            tracks_exploitability: true,
+            ..Self::default()
+        }
+    }
+
+    // Called when the request starts solving; `deadline` is the end of its time budget.
+    pub fn start(&self, deadline: Option<Instant>) {
+        let mut timing = self.timing();
+        timing.started = Some(Instant::now());
+        timing.deadline = deadline;
+    }
+
+    // Announces `count` more CFR solves before they start, so the fraction doesn't jump back.
+    pub fn add_parts(&self, count: usize) {
+        self.parts.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn begin(&self, iterations: usize) {
+        self.done.store(0, Ordering::Relaxed);
+        self.planned.store(iterations as u64, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+        self.total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn finish(&self) {
+        self.planned.store(0, Ordering::Relaxed);
+        self.done.store(0, Ordering::Relaxed);
+        self.finished.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn tracks_exploitability(&self) -> bool {
+        self.tracks_exploitability
+    }
+
+    pub fn record_exploitability(&self, exploitability: f64) {
+        self.exploitability
+            .store(exploitability.to_bits(), Ordering::Relaxed);
+        self.measured.store(true, Ordering::Relaxed);
+    }
+
+    // Latest measurement, of the board being solved in a multi-board solve.
+    pub fn exploitability(&self) -> Option<f64> {
+        self.measured
+            .load(Ordering::Relaxed)
+            .then(|| f64::from_bits(self.exploitability.load(Ordering::Relaxed)))
+    }
+
+    // Asks the solver to stop after the current iteration and return what it has.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    // Iterations run so far, over every CFR solve of the request.
+    pub fn iterations(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    // Between 0 and 1; a solve that stops early (target reached, deadline) jumps to its part's end.
+    pub fn fraction(&self) -> f64 {
+        let finished = self.finished.load(Ordering::Relaxed);
+        let planned = self.planned.load(Ordering::Relaxed);
+        let started = finished + u64::from(planned > 0);
+        let parts = self.parts.load(Ordering::Relaxed).max(started).max(1);
+        let current = if planned == 0 {
+            0.0
+        } else {
+            (self.done.load(Ordering::Relaxed) as f64 / planned as f64).min(1.0)
+        };
+        ((finished as f64 + current) / parts as f64).min(1.0)
+    }
+
+    // Extrapolated from the pace so far, and never past the deadline. None before there is
+    // anything to extrapolate from.
+    pub fn remaining(&self) -> Option<Duration> {
+        let timing = self.timing();
+        let now = Instant::now();
+        let left_in_budget = timing
+            .deadline
+            .map(|deadline| deadline.saturating_duration_since(now));
+        let fraction = self.fraction();
+        let extrapolated = timing.started.filter(|_| fraction > 0.0).map(|started| {
+            now.duration_since(started)
+                .mul_f64((1.0 - fraction) / fraction)
+        });
+        match (extrapolated, left_in_budget) {
+            (Some(extrapolated), Some(left)) => Some(extrapolated.min(left)),
+            (extrapolated, left) => extrapolated.or(left),
+        }
+    }
+
+    pub fn to_proto(&self) -> ProgressUpdate {
+        ProgressUpdate {
+            iterations: self.iterations(),
+            exploitability: self.exploitability().unwrap_or(-1.0),
+            remaining_ms: self
+                .remaining()
+                .map(|remaining| remaining.as_millis() as i64)
+                .unwrap_or(-1),
+            fraction: self.fraction(),
+        }
+    }
+
+    fn timing(&self) -> std::sync::MutexGuard<'_, Timing> {
+        self.timing
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
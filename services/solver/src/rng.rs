@@ -0,0 +1,65 @@
+// SplitMix64. Small, fast, and fully specified here so a seed reproduces the same stream on every
+// platform and toolchain, which golden-file tests depend on.
+#[derive(Clone, Debug)]
+pub struct SolverRng {
+    state: u64,
+}
+
+impl SolverRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            return 0;
+        }
+        (self.next_f64() * bound as f64) as usize % bound
+    }
+
+    // Index drawn in proportion to `weights`; uniform when they are all zero.
+    pub fn sample_index(&mut self, weights: &[f64]) -> usize {
+        let total: f64 = weights.iter().map(|weight| weight.max(0.0)).sum();
+        if total <= 0.0 {
+            return self.below(weights.len());
+        }
+        let mut target = self.next_f64() * total;
+        for (index, weight) in weights.iter().enumerate() {
+            target -= weight.max(0.0);
+            if target < 0.0 {
+                return index;
+            }
+        }
+        weights.len().saturating_sub(1)
+    }
+
+    // Independent child stream, e.g. one per worker, that stays reproducible.
+    pub fn fork(&mut self) -> Self {
+        Self::seeded(self.next_u64())
+    }
+}
+
+// Used to derive a stable seed from the request fingerprint when none is given.
+pub fn seed_from_fingerprint(fingerprint: &str) -> u64 {
+    fnv1a(fingerprint.as_bytes())
+}
+
+// FNV-1a: stable across builds and platforms, unlike the std hashers.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
@@ -1,40 +1,1620 @@
-use crate::abstraction::{parse_action_set, BlindSummary, GameStateSummary};
+use crate::abstraction::all_in::budgeted_all_in_showdowns;
+use crate::abstraction::blockers::{card_removal, exact_river};
+use crate::abstraction::matrix::{matrix_json, strategy_matrix};
+use crate::abstraction::overbet::expand_overbet_tokens;
+use crate::abstraction::quality::{abstraction_quality, QualityOptions};
+use crate::abstraction::realization::realized_showdowns;
+use crate::abstraction::templates::request_action_sets;
+use crate::abstraction::{
+    merge_similar_sizes, parse_action_set_strict, parse_action_set_with_policy,
+    street_action_tokens, ActionSpec, BettingStructure, GameStateSummary, GameVariant,
+    SizingPolicy, Street,
+};
+use crate::archive::StrategyArchive;
 use crate::budget::BudgetClock;
-use crate::cfr::{run_cfr, ActionStat};
-use crate::game_tree::GameTree;
-use crate::solver_proto::{ActionProb, SubgameRequest, SubgameResponse};
+use crate::cache::SolveCache;
+use crate::cards::flops::representative_flops;
+use crate::cards::runouts::{classify_river, river_cards, RunoutClass};
+use crate::cards::{check_deck, parse_cards, validate_board, Card, CardError};
+use crate::cfr::aivat::{evaluate_hands, PlayedHand};
+use crate::cfr::benchmark::{run_benchmark, suite_throughput};
+use crate::cfr::constraints::{RootConstraints, StrategyConstraint};
+use crate::cfr::deepening::run_cfr_deepening;
+use crate::cfr::diff::{diff_strategies, StrategyDiff};
+use crate::cfr::locks::NodeLocks;
+use crate::cfr::opponent::OpponentModel;
+use crate::cfr::purify::{purify_hands, round_frequencies, FrequencyRounding, Purification};
+use crate::cfr::refine::{RefinementConfig, RefinementStats};
+use crate::cfr::variant::CfrVariantKind;
+use crate::cfr::{range_stats, ActionStat, CfrConfig, CfrSolver, SolveMemory, WarmStartPrior};
+use crate::config::SolverConfig;
+use crate::error::SolverError;
+use crate::game_tree::chips::ChipRounding;
+use crate::game_tree::eval::RakeModel;
+use crate::game_tree::export::{export_tree, TreeFormat};
+use crate::game_tree::legality::{BettingContext, LegalizationReport};
+use crate::game_tree::limits::{
+    fit_tree, next_street_tree, shallower_trees, DegradationLevel, TreeFootprint,
+};
+use crate::game_tree::size_search::{best_size, search_bet_size, SizeSearch};
+use crate::game_tree::{ActionKind, GameTree, NodeKind};
+use crate::history::SolveHistory;
+use crate::metrics::SolverMetrics;
+use crate::persist::SolveSnapshot;
+use crate::pool::run_stealing;
+use crate::preflop::pushfold::{
+    push_fold_chart, push_fold_chart_with, PushFoldChart, PushFoldGame,
+};
+use crate::progress::SolveProgress;
+use crate::rng::{fnv1a, seed_from_fingerprint, SolverRng};
+use crate::solver_proto::{
+    AbstractionInfo, AbstractionInfoRequest, ActionFrequencyDelta, ActionKind as ActionKindReport,
+    ActionProb, ActionRemoval, ActionRemovalReport, ActionRemovalRequest, BenchmarkCase,
+    BenchmarkReport, BenchmarkRequest, BetDiagnostics, BetSizeSearchReport, BetSizeSearchRequest,
+    BoardResult, BucketQuality, DeepeningStats, GameState, HandEvaluation, HandEvaluationReport,
+    HandEvaluationRequest, HandStrategy, LegalizationNote, MemoryUsage, NodeFrequencyDiff,
+    NodeLock, OpponentModel as OpponentModelSpec, PushFoldHand, PushFoldReport,
+    RefinementStats as RefinementStatsReport, RunoutSummary,
+    SizeEvaluation as SizeEvaluationReport, StackSweepPoint, StackSweepReport, StackSweepRequest,
+    StrategyConstraint as StrategyConstraintSpec, StrategyDiffReport, StreetAbstraction,
+    SubgameRequest, SubgameResponse, TreeEstimate, TreeExport, TreeExportRequest, VillainResponse,
+    WarmStart, WeightedBoard,
+};
+use crate::tournament::bounty::{tournament_payoffs, BountyModel};
+use crate::tournament::icm::IcmModel;
+use crate::validation::{validate_request, RequestIssue};
+use prost::Message;
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::Instant;
+use tracing::{info_span, warn};
 
-pub struct SolverEngine;
+pub struct SolverEngine {
+    metrics: Arc<SolverMetrics>,
+    config: SolverConfig,
+    cache: SolveCache,
+    history: Option<Arc<SolveHistory>>,
+    archive: Option<Arc<StrategyArchive>>,
+    // Solves in flight, so shutdown can cancel them; set once it has.
+    running: Mutex<Vec<Weak<SolveProgress>>>,
+    stopping: AtomicBool,
+}
 
 impl SolverEngine {
     pub fn new() -> Self {
-        Self
+        Self::with_metrics(Arc::new(SolverMetrics::new()))
+    }
+
+    pub fn with_metrics(metrics: Arc<SolverMetrics>) -> Self {
+        Self::from_config(SolverConfig::default(), metrics)
+    }
+
+    pub fn from_config(config: SolverConfig, metrics: Arc<SolverMetrics>) -> Self {
+        let cache = SolveCache::new(config.cache.max_entries, config.cache.max_bytes);
+        Self {
+            metrics,
+            config,
+            cache,
+            history: None,
+            archive: None,
+            running: Mutex::default(),
+            stopping: AtomicBool::new(false),
+        }
+    }
+
+    // Records every solve from now on to `history`.
+    pub fn with_history(mut self, history: SolveHistory) -> Self {
+        self.history = Some(Arc::new(history));
+        self
+    }
+
+    pub fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+
+    pub fn metrics(&self) -> &Arc<SolverMetrics> {
+        &self.metrics
+    }
+
+    // Uploads the solved tree of every solve big enough to `archive`.
+    pub fn with_archive(mut self, archive: StrategyArchive) -> Self {
+        self.archive = Some(Arc::new(archive));
+        self
+    }
+
+    pub fn archive(&self) -> Option<&Arc<StrategyArchive>> {
+        self.archive.as_ref()
+    }
+
+    pub fn history(&self) -> Option<&Arc<SolveHistory>> {
+        self.history.as_ref()
+    }
+
+    pub fn cache(&self) -> &SolveCache {
+        &self.cache
+    }
+
+    // Cancels every solve in flight and every one started from now on: each stops after its
+    // current iteration and answers with what it has. Returns how many were still running.
+    pub fn cancel_running(&self) -> usize {
+        self.stopping.store(true, Ordering::SeqCst);
+        let running = self.running();
+        running
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter(|progress| !progress.is_cancelled())
+            .inspect(|progress| progress.cancel())
+            .count()
+    }
+
+    fn track(&self, progress: &Arc<SolveProgress>) {
+        let mut running = self.running();
+        running.retain(|solve| solve.strong_count() > 0);
+        running.push(Arc::downgrade(progress));
+        if self.stopping.load(Ordering::SeqCst) {
+            progress.cancel();
+        }
+    }
+
+    fn untrack(&self, progress: &Arc<SolveProgress>) {
+        self.running()
+            .retain(|solve| solve.strong_count() > 0 && solve.as_ptr() != Arc::as_ptr(progress));
+    }
+
+    fn running(&self) -> MutexGuard<'_, Vec<Weak<SolveProgress>>> {
+        self.running
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn is_cached(&self, request: &SubgameRequest) -> bool {
+        self.cache.contains(request)
+    }
+
+    // Hex digest of what determines the answer: the request with its effective seed, less the
+    // fields that only schedule it, and the server's solve settings. Debug output of the settings
+    // is only stable within a build, which `SOLVER_VERSION` identifies alongside it.
+    pub fn request_hash(&self, request: &SubgameRequest) -> String {
+        let mut effective = request.clone();
+        effective.seed = effective_seed(request);
+        effective.priority.clear();
+        if let Some(config) = effective.config.as_mut() {
+            config.skip_cache = false;
+        }
+        let mut bytes = effective.encode_to_vec();
+        let settings = &self.config;
+        bytes.extend_from_slice(
+            format!(
+                "{:?}{:?}{:?}{:?}{:?}",
+                settings.cfr,
+                settings.abstraction,
+                settings.realization,
+                settings.push_fold,
+                settings.limits
+            )
+            .as_bytes(),
+        );
+        format!("{:016x}", fnv1a(&bytes))
+    }
+
+    pub fn validate(&self, request: &SubgameRequest) -> Vec<RequestIssue> {
+        validate_request(request)
     }
 
-    pub fn solve(&self, request: &SubgameRequest) -> SubgameResponse {
+    pub fn solve(&self, request: &SubgameRequest) -> Result<SubgameResponse, SolverError> {
+        self.solve_with_progress(request, &Arc::default())
+    }
+
+    // Like `solve`, reporting iterations to `progress` as they run.
+    pub fn solve_with_progress(
+        &self,
+        request: &SubgameRequest,
+        progress: &Arc<SolveProgress>,
+    ) -> Result<SubgameResponse, SolverError> {
+        let span = info_span!(
+            "solve",
+            state_fingerprint = %request.state_fingerprint,
+            budget_ms = request.budget_ms,
+        );
+        let _guard = span.enter();
+        let started = Instant::now();
+        if let Some(mut response) = self.cached(request) {
+            response.source = "cache".to_string();
+            response.compute_time_ms = started.elapsed().as_millis() as i32;
+            let result = Ok(without_trunk(request, response));
+            self.record_history(request, &result);
+            return result;
+        }
+        progress.start(BudgetClock::new(request.budget_ms).deadline());
+        self.track(progress);
+        let result = if request.runout_report {
+            self.run_with_runouts(request, progress)
+        } else if let Some(spot) = self.push_fold_spot(request) {
+            self.run_push_fold(request, &spot)
+        } else if request.boards.is_empty() && request.representative_flops == 0 {
+            // Cached single-board solves keep every bucket's root strategy, the trunk later
+            // resolves of the spot are held to.
+            let solved = if self.cache.is_enabled() && !request.include_per_hand {
+                Cow::Owned(SubgameRequest {
+                    include_per_hand: true,
+                    ..request.clone()
+                })
+            } else {
+                Cow::Borrowed(request)
+            };
+            if request
+                .config
+                .as_ref()
+                .is_some_and(|config| config.resolve_from_blueprint)
+            {
+                self.run_blueprint_resolve(&solved, progress)
+            } else {
+                self.run_solve(&solved, progress)
+            }
+        } else {
+            self.run_multi_board(request, progress)
+        };
+        self.untrack(progress);
+        let result = result.map(|mut response| {
+            round_response(&mut response, &frequency_rounding(request));
+            // A blueprint answer keeps the stamp of the solve that produced it.
+            if response.request_hash.is_empty() {
+                response.request_hash = self.request_hash(request);
+                response.solver_version = SOLVER_VERSION.to_string();
+            }
+            response
+        });
+        let result = match result {
+            Err(error) if self.config.limits.coarsen && degrades(&error) => {
+                self.blueprint(request).ok_or(error)
+            }
+            result => result,
+        };
+        self.metrics.record_solve(result.is_ok(), started.elapsed());
+        match &result {
+            Err(SolverError::BudgetExhausted { .. }) => {
+                self.metrics.record_budget_exhausted();
+                warn!(reason = "BUDGET_EXHAUSTED", "solve rejected");
+            }
+            Err(error) => warn!(reason = error.reason(), "solve rejected: {}", error),
+            // A cancelled solve stopped short of what was asked for, and a blueprint answered
+            // a different one.
+            Ok(response) if !progress.is_cancelled() && response.source != "blueprint" => {
+                self.cache.insert(request, response)
+            }
+            Ok(_) => {}
+        }
+        let result = result.map(|response| without_trunk(request, response));
+        self.record_history(request, &result);
+        result
+    }
+
+    // A solve that can't be recorded still answers its caller.
+    pub fn record_history(
+        &self,
+        request: &SubgameRequest,
+        result: &Result<SubgameResponse, SolverError>,
+    ) {
+        if let Some(history) = &self.history {
+            if let Err(error) = history.record(request, result) {
+                warn!("solve not recorded: {}", error);
+            }
+        }
+    }
+
+    fn cached(&self, request: &SubgameRequest) -> Option<SubgameResponse> {
+        let skip = request
+            .config
+            .as_ref()
+            .is_some_and(|config| config.skip_cache);
+        if !self.cache.is_enabled() || skip || !has_budget(request) {
+            return None;
+        }
+        let response = self.cache.get(request);
+        self.metrics.record_cache_lookup(response.is_some());
+        response
+    }
+
+    // The bottom of the degradation ladder: a cached solve of the spot under other settings.
+    fn blueprint(&self, request: &SubgameRequest) -> Option<SubgameResponse> {
+        self.cache.blueprint(request).map(answer_with_blueprint)
+    }
+
+    // Blueprint plus real-time resolve. The latest cached solve of the spot, under any
+    // abstraction, is the trunk: hero's root strategy, bucket by bucket, and with it the ranges
+    // reaching each root action. The subgame below the root (villain's replies and any
+    // re-raises) is then solved again under the request's own tree with the root held to the
+    // trunk, so the answer stitches the blueprint's root frequencies to the resolved subgame's
+    // EVs, replies and diagnostics, as "blueprint+resolve". When the resolve can't fit the budget
+    // the blueprint answers on its own, and with no blueprint trunk yet this is a plain solve.
+    fn run_blueprint_resolve(
+        &self,
+        request: &SubgameRequest,
+        progress: &Arc<SolveProgress>,
+    ) -> Result<SubgameResponse, SolverError> {
+        let Some(blueprint) = self
+            .cache
+            .blueprint(request)
+            .filter(|blueprint| !blueprint.per_hand_strategies.is_empty())
+        else {
+            return self.run_solve(request, progress);
+        };
+        let resolved = info_span!("resolve")
+            .in_scope(|| self.solve_subgame(request, progress, false, Some(&blueprint)));
+        match resolved {
+            Ok((mut response, _)) => {
+                response.source = RESOLVED_SOURCE.to_string();
+                Ok(response)
+            }
+            Err(error) if degrades(&error) => Ok(answer_with_blueprint(blueprint)),
+            Err(error) => Err(error),
+        }
+    }
+
+    // Short-stacked preflop spots with push/fold on. Anything the equilibrium doesn't model (a
+    // board, fixed limit, short deck, locks or an opponent model) still gets a tree.
+    fn push_fold_spot(&self, request: &SubgameRequest) -> Option<PushFoldSpot> {
+        let enabled = self.config.push_fold.enabled
+            || request
+                .config
+                .as_ref()
+                .is_some_and(|config| config.push_fold);
+        if !enabled
+            || !request.board.is_empty()
+            || !request.locks.is_empty()
+            || request.opponent.is_some()
+        {
+            return None;
+        }
+        let summary = parse_game_state(request).ok()?;
+        if summary.street_kind() != Some(Street::Preflop)
+            || summary.game_variant() != GameVariant::Holdem
+            || summary.limit.is_some()
+        {
+            return None;
+        }
+        let stack_bb = summary
+            .hero_effective_stack_bb()
+            .unwrap_or(request.effective_stack_bb as f64);
+        if !(stack_bb > 0.0 && stack_bb <= self.config.push_fold.max_stack_bb) {
+            return None;
+        }
+        let big_blind = summary.chips_per_bb();
+        Some(PushFoldSpot {
+            game: PushFoldGame {
+                stack_bb,
+                small_blind_bb: if summary.blinds.small > 0.0 {
+                    summary.blinds.small / big_blind
+                } else {
+                    0.5
+                },
+                dead_bb: summary.blinds.dead_money(summary.seated_players()) / big_blind,
+            },
+            facing_jam: summary.facing_bet_bb() > summary.min_bet_bb(),
+            chips_per_bb: big_blind,
+        })
+    }
+
+    // No tree: hero's side of the push/fold equilibrium, over the whole range.
+    fn run_push_fold(
+        &self,
+        request: &SubgameRequest,
+        spot: &PushFoldSpot,
+    ) -> Result<SubgameResponse, SolverError> {
+        // The only actions are all-in, call and fold, so no tokens are needed.
+        let blocking: Vec<RequestIssue> = self
+            .validate(request)
+            .into_iter()
+            .filter(|issue| issue.blocking && issue.reason != "EMPTY_ACTION_SET")
+            .collect();
+        if !blocking.is_empty() {
+            return Err(SolverError::InvalidRequest(blocking));
+        }
         let clock = BudgetClock::new(request.budget_ms);
-        let summary = parse_game_state(&request.game_state_json);
-        let action_specs = parse_action_set(
-            &request.action_set,
-            &summary,
-            request.effective_stack_bb as f64,
+        let options = self.config.push_fold.options();
+        // In tournament equity, bounties included, when the request asks for dollar EV.
+        let tournament = request
+            .tournament
+            .as_ref()
+            .filter(|_| request.solve_dollar_ev)
+            .and_then(|spec| Some((IcmModel::from_spec(spec)?, BountyModel::from_spec(spec))));
+        let chart =
+            info_span!("push_fold", stack_bb = spot.game.stack_bb).in_scope(|| match &tournament {
+                Some((model, bounties)) => push_fold_chart_with(&spot.game, &options, |game| {
+                    tournament_payoffs(
+                        game,
+                        model,
+                        bounties.as_ref(),
+                        spot.chips_per_bb,
+                        !spot.facing_jam,
+                    )
+                }),
+                None => push_fold_chart(&spot.game, &options),
+            });
+        let label = if spot.facing_jam { "call" } else { "all-in" };
+        let taken = |hand: &PushFoldHand| {
+            if spot.facing_jam {
+                (hand.call, hand.call_ev)
+            } else {
+                (hand.jam, hand.jam_ev)
+            }
+        };
+        let hands = push_fold_hands(&chart);
+        let combos: f64 = hands.iter().map(|hand| hand.combos as f64).sum();
+        let (share, ev) = hands.iter().fold((0.0, 0.0), |(share, ev), hand| {
+            let (frequency, value) = taken(hand);
+            let weight = hand.combos as f64 * frequency;
+            (share + weight, ev + weight * value)
+        });
+        let frequency = share / combos.max(1.0);
+        // Folding is worth 0; the other action's EV is averaged over the hands taking it.
+        let pot_bb = spot.game.small_blind_bb + 1.0 + spot.game.dead_bb;
+        let actions = vec![
+            ActionProb {
+                action_type: "fold".to_string(),
+                kind: ActionKindReport::Fold as i32,
+                frequency: 1.0 - frequency,
+                ..Default::default()
+            },
+            ActionProb {
+                action_type: label.to_string(),
+                amount: spot.game.stack_bb,
+                kind: if spot.facing_jam {
+                    ActionKindReport::Call
+                } else {
+                    ActionKindReport::AllIn
+                } as i32,
+                pot_fraction: pot_fraction(spot.game.stack_bb, pot_bb),
+                frequency,
+                ev: if share > 0.0 { ev / share } else { 0.0 },
+                ..Default::default()
+            },
+        ];
+        Ok(SubgameResponse {
+            actions,
+            exploitability: chart.exploitability / chart.pot.max(f64::EPSILON),
+            compute_time_ms: clock.elapsed_millis() as i32,
+            source: PUSH_FOLD_SOURCE.to_string(),
+            iterations: chart.iterations as u64,
+            push_fold: Some(PushFoldReport {
+                stack_bb: chart.stack_bb,
+                iterations: chart.iterations as u32,
+                exploitability: chart.exploitability,
+                hands,
+            }),
+            ..Default::default()
+        })
+    }
+
+    // Solves the flop or turn, then every next card as a multi-board solve, and summarizes the
+    // next cards by class: hero's plan for each runout.
+    fn run_with_runouts(
+        &self,
+        request: &SubgameRequest,
+        progress: &Arc<SolveProgress>,
+    ) -> Result<SubgameResponse, SolverError> {
+        let clock = BudgetClock::new(request.budget_ms);
+        let board = validate_board(&request.board, &request.hole_cards, None)?;
+        let next_street = match board.len() {
+            3 => Street::Turn,
+            4 => Street::River,
+            found => {
+                return Err(CardError::StreetMismatch {
+                    street: Street::Turn,
+                    expected: 4,
+                    found,
+                }
+                .into())
+            }
+        };
+        let half = split_budget(request.budget_ms, 2);
+        progress.add_parts(1);
+        let mut response = self.run_solve(
+            &SubgameRequest {
+                budget_ms: half,
+                runout_report: false,
+                ..request.clone()
+            },
+            progress,
+        )?;
+
+        let dead = parse_cards(&request.hole_cards)?;
+        let variant = parse_game_state(request)?.game_variant();
+        let boards = river_cards(&board, &dead, variant)
+            .into_iter()
+            .map(|next| WeightedBoard {
+                cards: board
+                    .iter()
+                    .chain(std::iter::once(&next))
+                    .map(ToString::to_string)
+                    .collect(),
+                weight: 1.0,
+            })
+            .collect();
+        let next_cards = self.run_multi_board(
+            &SubgameRequest {
+                // The next street opens with nobody facing a bet.
+                game_state: request.game_state.clone().map(|state| GameState {
+                    street: next_street.as_str().to_string(),
+                    facing_bet: None,
+                    aggressor_seat: None,
+                    ..state
+                }),
+                game_state_json: on_street(&request.game_state_json, next_street)?,
+                budget_ms: if request.budget_ms > 0 {
+                    (request.budget_ms - half).max(1)
+                } else {
+                    request.budget_ms
+                },
+                boards,
+                representative_flops: 0,
+                runout_report: false,
+                ..request.clone()
+            },
+            progress,
+        )?;
+        response.runouts = summarize_runouts(&board, next_cards.boards);
+        response.iterations += next_cards.iterations;
+        response.budget_exhausted |= next_cards.budget_exhausted;
+        response.compute_time_ms = clock.elapsed_millis() as i32;
+        Ok(response)
+    }
+
+    // Solves every board as its own request on a pool of `threads.solver` workers. Each board's
+    // share of the budget follows its estimated tree size, so the pool finishes together.
+    fn run_multi_board(
+        &self,
+        request: &SubgameRequest,
+        progress: &Arc<SolveProgress>,
+    ) -> Result<SubgameResponse, SolverError> {
+        if !has_budget(request) {
+            return Err(SolverError::BudgetExhausted {
+                budget_ms: request.budget_ms,
+            });
+        }
+        let clock = BudgetClock::new(request.budget_ms);
+        let boards = board_set(request)?;
+        progress.add_parts(boards.len());
+        let workers = self.board_workers().min(boards.len()).max(1);
+        let tasks: Vec<_> = boards
+            .into_iter()
+            .map(|(cards, weight)| {
+                let work = self.estimated_work(&SubgameRequest {
+                    board: cards.clone(),
+                    ..request.clone()
+                });
+                ((cards, weight, work), work)
+            })
+            .collect();
+        let total_work: f64 = tasks.iter().map(|(_, work)| work).sum();
+        // Worker threads don't inherit the current span.
+        let parent = tracing::Span::current();
+        let solved = run_stealing(tasks, workers, |(cards, weight, work)| {
+            let single = SubgameRequest {
+                board: cards.clone(),
+                boards: Vec::new(),
+                representative_flops: 0,
+                budget_ms: board_budget(request.budget_ms, workers, work, total_work),
+                ..request.clone()
+            };
+            let response = parent.in_scope(|| {
+                info_span!("board", cards = %cards.join(""))
+                    .in_scope(|| self.run_solve(&single, progress))
+            });
+            (cards, weight, response)
+        });
+        let mut aggregate: Option<SubgameResponse> = None;
+        let mut results = Vec::with_capacity(solved.len());
+        let mut peaks = Vec::with_capacity(solved.len());
+        for (cards, weight, response) in solved {
+            let response = response?;
+            peaks.push(
+                response
+                    .memory
+                    .as_ref()
+                    .map_or(0, |memory| memory.peak_bytes),
+            );
+            let total = aggregate.get_or_insert_with(|| zeroed(&response));
+            blend_response(total, &response, weight);
+            total.iterations += response.iterations;
+            results.push(BoardResult {
+                cards,
+                weight,
+                actions: response.actions,
+                exploitability: response.exploitability,
+                archive_key: response.archive_key,
+            });
+        }
+        let mut response = aggregate.ok_or(SolverError::EmptyActionSet)?;
+        response.compute_time_ms = clock.elapsed_millis() as i32;
+        if let Some(memory) = response.memory.as_mut() {
+            peaks.sort_unstable_by(|a, b| b.cmp(a));
+            memory.peak_bytes = peaks.iter().take(workers).sum();
+        }
+        response.boards = results;
+        Ok(response)
+    }
+
+    fn run_solve(
+        &self,
+        request: &SubgameRequest,
+        progress: &Arc<SolveProgress>,
+    ) -> Result<SubgameResponse, SolverError> {
+        let (mut response, snapshot) = self.solve_spot(request, progress, false)?;
+        // A cancelled solve stopped short of what was asked for.
+        if let (Some(archive), Some(snapshot)) = (&self.archive, snapshot) {
+            if !progress.is_cancelled() {
+                response.archive_key = archive.submit(snapshot).unwrap_or_default();
+            }
+        }
+        Ok(response)
+    }
+
+    // Solves the request's own board, keeping the solved tree when asked to or when the archive
+    // wants it.
+    fn solve_spot(
+        &self,
+        request: &SubgameRequest,
+        progress: &Arc<SolveProgress>,
+        keep_snapshot: bool,
+    ) -> Result<(SubgameResponse, Option<Box<SolveSnapshot>>), SolverError> {
+        self.solve_subgame(request, progress, keep_snapshot, None)
+    }
+
+    // `solve_spot`, with hero's root held to `trunk`'s per-bucket strategies when given.
+    fn solve_subgame(
+        &self,
+        request: &SubgameRequest,
+        progress: &Arc<SolveProgress>,
+        keep_snapshot: bool,
+        trunk: Option<&SubgameResponse>,
+    ) -> Result<(SubgameResponse, Option<Box<SolveSnapshot>>), SolverError> {
+        if !has_budget(request) {
+            return Err(SolverError::BudgetExhausted {
+                budget_ms: request.budget_ms,
+            });
+        }
+        let clock = BudgetClock::with_deadlines(
+            request.budget_ms,
+            self.config.limits.soft_deadline_share,
+            self.config.limits.response_slack_ms,
         );
+        let spot = info_span!("parse").in_scope(|| self.prepare(request, true))?;
+        let PreparedSpot {
+            summary,
+            context,
+            action_specs,
+            effective_stack_bb,
+        } = spot;
 
-        if action_specs.is_empty() {
-            return SubgameResponse {
-                actions: vec![],
-                exploitability: 0.0,
-                compute_time_ms: clock.elapsed_millis() as i32,
-                source: "subgame".to_string(),
+        let exact = match request.config.as_ref().filter(|config| config.exact_river) {
+            Some(_) => {
+                let board = parse_cards(&request.board)?;
+                let exact = info_span!("exact_river")
+                    .in_scope(|| exact_river(&board, summary.game_variant()))
+                    .ok_or(CardError::StreetMismatch {
+                        street: Street::River,
+                        expected: 5,
+                        found: board.len(),
+                    })?;
+                Some(exact)
+            }
+            None => None,
+        };
+        let mut limits = self.config.tree_limits();
+        if request.budget_iterations == 0 {
+            limits = limits.with_time_budget(
+                request.budget_ms,
+                self.config.limits.min_iterations,
+                self.config.limits.info_sets_per_ms,
+            );
+        }
+        // Coarsening would bring the abstraction back; too big a tree fails instead.
+        limits.coarsen &= exact.is_none();
+        let fitted = info_span!("build_tree", actions = action_specs.len()).in_scope(|| {
+            fit_tree(
+                &action_specs,
+                &summary,
+                effective_stack_bb,
+                &context,
+                &rake_model(request),
+                exact
+                    .as_ref()
+                    .map_or(self.config.abstraction.buckets, |exact| exact.combos.len()),
+                &limits,
+            )
+        })?;
+        // Node-by-node locks and opponent models only line up with the fitted tree.
+        let deepen = (self.config.limits.deepening
+            || request
+                .config
+                .as_ref()
+                .is_some_and(|config| config.iterative_deepening))
+            && request.locks.is_empty()
+            && request.opponent.is_none()
+            && trunk.is_none();
+        let (mut passes, mut next_street) = if deepen {
+            (
+                shallower_trees(&fitted, effective_stack_bb, &rake_model(request)),
+                next_street_tree(&fitted, &limits),
+            )
+        } else {
+            (Vec::new(), None)
+        };
+        let mut tree = fitted.tree;
+        if let Some(config) = request
+            .config
+            .as_ref()
+            .filter(|config| config.integer_chips)
+        {
+            let units_per_bb = summary.chips_per_bb() / config.chip_increment.max(1) as f64;
+            tree = tree.with_integer_chips(units_per_bb);
+            passes = passes
+                .into_iter()
+                .map(|pass| pass.with_integer_chips(units_per_bb))
+                .collect();
+            next_street = next_street.map(|deeper| deeper.with_integer_chips(units_per_bb));
+        }
+
+        let iterations = match request.budget_iterations {
+            0 => determine_iterations(request.budget_ms, tree.actions.len()),
+            limit => limit as usize,
+        };
+        let mut config = cfr_config(request, iterations, &self.config);
+        // Timed solves aim for the soft deadline; deepening stops there with the deepest tree it
+        // got to.
+        config.deadline = clock.deadline();
+        config.soft_deadline = clock.soft_deadline();
+        config.warm_start = request
+            .warm_start
+            .as_ref()
+            .and_then(|warm_start| warm_start_prior(&tree, warm_start));
+        config.target_exploitability = request.target_exploitability.max(0.0);
+        config.seed = effective_seed(request);
+        config.buckets = fitted.buckets;
+        config.opponent = request
+            .opponent
+            .as_ref()
+            .and_then(|opponent| opponent_model(&tree, fitted.buckets, opponent));
+        let held = trunk.map_or_else(NodeLocks::default, |trunk| {
+            trunk_locks(&tree, fitted.buckets, trunk)
+        });
+        config.locks = node_locks(&tree, fitted.buckets, &request.locks, held);
+        config.constraints = root_constraints(&tree, &request.constraints);
+        if self.config.abstraction.card_removal
+            || request
+                .config
+                .as_ref()
+                .is_some_and(|config| config.card_removal)
+        {
+            let board = parse_cards(&request.board)?;
+            config.showdowns = info_span!("card_removal").in_scope(|| {
+                card_removal(&board, fitted.buckets, summary.game_variant()).map(Arc::new)
+            });
+        }
+        if self.config.realization.enabled
+            || request
+                .config
+                .as_ref()
+                .is_some_and(|config| config.equity_realization)
+        {
+            let board = parse_cards(&request.board)?;
+            let realization = self.config.realization.realization();
+            let mut rng = SolverRng::seeded(config.seed);
+            let realized = info_span!("equity_realization").in_scope(|| {
+                realized_showdowns(
+                    &board,
+                    fitted.buckets,
+                    summary.game_variant(),
+                    &realization,
+                    summary.hero_has_position(),
+                    &mut rng,
+                )
+            });
+            if let Some(realized) = realized {
+                config.showdowns = Some(Arc::new(realized));
+            }
+        }
+        let runouts = self.config.abstraction.all_in_runouts;
+        let all_in =
+            |tree: &GameTree| (0..tree.nodes.len()).any(|node| tree.is_all_in_showdown(node));
+        if runouts > 0 && (all_in(&tree) || next_street.as_ref().is_some_and(all_in)) {
+            let board = parse_cards(&request.board)?;
+            let mut rng = SolverRng::seeded(config.seed);
+            // Dealing runouts may take half the time left before the soft deadline; CFR gets
+            // the rest.
+            let deadline = clock.soft_deadline().map(|soft| {
+                let now = Instant::now();
+                now + soft.saturating_duration_since(now) / 2
+            });
+            config.all_in_showdowns = info_span!("all_in_equity").in_scope(|| {
+                budgeted_all_in_showdowns(
+                    &board,
+                    fitted.buckets,
+                    summary.game_variant(),
+                    runouts,
+                    deadline,
+                    &mut rng,
+                )
+            });
+        }
+        if let Some(exact) = &exact {
+            config.showdowns = Some(Arc::new(exact.showdowns.clone()));
+        }
+        config.progress = Some(progress.clone());
+        config.keep_snapshot = keep_snapshot
+            || self
+                .archive
+                .as_ref()
+                .is_some_and(|archive| archive.wants(tree.nodes.len()));
+        let cfr_span = info_span!(
+            "cfr",
+            nodes = tree.nodes.len(),
+            iterations = tracing::field::Empty,
+            exploitability = tracing::field::Empty,
+        );
+        passes.push(tree);
+        let fitted_depth = passes.len() - 1;
+        passes.extend(next_street);
+        let (depth, outcome) = cfr_span.in_scope(|| {
+            let solve_started = Instant::now();
+            let (depth, outcome) = run_cfr_deepening(&passes, &config);
+            self.metrics
+                .record_iterations(outcome.iterations, solve_started.elapsed());
+            (depth, outcome)
+        });
+        let depths = passes.len();
+        let tree = passes.swap_remove(depth);
+        let level = if depth < fitted_depth {
+            fitted.level.max(DegradationLevel::ShallowerTree)
+        } else {
+            fitted.level
+        };
+        cfr_span.record("iterations", outcome.iterations);
+        cfr_span.record("exploitability", outcome.exploitability);
+        let budget_exhausted = !outcome.converged && clock.out_of_time();
+        if budget_exhausted {
+            self.metrics.record_budget_exhausted();
+        }
+
+        let matrix = match outcome.strategies.node(0) {
+            Some(root) if request.include_matrix => {
+                let board = parse_cards(&request.board)?;
+                let strategy: Vec<Vec<f64>> =
+                    (0..root.buckets).map(|bucket| root.row(bucket)).collect();
+                let actions = outcome
+                    .actions
+                    .iter()
+                    .map(|stat| stat.label.clone())
+                    .collect();
+                let mut rng = SolverRng::seeded(config.seed);
+                info_span!("strategy_matrix").in_scope(|| {
+                    matrix_json(&strategy_matrix(
+                        &board,
+                        summary.game_variant(),
+                        actions,
+                        &strategy,
+                        QualityOptions::default().runouts,
+                        &mut rng,
+                    ))
+                })
+            }
+            _ => String::new(),
+        };
+        let snapshot = outcome.snapshot.map(|mut snapshot| {
+            snapshot.meta.state_fingerprint = request.state_fingerprint.clone();
+            snapshot
+        });
+        info_span!("build_response").in_scope(|| {
+            let mut stats = outcome.actions;
+            let mut hands = outcome.hands;
+            let purification = purification(request);
+            if purification != Purification::None {
+                purify_hands(&mut hands, purification);
+                stats = range_stats(&tree.actions, &hands);
+            }
+            let mut hands = if request.include_per_hand {
+                hands
+            } else {
+                Vec::new()
             };
+            if request.solve_dollar_ev {
+                if let Some(model) = request.tournament.as_ref().and_then(IcmModel::from_spec) {
+                    let chips_per_bb = summary.chips_per_bb();
+                    let to_dollars = |ev: f64| model.equity_delta(ev * chips_per_bb);
+                    for stat in &mut stats {
+                        stat.ev = to_dollars(stat.ev);
+                    }
+                    for hand in &mut hands {
+                        hand.ev = to_dollars(hand.ev);
+                        for stat in &mut hand.actions {
+                            stat.ev = to_dollars(stat.ev);
+                        }
+                    }
+                }
+            }
+            let mut response =
+                build_response(stats, &tree.legalization, &clock, outcome.exploitability);
+            let threshold = self.config.cfr.converged_exploitability;
+            response.converged = outcome.converged
+                || (config.target_exploitability <= 0.0
+                    && threshold > 0.0
+                    && outcome.exploitability <= threshold);
+            response.iterations = outcome.iterations as u64;
+            response.cfr_variant = config.variant.as_str().to_string();
+            response.budget_exhausted = budget_exhausted;
+            response.seed = config.seed;
+            response.buckets = config.buckets as u32;
+            response.strategy_matrix_json = matrix;
+            response.constraint_cost_bb = outcome.constraint_cost;
+            response.memory = Some(memory_usage(&outcome.memory));
+            response.degradation = level.as_str().to_string();
+            response.deepening = (depths > 1).then(|| DeepeningStats {
+                passes: depth as u32 + 1,
+                depths: depths as u32,
+                next_street: depth > fitted_depth,
+            });
+            response.refinement = outcome.refinement.as_ref().map(refinement_stats);
+            response.diagnostics = outcome
+                .diagnostics
+                .into_iter()
+                .map(|bet| BetDiagnostics {
+                    action_type: bet.label,
+                    pot_odds: bet.pot_odds,
+                    required_defense: bet.required_defense,
+                    defense_frequency: bet.defense_frequency,
+                    bluff_to_value: bet.bluff_to_value,
+                    indifferent_bluff_to_value: bet.indifferent_bluff_to_value,
+                    value_share: bet.value_share,
+                    merged_share: bet.merged_share,
+                    bluff_share: bet.bluff_share,
+                })
+                .collect();
+            if request.response_depth >= 2 {
+                for (action, replies) in response.actions.iter_mut().zip(&outcome.responses) {
+                    action.responses = replies
+                        .iter()
+                        .map(|reply| VillainResponse {
+                            action_type: reply.label.clone(),
+                            frequency: reply.frequency,
+                            kind: ActionKind::parse(&reply.label)
+                                .map_or(ActionKindReport::Unspecified, action_kind)
+                                as i32,
+                        })
+                        .collect();
+                }
+            }
+            response.per_hand_strategies = hands
+                .into_iter()
+                .map(|hand| HandStrategy {
+                    hand: exact
+                        .as_ref()
+                        .and_then(|exact| exact.combos.get(hand.bucket))
+                        .map(|combo| format!("{}{}", combo[0], combo[1]))
+                        .unwrap_or_default(),
+                    bucket: hand.bucket as u32,
+                    actions: hand.actions.into_iter().map(action_prob).collect(),
+                    ev: hand.ev,
+                })
+                .collect();
+            let rounding = ChipRounding::new(
+                summary.chips_per_bb(),
+                request
+                    .config
+                    .as_ref()
+                    .map_or(0, |config| config.chip_increment),
+                effective_stack_bb,
+                context.min_legal_amount(),
+            );
+            let per_hand = response
+                .per_hand_strategies
+                .iter_mut()
+                .flat_map(|hand| hand.actions.iter_mut());
+            for action in response.actions.iter_mut().chain(per_hand) {
+                action.amount_chips = rounding.to_chips(action.amount);
+                action.pot_after_bb = tree.pot_bb + action.amount;
+                action.stack_after_bb = (tree.effective_stack_bb - action.amount).max(0.0);
+                action.spr_after = action.stack_after_bb / action.pot_after_bb;
+                action.pot_fraction = pot_fraction(action.amount, tree.pot_bb);
+            }
+            Ok((response, snapshot))
+        })
+    }
+
+    // Solves `request` and returns the solved tree with the response, e.g. to compare with
+    // another solve of the spot. Only the request's own board is solved: extra boards,
+    // representative flops and runout reports are ignored, and the cache is bypassed.
+    pub fn solve_snapshot(
+        &self,
+        request: &SubgameRequest,
+    ) -> Result<(SubgameResponse, Box<SolveSnapshot>), SolverError> {
+        let progress = Arc::default();
+        let (response, snapshot) = self.solve_spot(request, &progress, true)?;
+        let snapshot = snapshot.ok_or(SolverError::NoLegalActions)?;
+        Ok((response, snapshot))
+    }
+
+    // Solves both requests, one after the other, and compares their strategies.
+    pub fn diff(
+        &self,
+        baseline: &SubgameRequest,
+        candidate: &SubgameRequest,
+    ) -> Result<StrategyDiffReport, SolverError> {
+        let (baseline_response, baseline_tree) = self.solve_snapshot(baseline)?;
+        let (candidate_response, candidate_tree) = self.solve_snapshot(candidate)?;
+        let diff = diff_strategies(
+            &baseline_tree.tree,
+            &baseline_tree.strategies,
+            &candidate_tree.tree,
+            &candidate_tree.strategies,
+        );
+        let mut report = diff_report(&diff);
+        report.baseline = Some(baseline_response);
+        report.candidate = Some(candidate_response);
+        Ok(report)
+    }
+
+    // Solves the spot, then re-solves it once per root action with that action constrained to
+    // zero frequency, so villain's response and the rest of hero's range adjust to its absence.
+    pub fn action_removal(
+        &self,
+        request: &ActionRemovalRequest,
+    ) -> Result<ActionRemovalReport, SolverError> {
+        let spot = request.spot.clone().unwrap_or_default();
+        let progress = Arc::default();
+        let (response, _) = self.solve_spot(&spot, &progress, false)?;
+        let ev = range_ev(&response.actions);
+        let mut actions = Vec::with_capacity(response.actions.len());
+        // Removing the only action leaves nothing to solve.
+        if response.actions.len() > 1 {
+            for action in &response.actions {
+                let mut restricted = spot.clone();
+                restricted.constraints.push(StrategyConstraintSpec {
+                    actions: vec![action.action_type.clone()],
+                    buckets: Vec::new(),
+                    min_frequency: 0.0,
+                    max_frequency: Some(0.0),
+                });
+                let (without, _) = info_span!("remove_action", action = %action.action_type)
+                    .in_scope(|| self.solve_spot(&restricted, &progress, false))?;
+                let ev_without = range_ev(&without.actions);
+                actions.push(ActionRemoval {
+                    action_type: action.action_type.clone(),
+                    amount: action.amount,
+                    frequency: action.frequency,
+                    ev_without_bb: ev_without,
+                    ev_loss_bb: ev - ev_without,
+                });
+            }
         }
+        Ok(ActionRemovalReport {
+            ev_bb: ev,
+            actions,
+            solve: Some(response),
+        })
+    }
 
-        let tree = GameTree::from_action_specs(&action_specs, request.effective_stack_bb as f64);
-        let iterations = determine_iterations(request.budget_ms, tree.actions.len());
-        let stats = run_cfr(&tree, iterations);
-        let exploitability = (summary.pot / 1000.0).clamp(0.0, 0.5);
-        build_response(stats, &clock, exploitability)
+    // Golden-section search over the root bet size, one solve per size tried.
+    pub fn optimize_bet_size(
+        &self,
+        request: &BetSizeSearchRequest,
+    ) -> Result<BetSizeSearchReport, SolverError> {
+        let defaults = SizeSearch::default();
+        let search = SizeSearch {
+            min: request.min_pot_fraction.unwrap_or(defaults.min),
+            max: request.max_pot_fraction.unwrap_or(defaults.max),
+            tolerance: if request.tolerance > 0.0 {
+                request.tolerance
+            } else {
+                defaults.tolerance
+            },
+            max_evaluations: match request.max_evaluations {
+                0 => defaults.max_evaluations,
+                evaluations => evaluations as usize,
+            },
+        };
+        let spot = request.spot.clone().unwrap_or_default();
+        let progress = Arc::default();
+        let mut best: Option<(f64, SubgameResponse)> = None;
+        let evaluations = search_bet_size(&search, |fraction| {
+            let sized = SubgameRequest {
+                action_set: std::iter::once(format!("pot:{:.4}", fraction))
+                    .chain(request.keep.iter().cloned())
+                    .collect(),
+                street_action_sets: None,
+                tree_template: String::new(),
+                budget_ms: split_budget(spot.budget_ms, search.max_evaluations),
+                ..spot.clone()
+            };
+            let (response, _) = info_span!("size", pot_fraction = fraction)
+                .in_scope(|| self.solve_spot(&sized, &progress, false))?;
+            let ev = range_ev(&response.actions);
+            if !matches!(&best, Some((best_ev, _)) if *best_ev >= ev) {
+                best = Some((ev, response));
+            }
+            Ok::<_, SolverError>(ev)
+        })?;
+        let top = best_size(&evaluations).ok_or(SolverError::NoLegalActions)?;
+        Ok(BetSizeSearchReport {
+            best_pot_fraction: top.pot_fraction,
+            best_ev_bb: top.ev,
+            evaluations: evaluations
+                .iter()
+                .map(|evaluation| SizeEvaluationReport {
+                    pot_fraction: evaluation.pot_fraction,
+                    ev_bb: evaluation.ev,
+                })
+                .collect(),
+            solve: best.map(|(_, response)| response),
+        })
     }
+
+    // Solves the spot once per stack, each with an even share of the budget.
+    pub fn sweep_stacks(
+        &self,
+        request: &StackSweepRequest,
+    ) -> Result<StackSweepReport, SolverError> {
+        if let Some(index) = request.stacks_bb.iter().position(|stack| *stack <= 0) {
+            return Err(SolverError::InvalidRequest(vec![RequestIssue {
+                field: format!("stacks_bb[{}]", index),
+                reason: "INVALID_STACK",
+                message: "stacks must be positive".to_string(),
+                blocking: true,
+            }]));
+        }
+        let spot = request.spot.clone().unwrap_or_default();
+        let budget_ms = split_budget(spot.budget_ms, request.stacks_bb.len());
+        let points = request
+            .stacks_bb
+            .iter()
+            .map(|stack| {
+                let deep = SubgameRequest {
+                    effective_stack_bb: *stack,
+                    budget_ms,
+                    ..spot.clone()
+                };
+                let solve = info_span!("stack", effective_stack_bb = stack)
+                    .in_scope(|| self.solve(&deep))?;
+                Ok(StackSweepPoint {
+                    effective_stack_bb: *stack,
+                    solve: Some(solve),
+                })
+            })
+            .collect::<Result<_, SolverError>>()?;
+        Ok(StackSweepReport { points })
+    }
+
+    // Times the benchmark suite with the configured CFR settings, the variant aside if the
+    // request names one. Needs no spot, so nothing goes through the cache.
+    pub fn benchmark(&self, request: &BenchmarkRequest) -> Result<BenchmarkReport, SolverError> {
+        let iterations = match request.iterations {
+            0 => DEFAULT_BENCHMARK_ITERATIONS,
+            iterations => iterations as usize,
+        };
+        let mut config = cfr_config(&SubgameRequest::default(), iterations, &self.config);
+        if !request.cfr_variant.is_empty() {
+            config.variant = match CfrVariantKind::parse(&request.cfr_variant) {
+                Some(CfrVariantKind::Deep) => {
+                    return Err(SolverError::InvalidRequest(vec![RequestIssue::blocking(
+                        "cfr_variant",
+                        "UNSUPPORTED_VARIANT",
+                        "deep CFR trains networks rather than iterating the tree; benchmark a \
+                         tabular variant"
+                            .to_string(),
+                    )]))
+                }
+                Some(variant) => variant,
+                None => {
+                    return Err(SolverError::InvalidRequest(vec![RequestIssue::blocking(
+                        "cfr_variant",
+                        "UNKNOWN_VARIANT",
+                        format!("unknown cfr_variant '{}'", request.cfr_variant),
+                    )]))
+                }
+            };
+        }
+        let cases = info_span!("benchmark", variant = config.variant.as_str())
+            .in_scope(|| run_benchmark(&config));
+        let suite = suite_throughput(&cases);
+        Ok(BenchmarkReport {
+            iterations_per_sec: suite.iterations_per_sec,
+            traversals_per_sec: suite.traversals_per_sec,
+            nodes_per_sec: suite.nodes_per_sec,
+            cfr_variant: config.variant.as_str().to_string(),
+            backend: config.backend.as_str().to_string(),
+            cores: self.config.threads.core_count() as u32,
+            cases: cases
+                .iter()
+                .map(|case| {
+                    let throughput = case.throughput();
+                    BenchmarkCase {
+                        name: case.name.clone(),
+                        nodes: case.nodes as u32,
+                        buckets: case.buckets as u32,
+                        iterations: case.iterations as u32,
+                        elapsed_ms: case.elapsed.as_secs_f64() * 1000.0,
+                        iterations_per_sec: throughput.iterations_per_sec,
+                        traversals_per_sec: throughput.traversals_per_sec,
+                        nodes_per_sec: throughput.nodes_per_sec,
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    // Solves the spot, then scores the played hands against it with AIVAT.
+    pub fn evaluate_hands(
+        &self,
+        request: &HandEvaluationRequest,
+    ) -> Result<HandEvaluationReport, SolverError> {
+        let spot = request.spot.clone().unwrap_or_default();
+        let (response, snapshot) = self.solve_snapshot(&spot)?;
+        let mut config = CfrConfig::new(0);
+        config.buckets = snapshot.meta.buckets as usize;
+        let mut solver = CfrSolver::new(&snapshot.tree, &config);
+        for node in 0..snapshot.tree.nodes.len() {
+            if let Some(strategy) = snapshot.strategies.node(node) {
+                let rows: Vec<Vec<f64>> = (0..strategy.buckets)
+                    .map(|bucket| strategy.row(bucket))
+                    .collect();
+                solver.set_average_strategy(node, &rows);
+            }
+        }
+        let hands: Vec<PlayedHand> = request
+            .hands
+            .iter()
+            .map(|hand| PlayedHand {
+                line: hand.actions.clone(),
+                player: hand.player as usize,
+                bucket: hand.bucket as usize,
+                opponent_bucket: hand.opponent_bucket.map(|bucket| bucket as usize),
+                outcome: hand.outcome_bb,
+            })
+            .collect();
+        let report = evaluate_hands(&solver, &hands).map_err(|(index, error)| {
+            SolverError::InvalidRequest(vec![RequestIssue {
+                field: format!("hands[{}]", index),
+                reason: "INVALID_HAND",
+                message: error.to_string(),
+                blocking: true,
+            }])
+        })?;
+        Ok(HandEvaluationReport {
+            hands: report
+                .hands
+                .iter()
+                .map(|hand| HandEvaluation {
+                    outcome_bb: hand.outcome,
+                    estimate_bb: hand.estimate,
+                })
+                .collect(),
+            mean_outcome_bb: report.mean_outcome,
+            outcome_std_error: report.outcome_std_error,
+            mean_estimate_bb: report.mean_estimate,
+            estimate_std_error: report.estimate_std_error,
+            solve: Some(response),
+        })
+    }
+}
+
+fn range_ev(actions: &[ActionProb]) -> f64 {
+    actions
+        .iter()
+        .map(|action| action.frequency * action.ev)
+        .sum()
+}
+
+fn diff_report(diff: &StrategyDiff) -> StrategyDiffReport {
+    StrategyDiffReport {
+        nodes: diff
+            .nodes
+            .iter()
+            .map(|node| NodeFrequencyDiff {
+                path: node.path.clone(),
+                player: node.player as u32,
+                actions: node
+                    .actions
+                    .iter()
+                    .map(|action| ActionFrequencyDelta {
+                        action_type: action.label.clone(),
+                        baseline: action.baseline,
+                        candidate: action.candidate,
+                        delta: action.delta(),
+                    })
+                    .collect(),
+                total_variation: node.total_variation,
+                bucket_total_variation: node.bucket_total_variation.unwrap_or(-1.0),
+                reach: node.reach,
+            })
+            .collect(),
+        max_total_variation: diff.max_total_variation(),
+        weighted_total_variation: diff.weighted_total_variation(),
+        unmatched: diff.unmatched.clone(),
+        baseline: None,
+        candidate: None,
+    }
+}
+
+impl SolverEngine {
+    // Sizes the tree a solve of `request` would build, without running CFR. The budget is not
+    // needed for this and is not checked.
+    pub fn estimate(&self, request: &SubgameRequest) -> Result<TreeEstimate, SolverError> {
+        let spot = self.prepare(request, false)?;
+        let stack = spot.effective_stack_bb;
+        let buckets = self.config.abstraction.buckets;
+        let tree = GameTree::build(&spot.action_specs, &spot.summary, stack, &spot.context);
+        if tree.is_empty() {
+            return Err(SolverError::NoLegalActions);
+        }
+        let requested = TreeFootprint::of(&tree, buckets);
+        let limits = self.config.tree_limits();
+        let fitted = fit_tree(
+            &spot.action_specs,
+            &spot.summary,
+            stack,
+            &spot.context,
+            &rake_model(request),
+            buckets,
+            &limits,
+        );
+        let (solve_buckets, solve_footprint, solve_actions) = match &fitted {
+            Ok(fitted) => (fitted.buckets, fitted.footprint, fitted.tree.actions.len()),
+            Err(_) => (0, TreeFootprint::default(), 0),
+        };
+        Ok(TreeEstimate {
+            nodes: requested.nodes as u64,
+            info_sets: requested.info_sets as u64,
+            memory_bytes: requested.bytes,
+            buckets: buckets as u32,
+            actions: tree.actions.len() as u32,
+            within_limits: limits.admits(&requested),
+            solvable: fitted.is_ok(),
+            solve_buckets: solve_buckets as u32,
+            solve_actions: solve_actions as u32,
+            solve_memory_bytes: solve_footprint.bytes,
+        })
+    }
+
+    // Threads for the boards of one multi-board solve; 0 configures one per usable core.
+    fn board_workers(&self) -> usize {
+        match self.config.threads.solver {
+            0 => self.config.threads.core_count(),
+            workers => workers,
+        }
+    }
+
+    // Bytes the fitted tree of a spot would take, as a stand-in for how long it takes to solve.
+    fn estimated_work(&self, request: &SubgameRequest) -> f64 {
+        self.prepare(request, false)
+            .ok()
+            .and_then(|spot| {
+                fit_tree(
+                    &spot.action_specs,
+                    &spot.summary,
+                    spot.effective_stack_bb,
+                    &spot.context,
+                    &rake_model(request),
+                    self.config.abstraction.buckets,
+                    &self.config.tree_limits(),
+                )
+                .ok()
+            })
+            .map_or(1.0, |fitted| fitted.footprint.bytes.max(1) as f64)
+    }
+
+    // Scores the equity buckets a solve of the spot would use, on its board and later streets.
+    pub fn abstraction_info(
+        &self,
+        request: &AbstractionInfoRequest,
+    ) -> Result<AbstractionInfo, SolverError> {
+        let spot_request = request.spot.clone().unwrap_or_default();
+        let spot = self.prepare(&spot_request, false)?;
+        let board = validate_board(
+            &spot_request.board,
+            &spot_request.hole_cards,
+            spot.summary.street_kind(),
+        )?;
+        let street = spot.summary.street_kind().unwrap_or(match board.len() {
+            0 => Street::Preflop,
+            3 => Street::Flop,
+            4 => Street::Turn,
+            _ => Street::River,
+        });
+        let buckets = fit_tree(
+            &spot.action_specs,
+            &spot.summary,
+            spot.effective_stack_bb,
+            &spot.context,
+            &rake_model(&spot_request),
+            self.config.abstraction.buckets,
+            &self.config.tree_limits(),
+        )
+        .map(|fitted| fitted.buckets)
+        .unwrap_or(self.config.abstraction.buckets);
+        let defaults = QualityOptions::default();
+        let options = QualityOptions {
+            runouts: match request.runouts {
+                0 => defaults.runouts,
+                runouts => (runouts as usize).min(MAX_QUALITY_RUNOUTS),
+            },
+            boards: match request.boards_per_street {
+                0 => defaults.boards,
+                boards => (boards as usize).min(MAX_QUALITY_BOARDS),
+            },
+            collision_tolerance: if request.collision_tolerance > 0.0 {
+                request.collision_tolerance
+            } else {
+                defaults.collision_tolerance
+            },
+        };
+        let mut rng = SolverRng::seeded(effective_seed(&spot_request));
+        let streets = abstraction_quality(
+            &board,
+            street,
+            buckets,
+            spot.summary.game_variant(),
+            &options,
+            &mut rng,
+        );
+        Ok(AbstractionInfo {
+            streets: streets
+                .iter()
+                .map(|quality| StreetAbstraction {
+                    street: quality.street.as_str().to_string(),
+                    buckets: quality.buckets as u32,
+                    boards: quality.boards as u32,
+                    combos: quality.combos as u32,
+                    equity_variance: quality.equity_variance,
+                    within_bucket_variance: quality.within_bucket_variance,
+                    collision_rate: quality.collision_rate,
+                    bucket_details: quality
+                        .bucket_details
+                        .iter()
+                        .enumerate()
+                        .map(|(bucket, details)| BucketQuality {
+                            bucket: bucket as u32,
+                            combos: details.combos as u32,
+                            mean_equity: details.mean_equity,
+                            equity_variance: details.equity_variance,
+                            min_equity: details.min_equity,
+                            max_equity: details.max_equity,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        })
+    }
+
+    // The spot's tree as DOT or JSON, built as a solve would build it and solved first if asked.
+    pub fn export_tree(&self, request: &TreeExportRequest) -> Result<TreeExport, SolverError> {
+        let Some(format) = TreeFormat::parse(&request.format) else {
+            return Err(SolverError::InvalidRequest(vec![RequestIssue {
+                field: "format".to_string(),
+                reason: "UNKNOWN_FORMAT",
+                message: format!("unknown format '{}'; expected dot or json", request.format),
+                blocking: true,
+            }]));
+        };
+        let spot_request = request.spot.clone().unwrap_or_default();
+        if request.solve {
+            let (response, snapshot) = self.solve_snapshot(&spot_request)?;
+            return Ok(TreeExport {
+                format: format.as_str().to_string(),
+                content: export_tree(&snapshot.tree, Some(&snapshot.strategies), format),
+                nodes: snapshot.tree.nodes.len() as u32,
+                solve: Some(response),
+            });
+        }
+        let spot = self.prepare(&spot_request, false)?;
+        let tree = fit_tree(
+            &spot.action_specs,
+            &spot.summary,
+            spot.effective_stack_bb,
+            &spot.context,
+            &rake_model(&spot_request),
+            self.config.abstraction.buckets,
+            &self.config.tree_limits(),
+        )?
+        .tree;
+        Ok(TreeExport {
+            format: format.as_str().to_string(),
+            content: export_tree(&tree, None, format),
+            nodes: tree.nodes.len() as u32,
+            solve: None,
+        })
+    }
+
+    fn prepare(
+        &self,
+        request: &SubgameRequest,
+        check_budget: bool,
+    ) -> Result<PreparedSpot, SolverError> {
+        let blocking: Vec<RequestIssue> = self
+            .validate(request)
+            .into_iter()
+            .filter(|issue| issue.blocking && (check_budget || issue.field != "budget_ms"))
+            .collect();
+        if !blocking.is_empty() {
+            return Err(SolverError::InvalidRequest(blocking));
+        }
+        let summary = parse_game_state(request)?;
+        let board = validate_board(&request.board, &request.hole_cards, summary.street_kind())?;
+        let variant = summary.game_variant();
+        check_deck(&board, variant)?;
+        check_deck(&parse_cards(&request.hole_cards)?, variant)?;
+        let context = BettingContext {
+            max_raises: max_raises(request, &self.config),
+            ..BettingContext::from_summary(&summary)
+        };
+        let effective_stack_bb = summary
+            .hero_effective_stack_bb()
+            .unwrap_or(request.effective_stack_bb as f64);
+        let sets = request_action_sets(request);
+        let tokens = street_action_tokens(
+            sets.as_ref(),
+            summary.street_kind(),
+            context.round(),
+            &request.action_set,
+        );
+        let policy = sizing_policy(request, &self.config);
+        let strict = request
+            .config
+            .as_ref()
+            .is_some_and(|config| config.strict_action_tokens);
+        // Street sets were only checked by validation against their own street.
+        let field = if std::ptr::eq(tokens, request.action_set.as_slice()) {
+            "action_set"
+        } else {
+            "street_action_sets"
+        };
+        let tokens = &expand_overbet_tokens(tokens, &summary, &board, effective_stack_bb);
+        let parsed = if strict {
+            parse_action_set_strict(tokens, &summary, effective_stack_bb, &policy).map_err(
+                |errors| {
+                    SolverError::InvalidRequest(
+                        errors
+                            .into_iter()
+                            .map(|(index, error)| {
+                                RequestIssue::blocking(
+                                    format!("{}[{}]", field, index),
+                                    error.reason(),
+                                    error.to_string(),
+                                )
+                            })
+                            .collect(),
+                    )
+                },
+            )?
+        } else {
+            parse_action_set_with_policy(tokens, &summary, effective_stack_bb, &policy)
+        };
+        let action_specs = merge_similar_sizes(parsed, merge_tolerance(request, &self.config));
+        let fixed_limit = summary.betting_structure() != BettingStructure::NoLimit;
+        if action_specs.is_empty() && !fixed_limit {
+            return Err(SolverError::EmptyActionSet);
+        }
+        Ok(PreparedSpot {
+            summary,
+            context,
+            action_specs,
+            effective_stack_bb,
+        })
+    }
+}
+
+struct PushFoldSpot {
+    game: PushFoldGame,
+    // Hero is the big blind facing the jam rather than the small blind deciding to make it.
+    facing_jam: bool,
+    chips_per_bb: f64,
+}
+
+struct PreparedSpot {
+    summary: GameStateSummary,
+    context: BettingContext,
+    action_specs: Vec<ActionSpec>,
+    effective_stack_bb: f64,
 }
 
 impl Default for SolverEngine {
@@ -43,24 +1623,97 @@ impl Default for SolverEngine {
     }
 }
 
+// A solve needs a time budget, an iteration budget, or both.
+// Errors the degradation ladder catches: the spot could be answered, just not as asked. A request
+// without any budget is the caller's mistake and still fails.
+fn degrades(error: &SolverError) -> bool {
+    matches!(
+        error,
+        SolverError::BudgetTooSmall { .. }
+            | SolverError::TreeTooLarge { .. }
+            | SolverError::MemoryBudgetExceeded { .. }
+    )
+}
+
+fn has_budget(request: &SubgameRequest) -> bool {
+    request.budget_ms > 0 || request.budget_iterations > 0
+}
+
+// A board's share of a time budget run on `workers` threads: the whole pool's time, split by
+// estimated work, and never more than the budget itself. No time budget stays none.
+fn board_budget(budget_ms: i32, workers: usize, work: f64, total_work: f64) -> i32 {
+    if budget_ms <= 0 || total_work <= 0.0 {
+        return budget_ms;
+    }
+    let share = budget_ms as f64 * workers as f64 * work / total_work;
+    (share as i32).clamp(1, budget_ms)
+}
+
+// Even shares of a time budget, at least 1ms each; no time budget stays none.
+fn split_budget(budget_ms: i32, parts: usize) -> i32 {
+    if budget_ms <= 0 {
+        return budget_ms;
+    }
+    (budget_ms / parts.max(1) as i32).max(1)
+}
+
 fn determine_iterations(budget_ms: i32, action_count: usize) -> usize {
     let base = (budget_ms.max(50) / 10) as usize;
     base.max(action_count.max(5))
 }
 
+fn action_prob(stat: ActionStat) -> ActionProb {
+    ActionProb {
+        action_type: stat.label,
+        amount: stat.amount,
+        kind: action_kind(stat.kind) as i32,
+        pot_fraction: 0.0,
+        frequency: stat.frequency,
+        ev: stat.ev,
+        regret: stat.regret,
+        equity: stat.equity,
+        amount_chips: 0,
+        pot_after_bb: 0.0,
+        stack_after_bb: 0.0,
+        spr_after: 0.0,
+        responses: Vec::new(),
+    }
+}
+
+fn action_kind(kind: ActionKind) -> ActionKindReport {
+    match kind {
+        ActionKind::Fold => ActionKindReport::Fold,
+        ActionKind::Check => ActionKindReport::Check,
+        ActionKind::Call => ActionKindReport::Call,
+        ActionKind::Bet => ActionKindReport::Bet,
+        ActionKind::Raise => ActionKindReport::Raise,
+        ActionKind::AllIn => ActionKindReport::AllIn,
+    }
+}
+
+fn pot_fraction(amount: f64, pot_bb: f64) -> f64 {
+    if pot_bb > 0.0 {
+        amount / pot_bb
+    } else {
+        0.0
+    }
+}
+
 fn build_response(
     stats: Vec<ActionStat>,
+    legalization: &LegalizationReport,
     clock: &BudgetClock,
     exploitability: f64,
 ) -> SubgameResponse {
-    let actions = stats
-        .into_iter()
-        .map(|stat| ActionProb {
-            action_type: stat.label,
-            amount: stat.amount,
-            frequency: stat.frequency,
-            ev: stat.ev,
-            regret: stat.regret,
+    let actions = stats.into_iter().map(action_prob).collect();
+    let legalization = legalization
+        .adjustments
+        .iter()
+        .map(|adjustment| LegalizationNote {
+            label: adjustment.label.clone(),
+            requested_amount: adjustment.requested,
+            legal_amount: adjustment.legal.unwrap_or(0.0),
+            reason: adjustment.reason.as_str().to_string(),
         })
         .collect();
 
@@ -69,13 +1722,617 @@ fn build_response(
         exploitability,
         compute_time_ms: clock.elapsed_millis() as i32,
         source: "subgame".to_string(),
+        legalization,
+        ..Default::default()
+    }
+}
+
+fn summarize_runouts(board: &[Card], next_cards: Vec<BoardResult>) -> Vec<RunoutSummary> {
+    let total = next_cards.len().max(1) as f64;
+    let mut classes: Vec<(RunoutClass, Vec<BoardResult>)> = RunoutClass::ALL
+        .iter()
+        .map(|class| (*class, Vec::new()))
+        .collect();
+    for result in next_cards {
+        let class = result
+            .cards
+            .last()
+            .and_then(|code| Card::parse(code).ok())
+            .map(|next| classify_river(board, next));
+        if let Some((_, members)) = classes.iter_mut().find(|(known, _)| Some(*known) == class) {
+            members.push(result);
+        }
+    }
+    classes
+        .into_iter()
+        .filter_map(|(class, members)| {
+            let first = members.first()?;
+            let share = 1.0 / members.len() as f64;
+            let mut actions = first.actions.clone();
+            actions.iter_mut().for_each(zero_action);
+            let mut exploitability = 0.0;
+            for member in &members {
+                exploitability += share * member.exploitability;
+                for (total, action) in actions.iter_mut().zip(&member.actions) {
+                    blend_action(total, action, share);
+                }
+            }
+            Some(RunoutSummary {
+                category: class.as_str().to_string(),
+                rivers: members
+                    .iter()
+                    .filter_map(|member| member.cards.last().cloned())
+                    .collect(),
+                weight: members.len() as f64 / total,
+                actions,
+                exploitability,
+                boards: members,
+            })
+        })
+        .collect()
+}
+
+// Rewrites the street of a game state; an empty state stays empty.
+fn on_street(json: &str, street: Street) -> Result<String, SolverError> {
+    if json.trim().is_empty() {
+        return Ok(String::new());
+    }
+    let mut state: serde_json::Value = serde_json::from_str(json)
+        .map_err(|error| SolverError::InvalidGameState(error.to_string()))?;
+    if let Some(object) = state.as_object_mut() {
+        object.insert("street".into(), street.as_str().into());
+        for field in ["facing_bet", "facingBet", "aggressor_seat", "aggressorSeat"] {
+            object.remove(field);
+        }
+    }
+    Ok(state.to_string())
+}
+
+// Explicit boards win over representative flops; weights are normalized, and all-zero weights
+// mean equal ones.
+fn board_set(request: &SubgameRequest) -> Result<Vec<(Vec<String>, f64)>, SolverError> {
+    let boards: Vec<(Vec<String>, f64)> = if request.boards.is_empty() {
+        let dead = parse_cards(&request.hole_cards)?;
+        let variant = parse_game_state(request)?.game_variant();
+        representative_flops(request.representative_flops as usize, &dead, variant)
+            .into_iter()
+            .map(|flop| {
+                let cards = flop.cards.iter().map(ToString::to_string).collect();
+                (cards, flop.weight)
+            })
+            .collect()
+    } else {
+        request
+            .boards
+            .iter()
+            .map(|board| (board.cards.clone(), board.weight.max(0.0)))
+            .collect()
+    };
+    let total: f64 = boards.iter().map(|(_, weight)| weight).sum();
+    let count = boards.len() as f64;
+    Ok(boards
+        .into_iter()
+        .map(|(cards, weight)| {
+            let weight = if total > 0.0 {
+                weight / total
+            } else {
+                1.0 / count
+            };
+            (cards, weight)
+        })
+        .collect())
+}
+
+fn memory_usage(memory: &SolveMemory) -> MemoryUsage {
+    MemoryUsage {
+        peak_bytes: memory.peak_bytes(),
+        tree_bytes: memory.tree_bytes,
+        table_bytes: memory.table_bytes,
+        nodes: memory.nodes as u32,
+        info_sets: memory.info_sets as u32,
+    }
+}
+
+fn refinement_stats(stats: &RefinementStats) -> RefinementStatsReport {
+    RefinementStatsReport {
+        rounds: stats.rounds as u32,
+        initial_buckets: stats.initial_buckets as u32,
+        final_buckets: stats.final_buckets as u32,
+        splits: stats.splits as u32,
+    }
+}
+
+fn push_fold_hands(chart: &PushFoldChart) -> Vec<PushFoldHand> {
+    chart
+        .hands
+        .iter()
+        .map(|hand| PushFoldHand {
+            hand: hand.hand.clone(),
+            combos: hand.combos as u32,
+            jam: hand.jam,
+            call: hand.call,
+            jam_ev: hand.jam_ev,
+            call_ev: hand.call_ev,
+            jam_threshold_bb: hand.jam_threshold_bb,
+            call_threshold_bb: hand.call_threshold_bb,
+        })
+        .collect()
+}
+
+// Field by field, for solves that ran one after another.
+fn largest_memory(a: Option<MemoryUsage>, b: Option<MemoryUsage>) -> Option<MemoryUsage> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(MemoryUsage {
+            peak_bytes: a.peak_bytes.max(b.peak_bytes),
+            tree_bytes: a.tree_bytes.max(b.tree_bytes),
+            table_bytes: a.table_bytes.max(b.table_bytes),
+            nodes: a.nodes.max(b.nodes),
+            info_sets: a.info_sets.max(b.info_sets),
+        }),
+        (a, b) => a.or(b),
+    }
+}
+
+// `response` with every averaged quantity zeroed, ready to accumulate into.
+fn zeroed(response: &SubgameResponse) -> SubgameResponse {
+    let mut zeroed = response.clone();
+    zeroed.exploitability = 0.0;
+    zeroed.constraint_cost_bb = 0.0;
+    zeroed.iterations = 0;
+    zeroed.archive_key.clear();
+    zeroed.actions.iter_mut().for_each(zero_action);
+    for hand in &mut zeroed.per_hand_strategies {
+        hand.ev = 0.0;
+        hand.actions.iter_mut().for_each(zero_action);
+    }
+    for bet in &mut zeroed.diagnostics {
+        *bet = BetDiagnostics {
+            action_type: std::mem::take(&mut bet.action_type),
+            ..Default::default()
+        };
+    }
+    zeroed
+}
+
+fn zero_action(action: &mut ActionProb) {
+    *action = ActionProb {
+        action_type: std::mem::take(&mut action.action_type),
+        amount: action.amount,
+        kind: action.kind,
+        pot_fraction: action.pot_fraction,
+        amount_chips: action.amount_chips,
+        pot_after_bb: action.pot_after_bb,
+        stack_after_bb: action.stack_after_bb,
+        spr_after: action.spr_after,
+        responses: std::mem::take(&mut action.responses)
+            .into_iter()
+            .map(|reply| VillainResponse {
+                frequency: 0.0,
+                ..reply
+            })
+            .collect(),
+        ..Default::default()
+    };
+}
+
+fn blend_action(total: &mut ActionProb, action: &ActionProb, weight: f64) {
+    total.frequency += weight * action.frequency;
+    total.ev += weight * action.ev;
+    total.regret += weight * action.regret;
+    total.equity += weight * action.equity;
+    for (total, reply) in total.responses.iter_mut().zip(&action.responses) {
+        total.frequency += weight * reply.frequency;
+    }
+}
+
+// Adds `weight` times every averaged quantity of `response` into `total`. Both come from the same
+// abstraction, so actions, hands and bets line up by index.
+fn blend_response(total: &mut SubgameResponse, response: &SubgameResponse, weight: f64) {
+    total.exploitability += weight * response.exploitability;
+    total.constraint_cost_bb += weight * response.constraint_cost_bb;
+    total.converged &= response.converged;
+    total.budget_exhausted |= response.budget_exhausted;
+    let level = |response: &SubgameResponse| {
+        DegradationLevel::parse(&response.degradation).unwrap_or_default()
+    };
+    if level(response) > level(total) {
+        total.degradation = response.degradation.clone();
+    }
+    total.memory = largest_memory(total.memory.take(), response.memory.clone());
+    for (total, action) in total.actions.iter_mut().zip(&response.actions) {
+        blend_action(total, action, weight);
+    }
+    for (total, hand) in total
+        .per_hand_strategies
+        .iter_mut()
+        .zip(&response.per_hand_strategies)
+    {
+        total.ev += weight * hand.ev;
+        for (total, action) in total.actions.iter_mut().zip(&hand.actions) {
+            blend_action(total, action, weight);
+        }
+    }
+    for (total, bet) in total.diagnostics.iter_mut().zip(&response.diagnostics) {
+        total.pot_odds += weight * bet.pot_odds;
+        total.required_defense += weight * bet.required_defense;
+        total.defense_frequency += weight * bet.defense_frequency;
+        total.bluff_to_value += weight * bet.bluff_to_value;
+        total.indifferent_bluff_to_value += weight * bet.indifferent_bluff_to_value;
+        total.value_share += weight * bet.value_share;
+        total.merged_share += weight * bet.merged_share;
+        total.bluff_share += weight * bet.bluff_share;
     }
 }
 
-fn parse_game_state(json: &str) -> GameStateSummary {
-    serde_json::from_str(json).unwrap_or(GameStateSummary {
-        pot: 0.0,
-        street: String::new(),
-        blinds: BlindSummary::default(),
+// Validation rejects unknown modes; anything that slips through is left unpurified.
+fn purification(request: &SubgameRequest) -> Purification {
+    request
+        .purification
+        .as_ref()
+        .and_then(|spec| Purification::parse(&spec.mode, spec.threshold))
+        .unwrap_or_default()
+}
+
+fn frequency_rounding(request: &SubgameRequest) -> FrequencyRounding {
+    request
+        .rounding
+        .as_ref()
+        .map(|rounding| FrequencyRounding {
+            step: rounding.step.max(0.0),
+            min_frequency: rounding.min_frequency.max(0.0),
+        })
+        .unwrap_or_default()
+}
+
+// Rounds the range's frequencies and each hand's on their own, so each still sums to 1.
+fn round_response(response: &mut SubgameResponse, rounding: &FrequencyRounding) {
+    if *rounding == FrequencyRounding::default() {
+        return;
+    }
+    let round = |actions: &mut [ActionProb]| {
+        let mut frequencies: Vec<f64> = actions.iter().map(|action| action.frequency).collect();
+        round_frequencies(&mut frequencies, rounding);
+        for (action, frequency) in actions.iter_mut().zip(frequencies) {
+            action.frequency = frequency;
+        }
+    };
+    round(&mut response.actions);
+    for hand in &mut response.per_hand_strategies {
+        round(&mut hand.actions);
+    }
+}
+
+fn rake_model(request: &SubgameRequest) -> RakeModel {
+    request
+        .config
+        .as_ref()
+        .and_then(|config| config.rake.as_ref())
+        .map(RakeModel::from_config)
+        .unwrap_or_default()
+}
+
+fn effective_seed(request: &SubgameRequest) -> u64 {
+    if request.seed != 0 {
+        request.seed
+    } else {
+        seed_from_fingerprint(&request.state_fingerprint)
+    }
+}
+
+fn cfr_config(request: &SubgameRequest, iterations: usize, defaults: &SolverConfig) -> CfrConfig {
+    let mut config = CfrConfig::new(iterations);
+    config.variant = defaults.variant().unwrap_or_default();
+    config.backend = defaults.backend().unwrap_or_default();
+    config.buckets = defaults.abstraction.buckets;
+    config.pruning.enabled = defaults.cfr.pruning;
+    config.pruning.threshold = defaults.cfr.prune_threshold;
+    config.pruning.recheck_interval = defaults.cfr.prune_recheck_interval.max(1);
+    config.check_interval = defaults.cfr.check_interval.max(1);
+    config.sampling_baseline = defaults.cfr.sampling_baseline;
+    if defaults.abstraction.refine
+        || request
+            .config
+            .as_ref()
+            .is_some_and(|config| config.refine_abstraction)
+    {
+        config.refinement = Some(RefinementConfig {
+            initial_buckets: defaults.abstraction.refine_initial_buckets,
+            rounds: defaults.abstraction.refine_rounds,
+        });
+    }
+    if let Some(solver_config) = request.config.as_ref() {
+        if solver_config.disable_pruning {
+            config.pruning.enabled = false;
+        }
+        if solver_config.prune_threshold > 0.0 {
+            config.pruning.threshold = solver_config.prune_threshold;
+        }
+        if solver_config.prune_recheck_interval > 0 {
+            config.pruning.recheck_interval = solver_config.prune_recheck_interval as usize;
+        }
+        if let Some(variant) = CfrVariantKind::parse(&solver_config.cfr_variant) {
+            config.variant = variant;
+        }
+        if solver_config.disable_sampling_baseline {
+            config.sampling_baseline = false;
+        }
+    }
+    config
+}
+
+// The crate version and the commit it was built from, e.g. "0.1.0+3f2a9c1d0b7e".
+pub const SOLVER_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "+", env!("SOLVER_GIT_SHA"));
+
+const RESOLVED_SOURCE: &str = "blueprint+resolve";
+const PUSH_FOLD_SOURCE: &str = "push-fold";
+
+fn answer_with_blueprint(mut response: SubgameResponse) -> SubgameResponse {
+    warn!(
+        reason = "BLUEPRINT",
+        "answering with a cached solve of the spot"
+    );
+    response.source = "blueprint".to_string();
+    response.degradation = DegradationLevel::Blueprint.as_str().to_string();
+    response
+}
+
+// Keep an abstraction report to seconds of work whatever the request asks for.
+const MAX_QUALITY_RUNOUTS: usize = 1_000;
+const MAX_QUALITY_BOARDS: usize = 32;
+
+const DEFAULT_BENCHMARK_ITERATIONS: usize = 200;
+
+const DEFAULT_WARM_START_WEIGHT: f64 = 10.0;
+const WARM_START_AMOUNT_TOLERANCE: f64 = 0.1;
+
+// Lines the prior up with this tree's root actions. Sizes that moved (a different stack depth
+// changes pot-relative amounts) are matched to the closest prior amount within 10%.
+fn warm_start_prior(tree: &GameTree, warm_start: &WarmStart) -> Option<WarmStartPrior> {
+    let root_strategy: Vec<f64> = tree
+        .actions
+        .iter()
+        .map(|action| {
+            let by_label = warm_start
+                .actions
+                .iter()
+                .find(|prior| prior.action_type == action.label);
+            let by_amount = || {
+                warm_start
+                    .actions
+                    .iter()
+                    .filter(|prior| {
+                        let largest = prior.amount.abs().max(action.amount.abs());
+                        largest > 0.0
+                            && (prior.amount - action.amount).abs() / largest
+                                <= WARM_START_AMOUNT_TOLERANCE
+                    })
+                    .min_by(|a, b| {
+                        (a.amount - action.amount)
+                            .abs()
+                            .total_cmp(&(b.amount - action.amount).abs())
+                    })
+            };
+            by_label
+                .or_else(by_amount)
+                .map(|prior| prior.frequency.max(0.0))
+                .unwrap_or(0.0)
+        })
+        .collect();
+
+    if root_strategy.iter().sum::<f64>() <= 0.0 {
+        return None;
+    }
+    let weight = if warm_start.weight > 0.0 {
+        warm_start.weight
+    } else {
+        DEFAULT_WARM_START_WEIGHT
+    };
+    Some(WarmStartPrior {
+        root_strategy,
+        weight,
     })
 }
+
+fn opponent_model(
+    tree: &GameTree,
+    buckets: usize,
+    opponent: &OpponentModelSpec,
+) -> Option<OpponentModel> {
+    if opponent.weight <= 0.0 {
+        return None;
+    }
+    let model = OpponentModel::from_fold_frequencies(tree, buckets, opponent.weight, |label| {
+        opponent
+            .tendencies
+            .iter()
+            .find(|tendency| tendency.action == label)
+            .map(|tendency| tendency.fold_frequency)
+            .or(opponent.fold_to_bet)
+    });
+    model
+        .strategies
+        .iter()
+        .any(Option::is_some)
+        .then_some(model)
+}
+
+// Locks whose path does not exist in the fitted tree, e.g. a size merged away, are skipped.
+// Added to `resolved`, replacing what it holds for the same buckets.
+fn node_locks(
+    tree: &GameTree,
+    buckets: usize,
+    locks: &[NodeLock],
+    mut resolved: NodeLocks,
+) -> NodeLocks {
+    for lock in locks {
+        let Some(node) = find_node(tree, &lock.path) else {
+            warn!(path = ?lock.path, "node lock does not match the tree; ignoring it");
+            continue;
+        };
+        let NodeKind::Decision { children, .. } = &tree.nodes[node].kind else {
+            warn!(path = ?lock.path, "node lock targets a terminal node; ignoring it");
+            continue;
+        };
+        let frequencies: Vec<f64> = children
+            .iter()
+            .map(|child| {
+                lock.actions
+                    .iter()
+                    .filter(|action| action.action_type == tree.nodes[*child].label)
+                    .map(|action| action.frequency)
+                    .sum()
+            })
+            .collect();
+        let lock_buckets: Vec<usize> = lock.buckets.iter().map(|bucket| *bucket as usize).collect();
+        if !resolved.lock(node, buckets, &frequencies, &lock_buckets) {
+            warn!(path = ?lock.path, "node lock has no playable action; ignoring it");
+        }
+    }
+    resolved
+}
+
+// Holds hero's root to the blueprint's per-bucket strategies. Buckets are equity quantiles, so
+// each bucket here plays the blueprint bucket covering the middle of its quantile. Blueprint
+// actions go to the root action with the same label or, when this tree has no such label, the
+// one with the closest amount.
+fn trunk_locks(tree: &GameTree, buckets: usize, trunk: &SubgameResponse) -> NodeLocks {
+    let mut locks = NodeLocks::default();
+    let NodeKind::Decision { children, .. } = &tree.nodes[0].kind else {
+        return locks;
+    };
+    let hands = &trunk.per_hand_strategies;
+    if hands.is_empty() || buckets == 0 {
+        return locks;
+    }
+    let trunk_buckets = hands.len();
+    for bucket in 0..buckets {
+        let hand =
+            &hands[((2 * bucket + 1) * trunk_buckets / (2 * buckets)).min(trunk_buckets - 1)];
+        let mut frequencies = vec![0.0; children.len()];
+        for action in &hand.actions {
+            let matched = children
+                .iter()
+                .position(|child| tree.nodes[*child].label == action.action_type)
+                .or_else(|| {
+                    (0..children.len()).min_by(|a, b| {
+                        let gap = |index: usize| (tree.actions[index].amount - action.amount).abs();
+                        gap(*a).total_cmp(&gap(*b))
+                    })
+                });
+            if let Some(index) = matched {
+                frequencies[index] += action.frequency;
+            }
+        }
+        locks.lock(0, buckets, &frequencies, &[bucket]);
+    }
+    locks
+}
+
+// Only requests that asked for per-bucket strategies get the trunk kept for the cache.
+fn without_trunk(request: &SubgameRequest, mut response: SubgameResponse) -> SubgameResponse {
+    if !request.include_per_hand {
+        response.per_hand_strategies.clear();
+    }
+    response
+}
+
+// Labels name root actions; "bet" stands for every bet and raise there.
+fn root_constraints(tree: &GameTree, constraints: &[StrategyConstraintSpec]) -> RootConstraints {
+    let NodeKind::Decision { children, .. } = &tree.nodes[0].kind else {
+        return RootConstraints::default();
+    };
+    let mut resolved = RootConstraints::default();
+    for constraint in constraints {
+        let actions: Vec<usize> = children
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| {
+                let label = &tree.nodes[**child].label;
+                constraint.actions.iter().any(|wanted| {
+                    wanted == label
+                        || (wanted == "bet" && !matches!(label.as_str(), "fold" | "check" | "call"))
+                })
+            })
+            .map(|(action, _)| action)
+            .collect();
+        if actions.is_empty() {
+            warn!(actions = ?constraint.actions, "constraint matches no root action; ignoring it");
+            continue;
+        }
+        resolved.constraints.push(StrategyConstraint {
+            actions,
+            buckets: constraint
+                .buckets
+                .iter()
+                .map(|bucket| *bucket as usize)
+                .collect(),
+            min: constraint.min_frequency.clamp(0.0, 1.0),
+            max: constraint.max_frequency.unwrap_or(1.0).clamp(0.0, 1.0),
+        });
+    }
+    resolved
+}
+
+fn find_node(tree: &GameTree, path: &[String]) -> Option<usize> {
+    path.iter().try_fold(0, |node, label| {
+        let NodeKind::Decision { children, .. } = &tree.nodes[node].kind else {
+            return None;
+        };
+        children
+            .iter()
+            .copied()
+            .find(|child| tree.nodes[*child].label == *label)
+    })
+}
+
+fn merge_tolerance(request: &SubgameRequest, defaults: &SolverConfig) -> f64 {
+    request
+        .config
+        .as_ref()
+        .map(|config| config.merge_tolerance)
+        .filter(|tolerance| *tolerance > 0.0)
+        .unwrap_or(defaults.abstraction.merge_tolerance)
+}
+
+fn max_raises(request: &SubgameRequest, defaults: &SolverConfig) -> u32 {
+    request
+        .config
+        .as_ref()
+        .and_then(|config| config.max_raises)
+        .unwrap_or(defaults.abstraction.max_raises)
+}
+
+// The server's sizing policy with the request's non-zero overrides.
+fn sizing_policy(request: &SubgameRequest, defaults: &SolverConfig) -> SizingPolicy {
+    let mut policy = defaults.sizing_policy();
+    if let Some(sizing) = request
+        .config
+        .as_ref()
+        .and_then(|config| config.sizing.as_ref())
+    {
+        if sizing.min_bet_bb > 0.0 {
+            policy.min_bet_bb = sizing.min_bet_bb;
+        }
+        if sizing.all_in_threshold > 0.0 {
+            policy.all_in_threshold = sizing.all_in_threshold.min(1.0);
+        }
+        if sizing.max_overbets > 0 {
+            policy.max_overbets = sizing.max_overbets as usize;
+        }
+        policy.reject_oversize |= sizing.reject_oversize;
+    }
+    policy
+}
+
+// An empty string means the caller sent no state; anything else has to parse.
+// The structured state wins over the JSON one.
+fn parse_game_state(request: &SubgameRequest) -> Result<GameStateSummary, SolverError> {
+    if let Some(state) = &request.game_state {
+        return Ok(GameStateSummary::from_proto(state));
+    }
+    let json = &request.game_state_json;
+    if json.trim().is_empty() {
+        return Ok(GameStateSummary::default());
+    }
+    serde_json::from_str(json).map_err(|error| SolverError::InvalidGameState(error.to_string()))
+}
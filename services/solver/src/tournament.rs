@@ -0,0 +1,2 @@
+pub mod bounty;
+pub mod icm;
@@ -0,0 +1,86 @@
+use crate::preflop::pushfold::{PushFoldGame, PushFoldPayoffs};
+use crate::solver_proto::TournamentSpec;
+use crate::tournament::icm::IcmModel;
+
+// Half paid in cash and half onto the winner's head, as most progressive knockouts do.
+const DEFAULT_CASH_SHARE: f64 = 0.5;
+
+// Progressive knockout: busting a player pays part of their bounty in cash and adds the rest to
+// the winner's own bounty.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BountyModel {
+    // In prize money, seat by seat like the stacks.
+    pub bounties: Vec<f64>,
+    pub cash_share: f64,
+}
+
+impl BountyModel {
+    pub fn from_spec(spec: &TournamentSpec) -> Option<Self> {
+        if spec.bounties.is_empty()
+            || spec.bounties.len() != spec.stacks.len()
+            || spec.bounties.iter().any(|bounty| *bounty < 0.0)
+        {
+            return None;
+        }
+        let cash_share = if spec.bounty_cash_share > 0.0 && spec.bounty_cash_share <= 1.0 {
+            spec.bounty_cash_share
+        } else {
+            DEFAULT_CASH_SHARE
+        };
+        Some(Self {
+            bounties: spec.bounties.clone(),
+            cash_share,
+        })
+    }
+
+    // What busting `loser` is worth to the winner: the cash now, plus the part added to their own
+    // head. That part is only collected by winning the tournament, so it is weighed by the
+    // winner's share of the chips, the same chance ICM gives them of finishing first.
+    pub fn capture_value(&self, loser: usize, winner_chip_share: f64) -> f64 {
+        let bounty = self.bounties.get(loser).copied().unwrap_or(0.0);
+        bounty * (self.cash_share + (1.0 - self.cash_share) * winner_chip_share.clamp(0.0, 1.0))
+    }
+}
+
+// Push/fold terminals in tournament equity: the ICM value of the stacks each player ends the hand
+// with, plus the bounty for busting the other one. Hero is the small blind deciding to jam, or
+// the big blind facing villain's jam.
+pub fn tournament_payoffs(
+    game: &PushFoldGame,
+    icm: &IcmModel,
+    bounties: Option<&BountyModel>,
+    chips_per_bb: f64,
+    hero_jams: bool,
+) -> PushFoldPayoffs {
+    let seats = if hero_jams {
+        [icm.hero, icm.villain]
+    } else {
+        [icm.villain, icm.hero]
+    };
+    let value = |deltas_bb: [f64; 2]| {
+        let mut stacks = icm.stacks.clone();
+        for (seat, delta) in seats.iter().zip(deltas_bb) {
+            stacks[*seat] = (stacks[*seat] + delta * chips_per_bb).max(0.0);
+        }
+        let equities = icm.equities_with(&stacks);
+        let total: f64 = stacks.iter().sum();
+        let mut value = seats.map(|seat| equities[seat]);
+        if let Some(bounties) = bounties {
+            for (winner, loser) in [(0, 1), (1, 0)] {
+                if stacks[seats[loser]] <= 0.0 && total > 0.0 {
+                    value[winner] +=
+                        bounties.capture_value(seats[loser], stacks[seats[winner]] / total);
+                }
+            }
+        }
+        value
+    };
+    // The same chips change hands as in the chip game; only what they're worth differs.
+    let chips = PushFoldPayoffs::chips(game);
+    PushFoldPayoffs {
+        small_blind_folds: value(chips.small_blind_folds),
+        big_blind_folds: value(chips.big_blind_folds),
+        small_blind_wins: value(chips.small_blind_wins),
+        big_blind_wins: value(chips.big_blind_wins),
+    }
+}
@@ -0,0 +1,125 @@
+use crate::solver_proto::TournamentSpec;
+
+// Above this many players the exact Malmuth-Harville recursion gets expensive; the shortest
+// stacks (other than the seats we care about) are folded into a single opponent.
+const MAX_EXACT_PLAYERS: usize = 12;
+
+#[derive(Clone, Debug)]
+pub struct IcmModel {
+    pub stacks: Vec<f64>,
+    pub payouts: Vec<f64>,
+    pub hero: usize,
+    pub villain: usize,
+}
+
+impl IcmModel {
+    pub fn from_spec(spec: &TournamentSpec) -> Option<Self> {
+        let hero = usize::try_from(spec.hero_seat).ok()?;
+        let villain = usize::try_from(spec.villain_seat).ok()?;
+        let count = spec.stacks.len();
+        if count < 2 || spec.payouts.is_empty() || hero >= count || villain >= count {
+            return None;
+        }
+        if hero == villain || spec.stacks.iter().any(|stack| *stack < 0.0) {
+            return None;
+        }
+        Some(Self {
+            stacks: spec.stacks.clone(),
+            payouts: spec.payouts.clone(),
+            hero,
+            villain,
+        })
+    }
+
+    pub fn hero_equity(&self) -> f64 {
+        equities_keeping(&self.stacks, &self.payouts, &[self.hero, self.villain])[self.hero]
+    }
+
+    // Everyone's equity with the stacks changed to `stacks`, hero and villain kept exact.
+    pub fn equities_with(&self, stacks: &[f64]) -> Vec<f64> {
+        equities_keeping(stacks, &self.payouts, &[self.hero, self.villain])
+    }
+
+    // Tournament equity hero gains when `chips` move from villain to hero (negative moves them
+    // the other way). Transfers are capped by the stacks involved.
+    pub fn equity_delta(&self, chips: f64) -> f64 {
+        let transfer = chips
+            .min(self.stacks[self.villain])
+            .max(-self.stacks[self.hero]);
+        let mut stacks = self.stacks.clone();
+        stacks[self.hero] += transfer;
+        stacks[self.villain] -= transfer;
+        equities_keeping(&stacks, &self.payouts, &[self.hero, self.villain])[self.hero]
+            - self.hero_equity()
+    }
+}
+
+// Malmuth-Harville: the chance of finishing first is proportional to stack size, and each lower
+// place is decided the same way among the players left.
+pub fn icm_equities(stacks: &[f64], payouts: &[f64]) -> Vec<f64> {
+    equities_keeping(stacks, payouts, &[])
+}
+
+fn equities_keeping(stacks: &[f64], payouts: &[f64], keep: &[usize]) -> Vec<f64> {
+    let count = stacks.len();
+    let mut equities = vec![0.0; count];
+    if count == 0 || payouts.is_empty() {
+        return equities;
+    }
+
+    let (indices, merged) = exact_subset(stacks, keep);
+    let places = payouts.len().min(merged.len());
+    let total = 1usize << merged.len();
+    let mut reach = vec![0.0; total];
+    reach[0] = 1.0;
+
+    for mask in 0..total {
+        let probability = reach[mask];
+        let place = mask.count_ones() as usize;
+        if probability <= 0.0 || place >= places {
+            continue;
+        }
+        let remaining: f64 = (0..merged.len())
+            .filter(|seat| mask & (1 << seat) == 0)
+            .map(|seat| merged[seat])
+            .sum();
+        if remaining <= 0.0 {
+            continue;
+        }
+        for seat in 0..merged.len() {
+            if mask & (1 << seat) != 0 || merged[seat] <= 0.0 {
+                continue;
+            }
+            let finish = probability * merged[seat] / remaining;
+            if let Some(&original) = indices.get(seat) {
+                equities[original] += finish * payouts[place];
+            }
+            reach[mask | (1 << seat)] += finish;
+        }
+    }
+
+    equities
+}
+
+fn exact_subset(stacks: &[f64], keep: &[usize]) -> (Vec<usize>, Vec<f64>) {
+    let mut order: Vec<usize> = (0..stacks.len()).collect();
+    if stacks.len() <= MAX_EXACT_PLAYERS {
+        return (order, stacks.iter().map(|stack| stack.max(0.0)).collect());
+    }
+    order.sort_by(|a, b| {
+        keep.contains(b).cmp(&keep.contains(a)).then(
+            stacks[*b]
+                .partial_cmp(&stacks[*a])
+                .unwrap_or(std::cmp::Ordering::Equal),
+        )
+    });
+    let kept: Vec<usize> = order[..MAX_EXACT_PLAYERS - 1].to_vec();
+    let mut merged: Vec<f64> = kept.iter().map(|&seat| stacks[seat].max(0.0)).collect();
+    merged.push(
+        order[MAX_EXACT_PLAYERS - 1..]
+            .iter()
+            .map(|&seat| stacks[seat].max(0.0))
+            .sum(),
+    );
+    (kept, merged)
+}
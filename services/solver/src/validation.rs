@@ -0,0 +1,792 @@
+use crate::abstraction::templates::{request_action_sets, tree_template, TREE_TEMPLATES};
+use crate::abstraction::{
+    check_action_token, check_action_token_in_spot, GameStateSummary, GameVariant, Street,
+};
+use crate::admission::SolvePriority;
+use crate::cards::{board_size, check_deck, parse_cards, validate_board, Card};
+use crate::cfr::purify::Purification;
+use crate::cfr::variant::CfrVariantKind;
+use crate::game_tree::chips::MAX_CHIP_UNITS;
+use crate::solver_proto::{
+    NodeLock, OpponentModel, StrategyConstraint, SubgameRequest, ValidationIssue, ValidationReport,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestIssue {
+    pub field: String,
+    pub reason: &'static str,
+    pub message: String,
+    // Blocking issues stop a solve; the rest are reported but worked around.
+    pub blocking: bool,
+}
+
+impl RequestIssue {
+    pub fn blocking(
+        field: impl Into<String>,
+        reason: &'static str,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            field: field.into(),
+            reason,
+            message: message.into(),
+            blocking: true,
+        }
+    }
+
+    fn warning(field: impl Into<String>, reason: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            blocking: false,
+            ..Self::blocking(field, reason, message)
+        }
+    }
+
+    pub fn to_proto(&self) -> ValidationIssue {
+        ValidationIssue {
+            field: self.field.clone(),
+            reason: self.reason.to_string(),
+            message: self.message.clone(),
+            blocking: self.blocking,
+        }
+    }
+}
+
+pub fn has_blocking(issues: &[RequestIssue]) -> bool {
+    issues.iter().any(|issue| issue.blocking)
+}
+
+pub fn to_report(issues: &[RequestIssue]) -> ValidationReport {
+    ValidationReport {
+        valid: !has_blocking(issues),
+        issues: issues.iter().map(RequestIssue::to_proto).collect(),
+    }
+}
+
+// Collects every problem with a request instead of stopping at the first one, so integrators
+// can fix their callers in one pass.
+pub fn validate_request(request: &SubgameRequest) -> Vec<RequestIssue> {
+    let mut issues = Vec::new();
+
+    // An iteration budget alone is enough; a time budget, when given, must be positive.
+    if request.budget_iterations == 0 && request.budget_ms <= 0 {
+        issues.push(RequestIssue::blocking(
+            "budget_ms",
+            "BUDGET_EXHAUSTED",
+            format!("budget_ms must be positive, got {}", request.budget_ms),
+        ));
+    } else if request.budget_ms < 0 {
+        issues.push(RequestIssue::blocking(
+            "budget_ms",
+            "BUDGET_EXHAUSTED",
+            format!("budget_ms must not be negative, got {}", request.budget_ms),
+        ));
+    }
+
+    if !request.priority.is_empty() && SolvePriority::parse(&request.priority).is_none() {
+        issues.push(RequestIssue::blocking(
+            "priority",
+            "UNKNOWN_PRIORITY",
+            format!(
+                "unknown priority '{}'; expected realtime, normal or background",
+                request.priority
+            ),
+        ));
+    }
+
+    let summary = check_game_state(request, &mut issues);
+    check_stacks(request, summary.as_ref(), &mut issues);
+    check_action_tokens(request, summary.as_ref(), &mut issues);
+    check_cards(request, summary.as_ref(), &mut issues);
+    check_boards(request, summary.as_ref(), &mut issues);
+    check_config(request, &mut issues);
+    check_chip_range(request, summary.as_ref(), &mut issues);
+
+    issues
+}
+
+fn check_game_state(
+    request: &SubgameRequest,
+    issues: &mut Vec<RequestIssue>,
+) -> Option<GameStateSummary> {
+    let (root, summary) = if let Some(state) = &request.game_state {
+        if !request.game_state_json.trim().is_empty() {
+            issues.push(RequestIssue::warning(
+                "game_state_json",
+                "IGNORED_GAME_STATE_JSON",
+                "game_state is set, so game_state_json is ignored",
+            ));
+        }
+        ("game_state", GameStateSummary::from_proto(state))
+    } else {
+        if request.game_state_json.trim().is_empty() {
+            return Some(GameStateSummary::default());
+        }
+        match serde_json::from_str(&request.game_state_json) {
+            Ok(summary) => ("game_state_json", summary),
+            Err(error) => {
+                issues.push(RequestIssue::blocking(
+                    "game_state_json",
+                    "INVALID_GAME_STATE",
+                    format!("game_state_json is not valid: {}", error),
+                ));
+                return None;
+            }
+        }
+    };
+
+    if !summary.street.is_empty() && summary.street_kind().is_none() {
+        issues.push(RequestIssue::warning(
+            format!("{}.street", root),
+            "UNKNOWN_STREET",
+            format!("street '{}' is not recognised", summary.street),
+        ));
+    }
+    if summary.pot < 0.0 {
+        issues.push(RequestIssue::blocking(
+            format!("{}.pot", root),
+            "NEGATIVE_POT",
+            format!("pot cannot be negative, got {}", summary.pot),
+        ));
+    }
+    let amounts = [
+        ("pot", summary.pot),
+        ("blinds.small", summary.blinds.small),
+        ("blinds.big", summary.blinds.big),
+        ("blinds.ante", summary.blinds.ante),
+    ];
+    for (field, value) in amounts {
+        if !value.is_finite() {
+            issues.push(RequestIssue::blocking(
+                format!("{}.{}", root, field),
+                "OUT_OF_RANGE",
+                format!("{} must be a finite number, got {}", field, value),
+            ));
+        }
+    }
+    let variant = GameVariant::parse(&summary.variant);
+    if variant.is_none() {
+        issues.push(RequestIssue::blocking(
+            format!("{}.variant", root),
+            "UNKNOWN_VARIANT",
+            format!("game variant '{}' is not supported", summary.variant),
+        ));
+    }
+    if variant == Some(GameVariant::ShortDeck) {
+        if summary.blinds.ante <= 0.0 {
+            issues.push(RequestIssue::warning(
+                format!("{}.blinds.ante", root),
+                "MISSING_ANTE",
+                "short deck is ante-only but no ante was given; amounts are treated as antes",
+            ));
+        }
+    } else if summary.blinds.big <= 0.0 {
+        issues.push(RequestIssue::warning(
+            format!("{}.blinds.big", root),
+            "MISSING_BIG_BLIND",
+            "big blind is missing; amounts are treated as big blinds",
+        ));
+    }
+    if let Some(limit) = &summary.limit {
+        for (field, value) in [("small_bet", limit.small_bet), ("big_bet", limit.big_bet)] {
+            if value < 0.0 {
+                issues.push(RequestIssue::blocking(
+                    format!("{}.limit.{}", root, field),
+                    "OUT_OF_RANGE",
+                    format!("{} cannot be negative, got {}", field, value),
+                ));
+            }
+        }
+    }
+    if let Some(facing) = summary
+        .facing_bet
+        .filter(|facing| !facing.is_finite() || *facing < 0.0)
+    {
+        issues.push(RequestIssue::blocking(
+            format!("{}.facing_bet", root),
+            "OUT_OF_RANGE",
+            format!("facing_bet cannot be negative, got {}", facing),
+        ));
+    }
+    check_players(root, &summary, issues);
+    Some(summary)
+}
+
+fn check_players(root: &str, summary: &GameStateSummary, issues: &mut Vec<RequestIssue>) {
+    for (index, player) in summary.players.iter().enumerate() {
+        let field = format!("{}.players[{}]", root, index);
+        if !(player.stack >= 0.0
+            && player.committed >= 0.0
+            && player.stack.is_finite()
+            && player.committed.is_finite())
+        {
+            issues.push(RequestIssue::blocking(
+                field.clone(),
+                "OUT_OF_RANGE",
+                "stack and committed chips must be finite and not negative",
+            ));
+        }
+        if summary.players[..index]
+            .iter()
+            .any(|earlier| earlier.seat == player.seat)
+        {
+            issues.push(RequestIssue::blocking(
+                field,
+                "DUPLICATE_SEAT",
+                format!("seat {} is listed more than once", player.seat),
+            ));
+        }
+    }
+    for (field, seat) in [
+        (format!("{}.hero_seat", root), summary.hero_seat),
+        (format!("{}.villain_seat", root), summary.villain_seat),
+        (format!("{}.aggressor_seat", root), summary.aggressor_seat),
+    ] {
+        if let Some(seat) = seat.filter(|seat| summary.player(*seat).is_none()) {
+            issues.push(RequestIssue::blocking(
+                field,
+                "UNKNOWN_SEAT",
+                format!("no player sits in seat {}", seat),
+            ));
+        }
+    }
+    if summary.hero_seat.is_some() && summary.villain_seat == summary.hero_seat {
+        issues.push(RequestIssue::blocking(
+            format!("{}.villain_seat", root),
+            "UNKNOWN_SEAT",
+            "villain_seat is hero's own seat",
+        ));
+    }
+    if summary.hero_seat.is_some() && summary.aggressor_seat == summary.hero_seat {
+        issues.push(RequestIssue::blocking(
+            format!("{}.aggressor_seat", root),
+            "UNKNOWN_SEAT",
+            "hero cannot face their own bet",
+        ));
+    }
+}
+
+fn check_stacks(
+    request: &SubgameRequest,
+    summary: Option<&GameStateSummary>,
+    issues: &mut Vec<RequestIssue>,
+) {
+    let seated = summary.is_some_and(|summary| summary.hero_effective_stack_bb().is_some());
+    if request.effective_stack_bb <= 0 && !seated {
+        issues.push(RequestIssue::warning(
+            "effective_stack_bb",
+            "NON_POSITIVE_STACK",
+            format!(
+                "effective_stack_bb is {}; a 1bb stack is assumed",
+                request.effective_stack_bb
+            ),
+        ));
+    }
+
+    if request.solve_dollar_ev && request.tournament.is_none() {
+        issues.push(RequestIssue::blocking(
+            "tournament",
+            "MISSING_TOURNAMENT",
+            "solve_dollar_ev requires a tournament spec",
+        ));
+    }
+    let Some(tournament) = request.tournament.as_ref() else {
+        return;
+    };
+    let seats = tournament.stacks.len();
+    for (field, seat) in [
+        ("tournament.hero_seat", tournament.hero_seat),
+        ("tournament.villain_seat", tournament.villain_seat),
+    ] {
+        if seat < 0 || seat as usize >= seats {
+            issues.push(RequestIssue::blocking(
+                field,
+                "INCONSISTENT_STACKS",
+                format!("seat {} is outside the {} tournament stacks", seat, seats),
+            ));
+        }
+    }
+    if tournament.hero_seat == tournament.villain_seat {
+        issues.push(RequestIssue::blocking(
+            "tournament.villain_seat",
+            "INCONSISTENT_STACKS",
+            "hero and villain must be different seats",
+        ));
+    }
+    if tournament.payouts.is_empty() {
+        issues.push(RequestIssue::blocking(
+            "tournament.payouts",
+            "MISSING_PAYOUTS",
+            "tournament payouts are empty",
+        ));
+    }
+    for (index, stack) in tournament.stacks.iter().enumerate() {
+        if *stack < 0.0 {
+            issues.push(RequestIssue::blocking(
+                format!("tournament.stacks[{}]", index),
+                "INCONSISTENT_STACKS",
+                format!("stack {} is negative", stack),
+            ));
+        }
+    }
+    if !tournament.bounties.is_empty() && tournament.bounties.len() != seats {
+        issues.push(RequestIssue::blocking(
+            "tournament.bounties",
+            "INCONSISTENT_BOUNTIES",
+            format!(
+                "{} bounties for {} tournament stacks",
+                tournament.bounties.len(),
+                seats
+            ),
+        ));
+    }
+    for (index, bounty) in tournament.bounties.iter().enumerate() {
+        if *bounty < 0.0 {
+            issues.push(RequestIssue::blocking(
+                format!("tournament.bounties[{}]", index),
+                "INCONSISTENT_BOUNTIES",
+                format!("bounty {} is negative", bounty),
+            ));
+        }
+    }
+    if !(0.0..=1.0).contains(&tournament.bounty_cash_share) {
+        issues.push(RequestIssue::blocking(
+            "tournament.bounty_cash_share",
+            "INCONSISTENT_BOUNTIES",
+            format!(
+                "bounty_cash_share must be within [0, 1], got {}",
+                tournament.bounty_cash_share
+            ),
+        ));
+    }
+}
+
+fn check_action_tokens(
+    request: &SubgameRequest,
+    summary: Option<&GameStateSummary>,
+    issues: &mut Vec<RequestIssue>,
+) {
+    if !request.tree_template.is_empty() && tree_template(&request.tree_template).is_none() {
+        issues.push(RequestIssue::blocking(
+            "tree_template",
+            "UNKNOWN_TREE_TEMPLATE",
+            format!(
+                "unknown tree template '{}'; expected one of {}",
+                request.tree_template,
+                TREE_TEMPLATES.join(", ")
+            ),
+        ));
+    }
+    let sets = request_action_sets(request);
+    let mut lists: Vec<(String, &Vec<String>)> =
+        vec![("action_set".to_string(), &request.action_set)];
+    if let Some(sets) = sets.as_ref() {
+        for (street, rounds) in [
+            (Street::Preflop, &sets.preflop),
+            (Street::Flop, &sets.flop),
+            (Street::Turn, &sets.turn),
+            (Street::River, &sets.river),
+        ] {
+            let Some(rounds) = rounds.as_ref() else {
+                continue;
+            };
+            for (round, tokens) in [
+                ("bet", &rounds.bet),
+                ("raise", &rounds.raise),
+                ("reraise", &rounds.reraise),
+            ] {
+                lists.push((
+                    format!("street_action_sets.{}.{}", street.as_str(), round),
+                    tokens,
+                ));
+            }
+        }
+    }
+
+    let strict = request
+        .config
+        .as_ref()
+        .is_some_and(|config| config.strict_action_tokens);
+    let mut any_token = false;
+    for (field, tokens) in lists {
+        for (index, token) in tokens.iter().enumerate() {
+            any_token = true;
+            // Street sets only apply on their street, so only the flat set is checked
+            // against this spot.
+            let checked = match summary.filter(|_| field == "action_set") {
+                Some(summary) => check_action_token_in_spot(token, summary),
+                None => check_action_token(token),
+            };
+            if let Err(error) = checked {
+                let field = format!("{}[{}]", field, index);
+                issues.push(if strict {
+                    RequestIssue::blocking(field, error.reason(), error.to_string())
+                } else {
+                    RequestIssue::warning(
+                        field,
+                        error.reason(),
+                        format!("{}; it will be ignored", error),
+                    )
+                });
+            }
+        }
+    }
+    // Fixed-limit sizes come from the betting structure, so no tokens are needed.
+    let fixed_limit = summary.is_some_and(|summary| summary.limit.is_some());
+    if !any_token && !fixed_limit {
+        issues.push(RequestIssue::blocking(
+            "action_set",
+            "EMPTY_ACTION_SET",
+            "no action tokens were supplied",
+        ));
+    }
+}
+
+fn check_cards(
+    request: &SubgameRequest,
+    summary: Option<&GameStateSummary>,
+    issues: &mut Vec<RequestIssue>,
+) {
+    let mut malformed = false;
+    for (field, codes) in [
+        ("board", &request.board),
+        ("hole_cards", &request.hole_cards),
+    ] {
+        for (index, code) in codes.iter().enumerate() {
+            if let Err(error) = Card::parse(code.trim()) {
+                malformed = true;
+                issues.push(RequestIssue::blocking(
+                    format!("{}[{}]", field, index),
+                    "INVALID_CARDS",
+                    error.to_string(),
+                ));
+            }
+        }
+    }
+    if malformed {
+        return;
+    }
+
+    let street = summary.and_then(GameStateSummary::street_kind);
+    if let Err(error) = validate_board(&request.board, &request.hole_cards, street) {
+        issues.push(RequestIssue::blocking(
+            "board",
+            "INVALID_CARDS",
+            error.to_string(),
+        ));
+    }
+    let variant = summary
+        .map(GameStateSummary::game_variant)
+        .unwrap_or_default();
+    for (field, codes) in [
+        ("board", &request.board),
+        ("hole_cards", &request.hole_cards),
+    ] {
+        if let Err(error) = parse_cards(codes).and_then(|cards| check_deck(&cards, variant)) {
+            issues.push(RequestIssue::blocking(
+                field,
+                "INVALID_CARDS",
+                error.to_string(),
+            ));
+        }
+    }
+    if let (Some(street), Ok(board)) = (street, parse_cards(&request.board)) {
+        let multi_board = !request.boards.is_empty() || request.representative_flops > 0;
+        if board.is_empty() && board_size(street) > 0 && !multi_board {
+            issues.push(RequestIssue::warning(
+                "board",
+                "MISSING_BOARD",
+                format!("no board supplied for the {}", street.as_str()),
+            ));
+        }
+    }
+}
+
+fn check_boards(
+    request: &SubgameRequest,
+    summary: Option<&GameStateSummary>,
+    issues: &mut Vec<RequestIssue>,
+) {
+    let street = summary.and_then(GameStateSummary::street_kind);
+    for (index, board) in request.boards.iter().enumerate() {
+        if let Err(error) = validate_board(&board.cards, &request.hole_cards, street) {
+            issues.push(RequestIssue::blocking(
+                format!("boards[{}]", index),
+                "INVALID_CARDS",
+                error.to_string(),
+            ));
+        }
+        if board.weight < 0.0 {
+            issues.push(RequestIssue::blocking(
+                format!("boards[{}].weight", index),
+                "OUT_OF_RANGE",
+                "board weights cannot be negative",
+            ));
+        }
+    }
+    if request.runout_report && !matches!(request.board.len(), 3 | 4) {
+        issues.push(RequestIssue::blocking(
+            "runout_report",
+            "STREET_MISMATCH",
+            format!(
+                "runout reports need a flop or turn board, found {} cards",
+                request.board.len()
+            ),
+        ));
+    }
+    if request.representative_flops == 0 {
+        return;
+    }
+    if !request.boards.is_empty() {
+        issues.push(RequestIssue::warning(
+            "representative_flops",
+            "IGNORED",
+            "explicit boards were supplied; representative_flops is ignored",
+        ));
+    } else if street.is_some_and(|street| street != Street::Flop) {
+        issues.push(RequestIssue::blocking(
+            "representative_flops",
+            "STREET_MISMATCH",
+            "representative flops need a flop game state",
+        ));
+    }
+}
+
+// Integer-chip solves count every amount in chip units, which only holds up to MAX_CHIP_UNITS.
+fn check_chip_range(
+    request: &SubgameRequest,
+    summary: Option<&GameStateSummary>,
+    issues: &mut Vec<RequestIssue>,
+) {
+    let (Some(config), Some(summary)) = (
+        request
+            .config
+            .as_ref()
+            .filter(|config| config.integer_chips),
+        summary,
+    ) else {
+        return;
+    };
+    let units_per_bb = summary.chips_per_bb() / config.chip_increment.max(1) as f64;
+    let stack_bb = summary
+        .hero_effective_stack_bb()
+        .unwrap_or(request.effective_stack_bb as f64);
+    for (field, bb) in [
+        ("pot", summary.pot_in_bb()),
+        ("effective_stack_bb", stack_bb),
+    ] {
+        let units = bb * units_per_bb;
+        if !(units.is_finite() && units <= MAX_CHIP_UNITS) {
+            issues.push(RequestIssue::blocking(
+                "config.integer_chips",
+                "OUT_OF_RANGE",
+                format!(
+                    "{} is {} chip units, more than integer chips can count",
+                    field, units
+                ),
+            ));
+        }
+    }
+}
+
+fn check_config(request: &SubgameRequest, issues: &mut Vec<RequestIssue>) {
+    if request.target_exploitability < 0.0 {
+        issues.push(RequestIssue::blocking(
+            "target_exploitability",
+            "OUT_OF_RANGE",
+            "target_exploitability cannot be negative",
+        ));
+    }
+    if request
+        .config
+        .as_ref()
+        .is_some_and(|config| config.exact_river)
+        && request.board.len() != 5
+    {
+        issues.push(RequestIssue::blocking(
+            "config.exact_river",
+            "STREET_MISMATCH",
+            format!(
+                "exact river solves need a river board, found {} cards",
+                request.board.len()
+            ),
+        ));
+    }
+    if request.response_depth > 2 {
+        issues.push(RequestIssue::warning(
+            "response_depth",
+            "OUT_OF_RANGE",
+            "the tree is two decisions deep, so responses stop at depth 2",
+        ));
+    }
+    if let Some(opponent) = request.opponent.as_ref() {
+        check_opponent(opponent, issues);
+    }
+    if let Some(sizing) = request
+        .config
+        .as_ref()
+        .and_then(|config| config.sizing.as_ref())
+    {
+        if !(sizing.min_bet_bb >= 0.0 && sizing.min_bet_bb.is_finite()) {
+            issues.push(RequestIssue::blocking(
+                "config.sizing.min_bet_bb",
+                "OUT_OF_RANGE",
+                "min_bet_bb cannot be negative",
+            ));
+        }
+        if !(0.0..=1.0).contains(&sizing.all_in_threshold) {
+            issues.push(RequestIssue::blocking(
+                "config.sizing.all_in_threshold",
+                "OUT_OF_RANGE",
+                "all_in_threshold must be in (0, 1], or 0 for the server default",
+            ));
+        }
+    }
+    if let Some(rounding) = request.rounding.as_ref() {
+        if !(0.0..=1.0).contains(&rounding.step) {
+            issues.push(RequestIssue::blocking(
+                "rounding.step",
+                "OUT_OF_RANGE",
+                "rounding step must be in [0, 1]",
+            ));
+        }
+        if !(0.0..1.0).contains(&rounding.min_frequency) {
+            issues.push(RequestIssue::blocking(
+                "rounding.min_frequency",
+                "OUT_OF_RANGE",
+                "min_frequency must be in [0, 1)",
+            ));
+        }
+    }
+    if let Some(purification) = request.purification.as_ref() {
+        if Purification::parse(&purification.mode, purification.threshold).is_none() {
+            issues.push(RequestIssue::blocking(
+                "purification.mode",
+                "UNKNOWN_PURIFICATION",
+                format!(
+                    "unknown purification '{}'; expected threshold or argmax",
+                    purification.mode
+                ),
+            ));
+        } else if !(0.0..1.0).contains(&purification.threshold) {
+            issues.push(RequestIssue::blocking(
+                "purification.threshold",
+                "OUT_OF_RANGE",
+                "purification threshold must be in [0, 1)",
+            ));
+        }
+    }
+    for (index, lock) in request.locks.iter().enumerate() {
+        check_lock(index, lock, issues);
+    }
+    for (index, constraint) in request.constraints.iter().enumerate() {
+        check_constraint(index, constraint, issues);
+    }
+    let Some(config) = request.config.as_ref() else {
+        return;
+    };
+    if !(0.0..1.0).contains(&config.merge_tolerance) {
+        issues.push(RequestIssue::blocking(
+            "config.merge_tolerance",
+            "OUT_OF_RANGE",
+            "merge_tolerance must be in [0, 1)",
+        ));
+    }
+    if let Some(rake) = config.rake.as_ref() {
+        if !(0.0..=100.0).contains(&rake.percentage) || rake.cap_bb < 0.0 {
+            issues.push(RequestIssue::blocking(
+                "config.rake",
+                "OUT_OF_RANGE",
+                "rake percentage must be in [0, 100] and the cap non-negative",
+            ));
+        }
+    }
+    if !config.cfr_variant.is_empty() && CfrVariantKind::parse(&config.cfr_variant).is_none() {
+        issues.push(RequestIssue::blocking(
+            "config.cfr_variant",
+            "UNKNOWN_VARIANT",
+            format!(
+                "unknown cfr_variant '{}'; expected vanilla, cfr+, pcfr+, dcfr, sampled or deep",
+                config.cfr_variant
+            ),
+        ));
+    }
+}
+
+fn check_opponent(opponent: &OpponentModel, issues: &mut Vec<RequestIssue>) {
+    let frequency = 0.0..=1.0;
+    if !frequency.contains(&opponent.weight) {
+        issues.push(RequestIssue::blocking(
+            "opponent.weight",
+            "OUT_OF_RANGE",
+            "opponent weight must be in [0, 1]",
+        ));
+    }
+    if opponent
+        .fold_to_bet
+        .is_some_and(|fold| !frequency.contains(&fold))
+    {
+        issues.push(RequestIssue::blocking(
+            "opponent.fold_to_bet",
+            "OUT_OF_RANGE",
+            "fold_to_bet must be in [0, 1]",
+        ));
+    }
+    for (index, tendency) in opponent.tendencies.iter().enumerate() {
+        if !frequency.contains(&tendency.fold_frequency) {
+            issues.push(RequestIssue::blocking(
+                format!("opponent.tendencies[{}]", index),
+                "OUT_OF_RANGE",
+                "fold_frequency must be in [0, 1]",
+            ));
+        }
+    }
+    if opponent.weight > 0.0 && opponent.fold_to_bet.is_none() && opponent.tendencies.is_empty() {
+        issues.push(RequestIssue::warning(
+            "opponent",
+            "EMPTY_MODEL",
+            "opponent model has no tendencies; solving for an equilibrium",
+        ));
+    }
+}
+
+// Paths are only checked against the tree at solve time, where unmatched locks are skipped.
+fn check_constraint(index: usize, constraint: &StrategyConstraint, issues: &mut Vec<RequestIssue>) {
+    if constraint.actions.is_empty() {
+        issues.push(RequestIssue::blocking(
+            format!("constraints[{}].actions", index),
+            "EMPTY_CONSTRAINT",
+            "a constraint needs at least one action",
+        ));
+    }
+    let max = constraint.max_frequency.unwrap_or(1.0);
+    if !(0.0..=1.0).contains(&constraint.min_frequency)
+        || !(0.0..=1.0).contains(&max)
+        || constraint.min_frequency > max
+    {
+        issues.push(RequestIssue::blocking(
+            format!("constraints[{}]", index),
+            "OUT_OF_RANGE",
+            "constraint frequencies must satisfy 0 <= min_frequency <= max_frequency <= 1",
+        ));
+    }
+}
+
+fn check_lock(index: usize, lock: &NodeLock, issues: &mut Vec<RequestIssue>) {
+    if lock
+        .actions
+        .iter()
+        .any(|action| !action.frequency.is_finite() || action.frequency < 0.0)
+    {
+        issues.push(RequestIssue::blocking(
+            format!("locks[{}].actions", index),
+            "OUT_OF_RANGE",
+            "locked frequencies cannot be negative",
+        ));
+    } else if lock.actions.iter().all(|action| action.frequency <= 0.0) {
+        issues.push(RequestIssue::blocking(
+            format!("locks[{}].actions", index),
+            "EMPTY_LOCK",
+            "a lock needs at least one action with a positive frequency",
+        ));
+    }
+}
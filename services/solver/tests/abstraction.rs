@@ -0,0 +1,270 @@
+use solver::abstraction::all_in::{all_in_showdowns, budgeted_all_in_showdowns, runout_count};
+use solver::abstraction::blockers::card_removal;
+use solver::abstraction::matrix::{hand_class, strategy_matrix};
+use solver::abstraction::quality::{abstraction_quality, QualityOptions};
+use solver::abstraction::realization::{realized_showdowns, Realization};
+use solver::abstraction::{GameVariant, Street};
+use solver::cards::Card;
+use solver::config::SolverConfig;
+use solver::rng::SolverRng;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn cards(codes: &[&str]) -> Vec<Card> {
+    codes
+        .iter()
+        .map(|code| Card::parse(code).unwrap())
+        .collect()
+}
+
+#[test]
+fn river_buckets_split_hands_by_equity_and_report_what_they_merge() {
+    let board = cards(&["As", "Kd", "7c", "7h", "2s"]);
+    let report = |buckets: usize, collision_tolerance: f64| {
+        let options = QualityOptions {
+            collision_tolerance,
+            ..Default::default()
+        };
+        let mut rng = SolverRng::seeded(1);
+        abstraction_quality(
+            &board,
+            Street::River,
+            buckets,
+            GameVariant::Holdem,
+            &options,
+            &mut rng,
+        )
+        .remove(0)
+    };
+
+    let eight = report(8, 0.05);
+    assert_eq!(eight.street, Street::River);
+    assert_eq!(eight.combos, 47 * 46 / 2);
+    let split: usize = eight
+        .bucket_details
+        .iter()
+        .map(|bucket| bucket.combos)
+        .sum();
+    assert_eq!(split, eight.combos);
+    assert!(eight
+        .bucket_details
+        .windows(2)
+        .all(|pair| pair[0].max_equity <= pair[1].min_equity));
+    let mean: f64 = eight
+        .bucket_details
+        .iter()
+        .map(|bucket| bucket.mean_equity * bucket.combos as f64)
+        .sum::<f64>()
+        / eight.combos as f64;
+    assert!((mean - 0.5).abs() < 0.01);
+    assert!(eight.within_bucket_variance < eight.equity_variance / 10.0);
+
+    // Fewer buckets lump more together; a looser tolerance forgives more of it.
+    let two = report(2, 0.05);
+    assert!(two.within_bucket_variance > eight.within_bucket_variance);
+    assert!(two.collision_rate > eight.collision_rate);
+    assert!(report(8, 0.5).collision_rate < eight.collision_rate);
+}
+
+#[test]
+fn later_streets_are_sampled_from_the_spot_board() {
+    let options = QualityOptions {
+        runouts: 4,
+        boards: 2,
+        ..Default::default()
+    };
+    let mut rng = SolverRng::seeded(3);
+    let streets = abstraction_quality(
+        &cards(&["As", "Kd", "7c", "2h"]),
+        Street::Turn,
+        4,
+        GameVariant::Holdem,
+        &options,
+        &mut rng,
+    );
+    let names: Vec<Street> = streets.iter().map(|street| street.street).collect();
+    assert_eq!(names, [Street::Turn, Street::River]);
+    assert_eq!(streets[0].boards, 1);
+    assert_eq!(streets[1].boards, 2);
+    assert_eq!(streets[1].combos, 47 * 46 / 2);
+    assert_eq!(streets[0].combos, 48 * 47 / 2);
+}
+
+#[test]
+fn card_removal_makes_blocked_pairings_rarer() {
+    let board = cards(&["Ah", "Kh", "7h", "2c", "3d"]);
+    let removal = card_removal(&board, 8, GameVariant::Holdem).unwrap();
+    let cells = 8 * 8;
+    let mean = removal.weight.iter().sum::<f64>() / cells as f64;
+    assert!((mean - 1.0).abs() < 1e-9);
+    assert!((0..cells).all(|cell| removal.win[cell] + removal.tie[cell] <= 1.0 + 1e-9));
+    // The strongest bucket is mostly flushes, each holding two of the ten hearts left, so two of
+    // them meet less often than independent buckets would.
+    let top = removal.cell(7, 7);
+    assert!(removal.weight[top] < 1.0);
+    assert!(removal.win[removal.cell(7, 0)] > 0.99);
+    assert!(card_removal(&board[..4], 8, GameVariant::Holdem).is_none());
+}
+
+#[test]
+fn realization_discounts_weak_offsuit_hands_and_the_out_of_position_seat() {
+    let realization = Realization {
+        runouts: 8,
+        ..Default::default()
+    };
+    let neutral_seats = Realization {
+        in_position: 1.0,
+        out_of_position: 1.0,
+        ..realization
+    };
+    let raw = Realization {
+        pair: 1.0,
+        suited: 1.0,
+        offsuit: 1.0,
+        ..neutral_seats
+    };
+    let showdowns = |realization: &Realization, in_position: bool| {
+        let mut rng = SolverRng::seeded(3);
+        realized_showdowns(
+            &[],
+            8,
+            GameVariant::Holdem,
+            realization,
+            in_position,
+            &mut rng,
+        )
+        .unwrap()
+    };
+    let raw = showdowns(&raw, true);
+    let classed = showdowns(&neutral_seats, true);
+    // The bottom bucket is offsuit trash and the top one pairs and big suited hands.
+    let (bottom, top) = (raw.cell(0, 7), raw.cell(7, 0));
+    assert!(classed.win[bottom] < raw.win[bottom]);
+    assert!(classed.win[top] > raw.win[top]);
+    assert!((raw.win[raw.cell(3, 3)] - 0.5).abs() < 1e-9);
+    assert!((raw.win[bottom] + raw.win[top] - 1.0).abs() < 1e-9);
+
+    let in_position = showdowns(&realization, true);
+    let out_of_position = showdowns(&realization, false);
+    assert!((0..64).all(|cell| out_of_position.win[cell] < in_position.win[cell]));
+
+    let river = cards(&["As", "Kd", "7c", "7h", "2s"]);
+    let mut rng = SolverRng::seeded(3);
+    assert!(
+        realized_showdowns(&river, 8, GameVariant::Holdem, &realization, true, &mut rng).is_none()
+    );
+}
+
+#[test]
+fn strategy_matrix_spreads_bucket_strategies_over_hand_classes() {
+    // Bets more the stronger the bucket.
+    let strategy: Vec<Vec<f64>> = (0..8)
+        .map(|bucket| {
+            let bet = bucket as f64 / 7.0;
+            vec![bet, 1.0 - bet]
+        })
+        .collect();
+    let mut rng = SolverRng::seeded(9);
+    let matrix = strategy_matrix(
+        &[],
+        GameVariant::Holdem,
+        vec!["bet".into(), "check".into()],
+        &strategy,
+        8,
+        &mut rng,
+    );
+    assert_eq!(matrix.rows.len(), 13);
+    assert!(matrix.rows.iter().all(|row| row.len() == 13));
+    assert_eq!(
+        (hand_class(0, 1).as_str(), hand_class(1, 0).as_str()),
+        ("AKs", "AKo")
+    );
+    let cell = |hand: &str| {
+        matrix
+            .rows
+            .iter()
+            .flatten()
+            .find(|cell| cell.hand == hand)
+            .unwrap()
+    };
+    assert_eq!(
+        (cell("AA").combos, cell("AKs").combos, cell("AKo").combos),
+        (6, 4, 12)
+    );
+    let total: usize = matrix.rows.iter().flatten().map(|cell| cell.combos).sum();
+    assert_eq!(total, 1326);
+    assert!(cell("AA").frequencies[0] > 0.9);
+    assert!(cell("72o").frequencies[0] < 0.2);
+    assert!((cell("T9s").frequencies.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+    // A board takes its cards out of the grid.
+    let board = cards(&["As", "Ad", "7c"]);
+    let matrix = strategy_matrix(
+        &board,
+        GameVariant::Holdem,
+        vec!["bet".into(), "check".into()],
+        &strategy,
+        8,
+        &mut rng,
+    );
+    assert_eq!(matrix.rows[0][0].combos, 1);
+}
+
+#[test]
+fn all_in_pots_before_the_river_settle_at_runout_equity() {
+    let turn = cards(&["As", "Kd", "7c", "2h"]);
+    let showdowns = |seed: u64| {
+        let mut rng = SolverRng::seeded(seed);
+        all_in_showdowns(&turn, 4, GameVariant::Holdem, 48, &mut rng).unwrap()
+    };
+    let matrix = showdowns(1);
+    // Every river is dealt, so the seed does not matter.
+    assert_eq!(matrix.win, showdowns(2).win);
+    for (win, tie) in matrix.win.iter().zip(&matrix.tie) {
+        assert!(*win >= 0.0 && *tie >= 0.0 && win + tie <= 1.0 + 1e-9);
+    }
+    let (top, bottom) = (matrix.cell(3, 0), matrix.cell(0, 3));
+    assert!(matrix.win[top] > 0.8);
+    // Unlike the plain showdown, the bottom bucket still outdraws the top one sometimes.
+    assert!(matrix.win[bottom] > 0.0);
+    assert!((matrix.win[top] + matrix.tie[top] + matrix.win[bottom] - 1.0).abs() < 1e-9);
+
+    // Every turn and river of a flop is dealt too; fewer runouts than that are sampled.
+    let flop = cards(&["As", "Kd", "7c"]);
+    let flop_showdowns = |runouts: usize, seed: u64| {
+        let mut rng = SolverRng::seeded(seed);
+        all_in_showdowns(&flop, 4, GameVariant::Holdem, runouts, &mut rng).unwrap()
+    };
+    let every = runout_count(&flop, GameVariant::Holdem);
+    assert_eq!(every, 49 * 48 / 2);
+    assert_eq!(SolverConfig::default().abstraction.all_in_runouts, every);
+    let exact = flop_showdowns(every, 1);
+    assert_eq!(exact.win, flop_showdowns(every, 2).win);
+    assert_ne!(flop_showdowns(100, 1).win, flop_showdowns(100, 2).win);
+    let (top, bottom) = (exact.cell(3, 0), exact.cell(0, 3));
+    assert!(exact.win[top] > 0.7);
+    assert!((exact.win[top] + exact.tie[top] + exact.win[bottom] - 1.0).abs() < 1e-9);
+
+    let river = cards(&["As", "Kd", "7c", "2h", "9s"]);
+    let mut rng = SolverRng::seeded(1);
+    assert!(all_in_showdowns(&river, 4, GameVariant::Holdem, 48, &mut rng).is_none());
+
+    // Solves reuse exact matrices, and deal nothing once their time is up.
+    let budgeted = |deadline: Option<Instant>| {
+        let mut rng = SolverRng::seeded(3);
+        budgeted_all_in_showdowns(&turn, 4, GameVariant::Holdem, 48, deadline, &mut rng)
+    };
+    let first = budgeted(Some(Instant::now() + Duration::from_secs(60))).unwrap();
+    assert_eq!(first.win, matrix.win);
+    assert!(Arc::ptr_eq(&first, &budgeted(None).unwrap()));
+    let mut rng = SolverRng::seeded(3);
+    let late = budgeted_all_in_showdowns(
+        &cards(&["Qs", "Jd", "4c", "3h"]),
+        4,
+        GameVariant::Holdem,
+        48,
+        Some(Instant::now()),
+        &mut rng,
+    );
+    assert!(late.is_none());
+}
@@ -0,0 +1,138 @@
+use solver::admission::{AdmissionControl, AdmissionLimits, SolvePriority};
+use solver::error::SolverError;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tonic::Code;
+
+#[test]
+fn rejects_when_queue_is_full() {
+    let control = Arc::new(AdmissionControl::new(AdmissionLimits {
+        max_concurrent: 1,
+        max_queued: 0,
+    }));
+    let permit = control
+        .acquire(Duration::from_millis(10))
+        .expect("slot free");
+    let error = control
+        .acquire(Duration::from_millis(10))
+        .err()
+        .expect("second solve is turned away");
+    assert_eq!(error, SolverError::QueueFull { limit: 0 });
+    assert_eq!(error.code(), Code::ResourceExhausted);
+
+    drop(permit);
+    assert!(control.acquire(Duration::from_millis(10)).is_ok());
+    assert_eq!(control.running(), 0);
+}
+
+#[test]
+fn queued_solve_runs_when_a_slot_frees() {
+    let control = Arc::new(AdmissionControl::new(AdmissionLimits {
+        max_concurrent: 1,
+        max_queued: 1,
+    }));
+    let permit = control.acquire(Duration::from_millis(10)).unwrap();
+
+    let waiter = {
+        let control = control.clone();
+        thread::spawn(move || {
+            control
+                .acquire(Duration::from_secs(5))
+                .map(|permit| permit.waited())
+        })
+    };
+    while control.queued() == 0 {
+        thread::yield_now();
+    }
+    thread::sleep(Duration::from_millis(20));
+    drop(permit);
+
+    let waited = waiter.join().unwrap().expect("admitted after release");
+    assert!(waited >= Duration::from_millis(20));
+
+    let _busy = control.acquire(Duration::from_millis(10)).unwrap();
+    let error = control.acquire(Duration::from_millis(10)).err().unwrap();
+    assert!(matches!(error, SolverError::QueueTimeout { .. }));
+    assert_eq!(control.queued(), 0);
+}
+
+#[test]
+fn close_turns_away_new_and_queued_solves() {
+    let control = Arc::new(AdmissionControl::new(AdmissionLimits {
+        max_concurrent: 1,
+        max_queued: 4,
+    }));
+    let running = control.acquire(Duration::from_millis(10)).unwrap();
+    let queued = {
+        let control = control.clone();
+        thread::spawn(move || control.acquire(Duration::from_secs(5)).err())
+    };
+    while control.queued() == 0 {
+        thread::yield_now();
+    }
+
+    control.close();
+    let error = queued.join().unwrap().expect("queued solve is rejected");
+    assert_eq!(error, SolverError::ShuttingDown);
+    assert_eq!(error.code(), Code::Unavailable);
+    assert_eq!(
+        control.acquire(Duration::from_millis(10)).err(),
+        Some(SolverError::ShuttingDown)
+    );
+    assert_eq!(control.running(), 1);
+
+    drop(running);
+    assert_eq!(control.running(), 0);
+    assert!(control.is_closed());
+}
+
+#[test]
+fn higher_priority_solves_jump_the_queue() {
+    let control = Arc::new(AdmissionControl::new(AdmissionLimits {
+        max_concurrent: 1,
+        max_queued: 2,
+    }));
+    let running = control.acquire(Duration::from_millis(10)).unwrap();
+    let (admitted, order) = std::sync::mpsc::channel();
+    let queue = |priority: SolvePriority| {
+        let control = control.clone();
+        let admitted = admitted.clone();
+        thread::spawn(move || {
+            let permit = control.acquire_with_priority(Duration::from_secs(5), priority);
+            admitted.send(priority).unwrap();
+            permit.map(drop)
+        })
+    };
+    let background = queue(SolvePriority::Background);
+    while control.queued() < 1 {
+        thread::yield_now();
+    }
+    let normal = queue(SolvePriority::Normal);
+    while control.queued() < 2 {
+        thread::yield_now();
+    }
+
+    // The queue is full, so a realtime solve takes the background solve's place.
+    let realtime = queue(SolvePriority::Realtime);
+    assert_eq!(
+        background.join().unwrap().err(),
+        Some(SolverError::QueueFull { limit: 2 })
+    );
+    while control.queued() < 2 {
+        thread::yield_now();
+    }
+    assert_eq!(order.recv().unwrap(), SolvePriority::Background);
+
+    drop(running);
+    realtime.join().unwrap().unwrap();
+    normal.join().unwrap().unwrap();
+    assert_eq!(order.recv().unwrap(), SolvePriority::Realtime);
+    assert_eq!(order.recv().unwrap(), SolvePriority::Normal);
+
+    assert_eq!(
+        SolvePriority::parse("real-time"),
+        Some(SolvePriority::Realtime)
+    );
+    assert_eq!(SolvePriority::parse(""), None);
+}
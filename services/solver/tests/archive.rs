@@ -0,0 +1,76 @@
+use solver::archive::{archive_key, open_store, FileStore, StrategyArchive};
+use solver::config::{ArchiveConfig, SolverConfig};
+use solver::metrics::SolverMetrics;
+use solver::persist::SolveSnapshot;
+use solver::solver::SolverEngine;
+use solver::solver_proto::SubgameRequest;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn request(fingerprint: &str) -> SubgameRequest {
+    SubgameRequest {
+        state_fingerprint: fingerprint.into(),
+        budget_iterations: 30,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "all-in".into()],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn solved_trees_are_uploaded_after_the_response() {
+    let root = std::env::temp_dir().join(format!("solver-archive-{}", std::process::id()));
+    let config = ArchiveConfig {
+        backend: "file".into(),
+        location: root.display().to_string(),
+        ..Default::default()
+    };
+    let metrics = Arc::new(SolverMetrics::new());
+    let store = open_store(&config).unwrap().expect("file backend");
+    let engine = SolverEngine::from_config(SolverConfig::default(), metrics.clone())
+        .with_archive(StrategyArchive::start(store, &config, metrics.clone()));
+
+    let response = engine.solve(&request("btn vs bb/flop")).unwrap();
+    assert!(response.archive_key.starts_with("solves/btn_vs_bb_flop/"));
+    let archive = engine.archive().unwrap();
+    assert!(archive.flush(Duration::from_secs(10)));
+    assert_eq!(metrics.archived.load(Ordering::Relaxed), 1);
+
+    let bytes = archive.store().get(&response.archive_key).unwrap();
+    let snapshot = SolveSnapshot::decode(&bytes).unwrap();
+    assert_eq!(snapshot.meta.state_fingerprint, "btn vs bb/flop");
+    assert_eq!(snapshot.meta.seed, response.seed);
+    assert_eq!(snapshot.meta.iterations, response.iterations);
+    assert_eq!(snapshot.regrets.len(), snapshot.tree.nodes.len());
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn small_trees_and_unknown_backends_are_not_archived() {
+    let root = std::env::temp_dir().join(format!("solver-archive-small-{}", std::process::id()));
+    let config = ArchiveConfig {
+        backend: "file".into(),
+        location: root.display().to_string(),
+        min_nodes: 1_000_000,
+        ..Default::default()
+    };
+    let metrics = Arc::new(SolverMetrics::new());
+    let engine = SolverEngine::from_config(SolverConfig::default(), metrics.clone()).with_archive(
+        StrategyArchive::start(Box::new(FileStore::new(&root)), &config, metrics),
+    );
+    assert!(engine
+        .solve(&request("small"))
+        .unwrap()
+        .archive_key
+        .is_empty());
+
+    let unknown = ArchiveConfig {
+        backend: "ftp".into(),
+        ..Default::default()
+    };
+    assert!(open_store(&unknown).is_err());
+    assert!(open_store(&ArchiveConfig::default()).unwrap().is_none());
+    assert!(archive_key("", "", 1).starts_with("unnamed/"));
+    assert!(!archive_key("", "../..", 1).contains(".."));
+}
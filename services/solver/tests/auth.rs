@@ -0,0 +1,62 @@
+use solver::auth::{bearer_token, ApiKeyAuth, AuthError};
+use solver::config::ApiKeyConfig;
+use std::time::{Duration, Instant};
+use tonic::metadata::MetadataMap;
+use tonic::{Code, Request};
+
+fn key(name: &str, secret: &str, rate_per_sec: f64, burst: u32) -> ApiKeyConfig {
+    ApiKeyConfig {
+        name: name.into(),
+        key: secret.into(),
+        rate_per_sec,
+        burst,
+    }
+}
+
+#[test]
+fn checks_keys_and_rate_limits() {
+    let auth = ApiKeyAuth::new(&[key("bot", "s3cret", 1.0, 2), key("ops", "admin", 0.0, 0)]);
+    let now = Instant::now();
+
+    assert_eq!(
+        auth.authorize(None, now),
+        Err(AuthError::MissingCredentials)
+    );
+    assert_eq!(
+        auth.authorize(Some("nope"), now),
+        Err(AuthError::UnknownKey)
+    );
+    assert_eq!(auth.authorize(Some("s3cret"), now), Ok(Some("bot")));
+    assert_eq!(auth.authorize(Some("s3cret"), now), Ok(Some("bot")));
+    let limited = auth.authorize(Some("s3cret"), now).unwrap_err();
+    assert_eq!(limited.to_status().code(), Code::ResourceExhausted);
+    assert_eq!(
+        auth.authorize(Some("s3cret"), now + Duration::from_secs(1)),
+        Ok(Some("bot"))
+    );
+
+    for _ in 0..100 {
+        assert_eq!(auth.authorize(Some("admin"), now), Ok(Some("ops")));
+    }
+}
+
+#[test]
+fn interceptor_reads_bearer_metadata() {
+    let open = ApiKeyAuth::new(&[]);
+    assert!(open.intercept(Request::new(())).is_ok());
+
+    let auth = ApiKeyAuth::new(&[key("bot", "s3cret", 0.0, 0)]);
+    let status = auth.intercept(Request::new(())).unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let mut request = Request::new(());
+    request
+        .metadata_mut()
+        .insert("authorization", "Bearer s3cret".parse().unwrap());
+    assert_eq!(bearer_token(request.metadata()).as_deref(), Some("s3cret"));
+    assert!(auth.intercept(request).is_ok());
+
+    let mut metadata = MetadataMap::new();
+    metadata.insert("x-api-key", "s3cret".parse().unwrap());
+    assert_eq!(bearer_token(&metadata).as_deref(), Some("s3cret"));
+}
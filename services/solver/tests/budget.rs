@@ -0,0 +1,49 @@
+use solver::budget::{clamp_to_deadline, parse_grpc_timeout, BudgetClock};
+use std::time::Duration;
+
+#[test]
+fn parses_grpc_timeout_headers() {
+    assert_eq!(parse_grpc_timeout("250m"), Some(Duration::from_millis(250)));
+    assert_eq!(parse_grpc_timeout("3S"), Some(Duration::from_secs(3)));
+    assert_eq!(parse_grpc_timeout("2M"), Some(Duration::from_secs(120)));
+    assert_eq!(
+        parse_grpc_timeout("1500u"),
+        Some(Duration::from_micros(1500))
+    );
+    assert_eq!(parse_grpc_timeout("m"), None);
+    assert_eq!(parse_grpc_timeout("123456789m"), None);
+    assert_eq!(parse_grpc_timeout("10x"), None);
+    assert_eq!(parse_grpc_timeout("-5m"), None);
+}
+
+#[test]
+fn deadlines_clamp_the_budget_minus_slack() {
+    let slack = Duration::from_millis(25);
+    assert_eq!(
+        clamp_to_deadline(1000, Duration::from_millis(300), slack),
+        275
+    );
+    assert_eq!(clamp_to_deadline(100, Duration::from_secs(5), slack), 100);
+    // Iteration-only requests take the deadline as their time budget.
+    assert_eq!(clamp_to_deadline(0, Duration::from_millis(300), slack), 275);
+    assert_eq!(clamp_to_deadline(500, Duration::from_millis(10), slack), 0);
+    assert_eq!(clamp_to_deadline(-1, Duration::from_secs(5), slack), -1);
+}
+
+#[test]
+fn soft_deadlines_come_before_hard_ones_which_keep_the_slack() {
+    let clock = BudgetClock::with_deadlines(1000, 0.8, 50);
+    let hard = clock.deadline().unwrap();
+    let soft = clock.soft_deadline().unwrap();
+    // 950ms to the hard deadline and 760ms to the soft one, give or take the time since.
+    let gap = hard - soft;
+    assert!(gap > Duration::from_millis(185) && gap <= Duration::from_millis(190));
+
+    // A plain clock has both at the end of the budget.
+    let plain = BudgetClock::new(1000);
+    assert_eq!(plain.deadline(), plain.soft_deadline());
+    // Slack beyond the budget leaves nothing to work with; no budget has no deadlines.
+    let starved = BudgetClock::with_deadlines(20, 0.8, 50);
+    assert!(starved.deadline().unwrap() <= starved.soft_deadline().unwrap());
+    assert!(BudgetClock::with_deadlines(0, 0.8, 50).deadline().is_none());
+}
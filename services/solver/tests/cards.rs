@@ -0,0 +1,137 @@
+use solver::abstraction::{GameVariant, Street};
+use solver::cards::flops::{canonical_flops, representative_flops};
+use solver::cards::ranking::{evaluate, HandCategory};
+use solver::cards::runouts::{classify_river, river_cards, RunoutClass};
+use solver::cards::{check_deck, validate_board, Card, CardError};
+
+fn codes(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+#[test]
+fn parses_card_codes() {
+    let card = Card::parse("tD").expect("valid card");
+    assert_eq!(card.to_string(), "Td");
+    assert!(matches!(Card::parse("10d"), Err(CardError::Malformed(_))));
+    assert!(matches!(Card::parse("Ax"), Err(CardError::Malformed(_))));
+}
+
+#[test]
+fn rejects_inconsistent_boards() {
+    let board = validate_board(
+        &codes(&["As", "Kd", "7c"]),
+        &codes(&["Qh", "Qs"]),
+        Some(Street::Flop),
+    )
+    .expect("valid flop");
+    assert_eq!(board.len(), 3);
+
+    assert_eq!(
+        validate_board(&codes(&["As", "Kd", "7c"]), &codes(&["As", "Qs"]), None),
+        Err(CardError::Duplicate("As".into()))
+    );
+    assert_eq!(
+        validate_board(&codes(&["As", "Kd", "7c"]), &[], Some(Street::Turn)),
+        Err(CardError::StreetMismatch {
+            street: Street::Turn,
+            expected: 4,
+            found: 3,
+        })
+    );
+}
+
+#[test]
+fn enumerates_strategically_distinct_flops() {
+    let flops = canonical_flops(GameVariant::Holdem);
+    assert_eq!(flops.len(), 1755);
+    let total: f64 = flops.iter().map(|flop| flop.weight).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    // Classes are named by their lowest suits. A monotone flop of three ranks stands for 4 raw
+    // flops; a rainbow one for 24.
+    let weight_of = |codes: [&str; 3]| {
+        let cards: Vec<Card> = codes
+            .iter()
+            .map(|code| Card::parse(code).unwrap())
+            .collect();
+        flops
+            .iter()
+            .find(|flop| flop.cards == cards)
+            .map(|flop| flop.weight * 22100.0)
+    };
+    assert!((weight_of(["Ac", "Kc", "Qc"]).unwrap() - 4.0).abs() < 1e-9);
+    assert!((weight_of(["Ac", "Kd", "Qh"]).unwrap() - 24.0).abs() < 1e-9);
+
+    let hole = vec![Card::parse("As").unwrap(), Card::parse("Ah").unwrap()];
+    let subset = representative_flops(25, &hole, GameVariant::Holdem);
+    assert_eq!(subset.len(), 25);
+    let total: f64 = subset.iter().map(|flop| flop.weight).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    assert!(subset
+        .iter()
+        .all(|flop| flop.cards.iter().all(|card| !hole.contains(card))));
+}
+
+#[test]
+fn classifies_river_cards() {
+    let cards = |values: &[&str]| -> Vec<Card> {
+        values
+            .iter()
+            .map(|code| Card::parse(code).unwrap())
+            .collect()
+    };
+    let turn = cards(&["Ah", "9h", "7c", "2d"]);
+    let river = |code: &str| classify_river(&turn, Card::parse(code).unwrap());
+    assert_eq!(river("Kh"), RunoutClass::FlushCompleting);
+    // Pairing the board with a third heart is still the flush card.
+    assert_eq!(river("7h"), RunoutClass::FlushCompleting);
+    assert_eq!(river("9s"), RunoutClass::Pairing);
+    assert_eq!(river("Ks"), RunoutClass::Brick);
+
+    let rivers = river_cards(&turn, &cards(&["Kd", "Kc"]), GameVariant::Holdem);
+    assert_eq!(rivers.len(), 46);
+    let flush = rivers
+        .iter()
+        .filter(|card| classify_river(&turn, **card) == RunoutClass::FlushCompleting)
+        .count();
+    assert_eq!(flush, 11);
+}
+
+#[test]
+fn short_deck_changes_the_deck_and_hand_rankings() {
+    let cards = |values: &[&str]| -> Vec<Card> {
+        values
+            .iter()
+            .map(|code| Card::parse(code).unwrap())
+            .collect()
+    };
+    assert_eq!(GameVariant::ShortDeck.deck().len(), 36);
+    assert_eq!(GameVariant::parse("6+"), Some(GameVariant::ShortDeck));
+    assert!(matches!(
+        check_deck(&cards(&["As", "5d"]), GameVariant::ShortDeck),
+        Err(CardError::NotInDeck { .. })
+    ));
+    assert!(check_deck(&cards(&["As", "5d"]), GameVariant::Holdem).is_ok());
+
+    let flush = cards(&["Ah", "Jh", "9h", "7h", "6h", "Kc", "Kd"]);
+    let full_house = cards(&["Ks", "Kh", "Kd", "9c", "9d", "7s", "6c"]);
+    let rank = |hand: &[Card], variant| evaluate(hand, variant).unwrap();
+    assert_eq!(
+        rank(&flush, GameVariant::Holdem).category,
+        HandCategory::Flush
+    );
+    assert!(rank(&full_house, GameVariant::Holdem) > rank(&flush, GameVariant::Holdem));
+    assert!(rank(&flush, GameVariant::ShortDeck) > rank(&full_house, GameVariant::ShortDeck));
+
+    // The ace plays low under the lowest rank of the deck.
+    let short_wheel = cards(&["As", "6d", "7h", "8c", "9s"]);
+    assert_eq!(
+        rank(&short_wheel, GameVariant::ShortDeck).category,
+        HandCategory::Straight
+    );
+    assert_eq!(
+        rank(&short_wheel, GameVariant::Holdem).category,
+        HandCategory::HighCard
+    );
+    // Nine ranks: 5 suit patterns per three ranks, 2 per pair, 1 per trips.
+    assert_eq!(canonical_flops(GameVariant::ShortDeck).len(), 420 + 144 + 9);
+}
@@ -0,0 +1,978 @@
+use solver::abstraction::blockers::card_removal;
+use solver::abstraction::realization::{realized_showdowns, Realization};
+use solver::abstraction::showdown::ShowdownMatrix;
+use solver::abstraction::{
+    parse_action_set, BlindSummary, GameStateSummary, GameVariant, LimitSummary,
+};
+use solver::cards::Card;
+use solver::cfr::aivat::{evaluate_hands, HandError, PlayedHand};
+use solver::cfr::backend::{CfrBackend, CfrBackendKind, CpuBackend};
+use solver::cfr::compressed::{
+    quantize_row, CompressedStrategies, QuantizedLayout, QuantizedStrategy, QUANT_SCALE,
+};
+use solver::cfr::constraints::{RootConstraints, StrategyConstraint};
+use solver::cfr::deep::{DeepCfrConfig, DeepCfrSolver};
+use solver::cfr::diff::diff_strategies;
+use solver::cfr::opponent::OpponentModel;
+use solver::cfr::purify::{
+    purify_hands, purify_row, round_frequencies, FrequencyRounding, Purification,
+};
+use solver::cfr::refine::{coarse_groups, split_groups, RefinementConfig};
+use solver::cfr::variant::{CfrVariant, CfrVariantKind, Discount};
+use solver::cfr::{range_stats, run_cfr_with_config, simd, ActionStat, CfrConfig, CfrSolver};
+use solver::game_tree::{GameTree, NodeKind, HERO};
+use solver::rng::SolverRng;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn river_tree() -> GameTree {
+    let summary = GameStateSummary {
+        pot: 10.0,
+        street: "river".into(),
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let specs = parse_action_set(
+        &[
+            "pot:0.33".to_string(),
+            "pot:0.75".to_string(),
+            "pot:1.5".to_string(),
+            "all-in".to_string(),
+        ],
+        &summary,
+        100.0,
+    );
+    GameTree::from_action_specs(&specs, &summary, 100.0)
+}
+
+#[test]
+fn regret_pruning_skips_branches_without_changing_strategy() {
+    let tree = river_tree();
+    let mut unpruned_config = CfrConfig::new(1000);
+    unpruned_config.pruning.enabled = false;
+    let mut unpruned = CfrSolver::new(&tree, &unpruned_config);
+    unpruned.run(1000);
+
+    let mut pruned = CfrSolver::new(&tree, &CfrConfig::new(1000));
+    pruned.run(1000);
+
+    assert_eq!(unpruned.pruned_branches(), 0);
+    assert!(pruned.pruned_branches() > 0);
+    for (a, b) in unpruned.root_stats().iter().zip(pruned.root_stats()) {
+        assert!((a.frequency - b.frequency).abs() < 0.02);
+    }
+}
+
+#[test]
+fn strongest_buckets_bet_for_value() {
+    let tree = river_tree();
+    let mut solver = CfrSolver::new(&tree, &CfrConfig::new(1000));
+    solver.run(1000);
+    let strategy = solver.average_strategy(0);
+    let nuts = strategy.last().expect("buckets");
+    let middle = &strategy[strategy.len() / 2];
+    // The best hands prefer large sizes; medium hands take the smallest bet.
+    assert!(nuts[0] < 0.1);
+    assert!(middle[0] > 0.9);
+}
+
+#[test]
+fn warm_start_begins_from_prior_strategy() {
+    let tree = river_tree();
+    let mut cold = CfrSolver::new(&tree, &CfrConfig::new(200));
+    cold.run(200);
+    let prior: Vec<f64> = cold.root_stats().iter().map(|s| s.frequency).collect();
+
+    let mut warm = CfrSolver::new(&tree, &CfrConfig::new(0));
+    warm.warm_start(0, &prior, 50.0);
+    let seeded: Vec<f64> = warm.root_stats().iter().map(|s| s.frequency).collect();
+    for (a, b) in prior.iter().zip(&seeded) {
+        assert!((a - b).abs() < 1e-9);
+    }
+
+    warm.run(20);
+    let mut fresh = CfrSolver::new(&tree, &CfrConfig::new(20));
+    fresh.run(20);
+    let distance =
+        |stats: Vec<f64>| -> f64 { stats.iter().zip(&prior).map(|(a, b)| (a - b).abs()).sum() };
+    let warm_freqs = warm.root_stats().iter().map(|s| s.frequency).collect();
+    let fresh_freqs = fresh.root_stats().iter().map(|s| s.frequency).collect();
+    assert!(distance(warm_freqs) < distance(fresh_freqs));
+}
+
+#[test]
+fn stops_early_once_target_exploitability_is_reached() {
+    let tree = river_tree();
+    let mut solver = CfrSolver::new(&tree, &CfrConfig::new(1));
+    solver.run(1);
+    let early = solver.exploitability();
+    solver.run(500);
+    assert!(solver.exploitability() < early);
+
+    let mut config = CfrConfig::new(5000);
+    config.target_exploitability = 0.05;
+    let outcome = run_cfr_with_config(&tree, &config);
+    assert!(outcome.converged);
+    assert!(outcome.exploitability <= 0.05);
+    assert!(outcome.iterations < 5000);
+}
+
+#[test]
+fn seeded_rng_streams_are_reproducible() {
+    let mut a = SolverRng::seeded(7);
+    let mut b = SolverRng::seeded(7);
+    let draws_a: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+    let draws_b: Vec<u64> = (0..8).map(|_| b.next_u64()).collect();
+    assert_eq!(draws_a, draws_b);
+    assert_ne!(SolverRng::seeded(8).next_u64(), draws_a[0]);
+    let unit = a.next_f64();
+    assert!((0.0..1.0).contains(&unit));
+    assert_eq!(a.sample_index(&[0.0, 0.0, 1.0]), 2);
+}
+
+#[test]
+fn quantized_rows_stay_within_error_bound() {
+    let mut rng = SolverRng::seeded(309);
+    for width in 1..12 {
+        let row: Vec<f64> = (0..width)
+            .map(|_| {
+                let value = rng.next_f64();
+                // Leave some actions at exactly zero so sparsity is exercised.
+                if value < 0.3 {
+                    0.0
+                } else {
+                    value
+                }
+            })
+            .collect();
+        let total: f64 = row.iter().sum();
+        let weights = quantize_row(&row);
+        assert_eq!(
+            weights.iter().map(|weight| *weight as u32).sum::<u32>(),
+            QUANT_SCALE
+        );
+        for (value, weight) in row.iter().zip(&weights) {
+            let expected = if total > 0.0 {
+                value / total
+            } else {
+                1.0 / width as f64
+            };
+            let error = (expected - *weight as f64 / QUANT_SCALE as f64).abs();
+            assert!(
+                error < 1.0 / QUANT_SCALE as f64,
+                "error {} too large",
+                error
+            );
+        }
+    }
+}
+
+#[test]
+fn compressed_strategies_round_trip_the_average_strategy() {
+    let tree = river_tree();
+    let config = CfrConfig::new(200);
+    let mut solver = CfrSolver::new(&tree, &config);
+    solver.run(200);
+    let compressed = CompressedStrategies::from_solver(&solver);
+
+    let mut dense_bytes = 0;
+    for (index, node) in tree.nodes.iter().enumerate() {
+        let Some(strategy) = compressed.node(index) else {
+            assert!(node.is_terminal());
+            continue;
+        };
+        let rows = solver.average_strategy(index);
+        dense_bytes += rows.len() * strategy.actions * std::mem::size_of::<f64>();
+        for (bucket, row) in rows.iter().enumerate() {
+            for (action, probability) in row.iter().enumerate() {
+                let error = (probability - strategy.probability(bucket, action)).abs();
+                assert!(error < 1.0 / QUANT_SCALE as f64);
+            }
+            let restored = strategy.row(bucket);
+            assert!((restored.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        }
+    }
+    let stored: usize = compressed
+        .nodes
+        .iter()
+        .flatten()
+        .map(|strategy| strategy.memory_bytes())
+        .sum();
+    assert!(stored * 4 <= dense_bytes, "{} vs {}", stored, dense_bytes);
+
+    let sparse = QuantizedStrategy::from_rows(&[
+        vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+        vec![0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.5],
+    ]);
+    assert!(matches!(sparse.layout, QuantizedLayout::Sparse { .. }));
+    assert_eq!(sparse.weight(0, 7), QUANT_SCALE as u16);
+    assert_eq!(sparse.probability(1, 3), 0.0);
+}
+
+#[test]
+fn every_variant_shares_the_traversal_and_converges() {
+    let tree = river_tree();
+    for kind in [
+        CfrVariantKind::Vanilla,
+        CfrVariantKind::CfrPlus,
+        CfrVariantKind::PredictiveCfrPlus,
+        CfrVariantKind::Discounted,
+        CfrVariantKind::Sampled,
+    ] {
+        let mut config = CfrConfig::new(500);
+        config.variant = kind;
+        config.seed = 3;
+        let mut solver = CfrSolver::new(&tree, &config);
+        solver.run(10);
+        let early = solver.exploitability();
+        solver.run(490);
+        assert_eq!(solver.variant().name(), kind.as_str());
+        assert!(
+            solver.exploitability() < early,
+            "{} did not improve",
+            kind.as_str()
+        );
+        assert!(solver.exploitability() < 0.1, "{}", kind.as_str());
+    }
+}
+
+#[test]
+fn custom_variants_plug_in_without_touching_the_solver() {
+    struct NoAveraging;
+    impl CfrVariant for NoAveraging {
+        fn name(&self) -> &'static str {
+            "last-iterate"
+        }
+        fn strategy_weight(&self, iteration: usize) -> f64 {
+            // Only the final iterations count; earlier sums are wiped by the discount.
+            iteration as f64
+        }
+        fn discount(&self, _iteration: usize) -> Discount {
+            Discount {
+                strategy: 0.0,
+                ..Discount::NONE
+            }
+        }
+    }
+
+    let tree = river_tree();
+    let mut solver = CfrSolver::with_variant(&tree, &CfrConfig::new(50), Box::new(NoAveraging));
+    solver.run(50);
+    assert_eq!(solver.variant().name(), "last-iterate");
+    for row in solver.average_strategy(0) {
+        assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+    assert!(CfrVariantKind::parse("DCFR") == Some(CfrVariantKind::Discounted));
+    assert!(CfrVariantKind::parse("cfr-plus") == Some(CfrVariantKind::CfrPlus));
+}
+
+#[test]
+fn simd_kernels_match_scalar_loops_including_the_tail() {
+    let a: Vec<f64> = (0..11).map(|i| i as f64 * 0.5 - 2.0).collect();
+    let b: Vec<f64> = (0..11).map(|i| 1.0 - i as f64 * 0.25).collect();
+
+    let mut sum = a.clone();
+    simd::add(&mut sum, &b);
+    let mut floored = a.clone();
+    simd::add_floored(&mut floored, &b);
+    let mut product = vec![1.0; 11];
+    simd::add_scaled_product(&mut product, &a, &b, 2.0);
+    for i in 0..11 {
+        assert_eq!(sum[i], a[i] + b[i]);
+        assert_eq!(floored[i], (a[i] + b[i]).max(0.0));
+        assert_eq!(product[i], 1.0 + 2.0 * a[i] * b[i]);
+    }
+
+    // Two actions over three buckets, action-major.
+    let regrets = [3.0, -1.0, 0.0, 1.0, -2.0, 0.0];
+    let mut strategy = [0.0; 6];
+    simd::regret_match(&regrets, 2, 3, &mut strategy);
+    assert_eq!(strategy, [0.75, 0.5, 0.5, 0.25, 0.5, 0.5]);
+}
+
+#[test]
+fn cpu_backend_matches_every_table_in_one_batch() {
+    let strategies = CpuBackend.regret_match_all(&[(&[2.0, -1.0, 2.0, 1.0], 2), (&[], 0)], 2);
+    assert_eq!(
+        strategies,
+        vec![vec![0.5, 0.0, 0.5, 1.0], Vec::<f64>::new()]
+    );
+
+    let tree = river_tree();
+    let solver = CfrSolver::new(&tree, &CfrConfig::new(10));
+    assert_eq!(solver.backend().name(), "cpu");
+    assert_eq!(CfrBackendKind::parse("AUTO"), Some(CfrBackendKind::Auto));
+    assert_eq!(CfrBackendKind::parse("tpu"), None);
+    // Without the gpu feature every kind resolves to the CPU.
+    #[cfg(not(feature = "gpu"))]
+    assert_eq!(CfrBackendKind::Gpu.select().name(), "cpu");
+}
+
+// Skipped where no adapter is found; software adapters such as llvmpipe count.
+#[cfg(feature = "gpu")]
+#[test]
+fn gpu_iterations_match_the_cpu_traversal() {
+    let tree = river_tree();
+    let buckets = 6;
+    // Card removal of a sort: the same bucket meets itself half as often and always splits.
+    let cells = buckets * buckets;
+    let mut matrix = ShowdownMatrix {
+        buckets,
+        weight: vec![1.0; cells],
+        win: vec![0.0; cells],
+        tie: vec![0.0; cells],
+    };
+    for hero in 0..buckets {
+        for villain in 0..buckets {
+            let cell = matrix.cell(hero, villain);
+            match hero.cmp(&villain) {
+                std::cmp::Ordering::Greater => matrix.win[cell] = 0.9,
+                std::cmp::Ordering::Equal => {
+                    matrix.weight[cell] = 0.5;
+                    matrix.tie[cell] = 1.0;
+                }
+                std::cmp::Ordering::Less => matrix.win[cell] = 0.1,
+            }
+        }
+    }
+    let matrix = Arc::new(matrix);
+    for variant in [
+        CfrVariantKind::Vanilla,
+        CfrVariantKind::CfrPlus,
+        CfrVariantKind::PredictiveCfrPlus,
+        CfrVariantKind::Discounted,
+    ] {
+        for showdowns in [None, Some(matrix.clone())] {
+            let mut config = CfrConfig::new(100);
+            config.variant = variant;
+            config.buckets = buckets;
+            config.pruning.enabled = false;
+            config.bucket_weights = vec![1.0, 2.0, 1.0, 1.0, 2.0, 1.0];
+            config.showdowns = showdowns.clone();
+            let mut cpu = CfrSolver::new(&tree, &config);
+            config.backend = CfrBackendKind::Gpu;
+            let mut gpu = CfrSolver::new(&tree, &config);
+            if gpu.backend().name() != "gpu" {
+                return;
+            }
+            assert!(gpu.runs_on_device() && !cpu.runs_on_device());
+            cpu.run(100);
+            gpu.run(100);
+            assert_eq!(gpu.iterations(), 100);
+            for node in 0..tree.nodes.len() {
+                for (ours, theirs) in gpu
+                    .average_strategy(node)
+                    .iter()
+                    .flatten()
+                    .zip(cpu.average_strategy(node).iter().flatten())
+                {
+                    assert!(
+                        (ours - theirs).abs() < 1e-3,
+                        "{variant:?} node {node}: {ours} vs {theirs}"
+                    );
+                }
+            }
+            assert!((gpu.exploitability() - cpu.exploitability()).abs() < 1e-4);
+        }
+    }
+
+    // Sampling walks one opponent action at a time, so it traverses on the CPU.
+    let mut config = CfrConfig::new(10);
+    config.variant = CfrVariantKind::Sampled;
+    config.backend = CfrBackendKind::Gpu;
+    assert!(!CfrSolver::new(&tree, &config).runs_on_device());
+}
+
+#[test]
+fn deep_cfr_learns_to_call_with_strong_hands() {
+    let tree = river_tree();
+    let mut config = CfrConfig::new(0);
+    config.buckets = 8;
+    config.seed = 11;
+    let mut deep = DeepCfrSolver::new(
+        &tree,
+        &config,
+        DeepCfrConfig {
+            iterations: 20,
+            ..DeepCfrConfig::default()
+        },
+    );
+    deep.run();
+    assert_eq!(deep.iterations(), 20);
+
+    // Facing the smallest bet: call is the second action.
+    let NodeKind::Decision { children, .. } = &tree.nodes[0].kind else {
+        panic!("root is a decision");
+    };
+    let facing = deep.average_strategy(children[0]);
+    assert!(facing[7][1] > facing[0][1]);
+    for row in &facing {
+        assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    let mut config = CfrConfig::new(320);
+    config.variant = CfrVariantKind::parse("deep").unwrap();
+    let outcome = run_cfr_with_config(&tree, &config);
+    assert_eq!(outcome.iterations, 10);
+    assert!(outcome.exploitability.is_finite());
+    assert!(outcome.strategies.node(0).is_some());
+}
+
+#[test]
+fn best_response_to_an_over_folder_bluffs_cheapest() {
+    let tree = river_tree();
+    let mut config = CfrConfig::new(400);
+    config.buckets = 8;
+    config.opponent = Some(OpponentModel::from_fold_frequencies(&tree, 8, 1.0, |_| {
+        Some(0.9)
+    }));
+    let mut exploit = CfrSolver::new(&tree, &config);
+    exploit.run(400);
+    let mut reference = CfrSolver::new(
+        &tree,
+        &CfrConfig {
+            opponent: None,
+            ..config.clone()
+        },
+    );
+    reference.run(400);
+
+    // Against a villain who folds 90% to any bet, hero's weakest hands bluff the cheapest size
+    // almost always; the equilibrium spreads them across sizes.
+    let weakest = |solver: &CfrSolver| solver.average_strategy(0)[0][0];
+    assert!(weakest(&exploit) > 0.9);
+    assert!(weakest(&exploit) > weakest(&reference));
+
+    let model = config.opponent.as_ref().unwrap();
+    let NodeKind::Decision { children, .. } = &tree.nodes[0].kind else {
+        panic!("root is a decision");
+    };
+    let facing = model.node(children[0]).expect("villain node is modelled");
+    // Fold share per bucket: the bottom 90% of the range folds.
+    assert_eq!(&facing[..7], &[1.0; 7]);
+    assert!((facing[7] - 0.2).abs() < 1e-9);
+    assert!((facing[15] - 0.8).abs() < 1e-9);
+    assert!(model.node(0).is_none());
+}
+
+#[test]
+fn locked_nodes_hold_their_strategy_and_the_rest_best_responds() {
+    let tree = river_tree();
+    let NodeKind::Decision { children, .. } = &tree.nodes[0].kind else {
+        panic!("root is a decision");
+    };
+    let mut config = CfrConfig::new(400);
+    config.buckets = 8;
+    // Villain never folds to a bet; the weakest bucket is locked separately to always fold
+    // facing the smallest size.
+    for &child in children {
+        assert!(config.locks.lock(child, 8, &[0.0, 1.0], &[]));
+    }
+    assert!(config.locks.lock(children[0], 8, &[2.0, 0.0], &[0]));
+    assert!(!config.locks.lock(children[1], 8, &[0.0, 0.0], &[]));
+
+    let mut solver = CfrSolver::new(&tree, &config);
+    solver.run(400);
+
+    let facing_small = solver.average_strategy(children[0]);
+    assert_eq!(facing_small[0], vec![1.0, 0.0]);
+    assert_eq!(facing_small[1], vec![0.0, 1.0]);
+    assert!(solver
+        .regrets(children[0])
+        .iter()
+        .all(|regret| *regret == 0.0));
+
+    // Against a calling station hero stops bluffing big and value-bets the nuts all-in.
+    let root = solver.average_strategy(0);
+    let all_in = root[7].len() - 1;
+    assert!(root[0][0] > 0.9);
+    assert!(root[7][all_in] > 0.9);
+}
+
+#[test]
+fn root_evs_are_real_values_relative_to_the_decision() {
+    let tree = river_tree();
+    let mut config = CfrConfig::new(300);
+    config.buckets = 8;
+    let mut solver = CfrSolver::new(&tree, &config);
+    solver.run(300);
+
+    let stats = solver.root_stats();
+    let mixed: f64 = stats.iter().map(|stat| stat.frequency * stat.ev).sum();
+    assert!((mixed - solver.expected_value(HERO)).abs() < 1e-9);
+    // Hero cannot lose more than the largest bet or win more than pot plus that bet.
+    let largest = stats.iter().map(|stat| stat.amount).fold(0.0, f64::max);
+    assert!(stats
+        .iter()
+        .all(|stat| stat.ev >= -largest && stat.ev <= tree.pot_bb + largest));
+
+    let hands = solver.root_hand_stats();
+    assert!(hands[7].ev > hands[0].ev);
+    assert!(hands[0].actions[0].equity < hands[7].actions[0].equity);
+    // Whichever action carries more of the strong hands has the higher equity.
+    let all_in = stats.len() - 1;
+    assert!(stats[all_in].equity > stats[0].equity);
+}
+
+#[test]
+fn bet_diagnostics_match_the_textbook_ratios() {
+    let tree = river_tree();
+    let mut config = CfrConfig::new(300);
+    config.buckets = 8;
+    let outcome = run_cfr_with_config(&tree, &config);
+    assert_eq!(outcome.diagnostics.len(), tree.actions.len());
+
+    // Three-quarter pot into 10: villain calls 7.5 to win 25.
+    let three_quarters = &outcome.diagnostics[1];
+    assert_eq!(three_quarters.label, "pot-0.75");
+    assert!((three_quarters.pot_odds - 0.3).abs() < 1e-9);
+    assert!((three_quarters.required_defense - 10.0 / 17.5).abs() < 1e-9);
+    assert!((three_quarters.indifferent_bluff_to_value - 7.5 / 17.5).abs() < 1e-9);
+    for bet in &outcome.diagnostics {
+        assert!((0.0..=1.0).contains(&bet.defense_frequency));
+        assert!(bet.bluff_to_value >= 0.0);
+        let shares = bet.value_share + bet.merged_share + bet.bluff_share;
+        assert!((shares - 1.0).abs() < 1e-9, "{:?}", bet);
+    }
+    // Polarized on the river: value and air, hardly any merged hands.
+    assert!(three_quarters.value_share > 0.5 && three_quarters.bluff_share > 0.2);
+    assert!(three_quarters.merged_share < 0.1);
+}
+
+#[test]
+fn strategy_diffs_match_nodes_by_path_and_measure_distance() {
+    let tree = river_tree();
+    let solve = |tree: &GameTree, iterations: usize| {
+        let mut config = CfrConfig::new(iterations);
+        config.seed = 3;
+        run_cfr_with_config(tree, &config).strategies
+    };
+    let converged = solve(&tree, 600);
+    let same = diff_strategies(&tree, &converged, &tree, &converged);
+    assert!(same.unmatched.is_empty());
+    assert_eq!(same.nodes[0].path, "");
+    assert_eq!(same.max_total_variation(), 0.0);
+    assert_eq!(same.nodes[0].reach, 1.0);
+
+    let early = solve(&tree, 5);
+    let diff = diff_strategies(&tree, &early, &tree, &converged);
+    assert!(diff.max_total_variation() > 0.0);
+    assert!(diff.weighted_total_variation() <= diff.max_total_variation());
+    for node in &diff.nodes {
+        let frequencies: f64 = node.actions.iter().map(|action| action.baseline).sum();
+        assert!((frequencies - 1.0).abs() < 1e-3);
+        // Bucket-level distance can only hide less than the aggregate.
+        assert!(node.bucket_total_variation.unwrap() >= node.total_variation - 1e-9);
+    }
+
+    // Dropping sizes leaves their lines unmatched but still compares the shared ones.
+    let summary = GameStateSummary {
+        pot: 10.0,
+        street: "river".into(),
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let specs = parse_action_set(&["pot:0.75".to_string()], &summary, 100.0);
+    let coarse = GameTree::from_action_specs(&specs, &summary, 100.0);
+    let diff = diff_strategies(&tree, &converged, &coarse, &solve(&coarse, 600));
+    assert!(!diff.unmatched.is_empty());
+    assert!(diff.nodes.len() > 1);
+    assert!(diff.nodes[0]
+        .actions
+        .iter()
+        .any(|action| action.candidate == 0.0 && action.baseline > 0.0));
+}
+
+#[test]
+fn predictive_cfr_plus_converges_faster_than_cfr_plus() {
+    let tree = river_tree();
+    let exploitability_after = |kind: CfrVariantKind, iterations: usize| {
+        let mut config = CfrConfig::new(iterations);
+        config.variant = kind;
+        let mut solver = CfrSolver::new(&tree, &config);
+        solver.run(iterations);
+        solver.exploitability()
+    };
+    // Predictions pay off once regrets settle; by a few hundred iterations PCFR+ is well ahead.
+    assert!(
+        exploitability_after(CfrVariantKind::PredictiveCfrPlus, 400)
+            < exploitability_after(CfrVariantKind::CfrPlus, 400)
+    );
+}
+
+#[test]
+fn sampling_baselines_cut_the_noise_of_small_budgets() {
+    // Fixed-limit raise wars put sampled nodes under sampled nodes, where the noise compounds.
+    let summary = GameStateSummary {
+        pot: 10.0,
+        street: "river".into(),
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        limit: Some(LimitSummary {
+            cap: 4,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let tree = GameTree::from_action_specs(&[], &summary, 100.0);
+    let mean_exploitability = |baseline: bool| {
+        (1..=8)
+            .map(|seed| {
+                let mut config = CfrConfig::new(120);
+                config.variant = CfrVariantKind::Sampled;
+                config.sampling_baseline = baseline;
+                config.seed = seed;
+                let mut solver = CfrSolver::new(&tree, &config);
+                solver.run(120);
+                solver.exploitability()
+            })
+            .sum::<f64>()
+            / 8.0
+    };
+    assert!(mean_exploitability(true) < mean_exploitability(false));
+}
+
+#[test]
+fn aivat_estimates_are_unbiased_with_less_variance_than_outcomes() {
+    let tree = river_tree();
+    let mut config = CfrConfig::new(300);
+    config.buckets = 8;
+    let mut solver = CfrSolver::new(&tree, &config);
+    solver.run(300);
+
+    // Deal and play hands from the solved strategies; a terminal's value against one villain
+    // bucket is exactly what the hand pays.
+    let mut rng = SolverRng::seeded(11);
+    let mut villain_buckets = Vec::new();
+    let hands: Vec<PlayedHand> = (0..400)
+        .map(|_| {
+            let buckets = [rng.below(8), rng.below(8)];
+            villain_buckets.push(buckets[1]);
+            let mut node = 0;
+            let mut line = Vec::new();
+            while let NodeKind::Decision { player, children } = &tree.nodes[node].kind {
+                let action = rng.sample_index(&solver.average_strategy(node)[buckets[*player]]);
+                node = children[action];
+                line.push(tree.nodes[node].label.clone());
+            }
+            let mut villain = vec![0.0; 8];
+            villain[buckets[1]] = 1.0;
+            PlayedHand {
+                line,
+                player: HERO,
+                bucket: buckets[0],
+                opponent_bucket: None,
+                outcome: solver.values_at(node, HERO, &villain)[buckets[0]],
+            }
+        })
+        .collect();
+
+    let report = evaluate_hands(&solver, &hands).unwrap();
+    assert_eq!(report.hands.len(), 400);
+    assert!(report.estimate_std_error < report.outcome_std_error);
+    let ev = solver.expected_value(HERO);
+    assert!((report.mean_estimate - ev).abs() < 3.0 * report.estimate_std_error);
+    // With villain's cards shown, the showdown luck comes out too.
+    let shown: Vec<PlayedHand> = hands
+        .iter()
+        .zip(&villain_buckets)
+        .map(|(hand, &villain)| PlayedHand {
+            opponent_bucket: Some(villain),
+            ..hand.clone()
+        })
+        .collect();
+    let shown = evaluate_hands(&solver, &shown).unwrap();
+    assert!(shown.estimate_std_error < report.estimate_std_error / 2.0);
+    assert!((shown.mean_estimate - ev).abs() < 3.0 * shown.estimate_std_error);
+
+    let unknown = PlayedHand {
+        line: vec!["pot-9".into()],
+        ..hands[0].clone()
+    };
+    assert_eq!(
+        evaluate_hands(&solver, &[hands[0].clone(), unknown]).unwrap_err(),
+        (
+            1,
+            HandError::UnknownAction {
+                label: "pot-9".into()
+            }
+        )
+    );
+}
+
+#[test]
+fn card_removal_changes_the_river_strategy_and_still_converges() {
+    let tree = river_tree();
+    let board: Vec<Card> = ["Ah", "Kh", "7h", "2c", "3d"]
+        .iter()
+        .map(|code| Card::parse(code).unwrap())
+        .collect();
+    let solve = |removal: Option<ShowdownMatrix>, iterations: usize| {
+        let mut config = CfrConfig::new(iterations);
+        config.buckets = 8;
+        config.showdowns = removal.map(Arc::new);
+        let mut solver = CfrSolver::new(&tree, &config);
+        solver.run(iterations);
+        (solver.exploitability(), solver.average_strategy(0))
+    };
+    let removal = card_removal(&board, 8, GameVariant::Holdem);
+    let (early, _) = solve(removal.clone(), 20);
+    let (late, strategy) = solve(removal, 300);
+    let (_, plain) = solve(None, 300);
+    assert!(late < early / 2.0);
+    assert!(strategy != plain);
+}
+
+#[test]
+fn equity_realization_changes_a_flop_strategy_and_still_converges() {
+    let summary = GameStateSummary {
+        pot: 10.0,
+        street: "flop".into(),
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let specs = parse_action_set(
+        &["pot:0.5".to_string(), "pot:1".to_string()],
+        &summary,
+        50.0,
+    );
+    let tree = GameTree::from_action_specs(&specs, &summary, 50.0);
+    let board: Vec<Card> = ["Qs", "9d", "4c"]
+        .iter()
+        .map(|code| Card::parse(code).unwrap())
+        .collect();
+    let realization = Realization {
+        runouts: 8,
+        ..Default::default()
+    };
+    let mut rng = SolverRng::seeded(5);
+    let realized = realized_showdowns(
+        &board,
+        8,
+        GameVariant::Holdem,
+        &realization,
+        summary.hero_has_position(),
+        &mut rng,
+    );
+    let solve = |showdowns: Option<ShowdownMatrix>, iterations: usize| {
+        let mut config = CfrConfig::new(iterations);
+        config.buckets = 8;
+        config.showdowns = showdowns.map(Arc::new);
+        let mut solver = CfrSolver::new(&tree, &config);
+        solver.run(iterations);
+        (solver.exploitability(), solver.average_strategy(0))
+    };
+    let (early, _) = solve(realized.clone(), 20);
+    let (late, strategy) = solve(realized, 300);
+    let (_, plain) = solve(None, 300);
+    assert!(late < early / 2.0);
+    assert!(strategy != plain);
+}
+
+#[test]
+fn purification_drops_small_mixes_and_keeps_the_range_consistent() {
+    let mut row = vec![0.03, 0.57, 0.4];
+    purify_row(&mut row, Purification::Threshold(0.05));
+    assert!(row[0] == 0.0 && (row[1] - 0.57 / 0.97).abs() < 1e-12);
+    purify_row(&mut row, Purification::Argmax);
+    assert_eq!(row, vec![0.0, 1.0, 0.0]);
+    let mut spread = vec![0.25; 4];
+    purify_row(&mut spread, Purification::Threshold(0.3));
+    assert_eq!(spread, vec![1.0, 0.0, 0.0, 0.0]);
+    assert_eq!(
+        Purification::parse("threshold", 0.0),
+        Some(Purification::Threshold(0.05))
+    );
+    assert_eq!(Purification::parse("round", 0.0), None);
+
+    let tree = river_tree();
+    let mut config = CfrConfig::new(200);
+    config.buckets = 8;
+    let mut solver = CfrSolver::new(&tree, &config);
+    solver.run(200);
+    let mut hands = solver.root_hand_stats();
+    let frequencies =
+        |stats: &[ActionStat]| -> Vec<f64> { stats.iter().map(|stat| stat.frequency).collect() };
+    assert_eq!(
+        frequencies(&range_stats(&tree.actions, &hands)),
+        frequencies(&solver.root_stats())
+    );
+    purify_hands(&mut hands, Purification::Argmax);
+    for hand in &hands {
+        assert!(hand
+            .actions
+            .iter()
+            .all(|action| action.frequency == 0.0 || action.frequency == 1.0));
+        let taken = hand.actions.iter().find(|action| action.frequency == 1.0);
+        assert!((hand.ev - taken.unwrap().ev).abs() < 1e-12);
+    }
+    let stats = range_stats(&tree.actions, &hands);
+    let total: f64 = stats.iter().map(|stat| stat.frequency).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    assert!(stats
+        .iter()
+        .all(|stat| (stat.frequency * 8.0 - (stat.frequency * 8.0).round()).abs() < 1e-9));
+}
+
+#[test]
+fn rounded_frequencies_still_sum_to_exactly_one() {
+    let round = |frequencies: &[f64], step: f64, min_frequency: f64| {
+        let mut frequencies = frequencies.to_vec();
+        round_frequencies(
+            &mut frequencies,
+            &FrequencyRounding {
+                step,
+                min_frequency,
+            },
+        );
+        assert_eq!(frequencies.iter().sum::<f64>(), 1.0, "{:?}", frequencies);
+        frequencies
+    };
+    // Rounding each to the nearest 10% would give 0.3 * 3; the spare unit goes to the largest
+    // remainder.
+    let tenths = round(&[0.34, 0.33, 0.33], 0.1, 0.0);
+    for (rounded, expected) in tenths.iter().zip([0.4, 0.3, 0.3]) {
+        assert!((rounded - expected).abs() < 1e-12, "{:?}", tenths);
+    }
+    let filtered = round(&[0.02, 0.49, 0.49], 0.0, 0.05);
+    assert_eq!(filtered[0], 0.0);
+    assert!((filtered[1] - 0.5).abs() < 1e-12);
+    let fifths = round(&[0.1, 0.2, 0.3, 0.4], 0.2, 0.15);
+    assert!(fifths
+        .iter()
+        .all(|frequency| ((frequency * 5.0) - (frequency * 5.0).round()).abs() < 1e-9));
+    assert_eq!(fifths[0], 0.0);
+    for seed in 0..50u64 {
+        let mut rng = SolverRng::seeded(seed);
+        let raw: Vec<f64> = (0..5).map(|_| rng.next_f64()).collect();
+        let total: f64 = raw.iter().sum();
+        let raw: Vec<f64> = raw.iter().map(|value| value / total).collect();
+        round(&raw, 0.01, 0.02);
+        round(&raw, 0.03, 0.0);
+    }
+    let mut nothing = vec![0.0, 0.0];
+    round_frequencies(&mut nothing, &FrequencyRounding::default());
+    assert_eq!(nothing, vec![0.0, 0.0]);
+}
+
+#[test]
+fn root_constraints_hold_in_the_average_and_report_their_cost() {
+    let tree = river_tree();
+    let actions = tree.actions.len();
+    let all_in = actions - 1;
+    let constraints = RootConstraints {
+        constraints: vec![
+            // Take the small bet with most of the range, and never jam the weakest bucket.
+            StrategyConstraint {
+                actions: vec![0],
+                buckets: Vec::new(),
+                min: 0.8,
+                max: 1.0,
+            },
+            StrategyConstraint {
+                actions: vec![all_in],
+                buckets: vec![0],
+                min: 0.0,
+                max: 0.0,
+            },
+        ],
+    };
+    let solve = |constraints: RootConstraints| {
+        let mut config = CfrConfig::new(300);
+        config.buckets = 8;
+        config.constraints = constraints;
+        let mut solver = CfrSolver::new(&tree, &config);
+        solver.run(300);
+        solver
+    };
+    let free = solve(RootConstraints::default());
+    let constrained = solve(constraints.clone());
+
+    let strategy = constrained.average_strategy(0);
+    let flat: Vec<f64> = (0..actions)
+        .flat_map(|action| strategy.iter().map(move |row| row[action]))
+        .collect();
+    assert!(constraints.satisfied(&flat, 8, 1e-9));
+    assert!(strategy[0][all_in] < 1e-9);
+    assert!(strategy.iter().map(|row| row[0]).sum::<f64>() / 8.0 >= 0.8 - 1e-9);
+    // An unconverged free solve still leaves a little on the table; the constraints cost more.
+    assert!(constrained.constraint_cost() > free.constraint_cost() + 0.05);
+}
+
+#[test]
+fn refinement_splits_the_buckets_that_disagree_and_keeps_the_rest_coarse() {
+    assert_eq!(coarse_groups(16, 4), vec![0..4, 4..8, 8..12, 12..16]);
+    let groups = vec![0..4, 4..8, 8..10, 10..16];
+    let (refined, parents) = split_groups(&groups, &[1.0, 5.0, 0.0, 9.0]);
+    assert_eq!(refined, vec![0..4, 4..6, 6..8, 8..10, 10..13, 13..16]);
+    assert_eq!(parents, vec![0, 1, 1, 2, 3, 3]);
+
+    // A split bucket starts out playing what its parent played.
+    let tree = river_tree();
+    let mut coarse_config = CfrConfig::new(200);
+    coarse_config.buckets = 2;
+    coarse_config.bucket_weights = vec![1.0, 3.0];
+    let mut coarse = CfrSolver::new(&tree, &coarse_config);
+    coarse.run(200);
+    let mut fine_config = CfrConfig::new(200);
+    fine_config.buckets = 4;
+    let mut fine = CfrSolver::new(&tree, &fine_config);
+    fine.inherit(&coarse, &[0, 1, 1, 1]);
+    assert_eq!(fine.iterations(), 200);
+    let (parent, child) = (coarse.average_strategy(0), fine.average_strategy(0));
+    for (bucket, group) in [(0, 0), (1, 1), (3, 1)] {
+        for (a, b) in child[bucket].iter().zip(&parent[group]) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+}
+
+#[test]
+fn refined_solves_report_their_rounds_over_the_configured_buckets() {
+    let tree = river_tree();
+    let mut config = CfrConfig::new(800);
+    config.refinement = Some(RefinementConfig::default());
+    let refined = run_cfr_with_config(&tree, &config);
+
+    let stats = refined.refinement.expect("refinement stats");
+    assert_eq!(stats.initial_buckets, 4);
+    assert!(stats.rounds >= 1);
+    assert!(stats.final_buckets > 4 && stats.final_buckets <= 16);
+    assert_eq!(stats.splits, stats.final_buckets - stats.initial_buckets);
+    assert_eq!(refined.hands.len(), 16);
+    assert_eq!(refined.iterations, 800);
+    assert!(refined.exploitability < 0.05, "{}", refined.exploitability);
+    assert!(run_cfr_with_config(&tree, &CfrConfig::new(800))
+        .refinement
+        .is_none());
+}
+
+#[test]
+fn solves_stop_at_the_soft_deadline_and_before_the_hard_one() {
+    let tree = river_tree();
+    let mut config = CfrConfig::new(10_000);
+    config.soft_deadline = Some(Instant::now());
+    assert_eq!(run_cfr_with_config(&tree, &config).iterations, 1);
+
+    // An iteration that would end past the hard deadline isn't started, whatever the soft one.
+    let mut config = CfrConfig::new(10_000);
+    config.soft_deadline = Some(Instant::now() + Duration::from_secs(60));
+    config.deadline = Some(Instant::now());
+    assert_eq!(run_cfr_with_config(&tree, &config).iterations, 1);
+
+    let mut config = CfrConfig::new(25);
+    config.soft_deadline = Some(Instant::now() + Duration::from_secs(60));
+    config.deadline = Some(Instant::now() + Duration::from_secs(120));
+    assert_eq!(run_cfr_with_config(&tree, &config).iterations, 25);
+}
@@ -0,0 +1,102 @@
+use solver::cfr::variant::CfrVariantKind;
+use solver::config::{ConfigError, SolverConfig};
+use std::collections::HashMap;
+
+fn env(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn env_overrides_defaults() {
+    let vars = env(&[
+        ("SOLVER_PORT", "6000"),
+        ("SOLVER_WORKER_THREADS", "4"),
+        ("SOLVER_BUCKETS", "32"),
+        ("SOLVER_CACHE_PATH", "/tmp/cache"),
+    ]);
+    let mut config = SolverConfig::default();
+    config
+        .apply_env(|key| vars.get(key).cloned())
+        .expect("overrides parse");
+    config.validate().expect("config is valid");
+
+    assert_eq!(config.server.listen_addr, "0.0.0.0:6000");
+    assert_eq!(config.threads.workers, 4);
+    assert_eq!(config.abstraction.buckets, 32);
+    assert_eq!(config.cache.path, "/tmp/cache");
+    assert_eq!(config.variant(), Some(CfrVariantKind::Vanilla));
+
+    let vars = env(&[("SOLVER_ADDR", "127.0.0.1:7000"), ("SOLVER_PORT", "6000")]);
+    let mut config = SolverConfig::default();
+    config.apply_env(|key| vars.get(key).cloned()).unwrap();
+    assert_eq!(config.server.listen_addr, "127.0.0.1:7000");
+}
+
+#[test]
+fn rejects_bad_values() {
+    let vars = env(&[("SOLVER_THREADS", "many")]);
+    let mut config = SolverConfig::default();
+    assert!(matches!(
+        config.apply_env(|key| vars.get(key).cloned()),
+        Err(ConfigError::InvalidValue { .. })
+    ));
+
+    let mut config = SolverConfig::default();
+    config.cfr.variant = "quantum".into();
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn tls_needs_cert_and_key_together() {
+    let mut config = SolverConfig::default();
+    assert!(!config.tls.is_enabled());
+    assert!(config.tls.load().unwrap().is_none());
+
+    config.tls.cert_path = "/etc/solver/server.pem".into();
+    assert!(config.validate().is_err());
+
+    config.tls.key_path = "/etc/solver/server.key".into();
+    config.tls.client_ca_path = "/etc/solver/clients.pem".into();
+    config
+        .validate()
+        .expect("cert, key and client CA are consistent");
+    assert!(config.tls.requires_client_cert());
+    assert!(matches!(config.tls.load(), Err(ConfigError::Io { .. })));
+}
+
+#[test]
+fn api_keys_come_from_env() {
+    let vars = env(&[("SOLVER_API_KEYS", "bot:s3cret:20, ops:admin")]);
+    let mut config = SolverConfig::default();
+    config.apply_env(|key| vars.get(key).cloned()).unwrap();
+    assert_eq!(config.auth.keys.len(), 2);
+    assert_eq!(config.auth.keys[0].name, "bot");
+    assert_eq!(config.auth.keys[0].rate_per_sec, 20.0);
+    assert_eq!(config.auth.keys[1].key, "admin");
+
+    let vars = env(&[("SOLVER_API_KEYS", "missing-secret")]);
+    assert!(config.apply_env(|key| vars.get(key).cloned()).is_err());
+}
+
+#[test]
+fn core_lists_parse_ranges_and_bound_the_thread_counts() {
+    let vars = env(&[("SOLVER_CORES", "6, 2-4,3")]);
+    let mut config = SolverConfig::default();
+    config.apply_env(|key| vars.get(key).cloned()).unwrap();
+    config.validate().expect("config is valid");
+    assert_eq!(config.threads.cores, vec![2, 3, 4, 6]);
+    assert_eq!(config.threads.core_count(), 4);
+    assert_eq!(config.admission_limits().max_concurrent, 4);
+
+    for raw in ["4-2", "two", "0-18446744073709551615", "4096"] {
+        let vars = env(&[("SOLVER_CORES", raw)]);
+        let mut config = SolverConfig::default();
+        assert!(config.apply_env(|key| vars.get(key).cloned()).is_err());
+    }
+    let mut config = SolverConfig::default();
+    config.threads.cores = vec![0, 4096];
+    assert!(config.validate().is_err());
+}
@@ -0,0 +1,156 @@
+use solver::dedup::SolveDedup;
+use solver::error::SolverError;
+use solver::solver_proto::{SubgameRequest, SubgameResponse};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn request(fingerprint: &str) -> SubgameRequest {
+    SubgameRequest {
+        state_fingerprint: fingerprint.into(),
+        budget_ms: 100,
+        action_set: vec!["pot:0.5".into()],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn concurrent_identical_requests_share_one_solve() {
+    let dedup = Arc::new(SolveDedup::new());
+    let solves = Arc::new(AtomicUsize::new(0));
+    let spawn = |request: SubgameRequest| {
+        let dedup = dedup.clone();
+        let solves = solves.clone();
+        thread::spawn(move || {
+            dedup.run(&request, || {
+                solves.fetch_add(1, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(50));
+                Ok(SubgameResponse {
+                    exploitability: 0.25,
+                    ..Default::default()
+                })
+            })
+        })
+    };
+    let leader = spawn(request("spot"));
+    while dedup.in_flight() == 0 {
+        thread::yield_now();
+    }
+    let follower = spawn(request("spot"));
+    let other = spawn(request("other spot"));
+
+    let (led, led_joined) = leader.join().unwrap();
+    let (followed, follower_joined) = follower.join().unwrap();
+    other.join().unwrap().0.unwrap();
+    assert!(!led_joined && follower_joined);
+    assert_eq!(led.unwrap(), followed.unwrap());
+    assert_eq!(solves.load(Ordering::SeqCst), 2);
+    assert_eq!(dedup.in_flight(), 0);
+}
+
+#[test]
+fn errors_are_shared_and_later_requests_solve_again() {
+    let dedup = SolveDedup::new();
+    let (result, joined) = dedup.run(&request("spot"), || Err(SolverError::EmptyActionSet));
+    assert_eq!(result, Err(SolverError::EmptyActionSet));
+    assert!(!joined);
+
+    // Nothing is cached once the solve is over.
+    let (result, joined) = dedup.run(&request("spot"), || Ok(SubgameResponse::default()));
+    assert!(result.is_ok() && !joined);
+}
+
+#[test]
+fn budgets_only_matter_when_the_solve_would_finish_too_late() {
+    let dedup = Arc::new(SolveDedup::new());
+    let solves = Arc::new(AtomicUsize::new(0));
+    let spawn = |budget_ms: i32| {
+        let dedup = dedup.clone();
+        let solves = solves.clone();
+        let request = SubgameRequest {
+            budget_ms,
+            priority: "realtime".into(),
+            ..request("spot")
+        };
+        thread::spawn(move || {
+            dedup.run(&request, || {
+                solves.fetch_add(1, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(50));
+                Ok(SubgameResponse::default())
+            })
+        })
+    };
+    let leader = spawn(100);
+    while dedup.in_flight() == 0 {
+        thread::yield_now();
+    }
+    // A deadline trimmed differently still shares the solve; a tighter one can't wait for it.
+    let later = spawn(5_000);
+    let sooner = spawn(10);
+
+    assert!(!leader.join().unwrap().1);
+    assert!(later.join().unwrap().1);
+    assert!(!sooner.join().unwrap().1);
+    assert_eq!(solves.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn requests_only_share_a_solve_at_least_as_thorough_as_their_own() {
+    let dedup = Arc::new(SolveDedup::new());
+    let solves = Arc::new(AtomicUsize::new(0));
+    let spawn = |budget_ms: i32, budget_iterations: u64| {
+        let dedup = dedup.clone();
+        let solves = solves.clone();
+        let request = SubgameRequest {
+            budget_ms,
+            budget_iterations,
+            ..request("spot")
+        };
+        thread::spawn(move || {
+            dedup.run(&request, || {
+                solves.fetch_add(1, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(100));
+                Ok(SubgameResponse::default())
+            })
+        })
+    };
+    let leader = spawn(0, 1_000);
+    while dedup.in_flight() == 0 {
+        thread::yield_now();
+    }
+    let fewer = spawn(0, 500);
+    let more = spawn(0, 2_000);
+    let timed = spawn(5_000, 0);
+
+    assert!(!leader.join().unwrap().1);
+    assert!(fewer.join().unwrap().1);
+    assert!(!more.join().unwrap().1);
+    assert!(!timed.join().unwrap().1);
+    assert_eq!(solves.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn an_iteration_budget_never_waits_on_a_timed_solve() {
+    let dedup = Arc::new(SolveDedup::new());
+    let leader = {
+        let dedup = dedup.clone();
+        thread::spawn(move || {
+            dedup.run(&request("spot"), || {
+                thread::sleep(Duration::from_millis(50));
+                Ok(SubgameResponse::default())
+            })
+        })
+    };
+    while dedup.in_flight() == 0 {
+        thread::yield_now();
+    }
+    let iterations_only = SubgameRequest {
+        budget_ms: 0,
+        budget_iterations: 200,
+        ..request("spot")
+    };
+    let (_, joined) = dedup.run(&iterations_only, || Ok(SubgameResponse::default()));
+    assert!(!joined);
+    leader.join().unwrap().0.unwrap();
+}
@@ -0,0 +1,95 @@
+use solver::game_tree::chips::ChipTerminal;
+use solver::game_tree::eval::{build_side_pots, PlayerContribution, RakeModel, TerminalNode};
+
+fn live(committed: f64) -> PlayerContribution {
+    PlayerContribution {
+        committed,
+        folded: false,
+    }
+}
+
+#[test]
+fn builds_side_pots_for_uneven_all_ins() {
+    let contributions = vec![live(20.0), live(50.0), live(100.0)];
+    let pots = build_side_pots(&contributions);
+    assert_eq!(pots.len(), 3);
+    assert!((pots[0].amount - 60.0).abs() < 1e-9);
+    assert_eq!(pots[0].eligible, vec![0, 1, 2]);
+    assert!((pots[1].amount - 60.0).abs() < 1e-9);
+    assert_eq!(pots[1].eligible, vec![1, 2]);
+    assert!((pots[2].amount - 50.0).abs() < 1e-9);
+    assert_eq!(pots[2].eligible, vec![2]);
+}
+
+#[test]
+fn short_stack_winner_only_takes_main_pot() {
+    let node = TerminalNode::new(
+        vec![
+            live(20.0),
+            live(50.0),
+            PlayerContribution {
+                committed: 10.0,
+                folded: true,
+            },
+            live(50.0),
+        ],
+        6.0,
+    );
+    // Seat 0 has the best hand, seat 1 beats seat 3 for the side pot.
+    let payouts = node.payouts(&[3.0, 2.0, 9.0, 1.0]);
+    assert!((payouts[0] - (76.0 - 20.0)).abs() < 1e-9);
+    assert!((payouts[1] - (60.0 - 50.0)).abs() < 1e-9);
+    assert!((payouts[2] + 10.0).abs() < 1e-9);
+    assert!((payouts[3] + 50.0).abs() < 1e-9);
+    assert!((payouts.iter().sum::<f64>() - node.dead_money).abs() < 1e-9);
+}
+
+#[test]
+fn rake_is_capped_and_skips_uncalled_bets() {
+    let rake = RakeModel {
+        percentage: 5.0,
+        cap: 3.0,
+        no_flop_no_drop: true,
+    };
+    assert_eq!(rake.rake(20.0, true), 0.0);
+    assert!((rake.rake(20.0, false) - 1.0).abs() < 1e-9);
+    assert!((rake.rake(200.0, false) - 3.0).abs() < 1e-9);
+
+    // Seat 1 over-bets seat 0's stack by 30; that excess is returned unraked.
+    let node = TerminalNode::new(vec![live(10.0), live(40.0)], 0.0).with_rake(&rake, false);
+    assert!((node.contested_pot() - 20.0).abs() < 1e-9);
+    let payouts = node.payouts(&[2.0, 1.0]);
+    assert!((payouts[0] - 9.0).abs() < 1e-9);
+    assert!((payouts[1] + 10.0).abs() < 1e-9);
+}
+
+#[test]
+fn chip_terminals_settle_in_whole_units() {
+    // Three dead units, both players in for five.
+    let showdown = ChipTerminal::new([5, 5], None, 3);
+    assert_eq!(showdown.contested(), 13);
+    assert_eq!(showdown.net(Some(0)), [8, -5]);
+    // The odd unit of a split goes to the first to act.
+    assert_eq!(showdown.net(None), [2, 1]);
+
+    let rake = RakeModel {
+        percentage: 10.0,
+        cap: 0.0,
+        no_flop_no_drop: false,
+    };
+    let raked = showdown.with_rake(&rake, false, 1.0);
+    assert_eq!(raked.rake, 1);
+    assert_eq!(raked.net(Some(0)), [7, -5]);
+
+    // Three uncalled units go back to the bettor.
+    let uncalled = ChipTerminal::new([8, 5], None, 3);
+    assert_eq!(uncalled.net(Some(1)), [-5, 8]);
+    let folded = ChipTerminal::new([8, 5], Some(1), 3);
+    assert_eq!(folded.contested(), 0);
+    assert_eq!(folded.net(None), [8, -5]);
+
+    // Amounts past the chip range saturate instead of overflowing.
+    let huge = ChipTerminal::new([i64::MAX, i64::MAX], None, i64::MAX);
+    assert_eq!(huge.contested(), i64::MAX);
+    assert_eq!(huge.net(Some(0))[1], -i64::MAX);
+}
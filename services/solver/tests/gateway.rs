@@ -0,0 +1,132 @@
+use axum::http::HeaderMap;
+use axum::routing::post;
+use solver::gateway::{
+    call_metadata, cors_headers, error_body, http_status, read_body, GatewayService, Route,
+    MAX_BODY_BYTES,
+};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tonic::transport::server::Routes;
+use tonic::{Code, Status};
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[test]
+fn every_rpc_has_a_route_and_bodies_are_bounded() {
+    for route in Route::ALL {
+        assert_eq!(Route::parse(route.path()), Some(route));
+    }
+    assert_eq!(
+        Route::parse("/v1/abstraction-info?pretty"),
+        Some(Route::AbstractionInfo)
+    );
+    assert_eq!(Route::parse("/v1/solve-stream"), Some(Route::SolveStream));
+    assert_eq!(Route::parse("/v1/equity"), None);
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-api-key", "secret".parse().unwrap());
+    headers.insert("cookie", "session".parse().unwrap());
+    let metadata = call_metadata(&headers);
+    assert_eq!(
+        metadata
+            .get("x-api-key")
+            .and_then(|value| value.to_str().ok()),
+        Some("secret")
+    );
+    assert!(metadata.get("cookie").is_none());
+
+    let body = block_on(read_body(
+        hyper::Body::from("{\"budgetMs\":5}"),
+        MAX_BODY_BYTES,
+        Duration::from_secs(1),
+    ))
+    .unwrap();
+    assert_eq!(&body[..], b"{\"budgetMs\":5}");
+    let huge = block_on(read_body(
+        hyper::Body::from(vec![b' '; 64]),
+        32,
+        Duration::from_secs(1),
+    ))
+    .unwrap_err();
+    assert_eq!(huge.code(), Code::ResourceExhausted);
+    // A caller that never finishes its body is cut off.
+    let (_sender, trickle) = hyper::Body::channel();
+    let stalled = block_on(read_body(
+        trickle,
+        MAX_BODY_BYTES,
+        Duration::from_millis(20),
+    ))
+    .unwrap_err();
+    assert_eq!(stalled.code(), Code::DeadlineExceeded);
+}
+
+#[test]
+fn errors_map_to_http_statuses_with_cors_headers() {
+    assert_eq!(http_status(Code::InvalidArgument), 400);
+    assert_eq!(http_status(Code::Unauthenticated), 401);
+    assert_eq!(http_status(Code::ResourceExhausted), 429);
+    assert_eq!(http_status(Code::DeadlineExceeded), 504);
+    assert_eq!(
+        error_body(&Status::invalid_argument("budget_ms must be positive")),
+        r#"{"code":"InvalidArgument","message":"budget_ms must be positive"}"#
+    );
+    let headers = cors_headers("https://ui.example");
+    assert_eq!(
+        headers.get("access-control-allow-origin").unwrap(),
+        "https://ui.example"
+    );
+    assert!(cors_headers("").is_empty());
+}
+
+// Sends one HTTP/1.1 request and reads the whole response.
+async fn http1(addr: SocketAddr, method: &str, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "{} {} HTTP/1.1\r\nhost: solver\r\ncontent-length: 2\r\nconnection: close\r\n\r\n{{}}",
+        method, path
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[test]
+fn the_grpc_server_routes_v1_paths_to_the_gateway() {
+    block_on(async {
+        let gateway = axum::Router::new().route("/v1/solve", post(|| async { "solved" }));
+        let routes = Routes::new(GatewayService::new(gateway));
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(
+            tonic::transport::Server::builder()
+                .accept_http1(true)
+                .add_routes(routes)
+                .serve_with_shutdown(addr, async {
+                    stopped.await.ok();
+                }),
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let solved = http1(addr, "POST", "/v1/solve").await;
+        assert!(solved.starts_with("HTTP/1.1 200"), "{}", solved);
+        assert!(solved.ends_with("solved"), "{}", solved);
+        // Anything outside /v1 is left to gRPC, which has no such service here.
+        let grpc = http1(addr, "POST", "/solver.Solver/Solve").await;
+        assert!(grpc.contains("grpc-status: 12"), "{}", grpc);
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    });
+}
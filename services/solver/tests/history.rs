@@ -0,0 +1,108 @@
+use solver::config::HistoryConfig;
+use solver::error::SolverError;
+use solver::history::{retention_cutoff, HistoryFilter, HistoryRecord, SolveHistory};
+use solver::solver_proto::{HistoryQuery, SubgameRequest, SubgameResponse};
+
+fn request(fingerprint: &str) -> SubgameRequest {
+    SubgameRequest {
+        state_fingerprint: fingerprint.into(),
+        budget_iterations: 30,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "all-in".into()],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn records_summarize_the_solve_and_queries_filter_them() {
+    let response = SubgameResponse {
+        exploitability: 0.02,
+        iterations: 30,
+        seed: 7,
+        source: "cfr".into(),
+        ..Default::default()
+    };
+    let solved = HistoryRecord::new(&request("a"), &Ok(response.clone()), 1_000, true);
+    assert_eq!(solved.status, "OK");
+    assert_eq!(solved.iterations, 30);
+    assert_eq!(solved.response, Some(response));
+    let entry = solved.to_proto(false);
+    assert_eq!(entry.state_fingerprint, "a");
+    assert_eq!(entry.seed, 7);
+    assert!(entry.response.is_none());
+    assert!(solved.to_proto(true).response.is_some());
+
+    let failed = HistoryRecord::new(
+        &request("b"),
+        &Err(SolverError::EmptyActionSet),
+        2_000,
+        true,
+    );
+    assert_eq!(failed.status, "EMPTY_ACTION_SET");
+    assert!(failed.response.is_none());
+
+    let everything = HistoryFilter::from_query(&HistoryQuery::default());
+    assert_eq!(everything.limit, 100);
+    assert!(everything.matches(&solved) && everything.matches(&failed));
+    let failures = HistoryFilter::from_query(&HistoryQuery {
+        status: "EMPTY_ACTION_SET".into(),
+        until_ms: 5_000,
+        limit: 50_000,
+        ..Default::default()
+    });
+    assert_eq!(failures.limit, 1000);
+    assert!(!failures.matches(&solved) && failures.matches(&failed));
+    let spot_a_later = HistoryFilter::from_query(&HistoryQuery {
+        state_fingerprint: "a".into(),
+        since_ms: 1_500,
+        ..Default::default()
+    });
+    assert!(!spot_a_later.matches(&solved));
+
+    assert_eq!(retention_cutoff(0, 10 * 86_400_000), None);
+    assert_eq!(retention_cutoff(3, 10 * 86_400_000), Some(7 * 86_400_000));
+}
+
+#[cfg(not(feature = "history"))]
+#[test]
+fn history_needs_the_feature() {
+    let config = HistoryConfig {
+        path: "history.sqlite".into(),
+        ..Default::default()
+    };
+    assert!(SolveHistory::open(&config).is_err());
+}
+
+#[cfg(feature = "history")]
+#[test]
+fn recorded_solves_can_be_queried_back() {
+    let path = std::env::temp_dir().join(format!("solver-history-{}.sqlite", std::process::id()));
+    let config = HistoryConfig {
+        path: path.display().to_string(),
+        store_strategy: true,
+        ..Default::default()
+    };
+    let history = SolveHistory::open(&config).expect("history opens");
+    let engine = solver::solver::SolverEngine::new();
+    let result = engine.solve(&request("a"));
+    history.record(&request("a"), &result).unwrap();
+    history
+        .record(&request("b"), &Err(SolverError::EmptyActionSet))
+        .unwrap();
+
+    let all = history.query(&HistoryQuery::default()).unwrap();
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].request.state_fingerprint, "b");
+    let spot = history
+        .query(&HistoryQuery {
+            state_fingerprint: "a".into(),
+            include_strategy: true,
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(spot.len(), 1);
+    assert_eq!(spot[0].request, request("a"));
+    assert_eq!(spot[0].response.as_ref(), result.as_ref().ok());
+    drop(history);
+    let _ = std::fs::remove_file(&path);
+}
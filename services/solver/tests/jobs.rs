@@ -0,0 +1,67 @@
+use solver::error::SolverError;
+use solver::jobs::JobStore;
+use solver::solver::SolverEngine;
+use solver::solver_proto::SubgameRequest;
+use std::thread;
+use std::time::Duration;
+use tonic::Code;
+
+#[test]
+fn jobs_report_progress_and_keep_their_result() {
+    let jobs = JobStore::new(Duration::from_secs(60), 8);
+    let (job_id, progress) = jobs.submit().expect("store has room");
+    let status = jobs.status(&job_id).unwrap();
+    assert_eq!(status.state, "queued");
+    assert_eq!(status.progress, 0.0);
+    let pending = jobs.result(&job_id).expect_err("not finished yet");
+    assert_eq!(pending.code(), Code::FailedPrecondition);
+
+    jobs.start(&job_id);
+    let request = SubgameRequest {
+        state_fingerprint: "background".into(),
+        budget_iterations: 40,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "all-in".into()],
+        ..Default::default()
+    };
+    let result = SolverEngine::new().solve_with_progress(&request, &progress);
+    assert_eq!(progress.fraction(), 1.0);
+    jobs.finish(&job_id, result);
+
+    let status = jobs.status(&job_id).unwrap();
+    assert_eq!(status.state, "done");
+    assert!(status.expires_in_ms > 0);
+    let response = jobs.result(&job_id).expect("job succeeded");
+    assert_eq!(response.iterations, 40);
+    // Fetching again still works until the job expires.
+    assert_eq!(jobs.result(&job_id).unwrap(), response);
+
+    let other = jobs.status("0-0000000000000000").expect_err("unknown id");
+    assert_eq!(other.code(), Code::NotFound);
+}
+
+#[test]
+fn finished_jobs_expire_and_the_store_is_bounded() {
+    let jobs = JobStore::new(Duration::from_millis(20), 2);
+    let (first, _) = jobs.submit().unwrap();
+    let (second, _) = jobs.submit().unwrap();
+    assert_ne!(first, second);
+    assert_eq!(
+        jobs.submit().err(),
+        Some(SolverError::TooManyJobs { limit: 2 })
+    );
+
+    jobs.finish(&first, Err(SolverError::EmptyActionSet));
+    let status = jobs.status(&first).unwrap();
+    assert_eq!(status.state, "failed");
+    assert_eq!(status.error.unwrap().reason, "EMPTY_ACTION_SET");
+
+    thread::sleep(Duration::from_millis(40));
+    assert!(matches!(
+        jobs.result(&first),
+        Err(SolverError::JobNotFound { .. })
+    ));
+    // Unfinished jobs don't expire.
+    assert_eq!(jobs.status(&second).unwrap().state, "queued");
+    assert!(jobs.submit().is_ok());
+}
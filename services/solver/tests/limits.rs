@@ -0,0 +1,529 @@
+use solver::abstraction::{ActionSpec, GameStateSummary, LimitSummary};
+use solver::cfr::deepening::run_cfr_deepening;
+use solver::cfr::{CfrConfig, CfrSolver};
+use solver::config::SolverConfig;
+use solver::error::SolverError;
+use solver::game_tree::eval::RakeModel;
+use solver::game_tree::legality::{AdjustmentReason, BettingContext};
+use solver::game_tree::limits::{
+    fit_tree, next_street_tree, shallower_trees, DegradationLevel, TreeFootprint, TreeLimits,
+    MIN_COARSE_BUCKETS,
+};
+use solver::game_tree::{ActionKind, GameTree, NodeKind, TerminalKind, HERO, VILLAIN};
+use solver::metrics::SolverMetrics;
+use solver::solver::SolverEngine;
+use solver::solver_proto::{SolverConfig as RequestConfig, SubgameRequest, SubgameResponse};
+use std::sync::Arc;
+use std::time::Instant;
+use tonic::Code;
+
+fn spec(label: &str, amount: f64) -> ActionSpec {
+    ActionSpec {
+        label: label.into(),
+        amount,
+        kind: ActionKind::aggressive(label, 0.0),
+    }
+}
+
+fn setup() -> (Vec<ActionSpec>, GameStateSummary, BettingContext) {
+    let summary = GameStateSummary {
+        pot: 10.0,
+        street: "river".into(),
+        ..Default::default()
+    };
+    let specs = vec![
+        spec("pot-0.33", 3.3),
+        spec("pot-0.50", 5.0),
+        spec("pot-1.00", 10.0),
+        spec("all-in", 100.0),
+    ];
+    let context = BettingContext::opening(&summary);
+    (specs, summary, context)
+}
+
+#[test]
+fn coarsens_buckets_then_drops_sizes() {
+    let (specs, summary, context) = setup();
+    let full = GameTree::build(&specs, &summary, 100.0, &context);
+    let coarse = TreeFootprint::of(&full, MIN_COARSE_BUCKETS);
+    assert!(TreeFootprint::of(&full, 16).bytes > coarse.bytes);
+    assert_eq!(coarse.info_sets, 5 * MIN_COARSE_BUCKETS);
+
+    let limits = TreeLimits {
+        max_bytes: coarse.bytes - 1,
+        ..TreeLimits::default()
+    };
+    let fitted = fit_tree(
+        &specs,
+        &summary,
+        100.0,
+        &context,
+        &RakeModel::default(),
+        16,
+        &limits,
+    )
+    .expect("coarsened tree fits");
+
+    assert_eq!(fitted.buckets, MIN_COARSE_BUCKETS);
+    assert!(fitted.footprint.bytes <= limits.max_bytes);
+    let labels: Vec<&str> = fitted
+        .tree
+        .actions
+        .iter()
+        .map(|action| action.label.as_str())
+        .collect();
+    assert_eq!(labels, ["pot-0.33", "pot-1.00", "all-in"]);
+    let dropped = fitted.tree.legalization.adjustments.last().unwrap();
+    assert_eq!(dropped.label, "pot-0.50");
+    assert_eq!(dropped.reason, AdjustmentReason::DroppedForMemory);
+}
+
+#[test]
+fn no_limit_trees_give_up_raises_after_sizes() {
+    let (specs, summary, context) = setup();
+    let context = BettingContext {
+        max_raises: 2,
+        ..context
+    };
+    let specs = &specs[1..2];
+    // The bet, villain's fold, call, raise or shove, and hero's answers down to the cap.
+    let full = GameTree::build(specs, &summary, 100.0, &context);
+    assert_eq!(full.nodes.len(), 16);
+
+    let limits = TreeLimits {
+        max_nodes: 12,
+        ..TreeLimits::default()
+    };
+    let fitted = fit_tree(
+        specs,
+        &summary,
+        100.0,
+        &context,
+        &RakeModel::default(),
+        16,
+        &limits,
+    )
+    .expect("a shallower tree fits");
+    assert_eq!(fitted.tree.nodes.len(), 10);
+    assert_eq!(fitted.level, DegradationLevel::ShallowerTree);
+    assert_eq!(fitted.context.max_raises, 1);
+}
+
+#[test]
+fn rejects_oversized_tree_when_coarsening_is_off() {
+    let (specs, summary, context) = setup();
+    let limits = TreeLimits {
+        max_bytes: 64,
+        coarsen: false,
+        ..TreeLimits::default()
+    };
+    let error = fit_tree(
+        &specs,
+        &summary,
+        100.0,
+        &context,
+        &RakeModel::default(),
+        16,
+        &limits,
+    )
+    .err()
+    .expect("tree is rejected");
+    assert!(matches!(
+        error,
+        SolverError::MemoryBudgetExceeded { limit: 64, .. }
+    ));
+    assert_eq!(error.code(), Code::ResourceExhausted);
+
+    let limits = TreeLimits {
+        max_nodes: 3,
+        ..TreeLimits::default()
+    };
+    let error = fit_tree(
+        &specs,
+        &summary,
+        100.0,
+        &context,
+        &RakeModel::default(),
+        16,
+        &limits,
+    )
+    .err()
+    .expect("even one size needs more nodes");
+    assert!(matches!(error, SolverError::TreeTooLarge { limit: 3, .. }));
+}
+
+#[test]
+fn estimates_tree_before_solving() {
+    let request = SubgameRequest {
+        state_fingerprint: "estimate".into(),
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "all-in".into()],
+        ..Default::default()
+    };
+    let engine = SolverEngine::new();
+    let estimate = engine.estimate(&request).expect("estimate needs no budget");
+    assert_eq!(estimate.nodes, 7);
+    assert_eq!(estimate.info_sets, 3 * 16);
+    assert_eq!(estimate.actions, 2);
+    assert!(estimate.within_limits && estimate.solvable);
+    assert_eq!(estimate.solve_buckets, 16);
+    assert_eq!(estimate.solve_memory_bytes, estimate.memory_bytes);
+
+    let mut config = SolverConfig::default();
+    config.limits.max_nodes = 5;
+    let engine = SolverEngine::from_config(config, Arc::new(SolverMetrics::new()));
+    let estimate = engine.estimate(&request).unwrap();
+    assert!(!estimate.within_limits);
+    assert!(estimate.solvable);
+    assert_eq!(estimate.nodes, 7);
+    assert_eq!(estimate.solve_actions, 1);
+    assert!(estimate.solve_memory_bytes < estimate.memory_bytes);
+}
+
+#[test]
+fn steps_down_the_ladder_one_rung_at_a_time() {
+    let (specs, summary, context) = setup();
+    let fit = |limits: &TreeLimits, summary: &GameStateSummary| {
+        fit_tree(
+            &specs,
+            summary,
+            100.0,
+            &context,
+            &RakeModel::default(),
+            16,
+            limits,
+        )
+    };
+    let full = fit(&TreeLimits::default(), &summary).unwrap();
+    assert_eq!(full.level, DegradationLevel::Full);
+    let fewer_buckets = TreeLimits {
+        max_info_sets: full.footprint.info_sets / 2,
+        ..TreeLimits::default()
+    };
+    assert_eq!(
+        fit(&fewer_buckets, &summary).unwrap().level,
+        DegradationLevel::FewerBuckets
+    );
+    let fewer_sizes = TreeLimits {
+        max_info_sets: 3 * MIN_COARSE_BUCKETS,
+        ..TreeLimits::default()
+    };
+    let fitted = fit(&fewer_sizes, &summary).unwrap();
+    assert_eq!(fitted.level, DegradationLevel::FewerSizes);
+    assert_eq!(fitted.tree.actions.len(), 2);
+
+    // A fixed-limit street gives up raises once the sizes are gone.
+    let limit = GameStateSummary {
+        limit: Some(LimitSummary {
+            cap: 4,
+            ..Default::default()
+        }),
+        ..summary.clone()
+    };
+    let capped = fit(&TreeLimits::default(), &limit).unwrap();
+    let shallower = TreeLimits {
+        max_nodes: capped.footprint.nodes - 1,
+        ..TreeLimits::default()
+    };
+    let fitted = fit(&shallower, &limit).unwrap();
+    assert_eq!(fitted.level, DegradationLevel::ShallowerTree);
+    assert!(fitted.footprint.nodes < capped.footprint.nodes);
+
+    let error = fit(
+        &TreeLimits {
+            max_info_sets: 1,
+            coarsen: false,
+            ..TreeLimits::default()
+        },
+        &summary,
+    )
+    .err()
+    .expect("no tree fits");
+    assert_eq!(error.reason(), "BUDGET_TOO_SMALL");
+    assert_eq!(error.code(), Code::DeadlineExceeded);
+}
+
+#[test]
+fn budgets_too_small_for_any_tree_fall_back_to_a_blueprint() {
+    let mut config = SolverConfig::default();
+    // One information set per millisecond: no time budget covers a tree.
+    config.limits.info_sets_per_ms = 1.0;
+    let engine = SolverEngine::from_config(config, Arc::new(SolverMetrics::new()));
+    let spot = SubgameRequest {
+        state_fingerprint: "blueprint".into(),
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "all-in".into()],
+        ..Default::default()
+    };
+    let blueprint = engine
+        .solve(&SubgameRequest {
+            budget_iterations: 10,
+            ..spot.clone()
+        })
+        .expect("iteration budgets aren't timed");
+    assert_eq!(blueprint.degradation, "");
+
+    let timed = SubgameRequest {
+        budget_ms: 50,
+        action_set: vec!["pot:0.33".into(), "pot:1".into(), "all-in".into()],
+        ..spot.clone()
+    };
+    let response = engine.solve(&timed).expect("the blueprint answers");
+    assert_eq!(response.source, "blueprint");
+    assert_eq!(response.degradation, "blueprint");
+    assert_eq!(response.actions, blueprint.actions);
+
+    let unseen = SubgameRequest {
+        state_fingerprint: "no-blueprint".into(),
+        ..timed
+    };
+    let error = engine.solve(&unseen).unwrap_err();
+    assert_eq!(error.reason(), "BUDGET_TOO_SMALL");
+}
+
+#[test]
+fn resolves_start_from_the_blueprint_and_fall_back_to_it() {
+    let mut config = SolverConfig::default();
+    config.limits.info_sets_per_ms = 1.0;
+    let engine = SolverEngine::from_config(config, Arc::new(SolverMetrics::new()));
+    let resolve = SubgameRequest {
+        state_fingerprint: "resolve".into(),
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.33".into(), "pot:1".into(), "all-in".into()],
+        budget_iterations: 20,
+        config: Some(RequestConfig {
+            resolve_from_blueprint: true,
+            skip_cache: true,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let first = engine.solve(&resolve).unwrap();
+    assert_eq!(first.source, "subgame");
+
+    let blueprint = engine
+        .solve(&SubgameRequest {
+            action_set: vec!["pot:0.5".into(), "all-in".into()],
+            budget_iterations: 50,
+            config: None,
+            ..resolve.clone()
+        })
+        .unwrap();
+    // The blueprint is the latest solve of the spot, so it is the trunk the resolve holds to.
+    let resolved = engine
+        .solve(&SubgameRequest {
+            budget_iterations: 30,
+            ..resolve.clone()
+        })
+        .unwrap();
+    assert_eq!(resolved.source, "blueprint+resolve");
+    // Under the request's own sizes, not the blueprint's.
+    let labels = |response: &SubgameResponse| {
+        response
+            .actions
+            .iter()
+            .map(|action| action.action_type.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(labels(&resolved), labels(&first));
+    assert_ne!(labels(&resolved), labels(&blueprint));
+    assert_eq!(resolved.degradation, "");
+    assert!(resolved.per_hand_strategies.is_empty());
+    // Hero's root plays the blueprint's mix: its half-pot bet moves to the closest size here,
+    // and the pot-sized bet it never made stays unplayed.
+    let frequency = |response: &SubgameResponse, label: &str| {
+        response
+            .actions
+            .iter()
+            .find(|action| action.action_type == label)
+            .map_or(0.0, |action| action.frequency)
+    };
+    assert!((frequency(&resolved, "pot-0.33") - frequency(&blueprint, "pot-0.50")).abs() < 1e-6);
+    assert!((frequency(&resolved, "all-in") - frequency(&blueprint, "all-in")).abs() < 1e-6);
+    assert!(frequency(&resolved, "pot-1") < 1e-9);
+
+    // A time budget no tree fits leaves the blueprint to answer, which is now the resolve.
+    let timed = engine
+        .solve(&SubgameRequest {
+            budget_iterations: 0,
+            budget_ms: 50,
+            ..resolve
+        })
+        .unwrap();
+    assert_eq!(timed.source, "blueprint");
+    assert_eq!(timed.actions, resolved.actions);
+}
+
+#[test]
+fn deepening_adds_a_raise_per_pass_and_answers_from_the_deepest_reached() {
+    let (specs, summary, context) = setup();
+    let limit = GameStateSummary {
+        limit: Some(LimitSummary {
+            cap: 4,
+            ..Default::default()
+        }),
+        ..summary.clone()
+    };
+    let rake = RakeModel::default();
+    let fitted = fit_tree(
+        &specs,
+        &limit,
+        100.0,
+        &context,
+        &rake,
+        8,
+        &TreeLimits::default(),
+    )
+    .unwrap();
+    let mut trees = shallower_trees(&fitted, 100.0, &rake);
+    assert_eq!(trees.len(), 3);
+    trees.push(fitted.tree.clone());
+    for pair in trees.windows(2) {
+        assert!(pair[0].nodes.len() < pair[1].nodes.len());
+        let shared = pair[1].matching_nodes(&pair[0]).iter().flatten().count();
+        assert_eq!(shared, pair[0].nodes.len());
+    }
+
+    // A deeper pass starts out playing what the shallower one settled on.
+    let mut config = CfrConfig::new(400);
+    config.buckets = 8;
+    let mut shallow = CfrSolver::new(&trees[0], &config);
+    shallow.run(100);
+    let mut deeper = CfrSolver::new(&trees[1], &config);
+    deeper.extend(&shallow);
+    assert_eq!(deeper.iterations(), 100);
+    assert_eq!(deeper.average_strategy(0), shallow.average_strategy(0));
+
+    let (depth, outcome) = run_cfr_deepening(&trees, &config);
+    assert_eq!(depth, 3);
+    assert_eq!(outcome.iterations, 400);
+
+    // Out of time after the first iteration: the one-raise tree answers.
+    config.deadline = Some(Instant::now());
+    let (depth, outcome) = run_cfr_deepening(&trees, &config);
+    assert_eq!(depth, 0);
+    assert_eq!(outcome.iterations, 1);
+    assert!(!outcome.actions.is_empty());
+
+    // No-limit trees deepen a re-raise at a time, from villain only folding or calling.
+    let context = BettingContext {
+        max_raises: 2,
+        ..context
+    };
+    let fitted = fit_tree(
+        &specs[1..2],
+        &summary,
+        100.0,
+        &context,
+        &rake,
+        8,
+        &TreeLimits::default(),
+    )
+    .unwrap();
+    let mut trees = shallower_trees(&fitted, 100.0, &rake);
+    assert_eq!(trees.len(), 2);
+    trees.push(fitted.tree);
+    for pair in trees.windows(2) {
+        assert!(pair[0].nodes.len() < pair[1].nodes.len());
+        let shared = pair[1].matching_nodes(&pair[0]).iter().flatten().count();
+        assert_eq!(shared, pair[0].nodes.len());
+    }
+}
+
+#[test]
+fn deepening_ends_with_the_next_street_after_each_showdown() {
+    let (specs, summary, context) = setup();
+    let rake = RakeModel::default();
+    let fit = |summary: &GameStateSummary| {
+        fit_tree(
+            &specs,
+            summary,
+            100.0,
+            &context,
+            &rake,
+            8,
+            &TreeLimits::default(),
+        )
+        .unwrap()
+    };
+    // The river has no next street.
+    assert!(next_street_tree(&fit(&summary), &TreeLimits::default()).is_none());
+
+    let flop = GameStateSummary {
+        street: "flop".into(),
+        ..summary.clone()
+    };
+    let fitted = fit(&flop);
+    let shallow = &fitted.tree;
+    let deeper = next_street_tree(&fitted, &TreeLimits::default()).unwrap();
+    assert_eq!(
+        deeper.matching_nodes(shallow).iter().flatten().count(),
+        shallow.nodes.len()
+    );
+    for node in 0..shallow.nodes.len() {
+        if !matches!(
+            shallow.nodes[node].kind,
+            NodeKind::Terminal(TerminalKind::Showdown)
+        ) {
+            continue;
+        }
+        if shallow.is_all_in_showdown(node) {
+            assert!(deeper.nodes[node].is_terminal());
+            continue;
+        }
+        // Hero is out of position after the flop, so checks or bets the turn first.
+        let NodeKind::Decision { player, children } = &deeper.nodes[node].kind else {
+            panic!("showdown {} has no turn", node);
+        };
+        assert_eq!(*player, HERO);
+        let labels: Vec<&str> = children
+            .iter()
+            .map(|child| deeper.nodes[*child].label.as_str())
+            .collect();
+        assert_eq!(labels[0], "check");
+        assert_eq!(labels.last(), Some(&"all-in"));
+        let NodeKind::Decision { player, .. } = &deeper.nodes[children[0]].kind else {
+            panic!("villain can't bet after hero checks");
+        };
+        assert_eq!(*player, VILLAIN);
+        // Turn bets are shares of the pot as the flop left it.
+        for child in &children[1..] {
+            let bet = &deeper.nodes[*child];
+            let pot = deeper.pot_bb + shallow.nodes[node].committed.iter().sum::<f64>();
+            if let Some(fraction) = bet.label.strip_prefix("pot-") {
+                let size = bet.committed[HERO] - shallow.nodes[node].committed[HERO];
+                assert!((size - fraction.parse::<f64>().unwrap() * pot).abs() < 1e-9);
+            }
+        }
+    }
+
+    // The turn pass starts from the flop's solution and answers when the budget lasts.
+    let mut config = CfrConfig::new(200);
+    config.buckets = 8;
+    let trees = vec![shallow.clone(), deeper];
+    let (depth, outcome) = run_cfr_deepening(&trees, &config);
+    assert_eq!(depth, 1);
+    assert_eq!(outcome.iterations, 200);
+
+    // End to end, the response says the turn was reached.
+    let engine = SolverEngine::new();
+    let response = engine
+        .solve(&SubgameRequest {
+            state_fingerprint: "next street".into(),
+            game_state_json: serde_json::json!({ "pot": 10.0, "street": "flop" }).to_string(),
+            board: vec!["As".into(), "Kd".into(), "7c".into()],
+            effective_stack_bb: 100,
+            action_set: vec!["pot:0.5".into(), "all-in".into()],
+            budget_iterations: 40,
+            config: Some(RequestConfig {
+                iterative_deepening: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .unwrap();
+    let deepening = response.deepening.unwrap();
+    assert_eq!(deepening.passes, deepening.depths);
+    assert!(deepening.next_street);
+    assert_eq!(response.degradation, "");
+}
@@ -0,0 +1,42 @@
+use solver::metrics::SolverMetrics;
+use solver::solver::SolverEngine;
+use solver::solver_proto::SubgameRequest;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn renders_prometheus_text() {
+    let metrics = SolverMetrics::new();
+    metrics.record_solve(true, Duration::from_millis(20));
+    metrics.record_solve(false, Duration::from_millis(3));
+    metrics.record_iterations(500, Duration::from_millis(250));
+    metrics.record_cache_lookup(true);
+    metrics.record_cache_lookup(false);
+    metrics.record_cache_lookup(true);
+
+    let text = metrics.render();
+    assert!(text.contains("solver_solves_total{status=\"ok\"} 1"));
+    assert!(text.contains("solver_solves_total{status=\"error\"} 1"));
+    assert!(text.contains("solver_solve_latency_seconds_bucket{le=\"0.005\"} 1"));
+    assert!(text.contains("solver_solve_latency_seconds_bucket{le=\"0.025\"} 2"));
+    assert!(text.contains("solver_solve_latency_seconds_count 2"));
+    assert!(text.contains("solver_iterations_total 500"));
+    assert!((metrics.iterations_per_sec() - 2000.0).abs() < 1e-6);
+    assert!((metrics.cache_hit_ratio() - 2.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn engine_counts_budget_exhaustion() {
+    let metrics = Arc::new(SolverMetrics::new());
+    let engine = SolverEngine::with_metrics(metrics.clone());
+    let request = SubgameRequest {
+        budget_ms: 0,
+        action_set: vec!["pot:0.5".into()],
+        ..Default::default()
+    };
+    assert!(engine.solve(&request).is_err());
+    assert_eq!(metrics.solves_failed.load(Ordering::Relaxed), 1);
+    assert_eq!(metrics.budget_exhausted.load(Ordering::Relaxed), 1);
+    assert_eq!(metrics.latency.count(), 1);
+}
@@ -0,0 +1,151 @@
+use solver::abstraction::{parse_action_set, BlindSummary, GameStateSummary};
+use solver::cfr::{CfrConfig, CfrSolver};
+use solver::game_tree::export::{exported_tree, tree_dot, TreeFormat};
+use solver::game_tree::GameTree;
+use solver::persist::pio::{export_pio, pio_nodes, PioExportOptions};
+use solver::persist::{PersistError, SolveSnapshot, FORMAT_VERSION};
+
+fn solved_snapshot() -> SolveSnapshot {
+    let summary = GameStateSummary {
+        pot: 10.0,
+        street: "river".into(),
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let specs = parse_action_set(
+        &["pot:0.5".to_string(), "all-in".to_string()],
+        &summary,
+        100.0,
+    );
+    let tree = GameTree::from_action_specs(&specs, &summary, 100.0);
+    let mut config = CfrConfig::new(200);
+    config.buckets = 8;
+    let mut solver = CfrSolver::new(&tree, &config);
+    solver.run(200);
+    SolveSnapshot::from_solver(&solver, "river-spot", 7)
+}
+
+#[test]
+fn snapshot_round_trips_through_bytes_and_disk() {
+    let snapshot = solved_snapshot();
+    let decoded = SolveSnapshot::decode(&snapshot.encode()).expect("decodes");
+    assert_eq!(decoded.meta, snapshot.meta);
+    assert_eq!(decoded.strategies, snapshot.strategies);
+    assert_eq!(decoded.regrets, snapshot.regrets);
+    assert_eq!(decoded.tree.nodes.len(), snapshot.tree.nodes.len());
+    assert_eq!(decoded.tree.rake, snapshot.tree.rake);
+
+    let path = std::env::temp_dir().join(format!("solver-snapshot-{}.bin", std::process::id()));
+    snapshot.save(&path).expect("saves");
+    let loaded = SolveSnapshot::load(&path).expect("loads");
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.meta.iterations, 200);
+    assert_eq!(loaded.meta.buckets, 8);
+    assert_eq!(loaded.strategies, snapshot.strategies);
+}
+
+#[test]
+fn snapshot_rejects_foreign_versions_and_corruption() {
+    let bytes = solved_snapshot().encode();
+
+    let mut newer = bytes.clone();
+    newer[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+    assert!(matches!(
+        SolveSnapshot::decode(&newer),
+        Err(PersistError::UnsupportedVersion(_))
+    ));
+
+    let mut flipped = bytes.clone();
+    let middle = bytes.len() / 2;
+    flipped[middle] ^= 0xff;
+    assert!(matches!(
+        SolveSnapshot::decode(&flipped),
+        Err(PersistError::ChecksumMismatch)
+    ));
+
+    assert!(matches!(
+        SolveSnapshot::decode(&bytes[..bytes.len() - 3]),
+        Err(PersistError::Truncated)
+    ));
+    assert!(matches!(
+        SolveSnapshot::decode(b"nope"),
+        Err(PersistError::BadMagic) | Err(PersistError::Truncated)
+    ));
+}
+
+#[test]
+fn pio_export_lists_every_decision_node_by_path() {
+    let summary = GameStateSummary {
+        pot: 10.0,
+        street: "river".into(),
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let specs = parse_action_set(&["pot:0.5".to_string()], &summary, 100.0);
+    let tree = GameTree::from_action_specs(&specs, &summary, 100.0);
+    let mut config = CfrConfig::new(100);
+    config.buckets = 4;
+    let mut solver = CfrSolver::new(&tree, &config);
+    solver.run(100);
+
+    let options = PioExportOptions::default();
+    let nodes = pio_nodes(&solver, &options);
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[0].path, "r:0");
+    assert_eq!(nodes[0].actions, vec!["b500".to_string()]);
+    assert_eq!(nodes[1].path, "r:0:b500");
+    assert_eq!(nodes[1].player, "IP");
+    assert_eq!(nodes[1].actions, vec!["f".to_string(), "c".to_string()]);
+    for bucket in 0..4 {
+        let total: f64 = nodes[1].strategy.iter().map(|row| row[bucket]).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+    }
+    assert_eq!(nodes[1].ev.len(), 4);
+    // The strongest bucket never does worse than the weakest when facing the bet.
+    assert!(nodes[1].ev[3] >= nodes[1].ev[0]);
+
+    let text = export_pio(&solver, &options);
+    assert!(text.starts_with("#PIO_STRATEGY_EXPORT 1\n#BUCKETS 4\n"));
+    assert!(text.contains("NODE r:0:b500\nPLAYER IP\nACTIONS f c\n"));
+    assert_eq!(text.matches("END\n").count(), 2);
+}
+
+#[test]
+fn tree_export_carries_pots_stacks_and_strategies() {
+    let snapshot = solved_snapshot();
+    let tree = &snapshot.tree;
+    let root = exported_tree(tree, Some(&snapshot.strategies));
+    assert_eq!(root.kind, "hero");
+    assert!((root.pot_bb - 10.0).abs() < 1e-9);
+    assert_eq!(root.stacks_bb, [100.0, 100.0]);
+    assert_eq!(root.children.len(), root.frequencies.len());
+    assert!((root.frequencies.iter().sum::<f64>() - 1.0).abs() < 1e-3);
+    assert!(root.strategy.iter().all(|row| row.len() == 8));
+    let bet = root
+        .children
+        .iter()
+        .find(|child| child.label == "pot-0.50")
+        .expect("half-pot bet");
+    assert_eq!(bet.kind, "villain");
+    assert!((bet.pot_bb - 15.0).abs() < 1e-9);
+    assert_eq!(bet.stacks_bb, [95.0, 100.0]);
+    assert!(bet
+        .children
+        .iter()
+        .any(|child| child.kind == "fold" && child.children.is_empty()));
+
+    let dot = tree_dot(tree, Some(&snapshot.strategies));
+    assert!(dot.starts_with("digraph tree {"));
+    assert_eq!(dot.matches(" -> ").count(), tree.nodes.len() - 1);
+    assert!(dot.contains("pot-0.50 "));
+    let bare = tree_dot(tree, None);
+    assert!(bare.contains("[label=\"pot-0.50\"]"));
+    assert_eq!(TreeFormat::parse("Graphviz"), Some(TreeFormat::Dot));
+    assert_eq!(TreeFormat::parse("yaml"), None);
+}
@@ -0,0 +1,96 @@
+use solver::affinity;
+use solver::config::SolverConfig;
+use solver::metrics::SolverMetrics;
+use solver::pool::run_stealing;
+use solver::solver::SolverEngine;
+use solver::solver_proto::SubgameRequest;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn every_task_runs_once_and_results_keep_task_order() {
+    let runs = AtomicUsize::new(0);
+    // One long task and many short ones: the other workers steal the short ones queued behind it.
+    let tasks: Vec<(u64, f64)> = (0..24u64)
+        .map(|task| (task, if task == 0 { 100.0 } else { 1.0 }))
+        .collect();
+    let results = run_stealing(tasks, 4, |task| {
+        runs.fetch_add(1, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(if task == 0 { 30 } else { 1 }));
+        (task * 2, thread::current().id())
+    });
+    assert_eq!(runs.load(Ordering::SeqCst), 24);
+    let doubled: Vec<u64> = results.iter().map(|(result, _)| *result).collect();
+    assert_eq!(doubled, (0..24u64).map(|task| task * 2).collect::<Vec<_>>());
+    // The long task's thread ran nothing else while the short ones finished elsewhere.
+    let long = results[0].1;
+    assert!(results[1..].iter().all(|(_, thread)| *thread != long));
+
+    assert_eq!(run_stealing(vec![(3, 1.0)], 8, |task| task + 1), [4]);
+    assert!(run_stealing(Vec::<(u8, f64)>::new(), 4, |task| task).is_empty());
+}
+
+#[test]
+fn parallel_boards_match_a_sequential_solve() {
+    let engine = |workers: usize| {
+        let mut config = SolverConfig::default();
+        config.threads.solver = workers;
+        SolverEngine::from_config(config, Arc::new(SolverMetrics::new()))
+    };
+    let request = SubgameRequest {
+        state_fingerprint: "parallel-boards".into(),
+        budget_iterations: 20,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "pot:1".into()],
+        representative_flops: 6,
+        ..Default::default()
+    };
+    let sequential = engine(1).solve(&request).expect("sequential solve");
+    let parallel = engine(4).solve(&request).expect("parallel solve");
+    assert_eq!(parallel.boards.len(), 6);
+    assert_eq!(parallel.iterations, sequential.iterations);
+    for (parallel, sequential) in parallel.boards.iter().zip(&sequential.boards) {
+        assert_eq!(parallel.cards, sequential.cards);
+        assert_eq!(parallel.actions, sequential.actions);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn pinned_threads_and_their_children_stay_on_the_pinned_core() {
+    let core = affinity::allowed_cores()[0];
+    thread::spawn(move || {
+        assert!(affinity::pin_current_thread(&[core]));
+        assert_eq!(affinity::allowed_cores(), vec![core]);
+        let child = thread::spawn(affinity::allowed_cores).join().unwrap();
+        assert_eq!(child, vec![core]);
+        assert!(!affinity::pin_current_thread(&[affinity::MAX_CORES]));
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn memory_peaks_add_up_the_boards_solving_at_once() {
+    let engine = |workers: usize| {
+        let mut config = SolverConfig::default();
+        config.threads.solver = workers;
+        SolverEngine::from_config(config, Arc::new(SolverMetrics::new()))
+    };
+    let request = SubgameRequest {
+        state_fingerprint: "board-memory".into(),
+        budget_iterations: 10,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "pot:1".into()],
+        representative_flops: 3,
+        ..Default::default()
+    };
+    let sequential = engine(1).solve(&request).unwrap().memory.unwrap();
+    let parallel = engine(3).solve(&request).unwrap().memory.unwrap();
+    assert!(sequential.peak_bytes <= sequential.tree_bytes + sequential.table_bytes);
+    assert_eq!(parallel.nodes, sequential.nodes);
+    assert!(parallel.peak_bytes > sequential.peak_bytes);
+    assert!(parallel.peak_bytes <= 3 * sequential.peak_bytes);
+}
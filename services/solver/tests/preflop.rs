@@ -0,0 +1,92 @@
+use solver::cfr::CfrConfig;
+use solver::game_tree::preflop::{preflop_tree, Position, PreflopSizing};
+use solver::game_tree::{GameTree, NodeKind};
+use solver::preflop::{solve_charts, ChartConfig};
+
+fn labels(tree: &GameTree, node: usize) -> Vec<String> {
+    match &tree.nodes[node].kind {
+        NodeKind::Decision { children, .. } => children
+            .iter()
+            .map(|&child| tree.nodes[child].label.clone())
+            .collect(),
+        NodeKind::Terminal(_) => Vec::new(),
+    }
+}
+
+fn child(tree: &GameTree, node: usize, label: &str) -> usize {
+    let NodeKind::Decision { children, .. } = &tree.nodes[node].kind else {
+        panic!("{} is terminal", node);
+    };
+    *children
+        .iter()
+        .find(|&&child| tree.nodes[child].label == label)
+        .expect(label)
+}
+
+#[test]
+fn preflop_trees_run_from_the_open_to_the_jam() {
+    let sizing = PreflopSizing::default();
+    let deep = preflop_tree(Position::Button, 100.0, &sizing);
+    assert!(deep.preflop);
+    assert_eq!(deep.pot_bb, 1.5);
+    assert_eq!(labels(&deep, 0), ["fold", "open", "all-in"]);
+    let open = child(&deep, 0, "open");
+    assert_eq!(labels(&deep, open), ["fold", "call", "3-bet", "all-in"]);
+    let three_bet = child(&deep, open, "3-bet");
+    assert_eq!(
+        labels(&deep, three_bet),
+        ["fold", "call", "4-bet", "all-in"]
+    );
+    let four_bet = child(&deep, three_bet, "4-bet");
+    assert_eq!(labels(&deep, four_bet), ["fold", "call", "all-in"]);
+    let jam = child(&deep, four_bet, "all-in");
+    assert_eq!(labels(&deep, jam), ["fold", "call"]);
+    // The big blind's posted chip is already in: calling the 2.5 open costs 1.5.
+    assert_eq!(deep.nodes[child(&deep, open, "call")].committed, [2.5, 1.5]);
+
+    // Twelve big blinds leave no room for a 4-bet short of all-in.
+    let short = preflop_tree(Position::SmallBlind, 12.0, &sizing);
+    assert_eq!(short.actions[1].amount, 3.0);
+    let three_bet = child(&short, child(&short, 0, "open"), "3-bet");
+    assert_eq!(labels(&short, three_bet), ["fold", "call", "all-in"]);
+    let called = child(&short, child(&short, 0, "all-in"), "call");
+    assert_eq!(short.nodes[called].committed, [11.5, 11.0]);
+}
+
+#[test]
+fn charts_cover_every_line_on_the_hand_grid() {
+    let config = ChartConfig {
+        iterations: 300,
+        buckets: 16,
+        runouts: 8,
+        ..ChartConfig::default()
+    };
+    let file = solve_charts(Position::Cutoff, 40.0, &config, &CfrConfig::new(0));
+    assert_eq!(file.file_name(), "CO_40bb.json");
+    assert_eq!(file.iterations, 300);
+    let names: Vec<&str> = file
+        .charts
+        .iter()
+        .map(|chart| chart.name.as_str())
+        .collect();
+    assert_eq!(names, ["rfi", "vs_open", "vs_3bet", "vs_4bet"]);
+    let players: Vec<&str> = file
+        .charts
+        .iter()
+        .map(|chart| chart.player.as_str())
+        .collect();
+    assert_eq!(players, ["CO", "BB", "CO", "BB"]);
+
+    let rfi = &file.charts[0];
+    assert_eq!(rfi.actions, ["fold", "open", "all-in"]);
+    assert_eq!(rfi.rows.len(), 13);
+    let aces = &rfi.rows[0][0];
+    assert_eq!(aces.hand, "AA");
+    assert!(aces.frequencies[0] < 0.05, "{:?}", aces.frequencies);
+    let trash = &rfi.rows[12][7];
+    assert_eq!(trash.hand, "72o");
+    assert!(trash.frequencies[0] > aces.frequencies[0]);
+    for cell in rfi.rows.iter().flatten() {
+        assert!((cell.frequencies.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,60 @@
+use solver::config::ProfilingConfig;
+use solver::profiling::{collect_profile, profile_request, ProfileError, ProfileKind};
+use std::time::Duration;
+
+fn enabled() -> ProfilingConfig {
+    ProfilingConfig {
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn profile_paths_follow_pprof_and_cap_the_duration() {
+    let cpu = |seconds| ProfileKind::Cpu {
+        duration: Duration::from_secs(seconds),
+    };
+    let config = enabled();
+    assert_eq!(
+        profile_request("/debug/pprof/profile", &config),
+        Some(cpu(30))
+    );
+    assert_eq!(
+        profile_request("/debug/pprof/profile?debug=0&seconds=5", &config),
+        Some(cpu(5))
+    );
+    assert_eq!(
+        profile_request("/debug/pprof/profile?seconds=600", &config),
+        Some(cpu(60))
+    );
+    assert_eq!(
+        profile_request("/debug/pprof/profile?seconds=0", &config),
+        Some(cpu(1))
+    );
+    assert_eq!(
+        profile_request("/debug/pprof/heap", &config),
+        Some(ProfileKind::Heap)
+    );
+    assert_eq!(profile_request("/debug/pprof/goroutine", &config), None);
+
+    // Off by default: the endpoints don't exist at all.
+    let config = ProfilingConfig::default();
+    assert_eq!(profile_request("/debug/pprof/heap", &config), None);
+}
+
+#[cfg(not(feature = "profiling"))]
+#[test]
+fn builds_without_the_feature_say_so() {
+    let config = enabled();
+    let cpu = ProfileKind::Cpu {
+        duration: Duration::from_secs(1),
+    };
+    assert_eq!(
+        collect_profile(cpu, &config),
+        Err(ProfileError::Unsupported)
+    );
+    assert_eq!(
+        collect_profile(ProfileKind::Heap, &config),
+        Err(ProfileError::Unsupported)
+    );
+}
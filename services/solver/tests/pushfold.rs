@@ -0,0 +1,140 @@
+use solver::config::SolverConfig;
+use solver::metrics::SolverMetrics;
+use solver::preflop::pushfold::{
+    push_fold_chart, solve_push_fold, ClassEquities, PushFoldGame, PushFoldOptions,
+    PushFoldPayoffs, CLASSES,
+};
+use solver::solver::SolverEngine;
+use solver::solver_proto::{
+    Blinds, GameState, HandAction, SolverConfig as RequestConfig, SubgameRequest,
+};
+use std::sync::Arc;
+
+fn class(name: &str) -> usize {
+    (0..CLASSES)
+        .find(|class| solver::abstraction::matrix::hand_class(class / 13, class % 13) == name)
+        .expect(name)
+}
+
+#[test]
+fn push_fold_equilibria_jam_wider_the_shorter_the_stack() {
+    let equities = ClassEquities::shared(32, 0);
+    let (aces, kings, trash) = (class("AA"), class("KK"), class("72o"));
+    assert!(equities.equity(aces, trash) > 0.75);
+    assert!((equities.equity(aces, trash) + equities.equity(trash, aces) - 1.0).abs() < 1e-9);
+    // Six aces combos, each leaving exactly one other pair of aces.
+    assert_eq!(equities.pairs(aces, aces), 6.0);
+    assert_eq!(equities.pairs(aces, kings), 36.0);
+
+    let deep = solve_push_fold(
+        &PushFoldPayoffs::chips(&PushFoldGame::new(15.0)),
+        &equities,
+        400,
+    );
+    let short = solve_push_fold(
+        &PushFoldPayoffs::chips(&PushFoldGame::new(2.0)),
+        &equities,
+        400,
+    );
+    assert!(deep.jam[aces] > 0.95 && deep.call[aces] > 0.95);
+    assert!(deep.jam[trash] < 0.05, "{}", deep.jam[trash]);
+    assert!(deep.exploitability < 0.05, "{}", deep.exploitability);
+    let range = |jam: &[f64]| jam.iter().sum::<f64>();
+    assert!(range(&short.jam) > range(&deep.jam));
+    assert!(range(&short.call) > range(&deep.call));
+
+    let options = PushFoldOptions {
+        iterations: 200,
+        runouts: 32,
+        seed: 0,
+        threshold_stacks_bb: vec![2.0, 5.0, 10.0, 15.0],
+    };
+    let chart = push_fold_chart(&PushFoldGame::new(10.0), &options);
+    assert_eq!(chart.hands.len(), CLASSES);
+    assert_eq!(chart.hands[0].hand, "AA");
+    assert_eq!(chart.hands[0].combos, 6);
+    assert_eq!(chart.hands[0].jam_threshold_bb, 15.0);
+    assert!(chart.hands[trash].jam_threshold_bb < 15.0);
+    assert!(chart.hands[trash].call_threshold_bb < chart.hands[kings].call_threshold_bb);
+}
+
+#[test]
+fn short_preflop_spots_skip_the_tree_when_push_fold_is_on() {
+    let mut config = SolverConfig::default();
+    config.push_fold.runouts = 32;
+    config.push_fold.iterations = 200;
+    let engine = SolverEngine::from_config(config, Arc::new(SolverMetrics::new()));
+    let state = GameState {
+        street: "preflop".into(),
+        blinds: Some(Blinds {
+            small: 1.0,
+            big: 2.0,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let open = SubgameRequest {
+        state_fingerprint: "push-fold".into(),
+        effective_stack_bb: 10,
+        budget_iterations: 50,
+        game_state: Some(state.clone()),
+        config: Some(RequestConfig {
+            push_fold: true,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let response = engine.solve(&open).unwrap();
+    assert_eq!(response.source, "push-fold");
+    let labels: Vec<&str> = response
+        .actions
+        .iter()
+        .map(|action| action.action_type.as_str())
+        .collect();
+    assert_eq!(labels, ["fold", "all-in"]);
+    assert_eq!(response.actions[1].amount, 10.0);
+    let total: f64 = response.actions.iter().map(|action| action.frequency).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    let report = response.push_fold.unwrap();
+    assert_eq!(report.hands.len(), 169);
+    assert_eq!(report.hands[0].jam_threshold_bb, 15.0);
+
+    // Facing the jam, hero is the big blind deciding to call.
+    let facing = engine
+        .solve(&SubgameRequest {
+            state_fingerprint: "push-fold-call".into(),
+            game_state: Some(GameState {
+                history: vec![HandAction {
+                    seat: 1,
+                    street: "preflop".into(),
+                    action: "raise".into(),
+                    amount: 20.0,
+                }],
+                ..state.clone()
+            }),
+            ..open.clone()
+        })
+        .unwrap();
+    assert_eq!(facing.actions[1].action_type, "call");
+
+    // Deeper than the limit, or without the switch, the spot gets a tree.
+    let deep = engine
+        .solve(&SubgameRequest {
+            state_fingerprint: "push-fold-deep".into(),
+            effective_stack_bb: 40,
+            action_set: vec!["all-in".into()],
+            ..open.clone()
+        })
+        .unwrap();
+    assert_eq!(deep.source, "subgame");
+    let off = engine
+        .solve(&SubgameRequest {
+            state_fingerprint: "push-fold-off".into(),
+            action_set: vec!["all-in".into()],
+            config: None,
+            ..open
+        })
+        .unwrap();
+    assert_eq!(off.source, "subgame");
+    assert!(off.push_fold.is_none());
+}
@@ -1,13 +1,41 @@
-use solver::abstraction::{parse_action_set, BlindSummary, GameStateSummary};
-use solver::solver::SolverEngine;
-use solver::solver_proto::SubgameRequest;
+use solver::abstraction::blockers::exact_river;
+use solver::abstraction::overbet::{auto_overbet_fractions, expand_overbet_tokens, nut_advantage};
+use solver::abstraction::templates::{tree_template, TREE_TEMPLATES};
+use solver::abstraction::{
+    merge_similar_sizes, parse_action_set, parse_action_set_strict, parse_action_set_with_policy,
+    street_action_tokens, ActionRecord, ActionSpec, BettingRound, BlindSummary, GameStateSummary,
+    GameVariant, LimitSummary, PlayerSummary, SizingPolicy, Street, TokenError,
+};
+use solver::cards::parse_cards;
+use solver::cfr::{run_cfr_with_config, CfrConfig};
+use solver::error::SolverError;
+use solver::game_tree::chips::ChipRounding;
+use solver::game_tree::legality::{AdjustmentReason, BettingContext};
+use solver::game_tree::size_search::{best_size, search_bet_size, SizeSearch};
+use solver::game_tree::{ActionKind as TreeActionKind, GameTree, NodeKind, TerminalKind, HERO};
+use solver::metrics::SolverMetrics;
+use solver::progress::SolveProgress;
+use solver::rng::seed_from_fingerprint;
+use solver::solver::{SolverEngine, SOLVER_VERSION};
+use solver::solver_proto::{
+    ActionKind, ActionRemovalRequest, BenchmarkRequest, BetSizeSearchRequest, BettingRoundActions,
+    Blinds, GameState, HandAction, Player, SolverConfig, StackSweepPoint, StackSweepRequest,
+    StreetActionSets, SubgameRequest, WeightedBoard,
+};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tonic::{Code, Status};
 
 #[test]
 fn parses_action_set() {
     let summary = GameStateSummary {
         pot: 20.0,
         street: "preflop".into(),
-        blinds: BlindSummary { big: 2.0 },
+        blinds: BlindSummary {
+            big: 2.0,
+            ..Default::default()
+        },
+        ..Default::default()
     };
     let specs = parse_action_set(
         &["pot:0.5".to_string(), "all-in".to_string()],
@@ -31,11 +59,1746 @@ fn solver_engine_returns_actions() {
         budget_ms: 200,
         effective_stack_bb: 120,
         action_set: vec!["pot:0.33".into(), "pot:0.75".into(), "all-in".into()],
+        ..Default::default()
     };
 
-    let response = engine.solve(&request);
+    let response = engine.solve(&request).expect("solve succeeds");
     assert_eq!(response.source, "subgame");
     assert_eq!(response.actions.len(), 3);
     let total_freq: f64 = response.actions.iter().map(|a| a.frequency).sum();
     assert!(total_freq > 0.0);
 }
+
+#[test]
+fn actions_keep_the_request_order_and_say_what_they_are() {
+    let engine = SolverEngine::new();
+    let request = SubgameRequest {
+        state_fingerprint: "ordered".into(),
+        game_state_json:
+            serde_json::json!({ "pot": 12.0, "street": "flop", "blinds": { "big": 2 } }).to_string(),
+        budget_iterations: 20,
+        effective_stack_bb: 100,
+        action_set: vec!["all-in".into(), "pot:0.75".into(), "pot:0.33".into()],
+        ..Default::default()
+    };
+    let response = engine.solve(&request).expect("solve succeeds");
+    let labels: Vec<&str> = response
+        .actions
+        .iter()
+        .map(|action| action.action_type.as_str())
+        .collect();
+    assert_eq!(labels, ["all-in", "pot-0.75", "pot-0.33"]);
+    let kinds: Vec<ActionKind> = response
+        .actions
+        .iter()
+        .map(|action| action.kind())
+        .collect();
+    assert_eq!(kinds, [ActionKind::AllIn, ActionKind::Bet, ActionKind::Bet]);
+    assert!((response.actions[1].pot_fraction - 0.75).abs() < 1e-9);
+    assert!((response.actions[0].pot_fraction - 100.0 / 6.0).abs() < 1e-9);
+}
+
+#[test]
+fn antes_and_straddle_are_folded_into_pot() {
+    let summary = GameStateSummary {
+        pot: 3.0,
+        street: "preflop".into(),
+        blinds: BlindSummary {
+            small: 1.0,
+            big: 2.0,
+            ante: 0.25,
+            straddle: 4.0,
+            ..Default::default()
+        },
+        num_players: 6,
+        ..Default::default()
+    };
+    // 3 in blinds + 6 * 0.25 antes + 4 straddle = 8.5 chips.
+    assert!((summary.pot_in_bb() - 4.25).abs() < 1e-9);
+    assert!((summary.min_bet_bb() - 2.0).abs() < 1e-9);
+
+    let bb_ante = GameStateSummary {
+        pot: 3.0,
+        blinds: BlindSummary {
+            big: 2.0,
+            big_blind_ante: 2.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!((bb_ante.pot_in_bb() - 2.5).abs() < 1e-9);
+}
+
+#[test]
+fn seated_players_set_the_effective_stack_per_matchup() {
+    let seat = |seat: usize, position: &str, stack: f64, committed: f64| PlayerSummary {
+        seat,
+        position: position.into(),
+        stack,
+        committed,
+        folded: false,
+    };
+    let mut summary = GameStateSummary {
+        blinds: BlindSummary {
+            big: 2.0,
+            ..Default::default()
+        },
+        players: vec![
+            seat(1, "CO", 300.0, 0.0),
+            seat(3, "BTN", 90.0, 10.0),
+            seat(5, "BB", 500.0, 2.0),
+        ],
+        hero_seat: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(summary.seated_players(), 3);
+    assert_eq!(summary.effective_stack_bb(1, 3), Some(50.0));
+    assert_eq!(summary.effective_stack_bb(3, 1), Some(45.0));
+    assert_eq!(summary.effective_stack_bb(1, 5), Some(150.0));
+    assert_eq!(summary.effective_stack_bb(1, 7), None);
+    // Without a named villain, hero can lose at most what the deepest live opponent covers.
+    assert_eq!(summary.hero_effective_stack_bb(), Some(150.0));
+    summary.players[2].folded = true;
+    assert_eq!(summary.hero_effective_stack_bb(), Some(50.0));
+    summary.villain_seat = Some(5);
+    assert_eq!(summary.hero_effective_stack_bb(), Some(150.0));
+    summary.hero_seat = None;
+    assert_eq!(summary.hero_effective_stack_bb(), None);
+}
+
+#[test]
+fn structured_game_states_replace_the_json_one() {
+    let engine = SolverEngine::new();
+    let state = GameState {
+        pot: 20.0,
+        street: "river".into(),
+        blinds: Some(Blinds {
+            big: 2.0,
+            ..Default::default()
+        }),
+        players: vec![
+            Player {
+                seat: 0,
+                stack: 60.0,
+                ..Default::default()
+            },
+            Player {
+                seat: 1,
+                stack: 400.0,
+                ..Default::default()
+            },
+        ],
+        hero_seat: Some(0),
+        ..Default::default()
+    };
+    let request = SubgameRequest {
+        state_fingerprint: "structured".into(),
+        budget_iterations: 50,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:1".into(), "all-in".into()],
+        board: vec![
+            "As".into(),
+            "Kd".into(),
+            "7c".into(),
+            "7h".into(),
+            "2s".into(),
+        ],
+        game_state: Some(state),
+        ..Default::default()
+    };
+    let response = engine.solve(&request).expect("solve succeeds");
+    // Hero's 60 chips are 30bb behind, whatever effective_stack_bb says; the pot is 10bb.
+    let amounts: Vec<f64> = response
+        .actions
+        .iter()
+        .map(|action| action.amount)
+        .collect();
+    assert_eq!(amounts, [10.0, 30.0]);
+}
+
+#[test]
+fn amounts_round_to_legal_chip_increments() {
+    // $1/$2 counted in cents, betting in quarters.
+    let rounding = ChipRounding::new(200.0, 25, 100.0, 1.0);
+    assert_eq!(rounding.stack, 20_000);
+    assert_eq!(rounding.to_chips(3.3), 650);
+    assert_eq!(rounding.to_chips(3.4), 675);
+    // Never below the minimum bet, and the all-in is the whole stack even off the increment.
+    assert_eq!(rounding.to_chips(0.2), 200);
+    let odd = ChipRounding::new(200.0, 25, 99.99, 1.0);
+    assert_eq!(odd.to_chips(99.99), 19_998);
+    assert_eq!(odd.to_chips(99.95), 19_998);
+    assert_eq!(rounding.to_chips(0.0), 0);
+
+    let engine = SolverEngine::new();
+    let response = engine
+        .solve(&SubgameRequest {
+            state_fingerprint: "chips".into(),
+            budget_iterations: 20,
+            effective_stack_bb: 100,
+            action_set: vec!["pot:0.33".into(), "all-in".into()],
+            game_state: Some(GameState {
+                pot: 1000.0,
+                street: "river".into(),
+                blinds: Some(Blinds {
+                    big: 100.0,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            config: Some(SolverConfig {
+                chip_increment: 25,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .expect("solve succeeds");
+    let chips: Vec<u64> = response
+        .actions
+        .iter()
+        .map(|action| action.amount_chips)
+        .collect();
+    assert_eq!(chips, [325, 10_000]);
+}
+
+#[test]
+fn actions_report_the_pot_and_stacks_they_leave() {
+    let response = SolverEngine::new()
+        .solve(&SubgameRequest {
+            state_fingerprint: "after-state".into(),
+            budget_iterations: 20,
+            effective_stack_bb: 100,
+            action_set: vec!["pot:0.5".into(), "all-in".into()],
+            game_state: Some(GameState {
+                pot: 20.0,
+                street: "river".into(),
+                blinds: Some(Blinds {
+                    big: 2.0,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .expect("solve succeeds");
+    let half_pot = &response.actions[0];
+    assert_eq!(half_pot.pot_after_bb, 15.0);
+    assert_eq!(half_pot.stack_after_bb, 95.0);
+    assert!((half_pot.spr_after - 95.0 / 15.0).abs() < 1e-9);
+    let all_in = &response.actions[1];
+    assert_eq!(all_in.pot_after_bb, 110.0);
+    assert_eq!(all_in.stack_after_bb, 0.0);
+    assert_eq!(all_in.spr_after, 0.0);
+}
+
+#[test]
+fn responses_report_the_memory_the_solve_held() {
+    let solve = |buckets: u32| {
+        let mut config = solver::config::SolverConfig::default();
+        config.abstraction.buckets = buckets as usize;
+        SolverEngine::from_config(config, Arc::new(SolverMetrics::new()))
+            .solve(&SubgameRequest {
+                state_fingerprint: "memory".into(),
+                budget_iterations: 10,
+                effective_stack_bb: 100,
+                action_set: vec!["pot:0.5".into(), "all-in".into()],
+                game_state: Some(GameState {
+                    pot: 20.0,
+                    street: "river".into(),
+                    blinds: Some(Blinds {
+                        big: 2.0,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .expect("solve succeeds")
+    };
+    let small = solve(16);
+    let memory = small.memory.clone().expect("memory is reported");
+    assert_eq!(memory.peak_bytes, memory.tree_bytes + memory.table_bytes);
+    assert!(memory.nodes > 1 && memory.tree_bytes > 0);
+    assert_eq!(memory.info_sets % small.buckets, 0);
+
+    // More buckets grow the tables, not the tree.
+    let large = solve(64).memory.expect("memory is reported");
+    assert_eq!(large.nodes, memory.nodes);
+    assert_eq!(large.tree_bytes, memory.tree_bytes);
+    assert_eq!(large.info_sets, 4 * memory.info_sets);
+    assert!(large.table_bytes > memory.table_bytes);
+}
+
+#[test]
+fn depth_two_reports_villains_reply_to_each_action() {
+    let mut request = SubgameRequest {
+        state_fingerprint: "depth".into(),
+        budget_iterations: 50,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "pot:1.5".into()],
+        ..Default::default()
+    };
+    let engine = SolverEngine::new();
+    let root_only = engine.solve(&request).expect("solve succeeds");
+    assert!(root_only
+        .actions
+        .iter()
+        .all(|action| action.responses.is_empty()));
+
+    request.response_depth = 2;
+    let response = engine.solve(&request).expect("solve succeeds");
+    for action in &response.actions {
+        let labels: Vec<&str> = action
+            .responses
+            .iter()
+            .map(|reply| reply.action_type.as_str())
+            .collect();
+        assert_eq!(labels, ["fold", "call"]);
+        let total: f64 = action.responses.iter().map(|reply| reply.frequency).sum();
+        assert!((total - 1.0).abs() < 1e-6, "{}", total);
+    }
+}
+
+#[test]
+fn integer_chip_mode_snaps_sizes_to_whole_units() {
+    // Quarters at a 100-chip big blind: four units to the BB.
+    let engine = SolverEngine::new();
+    let response = engine
+        .solve(&SubgameRequest {
+            state_fingerprint: "integer-chips".into(),
+            budget_iterations: 20,
+            effective_stack_bb: 100,
+            action_set: vec!["pot:0.33".into(), "all-in".into()],
+            game_state: Some(GameState {
+                pot: 1000.0,
+                street: "river".into(),
+                blinds: Some(Blinds {
+                    big: 100.0,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            config: Some(SolverConfig {
+                chip_increment: 25,
+                integer_chips: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .expect("solve succeeds");
+    assert_eq!(response.actions.len(), 2);
+    assert_eq!(response.actions[0].amount, 3.25);
+    for action in &response.actions {
+        assert_eq!((action.amount * 4.0).fract(), 0.0);
+        assert!(action.ev.is_finite());
+    }
+}
+
+#[test]
+fn legalizes_raise_sizes() {
+    let summary = GameStateSummary {
+        pot: 20.0,
+        street: "flop".into(),
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let specs = parse_action_set(
+        &[
+            "abs:8".to_string(),
+            "abs:20".to_string(),
+            "pot:3".to_string(),
+            "all-in".to_string(),
+        ],
+        &summary,
+        50.0,
+    );
+    let context = BettingContext {
+        facing_bet_bb: 6.0,
+        last_raise_bb: 6.0,
+        min_bet_bb: 1.0,
+        opponent_all_in: false,
+        raises_this_street: 0,
+        max_raises: 0,
+    };
+    let tree = GameTree::build(&specs, &summary, 50.0, &context);
+    let amounts: Vec<f64> = tree.actions.iter().map(|a| a.amount).collect();
+    assert_eq!(amounts, vec![12.0, 20.0, 50.0]);
+    let reasons: Vec<AdjustmentReason> = tree
+        .legalization
+        .adjustments
+        .iter()
+        .map(|a| a.reason)
+        .collect();
+    assert_eq!(
+        reasons,
+        vec![
+            AdjustmentReason::RaisedToMinimum,
+            AdjustmentReason::CappedToAllIn,
+            AdjustmentReason::MergedDuplicate,
+        ]
+    );
+
+    let facing_all_in = BettingContext {
+        opponent_all_in: true,
+        ..context
+    };
+    let tree = GameTree::build(&specs, &summary, 50.0, &facing_all_in);
+    assert!(tree.is_empty());
+    assert_eq!(tree.legalization.adjustments.len(), specs.len());
+}
+
+#[test]
+fn folds_checks_and_calls_are_root_actions_where_they_apply() {
+    let summary = GameStateSummary {
+        pot: 10.0,
+        street: "river".into(),
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let tokens: Vec<String> = ["check", "pot:0.5", "fold", "call", "abs:20"]
+        .iter()
+        .map(|token| token.to_string())
+        .collect();
+    let specs = parse_action_set(&tokens, &summary, 50.0);
+    assert_eq!(specs.len(), 5);
+
+    // Opening the action, hero can check but has nothing to fold or call.
+    let tree = GameTree::build(&specs, &summary, 50.0, &BettingContext::opening(&summary));
+    let kinds: Vec<TreeActionKind> = tree.actions.iter().map(|action| action.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            TreeActionKind::Check,
+            TreeActionKind::Bet,
+            TreeActionKind::Bet
+        ]
+    );
+    assert_eq!(tree.actions[0].amount, 0.0);
+    let NodeKind::Decision { children, .. } = &tree.root().kind else {
+        panic!("root is a decision");
+    };
+    let checked = &tree.nodes[children[0]];
+    assert!(matches!(
+        checked.kind,
+        NodeKind::Terminal(TerminalKind::Showdown)
+    ));
+    assert_eq!(checked.committed, [0.0, 0.0]);
+    let unavailable: Vec<&str> = tree
+        .legalization
+        .adjustments
+        .iter()
+        .filter(|note| note.reason == AdjustmentReason::Unavailable)
+        .map(|note| note.label.as_str())
+        .collect();
+    assert_eq!(unavailable, ["fold", "call"]);
+
+    // Facing a bet of 6 hero folds for nothing or calls for 6, and can still raise; the half-pot
+    // bet becomes a min-raise.
+    let facing = BettingContext {
+        facing_bet_bb: 6.0,
+        last_raise_bb: 6.0,
+        min_bet_bb: 1.0,
+        opponent_all_in: false,
+        raises_this_street: 0,
+        max_raises: 0,
+    };
+    let tree = GameTree::build(&specs, &summary, 50.0, &facing);
+    let actions: Vec<(TreeActionKind, f64)> = tree
+        .actions
+        .iter()
+        .map(|action| (action.kind, action.amount))
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            (TreeActionKind::Raise, 12.0),
+            (TreeActionKind::Fold, 0.0),
+            (TreeActionKind::Call, 6.0),
+            (TreeActionKind::Raise, 20.0),
+        ]
+    );
+    let NodeKind::Decision { children, .. } = &tree.root().kind else {
+        panic!("root is a decision");
+    };
+    assert!(matches!(
+        tree.nodes[children[1]].kind,
+        NodeKind::Terminal(TerminalKind::Fold { folder: HERO })
+    ));
+    assert_eq!(tree.nodes[children[2]].committed, [6.0, 0.0]);
+    let outcome = run_cfr_with_config(&tree, &CfrConfig::new(50));
+    let total: f64 = outcome.actions.iter().map(|stat| stat.frequency).sum();
+    assert!((total - 1.0).abs() < 1e-6);
+
+    // Facing an all-in only the fold and the call are left.
+    let tree = GameTree::build(
+        &specs,
+        &summary,
+        50.0,
+        &BettingContext {
+            opponent_all_in: true,
+            ..facing
+        },
+    );
+    let kinds: Vec<TreeActionKind> = tree.actions.iter().map(|action| action.kind).collect();
+    assert_eq!(kinds, [TreeActionKind::Fold, TreeActionKind::Call]);
+}
+
+#[test]
+fn the_street_history_puts_hero_facing_a_bet() {
+    let seat = |seat: u32, stack: f64, committed: f64| Player {
+        seat,
+        stack,
+        committed,
+        ..Default::default()
+    };
+    let action = |seat: u32, action: &str, amount: f64| HandAction {
+        seat,
+        street: "flop".into(),
+        action: action.into(),
+        amount,
+    };
+    // Hero bets 3 and villain raises to 9: a call costs 6 more, and a re-raise must add 6.
+    let state = GameState {
+        pot: 30.0,
+        street: "flop".into(),
+        blinds: Some(Blinds {
+            big: 1.0,
+            ..Default::default()
+        }),
+        players: vec![seat(0, 97.0, 3.0), seat(1, 91.0, 9.0), seat(2, 50.0, 0.0)],
+        hero_seat: Some(0),
+        history: vec![action(0, "bet", 3.0), action(1, "raise", 9.0)],
+        ..Default::default()
+    };
+    let summary = GameStateSummary::from_proto(&state);
+    assert_eq!(summary.facing_bet_bb(), 6.0);
+    assert_eq!(summary.aggressor(), Some(1));
+    assert_eq!(summary.hero_effective_stack_bb(), Some(97.0));
+    let context = BettingContext::from_summary(&summary);
+    assert_eq!((context.facing_bet_bb, context.last_raise_bb), (6.0, 6.0));
+    assert_eq!(context.round(), BettingRound::Reraise);
+    assert_eq!(context.min_legal_amount(), 12.0);
+
+    // An explicit facing bet and aggressor win over the history; an all-in aggressor leaves
+    // nothing to raise.
+    let shoved = GameStateSummary::from_proto(&GameState {
+        players: vec![seat(0, 97.0, 3.0), seat(1, 91.0, 9.0), seat(2, 0.0, 50.0)],
+        facing_bet: Some(47.0),
+        aggressor_seat: Some(2),
+        ..state.clone()
+    });
+    let context = BettingContext::from_summary(&shoved);
+    assert_eq!(context.facing_bet_bb, 47.0);
+    assert!(context.opponent_all_in);
+    // Without any bet on this street hero opens, preflop blinds included.
+    let unopened = GameStateSummary {
+        street: "preflop".into(),
+        ..summary.clone()
+    };
+    assert_eq!(
+        BettingContext::from_summary(&unopened),
+        BettingContext::opening(&unopened)
+    );
+
+    let engine = SolverEngine::new();
+    let request = SubgameRequest {
+        state_fingerprint: "facing-raise".into(),
+        budget_iterations: 20,
+        action_set: vec!["pot:1".into(), "fold".into(), "check".into(), "call".into()],
+        game_state: Some(state),
+        ..Default::default()
+    };
+    let response = engine.solve(&request).expect("solve succeeds");
+    let kinds: Vec<ActionKind> = response
+        .actions
+        .iter()
+        .map(|action| action.kind())
+        .collect();
+    assert_eq!(
+        kinds,
+        [ActionKind::Raise, ActionKind::Fold, ActionKind::Call]
+    );
+    assert_eq!(response.actions[2].amount, 6.0);
+
+    let mut request = request;
+    request.game_state.as_mut().unwrap().aggressor_seat = Some(0);
+    let issues = engine.validate(&request);
+    assert!(issues
+        .iter()
+        .any(|issue| issue.field == "game_state.aggressor_seat" && issue.blocking));
+}
+
+#[test]
+fn re_raises_alternate_up_to_the_street_cap() {
+    let summary = GameStateSummary {
+        pot: 10.0,
+        street: "flop".into(),
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let specs = parse_action_set(&["pot:0.5".to_string()], &summary, 100.0);
+    let opening = BettingContext::opening(&summary);
+    let tree = GameTree::build(&specs, &summary, 100.0, &opening);
+    assert_eq!(tree.nodes.len(), 4);
+
+    // Villain raises the 5bb bet to 15 or shoves; hero answers the 15 with 45 or a shove, and
+    // the second raise uses up the street.
+    let tree = GameTree::build(
+        &specs,
+        &summary,
+        100.0,
+        &BettingContext {
+            max_raises: 2,
+            ..opening.clone()
+        },
+    );
+    let decision = |index: usize| {
+        let NodeKind::Decision { player, children } = &tree.nodes[index].kind else {
+            panic!("node {} is a decision", index);
+        };
+        let replies: Vec<(&str, [f64; 2])> = children
+            .iter()
+            .map(|child| {
+                (
+                    tree.nodes[*child].label.as_str(),
+                    tree.nodes[*child].committed,
+                )
+            })
+            .collect();
+        (*player, replies)
+    };
+    let NodeKind::Decision { children, .. } = &tree.root().kind else {
+        panic!("root is a decision");
+    };
+    let (player, replies) = decision(children[0]);
+    assert_eq!(player, 1 - HERO);
+    assert_eq!(
+        replies,
+        [
+            ("fold", [5.0, 0.0]),
+            ("call", [5.0, 5.0]),
+            ("raise", [5.0, 15.0]),
+            ("all-in", [5.0, 100.0]),
+        ]
+    );
+    let NodeKind::Decision { children, .. } = &tree.nodes[children[0]].kind else {
+        panic!("villain decides");
+    };
+    let (player, replies) = decision(children[2]);
+    assert_eq!(player, HERO);
+    assert_eq!(
+        replies,
+        [
+            ("fold", [5.0, 15.0]),
+            ("call", [15.0, 15.0]),
+            ("raise", [45.0, 15.0]),
+            ("all-in", [100.0, 15.0]),
+        ]
+    );
+    let NodeKind::Decision { children, .. } = &tree.nodes[children[2]].kind else {
+        panic!("hero decides");
+    };
+    let (_, replies) = decision(children[2]);
+    assert_eq!(replies.len(), 2);
+    let outcome = run_cfr_with_config(&tree, &CfrConfig::new(50));
+    let total: f64 = outcome.actions.iter().map(|stat| stat.frequency).sum();
+    assert!((total - 1.0).abs() < 1e-6);
+
+    // Raises made before the root count toward the cap.
+    let facing = BettingContext {
+        facing_bet_bb: 3.0,
+        last_raise_bb: 3.0,
+        raises_this_street: 1,
+        max_raises: 2,
+        ..opening
+    };
+    let tree = GameTree::build(&specs, &summary, 100.0, &facing);
+    let NodeKind::Decision { children, .. } = &tree.root().kind else {
+        panic!("root is a decision");
+    };
+    assert_eq!(decision_children(&tree, children[0]), 2);
+
+    // The request can set the cap, and villain's replies then include the re-raises.
+    let engine = SolverEngine::new();
+    let response = engine
+        .solve(&SubgameRequest {
+            state_fingerprint: "reraises".into(),
+            game_state_json: serde_json::json!({ "pot": 10.0, "street": "flop" }).to_string(),
+            budget_iterations: 20,
+            effective_stack_bb: 100,
+            action_set: vec!["pot:0.5".into()],
+            response_depth: 2,
+            config: Some(SolverConfig {
+                max_raises: Some(1),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .expect("solve succeeds");
+    let kinds: Vec<ActionKind> = response.actions[0]
+        .responses
+        .iter()
+        .map(|reply| reply.kind())
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ActionKind::Fold,
+            ActionKind::Call,
+            ActionKind::Raise,
+            ActionKind::AllIn
+        ]
+    );
+}
+
+fn decision_children(tree: &GameTree, index: usize) -> usize {
+    match &tree.nodes[index].kind {
+        NodeKind::Decision { children, .. } => children.len(),
+        NodeKind::Terminal(_) => 0,
+    }
+}
+
+#[test]
+fn geometric_sizing_gets_stacks_in() {
+    let summary = GameStateSummary {
+        pot: 10.0,
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let specs = parse_action_set(&["geo:2".to_string(), "geo:1".to_string()], &summary, 40.0);
+    assert_eq!(specs[0].label, "geo-2");
+    // Pot-sized bets twice: 10 -> 30 -> 90, putting 40bb stacks in.
+    assert!((specs[0].amount - 10.0).abs() < 1e-9);
+    assert!((specs[1].amount - 40.0).abs() < 1e-9);
+}
+
+#[test]
+fn raise_multiples_follow_the_facing_bet() {
+    let tokens = ["x:3".to_string(), "x:2.5".to_string()];
+    let mut summary = GameStateSummary {
+        pot: 1000.0,
+        street: "flop".into(),
+        blinds: BlindSummary {
+            big: 100.0,
+            ..Default::default()
+        },
+        hero_seat: Some(0),
+        ..Default::default()
+    };
+    // Nothing to raise unopened postflop.
+    assert!(parse_action_set(&tokens, &summary, 100.0).is_empty());
+
+    // Villain bets 3bb, hero's own chips don't count, and earlier streets are history.
+    summary.history = vec![
+        ActionRecord {
+            seat: 1,
+            street: "preflop".into(),
+            action: "raise".into(),
+            amount: 900.0,
+        },
+        ActionRecord {
+            seat: 0,
+            street: "flop".into(),
+            action: "check".into(),
+            amount: 0.0,
+        },
+        ActionRecord {
+            seat: 1,
+            street: "flop".into(),
+            action: "bet".into(),
+            amount: 300.0,
+        },
+    ];
+    assert_eq!(summary.facing_bet_bb(), 3.0);
+    let specs = parse_action_set(&tokens, &summary, 8.0);
+    assert_eq!(specs[0].label, "x-3.00");
+    // Capped at the stack.
+    assert_eq!(specs[0].amount, 8.0);
+    assert_eq!(specs[1].amount, 7.5);
+
+    // An unopened preflop pot faces the big blind.
+    let preflop = GameStateSummary {
+        street: "preflop".into(),
+        history: Vec::new(),
+        ..summary
+    };
+    assert_eq!(
+        parse_action_set(&tokens[1..], &preflop, 100.0)[0].amount,
+        2.5
+    );
+}
+
+#[test]
+fn sizing_policy_sets_floors_caps_and_all_ins() {
+    let summary = GameStateSummary {
+        pot: 10.0,
+        street: "flop".into(),
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let tokens: Vec<String> = ["pot:0.01", "pot:0.5", "pot:1.9", "abs:30"]
+        .iter()
+        .map(|token| token.to_string())
+        .collect();
+    let amounts = |specs: Vec<ActionSpec>| -> Vec<(String, f64)> {
+        specs
+            .into_iter()
+            .map(|spec| (spec.label, spec.amount))
+            .collect()
+    };
+    assert_eq!(
+        amounts(parse_action_set(&tokens, &summary, 20.0)),
+        [
+            ("pot-0.01".to_string(), 0.5),
+            ("pot-0.50".to_string(), 5.0),
+            ("pot-1.90".to_string(), 19.0),
+            ("abs-30.00".to_string(), 20.0),
+        ]
+    );
+
+    let policy = SizingPolicy {
+        min_bet_bb: 2.0,
+        reject_oversize: true,
+        all_in_threshold: 0.9,
+        ..Default::default()
+    };
+    assert_eq!(
+        amounts(parse_action_set_with_policy(
+            &tokens, &summary, 20.0, &policy
+        )),
+        [
+            ("pot-0.01".to_string(), 2.0),
+            ("pot-0.50".to_string(), 5.0),
+            // 95% of the stack goes all-in; the 30bb bet is dropped rather than capped.
+            ("all-in".to_string(), 20.0),
+        ]
+    );
+    let errors = parse_action_set_strict(&tokens, &summary, 20.0, &policy).unwrap_err();
+    assert_eq!(errors, [(3, TokenError::Oversize("abs:30".into()))]);
+}
+
+#[test]
+fn auto_overbets_follow_spr_and_nut_advantage() {
+    let mut summary = GameStateSummary {
+        pot: 10.0,
+        street: "turn".into(),
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        hero_seat: Some(0),
+        history: vec![ActionRecord {
+            seat: 0,
+            street: "flop".into(),
+            action: "bet".into(),
+            amount: 5.0,
+        }],
+        ..Default::default()
+    };
+    let dry = parse_cards(&["Ks".into(), "Kd".into(), "7c".into(), "2h".into()]).unwrap();
+    let wet = parse_cards(&["9h".into(), "8h".into(), "7h".into(), "2c".into()]).unwrap();
+    assert!(nut_advantage(&summary, &dry) > 0.65);
+    assert!(nut_advantage(&summary, &wet) < 0.4);
+
+    // SPR 4: the two biggest overbets that leave chips behind on the dry board, none on the wet.
+    let tokens = vec!["pot:0.5".to_string(), "overbet:auto".to_string()];
+    assert_eq!(
+        expand_overbet_tokens(&tokens, &summary, &dry, 40.0),
+        ["pot:0.5", "pot:3", "pot:2.5"]
+    );
+    assert_eq!(
+        expand_overbet_tokens(&tokens, &summary, &wet, 40.0),
+        ["pot:0.5"]
+    );
+    // Short stacks just shove.
+    assert!(auto_overbet_fractions(1.2, 0.9).is_empty());
+    assert_eq!(auto_overbet_fractions(2.2, 0.5), [1.25]);
+
+    // The tree-level cap keeps the earliest overbets.
+    summary.history.clear();
+    let explicit: Vec<String> = ["pot:1.5", "pot:0.5", "pot:2", "pot:3", "all-in"]
+        .iter()
+        .map(|token| token.to_string())
+        .collect();
+    let policy = SizingPolicy {
+        max_overbets: 1,
+        ..Default::default()
+    };
+    let labels: Vec<String> = parse_action_set_with_policy(&explicit, &summary, 100.0, &policy)
+        .into_iter()
+        .map(|spec| spec.label)
+        .collect();
+    assert_eq!(labels, ["pot-1.50", "pot-0.50", "all-in"]);
+}
+
+#[test]
+fn selects_tokens_per_street_and_round() {
+    let flat = vec!["pot:0.5".to_string()];
+    let sets = StreetActionSets {
+        river: Some(BettingRoundActions {
+            bet: vec!["pot:0.33".into(), "pot:0.75".into(), "pot:1.5".into()],
+            raise: vec!["pot:1".into()],
+            reraise: vec![],
+        }),
+        ..Default::default()
+    };
+    let river_bet =
+        street_action_tokens(Some(&sets), Some(Street::River), BettingRound::Bet, &flat);
+    assert_eq!(river_bet.len(), 3);
+    let river_reraise = street_action_tokens(
+        Some(&sets),
+        Some(Street::River),
+        BettingRound::Reraise,
+        &flat,
+    );
+    assert_eq!(river_reraise, &["pot:1".to_string()]);
+    let flop_bet = street_action_tokens(Some(&sets), Some(Street::Flop), BettingRound::Bet, &flat);
+    assert_eq!(flop_bet, flat.as_slice());
+}
+
+#[test]
+fn merges_near_duplicate_sizes() {
+    let summary = GameStateSummary {
+        pot: 20.0,
+        blinds: BlindSummary {
+            big: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let specs = parse_action_set(
+        &[
+            "pot:0.70".to_string(),
+            "abs:14.5".to_string(),
+            "pot:1.0".to_string(),
+            "abs:48".to_string(),
+            "all-in".to_string(),
+        ],
+        &summary,
+        50.0,
+    );
+    let merged = merge_similar_sizes(specs.clone(), 0.05);
+    let labels: Vec<&str> = merged.iter().map(|s| s.label.as_str()).collect();
+    assert_eq!(labels, vec!["pot-0.70", "pot-1.00", "all-in"]);
+    assert_eq!(merge_similar_sizes(specs.clone(), 0.0), specs);
+}
+
+#[test]
+fn same_seed_reproduces_the_solve() {
+    let engine = SolverEngine::new();
+    let request = SubgameRequest {
+        state_fingerprint: "seeded".into(),
+        game_state_json:
+            serde_json::json!({ "pot": 20.0, "street": "river", "blinds": { "big": 2 } })
+                .to_string(),
+        budget_ms: 100,
+        effective_stack_bb: 80,
+        action_set: vec!["pot:0.5".into(), "pot:1".into(), "all-in".into()],
+        seed: 42,
+        ..Default::default()
+    };
+
+    let first = engine.solve(&request).expect("solve succeeds");
+    let second = engine.solve(&request).expect("solve succeeds");
+    assert_eq!(first.seed, 42);
+    assert_eq!(first.actions, second.actions);
+    assert_eq!(first.exploitability, second.exploitability);
+
+    let derived = engine
+        .solve(&SubgameRequest {
+            seed: 0,
+            ..request.clone()
+        })
+        .expect("solve succeeds");
+    assert_ne!(derived.seed, 0);
+}
+
+#[test]
+fn bad_requests_map_to_error_statuses() {
+    let engine = SolverEngine::new();
+    let request = SubgameRequest {
+        state_fingerprint: "errors".into(),
+        game_state_json: "{ not json".into(),
+        budget_ms: 100,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into()],
+        ..Default::default()
+    };
+
+    let error = engine.solve(&request).expect_err("malformed state");
+    assert_eq!(error.reason(), "INVALID_GAME_STATE");
+    assert_eq!(Status::from(error).code(), Code::InvalidArgument);
+
+    let error = engine
+        .solve(&SubgameRequest {
+            game_state_json: String::new(),
+            action_set: vec!["bogus".into()],
+            ..request.clone()
+        })
+        .expect_err("no usable tokens");
+    assert_eq!(error, SolverError::EmptyActionSet);
+
+    let error = engine
+        .solve(&SubgameRequest {
+            game_state_json: String::new(),
+            budget_ms: 0,
+            ..request.clone()
+        })
+        .expect_err("no budget");
+    assert_eq!(Status::from(error).code(), Code::DeadlineExceeded);
+}
+
+#[test]
+fn per_hand_strategies_are_opt_in() {
+    let engine = SolverEngine::new();
+    let mut request = SubgameRequest {
+        state_fingerprint: "per-hand".into(),
+        budget_ms: 200,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "pot:1".into(), "all-in".into()],
+        ..Default::default()
+    };
+    let aggregate = engine.solve(&request).expect("solve succeeds");
+    assert!(aggregate.per_hand_strategies.is_empty());
+
+    request.include_per_hand = true;
+    let response = engine.solve(&request).expect("solve succeeds");
+    assert_eq!(
+        response.per_hand_strategies.len(),
+        response.buckets as usize
+    );
+    for (bucket, hand) in response.per_hand_strategies.iter().enumerate() {
+        assert_eq!(hand.bucket as usize, bucket);
+        assert_eq!(hand.actions.len(), response.actions.len());
+        let total: f64 = hand.actions.iter().map(|action| action.frequency).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        let mixed: f64 = hand
+            .actions
+            .iter()
+            .map(|action| action.frequency * action.ev)
+            .sum();
+        assert!((mixed - hand.ev).abs() < 1e-9);
+    }
+    // The aggregate frequency is the average over equally likely buckets.
+    for (index, action) in response.actions.iter().enumerate() {
+        let average = response
+            .per_hand_strategies
+            .iter()
+            .map(|hand| hand.actions[index].frequency)
+            .sum::<f64>()
+            / response.buckets as f64;
+        assert!((average - action.frequency).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn multi_board_solves_aggregate_by_weight() {
+    let engine = SolverEngine::new();
+    let board = |codes: &[&str], weight: f64| WeightedBoard {
+        cards: codes.iter().map(|code| code.to_string()).collect(),
+        weight,
+    };
+    let mut request = SubgameRequest {
+        state_fingerprint: "multi-board".into(),
+        budget_ms: 200,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "pot:1".into()],
+        boards: vec![
+            board(&["As", "Kd", "7c"], 3.0),
+            board(&["9h", "8h", "2s"], 1.0),
+        ],
+        ..Default::default()
+    };
+    let response = engine.solve(&request).expect("multi-board solve succeeds");
+    assert_eq!(response.boards.len(), 2);
+    assert!((response.boards[0].weight - 0.75).abs() < 1e-9);
+    for (index, action) in response.actions.iter().enumerate() {
+        let blended: f64 = response
+            .boards
+            .iter()
+            .map(|board| board.weight * board.actions[index].frequency)
+            .sum();
+        assert!((blended - action.frequency).abs() < 1e-9);
+    }
+
+    request.boards.clear();
+    request.representative_flops = 3;
+    let response = engine
+        .solve(&request)
+        .expect("representative solve succeeds");
+    assert_eq!(response.boards.len(), 3);
+    assert!(response.boards.iter().all(|board| board.cards.len() == 3));
+}
+
+#[test]
+fn turn_solves_report_strategy_by_river_class() {
+    let engine = SolverEngine::new();
+    let request = SubgameRequest {
+        state_fingerprint: "runouts".into(),
+        budget_ms: 200,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "pot:1".into()],
+        board: vec!["Ah".into(), "9h".into(), "7c".into(), "2d".into()],
+        runout_report: true,
+        ..Default::default()
+    };
+    let response = engine.solve(&request).expect("runout solve succeeds");
+    let categories: Vec<&str> = response
+        .runouts
+        .iter()
+        .map(|runout| runout.category.as_str())
+        .collect();
+    assert_eq!(categories, vec!["flush", "pairing", "brick"]);
+    let rivers: usize = response
+        .runouts
+        .iter()
+        .map(|runout| runout.rivers.len())
+        .sum();
+    assert_eq!(rivers, 48);
+    let weight: f64 = response.runouts.iter().map(|runout| runout.weight).sum();
+    assert!((weight - 1.0).abs() < 1e-9);
+    assert!(response
+        .runouts
+        .iter()
+        .all(|runout| runout.actions.len() == response.actions.len()));
+}
+
+#[test]
+fn flop_solves_plan_for_each_turn_class() {
+    let request = SubgameRequest {
+        state_fingerprint: "turn-plan".into(),
+        budget_iterations: 10,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "pot:1".into()],
+        board: vec!["Ah".into(), "9h".into(), "7c".into()],
+        hole_cards: vec!["Kd".into(), "Kc".into()],
+        runout_report: true,
+        ..Default::default()
+    };
+    let response = SolverEngine::new()
+        .solve(&request)
+        .expect("runout solve succeeds");
+    let turns: usize = response
+        .runouts
+        .iter()
+        .map(|runout| runout.rivers.len())
+        .sum();
+    assert_eq!(turns, 47);
+    for runout in &response.runouts {
+        assert_eq!(runout.boards.len(), runout.rivers.len());
+        for board in &runout.boards {
+            assert_eq!(board.cards.len(), 4);
+            assert_eq!(board.actions.len(), response.actions.len());
+        }
+    }
+    let flush = &response.runouts[0];
+    assert_eq!(flush.category, "flush");
+    // Eleven hearts left to complete the flush draw.
+    assert_eq!(flush.rivers.len(), 11);
+}
+
+#[test]
+fn exact_river_gives_every_combo_its_own_bucket() {
+    let board = parse_cards(&[
+        "Ah".into(),
+        "Kd".into(),
+        "8c".into(),
+        "8s".into(),
+        "2h".into(),
+    ])
+    .unwrap();
+    let exact = exact_river(&board, GameVariant::Holdem).expect("river board");
+    assert_eq!(exact.combos.len(), 1081);
+    assert_eq!(exact.showdowns.buckets, 1081);
+    // Weakest first: the nuts (quad eights) comes last, and it never loses.
+    let last = exact.combos.len() - 1;
+    assert!(exact.combos[last].iter().all(|card| card.rank == 6));
+    let cell = exact.showdowns.cell(last, 0);
+    assert_eq!(exact.showdowns.win[cell], 1.0);
+    // Hands that share a card never meet.
+    let blocked = exact
+        .combos
+        .iter()
+        .position(|combo| combo.contains(&exact.combos[last][0]) && combo != &exact.combos[last])
+        .unwrap();
+    assert_eq!(
+        exact.showdowns.weight[exact.showdowns.cell(last, blocked)],
+        0.0
+    );
+    assert!(exact_river(&board[..4], GameVariant::Holdem).is_none());
+
+    let response = SolverEngine::new()
+        .solve(&SubgameRequest {
+            state_fingerprint: "exact-river".into(),
+            budget_iterations: 3,
+            effective_stack_bb: 100,
+            action_set: vec!["pot:1".into()],
+            board: board.iter().map(ToString::to_string).collect(),
+            include_per_hand: true,
+            config: Some(SolverConfig {
+                exact_river: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .expect("exact solve succeeds");
+    assert_eq!(response.buckets, 1081);
+    assert_eq!(response.per_hand_strategies.len(), 1081);
+    assert_eq!(response.per_hand_strategies[0].hand.len(), 4);
+}
+
+#[test]
+fn short_deck_is_ante_only() {
+    let summary = GameStateSummary {
+        pot: 0.0,
+        street: "preflop".into(),
+        blinds: BlindSummary {
+            small: 1.0,
+            big: 2.0,
+            ante: 10.0,
+            straddle: 40.0,
+            ..Default::default()
+        },
+        num_players: 6,
+        variant: "short-deck".into(),
+        ..Default::default()
+    };
+    // Six antes plus the button's second one; blinds and straddle are ignored and the ante is
+    // the unit.
+    assert!((summary.forced_bets() - 70.0).abs() < 1e-9);
+    assert!((summary.pot_in_bb() - 7.0).abs() < 1e-9);
+    assert!((summary.min_bet_bb() - 2.0).abs() < 1e-9);
+
+    let specs = parse_action_set(&["pot:1".to_string()], &summary, 100.0);
+    let tree = GameTree::from_action_specs(&specs, &summary, 100.0);
+    assert!((tree.pot_bb - 7.0).abs() < 1e-9);
+    assert!((tree.actions[0].amount - 7.0).abs() < 1e-9);
+}
+
+#[test]
+fn fixed_limit_trees_use_fixed_sizes_up_to_the_cap() {
+    let mut summary = GameStateSummary {
+        pot: 8.0,
+        street: "flop".into(),
+        blinds: BlindSummary {
+            big: 2.0,
+            ..Default::default()
+        },
+        limit: Some(LimitSummary {
+            small_bet: 2.0,
+            big_bet: 4.0,
+            cap: 4,
+        }),
+        ..Default::default()
+    };
+    // Sizes in the action set are ignored.
+    let specs = parse_action_set(&["pot:0.75".to_string()], &summary, 50.0);
+    let tree = GameTree::from_action_specs(&specs, &summary, 50.0);
+    assert_eq!(tree.actions.len(), 1);
+    assert_eq!(tree.actions[0].label, "bet");
+    assert!((tree.actions[0].amount - 1.0).abs() < 1e-9);
+
+    // Bet, raise, re-raise, cap: four decisions after the root, the last without a raise.
+    let decisions: Vec<usize> = tree
+        .nodes
+        .iter()
+        .filter_map(|node| match &node.kind {
+            NodeKind::Decision { children, .. } => Some(children.len()),
+            NodeKind::Terminal(_) => None,
+        })
+        .collect();
+    assert_eq!(
+        decisions.iter().filter(|children| **children == 3).count(),
+        3
+    );
+    assert_eq!(
+        decisions.iter().filter(|children| **children == 2).count(),
+        1
+    );
+    let deepest = tree
+        .nodes
+        .iter()
+        .filter(|node| matches!(node.kind, NodeKind::Terminal(TerminalKind::Showdown)))
+        .map(|node| node.committed[HERO])
+        .fold(0.0, f64::max);
+    assert!((deepest - 4.0).abs() < 1e-9);
+    assert!(tree.nodes.iter().any(|node| matches!(
+        node.kind,
+        NodeKind::Terminal(TerminalKind::Fold { folder: HERO })
+    )));
+
+    let mut config = CfrConfig::new(200);
+    config.buckets = 4;
+    assert!(run_cfr_with_config(&tree, &config).exploitability < 0.1);
+
+    // The turn uses the big bet; facing a capped street, hero has nothing to do.
+    summary.street = "turn".into();
+    let turn = GameTree::from_action_specs(&[], &summary, 50.0);
+    assert!((turn.actions[0].amount - 2.0).abs() < 1e-9);
+    let capped = BettingContext {
+        facing_bet_bb: 8.0,
+        last_raise_bb: 2.0,
+        min_bet_bb: 2.0,
+        opponent_all_in: false,
+        raises_this_street: 3,
+        max_raises: 0,
+    };
+    assert!(GameTree::build(&[], &summary, 50.0, &capped).is_empty());
+}
+
+#[test]
+fn iteration_budgets_run_exactly_that_many_iterations() {
+    let engine = SolverEngine::new();
+    let request = SubgameRequest {
+        state_fingerprint: "benchmark".into(),
+        budget_iterations: 37,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "all-in".into()],
+        ..Default::default()
+    };
+    assert!(engine.validate(&request).is_empty());
+    let first = engine.solve(&request).expect("solve succeeds");
+    let second = engine.solve(&request).expect("solve succeeds");
+    assert_eq!(first.iterations, 37);
+    assert_eq!(first.actions, second.actions);
+
+    // A time budget still applies; a generous one leaves the iteration count in charge.
+    let timed = engine
+        .solve(&SubgameRequest {
+            budget_ms: 60_000,
+            ..request.clone()
+        })
+        .expect("solve succeeds");
+    assert_eq!(timed.iterations, 37);
+
+    let neither = engine.solve(&SubgameRequest {
+        budget_iterations: 0,
+        ..request
+    });
+    assert!(matches!(neither, Err(SolverError::BudgetExhausted { .. })));
+}
+
+#[test]
+fn responses_say_how_the_solve_ended() {
+    let engine = SolverEngine::new();
+    let request = SubgameRequest {
+        state_fingerprint: "metadata".into(),
+        budget_iterations: 37,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "all-in".into()],
+        config: Some(SolverConfig {
+            cfr_variant: "cfr+".into(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let finished = engine.solve(&request).expect("solve succeeds");
+    assert_eq!(finished.iterations, 37);
+    assert_eq!(finished.cfr_variant, "cfr+");
+    assert!(!finished.budget_exhausted);
+
+    // A millisecond leaves time for one iteration of far more than were asked for.
+    let timed_out = engine
+        .solve(&SubgameRequest {
+            state_fingerprint: "metadata-timed".into(),
+            budget_ms: 1,
+            budget_iterations: 1_000_000,
+            ..request
+        })
+        .expect("solve succeeds");
+    assert!(timed_out.iterations < 1_000_000);
+    assert!(timed_out.budget_exhausted);
+}
+
+#[test]
+fn responses_carry_the_request_hash_and_solver_version() {
+    let engine = SolverEngine::new();
+    let request = SubgameRequest {
+        state_fingerprint: "traced".into(),
+        budget_iterations: 20,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "all-in".into()],
+        ..Default::default()
+    };
+    let response = engine.solve(&request).expect("solve succeeds");
+    assert_eq!(response.request_hash.len(), 16);
+    assert_eq!(
+        response.request_hash,
+        SolverEngine::new().request_hash(&request)
+    );
+    assert_eq!(response.solver_version, SOLVER_VERSION);
+    assert!(SOLVER_VERSION.starts_with(env!("CARGO_PKG_VERSION")));
+
+    // The seed a fingerprint derives is the seed it was solved with; scheduling doesn't count.
+    let seeded = SubgameRequest {
+        seed: seed_from_fingerprint("traced"),
+        priority: "realtime".into(),
+        ..request.clone()
+    };
+    assert_eq!(engine.request_hash(&seeded), response.request_hash);
+    let sized = SubgameRequest {
+        action_set: vec!["pot:0.75".into(), "all-in".into()],
+        ..request
+    };
+    assert_ne!(engine.request_hash(&sized), response.request_hash);
+}
+
+#[test]
+fn watched_solves_report_exploitability_and_can_be_cancelled() {
+    let engine = SolverEngine::new();
+    let request = SubgameRequest {
+        state_fingerprint: "watched".into(),
+        budget_iterations: 50,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "all-in".into()],
+        ..Default::default()
+    };
+    let progress = Arc::new(SolveProgress::with_exploitability());
+    let response = engine
+        .solve_with_progress(&request, &progress)
+        .expect("solve succeeds");
+    let update = progress.to_proto();
+    assert_eq!(update.iterations, 50);
+    assert_eq!(update.fraction, 1.0);
+    assert!(update.exploitability >= 0.0);
+    assert_eq!(progress.exploitability(), Some(response.exploitability));
+
+    // Unwatched solves skip the extra measurements.
+    let request = SubgameRequest {
+        config: Some(SolverConfig {
+            skip_cache: true,
+            ..Default::default()
+        }),
+        ..request
+    };
+    let quiet = Arc::new(SolveProgress::new());
+    engine.solve_with_progress(&request, &quiet).unwrap();
+    assert_eq!(quiet.to_proto().iterations, 50);
+    assert_eq!(quiet.to_proto().exploitability, -1.0);
+
+    let cancelled = Arc::new(SolveProgress::with_exploitability());
+    cancelled.cancel();
+    let response = engine
+        .solve_with_progress(&request, &cancelled)
+        .expect("a cancelled solve still returns its strategy");
+    assert_eq!(response.iterations, 1);
+
+    // Once shutdown cancels running solves, later ones stop after an iteration too.
+    assert_eq!(engine.cancel_running(), 0);
+    let late = Arc::new(SolveProgress::new());
+    let response = engine.solve_with_progress(&request, &late).unwrap();
+    assert_eq!(response.iterations, 1);
+    assert!(late.is_cancelled());
+}
+
+#[test]
+fn solved_spots_are_answered_from_the_cache() {
+    let engine = SolverEngine::new();
+    let prewarm = SubgameRequest {
+        state_fingerprint: "anticipated".into(),
+        budget_iterations: 60,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "all-in".into()],
+        priority: "background".into(),
+        ..Default::default()
+    };
+    assert!(!engine.is_cached(&prewarm));
+    let solved = engine.solve(&prewarm).expect("solve succeeds");
+    assert_eq!(solved.source, "subgame");
+
+    // The in-hand request for the spot has its own budget and priority.
+    let in_hand = SubgameRequest {
+        budget_ms: 50,
+        budget_iterations: 0,
+        priority: "realtime".into(),
+        ..prewarm.clone()
+    };
+    assert!(engine.is_cached(&in_hand));
+    let cached = engine.solve(&in_hand).expect("cache hit");
+    assert_eq!(cached.source, "cache");
+    assert_eq!(cached.actions, solved.actions);
+    assert_eq!(cached.iterations, 60);
+    assert_eq!(engine.metrics().cache_hits.load(Ordering::Relaxed), 1);
+
+    let other_spot = SubgameRequest {
+        effective_stack_bb: 40,
+        ..in_hand.clone()
+    };
+    assert!(!engine.is_cached(&other_spot));
+
+    // A restarted server picks the saved solves back up.
+    let path = std::env::temp_dir()
+        .join(format!("solver-cache-{}", std::process::id()))
+        .join("solves.bin");
+    assert_eq!(engine.cache().save(&path).unwrap(), 1);
+    let restarted = SolverEngine::new();
+    assert_eq!(restarted.cache().load(&path).unwrap(), 1);
+    let reloaded = restarted.solve(&in_hand).expect("cache hit");
+    assert_eq!(reloaded.source, "cache");
+    assert_eq!(reloaded.actions, solved.actions);
+    assert_eq!(
+        restarted
+            .cache()
+            .load(&path.with_extension("gone"))
+            .unwrap(),
+        0
+    );
+    std::fs::write(&path, b"not a cache").unwrap();
+    assert!(restarted.cache().load(&path).is_err());
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[test]
+fn diffs_compare_two_budgets_of_one_spot() {
+    let engine = SolverEngine::new();
+    let request = SubgameRequest {
+        state_fingerprint: "diff".into(),
+        budget_iterations: 400,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "all-in".into()],
+        ..Default::default()
+    };
+    let quick = SubgameRequest {
+        budget_iterations: 3,
+        ..request.clone()
+    };
+    let report = engine.diff(&quick, &request).expect("both solve");
+    assert_eq!(report.baseline.unwrap().iterations, 3);
+    assert_eq!(report.candidate.unwrap().iterations, 400);
+    assert!(report.unmatched.is_empty());
+    assert!(report.max_total_variation > 0.0);
+    let root = &report.nodes[0];
+    assert_eq!(root.path, "");
+    assert!(root.bucket_total_variation >= 0.0);
+    for action in &root.actions {
+        assert!((action.delta - (action.candidate - action.baseline)).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn removing_a_root_action_reports_what_it_costs() {
+    let engine = SolverEngine::new();
+    let request = ActionRemovalRequest {
+        spot: Some(SubgameRequest {
+            state_fingerprint: "removal".into(),
+            budget_iterations: 400,
+            effective_stack_bb: 100,
+            action_set: vec!["pot:0.5".into(), "pot:1".into(), "all-in".into()],
+            ..Default::default()
+        }),
+    };
+    let report = engine
+        .action_removal(&request)
+        .expect("every solve succeeds");
+    let solve = report.solve.unwrap();
+    assert_eq!(report.actions.len(), solve.actions.len());
+    for (removal, action) in report.actions.iter().zip(&solve.actions) {
+        assert_eq!(removal.action_type, action.action_type);
+        assert!((removal.ev_loss_bb - (report.ev_bb - removal.ev_without_bb)).abs() < 1e-12);
+        // Whatever hero still has is worth at least roughly what it was.
+        assert!(removal.ev_loss_bb > -0.1);
+    }
+    // The action played most is the one whose loss hurts most.
+    let most_played = report
+        .actions
+        .iter()
+        .max_by(|a, b| a.frequency.total_cmp(&b.frequency))
+        .unwrap();
+    assert!(report
+        .actions
+        .iter()
+        .all(|removal| removal.ev_loss_bb <= most_played.ev_loss_bb + 0.1));
+}
+
+#[test]
+fn golden_section_finds_the_best_size_within_its_budget() {
+    let search = SizeSearch::default();
+    let mut calls = 0;
+    let evaluations = search_bet_size(&search, |fraction| {
+        calls += 1;
+        Ok::<_, ()>(-(fraction - 0.7) * (fraction - 0.7))
+    })
+    .unwrap();
+    assert_eq!(calls, evaluations.len());
+    assert!(evaluations.len() <= search.max_evaluations);
+    let best = best_size(&evaluations).unwrap();
+    assert!((best.pot_fraction - 0.7).abs() < 0.05);
+
+    let short = SizeSearch {
+        max_evaluations: 3,
+        ..search
+    };
+    let evaluations = search_bet_size(&short, Ok::<_, ()>).unwrap();
+    assert_eq!(evaluations.len(), 3);
+    assert!(evaluations.iter().all(|size| size.pot_fraction <= 2.0));
+    assert_eq!(
+        search_bet_size(&search, |_| Err::<f64, _>("stop")),
+        Err("stop")
+    );
+}
+
+#[test]
+fn bet_size_search_reports_the_best_size_it_solved() {
+    let engine = SolverEngine::new();
+    let request = BetSizeSearchRequest {
+        spot: Some(SubgameRequest {
+            state_fingerprint: "size-search".into(),
+            budget_iterations: 200,
+            effective_stack_bb: 100,
+            action_set: vec!["pot:0.5".into()],
+            ..Default::default()
+        }),
+        max_evaluations: 5,
+        keep: vec!["all-in".into()],
+        ..Default::default()
+    };
+    let report = engine
+        .optimize_bet_size(&request)
+        .expect("every size solves");
+    assert_eq!(report.evaluations.len(), 5);
+    assert!(report
+        .evaluations
+        .iter()
+        .all(|size| size.ev_bb <= report.best_ev_bb));
+    let solve = report.solve.unwrap();
+    let labels: Vec<&str> = solve
+        .actions
+        .iter()
+        .map(|action| action.action_type.as_str())
+        .collect();
+    assert!(labels.contains(&"all-in"));
+    assert_eq!(labels.len(), 2);
+}
+
+#[test]
+fn stack_sweeps_solve_the_spot_at_every_depth() {
+    let engine = SolverEngine::new();
+    let spot = SubgameRequest {
+        state_fingerprint: "sweep".into(),
+        budget_iterations: 100,
+        action_set: vec!["pot:1".into(), "all-in".into()],
+        ..Default::default()
+    };
+    let request = StackSweepRequest {
+        spot: Some(spot.clone()),
+        stacks_bb: vec![20, 60, 100],
+    };
+    let report = engine.sweep_stacks(&request).expect("every stack solves");
+    let stacks: Vec<i32> = report
+        .points
+        .iter()
+        .map(|point| point.effective_stack_bb)
+        .collect();
+    assert_eq!(stacks, [20, 60, 100]);
+    let all_in = |point: &StackSweepPoint| {
+        let solve = point.solve.as_ref().unwrap();
+        solve
+            .actions
+            .iter()
+            .find(|action| action.action_type == "all-in")
+            .unwrap()
+            .amount
+    };
+    assert!(all_in(&report.points[0]) < all_in(&report.points[2]));
+    let single = engine
+        .solve(&SubgameRequest {
+            effective_stack_bb: 60,
+            ..spot
+        })
+        .unwrap();
+    assert_eq!(
+        report.points[1].solve.as_ref().unwrap().actions,
+        single.actions
+    );
+
+    let error = engine
+        .sweep_stacks(&StackSweepRequest {
+            stacks_bb: vec![40, 0],
+            ..request
+        })
+        .expect_err("zero stack");
+    assert_eq!(error.reason(), "INVALID_STACK");
+}
+
+#[test]
+fn tree_templates_stand_in_for_action_tokens() {
+    let sets = tree_template("river-polarized").expect("known template");
+    let river_bet = street_action_tokens(Some(&sets), Some(Street::River), BettingRound::Bet, &[]);
+    assert_eq!(river_bet, &["pot:0.25", "pot:1.5", "all-in"]);
+    let flop_raise =
+        street_action_tokens(Some(&sets), Some(Street::Flop), BettingRound::Raise, &[]);
+    assert_eq!(flop_raise, &["pot:1", "all-in"]);
+    assert!(TREE_TEMPLATES
+        .iter()
+        .all(|name| tree_template(name).is_some()));
+
+    let engine = SolverEngine::new();
+    let request = SubgameRequest {
+        state_fingerprint: "template".into(),
+        budget_iterations: 20,
+        effective_stack_bb: 50,
+        tree_template: "triple-geometric".into(),
+        ..Default::default()
+    };
+    assert!(engine.validate(&request).is_empty());
+    let unknown = SubgameRequest {
+        tree_template: "donk-only".into(),
+        ..request
+    };
+    let issues = engine.validate(&unknown);
+    assert!(issues
+        .iter()
+        .any(|issue| issue.field == "tree_template" && issue.blocking));
+}
+
+#[test]
+fn benchmark_times_every_suite_spot() {
+    let engine = SolverEngine::new();
+    let report = engine
+        .benchmark(&BenchmarkRequest {
+            iterations: 20,
+            cfr_variant: "cfr+".into(),
+        })
+        .expect("benchmark runs");
+
+    assert_eq!(report.cases.len(), 3);
+    assert_eq!(report.cfr_variant, "cfr+");
+    assert!(report.cores >= 1);
+    for case in &report.cases {
+        assert_eq!(case.iterations, 20);
+        assert!(case.nodes > 1);
+        assert!(case.elapsed_ms > 0.0);
+        assert!((case.traversals_per_sec - 2.0 * case.iterations_per_sec).abs() < 1e-6);
+        assert!(case.nodes_per_sec > case.traversals_per_sec);
+    }
+    assert!(report.iterations_per_sec > 0.0);
+    // The suite rate sits between its slowest and fastest spot.
+    let rates = report.cases.iter().map(|case| case.iterations_per_sec);
+    assert!(report.iterations_per_sec <= rates.clone().fold(0.0, f64::max));
+    assert!(report.iterations_per_sec >= rates.fold(f64::INFINITY, f64::min));
+
+    let error = engine
+        .benchmark(&BenchmarkRequest {
+            iterations: 1,
+            cfr_variant: "deep".into(),
+        })
+        .unwrap_err();
+    assert!(
+        matches!(error, SolverError::InvalidRequest(issues) if issues[0].reason == "UNSUPPORTED_VARIANT")
+    );
+}
@@ -0,0 +1,71 @@
+use solver::preflop::pushfold::{
+    solve_push_fold, ClassEquities, PushFoldGame, PushFoldPayoffs, PushFoldSolution,
+};
+use solver::solver_proto::TournamentSpec;
+use solver::tournament::bounty::{tournament_payoffs, BountyModel};
+use solver::tournament::icm::{icm_equities, IcmModel};
+
+#[test]
+fn icm_equities_sum_to_prize_pool() {
+    let equities = icm_equities(&[5000.0, 3000.0, 2000.0], &[50.0, 30.0, 20.0]);
+    let total: f64 = equities.iter().sum();
+    assert!((total - 100.0).abs() < 1e-9);
+    assert!(equities[0] > equities[1] && equities[1] > equities[2]);
+    // Chip leader holds half the chips but less than half the prize pool.
+    assert!(equities[0] < 50.0);
+}
+
+#[test]
+fn bubble_losses_outweigh_equal_wins() {
+    let model = IcmModel::from_spec(&TournamentSpec {
+        stacks: vec![4000.0, 4000.0, 1000.0, 1000.0],
+        payouts: vec![60.0, 40.0, 0.0],
+        hero_seat: 0,
+        villain_seat: 1,
+        ..Default::default()
+    })
+    .expect("valid spec");
+    let win = model.equity_delta(2000.0);
+    let loss = model.equity_delta(-2000.0);
+    assert!(win > 0.0);
+    assert!(loss < 0.0);
+    assert!(win < -loss);
+}
+
+#[test]
+fn bounties_widen_the_calls_the_bubble_tightens() {
+    // Hero covers villain's 10 BB jam from the big blind, with 100 chips to the BB.
+    let spec = TournamentSpec {
+        stacks: vec![3000.0, 1000.0, 2000.0],
+        payouts: vec![65.0, 35.0],
+        hero_seat: 0,
+        villain_seat: 1,
+        bounties: vec![10.0, 40.0, 10.0],
+        ..Default::default()
+    };
+    let model = IcmModel::from_spec(&spec).expect("valid spec");
+    let bounties = BountyModel::from_spec(&spec).expect("valid bounties");
+    assert_eq!(bounties.cash_share, 0.5);
+    let game = PushFoldGame::new(10.0);
+    let icm = tournament_payoffs(&game, &model, None, 100.0, false);
+    let pko = tournament_payoffs(&game, &model, Some(&bounties), 100.0, false);
+    // Only busting villain collects, half in cash and half onto hero's head.
+    assert_eq!(pko.small_blind_folds, icm.small_blind_folds);
+    assert_eq!(pko.small_blind_wins, icm.small_blind_wins);
+    let collected = pko.big_blind_wins[1] - icm.big_blind_wins[1];
+    assert!((collected - bounties.capture_value(1, 4000.0 / 6000.0)).abs() < 1e-9);
+    assert!(collected > 20.0 && collected < 40.0);
+
+    let equities = ClassEquities::shared(32, 0);
+    let calls = |solution: &PushFoldSolution| solution.call.iter().sum::<f64>();
+    let chips = solve_push_fold(&PushFoldPayoffs::chips(&game), &equities, 300);
+    let icm = solve_push_fold(&icm, &equities, 300);
+    let pko = solve_push_fold(&pko, &equities, 300);
+    assert!(
+        calls(&icm) < calls(&chips),
+        "{} {}",
+        calls(&icm),
+        calls(&chips)
+    );
+    assert!(calls(&pko) > calls(&icm), "{} {}", calls(&pko), calls(&icm));
+}
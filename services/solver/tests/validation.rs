@@ -0,0 +1,224 @@
+use solver::abstraction::{
+    parse_action_set_strict, GameStateSummary, SizingPolicy, Street, TokenError,
+};
+use solver::solver::SolverEngine;
+use solver::solver_proto::{
+    Blinds, GameState, Player, SolverConfig, SubgameRequest, TournamentSpec,
+};
+use solver::validation::{has_blocking, to_report, validate_request};
+use tonic::Code;
+
+#[test]
+fn reports_every_problem_at_once() {
+    let request = SubgameRequest {
+        state_fingerprint: "validation".into(),
+        budget_ms: 100,
+        effective_stack_bb: 0,
+        action_set: vec!["pot:0.5".into(), "pot:abc".into(), "raise-big".into()],
+        board: vec!["As".into(), "Zz".into(), "7c".into()],
+        hole_cards: vec!["Kd".into(), "Kh".into()],
+        tournament: Some(TournamentSpec {
+            stacks: vec![1000.0, 2000.0],
+            payouts: vec![70.0, 30.0],
+            hero_seat: 0,
+            villain_seat: 4,
+            bounties: vec![25.0],
+            bounty_cash_share: 0.0,
+        }),
+        config: Some(SolverConfig {
+            cfr_variant: "quantum".into(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let issues = validate_request(&request);
+    let fields: Vec<&str> = issues.iter().map(|issue| issue.field.as_str()).collect();
+    assert!(fields.contains(&"effective_stack_bb"));
+    assert!(fields.contains(&"action_set[1]"));
+    assert!(fields.contains(&"action_set[2]"));
+    assert!(fields.contains(&"board[1]"));
+    assert!(fields.contains(&"tournament.villain_seat"));
+    assert!(fields.contains(&"tournament.bounties"));
+    assert!(fields.contains(&"config.cfr_variant"));
+    assert!(has_blocking(&issues));
+
+    let report = to_report(&issues);
+    assert!(!report.valid);
+    assert_eq!(report.issues.len(), issues.len());
+}
+
+#[test]
+fn clean_request_has_no_issues() {
+    let request = SubgameRequest {
+        state_fingerprint: "clean".into(),
+        budget_ms: 100,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "geo:2".into(), "all-in".into()],
+        board: vec!["As".into(), "Kd".into(), "7c".into()],
+        ..Default::default()
+    };
+    let issues = validate_request(&request);
+    assert!(issues.is_empty(), "{:?}", issues);
+    assert!(to_report(&issues).valid);
+}
+
+#[test]
+fn structured_game_states_are_checked_like_json_ones() {
+    let state = GameState {
+        pot: 12.0,
+        street: "flop".into(),
+        blinds: Some(Blinds {
+            small: 1.0,
+            big: 2.0,
+            ..Default::default()
+        }),
+        players: vec![
+            Player {
+                seat: 2,
+                stack: 150.0,
+                ..Default::default()
+            },
+            Player {
+                seat: 2,
+                stack: -5.0,
+                ..Default::default()
+            },
+        ],
+        hero_seat: Some(2),
+        villain_seat: Some(6),
+        ..Default::default()
+    };
+    let request = SubgameRequest {
+        state_fingerprint: "structured".into(),
+        budget_ms: 100,
+        action_set: vec!["pot:0.5".into()],
+        board: vec!["As".into(), "Kd".into(), "7c".into()],
+        game_state: Some(state.clone()),
+        game_state_json: "{ not json".into(),
+        ..Default::default()
+    };
+    let issues = validate_request(&request);
+    let reasons: Vec<(&str, &str)> = issues
+        .iter()
+        .map(|issue| (issue.field.as_str(), issue.reason))
+        .collect();
+    assert!(reasons.contains(&("game_state_json", "IGNORED_GAME_STATE_JSON")));
+    assert!(reasons.contains(&("game_state.players[1]", "OUT_OF_RANGE")));
+    assert!(reasons.contains(&("game_state.players[1]", "DUPLICATE_SEAT")));
+    assert!(reasons.contains(&("game_state.villain_seat", "UNKNOWN_SEAT")));
+
+    let summary = GameStateSummary::from_proto(&state);
+    assert_eq!(summary.street_kind(), Some(Street::Flop));
+    assert_eq!(summary.pot_in_bb(), 6.0);
+    assert_eq!(summary.players.len(), 2);
+}
+
+#[test]
+fn strict_mode_rejects_tokens_it_would_drop() {
+    let mut request = SubgameRequest {
+        state_fingerprint: "strict".into(),
+        budget_ms: 100,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into(), "pot:abc".into(), "x:3".into()],
+        game_state: Some(GameState {
+            pot: 10.0,
+            street: "river".into(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let lenient = validate_request(&request);
+    assert!(!has_blocking(&lenient));
+    assert_eq!(
+        lenient
+            .iter()
+            .filter(|issue| issue.field.starts_with("action_set"))
+            .count(),
+        2
+    );
+
+    request.config = Some(SolverConfig {
+        strict_action_tokens: true,
+        ..Default::default()
+    });
+    let strict = validate_request(&request);
+    let reasons: Vec<(&str, &str)> = strict
+        .iter()
+        .filter(|issue| issue.blocking)
+        .map(|issue| (issue.field.as_str(), issue.reason))
+        .collect();
+    assert_eq!(
+        reasons,
+        [
+            ("action_set[1]", "INVALID_ACTION_TOKEN"),
+            ("action_set[2]", "NO_FACING_BET"),
+        ]
+    );
+    let error = SolverEngine::new().solve(&request).unwrap_err();
+    assert_eq!(error.code(), Code::InvalidArgument);
+
+    let summary = GameStateSummary::from_proto(request.game_state.as_ref().unwrap());
+    let errors = parse_action_set_strict(
+        &request.action_set,
+        &summary,
+        100.0,
+        &SizingPolicy::default(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        errors,
+        [
+            (1, TokenError::InvalidNumber("pot:abc".into())),
+            (2, TokenError::NoFacingBet("x:3".into())),
+        ]
+    );
+    assert_eq!(
+        parse_action_set_strict(
+            &request.action_set[..1],
+            &summary,
+            100.0,
+            &SizingPolicy::default()
+        )
+        .unwrap()
+        .len(),
+        1
+    );
+}
+
+#[test]
+fn integer_chips_refuse_amounts_they_cannot_count() {
+    let request = |pot: f64| SubgameRequest {
+        state_fingerprint: "huge-pot".into(),
+        budget_iterations: 5,
+        effective_stack_bb: 100,
+        action_set: vec!["pot:0.5".into()],
+        game_state: Some(GameState {
+            pot,
+            street: "river".into(),
+            blinds: Some(Blinds {
+                big: 100.0,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        config: Some(SolverConfig {
+            integer_chips: true,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let reasons = |pot: f64| -> Vec<(String, &'static str)> {
+        validate_request(&request(pot))
+            .into_iter()
+            .map(|issue| (issue.field, issue.reason))
+            .collect()
+    };
+    assert!(reasons(1e300).contains(&("config.integer_chips".to_string(), "OUT_OF_RANGE")));
+    assert!(reasons(f64::NAN).contains(&("game_state.pot".to_string(), "OUT_OF_RANGE")));
+    assert!(!reasons(1000.0)
+        .iter()
+        .any(|(field, _)| field == "config.integer_chips"));
+    let error = SolverEngine::new().solve(&request(1e300)).unwrap_err();
+    assert_eq!(error.code(), Code::InvalidArgument);
+}